@@ -76,6 +76,8 @@ object PreviewsData {
         isTransferring = true,
         isAllDone = false,
         canRetryAll = false,
+        canPauseAll = true,
+        canResumeAll = false,
         canAbortAll = true,
     )
 
@@ -106,6 +108,8 @@ object PreviewsData {
             speedDisplay = "495.1 KB/s",
             state = TransferState.Transferring,
             canRetry = false,
+            canPause = true,
+            canResume = false,
             canOpen = false,
         ),
         Transfer(
@@ -134,6 +138,8 @@ object PreviewsData {
             speedDisplay = "1.3 MB/s",
             state = TransferState.Transferring,
             canRetry = false,
+            canPause = true,
+            canResume = false,
             canOpen = false,
         ),
         Transfer(
@@ -162,6 +168,8 @@ object PreviewsData {
             speedDisplay = "0 MB/s",
             state = TransferState.Failed("Unknown error"),
             canRetry = true,
+            canPause = false,
+            canResume = false,
             canOpen = false,
         ),
         Transfer(
@@ -190,6 +198,8 @@ object PreviewsData {
             speedDisplay = null,
             state = TransferState.Done,
             canRetry = false,
+            canPause = false,
+            canResume = false,
             canOpen = true,
         ),
     )
@@ -104,6 +104,7 @@ fun getTransferDescription(state: TransferState): String {
         is TransferState.Waiting -> "Waiting"
         is TransferState.Processing -> "Processing"
         is TransferState.Transferring -> "Transferring"
+        is TransferState.Paused -> "Paused"
         is TransferState.Failed -> "Failed: ${state.error}"
         is TransferState.Done -> "Done"
     }
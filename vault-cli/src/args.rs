@@ -0,0 +1,306 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug, Clone)]
+#[command(author, version, about = "Koofr Vault command-line client", long_about = None)]
+pub struct Args {
+    /// JSON config file with the same options as the global arguments (e.g.
+    /// {"base_url": "http://127.0.0.1:3080"})
+    #[arg(long, env = "VAULT_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Koofr base URL (default https://app.koofr.net)
+    #[arg(long, env = "VAULT_BASE_URL")]
+    pub base_url: Option<String>,
+
+    /// OAuth2 auth base URL (default is the base URL)
+    #[arg(long, env = "VAULT_OAUTH2_AUTH_BASE_URL")]
+    pub oauth2_auth_base_url: Option<String>,
+
+    /// OAuth2 client ID
+    #[arg(long, env = "VAULT_OAUTH2_CLIENT_ID")]
+    pub oauth2_client_id: Option<String>,
+
+    /// OAuth2 client secret
+    #[arg(long, env = "VAULT_OAUTH2_CLIENT_SECRET", hide_env_values = true)]
+    pub oauth2_client_secret: Option<String>,
+
+    /// OAuth2 redirect URI registered for the client (default
+    /// http://127.0.0.1:1421/oauth2callback)
+    #[arg(long, env = "VAULT_OAUTH2_REDIRECT_URI")]
+    pub oauth2_redirect_uri: Option<String>,
+
+    /// HTTP user agent (default vault-cli)
+    #[arg(long, env = "VAULT_USER_AGENT")]
+    pub user_agent: Option<String>,
+
+    /// Data path where the login token is stored (default ~/.koofr-vault-cli)
+    #[arg(long, env = "VAULT_DATA_PATH")]
+    pub data_path: Option<PathBuf>,
+
+    /// Passphrase for the login token storage (default is a key from the OS
+    /// keyring)
+    #[arg(long, env = "VAULT_SECURE_STORAGE_PASSPHRASE", hide_env_values = true)]
+    pub secure_storage_passphrase: Option<String>,
+
+    /// Confirm all prompts (e.g. deleting files)
+    #[arg(short, long, default_value = "false")]
+    pub yes: bool,
+
+    /// Do not report transfer progress on stderr
+    #[arg(short, long, default_value = "false")]
+    pub quiet: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct RepoArgs {
+    /// Repo ID or name (can be omitted if there is only one repo or if
+    /// --rclone-config is used)
+    #[arg(short, long, env = "VAULT_REPO")]
+    pub repo: Option<String>,
+
+    /// Repo password
+    #[arg(
+        long,
+        env = "VAULT_PASSWORD",
+        hide_env_values = true,
+        conflicts_with_all = ["password_file", "rclone_config"]
+    )]
+    pub password: Option<String>,
+
+    /// Read the repo password from a file
+    #[arg(long, conflicts_with = "rclone_config")]
+    pub password_file: Option<PathBuf>,
+
+    /// Read the repo password from an rclone config file
+    #[arg(long)]
+    pub rclone_config: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct CipherArgs {
+    /// Password
+    #[arg(
+        long,
+        env = "VAULT_PASSWORD",
+        hide_env_values = true,
+        conflicts_with_all = ["password_file", "rclone_config"]
+    )]
+    pub password: Option<String>,
+
+    /// Read the password from a file
+    #[arg(long, conflicts_with = "rclone_config")]
+    pub password_file: Option<PathBuf>,
+
+    /// Salt (default is the rclone default salt)
+    #[arg(
+        long,
+        env = "VAULT_SALT",
+        hide_env_values = true,
+        conflicts_with = "rclone_config"
+    )]
+    pub salt: Option<String>,
+
+    /// Read the password and the salt from an rclone config file
+    #[arg(long)]
+    pub rclone_config: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct RcloneConfigArgs {
+    /// Name of the rclone remote in the generated config (default vault)
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Koofr path where the encrypted directory will be uploaded (default /)
+    #[arg(long)]
+    pub remote_path: Option<String>,
+
+    /// rclone config output path (default <DST>.rclone.conf)
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Log in to Koofr
+    Login,
+
+    /// Log out of Koofr
+    Logout,
+
+    /// List Vault repos
+    Repos,
+
+    /// Unlock a repo to check the password
+    Unlock {
+        #[command(flatten)]
+        repo: RepoArgs,
+    },
+
+    /// List a directory
+    Ls {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted path
+        #[arg(default_value = "/")]
+        path: String,
+    },
+
+    /// Write a file to stdout
+    Cat {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted path
+        path: String,
+    },
+
+    /// Download a file (directories are downloaded as ZIP files)
+    Get {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted path
+        path: String,
+
+        /// Local file or directory path
+        #[arg(default_value = ".")]
+        local_path: PathBuf,
+    },
+
+    /// Upload a local file or directory
+    Put {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Local file or directory path
+        local_path: PathBuf,
+
+        /// Decrypted destination directory path
+        #[arg(default_value = "/")]
+        path: String,
+    },
+
+    /// Create a directory (and its parents)
+    Mkdir {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted path
+        path: String,
+    },
+
+    /// Move or rename a file or a directory
+    Mv {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted source path
+        path: String,
+
+        /// Decrypted destination path (or an existing directory)
+        to_path: String,
+    },
+
+    /// Copy a file or a directory
+    Cp {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted source path
+        path: String,
+
+        /// Decrypted destination path (or an existing directory)
+        to_path: String,
+    },
+
+    /// Delete files or directories
+    Rm {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Decrypted paths
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+
+    /// Check the repo for files with names, sizes, tags or contents that
+    /// cannot be decrypted. Problems are written to stdout
+    Fsck {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Download and decrypt every file to check the contents and hashes
+        #[arg(long, default_value = "false")]
+        deep: bool,
+
+        /// Write a JSON report instead of one line per problem
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+
+    /// Two-way sync a local directory with a repo directory. Conflicting local
+    /// changes are kept as renamed files
+    Sync {
+        #[command(flatten)]
+        repo: RepoArgs,
+
+        /// Local directory path
+        local_path: PathBuf,
+
+        /// Decrypted directory path
+        #[arg(default_value = "/")]
+        path: String,
+
+        /// Sync state path (default <LOCAL_PATH>/.vault-sync.json)
+        #[arg(long)]
+        db_path: Option<PathBuf>,
+    },
+
+    /// Decrypt a local copy of an encrypted directory (e.g. made with `rclone
+    /// copy`) without connecting to Koofr
+    DecryptLocal {
+        #[command(flatten)]
+        cipher: CipherArgs,
+
+        /// Overwrite existing local files
+        #[arg(long, default_value = "false")]
+        overwrite: bool,
+
+        /// Keep the decrypted part of files with corrupt blocks
+        #[arg(long, default_value = "false")]
+        keep_partial: bool,
+
+        /// Local encrypted directory path
+        src: PathBuf,
+
+        /// Local destination directory path
+        dst: PathBuf,
+    },
+
+    /// Encrypt a local directory into an rclone compatible directory (e.g. to
+    /// upload it with rclone) and write the matching rclone config next to it
+    EncryptLocal {
+        #[command(flatten)]
+        cipher: CipherArgs,
+
+        #[command(flatten)]
+        config: RcloneConfigArgs,
+
+        /// Overwrite existing local files
+        #[arg(long, default_value = "false")]
+        overwrite: bool,
+
+        /// Local plaintext directory path
+        src: PathBuf,
+
+        /// Local encrypted destination directory path
+        dst: PathBuf,
+    },
+}
@@ -0,0 +1,1049 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{Local, TimeZone, Utc};
+use futures::{
+    future::{self, BoxFuture, Either},
+    AsyncWriteExt, StreamExt,
+};
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+use vault_core::{
+    cipher::Cipher,
+    rclone,
+    remote::ApiErrorCode,
+    repo_files::{
+        errors::LoadFileError,
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFileName},
+    },
+    repo_fsck::state::{RepoFsckOptions, RepoFsckReport},
+    repo_sync::state::RepoSyncAction,
+    repos::{selectors as repos_selectors, state::RepoUnlockMode},
+    store,
+    transfers::{
+        errors::TransferError,
+        selectors as transfers_selectors,
+        state::{TransferState, TransferUploadRelativeName},
+    },
+    types::{DecryptedName, DecryptedPath, EncryptedPath, RepoId},
+    user_error::UserError,
+    utils::{repo_encrypted_path_utils, repo_path_utils},
+    Vault,
+};
+use vault_native::{
+    local_crypt::{
+        decrypt::{self as local_decrypt, LocalDecryptEvent, LocalDecryptOptions},
+        encrypt::{self as local_encrypt, LocalEncryptEvent, LocalEncryptOptions},
+    },
+    repo_sync::sync::{RepoSync, RepoSyncEvent},
+    transfers::{file_downloadable::FileDownloadable, file_uploadable::FileUploadable},
+};
+
+use crate::{
+    args::{CipherArgs, Command, RcloneConfigArgs, RepoArgs},
+    dialogs::DialogsHandler,
+    errors::{io_error, CliError},
+    progress::TransfersProgress,
+};
+
+pub struct Cli {
+    vault: Arc<Vault>,
+    quiet: bool,
+
+    _dialogs_handler: DialogsHandler,
+}
+
+impl Cli {
+    pub fn new(
+        vault: Arc<Vault>,
+        tokio_runtime: Arc<tokio::runtime::Runtime>,
+        yes: bool,
+        quiet: bool,
+    ) -> Self {
+        let dialogs_handler = DialogsHandler::new(vault.clone(), tokio_runtime, yes);
+
+        Self {
+            vault,
+            quiet,
+
+            _dialogs_handler: dialogs_handler,
+        }
+    }
+
+    pub async fn run(&self, command: Command) -> Result<(), CliError> {
+        match command {
+            Command::Login => self.login().await,
+            Command::Logout => self.logout(),
+            Command::Repos => self.repos().await,
+            Command::Unlock { repo } => self.unlock(&repo).await,
+            Command::Ls { repo, path } => self.ls(&repo, &path).await,
+            Command::Cat { repo, path } => self.cat(&repo, &path).await,
+            Command::Get {
+                repo,
+                path,
+                local_path,
+            } => self.get(&repo, &path, local_path).await,
+            Command::Put {
+                repo,
+                local_path,
+                path,
+            } => self.put(&repo, &local_path, &path).await,
+            Command::Mkdir { repo, path } => self.mkdir(&repo, &path).await,
+            Command::Mv {
+                repo,
+                path,
+                to_path,
+            } => self.mv(&repo, &path, &to_path).await,
+            Command::Cp {
+                repo,
+                path,
+                to_path,
+            } => self.cp(&repo, &path, &to_path).await,
+            Command::Rm { repo, paths } => self.rm(&repo, &paths).await,
+            Command::Fsck { repo, deep, json } => self.fsck(&repo, deep, json).await,
+            Command::Sync {
+                repo,
+                local_path,
+                path,
+                db_path,
+            } => self.sync(&repo, local_path, &path, db_path).await,
+            Command::DecryptLocal {
+                cipher,
+                overwrite,
+                keep_partial,
+                src,
+                dst,
+            } => {
+                self.decrypt_local(
+                    &cipher,
+                    src,
+                    dst,
+                    LocalDecryptOptions {
+                        overwrite,
+                        keep_partial,
+                    },
+                )
+                .await
+            }
+            Command::EncryptLocal {
+                cipher,
+                config,
+                overwrite,
+                src,
+                dst,
+            } => {
+                self.encrypt_local(&cipher, config, src, dst, LocalEncryptOptions { overwrite })
+                    .await
+            }
+        }
+    }
+
+    // auth
+
+    async fn login(&self) -> Result<(), CliError> {
+        self.vault.oauth2_service.load()?;
+
+        let login_url = self.vault.oauth2_start_login_flow()?;
+
+        eprintln!("Open the following URL in your browser and log in:\n");
+        eprintln!("{}\n", login_url);
+        eprintln!("Then paste the URL you were redirected to:");
+
+        let redirect_url = read_stdin_line().await?;
+
+        self.vault
+            .oauth2_finish_flow_url(redirect_url.trim())
+            .await?;
+
+        eprintln!("Logged in.");
+
+        Ok(())
+    }
+
+    fn logout(&self) -> Result<(), CliError> {
+        self.vault.oauth2_service.load()?;
+
+        self.vault.logout()?;
+
+        eprintln!("Logged out.");
+
+        Ok(())
+    }
+
+    async fn load_repos(&self) -> Result<(), CliError> {
+        self.vault.oauth2_service.load()?;
+
+        if !self.vault.oauth2_service.is_authenticated() {
+            return Err(CliError(String::from(
+                "Not logged in. Run `vault-cli login` first.",
+            )));
+        }
+
+        self.vault.repos_service.load_repos().await?;
+
+        Ok(())
+    }
+
+    // repos
+
+    async fn repos(&self) -> Result<(), CliError> {
+        self.load_repos().await?;
+
+        self.vault.with_state(|state| {
+            for repo in repos_selectors::select_repos(state) {
+                println!("{}\t{}\t{}", repo.id.0, repo.name.0, repo.path.0);
+            }
+        });
+
+        Ok(())
+    }
+
+    fn select_repo_id(
+        &self,
+        repo: Option<&str>,
+        rclone_config: Option<&rclone::config::Config>,
+    ) -> Result<RepoId, CliError> {
+        self.vault.with_state(|state| {
+            let repos = repos_selectors::select_repos(state);
+
+            let matching: Vec<_> = match (repo, rclone_config) {
+                (Some(repo), _) => repos
+                    .into_iter()
+                    .filter(|r| r.id.0 == repo || r.name.0 == repo)
+                    .collect(),
+                (None, Some(config)) => repos
+                    .into_iter()
+                    .filter(|r| r.path.to_lowercase().0 == config.path.to_lowercase())
+                    .collect(),
+                (None, None) => repos,
+            };
+
+            match matching.as_slice() {
+                [repo] => Ok(repo.id.clone()),
+                [] => Err(CliError(String::from("Repo not found."))),
+                _ => Err(CliError(String::from(
+                    "Multiple repos found. Select a repo with --repo.",
+                ))),
+            }
+        })
+    }
+
+    async fn unlock_repo(&self, args: &RepoArgs) -> Result<RepoId, CliError> {
+        self.load_repos().await?;
+
+        let rclone_config = match &args.rclone_config {
+            Some(path) => Some(read_rclone_config(path).await?),
+            None => None,
+        };
+
+        let repo_id = self.select_repo_id(args.repo.as_deref(), rclone_config.as_ref())?;
+
+        let password = read_password(
+            args.password.as_deref(),
+            args.password_file.as_deref(),
+            rclone_config.as_ref(),
+        )
+        .await?;
+
+        self.vault
+            .repos_service
+            .unlock_repo(&repo_id, &password, RepoUnlockMode::Unlock)?;
+
+        Ok(repo_id)
+    }
+
+    async fn unlock(&self, args: &RepoArgs) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+
+        let name = self.vault.with_state(|state| {
+            repos_selectors::select_repo_name(state, &repo_id)
+                .map(|name| name.0.clone())
+                .unwrap_or_default()
+        });
+
+        eprintln!("Repo {} unlocked.", name);
+
+        Ok(())
+    }
+
+    // files
+
+    fn encrypt_path(&self, repo_id: &RepoId, path: &str) -> Result<EncryptedPath, CliError> {
+        let path = repo_path_utils::normalize_path(&DecryptedPath(path.to_owned()))?;
+
+        let cipher = self.vault.repos_service.get_cipher(repo_id)?;
+
+        Ok(cipher.encrypt_path(&path))
+    }
+
+    fn encrypt_dir_path(&self, repo_id: &RepoId, path: &str) -> Result<EncryptedPath, CliError> {
+        let path = repo_path_utils::normalize_path(&DecryptedPath(path.to_owned()))?;
+
+        let cipher = self.vault.repos_service.get_cipher(repo_id)?;
+
+        Ok(cipher.encrypt_dir_path(&path))
+    }
+
+    /// Files and dirs can have differently encrypted names (e.g. with
+    /// directory_name_encryption = false), so the file path is tried first and
+    /// the dir path if the file does not exist.
+    async fn encrypt_existing_path(
+        &self,
+        repo_id: &RepoId,
+        path: &str,
+    ) -> Result<EncryptedPath, CliError> {
+        let file_path = self.encrypt_path(repo_id, path)?;
+        let dir_path = self.encrypt_dir_path(repo_id, path)?;
+
+        if file_path == dir_path {
+            return Ok(file_path);
+        }
+
+        match self
+            .vault
+            .repo_files_service
+            .load_file(repo_id, &file_path)
+            .await
+        {
+            Err(LoadFileError::RemoteError(err))
+                if err.is_api_error_code(ApiErrorCode::NotFound) =>
+            {
+                Ok(dir_path)
+            }
+            _ => Ok(file_path),
+        }
+    }
+
+    async fn load_file(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<RepoFile, CliError> {
+        self.vault
+            .repo_files_service
+            .load_file(repo_id, path)
+            .await?;
+
+        self.vault
+            .with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(repo_id, path),
+                )
+                .cloned()
+            })
+            .ok_or_else(|| CliError(String::from("Not found")))
+    }
+
+    async fn ls(&self, args: &RepoArgs, path: &str) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = self.encrypt_dir_path(&repo_id, path)?;
+
+        self.vault.repo_files_load_files(&repo_id, &path).await?;
+
+        let mut files = self.vault.with_state(|state| {
+            repo_files_selectors::select_files(state, &repo_id, &path)
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+
+        files.sort_by(|a, b| {
+            a.typ
+                .cmp(&b.typ)
+                .then_with(|| a.name_lower_force().cmp(b.name_lower_force()))
+        });
+
+        for file in files {
+            println!(
+                "{}\t{}\t{}\t{}",
+                if file.typ.is_dir() { "d" } else { "-" },
+                match file.decrypted_size() {
+                    Ok(Some(size)) => size.to_string(),
+                    _ => String::from("-"),
+                },
+                file.modified
+                    .and_then(|modified| Utc.timestamp_millis_opt(modified).single())
+                    .map(|modified| modified
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string())
+                    .unwrap_or_else(|| String::from("-")),
+                match &file.name {
+                    RepoFileName::Decrypted { name, .. } => name.0.clone(),
+                    RepoFileName::DecryptError {
+                        encrypted_name,
+                        error,
+                        ..
+                    } => format!("{} ({})", encrypted_name.0, error),
+                }
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn cat(&self, args: &RepoArgs, path: &str) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = self.encrypt_existing_path(&repo_id, path).await?;
+
+        let file = self.load_file(&repo_id, &path).await?;
+
+        if file.typ.is_dir() {
+            return Err(CliError(String::from("Is a directory")));
+        }
+
+        let reader_provider = self.vault.repo_files_get_file_reader(&repo_id, &path)?;
+        let reader = reader_provider.reader().await?;
+
+        let (_, reader) = self.vault.transfers_download_reader(reader);
+
+        let mut stdout = tokio::io::stdout().compat_write();
+
+        futures::io::copy(reader.reader, &mut stdout)
+            .await
+            .map_err(io_error)?;
+
+        stdout.flush().await.map_err(io_error)?;
+
+        Ok(())
+    }
+
+    async fn get(&self, args: &RepoArgs, path: &str, local_path: PathBuf) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = self.encrypt_existing_path(&repo_id, path).await?;
+
+        self.load_file(&repo_id, &path).await?;
+
+        let reader_provider = self.vault.repo_files_get_file_reader(&repo_id, &path)?;
+
+        let is_dir = tokio::fs::metadata(&local_path)
+            .await
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
+
+        let done_path: Arc<Mutex<Option<PathBuf>>> = Default::default();
+        let on_done_done_path = done_path.clone();
+
+        let downloadable = Box::new(FileDownloadable {
+            original_path: local_path,
+            append_name: is_dir,
+            autorename: is_dir,
+            on_open: None,
+            on_done: Box::new(move |path, _| {
+                *on_done_done_path.lock().unwrap() = Some(path);
+
+                Ok(())
+            }),
+            path: None,
+            content_type: None,
+        });
+
+        let progress = self.progress();
+
+        let (id, create_future) = self.vault.transfers_download(reader_provider, downloadable);
+
+        let result_future = create_future.await?;
+
+        self.wait_transfer(id, result_future).await?;
+
+        drop(progress);
+
+        if let Some(path) = done_path.lock().unwrap().as_ref() {
+            println!("{}", path.display());
+        }
+
+        Ok(())
+    }
+
+    async fn put(&self, args: &RepoArgs, local_path: &Path, path: &str) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let parent_path = self.encrypt_dir_path(&repo_id, path)?;
+
+        let name = local_path
+            .canonicalize()
+            .map_err(io_error)?
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(str::to_owned)
+            .ok_or_else(|| CliError(String::from("Invalid local path")))?;
+
+        let mut files = Vec::new();
+        let mut empty_dirs = Vec::new();
+
+        collect_local_files(local_path, &name, &mut files, &mut empty_dirs).await?;
+
+        let cipher = self.vault.repos_service.get_cipher(&repo_id)?;
+
+        for dir_name in empty_dirs {
+            let dir_path = repo_encrypted_path_utils::join_paths(
+                &parent_path,
+                &cipher.encrypt_dir_path(&DecryptedPath(format!("/{}", dir_name))),
+            );
+
+            self.vault
+                .repo_files_service
+                .clone()
+                .ensure_dirs(&repo_id, &dir_path)
+                .await?;
+        }
+
+        let progress = self.progress();
+
+        let uploads = files.into_iter().map(|(file_path, name)| {
+            let (id, create_future) = self.vault.transfers_upload(
+                repo_id.clone(),
+                parent_path.clone(),
+                TransferUploadRelativeName(name.clone()),
+                Box::new(FileUploadable {
+                    path: file_path,
+                    cleanup: None,
+                }),
+            );
+
+            async move {
+                let res = match create_future.await {
+                    Ok(result_future) => self.wait_transfer(id, result_future).await.map(|_| ()),
+                    Err(err) => Err(err.into()),
+                };
+
+                (name, res)
+            }
+        });
+
+        let results = future::join_all(uploads).await;
+
+        drop(progress);
+
+        let mut failed_count = 0;
+
+        for (name, res) in results {
+            if let Err(err) = res {
+                eprintln!("Failed to upload {}: {}", name, err);
+
+                failed_count += 1;
+            }
+        }
+
+        if failed_count > 0 {
+            return Err(CliError(format!("{} uploads failed", failed_count)));
+        }
+
+        Ok(())
+    }
+
+    async fn mkdir(&self, args: &RepoArgs, path: &str) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = self.encrypt_dir_path(&repo_id, path)?;
+
+        self.vault
+            .repo_files_service
+            .clone()
+            .ensure_dirs(&repo_id, &path)
+            .await?;
+
+        Ok(())
+    }
+
+    /// If `to_path` is an existing dir, the file is moved or copied into it,
+    /// otherwise `to_path` is the new path of the file. Returns the new parent
+    /// path and name.
+    async fn resolve_to_parent_name(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        to_path: &str,
+    ) -> Result<(EncryptedPath, DecryptedName), CliError> {
+        if path.is_root() {
+            return Err(CliError(String::from("Cannot move or copy the repo root")));
+        }
+
+        let existing_to_path = self.encrypt_existing_path(repo_id, to_path).await?;
+
+        match self
+            .vault
+            .repo_files_service
+            .load_file(repo_id, &existing_to_path)
+            .await
+        {
+            Ok(()) => {
+                let to_file = self.load_file(repo_id, &existing_to_path).await?;
+
+                if !to_file.typ.is_dir() {
+                    return Err(CliError(String::from("Destination already exists")));
+                }
+
+                let name = self
+                    .load_file(repo_id, path)
+                    .await?
+                    .decrypted_name()?
+                    .to_owned();
+
+                Ok((existing_to_path, name))
+            }
+            Err(LoadFileError::RemoteError(err))
+                if err.is_api_error_code(ApiErrorCode::NotFound) =>
+            {
+                let to_path = repo_path_utils::normalize_path(&DecryptedPath(to_path.to_owned()))?;
+
+                let (to_parent_path, name) = repo_path_utils::split_parent_name(&to_path)
+                    .ok_or_else(|| CliError(String::from("Invalid path")))?;
+
+                Ok((self.encrypt_dir_path(repo_id, &to_parent_path.0)?, name))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn mv(&self, args: &RepoArgs, path: &str, to_path: &str) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = self.encrypt_existing_path(&repo_id, path).await?;
+        let (to_parent_path, name) = self
+            .resolve_to_parent_name(&repo_id, &path, to_path)
+            .await?;
+
+        self.load_file(&repo_id, &path).await?;
+        // the new parent is loaded for the name checks
+        self.vault
+            .repo_files_service
+            .load_files(&repo_id, &to_parent_path)
+            .await?;
+
+        if repo_encrypted_path_utils::parent_path(&path).as_ref() == Some(&to_parent_path) {
+            self.vault
+                .repo_files_service
+                .rename_file_name(&repo_id, &path, &name)
+                .await?;
+        } else {
+            self.vault
+                .repo_files_service
+                .move_file_name(&repo_id, &path, &to_parent_path, &name)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn cp(&self, args: &RepoArgs, path: &str, to_path: &str) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = self.encrypt_existing_path(&repo_id, path).await?;
+        let (to_parent_path, name) = self
+            .resolve_to_parent_name(&repo_id, &path, to_path)
+            .await?;
+
+        self.load_file(&repo_id, &path).await?;
+        // the new parent is loaded for the name checks
+        self.vault
+            .repo_files_service
+            .load_files(&repo_id, &to_parent_path)
+            .await?;
+
+        self.vault
+            .repo_files_service
+            .copy_file_name(&repo_id, &path, &to_parent_path, &name)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn rm(&self, args: &RepoArgs, paths: &[String]) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+
+        let mut files = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let path = self.encrypt_existing_path(&repo_id, path).await?;
+
+            if path.is_root() {
+                return Err(CliError(String::from("Cannot delete the repo root")));
+            }
+
+            files.push((repo_id.clone(), path));
+        }
+
+        self.vault.repo_files_delete_files(&files).await?;
+
+        Ok(())
+    }
+
+    // fsck
+
+    async fn fsck(&self, args: &RepoArgs, deep: bool, json: bool) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+
+        let mut entries = self
+            .vault
+            .repo_fsck_check(&repo_id, RepoFsckOptions { deep })
+            .await?;
+
+        let mut report = RepoFsckReport::default();
+
+        while let Some(entry) = entries.next().await {
+            if !json {
+                let path = entry
+                    .path
+                    .as_ref()
+                    .map(|path| path.0.clone())
+                    .or_else(|| entry.encrypted_path.as_ref().map(|path| path.0.clone()))
+                    .or_else(|| entry.remote_path.as_ref().map(|path| path.0.clone()))
+                    .unwrap_or_default();
+
+                for problem in &entry.problems {
+                    println!("{}: {}", path, problem);
+                }
+            }
+
+            report.add(entry);
+        }
+
+        if json {
+            println!("{}", report.to_json());
+        }
+
+        if !self.quiet {
+            eprintln!(
+                "Checked {} files and {} directories.",
+                report.files_count, report.dirs_count
+            );
+        }
+
+        match report.entries.len() {
+            0 => Ok(()),
+            failed_count => Err(CliError(format!("{} items have problems", failed_count))),
+        }
+    }
+
+    // sync
+
+    async fn sync(
+        &self,
+        args: &RepoArgs,
+        local_path: PathBuf,
+        path: &str,
+        db_path: Option<PathBuf>,
+    ) -> Result<(), CliError> {
+        let repo_id = self.unlock_repo(args).await?;
+        let path = repo_path_utils::normalize_path(&DecryptedPath(path.to_owned()))?;
+        let db_path = db_path.unwrap_or_else(|| RepoSync::default_db_path(&local_path));
+
+        let repo_sync = RepoSync::new(self.vault.clone(), repo_id, path, local_path, db_path);
+
+        let quiet = self.quiet;
+
+        let report = repo_sync
+            .sync(&mut |event| match event {
+                RepoSyncEvent::Synced { action } if !quiet => match action {
+                    RepoSyncAction::CreateLocalDir { path } => eprintln!("mkdir local: {}", path.0),
+                    RepoSyncAction::CreateRemoteDir { path } => {
+                        eprintln!("mkdir remote: {}", path.0)
+                    }
+                    RepoSyncAction::Upload { path, .. } => eprintln!("upload: {}", path.0),
+                    RepoSyncAction::Download { path } => eprintln!("download: {}", path.0),
+                    RepoSyncAction::DeleteLocal { path } => eprintln!("delete local: {}", path.0),
+                    RepoSyncAction::DeleteRemote { path } => {
+                        eprintln!("delete remote: {}", path.0)
+                    }
+                    RepoSyncAction::RenameLocal { path, new_path } => {
+                        eprintln!("conflict: {} -> {}", path.0, new_path.0)
+                    }
+                    RepoSyncAction::Record { .. } | RepoSyncAction::Forget { .. } => {}
+                },
+                RepoSyncEvent::Failed { path, error } => {
+                    eprintln!("Failed: {}: {}", path.0, error.user_error())
+                }
+                _ => {}
+            })
+            .await?;
+
+        eprintln!(
+            "Uploaded {}, downloaded {}, deleted {} local and {} remote, {} conflicts.",
+            report.uploaded_count,
+            report.downloaded_count,
+            report.deleted_local_count,
+            report.deleted_remote_count,
+            report.conflicts_count
+        );
+
+        match report.failed_count {
+            0 => Ok(()),
+            failed_count => Err(CliError(format!("{} items failed", failed_count))),
+        }
+    }
+
+    // local
+
+    async fn decrypt_local(
+        &self,
+        args: &CipherArgs,
+        src: PathBuf,
+        dst: PathBuf,
+        options: LocalDecryptOptions,
+    ) -> Result<(), CliError> {
+        let cipher = build_cipher(&read_local_config(args).await?);
+        let quiet = self.quiet;
+
+        let report = tokio::task::spawn_blocking(move || {
+            local_decrypt::decrypt_dir(&cipher, &src, &dst, &options, &mut |event| match event {
+                LocalDecryptEvent::FileDecrypted { path, .. } if !quiet => {
+                    eprintln!("{}", path.0);
+                }
+                LocalDecryptEvent::Failed {
+                    encrypted_path,
+                    path,
+                    error,
+                } => match path {
+                    Some(path) => eprintln!(
+                        "Failed: {} ({}): {}",
+                        path.0,
+                        encrypted_path.0,
+                        error.user_error()
+                    ),
+                    None => eprintln!("Failed: {}: {}", encrypted_path.0, error.user_error()),
+                },
+                _ => {}
+            })
+        })
+        .await
+        .map_err(|err| CliError(err.to_string()))?
+        .map_err(io_error)?;
+
+        eprintln!(
+            "Decrypted {} files ({} bytes) and {} directories.",
+            report.files_count, report.bytes, report.dirs_count
+        );
+
+        match report.failed_count {
+            0 => Ok(()),
+            failed_count => Err(CliError(format!("{} items failed", failed_count))),
+        }
+    }
+
+    async fn encrypt_local(
+        &self,
+        args: &CipherArgs,
+        config_args: RcloneConfigArgs,
+        src: PathBuf,
+        dst: PathBuf,
+        options: LocalEncryptOptions,
+    ) -> Result<(), CliError> {
+        let mut config = read_local_config(args).await?;
+
+        if let Some(name) = config_args.name {
+            config.name = Some(name);
+        }
+        if let Some(remote_path) = config_args.remote_path {
+            config.path = remote_path;
+        }
+
+        let cipher = build_cipher(&config);
+        let config_path = config_args
+            .config_path
+            .unwrap_or_else(|| local_encrypt::rclone_config_path(&dst));
+
+        // write the config first so that the password is not lost if the
+        // encryption is interrupted
+        local_encrypt::write_rclone_config(&config_path, &config).map_err(|err| {
+            CliError(format!(
+                "Failed to write {}: {}",
+                config_path.display(),
+                err
+            ))
+        })?;
+
+        let quiet = self.quiet;
+
+        let report = tokio::task::spawn_blocking(move || {
+            local_encrypt::encrypt_dir(&cipher, &src, &dst, &options, &mut |event| match event {
+                LocalEncryptEvent::FileEncrypted { path, .. } if !quiet => {
+                    eprintln!("{}", path.0);
+                }
+                LocalEncryptEvent::Failed { path, error } => {
+                    eprintln!("Failed: {}: {}", path.0, error.user_error())
+                }
+                _ => {}
+            })
+        })
+        .await
+        .map_err(|err| CliError(err.to_string()))?
+        .map_err(io_error)?;
+
+        eprintln!(
+            "Encrypted {} files ({} bytes) and {} directories.",
+            report.files_count, report.bytes, report.dirs_count
+        );
+        eprintln!("rclone config written to {}", config_path.display());
+
+        match report.failed_count {
+            0 => Ok(()),
+            failed_count => Err(CliError(format!("{} items failed", failed_count))),
+        }
+    }
+
+    // transfers
+
+    fn progress(&self) -> Option<TransfersProgress> {
+        if self.quiet {
+            None
+        } else {
+            Some(TransfersProgress::new(self.vault.clone()))
+        }
+    }
+
+    /// Waits for the transfer result. Failed transfers stay in the transfers
+    /// list so that they can be retried in the apps. Here we abort them once
+    /// they have failed and cannot be autoretried anymore.
+    async fn wait_transfer<T>(
+        &self,
+        id: u32,
+        result_future: BoxFuture<'static, Result<T, TransferError>>,
+    ) -> Result<T, CliError> {
+        let failed_store = self.vault.store.clone();
+
+        let failed_future = store::wait_for(
+            self.vault.store.clone(),
+            &[store::Event::Transfers],
+            move |_| {
+                failed_store.with_state(|state| {
+                    match transfers_selectors::select_transfer(state, id).map(|t| &t.state) {
+                        Some(TransferState::Failed { error }) => Some(error.clone()),
+                        _ => None,
+                    }
+                })
+            },
+        );
+
+        match future::select(result_future, failed_future).await {
+            Either::Left((res, _)) => Ok(res?),
+            Either::Right((err, _)) => {
+                self.vault.transfers_abort(id);
+
+                Err(err.into())
+            }
+        }
+    }
+}
+
+async fn read_stdin_line() -> Result<String, CliError> {
+    tokio::task::spawn_blocking(|| {
+        let mut line = String::new();
+
+        std::io::stdin().read_line(&mut line).map(|_| line)
+    })
+    .await
+    .map_err(|err| CliError(err.to_string()))?
+    .map_err(io_error)
+}
+
+async fn read_password(
+    password: Option<&str>,
+    password_file: Option<&Path>,
+    rclone_config: Option<&rclone::config::Config>,
+) -> Result<String, CliError> {
+    match (password, password_file, rclone_config) {
+        (Some(password), _, _) => Ok(password.to_owned()),
+        (None, Some(path), _) => Ok(read_file_string(path)
+            .await?
+            .trim_end_matches(['\r', '\n'])
+            .to_owned()),
+        (None, None, Some(config)) => Ok(config.password.clone()),
+        (None, None, None) => Err(CliError(String::from(
+            "Missing password. Use --password, --password-file or --rclone-config.",
+        ))),
+    }
+}
+
+async fn read_rclone_config(path: &Path) -> Result<rclone::config::Config, CliError> {
+    rclone::config::parse_config(&read_file_string(path).await?)
+        .map_err(|err| CliError(err.to_string()))
+}
+
+/// Reads the password and the salt for local operations that do not use a
+/// repo. The name and the path are only set if an rclone config is used.
+async fn read_local_config(args: &CipherArgs) -> Result<rclone::config::Config, CliError> {
+    let rclone_config = match &args.rclone_config {
+        Some(path) => Some(read_rclone_config(path).await?),
+        None => None,
+    };
+
+    let password = read_password(
+        args.password.as_deref(),
+        args.password_file.as_deref(),
+        rclone_config.as_ref(),
+    )
+    .await?;
+
+    Ok(match rclone_config {
+        Some(config) => rclone::config::Config { password, ..config },
+        None => rclone::config::Config {
+            name: None,
+            path: String::from("/"),
+            password,
+            salt: args.salt.clone(),
+            name_options: Default::default(),
+        },
+    })
+}
+
+fn build_cipher(config: &rclone::config::Config) -> Cipher {
+    Cipher::new(vault_crypto::Cipher::new_with_name_options(
+        &config.password,
+        config.salt.as_deref(),
+        config.name_options,
+    ))
+}
+
+async fn read_file_string(path: &Path) -> Result<String, CliError> {
+    tokio::fs::read_to_string(path)
+        .await
+        .map_err(|err| CliError(format!("Failed to read {}: {}", path.display(), err)))
+}
+
+/// Collects local files with their relative names (e.g. `dir/sub/file.txt`)
+/// and empty dirs that have to be created explicitly.
+fn collect_local_files<'a>(
+    local_path: &'a Path,
+    name: &'a str,
+    files: &'a mut Vec<(PathBuf, String)>,
+    empty_dirs: &'a mut Vec<String>,
+) -> BoxFuture<'a, Result<(), CliError>> {
+    Box::pin(async move {
+        let metadata = tokio::fs::metadata(local_path).await.map_err(io_error)?;
+
+        if !metadata.is_dir() {
+            files.push((local_path.to_owned(), name.to_owned()));
+
+            return Ok(());
+        }
+
+        let mut entries = tokio::fs::read_dir(local_path).await.map_err(io_error)?;
+        let mut is_empty = true;
+
+        while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+            is_empty = false;
+
+            let entry_name = match entry.file_name().into_string() {
+                Ok(entry_name) => entry_name,
+                Err(entry_name) => {
+                    eprintln!("Skipping invalid file name: {:?}", entry_name);
+
+                    continue;
+                }
+            };
+
+            collect_local_files(
+                &entry.path(),
+                &format!("{}/{}", name, entry_name),
+                files,
+                empty_dirs,
+            )
+            .await?;
+        }
+
+        if is_empty {
+            empty_dirs.push(name.to_owned());
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,109 @@
+use std::{fs, path::PathBuf};
+
+use serde::Deserialize;
+use vault_desktop_server::config::{
+    ConfigError, DEFAULT_BASE_URL, DEFAULT_OAUTH2_CLIENT_ID, DEFAULT_OAUTH2_CLIENT_SECRET,
+};
+
+use crate::args::Args;
+
+pub const DEFAULT_OAUTH2_REDIRECT_URI: &str = "http://127.0.0.1:1421/oauth2callback";
+pub const DEFAULT_USER_AGENT: &str = "vault-cli";
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub base_url: Option<String>,
+    pub oauth2_auth_base_url: Option<String>,
+    pub oauth2_client_id: Option<String>,
+    pub oauth2_client_secret: Option<String>,
+    pub oauth2_redirect_uri: Option<String>,
+    pub user_agent: Option<String>,
+    pub data_path: Option<PathBuf>,
+    pub secure_storage_passphrase: Option<String>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &PathBuf) -> Result<Self, ConfigError> {
+        let config_json = fs::read_to_string(path).map_err(|err| ConfigError::ReadError {
+            path: path.clone(),
+            error: err.to_string(),
+        })?;
+
+        serde_json::from_str(&config_json).map_err(|err| ConfigError::ParseError {
+            path: path.clone(),
+            error: err.to_string(),
+        })
+    }
+}
+
+/// Global options resolved from the arguments, env vars and the config file
+/// (in that order of precedence).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub base_url: String,
+    pub oauth2_auth_base_url: String,
+    pub oauth2_client_id: String,
+    pub oauth2_client_secret: String,
+    pub oauth2_redirect_uri: String,
+    pub user_agent: String,
+    /// None means the default data path for the app ID
+    pub data_path: Option<PathBuf>,
+    /// None means the storage key is kept in the OS keyring
+    pub secure_storage_passphrase: Option<String>,
+}
+
+impl Config {
+    pub fn load(args: &Args) -> Result<Self, ConfigError> {
+        let file = match &args.config {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+
+        Ok(Self::resolve(args, file))
+    }
+
+    pub fn resolve(args: &Args, file: ConfigFile) -> Self {
+        let base_url = args
+            .base_url
+            .clone()
+            .or(file.base_url)
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_owned());
+        let oauth2_auth_base_url = args
+            .oauth2_auth_base_url
+            .clone()
+            .or(file.oauth2_auth_base_url)
+            .unwrap_or_else(|| base_url.clone());
+
+        Self {
+            base_url,
+            oauth2_auth_base_url,
+            oauth2_client_id: args
+                .oauth2_client_id
+                .clone()
+                .or(file.oauth2_client_id)
+                .unwrap_or_else(|| DEFAULT_OAUTH2_CLIENT_ID.to_owned()),
+            oauth2_client_secret: args
+                .oauth2_client_secret
+                .clone()
+                .or(file.oauth2_client_secret)
+                .unwrap_or_else(|| DEFAULT_OAUTH2_CLIENT_SECRET.to_owned()),
+            oauth2_redirect_uri: args
+                .oauth2_redirect_uri
+                .clone()
+                .or(file.oauth2_redirect_uri)
+                .unwrap_or_else(|| DEFAULT_OAUTH2_REDIRECT_URI.to_owned()),
+            user_agent: args
+                .user_agent
+                .clone()
+                .or(file.user_agent)
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_owned()),
+            data_path: args.data_path.clone().or(file.data_path),
+            secure_storage_passphrase: args
+                .secure_storage_passphrase
+                .clone()
+                .or(file.secure_storage_passphrase)
+                .filter(|passphrase| !passphrase.is_empty()),
+        }
+    }
+}
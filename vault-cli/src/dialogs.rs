@@ -0,0 +1,108 @@
+use std::{
+    collections::HashSet,
+    io::{BufRead, Write},
+    sync::{Arc, Mutex},
+};
+
+use vault_core::{
+    dialogs::{self, state::DialogType},
+    store, Vault,
+};
+
+/// DialogsHandler answers dialogs shown by vault-core services (e.g. the
+/// delete confirmation) by prompting on stderr and reading the answer from
+/// stdin. If `yes` is set, all confirm dialogs are confirmed automatically.
+pub struct DialogsHandler {
+    vault: Arc<Vault>,
+    subscription_id: u32,
+}
+
+impl DialogsHandler {
+    pub fn new(vault: Arc<Vault>, tokio_runtime: Arc<tokio::runtime::Runtime>, yes: bool) -> Self {
+        let subscription_id = vault.get_next_id();
+
+        let handled: Arc<Mutex<HashSet<u32>>> = Default::default();
+        let callback_vault = Arc::downgrade(&vault);
+
+        vault.on(
+            subscription_id,
+            &[store::Event::Dialogs],
+            Box::new(move |_, _| {
+                let vault = match callback_vault.upgrade() {
+                    Some(vault) => vault,
+                    None => return,
+                };
+
+                let dialogs = vault.with_state(|state| {
+                    dialogs::selectors::select_dialogs(state)
+                        .into_iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                });
+
+                for dialog in dialogs {
+                    if !handled.lock().unwrap().insert(dialog.id) {
+                        continue;
+                    }
+
+                    let vault = vault.clone();
+
+                    tokio_runtime.spawn(async move {
+                        let dialog_id = dialog.id;
+
+                        let confirmed = match dialog.typ {
+                            DialogType::Alert => {
+                                eprintln!("{}", dialog.title);
+
+                                true
+                            }
+                            DialogType::Confirm if yes => true,
+                            DialogType::Confirm => tokio::task::spawn_blocking(move || {
+                                prompt_confirm(&dialog.title, dialog.message.as_deref())
+                            })
+                            .await
+                            .unwrap_or(false),
+                            // prompts need an input value and the cli always
+                            // provides names as arguments
+                            DialogType::Prompt => false,
+                        };
+
+                        if confirmed {
+                            vault.dialogs_confirm(dialog_id);
+                        } else {
+                            vault.dialogs_cancel(dialog_id);
+                        }
+                    });
+                }
+            }),
+        );
+
+        Self {
+            vault,
+            subscription_id,
+        }
+    }
+}
+
+impl Drop for DialogsHandler {
+    fn drop(&mut self) {
+        self.vault.remove_listener(self.subscription_id);
+    }
+}
+
+fn prompt_confirm(title: &str, message: Option<&str>) -> bool {
+    let mut stderr = std::io::stderr();
+
+    let _ = match message {
+        Some(message) => write!(stderr, "{}: {} [y/N] ", title, message),
+        None => write!(stderr, "{} [y/N] ", title),
+    };
+    let _ = stderr.flush();
+
+    let mut answer = String::new();
+
+    match std::io::stdin().lock().read_line(&mut answer) {
+        Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
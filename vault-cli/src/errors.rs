@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+use vault_core::user_error::UserError;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{0}")]
+pub struct CliError(pub String);
+
+impl<E: UserError> From<E> for CliError {
+    fn from(err: E) -> Self {
+        Self(err.user_error())
+    }
+}
+
+pub fn io_error(err: std::io::Error) -> CliError {
+    CliError(err.to_string())
+}
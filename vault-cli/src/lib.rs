@@ -0,0 +1,6 @@
+pub mod args;
+pub mod cli;
+pub mod config;
+pub mod dialogs;
+pub mod errors;
+pub mod progress;
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use vault_cli::{args::Args, cli::Cli, config::Config};
+use vault_core::oauth2::OAuth2Config;
+use vault_desktop_server::{
+    data_path::get_data_path, init_secure_storage::init_file_secure_storage,
+};
+use vault_native::vault::build_vault;
+
+fn main() {
+    let args = Args::parse();
+
+    let mut env_logger_builder = env_logger::Builder::from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "warn"),
+    );
+    let _ = env_logger_builder.try_init();
+
+    let config = match Config::load(&args) {
+        Ok(config) => config,
+        Err(err) => exit_error(&err.to_string()),
+    };
+
+    let app_id = String::from("koofr-vault-cli");
+
+    let data_path = match config.data_path.clone() {
+        Some(data_path) => match std::fs::create_dir_all(&data_path) {
+            Ok(()) => data_path,
+            Err(err) => exit_error(&format!("Failed to ensure data path: {}", err)),
+        },
+        None => match get_data_path(&app_id) {
+            Ok(data_path) => data_path,
+            Err(err) => exit_error(&err.to_string()),
+        },
+    };
+
+    // a plaintext storage.json from older versions is migrated on first load
+    let (secure_storage, secure_storage_error) = init_file_secure_storage(
+        Ok(data_path),
+        &app_id,
+        config.secure_storage_passphrase.as_deref(),
+    );
+
+    if let Some(err) = secure_storage_error {
+        exit_error(&err);
+    }
+
+    let tokio_runtime = Arc::new(tokio::runtime::Runtime::new().unwrap());
+
+    let oauth2_config = OAuth2Config {
+        base_url: config.base_url.clone(),
+        auth_base_url: config.oauth2_auth_base_url.clone(),
+        client_id: config.oauth2_client_id.clone(),
+        client_secret: config.oauth2_client_secret.clone(),
+        redirect_uri: config.oauth2_redirect_uri.clone(),
+    };
+
+    let (vault, _, _) = build_vault(
+        config.base_url.clone(),
+        config.user_agent.clone(),
+        oauth2_config,
+        secure_storage,
+        tokio_runtime.clone(),
+    );
+
+    let cli = Cli::new(vault, tokio_runtime.clone(), args.yes, args.quiet);
+
+    let res = tokio_runtime.block_on(cli.run(args.command));
+
+    match res {
+        // exit explicitly so that we do not wait for background tasks (e.g.
+        // eventstream) and do not drop the tokio runtime from within itself
+        Ok(()) => std::process::exit(0),
+        Err(err) => exit_error(&err.to_string()),
+    }
+}
+
+fn exit_error(message: &str) -> ! {
+    eprintln!("Error: {}", message);
+
+    std::process::exit(1)
+}
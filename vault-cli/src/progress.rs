@@ -0,0 +1,86 @@
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use vault_core::{store, transfers, Vault};
+
+/// TransfersProgress reports transfers progress on stderr on every transfers
+/// change (changes are already throttled by `config.transfers.progress_throttle`).
+pub struct TransfersProgress {
+    vault: Arc<Vault>,
+    subscription_id: u32,
+    printed: Arc<AtomicBool>,
+}
+
+impl TransfersProgress {
+    pub fn new(vault: Arc<Vault>) -> Self {
+        let subscription_id = vault.get_next_id();
+
+        let printed = Arc::new(AtomicBool::new(false));
+
+        let callback_vault = Arc::downgrade(&vault);
+        let callback_printed = printed.clone();
+
+        vault.on(
+            subscription_id,
+            &[store::Event::Transfers],
+            Box::new(move |_, _| {
+                let vault = match callback_vault.upgrade() {
+                    Some(vault) => vault,
+                    None => return,
+                };
+
+                if let Some(line) = vault.with_state(format_progress) {
+                    let mut stderr = std::io::stderr();
+                    let _ = write!(stderr, "\r\x1b[K{}", line);
+                    let _ = stderr.flush();
+
+                    callback_printed.store(true, Ordering::SeqCst);
+                }
+            }),
+        );
+
+        Self {
+            vault,
+            subscription_id,
+            printed,
+        }
+    }
+}
+
+impl Drop for TransfersProgress {
+    fn drop(&mut self) {
+        self.vault.remove_listener(self.subscription_id);
+
+        if self.printed.load(Ordering::SeqCst) {
+            eprintln!();
+        }
+    }
+}
+
+fn format_progress(state: &store::State) -> Option<String> {
+    let transfers_state = &state.transfers;
+
+    if transfers_state.total_count == 0 {
+        return None;
+    }
+
+    let mut line = format!(
+        "{}% {} / {} bytes, {} / {} files",
+        transfers::selectors::select_percentage(state),
+        transfers_state.done_bytes,
+        transfers_state.total_bytes,
+        transfers_state.done_count,
+        transfers_state.total_count,
+    );
+
+    if transfers_state.failed_count > 0 {
+        line.push_str(&format!(", {} failed", transfers_state.failed_count));
+    }
+
+    Some(line)
+}
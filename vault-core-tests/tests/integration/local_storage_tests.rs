@@ -0,0 +1,644 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use futures::{io::Cursor, AsyncReadExt, FutureExt, StreamExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    common::state::Status,
+    eventstream::{Event, Message, Request, WebSocketClient},
+    oauth2::OAuth2Config,
+    remote::{models, ApiErrorCode, RemoteFileReaderRange, RemoteFileUploadConflictResolution},
+    repo_files::state::RepoFilesUploadConflictResolution,
+    repos::state::RepoUnlockMode,
+    secure_storage::MemorySecureStorage,
+    storage_backend::{StorageBackend, StorageBackendUploadOptions},
+    types::{DecryptedPath, MountId, RemoteName, RemotePath},
+    utils::repo_encrypted_path_utils,
+};
+use vault_core_tests::helpers::{wait_for_async, with_tokio_runtime};
+use vault_crypto::name_cipher::{FilenameEncoding, FilenameEncryption, NameCipherOptions};
+use vault_native::local_storage::{
+    local_fs::LOCAL_MOUNT_ID,
+    sidecar::{INDEX_FILE_NAME, REPOS_FILE_NAME},
+    LocalEventstreamWebSocketClient, LocalFs, LocalStorageBackend,
+};
+use vault_native::vault::build_local_vault;
+
+fn temp_dir() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("vault-local-storage-{}", uuid::Uuid::new_v4()));
+
+    std::fs::create_dir_all(&path).unwrap();
+
+    path
+}
+
+fn mount_id() -> MountId {
+    MountId(LOCAL_MOUNT_ID.into())
+}
+
+fn path(path: &str) -> RemotePath {
+    RemotePath(path.into())
+}
+
+async fn upload(
+    backend: &LocalStorageBackend,
+    parent_path: &str,
+    name: &str,
+    content: &str,
+    conflict_resolution: RemoteFileUploadConflictResolution,
+) -> Result<models::FilesFile, vault_core::remote::RemoteError> {
+    backend
+        .upload_file_reader(
+            &mount_id(),
+            &path(parent_path),
+            &RemoteName(name.into()),
+            Box::pin(Cursor::new(content.as_bytes().to_vec())),
+            StorageBackendUploadOptions {
+                size: Some(content.len() as i64),
+                modified: Some(1700000000000),
+                conflict_resolution,
+                on_progress: None,
+            },
+        )
+        .await
+}
+
+async fn read(backend: &LocalStorageBackend, file_path: &str) -> String {
+    let mut content = String::new();
+
+    backend
+        .get_file_reader(&mount_id(), &path(file_path), None)
+        .await
+        .unwrap()
+        .reader
+        .read_to_string(&mut content)
+        .await
+        .unwrap();
+
+    content
+}
+
+async fn names(backend: &LocalStorageBackend, dir_path: &str) -> Vec<String> {
+    backend
+        .get_bundle(&mount_id(), &path(dir_path))
+        .await
+        .unwrap()
+        .files
+        .unwrap()
+        .into_iter()
+        .map(|file| file.name.0)
+        .collect()
+}
+
+#[test]
+fn test_local_storage_backend() {
+    with_tokio_runtime(|_| {
+        async move {
+            let root = temp_dir();
+            let backend = LocalStorageBackend::new(Arc::new(LocalFs::new(root.clone())));
+
+            backend
+                .create_dir(&mount_id(), &path("/"), RemoteName("dir".into()))
+                .await
+                .unwrap();
+
+            let file = upload(
+                &backend,
+                "/dir",
+                "file.txt",
+                "hello",
+                RemoteFileUploadConflictResolution::Error,
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(file.name.0, "file.txt");
+            assert_eq!(file.size, 5);
+            assert_eq!(file.modified, 1700000000000);
+            assert_eq!(file.content_type, "text/plain");
+            assert_eq!(
+                file.hash,
+                Some(format!("{:x}", md5::compute("hello".as_bytes())))
+            );
+            assert_eq!(read(&backend, "/dir/file.txt").await, "hello");
+            assert_eq!(
+                std::fs::read_to_string(root.join("dir").join("file.txt")).unwrap(),
+                "hello"
+            );
+
+            assert!(upload(
+                &backend,
+                "/dir",
+                "file.txt",
+                "x",
+                RemoteFileUploadConflictResolution::Error,
+            )
+            .await
+            .unwrap_err()
+            .is_api_error_code(ApiErrorCode::Conflict));
+            assert!(upload(
+                &backend,
+                "/dir",
+                "file.txt",
+                "x",
+                RemoteFileUploadConflictResolution::Overwrite {
+                    if_size: None,
+                    if_modified: None,
+                    if_hash: Some("otherhash".into()),
+                    ignore_nonexisting: false,
+                },
+            )
+            .await
+            .unwrap_err()
+            .is_api_error_code(ApiErrorCode::Conflict));
+            assert_eq!(
+                upload(
+                    &backend,
+                    "/dir",
+                    "file.txt",
+                    "other",
+                    RemoteFileUploadConflictResolution::Autorename,
+                )
+                .await
+                .unwrap()
+                .name
+                .0,
+                "file (1).txt"
+            );
+
+            let reader = backend
+                .get_file_reader(
+                    &mount_id(),
+                    &path("/dir/file.txt"),
+                    Some(&RemoteFileReaderRange {
+                        start: 1,
+                        end: Some(3),
+                    }),
+                )
+                .await
+                .unwrap();
+            assert_eq!(reader.size, 3);
+            let mut content = String::new();
+            let mut reader_reader = reader.reader;
+            reader_reader.read_to_string(&mut content).await.unwrap();
+            assert_eq!(content, "ell");
+
+            // tags are kept in the sidecar index and follow the file
+            backend
+                .file_set_tags(
+                    &mount_id(),
+                    &path("/dir/file.txt"),
+                    HashMap::from([("key".into(), vec!["value".into()])]),
+                    Default::default(),
+                )
+                .await
+                .unwrap();
+
+            backend
+                .rename_file(&mount_id(), &path("/dir"), RemoteName("renamed".into()))
+                .await
+                .unwrap();
+            backend
+                .copy_file(
+                    &mount_id(),
+                    &path("/renamed/file.txt"),
+                    &mount_id(),
+                    &path("/copy.txt"),
+                )
+                .await
+                .unwrap();
+
+            for file_path in ["/renamed/file.txt", "/copy.txt"] {
+                let file = backend
+                    .get_file(&mount_id(), &path(file_path))
+                    .await
+                    .unwrap();
+
+                assert_eq!(
+                    file.tags,
+                    HashMap::from([("key".into(), vec!["value".into()])])
+                );
+                assert_eq!(file.modified, 1700000000000);
+            }
+
+            assert!(backend
+                .move_file(
+                    &mount_id(),
+                    &path("/renamed"),
+                    &mount_id(),
+                    &path("/renamed/sub"),
+                    Default::default(),
+                )
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::MoveIntoSelf));
+
+            // sidecar files are not listed and cannot be accessed
+            assert!(root.join(INDEX_FILE_NAME).exists());
+            assert_eq!(names(&backend, "/").await, vec!["copy.txt", "renamed"]);
+            assert!(backend
+                .get_file(&mount_id(), &path(&format!("/{}", INDEX_FILE_NAME)))
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::InvalidPath));
+            assert!(backend
+                .get_file(&MountId("other".into()), &path("/copy.txt"))
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::NotFound));
+
+            let list_paths = backend
+                .get_list_recursive(&mount_id(), &path("/"))
+                .await
+                .unwrap()
+                .map(|item| match item.unwrap() {
+                    models::FilesListRecursiveItem::File { path, .. } => path.0,
+                    models::FilesListRecursiveItem::Error { .. } => panic!("unexpected error"),
+                })
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(
+                list_paths,
+                vec![
+                    "/",
+                    "/copy.txt",
+                    "/renamed",
+                    "/renamed/file (1).txt",
+                    "/renamed/file.txt"
+                ]
+            );
+
+            backend
+                .delete_file(&mount_id(), &path("/renamed"), Default::default())
+                .await
+                .unwrap();
+            assert_eq!(names(&backend, "/").await, vec!["copy.txt"]);
+            assert!(backend
+                .get_file(&mount_id(), &path("/renamed/file.txt"))
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::NotFound));
+
+            std::fs::remove_dir_all(root).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_local_storage_backend_vault_repos() {
+    with_tokio_runtime(|_| {
+        async move {
+            let root = temp_dir();
+            let backend = LocalStorageBackend::new(Arc::new(LocalFs::new(root.clone())));
+
+            assert!(backend.has_vault_repos_api());
+
+            let create = models::VaultRepoCreate {
+                mount_id: mount_id(),
+                path: path("/My safe box"),
+                salt: Some("salt".into()),
+                password_validator: "validator".into(),
+                password_validator_encrypted: "encrypted".into(),
+                ..Default::default()
+            };
+
+            assert!(backend
+                .create_vault_repo(create.clone())
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::VaultReposLocationNotFound));
+
+            backend
+                .create_dir(&mount_id(), &path("/"), RemoteName("My safe box".into()))
+                .await
+                .unwrap();
+
+            let repo = backend.create_vault_repo(create.clone()).await.unwrap();
+
+            assert_eq!(repo.name, "My safe box");
+            assert!(root.join(REPOS_FILE_NAME).exists());
+            assert!(backend
+                .create_vault_repo(create)
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::VaultReposAlreadyExists));
+
+            let bundle = backend.get_vault_repos().await.unwrap();
+            assert_eq!(bundle.repos, vec![repo.clone()]);
+            assert_eq!(bundle.mounts.get(&mount_id()).unwrap().typ, "device");
+
+            // repos follow their location
+            backend
+                .rename_file(
+                    &mount_id(),
+                    &path("/My safe box"),
+                    RemoteName("Renamed".into()),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                backend.get_vault_repos().await.unwrap().repos[0].path,
+                path("/Renamed")
+            );
+
+            backend
+                .delete_file(&mount_id(), &path("/Renamed"), Default::default())
+                .await
+                .unwrap();
+            assert_eq!(backend.get_vault_repos().await.unwrap().repos, vec![]);
+            assert!(backend
+                .remove_vault_repo(&repo.id)
+                .await
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::NotFound));
+
+            std::fs::remove_dir_all(root).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_local_eventstream() {
+    with_tokio_runtime(|tokio_runtime| {
+        async move {
+            let root = temp_dir();
+            std::fs::create_dir(root.join("dir")).unwrap();
+
+            let client = LocalEventstreamWebSocketClient::new(
+                tokio_runtime,
+                Arc::new(LocalFs::new(root.clone())),
+            );
+
+            let messages: Arc<Mutex<Vec<Message>>> = Arc::new(Mutex::new(Vec::new()));
+            let on_message_messages = messages.clone();
+
+            client.open(
+                "".into(),
+                Box::new(|| {}),
+                Box::new(move |data| {
+                    on_message_messages
+                        .lock()
+                        .unwrap()
+                        .push(serde_json::from_str(&data).unwrap());
+                }),
+                Box::new(|| {}),
+            );
+
+            let send = |request: Request| client.send(serde_json::to_string(&request).unwrap());
+
+            let wait_for_message = |f: fn(&Message) -> bool| {
+                let messages = messages.clone();
+
+                async move {
+                    assert!(
+                        wait_for_async(2000, move || messages.lock().unwrap().iter().any(f)).await
+                    );
+                }
+            };
+
+            send(Request::Auth {
+                authorization: "".into(),
+            });
+            wait_for_message(|message| matches!(message, Message::Authenticated)).await;
+
+            send(Request::Register {
+                request_id: Some(1),
+                mount_id: mount_id(),
+                path: path("/"),
+            });
+            wait_for_message(|message| {
+                matches!(
+                    message,
+                    Message::Registered {
+                        request_id: Some(1),
+                        ..
+                    }
+                )
+            })
+            .await;
+
+            // inotify watches are added after the registration
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+            std::fs::write(root.join("dir").join("file.txt"), "hello").unwrap();
+            wait_for_message(|message| {
+                matches!(
+                    message,
+                    Message::Event {
+                        event: Event::FileCreatedEvent { path, file, .. },
+                        ..
+                    } if path.0 == "/dir/file.txt" && file.size == 5
+                )
+            })
+            .await;
+
+            std::fs::rename(root.join("dir"), root.join("renamed")).unwrap();
+            wait_for_message(|message| {
+                matches!(
+                    message,
+                    Message::Event {
+                        event: Event::FileMovedEvent { path, new_path, .. },
+                        ..
+                    } if path.0 == "/dir" && new_path.0 == "/renamed"
+                )
+            })
+            .await;
+
+            // watches follow moved dirs
+            std::fs::remove_file(root.join("renamed").join("file.txt")).unwrap();
+            wait_for_message(|message| {
+                matches!(
+                    message,
+                    Message::Event {
+                        event: Event::FileRemovedEvent { path, .. },
+                        ..
+                    } if path.0 == "/renamed/file.txt"
+                )
+            })
+            .await;
+
+            client.close();
+            drop(client);
+
+            std::fs::remove_dir_all(root).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_local_vault_without_remote() {
+    with_tokio_runtime(|tokio_runtime| {
+        async move {
+            let root = temp_dir();
+
+            // nothing listens on the base url so any Koofr request would fail
+            let base_url = String::from("http://127.0.0.1:1");
+
+            let (vault, _) = build_local_vault(
+                base_url.clone(),
+                "vault-core-tests".into(),
+                OAuth2Config {
+                    base_url: base_url.clone(),
+                    auth_base_url: base_url,
+                    client_id: "client".into(),
+                    client_secret: "secret".into(),
+                    redirect_uri: "http://127.0.0.1:5173/oauth2callback".into(),
+                },
+                Box::new(MemorySecureStorage::new()),
+                tokio_runtime,
+                root.clone(),
+            );
+
+            vault.load().unwrap().await.unwrap();
+
+            vault.with_state(|state| {
+                assert!(matches!(state.oauth2.status, Status::Loaded));
+                assert_eq!(state.user.user.as_ref().unwrap().full_name, "Local");
+                assert!(matches!(state.repos.status, Status::Loaded));
+            });
+
+            let repo_id = vault
+                .repos_service
+                .create_repo(
+                    &mount_id(),
+                    &path("/My safe box"),
+                    "password",
+                    Some("salt"),
+                    Default::default(),
+                )
+                .await
+                .unwrap()
+                .repo_id;
+
+            vault
+                .repos_service
+                .unlock_repo(&repo_id, "password", RepoUnlockMode::Unlock)
+                .unwrap();
+
+            let cipher = vault.repos_service.get_cipher(&repo_id).unwrap();
+            let file_path = cipher.encrypt_path(&DecryptedPath("/file.txt".into()));
+            let (parent_path, name) =
+                repo_encrypted_path_utils::split_parent_name(&file_path).unwrap();
+
+            vault
+                .repo_files_service
+                .clone()
+                .upload_file_reader(
+                    &repo_id,
+                    &parent_path,
+                    name,
+                    Box::pin(Cursor::new(b"local".to_vec())),
+                    Some(5),
+                    RepoFilesUploadConflictResolution::Error,
+                    None,
+                )
+                .await
+                .unwrap();
+
+            let mut content = String::new();
+            vault
+                .repo_files_get_file_reader(&repo_id, &file_path)
+                .unwrap()
+                .reader()
+                .await
+                .unwrap()
+                .reader
+                .read_to_string(&mut content)
+                .await
+                .unwrap();
+            assert_eq!(content, "local");
+
+            // repos are loaded from the sidecar file
+            vault.repos_service.load_repos().await.unwrap();
+            assert_eq!(vault.with_state(|state| state.repos.repos_by_id.len()), 1);
+
+            drop(vault);
+
+            std::fs::remove_dir_all(root).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_local_vault_name_options() {
+    with_tokio_runtime(|tokio_runtime| {
+        async move {
+            let root = temp_dir();
+
+            let build = || {
+                let base_url = String::from("http://127.0.0.1:1");
+
+                build_local_vault(
+                    base_url.clone(),
+                    "vault-core-tests".into(),
+                    OAuth2Config {
+                        base_url: base_url.clone(),
+                        auth_base_url: base_url,
+                        client_id: "client".into(),
+                        client_secret: "secret".into(),
+                        redirect_uri: "http://127.0.0.1:5173/oauth2callback".into(),
+                    },
+                    Box::new(MemorySecureStorage::new()),
+                    tokio_runtime.clone(),
+                    root.clone(),
+                )
+                .0
+            };
+
+            let name_options = NameCipherOptions {
+                filename_encryption: FilenameEncryption::Obfuscate,
+                directory_name_encryption: false,
+                filename_encoding: FilenameEncoding::Base32,
+            };
+
+            let vault = build();
+            vault.load().unwrap().await.unwrap();
+
+            let repo_id = vault
+                .repos_service
+                .create_repo(
+                    &mount_id(),
+                    &path("/My safe box"),
+                    "password",
+                    Some("salt"),
+                    name_options,
+                )
+                .await
+                .unwrap()
+                .repo_id;
+
+            drop(vault);
+
+            // the options are stored with the repo so another client on the
+            // same directory decrypts the names with them
+            let vault = build();
+            vault.load().unwrap().await.unwrap();
+
+            vault
+                .repos_service
+                .unlock_repo(&repo_id, "password", RepoUnlockMode::Unlock)
+                .unwrap();
+
+            assert_eq!(
+                vault
+                    .repos_service
+                    .get_cipher(&repo_id)
+                    .unwrap()
+                    .name_options(),
+                &name_options
+            );
+
+            drop(vault);
+
+            std::fs::remove_dir_all(root).unwrap();
+        }
+        .boxed()
+    });
+}
@@ -1,13 +1,27 @@
+mod local_storage_tests;
 mod oauth2_tests;
 mod remote_files_browsers_tests;
 mod remote_files_tests;
 mod repo_create_tests;
 mod repo_files_browsers_tests;
+mod repo_files_cross_move_tests;
+mod repo_files_details_merge_tests;
 mod repo_files_details_tests;
+mod repo_files_read_tests;
+mod repo_files_repair_tests;
 mod repo_files_tags_tests;
 mod repo_files_tests;
+mod repo_fsck_tests;
 mod repo_locker_tests;
+mod repo_password_change_tests;
+mod repo_search_tests;
+mod repo_sync_tests;
+mod repo_trash_tests;
+mod repo_versions_tests;
+mod repos_local_registry_tests;
 mod transfers_download_reader_tests;
 mod transfers_download_tests;
+mod transfers_persistence_tests;
 mod transfers_upload_tests;
 mod user_tests;
+mod webdav_tests;
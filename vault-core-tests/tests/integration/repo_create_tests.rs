@@ -1,5 +1,10 @@
 use std::collections::HashSet;
 
+use axum::{
+    body::{self, HttpBody},
+    http::header,
+    response::Response,
+};
 use futures::FutureExt;
 use similar_asserts::assert_eq;
 use vault_core::{
@@ -8,18 +13,55 @@ use vault_core::{
     remote_files::state::RemoteFilesLocation,
     repo_create::state::{RepoCreate, RepoCreateForm, RepoCreatesState},
     repos::{
-        errors::CreateRepoError,
+        errors::{CreateRepoError, UnlockRepoError},
         state::{RepoConfig, RepoCreated, RepoUnlockMode},
     },
     store,
     types::{DecryptedName, EncryptedPath, RemotePath},
 };
 use vault_core_tests::{
+    fake_remote::FakeRemote,
     fixtures::user_fixture::UserFixture,
     helpers::{with_repo, with_user},
 };
+use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
 use vault_store::{test_helpers::StateRecorder, NextId};
 
+/// Transforms every repo returned by the vault repos API.
+fn transform_repos(fake_remote: &FakeRemote, transform: fn(&mut serde_json::Value)) {
+    fake_remote.intercept(Box::new(move |parts| {
+        if parts.uri.path() == "/api/v2.1/vault/repos" {
+            InterceptorResult::AsyncTransform(Box::new(move |response| {
+                async move {
+                    let (mut parts, mut body) = response.into_parts();
+                    parts.headers.remove(header::CONTENT_LENGTH);
+
+                    let mut bytes = Vec::new();
+
+                    while let Some(chunk) = body.data().await {
+                        bytes.extend_from_slice(&chunk.unwrap());
+                    }
+
+                    let mut json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+                    match json.get_mut("repos") {
+                        Some(repos) => repos.as_array_mut().unwrap().iter_mut().for_each(transform),
+                        None => transform(&mut json),
+                    }
+
+                    Response::from_parts(
+                        parts,
+                        body::boxed(body::Full::from(serde_json::to_vec(&json).unwrap())),
+                    )
+                }
+                .boxed()
+            }))
+        } else {
+            InterceptorResult::Ignore
+        }
+    }));
+}
+
 #[test]
 fn test_create() {
     with_user(|fixture| {
@@ -278,6 +320,78 @@ fn test_create_custom_location() {
     });
 }
 
+#[test]
+fn test_create_rclone_config_name_options_not_supported() {
+    with_user(|fixture| {
+        async move {
+            fixture.load().await;
+
+            let (create_id, load_future) = fixture.vault.repo_create_create();
+            load_future.await.unwrap();
+            fixture
+                .vault
+                .repo_create_fill_from_rclone_config(
+                    create_id,
+                    "[vault-name]\ntype=crypt\nremote=koofr:/Vault\npassword=YMRulMcUAOo9raAGnYdie57EWnDFi_N283rEVw\nfilename_encryption=obfuscate\ndirectory_name_encryption=false\n".into(),
+                )
+                .unwrap();
+            fixture.vault.repo_create_create_repo(create_id).await;
+
+            // Koofr does not store the name options so other clients could not
+            // decrypt the names
+            let create_repo_status = fixture.vault.with_state(|state| {
+                state
+                    .repo_creates
+                    .creates
+                    .get(&create_id)
+                    .unwrap()
+                    .form()
+                    .unwrap()
+                    .create_repo_status
+                    .clone()
+            });
+            fixture.vault.repo_create_destroy(create_id);
+
+            assert_eq!(
+                create_repo_status,
+                Status::Error {
+                    error: CreateRepoError::NameOptionsNotSupported,
+                    loaded: false,
+                }
+            );
+            assert_eq!(
+                fixture.vault.with_state(|state| state.repos.repos_by_id.len()),
+                0
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_unlock_unknown_name_options() {
+    with_repo(|fixture| {
+        async move {
+            transform_repos(&fixture.fake_remote, |repo| {
+                repo["filenameEncoding"] = "base1024".into();
+            });
+
+            fixture.lock();
+            fixture.vault.repos_load().await.unwrap();
+
+            assert!(matches!(
+                fixture.vault.repos_service.unlock_repo(
+                    &fixture.repo_id,
+                    "password",
+                    RepoUnlockMode::Unlock
+                ),
+                Err(UnlockRepoError::InvalidNameOptions(_))
+            ));
+        }
+        .boxed()
+    });
+}
+
 #[test]
 fn test_create_location_error() {
     with_repo(|fixture| {
@@ -431,6 +545,7 @@ fn expected_create_form(
                 .clone()
                 .unwrap_or("expected salt".into()),
         ),
+        name_options: Default::default(),
         fill_from_rclone_config_error: None,
         create_repo_status: Status::Initial,
     };
@@ -503,6 +618,7 @@ fn expected_create_created(
             },
             password: "password".into(),
             salt: state_created.config.salt.clone(),
+            name_options: Default::default(),
             rclone_config: state_created.config.rclone_config.clone(),
         },
     };
@@ -0,0 +1,288 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use futures::{AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    repo_files::{errors::RepoFilesErrors, selectors as repo_files_selectors, state::RepoFileType},
+    repo_files_cross_move::errors::CrossMoveError,
+    repo_files_move::state::RepoFilesMoveMode,
+    repos::state::RepoUnlockMode,
+    types::{DecryptedPath, EncryptedPath, RemotePath, RepoId},
+    utils::repo_encrypted_path_utils,
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::transfers::with_transfers};
+use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
+
+async fn create_dest_repo(fixture: &RepoFixture) -> RepoId {
+    let repo_id = fixture
+        .vault
+        .repos_service
+        .create_repo(
+            &fixture.mount_id,
+            &RemotePath("/Other safe box".into()),
+            "other password",
+            Some("other salt"),
+            Default::default(),
+        )
+        .await
+        .unwrap()
+        .repo_id;
+
+    fixture
+        .vault
+        .repos_service
+        .unlock_repo(&repo_id, "other password", RepoUnlockMode::Unlock)
+        .unwrap();
+
+    repo_id
+}
+
+fn encrypt_path(fixture: &RepoFixture, repo_id: &RepoId, path: &str) -> EncryptedPath {
+    fixture
+        .vault
+        .repos_service
+        .get_cipher(repo_id)
+        .unwrap()
+        .encrypt_path(&DecryptedPath(path.into()))
+}
+
+async fn read_file(fixture: &RepoFixture, repo_id: &RepoId, path: &str) -> String {
+    let path = encrypt_path(fixture, repo_id, path);
+
+    fixture
+        .vault
+        .repo_files_service
+        .load_file(repo_id, &path)
+        .await
+        .unwrap();
+
+    let mut reader = fixture
+        .vault
+        .repo_files_get_file_reader(repo_id, &path)
+        .unwrap()
+        .reader()
+        .await
+        .unwrap();
+
+    let mut content = String::new();
+    reader.reader.read_to_string(&mut content).await.unwrap();
+
+    content
+}
+
+async fn file_exists(fixture: &RepoFixture, repo_id: &RepoId, path: &str) -> bool {
+    let path = encrypt_path(fixture, repo_id, path);
+
+    fixture
+        .vault
+        .repo_files_service
+        .load_file(repo_id, &path)
+        .await
+        .is_ok()
+}
+
+#[test]
+fn test_copy_dir() {
+    with_transfers(|fixture| {
+        async move {
+            let dest_repo_id = create_dest_repo(&fixture).await;
+
+            let _ = fixture.upload_file("/dir/a.txt", "a").await;
+            let _ = fixture.upload_file("/dir/sub/b.txt", "bb").await;
+            fixture.create_dir("/dir/empty").await;
+
+            fixture
+                .vault
+                .repo_files_cross_move_move_files(
+                    &fixture.repo_id,
+                    &[fixture.encrypt_path("/dir")],
+                    &dest_repo_id,
+                    &encrypt_path(&fixture, &dest_repo_id, "/"),
+                    RepoFilesMoveMode::Copy,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(read_file(&fixture, &dest_repo_id, "/dir/a.txt").await, "a");
+            assert_eq!(
+                read_file(&fixture, &dest_repo_id, "/dir/sub/b.txt").await,
+                "bb"
+            );
+
+            let empty_path = encrypt_path(&fixture, &dest_repo_id, "/dir/empty");
+            fixture
+                .vault
+                .repo_files_service
+                .load_file(&dest_repo_id, &empty_path)
+                .await
+                .unwrap();
+            assert_eq!(
+                fixture.vault.with_state(|state| {
+                    repo_files_selectors::select_file(
+                        state,
+                        &repo_files_selectors::get_file_id(&dest_repo_id, &empty_path),
+                    )
+                    .map(|file| file.typ.clone())
+                }),
+                Some(RepoFileType::Dir)
+            );
+
+            // the source is kept
+            assert_eq!(
+                read_file(&fixture, &fixture.repo_id, "/dir/sub/b.txt").await,
+                "bb"
+            );
+
+            // every file is uploaded as a transfer
+            assert!(fixture
+                .vault
+                .with_state(|state| state.transfers.transfers.is_empty()));
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_move_file() {
+    with_transfers(|fixture| {
+        async move {
+            let dest_repo_id = create_dest_repo(&fixture).await;
+
+            let _ = fixture.upload_file("/file.txt", "test").await;
+
+            fixture
+                .vault
+                .repo_files_cross_move_move_files(
+                    &fixture.repo_id,
+                    &[fixture.encrypt_path("/file.txt")],
+                    &dest_repo_id,
+                    &encrypt_path(&fixture, &dest_repo_id, "/"),
+                    RepoFilesMoveMode::Move,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                read_file(&fixture, &dest_repo_id, "/file.txt").await,
+                "test"
+            );
+            assert!(!file_exists(&fixture, &fixture.repo_id, "/file.txt").await);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_move_file_read_back_error() {
+    with_transfers(|fixture| {
+        async move {
+            let dest_repo_id = create_dest_repo(&fixture).await;
+
+            let _ = fixture.upload_file("/file.txt", "test").await;
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/get")
+                    && parts.uri.query().unwrap_or("").contains("Other")
+                {
+                    InterceptorResult::Response(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+                } else {
+                    InterceptorResult::Ignore
+                }
+            }));
+
+            assert!(fixture
+                .vault
+                .repo_files_cross_move_move_files(
+                    &fixture.repo_id,
+                    &[fixture.encrypt_path("/file.txt")],
+                    &dest_repo_id,
+                    &encrypt_path(&fixture, &dest_repo_id, "/"),
+                    RepoFilesMoveMode::Move,
+                )
+                .await
+                .is_err());
+
+            // the source is kept if the copy cannot be read back
+            assert!(file_exists(&fixture, &fixture.repo_id, "/file.txt").await);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_move_file_already_exists() {
+    with_transfers(|fixture| {
+        async move {
+            let dest_repo_id = create_dest_repo(&fixture).await;
+
+            let _ = fixture.upload_file("/file.txt", "test").await;
+
+            let dest_path = encrypt_path(&fixture, &dest_repo_id, "/file.txt");
+            let (dest_parent_path, dest_name) =
+                repo_encrypted_path_utils::split_parent_name(&dest_path).unwrap();
+            fixture
+                .vault
+                .repo_files_service
+                .clone()
+                .create_dir_name(&dest_repo_id, &dest_parent_path, dest_name)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_files_cross_move_move_files(
+                        &fixture.repo_id,
+                        &[fixture.encrypt_path("/file.txt")],
+                        &dest_repo_id,
+                        &dest_parent_path,
+                        RepoFilesMoveMode::Move,
+                    )
+                    .await
+                    .unwrap_err()
+                    .to_string(),
+                CrossMoveError::from(RepoFilesErrors::already_exists()).to_string()
+            );
+
+            // nothing is deleted if the move fails
+            assert!(file_exists(&fixture, &fixture.repo_id, "/file.txt").await);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_move_file_to_trash() {
+    with_transfers(|fixture| {
+        async move {
+            let dest_repo_id = create_dest_repo(&fixture).await;
+
+            let _ = fixture.upload_file("/file.txt", "test").await;
+
+            let dest_trash_path = fixture
+                .vault
+                .repo_files_service
+                .get_trash_path(&dest_repo_id)
+                .unwrap();
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_files_cross_move_move_files(
+                        &fixture.repo_id,
+                        &[fixture.encrypt_path("/file.txt")],
+                        &dest_repo_id,
+                        &dest_trash_path,
+                        RepoFilesMoveMode::Move,
+                    )
+                    .await
+                    .unwrap_err()
+                    .to_string(),
+                CrossMoveError::from(RepoFilesErrors::invalid_path()).to_string()
+            );
+
+            assert!(file_exists(&fixture, &fixture.repo_id, "/file.txt").await);
+        }
+        .boxed()
+    });
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use futures::{AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    files::{file_category::FileCategory, files_filter::FilesFilter},
+    repo_files_details::{
+        errors::SaveError,
+        selectors,
+        state::{RepoFilesDetailsMergeHunk, RepoFilesDetailsOptions},
+    },
+};
+use vault_core_tests::{
+    fixtures::repo_fixture::RepoFixture,
+    helpers::{repo_files_details::details_wait_content_loaded, with_repo},
+};
+
+async fn create_details(fixture: &RepoFixture, path: &str) -> u32 {
+    let (details_id, load_future) = fixture.vault.repo_files_details_create(
+        fixture.repo_id.clone(),
+        &fixture.encrypt_path(path),
+        true,
+        RepoFilesDetailsOptions {
+            autosave_interval: Duration::from_secs(20),
+            load_content: FilesFilter {
+                categories: vec![FileCategory::Text],
+                exts: vec![],
+            },
+        },
+    );
+    load_future.await.unwrap();
+
+    details_wait_content_loaded(fixture.vault.store.clone(), details_id).await;
+
+    details_id
+}
+
+async fn destroy_details(fixture: &RepoFixture, details_id: u32) {
+    fixture
+        .vault
+        .repo_files_details_destroy(details_id)
+        .await
+        .unwrap();
+}
+
+async fn read_file(fixture: &RepoFixture, path: &str) -> String {
+    let path = fixture.encrypt_path(path);
+
+    fixture
+        .vault
+        .repo_files_service
+        .load_file(&fixture.repo_id, &path)
+        .await
+        .unwrap();
+
+    let mut reader = fixture
+        .vault
+        .repo_files_get_file_reader(&fixture.repo_id, &path)
+        .unwrap()
+        .reader()
+        .await
+        .unwrap();
+
+    let mut content = String::new();
+    reader.reader.read_to_string(&mut content).await.unwrap();
+
+    content
+}
+
+fn content(fixture: &RepoFixture, details_id: u32) -> String {
+    fixture.vault.with_state(|state| {
+        String::from_utf8(
+            selectors::select_content_bytes_version(state, details_id)
+                .0
+                .unwrap()
+                .to_vec(),
+        )
+        .unwrap()
+    })
+}
+
+fn is_dirty(fixture: &RepoFixture, details_id: u32) -> bool {
+    fixture
+        .vault
+        .with_state(|state| selectors::select_is_dirty(state, details_id))
+}
+
+#[test]
+fn test_save_merge() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "a\nb\nc\n").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            fixture
+                .vault
+                .repo_files_details_set_content(details_id, "a\nb\nc\nours\n".into())
+                .unwrap();
+
+            // the file is changed by someone else
+            fixture.upload_file("/file.txt", "theirs\na\nb\nc\n").await;
+
+            fixture
+                .vault
+                .repo_files_details_save(details_id)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                read_file(&fixture, "/file.txt").await,
+                "theirs\na\nb\nc\nours\n"
+            );
+            assert_eq!(content(&fixture, details_id), "theirs\na\nb\nc\nours\n");
+            assert!(!is_dirty(&fixture, details_id));
+            assert_eq!(
+                fixture
+                    .vault
+                    .with_state(|state| selectors::select_info(state, details_id).unwrap().error),
+                None
+            );
+
+            // the merged content is the base for the next save
+            fixture
+                .vault
+                .repo_files_details_set_content(details_id, "theirs\na\nb\nc\n".into())
+                .unwrap();
+            fixture
+                .vault
+                .repo_files_details_save(details_id)
+                .await
+                .unwrap();
+
+            assert_eq!(read_file(&fixture, "/file.txt").await, "theirs\na\nb\nc\n");
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_save_merge_conflict() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "a\nb\nc\n").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            fixture
+                .vault
+                .repo_files_details_set_content(details_id, "a\nours\nc\n".into())
+                .unwrap();
+
+            fixture.upload_file("/file.txt", "a\ntheirs\nc\n").await;
+
+            assert_eq!(
+                fixture.vault.repo_files_details_save(details_id).await,
+                Err(SaveError::MergeConflict)
+            );
+
+            assert_eq!(
+                fixture.vault.with_state(|state| {
+                    selectors::select_merge_conflict(state, details_id)
+                        .unwrap()
+                        .hunks
+                        .clone()
+                }),
+                vec![
+                    RepoFilesDetailsMergeHunk::Resolved {
+                        content: "a\n".into()
+                    },
+                    RepoFilesDetailsMergeHunk::Conflict {
+                        base: "b\n".into(),
+                        ours: "ours\n".into(),
+                        theirs: "theirs\n".into(),
+                    },
+                    RepoFilesDetailsMergeHunk::Resolved {
+                        content: "c\n".into()
+                    },
+                ]
+            );
+            assert!(is_dirty(&fixture, details_id));
+            assert_eq!(read_file(&fixture, "/file.txt").await, "a\ntheirs\nc\n");
+
+            fixture
+                .vault
+                .repo_files_details_resolve_merge_conflict(
+                    details_id,
+                    "a\nours\ntheirs\nc\n".into(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                read_file(&fixture, "/file.txt").await,
+                "a\nours\ntheirs\nc\n"
+            );
+            assert!(fixture
+                .vault
+                .with_state(|state| selectors::select_merge_conflict(state, details_id).is_none()));
+            assert!(!is_dirty(&fixture, details_id));
+
+            // resolving without a conflict is not possible
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_files_details_resolve_merge_conflict(details_id, "x".into())
+                    .await,
+                Err(SaveError::InvalidState)
+            );
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
@@ -161,6 +161,7 @@ fn test_content() {
                                     remote_size: upload_result.remote_file.size,
                                     remote_modified: upload_result.remote_file.modified,
                                     remote_hash: upload_result.remote_file.hash.clone(),
+                                    remote_bytes: Some("test".as_bytes().to_owned()),
                                 });
                                 location.content.version = 1;
                             }
@@ -1184,6 +1185,7 @@ fn expected_details_state(
             is_editing: false,
             is_dirty: false,
             save_status: Status::Initial,
+            merge_conflict: None,
             delete_status: Status::Initial,
             should_destroy: false,
         }),
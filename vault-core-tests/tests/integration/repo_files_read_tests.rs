@@ -0,0 +1,166 @@
+use std::sync::{Arc, Mutex};
+
+use axum::http::header;
+use futures::{AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+
+use vault_core::{
+    common::state::SizeInfo,
+    repo_files_read::{
+        errors::GetFilesReaderError,
+        state::{RepoFileReaderContentRange, RepoFileReaderRange},
+    },
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+use vault_crypto::constants::{BLOCK_DATA_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE};
+use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
+
+fn content() -> String {
+    (0..BLOCK_DATA_SIZE * 3 + 100)
+        .map(|i| (b'a' + (i % 26) as u8) as char)
+        .collect()
+}
+
+fn record_ranges(fixture: &RepoFixture) -> Arc<Mutex<Vec<String>>> {
+    let ranges = Arc::new(Mutex::new(Vec::new()));
+    let interceptor_ranges = ranges.clone();
+
+    fixture.fake_remote.intercept(Box::new(move |parts| {
+        if parts.uri.path().contains("/files/get") {
+            interceptor_ranges.lock().unwrap().push(
+                parts
+                    .headers
+                    .get(header::RANGE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_owned(),
+            );
+        }
+
+        InterceptorResult::Ignore
+    }));
+
+    ranges
+}
+
+async fn read_range(
+    fixture: &RepoFixture,
+    start: i64,
+    end: Option<i64>,
+) -> Result<(String, RepoFileReaderContentRange), GetFilesReaderError> {
+    let mut reader = fixture
+        .vault
+        .repo_files_get_file_reader_range(
+            &fixture.repo_id,
+            &fixture.encrypt_path("/file.txt"),
+            &RepoFileReaderRange { start, end },
+        )
+        .await?;
+
+    let content_range = reader.content_range.clone().unwrap();
+
+    assert_eq!(
+        reader.size,
+        SizeInfo::Exact(content_range.end - content_range.start + 1)
+    );
+
+    let mut content = String::new();
+
+    reader.reader.read_to_string(&mut content).await.unwrap();
+
+    Ok((content, content_range))
+}
+
+#[test]
+fn test_get_file_reader_range() {
+    with_repo(|fixture| {
+        async move {
+            let data = content();
+            let size = data.len() as i64;
+
+            fixture.upload_file("/file.txt", &data).await;
+
+            let ranges = record_ranges(&fixture);
+
+            // first block
+            let (res, content_range) = read_range(&fixture, 10, Some(19)).await.unwrap();
+            assert_eq!(res, data[10..20]);
+            assert_eq!(
+                content_range,
+                RepoFileReaderContentRange {
+                    start: 10,
+                    end: 19,
+                    size,
+                }
+            );
+
+            // across blocks
+            let start = BLOCK_DATA_SIZE * 2 - 5;
+            let (res, _) = read_range(&fixture, start as i64, Some(start as i64 + 9))
+                .await
+                .unwrap();
+            assert_eq!(res, data[start..start + 10]);
+
+            // until the end
+            let start = BLOCK_DATA_SIZE * 3 + 50;
+            let (res, content_range) = read_range(&fixture, start as i64, None).await.unwrap();
+            assert_eq!(res, data[start..]);
+            assert_eq!(content_range.end, size - 1);
+
+            // end after the end of the file
+            let (res, _) = read_range(&fixture, start as i64, Some(size + 1000))
+                .await
+                .unwrap();
+            assert_eq!(res, data[start..]);
+
+            // only the header and the blocks of the range are downloaded
+            assert_eq!(
+                *ranges.lock().unwrap(),
+                vec![
+                    format!("bytes=0-{}", FILE_HEADER_SIZE + BLOCK_SIZE - 1),
+                    format!("bytes=0-{}", FILE_HEADER_SIZE - 1),
+                    format!(
+                        "bytes={}-{}",
+                        FILE_HEADER_SIZE + BLOCK_SIZE,
+                        FILE_HEADER_SIZE + BLOCK_SIZE * 3 - 1
+                    ),
+                    format!("bytes=0-{}", FILE_HEADER_SIZE - 1),
+                    format!(
+                        "bytes={}-{}",
+                        FILE_HEADER_SIZE + BLOCK_SIZE * 3,
+                        FILE_HEADER_SIZE + BLOCK_SIZE * 4 - 1
+                    ),
+                    format!("bytes=0-{}", FILE_HEADER_SIZE - 1),
+                    format!(
+                        "bytes={}-{}",
+                        FILE_HEADER_SIZE + BLOCK_SIZE * 3,
+                        FILE_HEADER_SIZE + BLOCK_SIZE * 4 - 1
+                    ),
+                ]
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_get_file_reader_range_invalid() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            assert_eq!(
+                read_range(&fixture, 4, None).await.unwrap_err(),
+                GetFilesReaderError::InvalidRange
+            );
+            assert_eq!(
+                read_range(&fixture, 3, Some(2)).await.unwrap_err(),
+                GetFilesReaderError::InvalidRange
+            );
+
+            let (res, _) = read_range(&fixture, 3, None).await.unwrap();
+            assert_eq!(res, "t");
+        }
+        .boxed()
+    });
+}
@@ -0,0 +1,232 @@
+use futures::{io::Cursor, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    remote::RemoteFileUploadConflictResolution,
+    repo_files::{
+        errors::RepairFilesError,
+        selectors as repo_files_selectors,
+        state::{RepoFileType, RepoFilesRepairAction, RepoFilesRepairStatus},
+    },
+    types::{DecryptedName, EncryptedPath, RemoteName},
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+
+async fn upload_raw_file(fixture: &RepoFixture, name: &str) -> EncryptedPath {
+    let (mount_id, remote_parent_path) = fixture
+        .vault
+        .repo_files_service
+        .get_repo_mount_path(&fixture.repo_id, &EncryptedPath("/".into()))
+        .unwrap();
+
+    fixture
+        .vault
+        .remote_files_service
+        .upload_file_reader(
+            &mount_id,
+            &remote_parent_path,
+            &RemoteName(name.into()),
+            Box::pin(Cursor::new(b"test".to_vec())),
+            None,
+            RemoteFileUploadConflictResolution::Overwrite {
+                if_size: None,
+                if_modified: None,
+                if_hash: None,
+                ignore_nonexisting: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    EncryptedPath(format!("/{}", name))
+}
+
+async fn load_decrypted_name(fixture: &RepoFixture, path: &EncryptedPath) -> Option<String> {
+    fixture
+        .vault
+        .repo_files_service
+        .load_file(&fixture.repo_id, path)
+        .await
+        .ok()?;
+
+    fixture.vault.with_state(|state| {
+        repo_files_selectors::select_file(
+            state,
+            &repo_files_selectors::get_file_id(&fixture.repo_id, path),
+        )
+        .and_then(|file| file.decrypted_name().ok().map(|name| name.0.clone()))
+    })
+}
+
+#[test]
+fn test_repair_rename_dry_run() {
+    with_repo(|fixture| {
+        async move {
+            let path = upload_raw_file(&fixture, "plain.txt").await;
+
+            let items = fixture
+                .vault
+                .repo_files_repair_files(
+                    &fixture.repo_id,
+                    std::slice::from_ref(&path),
+                    RepoFilesRepairAction::Rename,
+                    true,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].path, path);
+            assert_eq!(items[0].typ, RepoFileType::File);
+            assert_eq!(items[0].new_name, Some(DecryptedName("plain.txt".into())));
+            assert_eq!(items[0].new_path, Some(fixture.encrypt_path("/plain.txt")));
+            assert_eq!(items[0].status, RepoFilesRepairStatus::Planned);
+
+            // nothing is changed
+            fixture
+                .vault
+                .repo_files_service
+                .load_file(&fixture.repo_id, &path)
+                .await
+                .unwrap();
+            assert_eq!(
+                load_decrypted_name(&fixture, &fixture.encrypt_path("/plain.txt")).await,
+                None
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_repair_rename() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/plain.txt", "test").await;
+            let path = upload_raw_file(&fixture, "plain.txt").await;
+
+            let items = fixture
+                .vault
+                .repo_files_repair_files(
+                    &fixture.repo_id,
+                    std::slice::from_ref(&path),
+                    RepoFilesRepairAction::Rename,
+                    false,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(items[0].status, RepoFilesRepairStatus::Repaired);
+            assert_eq!(
+                items[0].new_name,
+                Some(DecryptedName("plain (1).txt".into()))
+            );
+            assert_eq!(
+                load_decrypted_name(&fixture, &fixture.encrypt_path("/plain (1).txt")).await,
+                Some(String::from("plain (1).txt"))
+            );
+            assert!(fixture
+                .vault
+                .repo_files_service
+                .load_file(&fixture.repo_id, &path)
+                .await
+                .is_err());
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_repair_move_to_lost_found() {
+    with_repo(|fixture| {
+        async move {
+            let path1 = upload_raw_file(&fixture, "a.txt").await;
+            let path2 = upload_raw_file(&fixture, "b.txt").await;
+
+            let items = fixture
+                .vault
+                .repo_files_repair_files(
+                    &fixture.repo_id,
+                    &[path1, path2],
+                    RepoFilesRepairAction::MoveToLostFound,
+                    false,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                items
+                    .iter()
+                    .map(|item| (item.new_name.clone().unwrap().0, item.status.clone()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (String::from("a.txt"), RepoFilesRepairStatus::Repaired),
+                    (String::from("b.txt"), RepoFilesRepairStatus::Repaired),
+                ]
+            );
+            assert_eq!(
+                load_decrypted_name(&fixture, &fixture.encrypt_path("/lost+found/a.txt")).await,
+                Some(String::from("a.txt"))
+            );
+            assert_eq!(
+                load_decrypted_name(&fixture, &fixture.encrypt_path("/lost+found/b.txt")).await,
+                Some(String::from("b.txt"))
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_repair_delete() {
+    with_repo(|fixture| {
+        async move {
+            let path = upload_raw_file(&fixture, "plain.txt").await;
+
+            let items = fixture
+                .vault
+                .repo_files_repair_files(
+                    &fixture.repo_id,
+                    std::slice::from_ref(&path),
+                    RepoFilesRepairAction::Delete,
+                    false,
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(items[0].new_path, None);
+            assert_eq!(items[0].status, RepoFilesRepairStatus::Repaired);
+            assert!(fixture
+                .vault
+                .repo_files_service
+                .load_file(&fixture.repo_id, &path)
+                .await
+                .is_err());
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_repair_name_not_broken() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+            let path = fixture.encrypt_path("/file.txt");
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_files_repair_files(
+                        &fixture.repo_id,
+                        std::slice::from_ref(&path),
+                        RepoFilesRepairAction::Delete,
+                        false,
+                    )
+                    .await,
+                Err(RepairFilesError::NameNotBroken(path.0.clone()))
+            );
+        }
+        .boxed()
+    });
+}
@@ -22,6 +22,10 @@ fn test_upload_overwrite() {
                 Some(Ok(RepoFileTags {
                     encrypted_hash: Some(hex::decode(repo_file.remote_hash.unwrap()).unwrap()),
                     hash: Some(md5::compute("test").to_vec()),
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::new(),
                 }))
             );
@@ -33,6 +37,10 @@ fn test_upload_overwrite() {
                 Some(Ok(RepoFileTags {
                     encrypted_hash: Some(hex::decode(repo_file.remote_hash.unwrap()).unwrap()),
                     hash: Some(md5::compute("test1").to_vec()),
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::new(),
                 }))
             );
@@ -54,6 +62,10 @@ fn test_upload_overwrite_without_hash() {
                         hex::decode(repo_file.remote_hash.clone().unwrap()).unwrap()
                     ),
                     hash: Some(md5::compute("test").to_vec()),
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::new(),
                 }))
             );
@@ -235,6 +247,10 @@ fn test_only_unknown() {
                 Some(Ok(RepoFileTags {
                     encrypted_hash: Some(hex::decode(repo_file.remote_hash.unwrap()).unwrap()),
                     hash: None,
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::from([("k1".into(), "v1".into())]),
                 }))
             );
@@ -284,6 +300,10 @@ fn test_only_unknown_no_encrypted_hash() {
             let tags = RepoFileTags {
                 encrypted_hash: None,
                 hash: None,
+                trash_original_path: None,
+                trash_deleted: None,
+                version_created: None,
+                version_user_agent: None,
                 unknown: HashMap::from([("k1".into(), "v1".into())]),
             };
             let encrypted_tags = tags.to_string(&cipher).unwrap();
@@ -341,6 +361,10 @@ fn test_tags_conflict_retry() {
                         hex::decode(repo_file.remote_hash.clone().unwrap()).unwrap()
                     ),
                     hash: Some(md5::compute("test").to_vec()),
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::new(),
                 }))
             );
@@ -407,6 +431,10 @@ fn test_tags_conflict_retry() {
                 Some(Ok(RepoFileTags {
                     encrypted_hash: Some(hex::decode(repo_file.remote_hash.unwrap()).unwrap()),
                     hash: Some(md5::compute("test").to_vec()),
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::from([("k1".into(), "v1".into())]),
                 }))
             );
@@ -451,6 +479,10 @@ fn test_dir() {
                 Some(Ok(RepoFileTags {
                     encrypted_hash: None,
                     hash: None,
+                    trash_original_path: None,
+                    trash_deleted: None,
+                    version_created: None,
+                    version_user_agent: None,
                     unknown: HashMap::from([("k1".into(), "v1".into())]),
                 }))
             );
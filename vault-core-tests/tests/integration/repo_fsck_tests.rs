@@ -0,0 +1,222 @@
+use futures::{io::Cursor, FutureExt, StreamExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    remote::RemoteFileUploadConflictResolution,
+    repo_fsck::state::{RepoFsckEntryType, RepoFsckOptions, RepoFsckProblem},
+    types::{DecryptedName, DecryptedPath, EncryptedPath, RemoteName},
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+
+async fn upload_raw_file(fixture: &RepoFixture, name: &str, content: Vec<u8>) {
+    let (mount_id, remote_parent_path) = fixture
+        .vault
+        .repo_files_service
+        .get_repo_mount_path(&fixture.repo_id, &EncryptedPath("/".into()))
+        .unwrap();
+
+    fixture
+        .vault
+        .remote_files_service
+        .upload_file_reader(
+            &mount_id,
+            &remote_parent_path,
+            &RemoteName(name.into()),
+            Box::pin(Cursor::new(content)),
+            None,
+            RemoteFileUploadConflictResolution::Overwrite {
+                if_size: None,
+                if_modified: None,
+                if_hash: None,
+                ignore_nonexisting: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_check_ok() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/dir/file.txt", "test").await;
+            fixture.upload_file("/file.txt", "test1").await;
+
+            let report = fixture
+                .vault
+                .repo_fsck_report(&fixture.repo_id, RepoFsckOptions { deep: true })
+                .await
+                .unwrap();
+
+            assert!(report.is_ok());
+            assert_eq!(report.dirs_count, 1);
+            assert_eq!(report.files_count, 2);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_check_stream() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            let entries = fixture
+                .vault
+                .repo_fsck_check(&fixture.repo_id, RepoFsckOptions::default())
+                .await
+                .unwrap()
+                .collect::<Vec<_>>()
+                .await;
+
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].path, Some(DecryptedPath("/file.txt".into())));
+            assert_eq!(
+                entries[0].encrypted_path,
+                Some(fixture.encrypt_path("/file.txt"))
+            );
+            assert_eq!(entries[0].typ, Some(RepoFsckEntryType::File));
+            assert!(entries[0].is_ok());
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_check_invalid_name() {
+    with_repo(|fixture| {
+        async move {
+            upload_raw_file(&fixture, "plain.txt", b"test".to_vec()).await;
+
+            let report = fixture
+                .vault
+                .repo_fsck_report(&fixture.repo_id, RepoFsckOptions::default())
+                .await
+                .unwrap();
+
+            assert!(!report.is_ok());
+            assert_eq!(report.entries.len(), 1);
+            assert_eq!(report.entries[0].path, None);
+            assert!(matches!(
+                report.entries[0].problems.as_slice(),
+                [RepoFsckProblem::InvalidName { .. }, ..]
+            ));
+            assert!(report.to_json().contains("\"type\": \"invalidName\""));
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_check_invalid_size() {
+    with_repo(|fixture| {
+        async move {
+            let cipher = fixture
+                .vault
+                .repos_service
+                .get_cipher(&fixture.repo_id)
+                .unwrap();
+            let name = cipher.encrypt_filename(&DecryptedName("file.txt".into()));
+
+            upload_raw_file(&fixture, &name.0, b"short".to_vec()).await;
+
+            let report = fixture
+                .vault
+                .repo_fsck_report(&fixture.repo_id, RepoFsckOptions::default())
+                .await
+                .unwrap();
+
+            assert_eq!(report.entries.len(), 1);
+            assert_eq!(
+                report.entries[0].path,
+                Some(DecryptedPath("/file.txt".into()))
+            );
+            assert!(matches!(
+                report.entries[0].problems.as_slice(),
+                [RepoFsckProblem::InvalidSize {
+                    encrypted_size: 5,
+                    ..
+                }]
+            ));
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_check_invalid_tags() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            // overwriting the file without updating the tags
+            let cipher = fixture
+                .vault
+                .repos_service
+                .get_cipher(&fixture.repo_id)
+                .unwrap();
+            let name = cipher.encrypt_filename(&DecryptedName("file.txt".into()));
+            let mut content = Vec::new();
+            futures::AsyncReadExt::read_to_end(
+                &mut cipher.encrypt_reader_async(Cursor::new(b"test1".to_vec())),
+                &mut content,
+            )
+            .await
+            .unwrap();
+
+            upload_raw_file(&fixture, &name.0, content).await;
+
+            let report = fixture
+                .vault
+                .repo_fsck_report(&fixture.repo_id, RepoFsckOptions::default())
+                .await
+                .unwrap();
+
+            assert_eq!(report.entries.len(), 1);
+            assert!(matches!(
+                report.entries[0].problems.as_slice(),
+                [RepoFsckProblem::InvalidTags { .. }]
+            ));
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_check_deep_content_decrypt_error() {
+    with_repo(|fixture| {
+        async move {
+            let cipher = fixture
+                .vault
+                .repos_service
+                .get_cipher(&fixture.repo_id)
+                .unwrap();
+            let name = cipher.encrypt_filename(&DecryptedName("file.txt".into()));
+
+            // header and one block with a valid size but random content
+            upload_raw_file(&fixture, &name.0, vec![1; 60]).await;
+
+            let report = fixture
+                .vault
+                .repo_fsck_report(&fixture.repo_id, RepoFsckOptions::default())
+                .await
+                .unwrap();
+
+            assert!(report.is_ok());
+
+            let report = fixture
+                .vault
+                .repo_fsck_report(&fixture.repo_id, RepoFsckOptions { deep: true })
+                .await
+                .unwrap();
+
+            assert_eq!(report.entries.len(), 1);
+            assert!(matches!(
+                report.entries[0].problems.as_slice(),
+                [RepoFsckProblem::ContentDecryptError { .. }]
+            ));
+        }
+        .boxed()
+    });
+}
@@ -0,0 +1,432 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use axum::{
+    body::{self, HttpBody},
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::{io::Cursor, AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    cipher::Cipher,
+    common::state::Status,
+    remote::RemoteFileUploadConflictResolution,
+    repo_password_change::{
+        errors::ChangeRepoPasswordError, selectors, state::RepoPasswordChangePhase,
+    },
+    repos::{self, state::RepoUnlockMode},
+    types::{DecryptedName, DecryptedPath, RemoteName, RemotePath},
+    utils::{remote_path_utils, repo_encrypted_path_utils},
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
+
+async fn read_file(fixture: &RepoFixture, path: &str) -> String {
+    let cipher = fixture
+        .vault
+        .repos_service
+        .get_cipher(&fixture.repo_id)
+        .unwrap();
+    let path = cipher.encrypt_path(&DecryptedPath(path.to_owned()));
+
+    fixture
+        .vault
+        .repo_files_service
+        .load_files(
+            &fixture.repo_id,
+            &repo_encrypted_path_utils::parent_path(&path).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let mut reader = fixture
+        .vault
+        .repo_files_get_file_reader(&fixture.repo_id, &path)
+        .unwrap()
+        .reader()
+        .await
+        .unwrap();
+
+    let mut content = String::new();
+
+    reader.reader.read_to_string(&mut content).await.unwrap();
+
+    content
+}
+
+fn check_unlock(fixture: &RepoFixture, password: &str) -> bool {
+    fixture
+        .vault
+        .repos_service
+        .unlock_repo(&fixture.repo_id, password, RepoUnlockMode::Verify)
+        .is_ok()
+}
+
+#[test]
+fn test_change() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+            fixture.create_dir("/dir").await;
+            fixture.upload_file("/dir/nested.txt", "nested").await;
+
+            let change_id = fixture
+                .vault
+                .repo_password_change_create(fixture.repo_id.clone());
+
+            fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", Some("newsalt"))
+                .await
+                .unwrap();
+
+            fixture.vault.with_state(|state| {
+                let info = selectors::select_info(state, change_id).unwrap();
+
+                assert_eq!(info.status, &Status::Loaded);
+                assert_eq!(info.phase, Some(RepoPasswordChangePhase::Cleanup));
+                assert_eq!(info.total_count, 3);
+                assert_eq!(info.done_count, 3);
+                assert_eq!(info.skipped_count, 0);
+                assert!(info.failures.is_empty());
+
+                let repo = repos::selectors::select_repo(state, &fixture.repo_id).unwrap();
+
+                assert_eq!(repo.path, fixture.path);
+                assert_eq!(repo.salt.as_deref(), Some("newsalt"));
+                assert!(repo.state.is_locked());
+            });
+
+            assert!(!check_unlock(&fixture, "password"));
+            assert!(check_unlock(&fixture, "newpassword"));
+
+            fixture
+                .vault
+                .repos_service
+                .unlock_repo(&fixture.repo_id, "newpassword", RepoUnlockMode::Unlock)
+                .unwrap();
+
+            assert_eq!(read_file(&fixture, "/file.txt").await, "test");
+            assert_eq!(read_file(&fixture, "/dir/nested.txt").await, "nested");
+
+            // the old files are removed and the new location is renamed back
+            let new_session = fixture.new_session();
+            new_session.user_fixture.login();
+            new_session.user_fixture.load().await;
+
+            new_session.vault.with_state(|state| {
+                let repos = repos::selectors::select_repos(state);
+
+                assert_eq!(repos.len(), 1);
+                assert_eq!(repos[0].path, fixture.path);
+            });
+
+            fixture.vault.repo_password_change_destroy(change_id);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_change_resume() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file1.txt", "test1").await;
+            fixture.upload_file("/file2.txt", "test2").await;
+
+            let fail_uploads = Arc::new(AtomicBool::new(false));
+            let interceptor_fail_uploads = fail_uploads.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/put")
+                    && interceptor_fail_uploads.swap(false, Ordering::SeqCst)
+                {
+                    InterceptorResult::Response(StatusCode::FORBIDDEN.into_response())
+                } else {
+                    InterceptorResult::Ignore
+                }
+            }));
+
+            let change_id = fixture
+                .vault
+                .repo_password_change_create(fixture.repo_id.clone());
+
+            // the first re-encrypted upload fails, the repo is not swapped
+            fail_uploads.store(true, Ordering::SeqCst);
+
+            let res = fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", None)
+                .await;
+
+            assert_eq!(res, Err(ChangeRepoPasswordError::FilesFailed(1)));
+
+            fixture.vault.with_state(|state| {
+                let info = selectors::select_info(state, change_id).unwrap();
+
+                assert_eq!(info.phase, Some(RepoPasswordChangePhase::Copying));
+                assert_eq!(info.done_count, 1);
+                assert_eq!(info.failures.len(), 1);
+            });
+
+            assert!(check_unlock(&fixture, "password"));
+
+            fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", None)
+                .await
+                .unwrap();
+
+            fixture.vault.with_state(|state| {
+                let info = selectors::select_info(state, change_id).unwrap();
+
+                assert_eq!(info.done_count, 2);
+                assert_eq!(info.skipped_count, 1);
+                assert!(info.failures.is_empty());
+            });
+
+            assert!(check_unlock(&fixture, "newpassword"));
+
+            // running the change again after it is done is a no-op
+            fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", None)
+                .await
+                .unwrap();
+
+            fixture
+                .vault
+                .repos_service
+                .unlock_repo(&fixture.repo_id, "newpassword", RepoUnlockMode::Unlock)
+                .unwrap();
+
+            assert_eq!(read_file(&fixture, "/file1.txt").await, "test1");
+            assert_eq!(read_file(&fixture, "/file2.txt").await, "test2");
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_change_resume_stale_file() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            // a file with the same size in the new location, e.g. from an
+            // earlier change of a different file
+            let new_cipher = Cipher::new(vault_crypto::Cipher::new("newpassword", None));
+            let (parent_path, name) = remote_path_utils::split_parent_name(&fixture.path).unwrap();
+            let new_location_name = RemoteName(format!("{} (password change)", name.0));
+            let new_location = remote_path_utils::join_path_name(&parent_path, &new_location_name);
+
+            fixture
+                .vault
+                .remote_files_service
+                .create_dir_name(&fixture.mount_id, &parent_path, new_location_name)
+                .await
+                .unwrap();
+            fixture
+                .vault
+                .remote_files_service
+                .upload_file_reader(
+                    &fixture.mount_id,
+                    &new_location,
+                    &RemoteName(
+                        new_cipher
+                            .encrypt_filename(&DecryptedName("file.txt".into()))
+                            .0,
+                    ),
+                    Box::pin(new_cipher.encrypt_reader_async(Cursor::new(b"XXXX".to_vec()))),
+                    None,
+                    RemoteFileUploadConflictResolution::Error,
+                    None,
+                )
+                .await
+                .unwrap();
+
+            let change_id = fixture
+                .vault
+                .repo_password_change_create(fixture.repo_id.clone());
+
+            fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", None)
+                .await
+                .unwrap();
+
+            fixture.vault.with_state(|state| {
+                let info = selectors::select_info(state, change_id).unwrap();
+
+                assert_eq!(info.done_count, 1);
+                assert_eq!(info.skipped_count, 0);
+            });
+
+            fixture
+                .vault
+                .repos_service
+                .unlock_repo(&fixture.repo_id, "newpassword", RepoUnlockMode::Unlock)
+                .unwrap();
+
+            assert_eq!(read_file(&fixture, "/file.txt").await, "test");
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_change_repo_not_updated() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            let old_path = fixture.path.clone();
+
+            // the server does not keep the repo update
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.method == Method::GET && parts.uri.path() == "/api/v2.1/vault/repos" {
+                    let old_path = old_path.clone();
+
+                    InterceptorResult::AsyncTransform(Box::new(move |response| {
+                        async move {
+                            let (mut parts, mut body) = response.into_parts();
+                            parts.headers.remove(header::CONTENT_LENGTH);
+
+                            let mut bytes = Vec::new();
+
+                            while let Some(chunk) = body.data().await {
+                                bytes.extend_from_slice(&chunk.unwrap());
+                            }
+
+                            let mut bundle: serde_json::Value =
+                                serde_json::from_slice(&bytes).unwrap();
+
+                            for repo in bundle["repos"].as_array_mut().unwrap() {
+                                repo["path"] = old_path.0.clone().into();
+                            }
+
+                            Response::from_parts(
+                                parts,
+                                body::boxed(body::Full::from(serde_json::to_vec(&bundle).unwrap())),
+                            )
+                        }
+                        .boxed()
+                    }))
+                } else {
+                    InterceptorResult::Ignore
+                }
+            }));
+
+            let change_id = fixture
+                .vault
+                .repo_password_change_create(fixture.repo_id.clone());
+
+            let res = fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", None)
+                .await;
+
+            assert_eq!(res, Err(ChangeRepoPasswordError::RepoNotUpdated));
+
+            // the old files are not removed
+            fixture
+                .vault
+                .remote_files_service
+                .load_file(
+                    &fixture.mount_id,
+                    &remote_path_utils::join_paths(
+                        &fixture.path,
+                        &RemotePath(fixture.encrypt_path("/file.txt").0),
+                    ),
+                )
+                .await
+                .unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_change_repo_update_not_supported() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.method == Method::PUT
+                    && parts.uri.path().starts_with("/api/v2.1/vault/repos/")
+                {
+                    InterceptorResult::Response(StatusCode::METHOD_NOT_ALLOWED.into_response())
+                } else {
+                    InterceptorResult::Ignore
+                }
+            }));
+
+            let change_id = fixture
+                .vault
+                .repo_password_change_create(fixture.repo_id.clone());
+
+            let res = fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "newpassword", None)
+                .await;
+
+            assert_eq!(
+                res,
+                Err(ChangeRepoPasswordError::UpdateRepoError(
+                    repos::errors::UpdateRepoError::UpdateNotSupported
+                ))
+            );
+
+            // nothing was copied to the new location
+            let (parent_path, name) = remote_path_utils::split_parent_name(&fixture.path).unwrap();
+            let new_location = remote_path_utils::join_path_name(
+                &parent_path,
+                &RemoteName(format!("{} (password change)", name.0)),
+            );
+
+            assert!(fixture
+                .vault
+                .remote_files_service
+                .load_file(&fixture.mount_id, &new_location)
+                .await
+                .is_err());
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_change_invalid_password() {
+    with_repo(|fixture| {
+        async move {
+            let change_id = fixture
+                .vault
+                .repo_password_change_create(fixture.repo_id.clone());
+
+            let res = fixture
+                .vault
+                .repo_password_change_change(change_id, "wrong", "newpassword", None)
+                .await;
+
+            assert_eq!(
+                res,
+                Err(ChangeRepoPasswordError::InvalidPassword(
+                    repos::errors::InvalidPasswordError
+                ))
+            );
+
+            let res = fixture
+                .vault
+                .repo_password_change_change(change_id, "password", "password", Some("salt"))
+                .await;
+
+            assert_eq!(res, Err(ChangeRepoPasswordError::SamePassword));
+        }
+        .boxed()
+    });
+}
@@ -0,0 +1,339 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use futures::{AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    repo_search::{selectors, service::INDEX_NAME, state::RepoSearchIndex},
+    repos::state::RepoTrashSettings,
+    types::DecryptedName,
+    utils::repo_encrypted_path_utils,
+};
+use vault_core_tests::{
+    fixtures::repo_fixture::RepoFixture,
+    helpers::{eventstream::eventstream_wait_registered, wait_for_async, with_repo},
+};
+use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
+
+fn search(fixture: &RepoFixture, search_id: u32, query: &str) -> Vec<(String, bool)> {
+    fixture
+        .vault
+        .repo_search_set_query(search_id, query.to_owned());
+
+    fixture.vault.with_state(|state| {
+        selectors::select_info(state, search_id)
+            .unwrap()
+            .results
+            .iter()
+            .map(|result| (result.entry.path.0.clone(), result.content_matched))
+            .collect()
+    })
+}
+
+fn indexed_count(fixture: &RepoFixture, search_id: u32) -> usize {
+    fixture.vault.with_state(|state| {
+        selectors::select_info(state, search_id)
+            .unwrap()
+            .indexed_count
+    })
+}
+
+#[test]
+fn test_search_name() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/Dir/Report.txt", "test").await;
+            fixture.upload_file("/notes.md", "test").await;
+
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, false)
+                .await
+                .unwrap();
+
+            let search_id = fixture.vault.repo_search_create(fixture.repo_id.clone());
+
+            assert_eq!(indexed_count(&fixture, search_id), 4);
+            assert_eq!(
+                search(&fixture, search_id, "report"),
+                vec![("/Dir/Report.txt".into(), false)]
+            );
+            assert_eq!(
+                search(&fixture, search_id, "dir"),
+                vec![("/Dir".into(), false), ("/Dir/Report.txt".into(), false)]
+            );
+            assert_eq!(
+                search(&fixture, search_id, "dir notes"),
+                Vec::<(String, bool)>::new()
+            );
+            assert_eq!(search(&fixture, search_id, ""), vec![]);
+
+            fixture.vault.repo_search_destroy(search_id);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_search_content() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/a.txt", "Hello World").await;
+            fixture.upload_file("/b.txt", "other").await;
+            fixture.upload_file("/image.jpg", "world").await;
+
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, true)
+                .await
+                .unwrap();
+
+            let search_id = fixture.vault.repo_search_create(fixture.repo_id.clone());
+
+            assert_eq!(
+                search(&fixture, search_id, "world"),
+                vec![("/a.txt".into(), true)]
+            );
+            assert_eq!(
+                search(&fixture, search_id, "hello a.txt"),
+                vec![("/a.txt".into(), true)]
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_search_incremental_upload() {
+    with_repo(|fixture| {
+        async move {
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, true)
+                .await
+                .unwrap();
+
+            let search_id = fixture.vault.repo_search_create(fixture.repo_id.clone());
+
+            fixture.upload_file("/new.txt", "needle").await;
+
+            assert_eq!(
+                search(&fixture, search_id, "new"),
+                vec![("/new.txt".into(), false)]
+            );
+
+            fixture
+                .vault
+                .repo_search_service
+                .process_pending(&fixture.repo_id)
+                .await;
+
+            assert_eq!(
+                search(&fixture, search_id, "needle"),
+                vec![("/new.txt".into(), true)]
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_search_move_delete() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/dir/file.txt", "test").await;
+            fixture.upload_file("/other.txt", "test").await;
+            fixture.create_dir("/target").await;
+
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, false)
+                .await
+                .unwrap();
+
+            let search_id = fixture.vault.repo_search_create(fixture.repo_id.clone());
+
+            // moves are only applied to the state by eventstream events, the
+            // index subscribes to the repo while it is loaded
+            eventstream_wait_registered(
+                fixture.vault.store.clone(),
+                &fixture.mount_id,
+                &fixture.path,
+            )
+            .await;
+
+            fixture
+                .vault
+                .repo_files_service
+                .move_file(
+                    &fixture.repo_id,
+                    &fixture.encrypt_path("/dir"),
+                    &fixture.encrypt_path("/target"),
+                )
+                .await
+                .unwrap();
+
+            let moved_fixture = fixture.clone();
+
+            assert!(
+                wait_for_async(1000, move || search(&moved_fixture, search_id, "file")
+                    == vec![("/target/dir/file.txt".into(), false)])
+                .await
+            );
+
+            let (mount_id, remote_path) = fixture
+                .vault
+                .repo_files_service
+                .get_repo_mount_path(&fixture.repo_id, &fixture.encrypt_path("/other.txt"))
+                .unwrap();
+
+            fixture
+                .vault
+                .remote_files_service
+                .delete_file(&mount_id, &remote_path)
+                .await
+                .unwrap();
+
+            assert_eq!(search(&fixture, search_id, "other"), vec![]);
+            assert_eq!(indexed_count(&fixture, search_id), 4);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_search_trash_not_indexed() {
+    with_repo(|fixture| {
+        async move {
+            fixture
+                .vault
+                .repos_set_trash_settings(
+                    &fixture.repo_id,
+                    RepoTrashSettings {
+                        enabled: true,
+                        retention_days: None,
+                    },
+                )
+                .unwrap();
+
+            fixture.upload_file("/deleted.txt", "test").await;
+            fixture.upload_file("/kept.txt", "test").await;
+
+            fixture
+                .vault
+                .repo_files_service
+                .delete_file(&fixture.repo_id, &fixture.encrypt_path("/deleted.txt"))
+                .await
+                .unwrap();
+
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, false)
+                .await
+                .unwrap();
+
+            let search_id = fixture.vault.repo_search_create(fixture.repo_id.clone());
+
+            assert_eq!(search(&fixture, search_id, "deleted"), vec![]);
+            assert_eq!(search(&fixture, search_id, "trash"), vec![]);
+            assert_eq!(
+                search(&fixture, search_id, "kept"),
+                vec![("/kept.txt".into(), false)]
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_search_persisted() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/secret-name.txt", "test").await;
+
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, false)
+                .await
+                .unwrap();
+
+            // the index is stored in the search folder of the repo
+            let search_path = fixture
+                .vault
+                .repo_files_service
+                .get_search_path(&fixture.repo_id)
+                .unwrap();
+            let cipher = fixture
+                .vault
+                .repos_service
+                .get_cipher(&fixture.repo_id)
+                .unwrap();
+            let index_path = repo_encrypted_path_utils::join_path_name(
+                &search_path,
+                &cipher.encrypt_filename(&DecryptedName(INDEX_NAME.into())),
+            );
+            let (mount_id, remote_path) = fixture
+                .vault
+                .repo_files_service
+                .get_repo_mount_path(&fixture.repo_id, &index_path)
+                .unwrap();
+
+            let mut data = Vec::new();
+            fixture
+                .vault
+                .remote_files_service
+                .get_file_reader(&mount_id, &remote_path, None)
+                .await
+                .unwrap()
+                .reader
+                .read_to_end(&mut data)
+                .await
+                .unwrap();
+
+            assert!(!String::from_utf8_lossy(&data).contains("secret-name"));
+
+            let index: RepoSearchIndex =
+                serde_json::from_slice(&cipher.decrypt_vec(&data).unwrap()).unwrap();
+
+            assert!(index
+                .entries
+                .contains_key(&fixture.encrypt_path("/secret-name.txt")));
+
+            let search_id = fixture.vault.repo_search_create(fixture.repo_id.clone());
+
+            fixture.lock();
+
+            assert_eq!(indexed_count(&fixture, search_id), 0);
+
+            fixture.unlock();
+
+            let list_counter = Arc::new(AtomicUsize::new(0));
+            let interceptor_list_counter = list_counter.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/files/listrecursive") {
+                    interceptor_list_counter.fetch_add(1, Ordering::SeqCst);
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            fixture
+                .vault
+                .repo_search_load_index(&fixture.repo_id, false)
+                .await
+                .unwrap();
+
+            // the stored index is used without listing the repo
+            assert_eq!(list_counter.load(Ordering::SeqCst), 0);
+            assert_eq!(
+                search(&fixture, search_id, "secret"),
+                vec![("/secret-name.txt".into(), false)]
+            );
+            assert_eq!(search(&fixture, search_id, "index"), vec![]);
+        }
+        .boxed()
+    });
+}
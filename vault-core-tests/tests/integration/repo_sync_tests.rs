@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+
+use futures::{AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    remote::ApiErrorCode, repo_files::errors::LoadFileError, repos::state::RepoTrashSettings,
+    types::DecryptedPath, utils::repo_encrypted_path_utils,
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+use vault_native::repo_sync::sync::{RepoSync, RepoSyncEvent, RepoSyncReport};
+
+fn temp_dir() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("vault-repo-sync-{}", uuid::Uuid::new_v4()));
+
+    std::fs::create_dir_all(&path).unwrap();
+
+    path
+}
+
+async fn sync(fixture: &RepoFixture, local_path: &Path) -> (RepoSyncReport, Vec<RepoSyncEvent>) {
+    let repo_sync = RepoSync::new(
+        fixture.vault.clone(),
+        fixture.repo_id.clone(),
+        DecryptedPath("/".into()),
+        local_path.to_owned(),
+        RepoSync::default_db_path(local_path),
+    );
+
+    let mut events = Vec::new();
+
+    let report = repo_sync
+        .sync(&mut |event| events.push(event))
+        .await
+        .unwrap();
+
+    (report, events)
+}
+
+fn read_local(local_path: &Path, path: &str) -> String {
+    std::fs::read_to_string(local_path.join(path)).unwrap()
+}
+
+async fn read_remote(fixture: &RepoFixture, path: &str) -> Option<String> {
+    let cipher = fixture
+        .vault
+        .repos_service
+        .get_cipher(&fixture.repo_id)
+        .unwrap();
+    let path = cipher.encrypt_path(&DecryptedPath(path.to_owned()));
+
+    match fixture
+        .vault
+        .repo_files_service
+        .load_file(&fixture.repo_id, &path)
+        .await
+    {
+        Ok(()) => {}
+        Err(LoadFileError::RemoteError(err)) if err.is_api_error_code(ApiErrorCode::NotFound) => {
+            return None
+        }
+        Err(err) => panic!("{:?}", err),
+    }
+
+    fixture
+        .vault
+        .repo_files_service
+        .load_files(
+            &fixture.repo_id,
+            &repo_encrypted_path_utils::parent_path(&path).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let mut reader = fixture
+        .vault
+        .repo_files_get_file_reader(&fixture.repo_id, &path)
+        .unwrap()
+        .reader()
+        .await
+        .unwrap();
+
+    let mut content = String::new();
+
+    reader.reader.read_to_string(&mut content).await.unwrap();
+
+    Some(content)
+}
+
+async fn delete_remote(fixture: &RepoFixture, path: &str) {
+    let cipher = fixture
+        .vault
+        .repos_service
+        .get_cipher(&fixture.repo_id)
+        .unwrap();
+    let path = cipher.encrypt_path(&DecryptedPath(path.to_owned()));
+
+    let (mount_id, remote_path) = fixture
+        .vault
+        .repo_files_service
+        .get_repo_mount_path(&fixture.repo_id, &path)
+        .unwrap();
+
+    fixture
+        .vault
+        .remote_files_service
+        .delete_file(&mount_id, &remote_path)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_sync_initial() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/remote.txt", "remote").await;
+            fixture.upload_file("/dir/nested.txt", "nested").await;
+
+            let local_path = temp_dir();
+            std::fs::write(local_path.join("local.txt"), "local").unwrap();
+            std::fs::create_dir(local_path.join("localdir")).unwrap();
+            std::fs::write(local_path.join("localdir").join("a.txt"), "a").unwrap();
+            std::fs::create_dir(local_path.join("empty")).unwrap();
+
+            let (report, events) = sync(&fixture, &local_path).await;
+
+            assert!(!events
+                .iter()
+                .any(|event| matches!(event, RepoSyncEvent::Failed { .. })));
+            assert_eq!(report.uploaded_count, 2);
+            assert_eq!(report.downloaded_count, 2);
+            assert_eq!(report.failed_count, 0);
+
+            assert_eq!(read_local(&local_path, "remote.txt"), "remote");
+            assert_eq!(read_local(&local_path, "dir/nested.txt"), "nested");
+            assert_eq!(
+                read_remote(&fixture, "/local.txt").await.as_deref(),
+                Some("local")
+            );
+            assert_eq!(
+                read_remote(&fixture, "/localdir/a.txt").await.as_deref(),
+                Some("a")
+            );
+            assert!(local_path.join(".vault-sync.json").exists());
+
+            // nothing changed, the second sync is a no-op
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report, RepoSyncReport::default());
+
+            std::fs::remove_dir_all(&local_path).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_sync_changes() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "v1").await;
+
+            let local_path = temp_dir();
+
+            sync(&fixture, &local_path).await;
+
+            std::fs::write(local_path.join("file.txt"), "local v2").unwrap();
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report.uploaded_count, 1);
+            assert_eq!(
+                read_remote(&fixture, "/file.txt").await.as_deref(),
+                Some("local v2")
+            );
+
+            fixture.upload_file("/file.txt", "remote v3").await;
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report.downloaded_count, 1);
+            assert_eq!(read_local(&local_path, "file.txt"), "remote v3");
+
+            std::fs::remove_dir_all(&local_path).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_sync_conflict() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "v1").await;
+
+            let local_path = temp_dir();
+
+            sync(&fixture, &local_path).await;
+
+            std::fs::write(local_path.join("file.txt"), "local change").unwrap();
+            fixture.upload_file("/file.txt", "remote change!").await;
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report.conflicts_count, 1);
+            assert_eq!(report.failed_count, 0);
+
+            // the local file is kept under a new name
+            assert_eq!(read_local(&local_path, "file.txt"), "remote change!");
+            assert_eq!(read_local(&local_path, "file (1).txt"), "local change");
+            assert_eq!(
+                read_remote(&fixture, "/file.txt").await.as_deref(),
+                Some("remote change!")
+            );
+            assert_eq!(
+                read_remote(&fixture, "/file (1).txt").await.as_deref(),
+                Some("local change")
+            );
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report, RepoSyncReport::default());
+
+            std::fs::remove_dir_all(&local_path).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_sync_delete() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/a.txt", "a").await;
+            fixture.upload_file("/b.txt", "b").await;
+            fixture.upload_file("/dir/c.txt", "c").await;
+
+            let local_path = temp_dir();
+
+            sync(&fixture, &local_path).await;
+
+            std::fs::remove_file(local_path.join("a.txt")).unwrap();
+            std::fs::remove_dir_all(local_path.join("dir")).unwrap();
+            delete_remote(&fixture, "/b.txt").await;
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report.deleted_remote_count, 2);
+            assert_eq!(report.deleted_local_count, 1);
+
+            assert!(!local_path.join("b.txt").exists());
+            assert_eq!(read_remote(&fixture, "/a.txt").await, None);
+            assert_eq!(read_remote(&fixture, "/dir/c.txt").await, None);
+
+            std::fs::remove_dir_all(&local_path).unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_sync_delete_moves_to_trash() {
+    with_repo(|fixture| {
+        async move {
+            fixture
+                .vault
+                .repos_set_trash_settings(
+                    &fixture.repo_id,
+                    RepoTrashSettings {
+                        enabled: true,
+                        retention_days: None,
+                    },
+                )
+                .unwrap();
+
+            fixture.upload_file("/a.txt", "a").await;
+
+            let local_path = temp_dir();
+
+            sync(&fixture, &local_path).await;
+
+            std::fs::remove_file(local_path.join("a.txt")).unwrap();
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report.deleted_remote_count, 1);
+            assert_eq!(read_remote(&fixture, "/a.txt").await, None);
+
+            let trash_paths = fixture
+                .vault
+                .repo_trash_list(&fixture.repo_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.original_decrypted_path.unwrap().0)
+                .collect::<Vec<_>>();
+            assert_eq!(trash_paths, vec![String::from("/a.txt")]);
+
+            // the trash folder is neither downloaded nor uploaded
+            std::fs::create_dir(local_path.join(".trash")).unwrap();
+            std::fs::write(local_path.join(".trash").join("b.txt"), "b").unwrap();
+
+            let (report, _) = sync(&fixture, &local_path).await;
+
+            assert_eq!(report.downloaded_count, 0);
+            assert_eq!(report.uploaded_count, 0);
+            assert!(!local_path.join(".trash").join("a.txt").exists());
+            assert_eq!(read_remote(&fixture, "/.trash/b.txt").await, None);
+
+            std::fs::remove_dir_all(&local_path).unwrap();
+        }
+        .boxed()
+    });
+}
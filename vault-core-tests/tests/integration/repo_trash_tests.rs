@@ -0,0 +1,393 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::{join, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    dialogs,
+    repo_files::selectors as repo_files_selectors,
+    repo_files_browsers::{self, state::RepoFilesBrowserOptions},
+    repo_trash::{errors::RepoTrashError, state::RepoTrashEntry},
+    repos::{service::REPO_TRASH_SETTINGS_STORAGE_KEY, state::RepoTrashSettings},
+    store,
+    types::{EncryptedPath, RepoId},
+};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+
+fn enable_trash(fixture: &RepoFixture, retention_days: Option<u32>) {
+    fixture
+        .vault
+        .repos_set_trash_settings(
+            &fixture.repo_id,
+            RepoTrashSettings {
+                enabled: true,
+                retention_days,
+            },
+        )
+        .unwrap();
+}
+
+async fn delete_files(fixture: &RepoFixture, paths: &[EncryptedPath]) {
+    let files: Vec<(RepoId, EncryptedPath)> = paths
+        .iter()
+        .map(|path| (fixture.repo_id.clone(), path.clone()))
+        .collect();
+
+    let delete_future = fixture.vault.repo_files_delete_files(&files);
+
+    let dialog_vault = fixture.vault.clone();
+    let dialog_future = fixture.fake_remote.tokio_runtime.spawn(async move {
+        let wait_store = dialog_vault.store.clone();
+        let dialog_id = store::wait_for(wait_store.clone(), &[store::Event::Dialogs], move |_| {
+            wait_store.with_state(|state| {
+                dialogs::selectors::select_dialogs(state)
+                    .first()
+                    .map(|dialog| dialog.id)
+            })
+        })
+        .await;
+
+        dialog_vault.dialogs_confirm(dialog_id);
+    });
+
+    let (delete_res, _) = join!(delete_future, dialog_future);
+
+    delete_res.unwrap();
+}
+
+async fn list(fixture: &RepoFixture) -> Vec<RepoTrashEntry> {
+    fixture
+        .vault
+        .repo_trash_list(&fixture.repo_id)
+        .await
+        .unwrap()
+}
+
+fn original_paths(entries: &[RepoTrashEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| entry.original_decrypted_path.clone().unwrap().0)
+        .collect()
+}
+
+async fn root_names(fixture: &RepoFixture) -> Vec<String> {
+    let root_path = EncryptedPath("/".into());
+
+    fixture
+        .vault
+        .repo_files_service
+        .load_files(&fixture.repo_id, &root_path)
+        .await
+        .unwrap();
+
+    let mut names: Vec<String> = fixture.vault.with_state(|state| {
+        repo_files_selectors::select_files(state, &fixture.repo_id, &root_path)
+            .map(|file| file.decrypted_name().unwrap().0.clone())
+            .collect()
+    });
+
+    names.sort();
+
+    names
+}
+
+#[test]
+fn test_delete_without_trash() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            delete_files(&fixture, &[fixture.encrypt_path("/file.txt")]).await;
+
+            assert_eq!(root_names(&fixture).await, Vec::<String>::new());
+            assert_eq!(list(&fixture).await, vec![]);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_delete_moves_to_trash() {
+    with_repo(|fixture| {
+        async move {
+            enable_trash(&fixture, None);
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .secure_storage_service
+                    .get::<HashMap<RepoId, RepoTrashSettings>>(REPO_TRASH_SETTINGS_STORAGE_KEY)
+                    .unwrap(),
+                Some(HashMap::from([(
+                    fixture.repo_id.clone(),
+                    RepoTrashSettings {
+                        enabled: true,
+                        retention_days: None,
+                    }
+                )]))
+            );
+
+            fixture.upload_file("/dir/file.txt", "test").await;
+            fixture.upload_file("/other.txt", "test").await;
+
+            delete_files(&fixture, &[fixture.encrypt_path("/dir")]).await;
+
+            assert_eq!(root_names(&fixture).await, vec![".trash", "other.txt"]);
+
+            let entries = list(&fixture).await;
+
+            assert_eq!(original_paths(&entries), vec!["/dir"]);
+            assert_eq!(entries[0].original_path, Some(fixture.encrypt_path("/dir")));
+            assert!(entries[0].deleted.is_some());
+            assert_eq!(entries[0].file.decrypted_path().unwrap().0, "/.trash/dir");
+
+            // the trash folder is hidden in browsers
+            let (browser_id, load_future) = fixture.vault.repo_files_browsers_create(
+                fixture.repo_id.clone(),
+                &EncryptedPath("/".into()),
+                RepoFilesBrowserOptions { select_name: None },
+            );
+            load_future.await.unwrap();
+
+            let names: Vec<String> = fixture.vault.with_state(|state| {
+                repo_files_browsers::selectors::select_items(state, browser_id)
+                    .iter()
+                    .map(|item| item.file.decrypted_name().unwrap().0.clone())
+                    .collect()
+            });
+
+            assert_eq!(names, vec!["other.txt"]);
+
+            fixture.vault.repo_files_browsers_destroy(browser_id);
+
+            // files in the trash are deleted permanently
+            delete_files(&fixture, &[entries[0].file.encrypted_path.clone()]).await;
+
+            assert_eq!(list(&fixture).await, vec![]);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_delete_same_name() {
+    with_repo(|fixture| {
+        async move {
+            enable_trash(&fixture, None);
+
+            fixture.upload_file("/a/file.txt", "a").await;
+            fixture.upload_file("/b/file.txt", "b").await;
+
+            delete_files(
+                &fixture,
+                &[
+                    fixture.encrypt_path("/a/file.txt"),
+                    fixture.encrypt_path("/b/file.txt"),
+                ],
+            )
+            .await;
+
+            let mut entries: Vec<(String, String)> = list(&fixture)
+                .await
+                .into_iter()
+                .map(|entry| {
+                    (
+                        entry.file.decrypted_name().unwrap().0.clone(),
+                        entry.original_decrypted_path.unwrap().0,
+                    )
+                })
+                .collect();
+            entries.sort();
+
+            assert_eq!(
+                entries,
+                vec![
+                    ("file (1).txt".into(), "/b/file.txt".into()),
+                    ("file.txt".into(), "/a/file.txt".into()),
+                ]
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_restore() {
+    with_repo(|fixture| {
+        async move {
+            enable_trash(&fixture, None);
+
+            fixture.upload_file("/dir/file.txt", "old").await;
+            fixture.upload_file("/parent/child.txt", "child").await;
+
+            delete_files(
+                &fixture,
+                &[
+                    fixture.encrypt_path("/dir/file.txt"),
+                    fixture.encrypt_path("/parent/child.txt"),
+                ],
+            )
+            .await;
+
+            // a new file with the same name is uploaded after the delete
+            fixture.upload_file("/dir/file.txt", "new").await;
+
+            // the parent of the deleted file is removed
+            let (mount_id, remote_path) = fixture
+                .vault
+                .repo_files_service
+                .get_repo_mount_path(&fixture.repo_id, &fixture.encrypt_path("/parent"))
+                .unwrap();
+            fixture
+                .vault
+                .remote_files_service
+                .delete_file(&mount_id, &remote_path)
+                .await
+                .unwrap();
+
+            let mut paths: Vec<EncryptedPath> = list(&fixture)
+                .await
+                .into_iter()
+                .map(|entry| entry.file.encrypted_path)
+                .collect();
+            paths.sort();
+
+            let mut restored_paths = fixture
+                .vault
+                .repo_trash_restore(&fixture.repo_id, &paths)
+                .await
+                .unwrap();
+            restored_paths.sort();
+
+            let mut expected_paths = vec![
+                fixture.encrypt_path("/dir/file (1).txt"),
+                fixture.encrypt_path("/parent/child.txt"),
+            ];
+            expected_paths.sort();
+
+            assert_eq!(restored_paths, expected_paths);
+            assert_eq!(list(&fixture).await, vec![]);
+
+            let tags = fixture.vault.with_state(|state| {
+                repo_files_selectors::select_file(state, &fixture.get_file_id("/dir/file (1).txt"))
+                    .unwrap()
+                    .tags
+                    .clone()
+                    .unwrap()
+                    .unwrap()
+            });
+
+            assert_eq!(tags.trash_original_path, None);
+            assert_eq!(tags.trash_deleted, None);
+            assert!(tags.hash.is_some());
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_purge() {
+    with_repo(|fixture| {
+        async move {
+            enable_trash(&fixture, None);
+
+            fixture.upload_file("/a.txt", "a").await;
+            fixture.upload_file("/b.txt", "b").await;
+            fixture.upload_file("/c.txt", "c").await;
+
+            delete_files(
+                &fixture,
+                &[
+                    fixture.encrypt_path("/a.txt"),
+                    fixture.encrypt_path("/b.txt"),
+                ],
+            )
+            .await;
+
+            let entries = list(&fixture).await;
+            let a_entry = entries
+                .iter()
+                .find(|entry| entry.original_decrypted_path.as_ref().unwrap().0 == "/a.txt")
+                .unwrap();
+
+            fixture
+                .vault
+                .repo_trash_purge(
+                    &fixture.repo_id,
+                    std::slice::from_ref(&a_entry.file.encrypted_path),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(original_paths(&list(&fixture).await), vec!["/b.txt"]);
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_trash_purge(&fixture.repo_id, &[fixture.encrypt_path("/c.txt")])
+                    .await,
+                Err(RepoTrashError::NotInTrash)
+            );
+
+            fixture
+                .vault
+                .repo_trash_purge_all(&fixture.repo_id)
+                .await
+                .unwrap();
+
+            assert_eq!(list(&fixture).await, vec![]);
+            assert_eq!(root_names(&fixture).await, vec!["c.txt"]);
+
+            // purging an empty trash is not an error
+            fixture
+                .vault
+                .repo_trash_purge_all(&fixture.repo_id)
+                .await
+                .unwrap();
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_purge_expired() {
+    with_repo(|fixture| {
+        async move {
+            enable_trash(&fixture, Some(1));
+
+            fixture.upload_file("/file.txt", "test").await;
+
+            delete_files(&fixture, &[fixture.encrypt_path("/file.txt")]).await;
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_trash_purge_expired(&fixture.repo_id)
+                    .await
+                    .unwrap(),
+                0
+            );
+            assert_eq!(original_paths(&list(&fixture).await), vec!["/file.txt"]);
+
+            enable_trash(&fixture, Some(0));
+
+            // expired entries are purged when the repo is unlocked
+            fixture.lock();
+            fixture.unlock();
+
+            let mut purged = false;
+
+            for _ in 0..100 {
+                if list(&fixture).await.is_empty() {
+                    purged = true;
+
+                    break;
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+
+            assert!(purged);
+        }
+        .boxed()
+    });
+}
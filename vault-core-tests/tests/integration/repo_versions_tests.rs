@@ -0,0 +1,372 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::{io::Cursor, AsyncReadExt, FutureExt};
+use similar_asserts::assert_eq;
+use vault_core::{
+    files::{file_category::FileCategory, files_filter::FilesFilter},
+    repo_files::state::RepoFilesUploadConflictResolution,
+    repo_files_browsers::{self, state::RepoFilesBrowserOptions},
+    repo_files_details::state::RepoFilesDetailsOptions,
+    repo_versions::{
+        errors::RepoVersionsError,
+        state::{RepoVersion, RepoVersionDiffLine, RepoVersionDiffLineKind},
+    },
+    repos::{service::REPO_VERSIONS_SETTINGS_STORAGE_KEY, state::RepoVersionsSettings},
+    types::{EncryptedPath, RepoId},
+};
+use vault_core_tests::{
+    fixtures::repo_fixture::RepoFixture,
+    helpers::{repo_files_details::details_wait_content_loaded, with_repo},
+};
+
+fn enable_versions(fixture: &RepoFixture, max_versions: u32) {
+    fixture
+        .vault
+        .repos_set_versions_settings(
+            &fixture.repo_id,
+            RepoVersionsSettings {
+                enabled: true,
+                max_versions,
+            },
+        )
+        .unwrap();
+}
+
+async fn create_details(fixture: &RepoFixture, path: &str) -> u32 {
+    let (details_id, load_future) = fixture.vault.repo_files_details_create(
+        fixture.repo_id.clone(),
+        &fixture.encrypt_path(path),
+        true,
+        RepoFilesDetailsOptions {
+            autosave_interval: Duration::from_secs(20),
+            load_content: FilesFilter {
+                categories: vec![FileCategory::Text],
+                exts: vec![],
+            },
+        },
+    );
+    load_future.await.unwrap();
+
+    details_wait_content_loaded(fixture.vault.store.clone(), details_id).await;
+
+    details_id
+}
+
+async fn save(fixture: &RepoFixture, details_id: u32, content: &str) {
+    fixture
+        .vault
+        .repo_files_details_set_content(details_id, content.as_bytes().to_vec())
+        .unwrap();
+
+    fixture
+        .vault
+        .repo_files_details_save(details_id)
+        .await
+        .unwrap();
+}
+
+async fn destroy_details(fixture: &RepoFixture, details_id: u32) {
+    fixture
+        .vault
+        .repo_files_details_destroy(details_id)
+        .await
+        .unwrap();
+}
+
+async fn versions(fixture: &RepoFixture, details_id: u32) -> Vec<RepoVersion> {
+    fixture
+        .vault
+        .repo_files_details_versions(details_id)
+        .await
+        .unwrap()
+}
+
+async fn read_version(fixture: &RepoFixture, details_id: u32, version: &RepoVersion) -> String {
+    let provider = fixture
+        .vault
+        .repo_files_details_version_get_file_reader(details_id, &version.file.encrypted_path)
+        .await
+        .unwrap();
+
+    assert_eq!(provider.name.0, "file.txt");
+
+    let mut content = String::new();
+
+    provider
+        .reader()
+        .await
+        .unwrap()
+        .reader
+        .read_to_string(&mut content)
+        .await
+        .unwrap();
+
+    content
+}
+
+async fn read_versions(fixture: &RepoFixture, details_id: u32) -> Vec<String> {
+    let mut contents = Vec::new();
+
+    for version in versions(fixture, details_id).await {
+        contents.push(read_version(fixture, details_id, &version).await);
+    }
+
+    contents
+}
+
+#[test]
+fn test_save_without_versions() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "v1").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            save(&fixture, details_id, "v2").await;
+
+            assert_eq!(versions(&fixture, details_id).await, vec![]);
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_save_creates_version() {
+    with_repo(|fixture| {
+        async move {
+            enable_versions(&fixture, 5);
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .secure_storage_service
+                    .get::<HashMap<RepoId, RepoVersionsSettings>>(
+                        REPO_VERSIONS_SETTINGS_STORAGE_KEY
+                    )
+                    .unwrap(),
+                Some(HashMap::from([(
+                    fixture.repo_id.clone(),
+                    RepoVersionsSettings {
+                        enabled: true,
+                        max_versions: 5,
+                    }
+                )]))
+            );
+
+            fixture.upload_file("/file.txt", "v1").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            save(&fixture, details_id, "v2").await;
+
+            let file_versions = versions(&fixture, details_id).await;
+
+            assert_eq!(file_versions.len(), 1);
+            assert!(file_versions[0].created.is_some());
+            assert_eq!(
+                file_versions[0].user_agent.as_deref(),
+                Some("vault-core-tests")
+            );
+            assert!(file_versions[0]
+                .file
+                .decrypted_path()
+                .unwrap()
+                .0
+                .starts_with("/.versions/file.txt/"));
+            assert_eq!(read_versions(&fixture, details_id).await, vec!["v1"]);
+
+            // the versions folder is hidden in browsers
+            let (browser_id, load_future) = fixture.vault.repo_files_browsers_create(
+                fixture.repo_id.clone(),
+                &EncryptedPath("/".into()),
+                RepoFilesBrowserOptions { select_name: None },
+            );
+            load_future.await.unwrap();
+
+            let names: Vec<String> = fixture.vault.with_state(|state| {
+                repo_files_browsers::selectors::select_items(state, browser_id)
+                    .iter()
+                    .map(|item| item.file.decrypted_name().unwrap().0.clone())
+                    .collect()
+            });
+
+            assert_eq!(names, vec!["file.txt"]);
+
+            fixture.vault.repo_files_browsers_destroy(browser_id);
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_max_versions() {
+    with_repo(|fixture| {
+        async move {
+            enable_versions(&fixture, 2);
+
+            // uploads that overwrite the file also create versions
+            fixture.upload_file("/file.txt", "v1").await;
+            fixture.upload_file("/file.txt", "v2").await;
+            fixture.upload_file("/file.txt", "v3").await;
+            fixture.upload_file("/file.txt", "v4").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            assert_eq!(read_versions(&fixture, details_id).await, vec!["v3", "v2"]);
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_failed_overwrite_does_not_create_version() {
+    with_repo(|fixture| {
+        async move {
+            enable_versions(&fixture, 1);
+
+            fixture.upload_file("/file.txt", "v1").await;
+            fixture.upload_file("/file.txt", "v2").await;
+
+            // the overwrite precondition fails
+            let res = fixture
+                .vault
+                .repo_files_service
+                .clone()
+                .upload_file_reader(
+                    &fixture.repo_id,
+                    &EncryptedPath("/".into()),
+                    fixture.encrypt_filename("file.txt"),
+                    Box::pin(Cursor::new("v3".as_bytes().to_vec())),
+                    Some(2),
+                    RepoFilesUploadConflictResolution::Overwrite {
+                        if_remote_size: None,
+                        if_remote_modified: None,
+                        if_remote_hash: Some("00000000000000000000000000000000".into()),
+                    },
+                    None,
+                )
+                .await;
+            assert!(res.is_err());
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            assert_eq!(read_versions(&fixture, details_id).await, vec!["v1"]);
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_diff() {
+    with_repo(|fixture| {
+        async move {
+            enable_versions(&fixture, 5);
+
+            fixture.upload_file("/file.txt", "a\nb\nc\n").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            save(&fixture, details_id, "a\nB\nc\n").await;
+
+            let version = versions(&fixture, details_id).await.remove(0);
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_files_details_version_diff(details_id, &version.file.encrypted_path)
+                    .await
+                    .unwrap(),
+                vec![
+                    RepoVersionDiffLine {
+                        kind: RepoVersionDiffLineKind::Equal,
+                        old_line_number: Some(1),
+                        new_line_number: Some(1),
+                        content: "a".into(),
+                    },
+                    RepoVersionDiffLine {
+                        kind: RepoVersionDiffLineKind::Delete,
+                        old_line_number: Some(2),
+                        new_line_number: None,
+                        content: "b".into(),
+                    },
+                    RepoVersionDiffLine {
+                        kind: RepoVersionDiffLineKind::Insert,
+                        old_line_number: None,
+                        new_line_number: Some(2),
+                        content: "B".into(),
+                    },
+                    RepoVersionDiffLine {
+                        kind: RepoVersionDiffLineKind::Equal,
+                        old_line_number: Some(3),
+                        new_line_number: Some(3),
+                        content: "c".into(),
+                    },
+                ]
+            );
+
+            assert_eq!(
+                fixture
+                    .vault
+                    .repo_files_details_version_diff(details_id, &fixture.encrypt_path("/file.txt"))
+                    .await,
+                Err(RepoVersionsError::NotAVersion)
+            );
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_restore() {
+    with_repo(|fixture| {
+        async move {
+            enable_versions(&fixture, 1);
+
+            fixture.upload_file("/file.txt", "v1").await;
+
+            let details_id = create_details(&fixture, "/file.txt").await;
+
+            save(&fixture, details_id, "v2").await;
+
+            let version = versions(&fixture, details_id).await.remove(0);
+
+            fixture
+                .vault
+                .repo_files_details_version_restore(details_id, &version.file.encrypted_path)
+                .await
+                .unwrap();
+
+            let mut content = String::new();
+            fixture
+                .vault
+                .repo_files_get_file_reader(&fixture.repo_id, &fixture.encrypt_path("/file.txt"))
+                .unwrap()
+                .reader()
+                .await
+                .unwrap()
+                .reader
+                .read_to_string(&mut content)
+                .await
+                .unwrap();
+
+            assert_eq!(content, "v1");
+
+            // the overwritten content is kept as a version and the restored
+            // version is pruned
+            assert_eq!(read_versions(&fixture, details_id).await, vec!["v2"]);
+
+            destroy_details(&fixture, details_id).await;
+        }
+        .boxed()
+    });
+}
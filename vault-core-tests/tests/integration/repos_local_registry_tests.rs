@@ -0,0 +1,70 @@
+use futures::FutureExt;
+use similar_asserts::assert_eq;
+use vault_core::{
+    remote::{models, ApiErrorCode},
+    repos::local_repos_registry::LocalReposRegistry,
+    types::{MountId, RemotePath},
+};
+use vault_core_tests::helpers::with_vault;
+
+#[test]
+fn test_local_repos_registry() {
+    with_vault(|fixture| {
+        async move {
+            let registry = LocalReposRegistry::new(
+                fixture.vault.secure_storage_service.clone(),
+                fixture.vault.runtime.clone(),
+            );
+
+            assert_eq!(registry.get_repos().unwrap(), vec![]);
+
+            let repo = registry
+                .create_repo(models::VaultRepoCreate {
+                    mount_id: MountId("local".into()),
+                    path: RemotePath("/My safe box".into()),
+                    salt: Some("salt".into()),
+                    password_validator: "validator".into(),
+                    password_validator_encrypted: "encrypted".into(),
+                    ..Default::default()
+                })
+                .unwrap();
+
+            assert_eq!(repo.name, "My safe box");
+            assert_eq!(registry.get_repos().unwrap(), vec![repo.clone()]);
+
+            assert!(registry
+                .create_repo(models::VaultRepoCreate {
+                    mount_id: MountId("local".into()),
+                    path: RemotePath("/My safe box".into()),
+                    ..Default::default()
+                })
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::VaultReposAlreadyExists));
+
+            let updated_repo = registry
+                .update_repo(
+                    &repo.id,
+                    models::VaultRepoUpdate {
+                        path: RemotePath("/Renamed".into()),
+                        salt: None,
+                        password_validator: "validator2".into(),
+                        password_validator_encrypted: "encrypted2".into(),
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(updated_repo.name, "Renamed");
+            assert_eq!(updated_repo.added, repo.added);
+            assert_eq!(registry.get_repos().unwrap(), vec![updated_repo]);
+
+            registry.remove_repo(&repo.id).unwrap();
+
+            assert_eq!(registry.get_repos().unwrap(), vec![]);
+            assert!(registry
+                .remove_repo(&repo.id)
+                .unwrap_err()
+                .is_api_error_code(ApiErrorCode::NotFound));
+        }
+        .boxed()
+    });
+}
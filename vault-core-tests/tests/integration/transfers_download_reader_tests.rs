@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::{AsyncReadExt, FutureExt};
 use similar_asserts::assert_eq;
@@ -8,7 +8,8 @@ use vault_core::{
     files::file_category::FileCategory,
     store::NextId,
     transfers::state::{
-        Transfer, TransferDisplayName, TransferState, TransferType, TransfersState,
+        Transfer, TransferDisplayName, TransferState, TransferType, TransfersBandwidthLimits,
+        TransfersState,
     },
     types::TimeMillis,
 };
@@ -59,6 +60,47 @@ fn test_download_reader() {
     });
 }
 
+#[test]
+fn test_download_reader_bandwidth_limit() {
+    with_transfers(|fixture| {
+        async move {
+            fixture
+                .upload_file("/file.txt", &"a".repeat(300 * 1024))
+                .await;
+
+            fixture.vault.transfers_set_bandwidth_limits(
+                TransfersBandwidthLimits {
+                    download: Some(100 * 1024),
+                    ..Default::default()
+                },
+                None,
+            );
+
+            let reader = fixture
+                .vault
+                .repo_files_get_file_reader(&fixture.repo_id, &fixture.encrypt_path("/file.txt"))
+                .unwrap()
+                .reader()
+                .await
+                .unwrap();
+
+            let started = Instant::now();
+
+            let (_, mut reader) = fixture.vault.transfers_download_reader(reader);
+
+            let mut content = Vec::new();
+
+            reader.reader.read_to_end(&mut content).await.unwrap();
+
+            assert_eq!(content.len(), 300 * 1024);
+
+            // one second burst, the rest is limited to 100 KiB/s
+            assert!(started.elapsed() >= Duration::from_millis(1500));
+        }
+        .boxed()
+    });
+}
+
 #[test]
 fn test_download_reader_fail() {
     with_transfers(|fixture| {
@@ -193,6 +235,7 @@ fn expected_transfers_transferring(transfers: &TransfersState, attempts: usize)
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -239,6 +282,7 @@ fn expected_transfers_transferring_progress(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 4,
         failed_bytes: 0,
@@ -1086,6 +1086,7 @@ fn expected_transfers_waiting(transfers: &TransfersState) -> TransfersState {
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -1129,6 +1130,7 @@ fn expected_transfers_processing(transfers: &TransfersState, attempts: usize) ->
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -1172,6 +1174,7 @@ fn expected_transfers_transferring(transfers: &TransfersState, attempts: usize)
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -1218,6 +1221,7 @@ fn expected_transfers_transferring_progress(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 4,
         failed_bytes: 0,
@@ -1258,6 +1262,7 @@ fn expected_transfers_waiting_failed(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -1300,6 +1305,7 @@ fn expected_transfers_failed(transfers: &TransfersState, attempts: usize) -> Tra
         done_count: 0,
         failed_count: 1,
         retriable_count: 1,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 4,
@@ -1344,6 +1350,7 @@ fn expected_transfers_done_openable(transfers: &TransfersState, attempts: usize)
         done_count: 1,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 4,
         failed_bytes: 0,
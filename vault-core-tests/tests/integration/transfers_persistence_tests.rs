@@ -0,0 +1,196 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use axum::{http::StatusCode, response::IntoResponse};
+use futures::FutureExt;
+use similar_asserts::assert_eq;
+use vault_core::{
+    store,
+    transfers::{
+        persistence::{TransfersPersistenceService, TRANSFERS_STORAGE_KEY},
+        state::{
+            PersistedTransfers, PersistedUploadTransfer, TransferState, TransferUploadRelativeName,
+            TransferUploadSource,
+        },
+    },
+    types::EncryptedPath,
+};
+use vault_core_tests::{
+    fixtures::repo_fixture::RepoFixture,
+    helpers::{
+        transfers::{transfer_wait, with_transfers},
+        wait_for_async,
+    },
+};
+use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
+use vault_native::transfers::{
+    file_transfers_persistence::FileTransfersPersistence, file_uploadable::FileUploadable,
+};
+
+fn temp_dir() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("vault-transfers-{}", uuid::Uuid::new_v4()));
+
+    std::fs::create_dir_all(&path).unwrap();
+
+    path
+}
+
+fn create_persistence_service(fixture: &RepoFixture) -> Arc<TransfersPersistenceService> {
+    TransfersPersistenceService::new(
+        fixture.vault.transfers_service.clone(),
+        fixture.vault.store.clone(),
+        fixture.vault.runtime.clone(),
+        Box::new(FileTransfersPersistence::new(
+            fixture.vault.secure_storage_service.clone(),
+        )),
+    )
+}
+
+fn read_persisted(fixture: &RepoFixture) -> Option<PersistedTransfers> {
+    fixture
+        .vault
+        .secure_storage_service
+        .get(TRANSFERS_STORAGE_KEY)
+        .unwrap()
+}
+
+/// Transfers are saved in a store side effect so they have to be polled.
+async fn wait_persisted(
+    fixture: &RepoFixture,
+    f: impl Fn(&PersistedTransfers) -> bool + Send + Sync,
+) {
+    assert!(
+        wait_for_async(500, || read_persisted(fixture)
+            .filter(|persisted| f(persisted))
+            .is_some())
+        .await
+    );
+}
+
+#[test]
+fn test_persisted_upload_restored_after_unlock() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.store.mutate(|state, _, _, _| {
+                state.config.transfers.autoretry_attempts = 1;
+            });
+
+            let fail_uploads = Arc::new(AtomicBool::new(true));
+            let interceptor_fail_uploads = fail_uploads.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/put")
+                    && interceptor_fail_uploads.load(Ordering::SeqCst)
+                {
+                    InterceptorResult::Response(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+                } else {
+                    InterceptorResult::Ignore
+                }
+            }));
+
+            let dir = temp_dir();
+            let local_file_path = dir.join("file.txt");
+            std::fs::write(&local_file_path, "test").unwrap();
+
+            let persistence_service = create_persistence_service(&fixture);
+
+            let (_, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file.txt".into()),
+                Box::new(FileUploadable {
+                    path: local_file_path.clone(),
+                    cleanup: None,
+                }),
+            );
+            let future = create_future.await.unwrap();
+            fixture.vault.runtime.spawn(Box::pin(async move {
+                let _ = future.await;
+            }));
+
+            transfer_wait(fixture.vault.store.clone(), 1, |t| {
+                matches!(t.state, TransferState::Failed { .. })
+            })
+            .await;
+
+            wait_persisted(&fixture, |persisted| {
+                persisted
+                    .uploads
+                    .first()
+                    .filter(|upload| upload.error.is_some())
+                    .is_some()
+            })
+            .await;
+
+            let persisted = read_persisted(&fixture).unwrap();
+            assert_eq!(persisted.uploads.len(), 1);
+            assert_eq!(
+                persisted.uploads[0],
+                PersistedUploadTransfer {
+                    repo_id: fixture.repo_id.clone(),
+                    parent_path: EncryptedPath("/".into()),
+                    name: "file.txt".into(),
+                    source: TransferUploadSource::File {
+                        path: local_file_path.to_str().unwrap().to_owned(),
+                    },
+                    attempts: 1,
+                    error: persisted.uploads[0].error.clone(),
+                    paused: false,
+                }
+            );
+
+            drop(persistence_service);
+
+            // the new session starts with a locked repo and the secure storage
+            // of the previous session
+            let fixture1 = fixture.new_session();
+            fixture1
+                .vault
+                .secure_storage_service
+                .set(TRANSFERS_STORAGE_KEY, &persisted)
+                .unwrap();
+            fixture1.user_fixture.login();
+            fixture1.user_fixture.load().await;
+
+            let persistence_service1 = create_persistence_service(&fixture1);
+
+            assert!(fixture1
+                .vault
+                .with_state(|state| state.transfers.transfers.is_empty()));
+            assert_eq!(read_persisted(&fixture1), Some(persisted));
+
+            fixture1.unlock();
+
+            transfer_wait(fixture1.vault.store.clone(), 1, |t| {
+                t.is_persistent
+                    && t.attempts == 1
+                    && matches!(t.state, TransferState::Failed { .. })
+            })
+            .await;
+
+            fail_uploads.store(false, Ordering::SeqCst);
+
+            fixture1.vault.transfers_retry(1);
+
+            let wait_store = fixture1.vault.store.clone();
+            store::wait_for(wait_store.clone(), &[store::Event::Transfers], move |_| {
+                wait_store.with_state(|state| state.transfers.transfers.is_empty().then_some(()))
+            })
+            .await;
+
+            wait_persisted(&fixture1, |persisted| persisted.uploads.is_empty()).await;
+
+            drop(persistence_service1);
+            drop(fixture1);
+
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+        .boxed()
+    });
+}
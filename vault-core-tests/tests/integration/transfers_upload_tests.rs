@@ -1,24 +1,34 @@
 use std::{
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use axum::{http::StatusCode, response::IntoResponse};
-use futures::{future, io::Cursor, FutureExt};
+use axum::{
+    body::{self, HttpBody},
+    http::{Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::{channel::oneshot, future, io::Cursor, AsyncReadExt, FutureExt};
 use similar_asserts::assert_eq;
 
 use vault_core::{
     common::state::{BoxAsyncRead, SizeInfo},
     files::file_category::FileCategory,
+    repo_files::{
+        errors::UploadFileReaderError, selectors as repo_files_selectors,
+        service::RESUMABLE_UPLOAD_CHUNK_BLOCKS, state::RepoFilesUploadConflictResolution,
+    },
     store::{self, NextId},
     transfers::{
         errors::{TransferError, UploadableError},
+        selectors as transfers_selectors,
         state::{
             Transfer, TransferDisplayName, TransferState, TransferType, TransferUploadRelativeName,
-            TransferUploadRelativeNamePath, TransfersState, UploadTransfer,
+            TransferUploadRelativeNamePath, TransfersBandwidthLimits, TransfersState,
+            UploadTransfer,
         },
     },
     types::{DecryptedName, EncryptedPath, RepoFileId, TimeMillis},
@@ -26,10 +36,11 @@ use vault_core::{
 use vault_core_tests::{
     fixtures::repo_fixture::RepoFixture,
     helpers::transfers::{
-        capture_upload_uri, patch_transfer, transfer_abort_when, transfer_do_when,
+        capture_upload_uri, patch_transfer, transfer_abort_when, transfer_do_when, transfer_wait,
         transfers_recorder, uploaded_server_error, with_transfers, TestUploadable,
     },
 };
+use vault_crypto::constants::{BLOCK_DATA_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE};
 use vault_fake_remote::fake_remote::interceptor::InterceptorResult;
 use vault_store::test_helpers::StoreWatcher;
 
@@ -768,6 +779,258 @@ fn test_upload_concurrency() {
     });
 }
 
+#[test]
+fn test_upload_pause_resume_move_to_front() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.store.mutate(|state, _, _, _| {
+                state.config.transfers.upload_concurrency = 1;
+            });
+
+            let started_ids = Arc::new(Mutex::new(Vec::<u32>::new()));
+            let watcher_started_ids = started_ids.clone();
+            let watcher = StoreWatcher::watch_store(
+                fixture.vault.store.clone(),
+                &[store::Event::Transfers],
+                move |store, _| {
+                    store.with_state(|state| {
+                        let mut started_ids = watcher_started_ids.lock().unwrap();
+
+                        for transfer in state.transfers.transfers.values() {
+                            if matches!(transfer.state, TransferState::Processing)
+                                && !started_ids.contains(&transfer.id)
+                            {
+                                started_ids.push(transfer.id);
+                            }
+                        }
+                    })
+                },
+            );
+
+            // the first read never finishes so that the transfer can be
+            // paused while it is processing
+            let reader_counter = Arc::new(AtomicUsize::new(0));
+            let uploadable_reader_counter = reader_counter.clone();
+            let (reading_sender, reading_receiver) = oneshot::channel();
+            let reading_sender = Mutex::new(Some(reading_sender));
+
+            let (transfer_id_1, create_future_1) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file1.txt".into()),
+                Box::new(TestUploadable {
+                    size_fn: Box::new(move || future::ready(Ok(SizeInfo::Exact(5))).boxed()),
+                    is_retriable_fn: Box::new(|| future::ready(Ok(true)).boxed()),
+                    reader_fn: Box::new(move || {
+                        if uploadable_reader_counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                            if let Some(sender) = reading_sender.lock().unwrap().take() {
+                                let _ = sender.send(());
+                            }
+
+                            future::pending().boxed()
+                        } else {
+                            future::ready(Ok((
+                                Box::pin(Cursor::new("test1".as_bytes().to_vec())) as BoxAsyncRead,
+                                SizeInfo::Exact(5),
+                            )))
+                            .boxed()
+                        }
+                    }),
+                }),
+            );
+            let future_1 = create_future_1.await.unwrap();
+
+            let (transfer_id_2, create_future_2) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file2.txt".into()),
+                TestUploadable::string("test2"),
+            );
+            let future_2 = create_future_2.await.unwrap();
+
+            let (transfer_id_3, create_future_3) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file3.txt".into()),
+                TestUploadable::string("test3"),
+            );
+            let future_3 = create_future_3.await.unwrap();
+
+            fixture.vault.transfers_move_to_front(transfer_id_3);
+
+            assert_eq!(
+                fixture.vault.store.with_state(|state| {
+                    transfers_selectors::select_transfers(state)
+                        .into_iter()
+                        .map(|transfer| transfer.id)
+                        .collect::<Vec<_>>()
+                }),
+                vec![transfer_id_3, transfer_id_1, transfer_id_2]
+            );
+
+            reading_receiver.await.unwrap();
+
+            fixture.vault.transfers_pause(transfer_id_1);
+
+            future_3.await.unwrap();
+            future_2.await.unwrap();
+
+            fixture.vault.store.with_state(|state| {
+                let transfer = state.transfers.transfers.get(&transfer_id_1).unwrap();
+
+                assert_eq!(transfer.state, TransferState::Paused);
+                assert_eq!(transfer.order, 1);
+                assert_eq!(transfer.attempts, 0);
+                assert_eq!(state.transfers.paused_count, 1);
+                assert_eq!(state.transfers.transferring_count, 0);
+            });
+
+            fixture.vault.transfers_resume(transfer_id_1);
+
+            let res = future_1.await.unwrap();
+            assert_eq!(res.name.0, "file1.txt");
+            assert_eq!(reader_counter.load(Ordering::SeqCst), 2);
+
+            drop(watcher);
+
+            assert_eq!(
+                *started_ids.lock().unwrap(),
+                vec![transfer_id_1, transfer_id_3, transfer_id_2]
+            );
+            assert_eq!(
+                fixture
+                    .vault
+                    .store
+                    .with_state(|state| state.transfers.clone()),
+                TransfersState {
+                    next_id: NextId(4),
+                    ..Default::default()
+                }
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_pause_all_resume_all() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.store.mutate(|state, _, _, _| {
+                state.config.transfers.upload_concurrency = 1;
+            });
+
+            let (transfer_id_1, create_future_1) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file1.txt".into()),
+                Box::new(TestUploadable {
+                    size_fn: Box::new(move || future::ready(Ok(SizeInfo::Exact(5))).boxed()),
+                    is_retriable_fn: Box::new(|| future::ready(Ok(true)).boxed()),
+                    reader_fn: Box::new(move || future::pending().boxed()),
+                }),
+            );
+            let future_1 = create_future_1.await.unwrap();
+
+            let (transfer_id_2, create_future_2) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file2.txt".into()),
+                TestUploadable::string("test2"),
+            );
+            let future_2 = create_future_2.await.unwrap();
+
+            fixture.vault.transfers_pause_all();
+
+            fixture.vault.store.with_state(|state| {
+                for id in [transfer_id_1, transfer_id_2] {
+                    assert_eq!(
+                        state.transfers.transfers.get(&id).unwrap().state,
+                        TransferState::Paused
+                    );
+                }
+
+                assert_eq!(state.transfers.paused_count, 2);
+                assert_eq!(state.transfers.transferring_count, 0);
+                assert!(transfers_selectors::select_can_resume_all(state));
+                assert!(!transfers_selectors::select_can_pause_all(state));
+            });
+
+            // the paused upload does not start when the running upload is
+            // aborted
+            fixture.vault.transfers_abort(transfer_id_1);
+
+            assert!(matches!(future_1.await, Err(TransferError::Aborted)));
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            assert_eq!(
+                fixture.vault.store.with_state(|state| state
+                    .transfers
+                    .transfers
+                    .get(&transfer_id_2)
+                    .unwrap()
+                    .state
+                    .clone()),
+                TransferState::Paused
+            );
+
+            fixture.vault.transfers_resume_all();
+
+            let res = future_2.await.unwrap();
+            assert_eq!(res.name.0, "file2.txt");
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_bandwidth_limit() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.transfers_set_bandwidth_limits(
+                TransfersBandwidthLimits {
+                    total: Some(100 * 1024),
+                    ..Default::default()
+                },
+                None,
+            );
+
+            let started = Instant::now();
+
+            let (_, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file.txt".into()),
+                TestUploadable::bytes(vec![0; 300 * 1024]),
+            );
+            let future = create_future.await.unwrap();
+            future.await.unwrap();
+
+            // one second burst, the rest is limited to 100 KiB/s
+            assert!(started.elapsed() >= Duration::from_millis(1500));
+
+            fixture
+                .vault
+                .transfers_set_bandwidth_limits(Default::default(), None);
+
+            let started = Instant::now();
+
+            let (_, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file2.txt".into()),
+                TestUploadable::bytes(vec![0; 300 * 1024]),
+            );
+            let future = create_future.await.unwrap();
+            future.await.unwrap();
+
+            assert!(started.elapsed() < Duration::from_millis(1500));
+        }
+        .boxed()
+    });
+}
+
 #[test]
 fn test_upload_load_root_error() {
     with_transfers(|fixture| {
@@ -1276,6 +1539,7 @@ fn test_upload_abort_all() {
                             done_count: 0,
                             failed_count: 0,
                             retriable_count: 0,
+                            paused_count: 0,
                             total_count: 1,
                             done_bytes: 0,
                             failed_bytes: 0,
@@ -1331,6 +1595,7 @@ fn test_upload_abort_all() {
                             done_count: 0,
                             failed_count: 0,
                             retriable_count: 0,
+                            paused_count: 0,
                             total_count: 1,
                             done_bytes: 0,
                             failed_bytes: 0,
@@ -1418,6 +1683,7 @@ fn test_upload_abort_all() {
                             done_count: 0,
                             failed_count: 0,
                             retriable_count: 0,
+                            paused_count: 0,
                             total_count: 2,
                             done_bytes: 0,
                             failed_bytes: 0,
@@ -1511,6 +1777,7 @@ fn test_upload_abort_all() {
                             done_count: 0,
                             failed_count: 0,
                             retriable_count: 0,
+                            paused_count: 0,
                             total_count: 2,
                             done_bytes: 0,
                             failed_bytes: 0,
@@ -2049,6 +2316,531 @@ fn test_upload_retry_all() {
     });
 }
 
+#[test]
+fn test_upload_resumable() {
+    with_transfers(|fixture| {
+        async move {
+            let chunk_size = (RESUMABLE_UPLOAD_CHUNK_BLOCKS * BLOCK_DATA_SIZE as i64) as usize;
+            let data: Vec<u8> = (0..2 * chunk_size + 1000)
+                .map(|i| (i % 251) as u8)
+                .collect();
+
+            let chunk_offsets = Arc::new(Mutex::new(Vec::new()));
+            let interceptor_chunk_offsets = chunk_offsets.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.method == Method::PUT && parts.uri.path().contains("/files/put/sessions/")
+                {
+                    let mut chunk_offsets = interceptor_chunk_offsets.lock().unwrap();
+
+                    chunk_offsets.push(parts.uri.query().unwrap_or_default().to_owned());
+
+                    // fail the second chunk once
+                    if chunk_offsets.len() == 2 {
+                        return InterceptorResult::Response(
+                            StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                        );
+                    }
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            let resumed_transferred_bytes = Arc::new(Mutex::new(None));
+            let watcher_resumed_transferred_bytes = resumed_transferred_bytes.clone();
+            let watcher = StoreWatcher::watch_store(
+                fixture.vault.store.clone(),
+                &[store::Event::Transfers],
+                move |store, _| {
+                    store.with_state(|state| {
+                        if let Some(transfer) = state.transfers.transfers.get(&1) {
+                            let mut resumed = watcher_resumed_transferred_bytes.lock().unwrap();
+
+                            if transfer.attempts == 2
+                                && transfer.transferred_bytes > 0
+                                && resumed.is_none()
+                            {
+                                *resumed = Some(transfer.transferred_bytes);
+                            }
+                        }
+                    })
+                },
+            );
+
+            let (_, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file.bin".into()),
+                TestUploadable::bytes(data.clone()),
+            );
+            let future = create_future.await.unwrap();
+
+            let res = future.await.unwrap();
+            assert_eq!(res.name.0, "file.bin");
+
+            drop(watcher);
+
+            let first_chunk_end =
+                FILE_HEADER_SIZE + RESUMABLE_UPLOAD_CHUNK_BLOCKS as usize * BLOCK_SIZE;
+            let second_chunk_end =
+                first_chunk_end + RESUMABLE_UPLOAD_CHUNK_BLOCKS as usize * BLOCK_SIZE;
+
+            // the failed chunk is uploaded again, the first chunk is not
+            assert_eq!(
+                *chunk_offsets.lock().unwrap(),
+                vec![
+                    String::from("offset=0"),
+                    format!("offset={}", first_chunk_end),
+                    format!("offset={}", first_chunk_end),
+                    format!("offset={}", second_chunk_end),
+                ]
+            );
+            // the progress of the retry starts at the committed bytes
+            assert_eq!(
+                *resumed_transferred_bytes.lock().unwrap(),
+                Some(chunk_size as i64)
+            );
+
+            fixture
+                .vault
+                .repo_files_service
+                .load_files(&fixture.repo_id, &EncryptedPath("/".into()))
+                .await
+                .unwrap();
+
+            let mut reader = fixture
+                .vault
+                .repo_files_get_file_reader(&fixture.repo_id, &fixture.encrypt_path("/file.bin"))
+                .unwrap()
+                .reader()
+                .await
+                .unwrap();
+
+            let mut content = Vec::new();
+            reader.reader.read_to_end(&mut content).await.unwrap();
+
+            assert!(content == data);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_resumable_not_supported() {
+    with_transfers(|fixture| {
+        async move {
+            let chunk_size = (RESUMABLE_UPLOAD_CHUNK_BLOCKS * BLOCK_DATA_SIZE as i64) as usize;
+            let data: Vec<u8> = (0..chunk_size + 1000).map(|i| (i % 251) as u8).collect();
+
+            let session_counter = Arc::new(AtomicUsize::new(0));
+            let interceptor_session_counter = session_counter.clone();
+            let upload_counter = Arc::new(AtomicUsize::new(0));
+            let interceptor_upload_counter = upload_counter.clone();
+
+            // the server does not implement upload sessions
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/files/put/sessions") {
+                    interceptor_session_counter.fetch_add(1, Ordering::SeqCst);
+
+                    return InterceptorResult::Response(StatusCode::NOT_FOUND.into_response());
+                } else if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/put")
+                {
+                    interceptor_upload_counter.fetch_add(1, Ordering::SeqCst);
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            for name in ["file1.bin", "file2.bin"] {
+                let (_, create_future) = fixture.vault.transfers_upload(
+                    fixture.repo_id.clone(),
+                    EncryptedPath("/".into()),
+                    TransferUploadRelativeName(name.into()),
+                    TestUploadable::bytes(data.clone()),
+                );
+                let future = create_future.await.unwrap();
+
+                let res = future.await.unwrap();
+                assert_eq!(res.name.0, name);
+            }
+
+            // upload sessions are only tried once
+            assert_eq!(session_counter.load(Ordering::SeqCst), 1);
+            assert_eq!(upload_counter.load(Ordering::SeqCst), 2);
+
+            fixture
+                .vault
+                .repo_files_service
+                .load_files(&fixture.repo_id, &EncryptedPath("/".into()))
+                .await
+                .unwrap();
+
+            let mut reader = fixture
+                .vault
+                .repo_files_get_file_reader(&fixture.repo_id, &fixture.encrypt_path("/file1.bin"))
+                .unwrap()
+                .reader()
+                .await
+                .unwrap();
+
+            let mut content = Vec::new();
+            reader.reader.read_to_end(&mut content).await.unwrap();
+
+            assert!(content == data);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_verify() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.transfers_set_verify_uploads(true);
+
+            let upload_counter = Arc::new(AtomicUsize::new(0));
+            let interceptor_upload_counter = upload_counter.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/put")
+                {
+                    // the remote returns a wrong hash for the first upload
+                    if interceptor_upload_counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return InterceptorResult::AsyncTransform(Box::new(|response| {
+                            async move {
+                                let (parts, mut body) = response.into_parts();
+
+                                let mut bytes = Vec::new();
+
+                                while let Some(chunk) = body.data().await {
+                                    bytes.extend_from_slice(&chunk.unwrap());
+                                }
+
+                                let mut file: serde_json::Value =
+                                    serde_json::from_slice(&bytes).unwrap();
+                                file["hash"] = "00000000000000000000000000000000".into();
+
+                                Response::from_parts(
+                                    parts,
+                                    body::boxed(body::Full::from(
+                                        serde_json::to_vec(&file).unwrap(),
+                                    )),
+                                )
+                            }
+                            .boxed()
+                        }));
+                    }
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            let (_, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file.txt".into()),
+                TestUploadable::string("test"),
+            );
+            let future = create_future.await.unwrap();
+
+            // the file with the wrong hash is overwritten by the retried
+            // upload so it does not get a new name
+            let res = future.await.unwrap();
+            assert_eq!(res.name.0, "file.txt");
+            assert_eq!(upload_counter.load(Ordering::SeqCst), 2);
+
+            fixture
+                .vault
+                .repo_files_service
+                .load_files(&fixture.repo_id, &EncryptedPath("/".into()))
+                .await
+                .unwrap();
+
+            let tags = fixture.vault.store.with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(
+                        &fixture.repo_id,
+                        &fixture.encrypt_path("/file.txt"),
+                    ),
+                )
+                .unwrap()
+                .tags
+                .clone()
+            });
+
+            assert_eq!(
+                tags.unwrap().unwrap().hash,
+                Some(md5::compute("test").to_vec())
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_verify_hash_mismatch_retriable() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.transfers_set_verify_uploads(true);
+            fixture.vault.store.mutate(|state, _, _, _| {
+                state.config.transfers.autoretry_attempts = 0;
+            });
+
+            let corrupt_hash = Arc::new(AtomicBool::new(true));
+            let interceptor_corrupt_hash = corrupt_hash.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/put")
+                    && interceptor_corrupt_hash.load(Ordering::SeqCst)
+                {
+                    return InterceptorResult::AsyncTransform(Box::new(|response| {
+                        async move {
+                            let (parts, mut body) = response.into_parts();
+
+                            let mut bytes = Vec::new();
+
+                            while let Some(chunk) = body.data().await {
+                                bytes.extend_from_slice(&chunk.unwrap());
+                            }
+
+                            let mut file: serde_json::Value =
+                                serde_json::from_slice(&bytes).unwrap();
+                            file["hash"] = "00000000000000000000000000000000".into();
+
+                            Response::from_parts(
+                                parts,
+                                body::boxed(body::Full::from(serde_json::to_vec(&file).unwrap())),
+                            )
+                        }
+                        .boxed()
+                    }));
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            let (transfer_id, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file.txt".into()),
+                TestUploadable::string("test"),
+            );
+            let future = create_future.await.unwrap();
+
+            transfer_wait(fixture.vault.store.clone(), transfer_id, |t| {
+                matches!(t.state, TransferState::Failed { .. })
+            })
+            .await;
+
+            let transfer = fixture
+                .vault
+                .store
+                .with_state(|state| state.transfers.transfers.get(&transfer_id).unwrap().clone());
+            assert!(matches!(
+                transfer.state,
+                TransferState::Failed {
+                    error: TransferError::IOError(_)
+                }
+            ));
+            assert!(transfer.is_retriable);
+
+            // the file with the wrong hash is kept
+            fixture
+                .vault
+                .repo_files_service
+                .load_files(&fixture.repo_id, &EncryptedPath("/".into()))
+                .await
+                .unwrap();
+
+            let file_id = repo_files_selectors::get_file_id(
+                &fixture.repo_id,
+                &fixture.encrypt_path("/file.txt"),
+            );
+
+            assert!(fixture.vault.store.with_state(|state| {
+                repo_files_selectors::select_file(state, &file_id).is_some()
+            }));
+
+            corrupt_hash.store(false, Ordering::SeqCst);
+
+            fixture.vault.transfers_retry(transfer_id);
+
+            // the retried upload overwrites the file
+            let res = future.await.unwrap();
+            assert_eq!(res.name.0, "file.txt");
+
+            fixture
+                .vault
+                .repo_files_service
+                .load_files(&fixture.repo_id, &EncryptedPath("/".into()))
+                .await
+                .unwrap();
+
+            let (names, hash) = fixture.vault.store.with_state(|state| {
+                (
+                    repo_files_selectors::select_files(
+                        state,
+                        &fixture.repo_id,
+                        &EncryptedPath("/".into()),
+                    )
+                    .map(|file| file.decrypted_name().unwrap().0.clone())
+                    .collect::<Vec<_>>(),
+                    repo_files_selectors::select_file(state, &file_id)
+                        .unwrap()
+                        .tags
+                        .clone()
+                        .unwrap()
+                        .unwrap()
+                        .hash,
+                )
+            });
+            assert_eq!(names, vec!["file.txt"]);
+            assert_eq!(hash, Some(md5::compute("test").to_vec()));
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_verify_hash_mismatch_overwrite() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "old").await;
+
+            fixture.vault.transfers_set_verify_uploads(true);
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/content/api")
+                    && parts.uri.path().contains("/files/put")
+                {
+                    return InterceptorResult::AsyncTransform(Box::new(|response| {
+                        async move {
+                            let (parts, mut body) = response.into_parts();
+
+                            let mut bytes = Vec::new();
+
+                            while let Some(chunk) = body.data().await {
+                                bytes.extend_from_slice(&chunk.unwrap());
+                            }
+
+                            let mut file: serde_json::Value =
+                                serde_json::from_slice(&bytes).unwrap();
+                            file["hash"] = "00000000000000000000000000000000".into();
+
+                            Response::from_parts(
+                                parts,
+                                body::boxed(body::Full::from(serde_json::to_vec(&file).unwrap())),
+                            )
+                        }
+                        .boxed()
+                    }));
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            let res = fixture
+                .vault
+                .repo_files_service
+                .clone()
+                .upload_file_reader(
+                    &fixture.repo_id,
+                    &EncryptedPath("/".into()),
+                    fixture.encrypt_filename("file.txt"),
+                    Box::pin(Cursor::new("new".as_bytes().to_vec())),
+                    Some(3),
+                    RepoFilesUploadConflictResolution::Overwrite {
+                        if_remote_size: None,
+                        if_remote_modified: None,
+                        if_remote_hash: None,
+                    },
+                    None,
+                )
+                .await;
+            assert!(matches!(
+                res,
+                Err(UploadFileReaderError::HashMismatch { .. })
+            ));
+
+            // the overwritten file is not deleted
+            fixture
+                .vault
+                .repo_files_service
+                .load_files(&fixture.repo_id, &EncryptedPath("/".into()))
+                .await
+                .unwrap();
+
+            assert!(fixture.vault.store.with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(
+                        &fixture.repo_id,
+                        &fixture.encrypt_path("/file.txt"),
+                    ),
+                )
+                .is_some()
+            }));
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_upload_verify_resumable() {
+    with_transfers(|fixture| {
+        async move {
+            fixture.vault.transfers_set_verify_uploads(true);
+
+            let chunk_size = (RESUMABLE_UPLOAD_CHUNK_BLOCKS * BLOCK_DATA_SIZE as i64) as usize;
+            let data: Vec<u8> = (0..2 * chunk_size + 1000)
+                .map(|i| (i % 251) as u8)
+                .collect();
+
+            let chunk_counter = Arc::new(AtomicUsize::new(0));
+            let interceptor_chunk_counter = chunk_counter.clone();
+            let commit_counter = Arc::new(AtomicUsize::new(0));
+            let interceptor_commit_counter = commit_counter.clone();
+
+            fixture.fake_remote.intercept(Box::new(move |parts| {
+                if parts.uri.path().contains("/files/put/sessions/") {
+                    if parts.uri.path().ends_with("/commit") {
+                        interceptor_commit_counter.fetch_add(1, Ordering::SeqCst);
+                    } else if parts.method == Method::PUT {
+                        // fail the second chunk once
+                        if interceptor_chunk_counter.fetch_add(1, Ordering::SeqCst) == 1 {
+                            return InterceptorResult::Response(
+                                StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                            );
+                        }
+                    }
+                }
+
+                InterceptorResult::Ignore
+            }));
+
+            let (_, create_future) = fixture.vault.transfers_upload(
+                fixture.repo_id.clone(),
+                EncryptedPath("/".into()),
+                TransferUploadRelativeName("file.bin".into()),
+                TestUploadable::bytes(data.clone()),
+            );
+            let future = create_future.await.unwrap();
+
+            // the hash of the resumed upload includes the ciphertext uploaded
+            // in the first attempt
+            let res = future.await.unwrap();
+            assert_eq!(res.name.0, "file.bin");
+            assert_eq!(chunk_counter.load(Ordering::SeqCst), 4);
+            assert_eq!(commit_counter.load(Ordering::SeqCst), 1);
+        }
+        .boxed()
+    });
+}
+
 fn expected_transfers_waiting(fixture: &RepoFixture, transfers: &TransfersState) -> TransfersState {
     TransfersState {
         transfers: [(
@@ -2087,6 +2879,7 @@ fn expected_transfers_waiting(fixture: &RepoFixture, transfers: &TransfersState)
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -2142,6 +2935,7 @@ fn expected_transfers_processing(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -2197,6 +2991,7 @@ fn expected_transfers_transferring(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -2252,6 +3047,7 @@ fn expected_transfers_transferring_progress(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 4,
         failed_bytes: 0,
@@ -2301,6 +3097,7 @@ fn expected_transfers_waiting_failed(
         done_count: 0,
         failed_count: 0,
         retriable_count: 0,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 0,
@@ -2355,6 +3152,7 @@ fn expected_transfers_failed(
         done_count: 0,
         failed_count: 1,
         retriable_count: 1,
+        paused_count: 0,
         total_count: 1,
         done_bytes: 0,
         failed_bytes: 4,
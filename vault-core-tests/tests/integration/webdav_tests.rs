@@ -0,0 +1,323 @@
+use std::net::SocketAddr;
+
+use futures::FutureExt;
+use reqwest::{header, Method, StatusCode};
+use similar_asserts::assert_eq;
+use vault_core::repos::{selectors as repos_selectors, state::RepoTrashSettings};
+use vault_core_tests::{fixtures::repo_fixture::RepoFixture, helpers::with_repo};
+use vault_desktop_server::webdav::app::serve;
+
+struct WebDav {
+    client: reqwest::Client,
+    base_url: String,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for WebDav {
+    fn drop(&mut self) {
+        // the server holds a reference to the vault
+        self.server.abort();
+    }
+}
+
+impl WebDav {
+    async fn start(fixture: &RepoFixture) -> Self {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(serve(listener, fixture.vault.clone()));
+
+        let repo_name = fixture.vault.with_state(|state| {
+            repos_selectors::select_repo(state, &fixture.repo_id)
+                .unwrap()
+                .name
+                .0
+                .clone()
+        });
+
+        Self {
+            client: reqwest::Client::new(),
+            base_url: format!("http://{}/{}", addr, repo_name.replace(' ', "%20")),
+            server,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn request(&self, method: &str, path: &str) -> reqwest::RequestBuilder {
+        self.client.request(
+            Method::from_bytes(method.as_bytes()).unwrap(),
+            self.url(path),
+        )
+    }
+
+    async fn status(&self, method: &str, path: &str) -> StatusCode {
+        self.request(method, path).send().await.unwrap().status()
+    }
+
+    async fn get(&self, path: &str) -> (StatusCode, String) {
+        let res = self.request("GET", path).send().await.unwrap();
+        let status = res.status();
+
+        (status, res.text().await.unwrap())
+    }
+
+    async fn propfind(&self, path: &str) -> (StatusCode, String) {
+        let res = self
+            .request("PROPFIND", path)
+            .header("Depth", "1")
+            .send()
+            .await
+            .unwrap();
+        let status = res.status();
+
+        (status, res.text().await.unwrap())
+    }
+}
+
+#[test]
+fn test_webdav_propfind_get() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/dir/file & more.txt", "test").await;
+
+            let webdav = WebDav::start(&fixture).await;
+
+            let (status, xml) = webdav.propfind("/").await;
+            assert_eq!(status, StatusCode::MULTI_STATUS);
+            assert!(xml.contains("<D:href>/My%20safe%20box/dir/</D:href>"));
+            assert!(xml.contains("<D:resourcetype><D:collection/></D:resourcetype>"));
+
+            let (status, xml) = webdav.propfind("/dir").await;
+            assert_eq!(status, StatusCode::MULTI_STATUS);
+            assert!(xml.contains("<D:href>/My%20safe%20box/dir/file%20%26%20more.txt</D:href>"));
+            assert!(xml.contains("<D:displayname>file &amp; more.txt</D:displayname>"));
+            assert!(xml.contains("<D:getcontentlength>4</D:getcontentlength>"));
+
+            assert_eq!(
+                webdav.get("/dir/file%20%26%20more.txt").await,
+                (StatusCode::OK, String::from("test"))
+            );
+
+            let res = webdav
+                .request("GET", "/dir/file%20%26%20more.txt")
+                .header(header::RANGE, "bytes=1-2")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+            assert_eq!(
+                res.headers().get(header::CONTENT_RANGE).unwrap(),
+                "bytes 1-2/4"
+            );
+            assert_eq!(res.text().await.unwrap(), "es");
+
+            assert_eq!(
+                webdav.status("GET", "/missing.txt").await,
+                StatusCode::NOT_FOUND
+            );
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_webdav_put_mkcol_move_copy_delete() {
+    with_repo(|fixture| {
+        async move {
+            let webdav = WebDav::start(&fixture).await;
+
+            assert_eq!(webdav.status("MKCOL", "/dir").await, StatusCode::CREATED);
+            assert_eq!(
+                webdav.status("MKCOL", "/dir").await,
+                StatusCode::METHOD_NOT_ALLOWED
+            );
+            assert_eq!(
+                webdav.status("MKCOL", "/missing/dir").await,
+                StatusCode::CONFLICT
+            );
+
+            let put = |path: &'static str, body: &'static str| {
+                webdav.request("PUT", path).body(body).send()
+            };
+
+            assert_eq!(
+                put("/dir/file.txt", "v1").await.unwrap().status(),
+                StatusCode::CREATED
+            );
+            assert_eq!(
+                put("/dir/file.txt", "v2").await.unwrap().status(),
+                StatusCode::NO_CONTENT
+            );
+            assert_eq!(
+                put("/missing/file.txt", "v1").await.unwrap().status(),
+                StatusCode::CONFLICT
+            );
+            assert_eq!(
+                webdav.get("/dir/file.txt").await,
+                (StatusCode::OK, String::from("v2"))
+            );
+
+            let copy = webdav
+                .request("COPY", "/dir/file.txt")
+                .header("Destination", webdav.url("/copy.txt"))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(copy.status(), StatusCode::CREATED);
+            assert_eq!(
+                webdav.get("/copy.txt").await,
+                (StatusCode::OK, String::from("v2"))
+            );
+
+            let move_no_overwrite = webdav
+                .request("MOVE", "/dir/file.txt")
+                .header("Destination", webdav.url("/copy.txt"))
+                .header("Overwrite", "F")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(move_no_overwrite.status(), StatusCode::PRECONDITION_FAILED);
+
+            let move_rename = webdav
+                .request("MOVE", "/dir/file.txt")
+                .header("Destination", webdav.url("/renamed.txt"))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(move_rename.status(), StatusCode::CREATED);
+            assert_eq!(
+                webdav.get("/renamed.txt").await,
+                (StatusCode::OK, String::from("v2"))
+            );
+            assert_eq!(
+                webdav.status("GET", "/dir/file.txt").await,
+                StatusCode::NOT_FOUND
+            );
+
+            assert_eq!(
+                webdav.status("DELETE", "/renamed.txt").await,
+                StatusCode::NO_CONTENT
+            );
+            assert_eq!(
+                webdav.status("GET", "/renamed.txt").await,
+                StatusCode::NOT_FOUND
+            );
+            assert_eq!(
+                webdav.status("DELETE", "/renamed.txt").await,
+                StatusCode::NOT_FOUND
+            );
+            assert_eq!(webdav.status("DELETE", "/").await, StatusCode::FORBIDDEN);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_webdav_delete_moves_to_trash() {
+    with_repo(|fixture| {
+        async move {
+            fixture
+                .vault
+                .repos_set_trash_settings(
+                    &fixture.repo_id,
+                    RepoTrashSettings {
+                        enabled: true,
+                        retention_days: None,
+                    },
+                )
+                .unwrap();
+
+            fixture.upload_file("/dir/file.txt", "v1").await;
+            fixture.upload_file("/other/existing.txt", "old").await;
+
+            let webdav = WebDav::start(&fixture).await;
+
+            let move_other_dir = webdav
+                .request("MOVE", "/dir/file.txt")
+                .header("Destination", webdav.url("/other/existing.txt"))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(move_other_dir.status(), StatusCode::NO_CONTENT);
+            assert_eq!(
+                webdav.get("/other/existing.txt").await,
+                (StatusCode::OK, String::from("v1"))
+            );
+
+            assert_eq!(
+                webdav.status("DELETE", "/other/existing.txt").await,
+                StatusCode::NO_CONTENT
+            );
+            assert_eq!(
+                webdav.status("GET", "/other/existing.txt").await,
+                StatusCode::NOT_FOUND
+            );
+
+            // the overwritten and the deleted file are in the trash
+            let mut original_paths: Vec<String> = fixture
+                .vault
+                .repo_trash_list(&fixture.repo_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.original_decrypted_path.unwrap().0)
+                .collect();
+            original_paths.sort();
+            assert_eq!(
+                original_paths,
+                vec!["/other/existing.txt", "/other/existing.txt"]
+            );
+
+            // the trash folder is not exposed
+            let (status, xml) = webdav.propfind("/").await;
+            assert_eq!(status, StatusCode::MULTI_STATUS);
+            assert!(xml.contains("<D:href>/My%20safe%20box/other/</D:href>"));
+            assert!(!xml.contains(".trash"));
+            assert_eq!(webdav.propfind("/.trash").await.0, StatusCode::NOT_FOUND);
+        }
+        .boxed()
+    });
+}
+
+#[test]
+fn test_webdav_repo_locked() {
+    with_repo(|fixture| {
+        async move {
+            fixture.upload_file("/file.txt", "test").await;
+
+            let webdav = WebDav::start(&fixture).await;
+
+            assert_eq!(
+                webdav.get("/file.txt").await,
+                (StatusCode::OK, String::from("test"))
+            );
+
+            fixture.vault.repos_lock_repo(&fixture.repo_id).unwrap();
+
+            assert_eq!(webdav.status("GET", "/file.txt").await, StatusCode::LOCKED);
+            assert_eq!(webdav.status("PROPFIND", "/").await, StatusCode::LOCKED);
+
+            // locked repos are not listed
+            let root_url = webdav.base_url.rsplit_once('/').unwrap().0.to_owned();
+            let xml = webdav
+                .client
+                .request(
+                    Method::from_bytes(b"PROPFIND").unwrap(),
+                    format!("{}/", root_url),
+                )
+                .send()
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            assert!(!xml.contains("My%20safe%20box"));
+        }
+        .boxed()
+    });
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use super::{errors::AuthError, AuthProvider};
+
+/// Authorizes the requests of a vault without a Koofr account (e.g. with a
+/// local storage backend). The authorization is never sent to Koofr.
+pub struct LocalAuthProvider;
+
+#[async_trait]
+impl AuthProvider for LocalAuthProvider {
+    async fn get_authorization(&self, _force_refresh_token: bool) -> Result<String, AuthError> {
+        Ok(String::from("Local"))
+    }
+}
@@ -1,5 +1,6 @@
 pub mod auth_provider;
 pub mod errors;
+pub mod local_auth_provider;
 pub mod mock_auth_provider;
 
 pub use self::auth_provider::AuthProvider;
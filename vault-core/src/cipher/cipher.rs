@@ -1,5 +1,7 @@
 use std::fmt::Debug;
 
+use vault_crypto::name_cipher::NameCipherOptions;
+
 use crate::{
     types::{DecryptedName, DecryptedPath, EncryptedName, EncryptedPath},
     utils::name_utils,
@@ -22,47 +24,54 @@ impl Cipher {
         Self { cipher }
     }
 
+    pub fn name_options(&self) -> &NameCipherOptions {
+        self.cipher.name_options()
+    }
+
     pub fn encrypt_filename(&self, plaintext: &DecryptedName) -> EncryptedName {
         EncryptedName(self.cipher.encrypt_filename(&plaintext.0))
     }
 
+    pub fn encrypt_dir_name(&self, plaintext: &DecryptedName) -> EncryptedName {
+        EncryptedName(self.cipher.encrypt_dir_name(&plaintext.0))
+    }
+
+    /// Encrypts a path of a file. Use encrypt_dir_path for dirs.
     pub fn encrypt_path(&self, plaintext: &DecryptedPath) -> EncryptedPath {
         EncryptedPath(self.cipher.encrypt_path(&plaintext.0))
     }
 
+    pub fn encrypt_dir_path(&self, plaintext: &DecryptedPath) -> EncryptedPath {
+        EncryptedPath(self.cipher.encrypt_dir_path(&plaintext.0))
+    }
+
     pub fn decrypt_filename(
         &self,
         ciphertext: &EncryptedName,
     ) -> Result<DecryptedName, DecryptFilenameError> {
-        self.cipher
-            .decrypt_filename(&ciphertext.0)
-            .map(DecryptedName)
-            .map_err(DecryptFilenameError::DecryptFilenameError)
-            .and_then(|name| match name_utils::validate_name(&name.0) {
-                Ok(()) => Ok(name),
-                Err(err) => Err(DecryptFilenameError::InvalidNameError(err)),
-            })
+        validate_name(self.cipher.decrypt_filename(&ciphertext.0))
+    }
+
+    pub fn decrypt_dir_name(
+        &self,
+        ciphertext: &EncryptedName,
+    ) -> Result<DecryptedName, DecryptFilenameError> {
+        validate_name(self.cipher.decrypt_dir_name(&ciphertext.0))
     }
 
+    /// Decrypts a path of a file. Use decrypt_dir_path for dirs.
     pub fn decrypt_path(
         &self,
         ciphertext: &EncryptedPath,
     ) -> Result<DecryptedPath, DecryptFilenameError> {
-        self.cipher
-            .decrypt_path(&ciphertext.0)
-            .map(DecryptedPath)
-            .map_err(Into::into)
-            .and_then(|path| {
-                if path.0 != "/" {
-                    for name in path.0.split("/").skip(1) {
-                        if let Err(err) = name_utils::validate_name(&name) {
-                            return Err(DecryptFilenameError::InvalidNameError(err));
-                        }
-                    }
-                }
+        validate_path(self.cipher.decrypt_path(&ciphertext.0))
+    }
 
-                Ok(path)
-            })
+    pub fn decrypt_dir_path(
+        &self,
+        ciphertext: &EncryptedPath,
+    ) -> Result<DecryptedPath, DecryptFilenameError> {
+        validate_path(self.cipher.decrypt_dir_path(&ciphertext.0))
     }
 
     pub fn encrypt_reader_async<R>(
@@ -72,6 +81,24 @@ impl Cipher {
         self.cipher.encrypt_reader_async(reader)
     }
 
+    pub fn encrypt_reader_async_nonce<R>(
+        &self,
+        reader: R,
+        nonce: vault_crypto::nonce::Nonce,
+    ) -> vault_crypto::encrypt_reader::AsyncEncryptReader<R> {
+        self.cipher.encrypt_reader_async_nonce(reader, nonce)
+    }
+
+    pub fn encrypt_reader_async_at_block<R>(
+        &self,
+        reader: R,
+        nonce: vault_crypto::nonce::Nonce,
+        block: u64,
+    ) -> vault_crypto::encrypt_reader::AsyncEncryptReader<R> {
+        self.cipher
+            .encrypt_reader_async_at_block(reader, nonce, block)
+    }
+
     pub fn encrypt_reader_sync<R>(
         &self,
         reader: R,
@@ -100,6 +127,17 @@ impl Cipher {
         self.cipher.decrypt_reader_async(reader)
     }
 
+    pub fn decrypt_reader_async_at_block<R>(
+        &self,
+        reader: R,
+        nonce: vault_crypto::nonce::Nonce,
+        block: u64,
+        skip: usize,
+    ) -> vault_crypto::decrypt_reader::AsyncDecryptReader<R> {
+        self.cipher
+            .decrypt_reader_async_at_block(reader, nonce, block, skip)
+    }
+
     pub fn decrypt_reader_sync<R>(
         &self,
         reader: R,
@@ -119,3 +157,32 @@ impl Cipher {
         Ok(buf)
     }
 }
+
+fn validate_name(
+    name: Result<String, vault_crypto::errors::DecryptFilenameError>,
+) -> Result<DecryptedName, DecryptFilenameError> {
+    name.map(DecryptedName)
+        .map_err(DecryptFilenameError::DecryptFilenameError)
+        .and_then(|name| match name_utils::validate_name(&name.0) {
+            Ok(()) => Ok(name),
+            Err(err) => Err(DecryptFilenameError::InvalidNameError(err)),
+        })
+}
+
+fn validate_path(
+    path: Result<String, vault_crypto::errors::DecryptFilenameError>,
+) -> Result<DecryptedPath, DecryptFilenameError> {
+    path.map(DecryptedPath)
+        .map_err(Into::into)
+        .and_then(|path| {
+            if path.0 != "/" {
+                for name in path.0.split("/").skip(1) {
+                    if let Err(err) = name_utils::validate_name(name) {
+                        return Err(DecryptFilenameError::InvalidNameError(err));
+                    }
+                }
+            }
+
+            Ok(path)
+        })
+}
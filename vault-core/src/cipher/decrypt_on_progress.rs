@@ -4,6 +4,16 @@ use vault_crypto::data_cipher::decrypt_size;
 
 pub fn decrypt_on_progress(
     encrypted_on_progress: Box<dyn Fn(usize) + Send + Sync>,
+) -> Box<dyn Fn(usize) + Send + Sync> {
+    decrypt_on_progress_from(0, encrypted_on_progress)
+}
+
+/// decrypt_on_progress_from is used when the encrypted content is not
+/// transferred from the start (e.g. a resumed upload). Only the progress
+/// after encrypted_offset is reported.
+pub fn decrypt_on_progress_from(
+    encrypted_offset: i64,
+    encrypted_on_progress: Box<dyn Fn(usize) + Send + Sync>,
 ) -> Box<dyn Fn(usize) + Send + Sync> {
     struct OnProgressState {
         pub encrypted_bytes: i64,
@@ -11,8 +21,8 @@ pub fn decrypt_on_progress(
     }
 
     let state = Arc::new(Mutex::new(OnProgressState {
-        encrypted_bytes: 0,
-        decrypted_bytes: 0,
+        encrypted_bytes: encrypted_offset,
+        decrypted_bytes: decrypt_size(encrypted_offset).unwrap_or(0),
     }));
 
     Box::new(move |n: usize| {
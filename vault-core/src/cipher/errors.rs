@@ -32,6 +32,7 @@ impl UserError for DecryptFilenameError {
             Self::DecryptFilenameError(DecryptFilenameError::DecodeError(_)) => "Failed to decode file name".into(),
             Self::DecryptFilenameError(DecryptFilenameError::DecryptError) => "Failed to decrypt file name. Vault files can only be uploaded using Vault apps or rclone. If all your files have errors please check that you've used the correct salt.".into(),
             Self::DecryptFilenameError(DecryptFilenameError::UnicodeError(_)) => "File name is not a valid Unicode text".into(),
+            Self::DecryptFilenameError(DecryptFilenameError::NotEncryptedName) => "File name is not encrypted. Please check that the repo file name options match the rclone configuration.".into(),
             Self::InvalidNameError(err) => err.user_error(),
         }
     }
@@ -3,6 +3,7 @@ use std::time::Duration;
 use crate::{
     locale::{get_locale, BoxLocale},
     repos::state::{RepoAutoLock, RepoAutoLockAfter},
+    transfers::state::{TransfersBandwidthLimits, TransfersBandwidthSchedule},
 };
 
 pub struct LocaleConfig {
@@ -34,6 +35,11 @@ pub struct TransfersConfig {
     pub autoretry_attempts: usize,
     pub min_time_per_file: Duration,
     pub progress_throttle: Duration,
+    pub bandwidth_limits: TransfersBandwidthLimits,
+    pub bandwidth_schedule: Option<TransfersBandwidthSchedule>,
+    /// Compare the MD5 hash of the uploaded ciphertext with the remote file
+    /// hash and retry the upload on mismatch.
+    pub verify_uploads: bool,
 }
 
 impl Default for TransfersConfig {
@@ -44,6 +50,9 @@ impl Default for TransfersConfig {
             autoretry_attempts: 5,
             min_time_per_file: Duration::from_millis(500),
             progress_throttle: Duration::from_millis(100),
+            bandwidth_limits: TransfersBandwidthLimits::default(),
+            bandwidth_schedule: None,
+            verify_uploads: false,
         }
     }
 }
@@ -107,6 +116,13 @@ impl Default for RepoFilesTagsConfig {
 #[derive(Debug, Clone)]
 pub struct ConfigState {
     pub base_url: String,
+    /// User agent of this client. It is stored with file versions so that
+    /// users can see which client overwrote a file.
+    pub user_agent: Option<String>,
+    /// Repos and files are stored in a storage backend without a Koofr
+    /// account (e.g. a local directory). OAuth2, the user and the space usage
+    /// are not loaded.
+    pub local: bool,
     pub locale: LocaleConfig,
     pub transfers: TransfersConfig,
     pub eventstream: EventstreamConfig,
@@ -119,6 +135,8 @@ impl Default for ConfigState {
     fn default() -> Self {
         Self {
             base_url: String::from(""),
+            user_agent: None,
+            local: false,
             locale: LocaleConfig {
                 name: String::from("en"),
                 locale: get_locale("en").unwrap(),
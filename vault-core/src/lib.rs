@@ -22,22 +22,30 @@ pub mod repo_config_backup;
 pub mod repo_create;
 pub mod repo_files;
 pub mod repo_files_browsers;
+pub mod repo_files_cross_move;
 pub mod repo_files_details;
 pub mod repo_files_dir_pickers;
 pub mod repo_files_list;
 pub mod repo_files_move;
 pub mod repo_files_read;
 pub mod repo_files_tags;
+pub mod repo_fsck;
 pub mod repo_locker;
+pub mod repo_password_change;
 pub mod repo_remove;
+pub mod repo_search;
 pub mod repo_space_usage;
+pub mod repo_sync;
+pub mod repo_trash;
 pub mod repo_unlock;
+pub mod repo_versions;
 pub mod repos;
 pub mod runtime;
 pub mod secure_storage;
 pub mod selection;
 pub mod sort;
 pub mod space_usage;
+pub mod storage_backend;
 pub mod store;
 pub mod transfers;
 pub mod types;
@@ -1,4 +1,4 @@
-use crate::{common::state::Status, eventstream, store};
+use crate::{common::state::Status, eventstream, store, user::state::User};
 
 use super::state::AppVisibility;
 
@@ -15,6 +15,26 @@ pub fn on_logout(state: &mut store::State, notify: &store::Notify) {
     }
 }
 
+/// Vaults without a Koofr account are always logged in as a local user.
+pub fn local_loaded(state: &mut store::State, notify: &store::Notify) {
+    notify(store::Event::Auth);
+    notify(store::Event::User);
+
+    state.oauth2.status = Status::Loaded;
+
+    state.user.user = Some(User {
+        id: String::from("local"),
+        first_name: String::new(),
+        last_name: String::new(),
+        full_name: String::from("Local"),
+        email: String::new(),
+        // there is no profile picture to load
+        profile_picture_status: Status::Loaded,
+        profile_picture_bytes: None,
+    });
+    state.user.status = Status::Loaded;
+}
+
 pub fn app_visible(
     state: &mut store::State,
     notify: &store::Notify,
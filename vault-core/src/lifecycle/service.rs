@@ -72,12 +72,20 @@ impl LifecycleService {
     /// BoxFuture is used so that calling load immediately loads oauth2 service
     /// and then loads the rest asynchronously
     pub fn load(self: Arc<Self>) -> Result<BoxFuture<'static, Result<(), LoadError>>, LoadError> {
-        self.oauth2_service
-            .load()
-            .map_err(LoadError::OAuth2LoadError)?;
+        let local = self.store.with_state(|state| state.config.local);
+
+        if local {
+            self.store.mutate(|state, notify, _, _| {
+                mutations::local_loaded(state, notify);
+            });
+        } else {
+            self.oauth2_service
+                .load()
+                .map_err(LoadError::OAuth2LoadError)?;
+        }
 
         let load_future: BoxFuture<'static, Result<(), LoadError>> =
-            if self.oauth2_service.is_authenticated() {
+            if local || self.oauth2_service.is_authenticated() {
                 let on_login_self = self.clone();
 
                 async move {
@@ -97,6 +105,15 @@ impl LifecycleService {
     pub async fn on_login(&self) -> Result<(), OnLoginError> {
         self.eventstream_service.clone().connect();
 
+        // there is no Koofr user or space usage without a Koofr account
+        if self.store.with_state(|state| state.config.local) {
+            return self
+                .repos_service
+                .load_repos()
+                .await
+                .map_err(OnLoginError::LoadReposError);
+        }
+
         let user_future = self
             .user_service
             .load_user()
@@ -3,7 +3,10 @@ use std::collections::HashMap;
 use ini::Ini;
 use slug::slugify;
 use thiserror::Error;
-use vault_crypto::rclone_obscure::{obscure, reveal};
+use vault_crypto::{
+    name_cipher::NameCipherOptions,
+    rclone_obscure::{obscure, reveal},
+};
 
 use crate::utils::path_utils::normalize_path;
 
@@ -13,6 +16,7 @@ pub struct Config {
     pub path: String,
     pub password: String,
     pub salt: Option<String>,
+    pub name_options: NameCipherOptions,
 }
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -65,12 +69,54 @@ pub fn parse_config(config_str: &str) -> Result<Config, ParseConfigError> {
         })?),
         None => None,
     };
+    let name_options = parse_name_options(props)?;
 
     Ok(Config {
         name: section_name.map(|name| name.to_string()),
         path: path.to_owned(),
         password: password.to_string(),
         salt,
+        name_options,
+    })
+}
+
+fn parse_name_options(props: &HashMap<&str, &str>) -> Result<NameCipherOptions, ParseConfigError> {
+    let default = NameCipherOptions::default();
+
+    Ok(NameCipherOptions {
+        filename_encryption: match props.get("filename_encryption") {
+            Some(value) => {
+                value
+                    .parse()
+                    .map_err(|e: vault_crypto::errors::ParseNameOptionError| {
+                        ParseConfigError(e.to_string())
+                    })?
+            }
+            None => default.filename_encryption,
+        },
+        directory_name_encryption: match props.get("directory_name_encryption") {
+            Some(value) => match value.to_lowercase().as_str() {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(ParseConfigError(format!(
+                        "invalid directory_name_encryption value: {}",
+                        value
+                    )))
+                }
+            },
+            None => default.directory_name_encryption,
+        },
+        filename_encoding: match props.get("filename_encoding") {
+            Some(value) => {
+                value
+                    .parse()
+                    .map_err(|e: vault_crypto::errors::ParseNameOptionError| {
+                        ParseConfigError(e.to_string())
+                    })?
+            }
+            None => default.filename_encoding,
+        },
     })
 }
 
@@ -91,6 +137,31 @@ pub fn generate_config(config: &Config) -> String {
             i.with_section(Some(&section_name))
                 .set("password2", obscure(&salt).unwrap());
         }
+
+        // rclone defaults are not written so that the configs look the same as
+        // before name options were supported
+        let default = NameCipherOptions::default();
+
+        if config.name_options.filename_encryption != default.filename_encryption {
+            i.with_section(Some(&section_name)).set(
+                "filename_encryption",
+                config.name_options.filename_encryption.as_str(),
+            );
+        }
+
+        if config.name_options.directory_name_encryption != default.directory_name_encryption {
+            i.with_section(Some(&section_name)).set(
+                "directory_name_encryption",
+                config.name_options.directory_name_encryption.to_string(),
+            );
+        }
+
+        if config.name_options.filename_encoding != default.filename_encoding {
+            i.with_section(Some(&section_name)).set(
+                "filename_encoding",
+                config.name_options.filename_encoding.as_str(),
+            );
+        }
     }
 
     let mut out = Vec::new();
@@ -103,6 +174,7 @@ pub fn generate_config(config: &Config) -> String {
 #[cfg(test)]
 pub mod tests {
     use regex::Regex;
+    use vault_crypto::name_cipher::{FilenameEncoding, FilenameEncryption, NameCipherOptions};
 
     use super::{generate_config, parse_config, Config};
 
@@ -116,6 +188,7 @@ pub mod tests {
                 path: String::from("/Vault"),
                 password: String::from("testpassword"),
                 salt: Some(String::from("testsalt")),
+                name_options: NameCipherOptions::default(),
             }
         );
 
@@ -127,6 +200,7 @@ pub mod tests {
                 path: String::from("/Vault"),
                 password: String::from("testpassword"),
                 salt: None,
+                name_options: NameCipherOptions::default(),
             }
         );
 
@@ -138,6 +212,7 @@ pub mod tests {
                 path: String::from("/Vault"),
                 password: String::from("testpassword"),
                 salt: None,
+                name_options: NameCipherOptions::default(),
             }
         );
 
@@ -149,6 +224,7 @@ pub mod tests {
                 path: String::from("/"),
                 password: String::from("testpassword"),
                 salt: None,
+                name_options: NameCipherOptions::default(),
             }
         );
 
@@ -160,6 +236,7 @@ pub mod tests {
                 path: String::from("/"),
                 password: String::from("testpassword"),
                 salt: None,
+                name_options: NameCipherOptions::default(),
             }
         );
 
@@ -218,6 +295,7 @@ pub mod tests {
             path: String::from("/Vault"),
             password: String::from("testpassword"),
             salt: Some(String::from("testsalt")),
+            name_options: NameCipherOptions::default(),
         });
         let expected =
             "^\\[vault-name\\]\ntype=crypt\nremote=koofr:/Vault\npassword=.*\npassword2=.*\n$";
@@ -228,6 +306,7 @@ pub mod tests {
             path: String::from("/Vault"),
             password: String::from("testpassword"),
             salt: Some(String::from("testsalt")),
+            name_options: NameCipherOptions::default(),
         });
         let expected =
             "^\\[vault\\]\ntype=crypt\nremote=koofr:/Vault\npassword=.*\npassword2=.*\n$";
@@ -238,8 +317,80 @@ pub mod tests {
             path: String::from("/Vault"),
             password: String::from("testpassword"),
             salt: None,
+            name_options: NameCipherOptions::default(),
         });
         let expected = "^\\[vault-name\\]\ntype=crypt\nremote=koofr:/Vault\npassword=.*\n$";
         assert!(Regex::new(expected).unwrap().is_match(&config));
     }
+
+    #[test]
+    fn test_parse_config_name_options() {
+        assert_eq!(
+            parse_config("[vault-name]\ntype=crypt\nremote=koofr:/Vault\npassword=YMRulMcUAOo9raAGnYdie57EWnDFi_N283rEVw\nfilename_encryption=obfuscate\ndirectory_name_encryption=false\nfilename_encoding=base32768\n")
+                .unwrap()
+                .name_options,
+            NameCipherOptions {
+                filename_encryption: FilenameEncryption::Obfuscate,
+                directory_name_encryption: false,
+                filename_encoding: FilenameEncoding::Base32768,
+            }
+        );
+
+        assert_eq!(
+            parse_config("[vault-name]\ntype=crypt\nremote=koofr:/Vault\npassword=YMRulMcUAOo9raAGnYdie57EWnDFi_N283rEVw\nfilename_encryption=off\n")
+                .unwrap()
+                .name_options,
+            NameCipherOptions {
+                filename_encryption: FilenameEncryption::Off,
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            parse_config("[vault-name]\ntype=crypt\nremote=koofr:/Vault\npassword=YMRulMcUAOo9raAGnYdie57EWnDFi_N283rEVw\nfilename_encryption=rot13\n")
+                .unwrap_err()
+                .to_string(),
+            "parse config failed: invalid filename_encryption value: rot13"
+        );
+
+        assert_eq!(
+            parse_config("[vault-name]\ntype=crypt\nremote=koofr:/Vault\npassword=YMRulMcUAOo9raAGnYdie57EWnDFi_N283rEVw\ndirectory_name_encryption=maybe\n")
+                .unwrap_err()
+                .to_string(),
+            "parse config failed: invalid directory_name_encryption value: maybe"
+        );
+
+        assert_eq!(
+            parse_config("[vault-name]\ntype=crypt\nremote=koofr:/Vault\npassword=YMRulMcUAOo9raAGnYdie57EWnDFi_N283rEVw\nfilename_encoding=base16\n")
+                .unwrap_err()
+                .to_string(),
+            "parse config failed: invalid filename_encoding value: base16"
+        );
+    }
+
+    #[test]
+    fn test_generate_config_name_options() {
+        let config = Config {
+            name: Some(String::from("Vault name")),
+            path: String::from("/Vault"),
+            password: String::from("testpassword"),
+            salt: None,
+            name_options: NameCipherOptions {
+                filename_encryption: FilenameEncryption::Obfuscate,
+                directory_name_encryption: false,
+                filename_encoding: FilenameEncoding::Base64,
+            },
+        };
+        let generated = generate_config(&config);
+        let expected = "^\\[vault-name\\]\ntype=crypt\nremote=koofr:/Vault\npassword=.*\nfilename_encryption=obfuscate\ndirectory_name_encryption=false\nfilename_encoding=base64\n$";
+        assert!(Regex::new(expected).unwrap().is_match(&generated));
+
+        assert_eq!(
+            parse_config(&generated).unwrap(),
+            Config {
+                name: Some(String::from("vault-name")),
+                ..config
+            }
+        );
+    }
 }
@@ -64,7 +64,7 @@ pub enum RemoteError {
         code: ApiErrorCode,
         message: String,
         request_id: Option<String>,
-        extra: Option<HashMap<String, serde_json::Value>>,
+        extra: Option<Box<HashMap<String, serde_json::Value>>>,
         status_code: Option<u16>,
     },
     #[error("unexpected status: {status_code}: {message}")]
@@ -101,7 +101,7 @@ impl RemoteError {
             code: api_error_details.code.as_str().into(),
             message: api_error_details.message,
             request_id,
-            extra: api_error_details.extra,
+            extra: api_error_details.extra.map(Box::new),
             status_code,
         }
     }
@@ -112,6 +112,21 @@ impl RemoteError {
             _ => false,
         }
     }
+
+    /// Returns true if the server does not implement the endpoint. API errors
+    /// (e.g. a missing parent dir) have a request id, a 404 for an unknown
+    /// route does not.
+    pub fn is_not_implemented(&self) -> bool {
+        match &self {
+            Self::ApiError {
+                code, request_id, ..
+            } => code == &ApiErrorCode::NotFound && request_id.is_none(),
+            Self::UnexpectedStatus { status_code, .. } => {
+                *status_code == 405 || *status_code == 501
+            }
+            Self::HttpError(_) => false,
+        }
+    }
 }
 
 impl UserError for RemoteError {
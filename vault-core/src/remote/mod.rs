@@ -7,7 +7,7 @@ pub mod test_helpers;
 pub use self::{
     errors::{ApiErrorCode, RemoteError},
     remote::{
-        Remote, RemoteFileMoveConditions, RemoteFileReader, RemoteFileRemoveConditions,
-        RemoteFileUploadConflictResolution,
+        Remote, RemoteFileContentRange, RemoteFileMoveConditions, RemoteFileReader,
+        RemoteFileReaderRange, RemoteFileRemoveConditions, RemoteFileUploadConflictResolution,
     },
 };
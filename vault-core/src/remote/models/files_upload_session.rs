@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct FilesUploadSession {
+    pub id: String,
+    /// number of bytes that were stored by the server
+    pub offset: i64,
+}
@@ -13,6 +13,7 @@ pub mod files_move;
 pub mod files_move_result;
 pub mod files_rename;
 pub mod files_tags_set;
+pub mod files_upload_session;
 pub mod mount;
 pub mod places;
 pub mod shared;
@@ -20,6 +21,7 @@ pub mod shared_file;
 pub mod user;
 pub mod vault_repo;
 pub mod vault_repo_create;
+pub mod vault_repo_update;
 pub mod vault_repos_bundle;
 
 pub use self::{
@@ -28,7 +30,8 @@ pub use self::{
     files_copy_result::FilesCopyResult, files_file::FilesFile,
     files_folder_create::FilesFolderCreate, files_list_recursive_item::FilesListRecursiveItem,
     files_move::FilesMove, files_move_result::FilesMoveResult, files_rename::FilesRename,
-    files_tags_set::FilesTagsSet, mount::Mount, places::Places, shared::Shared,
-    shared_file::SharedFile, user::User, vault_repo::VaultRepo, vault_repo_create::VaultRepoCreate,
+    files_tags_set::FilesTagsSet, files_upload_session::FilesUploadSession, mount::Mount,
+    places::Places, shared::Shared, shared_file::SharedFile, user::User, vault_repo::VaultRepo,
+    vault_repo_create::VaultRepoCreate, vault_repo_update::VaultRepoUpdate,
     vault_repos_bundle::VaultReposBundle,
 };
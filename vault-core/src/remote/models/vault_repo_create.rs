@@ -12,4 +12,13 @@ pub struct VaultRepoCreate {
     pub password_validator: String,
     #[serde(rename = "passwordValidatorEncrypted")]
     pub password_validator_encrypted: String,
+    #[serde(rename = "filenameEncryption", skip_serializing_if = "Option::is_none")]
+    pub filename_encryption: Option<String>,
+    #[serde(
+        rename = "directoryNameEncryption",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub directory_name_encryption: Option<bool>,
+    #[serde(rename = "filenameEncoding", skip_serializing_if = "Option::is_none")]
+    pub filename_encoding: Option<String>,
 }
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::RemotePath;
+
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct VaultRepoUpdate {
+    pub path: RemotePath,
+    pub salt: Option<String>,
+    #[serde(rename = "passwordValidator")]
+    pub password_validator: String,
+    #[serde(rename = "passwordValidatorEncrypted")]
+    pub password_validator_encrypted: String,
+}
@@ -32,9 +32,27 @@ pub type ListRecursiveItemStream =
 pub struct RemoteFileReader {
     pub file: models::FilesFile,
     pub size: i64,
+    /// content_range is set if a range was requested and the response is
+    /// partial content
+    pub content_range: Option<RemoteFileContentRange>,
     pub reader: BoxAsyncRead,
 }
 
+/// Inclusive byte range, end None reads until the end of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFileReaderRange {
+    pub start: i64,
+    pub end: Option<i64>,
+}
+
+/// Inclusive byte range of partial content and the size of the whole file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFileContentRange {
+    pub start: i64,
+    pub end: i64,
+    pub size: i64,
+}
+
 #[derive(Debug, Clone)]
 pub enum RemoteFileUploadConflictResolution {
     Autorename,
@@ -299,6 +317,31 @@ impl Remote {
         res_json(res).await
     }
 
+    pub async fn update_vault_repo(
+        &self,
+        repo_id: &RepoId,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        let (req_body, req_headers) = req_json(&update);
+
+        let res = self
+            .request(HttpRequest {
+                method: String::from("PUT"),
+                url: format!("/api/v2.1/vault/repos/{}", repo_id.0),
+                headers: req_headers,
+                body: req_body,
+                is_retriable: true,
+                ..Default::default()
+            })
+            .await?;
+
+        if res.status_code() != 200 {
+            return res_error(res).await;
+        }
+
+        res_json(res).await
+    }
+
     pub async fn remove_vault_repo(&self, repo_id: &RepoId) -> Result<(), RemoteError> {
         let res = self
             .request(HttpRequest {
@@ -467,11 +510,25 @@ impl Remote {
         res_json(res).await
     }
 
+    /// Gets the file content. If range is set, the server can respond with
+    /// partial content (content_range is set) or with the whole file.
     pub async fn get_file_reader(
         &self,
         mount_id: &MountId,
         path: &RemotePath,
+        range: Option<&RemoteFileReaderRange>,
     ) -> Result<RemoteFileReader, RemoteError> {
+        let mut headers = HeaderMap::new();
+
+        if let Some(range) = range {
+            let range = match range.end {
+                Some(end) => format!("bytes={}-{}", range.start, end),
+                None => format!("bytes={}-", range.start),
+            };
+
+            headers.insert(header::RANGE, HeaderValue::from_str(&range).unwrap());
+        }
+
         let res = self
             .request(HttpRequest {
                 method: String::from("GET"),
@@ -480,14 +537,21 @@ impl Remote {
                     &mount_id.0,
                     encode(&path.0)
                 ),
+                headers,
                 is_retriable: true,
                 ..Default::default()
             })
             .await?;
 
-        if res.status_code() != 200 {
-            return res_error(res).await;
-        }
+        let content_range = match res.status_code() {
+            200 => None,
+            206 => Some(parse_content_range(
+                res.headers()
+                    .get("Content-Range")
+                    .and_then(|value| value.to_str().ok()),
+            )?),
+            _ => return res_error(res).await,
+        };
 
         let file_info_header = res.headers().get("X-File-Info").ok_or_else(|| {
             RemoteError::HttpError(HttpError::ResponseError(String::from(
@@ -519,6 +583,7 @@ impl Remote {
         Ok(RemoteFileReader {
             file,
             size,
+            content_range,
             reader: Box::pin(reader),
         })
     }
@@ -534,69 +599,145 @@ impl Remote {
         conflict_resolution: RemoteFileUploadConflictResolution,
         on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
     ) -> Result<models::FilesFile, RemoteError> {
-        let (
-            autorename,
-            overwrite,
-            overwrite_if_size,
-            overwrite_if_modified,
-            overwrite_if_hash,
-            overwrite_ignore_nonexisting,
-        ) = match conflict_resolution {
-            RemoteFileUploadConflictResolution::Autorename => {
-                (true, false, None, None, None, false)
-            }
-            RemoteFileUploadConflictResolution::Overwrite {
-                if_size,
-                if_modified,
-                if_hash,
-                ignore_nonexisting,
-            } => (
-                false,
-                true,
-                if_size,
-                if_modified,
-                if_hash,
-                ignore_nonexisting,
-            ),
-            RemoteFileUploadConflictResolution::Error => (false, false, None, None, None, false),
-        };
-
-        let mut url = format!(
-            "/content/api/v2.1/mounts/{}/files/put?path={}&filename={}&autorename={}&overwrite={}&info=true",
-            &mount_id.0,
-            encode(&parent_path.0),
-            encode(&name.0),
-            autorename,
-            overwrite,
+        let url = upload_url(
+            &format!("/content/api/v2.1/mounts/{}/files/put", &mount_id.0),
+            parent_path,
+            name,
+            size,
+            modified,
+            conflict_resolution,
         );
 
-        if let Some(size) = size {
-            url = format!("{}&size={}", url, size);
-        }
-        if let Some(modified) = modified {
-            url = format!("{}&modified={}", url, modified);
-        }
-        if let Some(overwrite_if_size) = overwrite_if_size {
-            url = format!("{}&overwriteIfSize={}", url, overwrite_if_size);
-        }
-        if let Some(overwrite_if_modified) = overwrite_if_modified {
-            url = format!("{}&overwriteIfModified={}", url, overwrite_if_modified);
-        }
-        if let Some(overwrite_if_hash) = overwrite_if_hash {
-            url = format!("{}&overwriteIfHash={}", url, overwrite_if_hash);
-        }
-        if overwrite_ignore_nonexisting {
-            url = format!("{}&overwriteIgnoreNonexisting=", url);
+        let res = self
+            .request(HttpRequest {
+                method: String::from("POST"),
+                url,
+                headers: HeaderMap::new(),
+                body: Some(HttpRequestBody::Reader(reader)),
+                on_body_progress: on_progress,
+                is_retriable: false,
+            })
+            .await?;
+
+        if res.status_code() != 200 {
+            return res_error(res).await;
         }
 
+        res_json(res).await
+    }
+
+    /// Starts a resumable upload. The content is uploaded in chunks with
+    /// upload_session_chunk and the file is created with commit_upload_session.
+    pub async fn create_upload_session(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        size: Option<i64>,
+        modified: Option<i64>,
+        conflict_resolution: RemoteFileUploadConflictResolution,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        let url = upload_url(
+            &format!(
+                "/content/api/v2.1/mounts/{}/files/put/sessions",
+                &mount_id.0
+            ),
+            parent_path,
+            name,
+            size,
+            modified,
+            conflict_resolution,
+        );
+
         let res = self
             .request(HttpRequest {
                 method: String::from("POST"),
                 url,
+                is_retriable: true,
+                ..Default::default()
+            })
+            .await?;
+
+        if res.status_code() != 200 {
+            return res_error(res).await;
+        }
+
+        res_json(res).await
+    }
+
+    pub async fn get_upload_session(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        let res = self
+            .request(HttpRequest {
+                method: String::from("GET"),
+                url: format!(
+                    "/content/api/v2.1/mounts/{}/files/put/sessions/{}",
+                    &mount_id.0,
+                    encode(session_id)
+                ),
+                is_retriable: true,
+                ..Default::default()
+            })
+            .await?;
+
+        if res.status_code() != 200 {
+            return res_error(res).await;
+        }
+
+        res_json(res).await
+    }
+
+    /// Uploads a chunk at offset. offset must not be greater than the session
+    /// offset, the server discards the stored bytes after offset. The chunk is
+    /// only stored if it is received completely.
+    pub async fn upload_session_chunk(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+        offset: i64,
+        reader: BoxAsyncRead,
+        on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        let res = self
+            .request(HttpRequest {
+                method: String::from("PUT"),
+                url: format!(
+                    "/content/api/v2.1/mounts/{}/files/put/sessions/{}?offset={}",
+                    &mount_id.0,
+                    encode(session_id),
+                    offset
+                ),
                 headers: HeaderMap::new(),
                 body: Some(HttpRequestBody::Reader(reader)),
                 on_body_progress: on_progress,
                 is_retriable: false,
+            })
+            .await?;
+
+        if res.status_code() != 200 {
+            return res_error(res).await;
+        }
+
+        res_json(res).await
+    }
+
+    pub async fn commit_upload_session(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+    ) -> Result<models::FilesFile, RemoteError> {
+        let res = self
+            .request(HttpRequest {
+                method: String::from("POST"),
+                url: format!(
+                    "/content/api/v2.1/mounts/{}/files/put/sessions/{}/commit",
+                    &mount_id.0,
+                    encode(session_id)
+                ),
+                is_retriable: false,
                 ..Default::default()
             })
             .await?;
@@ -810,6 +951,70 @@ impl Remote {
     }
 }
 
+fn upload_url(
+    base_url: &str,
+    parent_path: &RemotePath,
+    name: &RemoteName,
+    size: Option<i64>,
+    modified: Option<i64>,
+    conflict_resolution: RemoteFileUploadConflictResolution,
+) -> String {
+    let (
+        autorename,
+        overwrite,
+        overwrite_if_size,
+        overwrite_if_modified,
+        overwrite_if_hash,
+        overwrite_ignore_nonexisting,
+    ) = match conflict_resolution {
+        RemoteFileUploadConflictResolution::Autorename => (true, false, None, None, None, false),
+        RemoteFileUploadConflictResolution::Overwrite {
+            if_size,
+            if_modified,
+            if_hash,
+            ignore_nonexisting,
+        } => (
+            false,
+            true,
+            if_size,
+            if_modified,
+            if_hash,
+            ignore_nonexisting,
+        ),
+        RemoteFileUploadConflictResolution::Error => (false, false, None, None, None, false),
+    };
+
+    let mut url = format!(
+        "{}?path={}&filename={}&autorename={}&overwrite={}&info=true",
+        base_url,
+        encode(&parent_path.0),
+        encode(&name.0),
+        autorename,
+        overwrite,
+    );
+
+    if let Some(size) = size {
+        url = format!("{}&size={}", url, size);
+    }
+    if let Some(modified) = modified {
+        url = format!("{}&modified={}", url, modified);
+    }
+    if let Some(overwrite_if_size) = overwrite_if_size {
+        url = format!("{}&overwriteIfSize={}", url, overwrite_if_size);
+    }
+    if let Some(overwrite_if_modified) = overwrite_if_modified {
+        url = format!("{}&overwriteIfModified={}", url, overwrite_if_modified);
+    }
+    if let Some(overwrite_if_hash) = overwrite_if_hash {
+        url = format!("{}&overwriteIfHash={}", url, overwrite_if_hash);
+    }
+    if overwrite_ignore_nonexisting {
+        url = format!("{}&overwriteIgnoreNonexisting=", url);
+    }
+
+    url
+}
+
 pub fn req_json<T>(value: &T) -> (Option<HttpRequestBody>, HeaderMap)
 where
     T: ?Sized + Serialize,
@@ -829,6 +1034,28 @@ async fn res_bytes(res: BoxHttpResponse) -> Result<Vec<u8>, RemoteError> {
     res.bytes().await.map_err(RemoteError::HttpError)
 }
 
+/// Parses a Content-Range header value (e.g. bytes 0-99/1000)
+fn parse_content_range(value: Option<&str>) -> Result<RemoteFileContentRange, RemoteError> {
+    let invalid = || {
+        RemoteError::HttpError(HttpError::ResponseError(format!(
+            "Invalid response header Content-Range: {:?}",
+            value
+        )))
+    };
+
+    let (range, size) = value
+        .and_then(|value| value.strip_prefix("bytes "))
+        .and_then(|value| value.split_once('/'))
+        .ok_or_else(invalid)?;
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+
+    Ok(RemoteFileContentRange {
+        start: start.parse().map_err(|_| invalid())?,
+        end: end.parse().map_err(|_| invalid())?,
+        size: size.parse().map_err(|_| invalid())?,
+    })
+}
+
 async fn res_error<T>(res: BoxHttpResponse) -> Result<T, RemoteError> {
     let status_code = res.status_code();
 
@@ -59,6 +59,9 @@ pub fn create_repo(repo_id: &str, mount_id: &str, path: &str) -> models::VaultRe
         salt: Some("salt".into()),
         password_validator: String::from("a8668309-60f9-40f1-9a4c-0d1de0ff5852"),
         password_validator_encrypted: String::from("v2:UkNMT05FAADWjQahYq7E1ij2zegBBHbFuDbGIHAvdpym3P4eW2CPQcWhcTuAz4YGLAwRQzj2PoP4vwS2hAEwFwqMlFsWTgLMQ2ONzdNJK4d3kaVw"),
+        filename_encryption: None,
+        directory_name_encryption: None,
+        filename_encoding: None,
         added: 1,
     }
 }
@@ -4,9 +4,11 @@ use crate::{
     common::state::BoxAsyncRead,
     dialogs,
     remote::{
+        models,
         remote::{ListRecursiveItemStream, RemoteFileTagsSetConditions},
-        Remote, RemoteError, RemoteFileUploadConflictResolution,
+        RemoteError, RemoteFileReaderRange, RemoteFileUploadConflictResolution,
     },
+    storage_backend::{BoxStorageBackend, StorageBackendUploadOptions},
     store,
     types::{MountId, RemoteFileId, RemoteName, RemotePath},
     utils::remote_path_utils,
@@ -19,7 +21,7 @@ use super::{
 };
 
 pub struct RemoteFilesService {
-    remote: Arc<Remote>,
+    storage_backend: Arc<BoxStorageBackend>,
     dialogs_service: Arc<dialogs::DialogsService>,
     store: Arc<store::Store>,
     eventstream_events_mutation_subscription_id: u32,
@@ -27,14 +29,14 @@ pub struct RemoteFilesService {
 
 impl RemoteFilesService {
     pub fn new(
-        remote: Arc<Remote>,
+        storage_backend: Arc<BoxStorageBackend>,
         dialogs_service: Arc<dialogs::DialogsService>,
         store: Arc<store::Store>,
     ) -> Self {
         let eventstream_events_mutation_subscription_id = store.get_next_id();
 
         let remote_files_service = Self {
-            remote,
+            storage_backend,
             dialogs_service,
             store: store.clone(),
             eventstream_events_mutation_subscription_id,
@@ -57,7 +59,7 @@ impl RemoteFilesService {
     }
 
     pub async fn load_places(&self) -> Result<(), RemoteError> {
-        let mounts = self.remote.get_places().await?;
+        let mounts = self.storage_backend.get_places().await?;
 
         self.store.mutate(|state, notify, _, _| {
             notify(store::Event::RemoteFiles);
@@ -69,7 +71,7 @@ impl RemoteFilesService {
     }
 
     pub async fn load_bookmarks(&self) -> Result<(), RemoteError> {
-        let bookmarks = self.remote.get_bookmarks().await?;
+        let bookmarks = self.storage_backend.get_bookmarks().await?;
 
         self.store.mutate(|state, notify, _, _| {
             notify(store::Event::RemoteFiles);
@@ -81,7 +83,7 @@ impl RemoteFilesService {
     }
 
     pub async fn load_shared(&self) -> Result<(), RemoteError> {
-        let shared_files = self.remote.get_shared().await?;
+        let shared_files = self.storage_backend.get_shared().await?;
 
         self.store.mutate(|state, notify, _, _| {
             notify(store::Event::RemoteFiles);
@@ -93,7 +95,7 @@ impl RemoteFilesService {
     }
 
     pub async fn load_mount(&self, mount_id: &MountId) -> Result<MountId, RemoteError> {
-        let mount = self.remote.get_mount(mount_id).await?;
+        let mount = self.storage_backend.get_mount(mount_id).await?;
         // mount_id parameter can be "primary" but we want an actual id
         let mount_id = mount.id.clone();
 
@@ -111,7 +113,7 @@ impl RemoteFilesService {
         mount_id: &MountId,
         path: &RemotePath,
     ) -> Result<(), RemoteError> {
-        let bundle = self.remote.get_bundle(mount_id, path).await?;
+        let bundle = self.storage_backend.get_bundle(mount_id, path).await?;
 
         self.store
             .mutate(|state, notify, mutation_state, mutation_notify| {
@@ -135,7 +137,7 @@ impl RemoteFilesService {
         mount_id: &MountId,
         path: &RemotePath,
     ) -> Result<(), RemoteError> {
-        let file = self.remote.get_file(mount_id, path).await?;
+        let file = self.storage_backend.get_file(mount_id, path).await?;
 
         self.store
             .mutate(|state, notify, mutation_state, mutation_notify| {
@@ -158,8 +160,12 @@ impl RemoteFilesService {
         &self,
         mount_id: &MountId,
         path: &RemotePath,
+        range: Option<&RemoteFileReaderRange>,
     ) -> Result<RemoteFilesFileReader, RemoteError> {
-        let reader = self.remote.get_file_reader(&mount_id, &path).await?;
+        let reader = self
+            .storage_backend
+            .get_file_reader(mount_id, path, range)
+            .await?;
 
         Ok(RemoteFilesFileReader {
             file: mutations::files_file_to_remote_file(
@@ -169,6 +175,7 @@ impl RemoteFilesService {
                 reader.file,
             ),
             size: reader.size,
+            content_range: reader.content_range,
             reader: reader.reader,
         })
     }
@@ -178,7 +185,9 @@ impl RemoteFilesService {
         mount_id: &MountId,
         path: &RemotePath,
     ) -> Result<ListRecursiveItemStream, RemoteError> {
-        self.remote.get_list_recursive(mount_id, path).await
+        self.storage_backend
+            .get_list_recursive(mount_id, path)
+            .await
     }
 
     pub async fn upload_file_reader(
@@ -192,19 +201,86 @@ impl RemoteFilesService {
         on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
     ) -> Result<(RemoteFileId, RemoteFile), RemoteError> {
         let file = self
-            .remote
+            .storage_backend
             .upload_file_reader(
                 mount_id,
                 parent_path,
                 name,
                 reader,
-                size,
-                None,
-                conflict_resolution,
-                on_progress,
+                StorageBackendUploadOptions {
+                    size,
+                    modified: None,
+                    conflict_resolution,
+                    on_progress,
+                },
             )
             .await?;
 
+        Ok(self.file_uploaded(mount_id, parent_path, file))
+    }
+
+    /// Returns false if the storage backend does not support resumable
+    /// uploads.
+    pub fn has_upload_sessions(&self) -> bool {
+        self.storage_backend.has_upload_sessions()
+    }
+
+    pub async fn create_upload_session(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        size: Option<i64>,
+        conflict_resolution: RemoteFileUploadConflictResolution,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        self.storage_backend
+            .create_upload_session(mount_id, parent_path, name, size, None, conflict_resolution)
+            .await
+    }
+
+    pub async fn get_upload_session(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        self.storage_backend
+            .get_upload_session(mount_id, session_id)
+            .await
+    }
+
+    pub async fn upload_session_chunk(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+        offset: i64,
+        reader: BoxAsyncRead,
+        on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        self.storage_backend
+            .upload_session_chunk(mount_id, session_id, offset, reader, on_progress)
+            .await
+    }
+
+    pub async fn commit_upload_session(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        session_id: &str,
+    ) -> Result<(RemoteFileId, RemoteFile), RemoteError> {
+        let file = self
+            .storage_backend
+            .commit_upload_session(mount_id, session_id)
+            .await?;
+
+        Ok(self.file_uploaded(mount_id, parent_path, file))
+    }
+
+    fn file_uploaded(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        file: models::FilesFile,
+    ) -> (RemoteFileId, RemoteFile) {
         let path = remote_path_utils::join_path_name(parent_path, &file.name);
 
         self.store
@@ -229,7 +305,7 @@ impl RemoteFilesService {
             file,
         );
 
-        Ok((file_id, file))
+        (file_id, file)
     }
 
     pub async fn delete_file(
@@ -237,7 +313,7 @@ impl RemoteFilesService {
         mount_id: &MountId,
         path: &RemotePath,
     ) -> Result<(), RemoteError> {
-        self.remote
+        self.storage_backend
             .delete_file(mount_id, path, Default::default())
             .await?;
 
@@ -309,7 +385,7 @@ impl RemoteFilesService {
     ) -> Result<(), RemoteError> {
         let path = remote_path_utils::join_path_name(parent_path, &name);
 
-        self.remote
+        self.storage_backend
             .create_dir(mount_id, parent_path, name.clone())
             .await?;
 
@@ -335,7 +411,7 @@ impl RemoteFilesService {
         to_mount_id: &MountId,
         to_path: &RemotePath,
     ) -> Result<(), RemoteError> {
-        self.remote
+        self.storage_backend
             .copy_file(mount_id, path, to_mount_id, to_path)
             .await?;
 
@@ -351,7 +427,7 @@ impl RemoteFilesService {
         to_mount_id: &MountId,
         to_path: &RemotePath,
     ) -> Result<(), RemoteError> {
-        self.remote
+        self.storage_backend
             .move_file(mount_id, path, to_mount_id, to_path, Default::default())
             .await?;
 
@@ -366,7 +442,9 @@ impl RemoteFilesService {
         path: &RemotePath,
         new_name: RemoteName,
     ) -> Result<(), RemoteError> {
-        self.remote.rename_file(mount_id, path, new_name).await?;
+        self.storage_backend
+            .rename_file(mount_id, path, new_name)
+            .await?;
 
         // state is updated by eventstream event
 
@@ -380,7 +458,7 @@ impl RemoteFilesService {
         tags: HashMap<String, Vec<String>>,
         conditions: RemoteFileTagsSetConditions,
     ) -> Result<(), RemoteError> {
-        self.remote
+        self.storage_backend
             .file_set_tags(mount_id, path, tags.clone(), conditions.clone())
             .await?;
 
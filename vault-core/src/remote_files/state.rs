@@ -7,7 +7,7 @@ use crate::{
     common::state::BoxAsyncRead,
     dir_pickers::state::DirPickerItemType,
     files::{file_category::FileCategory, file_icon::FileIconAttrs},
-    remote::models,
+    remote::{models, RemoteFileContentRange},
     sort::state::SortDirection,
     types::{MountId, RemoteFileId, RemoteName, RemoteNameLower, RemotePath},
 };
@@ -247,6 +247,7 @@ impl RemoteFile {
 pub struct RemoteFilesFileReader {
     pub file: RemoteFile,
     pub size: i64,
+    pub content_range: Option<RemoteFileContentRange>,
     pub reader: BoxAsyncRead,
 }
 
@@ -27,6 +27,7 @@ pub fn create(state: &mut store::State, notify: &store::Notify, salt: String) ->
         location_dir_picker_id: None,
         password: String::from(""),
         salt: Some(salt),
+        name_options: Default::default(),
         fill_from_rclone_config_error: None,
         create_repo_status: Status::Initial,
     });
@@ -198,6 +199,7 @@ pub fn fill_from_rclone_config(
                 path,
                 password,
                 salt,
+                name_options,
                 ..
             } = config;
 
@@ -210,6 +212,7 @@ pub fn fill_from_rclone_config(
 
             form.password = password;
             form.salt = salt;
+            form.name_options = name_options;
 
             form.fill_from_rclone_config_error = None;
         }
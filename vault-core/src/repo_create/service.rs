@@ -263,6 +263,7 @@ impl RepoCreateService {
                 &location.path,
                 &form.password,
                 form.salt.as_deref(),
+                form.name_options,
             )
             .await;
 
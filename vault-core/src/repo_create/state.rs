@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use vault_crypto::name_cipher::NameCipherOptions;
+
 use crate::{
     common::state::Status,
     rclone,
@@ -19,6 +21,7 @@ pub struct RepoCreateForm {
     pub location_dir_picker_id: Option<u32>,
     pub password: String,
     pub salt: Option<String>,
+    pub name_options: NameCipherOptions,
     pub fill_from_rclone_config_error: Option<rclone::config::ParseConfigError>,
     pub create_repo_status: Status<CreateRepoError>,
 }
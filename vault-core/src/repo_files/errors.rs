@@ -3,6 +3,7 @@ use thiserror::Error;
 use crate::{
     cipher::errors::DecryptFilenameError,
     remote::{ApiErrorCode, RemoteError},
+    repo_files_tags::errors::SetTagsError,
     repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
     user_error::UserError,
 };
@@ -67,6 +68,16 @@ pub enum LoadFileError {
     RemoteError(#[from] RemoteError),
 }
 
+impl UserError for LoadFileError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::RemoteError(err) => err.user_error(),
+        }
+    }
+}
+
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum FileNameError {
     #[error("{0}")]
@@ -96,6 +107,15 @@ pub enum UploadFileReaderError {
     Canceled,
     #[error("{0}")]
     RemoteError(#[from] RemoteError),
+    #[error("{0}")]
+    IOError(String),
+    /// The uploaded file is kept, the size and the modified time identify it
+    /// so that a retried upload can overwrite it.
+    #[error("uploaded file hash mismatch")]
+    HashMismatch {
+        remote_size: Option<i64>,
+        remote_modified: Option<i64>,
+    },
 }
 
 impl UserError for UploadFileReaderError {
@@ -106,6 +126,8 @@ impl UserError for UploadFileReaderError {
             Self::DecryptFilenameError(err) => err.user_error(),
             Self::Canceled => self.to_string(),
             Self::RemoteError(err) => err.user_error(),
+            Self::IOError(_) => self.to_string(),
+            Self::HashMismatch { .. } => self.to_string(),
         }
     }
 }
@@ -131,14 +153,38 @@ impl From<EnsureDirError> for UploadFileReaderError {
     }
 }
 
+impl From<LoadFileError> for UploadFileReaderError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<LoadFilesError> for UploadFileReaderError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFilesError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFilesError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum DeleteFileError {
     #[error("{0}")]
     RepoNotFound(#[from] RepoNotFoundError),
     #[error("{0}")]
     RepoLocked(#[from] RepoLockedError),
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
     #[error("canceled")]
     Canceled,
+    #[error("failed to move to trash: {0}")]
+    SetTagsError(#[from] SetTagsError),
     #[error("{0}")]
     RemoteError(#[from] RemoteError),
 }
@@ -148,12 +194,55 @@ impl UserError for DeleteFileError {
         match self {
             Self::RepoNotFound(err) => err.user_error(),
             Self::RepoLocked(err) => err.user_error(),
+            Self::DecryptFilenameError(err) => err.user_error(),
             Self::Canceled => self.to_string(),
+            Self::SetTagsError(_) => self.to_string(),
             Self::RemoteError(err) => err.user_error(),
         }
     }
 }
 
+impl From<GetCipherError> for DeleteFileError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
+impl From<LoadFilesError> for DeleteFileError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFilesError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFilesError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<LoadFileError> for DeleteFileError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<EnsureDirError> for DeleteFileError {
+    fn from(err: EnsureDirError) -> Self {
+        match err {
+            EnsureDirError::RepoNotFound(err) => Self::RepoNotFound(err),
+            EnsureDirError::RepoLocked(err) => Self::RepoLocked(err),
+            EnsureDirError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            EnsureDirError::Canceled => Self::Canceled,
+            EnsureDirError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum CreateDirError {
     #[error("{0}")]
@@ -215,6 +304,8 @@ pub enum CreateFileError {
     Canceled,
     #[error("{0}")]
     RemoteError(#[from] RemoteError),
+    #[error("{0}")]
+    IOError(String),
 }
 
 impl UserError for CreateFileError {
@@ -229,6 +320,7 @@ impl UserError for CreateFileError {
                 ..
             }) => String::from("File with this name already exists."),
             Self::RemoteError(err) => err.user_error(),
+            Self::IOError(_) => self.to_string(),
         }
     }
 }
@@ -250,6 +342,8 @@ impl From<UploadFileReaderError> for CreateFileError {
             UploadFileReaderError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
             UploadFileReaderError::Canceled => Self::Canceled,
             UploadFileReaderError::RemoteError(err) => Self::RemoteError(err),
+            UploadFileReaderError::IOError(err) => Self::IOError(err),
+            UploadFileReaderError::HashMismatch { .. } => Self::IOError(err.to_string()),
         }
     }
 }
@@ -268,6 +362,18 @@ pub enum EnsureDirError {
     RemoteError(#[from] RemoteError),
 }
 
+impl UserError for EnsureDirError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::Canceled => self.to_string(),
+            Self::RemoteError(err) => err.user_error(),
+        }
+    }
+}
+
 impl From<CreateDirError> for EnsureDirError {
     fn from(err: CreateDirError) -> Self {
         match err {
@@ -351,6 +457,15 @@ impl UserError for CopyFileError {
     }
 }
 
+impl From<GetCipherError> for CopyFileError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum MoveFileError {
     #[error("invalid path")]
@@ -391,3 +506,73 @@ impl From<CopyFileError> for MoveFileError {
         }
     }
 }
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RepairFilesError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("{0}")]
+    RepoLocked(#[from] RepoLockedError),
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("name is not broken: {0}")]
+    NameNotBroken(String),
+    #[error("canceled")]
+    Canceled,
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+}
+
+impl UserError for RepairFilesError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::NameNotBroken(_) => "Only files with invalid names can be repaired".into(),
+            Self::Canceled => self.to_string(),
+            Self::RemoteError(err) => err.user_error(),
+        }
+    }
+}
+
+impl From<GetCipherError> for RepairFilesError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
+impl From<LoadFileError> for RepairFilesError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<LoadFilesError> for RepairFilesError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFilesError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFilesError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<EnsureDirError> for RepairFilesError {
+    fn from(err: EnsureDirError) -> Self {
+        match err {
+            EnsureDirError::RepoNotFound(err) => Self::RepoNotFound(err),
+            EnsureDirError::RepoLocked(err) => Self::RepoLocked(err),
+            EnsureDirError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            EnsureDirError::Canceled => Self::Canceled,
+            EnsureDirError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
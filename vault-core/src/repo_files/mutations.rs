@@ -168,6 +168,16 @@ pub fn handle_remote_files_mutation(
         notify(store::Event::RepoFiles);
     }
 
+    let created_repo_files: Vec<(RepoId, EncryptedPath)> = remote_files_to_repo_files(
+        state,
+        mutation_state
+            .remote_files
+            .created_files
+            .iter()
+            .map(|(mount_id, path)| (mount_id, path.to_owned())),
+    )
+    .map(|(_, _, repo_id, path)| (repo_id, path))
+    .collect();
     let removed_repo_files = remote_files_to_repo_files(
         state,
         mutation_state
@@ -208,6 +218,15 @@ pub fn handle_remote_files_mutation(
         )
         .collect();
 
+    for (repo_id, path) in created_repo_files {
+        mutation_state
+            .repo_files
+            .created_files
+            .push((repo_id, path));
+
+        repo_files_dirty = true;
+    }
+
     for (repo_id, path) in removed_repo_files {
         mutation_state
             .repo_files
@@ -352,7 +371,7 @@ pub fn decrypt_files(
                     .unwrap()
                     .to_owned(),
             );
-            let decrypted_parent_path = cipher.decrypt_path(&encrypted_parent_path);
+            let decrypted_parent_path = cipher.decrypt_dir_path(&encrypted_parent_path);
 
             decrypt_file(
                 repo_id,
@@ -370,7 +389,7 @@ pub fn decrypt_files(
             .insert(root_repo_file_id.clone(), root_repo_file);
 
         if let Some(remote_children_ids) = state.remote_files.children.get(&root_remote_file_id) {
-            let path = cipher.decrypt_path(encrypted_path);
+            let path = cipher.decrypt_dir_path(encrypted_path);
 
             let mut children = Vec::with_capacity(remote_children_ids.len());
 
@@ -439,7 +458,11 @@ pub fn decrypt_file(
     ));
     let encrypted_name = EncryptedName(remote_file.name.0.clone());
     let id = selectors::get_file_id(repo_id, &encrypted_path);
-    let name = match cipher.decrypt_filename(&encrypted_name) {
+    let decrypted_name = match remote_file.typ {
+        RemoteFileType::Dir => cipher.decrypt_dir_name(&encrypted_name),
+        RemoteFileType::File => cipher.decrypt_filename(&encrypted_name),
+    };
+    let name = match decrypted_name {
         Ok(name) => {
             let name_lower = name.to_lowercase().0;
 
@@ -11,14 +11,15 @@ use crate::{
     repos::{errors::RepoNotFoundError, selectors as repos_selectors},
     store,
     types::{
-        DecryptedName, DecryptedNameLower, EncryptedName, EncryptedPath, MountId, RemotePath,
-        RepoFileId, RepoId,
+        DecryptedName, DecryptedNameLower, DecryptedPath, EncryptedName, EncryptedPath, MountId,
+        RemotePath, RepoFileId, RepoId,
     },
     utils::{name_utils, remote_path_utils, repo_encrypted_path_utils},
 };
 
 use super::{
     errors::{FileNameError, RenameFileError, RepoFilesErrors},
+    service::{SEARCH_NAME, TRASH_NAME, VERSIONS_NAME},
     state::{RepoFile, RepoFileType, RepoFilesBreadcrumb, RepoFilesSort, RepoFilesSortField},
 };
 
@@ -75,6 +76,63 @@ pub fn select_file<'a>(state: &'a store::State, file_id: &RepoFileId) -> Option<
     state.repo_files.files.get(file_id)
 }
 
+/// Returns the time the version was created. Versions without tags (e.g.
+/// uploaded to the versions folder directly) fall back to the modified time.
+pub fn get_version_created(file: &RepoFile) -> Option<i64> {
+    file.tags
+        .as_ref()
+        .and_then(|tags| tags.as_ref().ok())
+        .and_then(|tags| tags.version_created)
+        .or(file.modified)
+}
+
+/// Returns the files in the versions folder of a file, the newest first.
+pub fn select_versions<'a>(
+    state: &'a store::State,
+    repo_id: &RepoId,
+    versions_path: &EncryptedPath,
+) -> Vec<&'a RepoFile> {
+    let mut files: Vec<&RepoFile> = select_files(state, repo_id, versions_path)
+        .filter(|file| file.typ == RepoFileType::File)
+        .collect();
+
+    // versions created in the same millisecond are autorenamed, so a longer
+    // name is a newer version
+    files.sort_by(|a, b| {
+        get_version_created(b)
+            .cmp(&get_version_created(a))
+            .then_with(|| b.name_lower_force().len().cmp(&a.name_lower_force().len()))
+            .then_with(|| b.name_lower_force().cmp(a.name_lower_force()))
+    });
+
+    files
+}
+
+/// Returns true for the trash, versions and search folders in the repo root.
+pub fn is_hidden_dir(file: &RepoFile) -> bool {
+    file.typ == RepoFileType::Dir
+        && repo_encrypted_path_utils::parent_path(&file.encrypted_path)
+            .map(|parent_path| parent_path.is_root())
+            .unwrap_or(false)
+        && file
+            .decrypted_name()
+            .map(|name| is_hidden_name(&name.0))
+            .unwrap_or(false)
+}
+
+/// Returns true for paths in the trash, versions and search folders.
+pub fn is_hidden_path(path: &DecryptedPath) -> bool {
+    path.0
+        .split('/')
+        .nth(1)
+        .map(is_hidden_name)
+        .unwrap_or(false)
+}
+
+fn is_hidden_name(name: &str) -> bool {
+    name == TRASH_NAME || name == VERSIONS_NAME || name == SEARCH_NAME
+}
+
 pub fn select_file_name<'a>(
     state: &'a store::State,
     file: &'a RepoFile,
@@ -221,7 +279,7 @@ pub fn select_breadcrumbs(
             let id = get_file_id(repo_id, &path);
             let name = match repo_encrypted_path_utils::path_to_name(&path) {
                 Some(name) => cipher
-                    .decrypt_filename(&name)
+                    .decrypt_dir_name(&name)
                     .map(|x| x.0)
                     .unwrap_or(name.0),
                 None => repo.name.0.clone(),
@@ -1,32 +1,44 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 
 use futures::{
+    channel::oneshot,
     future::{BoxFuture, Shared},
-    io::Cursor,
-    FutureExt,
+    io::{self, Cursor},
+    AsyncReadExt, FutureExt,
+};
+use vault_crypto::{
+    constants::{BLOCK_SIZE, FILE_HEADER_SIZE},
+    data_cipher::{block_offsets, encrypted_size},
+    nonce::Nonce,
 };
-use vault_crypto::data_cipher::encrypted_size;
 
 use crate::{
-    cipher::decrypt_on_progress::decrypt_on_progress,
+    cipher::{
+        decrypt_on_progress::{decrypt_on_progress, decrypt_on_progress_from},
+        Cipher,
+    },
     common::state::BoxAsyncRead,
     dialogs, remote,
-    remote_files::RemoteFilesService,
+    remote_files::{state::RemoteFile, RemoteFilesService},
     repo_files_read::{
-        errors::GetFilesReaderError, state::RepoFileReaderProvider, RepoFilesReadService,
+        errors::GetFilesReaderError,
+        state::{RepoFileReader, RepoFileReaderProvider, RepoFileReaderRange},
+        RepoFilesReadService,
     },
     repo_files_tags::RepoFilesTagsService,
     repos::{
         errors::{GetCipherError, RepoNotFoundError},
-        ReposService,
+        selectors as repos_selectors, ReposService,
     },
+    runtime,
+    storage_backend::errors::StorageBackendErrors,
     store,
     types::{
-        DecryptedName, EncryptedName, EncryptedPath, MountId, RemoteName, RemotePath, RepoFileId,
-        RepoId,
+        DecryptedName, DecryptedNameLower, EncryptedName, EncryptedPath, MountId, RemoteName,
+        RemotePath, RepoFileId, RepoId,
     },
     utils::{md5_reader, name_utils, repo_encrypted_path_utils},
 };
@@ -34,13 +46,39 @@ use crate::{
 use super::{
     errors::{
         CopyFileError, CreateDirError, CreateFileError, DeleteFileError, EnsureDirError,
-        LoadFileError, LoadFilesError, MoveFileError, RenameFileError, RepoFilesErrors,
-        UploadFileReaderError,
+        LoadFileError, LoadFilesError, MoveFileError, RenameFileError, RepairFilesError,
+        RepoFilesErrors, UploadFileReaderError,
     },
     mutations, selectors,
-    state::{RepoFileType, RepoFilesUploadConflictResolution, RepoFilesUploadResult},
+    state::{
+        RepoFileName, RepoFileType, RepoFilesRepairAction, RepoFilesRepairItem,
+        RepoFilesRepairStatus, RepoFilesUploadConflictResolution, RepoFilesUploadOptions,
+        RepoFilesUploadResult, RepoFilesUploadResumable, RepoFilesUploadSession,
+    },
 };
 
+/// Resumable uploads are uploaded in chunks of this many encryption blocks
+/// (about 4 MiB).
+pub const RESUMABLE_UPLOAD_CHUNK_BLOCKS: i64 = 64;
+
+/// Folder in the repo root where files with broken names are moved to.
+pub const LOST_AND_FOUND_NAME: &str = "lost+found";
+
+/// Hidden folder in the repo root where deleted files are moved to if the
+/// trash is enabled for the repo.
+pub const TRASH_NAME: &str = ".trash";
+
+/// Hidden folder in the repo root where previous contents of overwritten
+/// files are kept if versions are enabled for the repo. Versions of a file
+/// are stored in a folder with the same path as the file.
+pub const VERSIONS_NAME: &str = ".versions";
+
+/// Hidden folder in the repo root where the encrypted search index is stored
+/// so that it is shared by all clients.
+pub const SEARCH_NAME: &str = ".search";
+
+type MountPath = (MountId, RemotePath);
+
 pub struct RepoFilesService {
     repos_service: Arc<ReposService>,
     remote_files_service: Arc<RemoteFilesService>,
@@ -48,6 +86,7 @@ pub struct RepoFilesService {
     repo_files_read_service: Arc<RepoFilesReadService>,
     dialogs_service: Arc<dialogs::DialogsService>,
     store: Arc<store::Store>,
+    runtime: Arc<runtime::BoxRuntime>,
     ensure_dirs_futures:
         Arc<Mutex<HashMap<RepoFileId, Shared<BoxFuture<'static, Result<(), EnsureDirError>>>>>>,
     remote_files_mutation_subscription_id: u32,
@@ -62,6 +101,7 @@ impl RepoFilesService {
         repo_files_read_service: Arc<RepoFilesReadService>,
         dialogs_service: Arc<dialogs::DialogsService>,
         store: Arc<store::Store>,
+        runtime: Arc<runtime::BoxRuntime>,
     ) -> Self {
         let remote_files_mutation_subscription_id = store.get_next_id();
 
@@ -95,6 +135,7 @@ impl RepoFilesService {
             repo_files_read_service,
             dialogs_service,
             store: store.clone(),
+            runtime,
             ensure_dirs_futures: Arc::new(Mutex::new(HashMap::new())),
             remote_files_mutation_subscription_id,
             repos_mutation_subscription_id,
@@ -150,6 +191,16 @@ impl RepoFilesService {
         Ok(cipher.encrypt_filename(name))
     }
 
+    pub fn encrypt_dir_name(
+        &self,
+        repo_id: &RepoId,
+        name: &DecryptedName,
+    ) -> Result<EncryptedName, GetCipherError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        Ok(cipher.encrypt_dir_name(name))
+    }
+
     pub fn get_file_reader(
         self: Arc<Self>,
         repo_id: &RepoId,
@@ -168,6 +219,24 @@ impl RepoFilesService {
             .get_files_reader(vec![file])
     }
 
+    pub async fn get_file_reader_range(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        range: &RepoFileReaderRange,
+    ) -> Result<RepoFileReader, GetFilesReaderError> {
+        let file = self
+            .store
+            .with_state(|state| {
+                selectors::select_file(state, &selectors::get_file_id(repo_id, path)).cloned()
+            })
+            .ok_or(GetFilesReaderError::FileNotFound)?;
+
+        self.repo_files_read_service
+            .get_file_reader_range(&file, range)
+            .await
+    }
+
     pub async fn upload_file_reader(
         self: Arc<Self>,
         repo_id: &RepoId,
@@ -177,6 +246,30 @@ impl RepoFilesService {
         size: Option<i64>,
         conflict_resolution: RepoFilesUploadConflictResolution,
         on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    ) -> Result<RepoFilesUploadResult, UploadFileReaderError> {
+        self.upload_file_reader_with_options(
+            repo_id,
+            parent_path,
+            name,
+            reader,
+            conflict_resolution,
+            RepoFilesUploadOptions {
+                size,
+                on_progress,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn upload_file_reader_with_options(
+        self: Arc<Self>,
+        repo_id: &RepoId,
+        parent_path: &EncryptedPath,
+        name: EncryptedName,
+        reader: BoxAsyncRead,
+        conflict_resolution: RepoFilesUploadConflictResolution,
+        options: RepoFilesUploadOptions,
     ) -> Result<RepoFilesUploadResult, UploadFileReaderError> {
         self.clone().ensure_dirs(repo_id, parent_path).await?;
 
@@ -184,25 +277,435 @@ impl RepoFilesService {
 
         let (mount_id, remote_parent_path) = self.get_repo_mount_path(repo_id, parent_path)?;
 
+        let version_path = match conflict_resolution {
+            RepoFilesUploadConflictResolution::Overwrite { .. } => {
+                self.clone()
+                    .create_version(
+                        repo_id,
+                        &repo_encrypted_path_utils::join_path_name(parent_path, &name),
+                    )
+                    .await?
+            }
+            RepoFilesUploadConflictResolution::Error => None,
+        };
+
         let (md5_reader, md5_digest_future) = md5_reader::MD5Reader::new(reader);
 
-        let encrypted_size = size.map(encrypted_size);
-        let encrypted_reader = cipher.encrypt_reader_async(md5_reader);
+        let encrypted_size = options.size.map(encrypted_size);
+        let (encrypted_reader, encrypted_md5_digest_future) = encrypted_md5_reader(
+            Box::pin(cipher.encrypt_reader_async(md5_reader)),
+            self.verify_uploads(),
+        );
+        let encrypted_reader = match options.wrap_reader {
+            Some(wrap_reader) => wrap_reader(encrypted_reader),
+            None => encrypted_reader,
+        };
 
-        let (_, remote_file) = self
+        let upload_res = self
             .remote_files_service
             .upload_file_reader(
                 &mount_id,
                 &remote_parent_path,
                 &RemoteName(name.0),
-                Box::pin(encrypted_reader),
+                encrypted_reader,
                 encrypted_size,
                 conflict_resolution.into(),
-                on_progress.map(decrypt_on_progress),
+                options.on_progress.map(decrypt_on_progress),
             )
+            .await;
+
+        if let Some(version_path) = version_path {
+            self.version_finished(repo_id, &version_path, upload_res.is_ok())
+                .await;
+        }
+
+        let (_, remote_file) = upload_res.map_err(UploadFileReaderError::RemoteError)?;
+
+        if let Some(encrypted_md5_digest_future) = encrypted_md5_digest_future {
+            self.verify_uploaded(&remote_file, encrypted_md5_digest_future)
+                .await?;
+        }
+
+        self.file_uploaded(
+            repo_id,
+            parent_path,
+            &cipher,
+            remote_file,
+            md5_digest_future,
+        )
+        .await
+    }
+
+    /// Uploads the file in chunks. If an attempt fails, the next attempt with
+    /// the same session resumes the upload at the last encryption block that
+    /// was stored by the server.
+    pub async fn upload_file_reader_resumable(
+        self: Arc<Self>,
+        repo_id: &RepoId,
+        parent_path: &EncryptedPath,
+        name: EncryptedName,
+        reader: BoxAsyncRead,
+        conflict_resolution: RepoFilesUploadConflictResolution,
+        resumable: RepoFilesUploadResumable,
+    ) -> Result<RepoFilesUploadResult, UploadFileReaderError> {
+        if !self.remote_files_service.has_upload_sessions() {
+            return self
+                .upload_file_reader_single(
+                    repo_id,
+                    parent_path,
+                    name,
+                    reader,
+                    conflict_resolution,
+                    resumable,
+                )
+                .await;
+        }
+
+        self.clone().ensure_dirs(repo_id, parent_path).await?;
+
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let (mount_id, remote_parent_path) = self.get_repo_mount_path(repo_id, parent_path)?;
+
+        let previous_session = resumable.session.lock().unwrap().clone();
+
+        let (session, session_offset) =
+            match self.get_upload_session(&mount_id, previous_session).await? {
+                Some(res) => res,
+                None => {
+                    let nonce = Nonce::new_random()
+                        .map_err(|err| UploadFileReaderError::IOError(err.to_string()))?;
+
+                    let remote_session = match self
+                        .remote_files_service
+                        .create_upload_session(
+                            &mount_id,
+                            &remote_parent_path,
+                            &RemoteName(name.0.clone()),
+                            Some(encrypted_size(resumable.size)),
+                            conflict_resolution.clone().into(),
+                        )
+                        .await
+                    {
+                        Ok(remote_session) => remote_session,
+                        // the server does not implement upload sessions
+                        Err(err) if StorageBackendErrors::is_not_supported(&err) => {
+                            return self
+                                .upload_file_reader_single(
+                                    repo_id,
+                                    parent_path,
+                                    name,
+                                    reader,
+                                    conflict_resolution,
+                                    resumable,
+                                )
+                                .await;
+                        }
+                        Err(err) => return Err(err.into()),
+                    };
+
+                    let session = RepoFilesUploadSession {
+                        session_id: remote_session.id,
+                        nonce,
+                    };
+
+                    *resumable.session.lock().unwrap() = Some(session.clone());
+
+                    (session, 0)
+                }
+            };
+
+        let (mut md5_reader, md5_digest_future) = md5_reader::MD5Reader::new(reader);
+
+        let verify = self.verify_uploads();
+
+        // the upload is resumed at the start of the last stored block. the
+        // plaintext before it still has to be read for the MD5 hash
+        let (encrypted_reader, encrypted_md5_digest_future, mut offset) =
+            match block_offsets(session_offset) {
+                Some((_, encrypted_offset, decrypted_offset)) if verify => {
+                    // the ciphertext before the block is needed for the
+                    // encrypted MD5 hash. it is the same as in the previous
+                    // attempts because the session nonce is the same
+                    let (mut encrypted_reader, encrypted_md5_digest_future) = encrypted_md5_reader(
+                        Box::pin(
+                            cipher.encrypt_reader_async_nonce(md5_reader, session.nonce.clone()),
+                        ),
+                        true,
+                    );
+
+                    let skipped = io::copy(
+                        (&mut encrypted_reader).take(encrypted_offset as u64),
+                        &mut io::sink(),
+                    )
+                    .await
+                    .map_err(|err| UploadFileReaderError::IOError(err.to_string()))?;
+
+                    if skipped != encrypted_offset as u64 {
+                        return Err(UploadFileReaderError::IOError(String::from(
+                            "file is shorter than the uploaded part",
+                        )));
+                    }
+
+                    if let Some(on_resume) = &resumable.on_resume {
+                        on_resume(decrypted_offset);
+                    }
+
+                    (
+                        encrypted_reader,
+                        encrypted_md5_digest_future,
+                        encrypted_offset,
+                    )
+                }
+                Some((block, encrypted_offset, decrypted_offset)) => {
+                    let skipped = io::copy(
+                        (&mut md5_reader).take(decrypted_offset as u64),
+                        &mut io::sink(),
+                    )
+                    .await
+                    .map_err(|err| UploadFileReaderError::IOError(err.to_string()))?;
+
+                    if skipped != decrypted_offset as u64 {
+                        return Err(UploadFileReaderError::IOError(String::from(
+                            "file is shorter than the uploaded part",
+                        )));
+                    }
+
+                    if let Some(on_resume) = &resumable.on_resume {
+                        on_resume(decrypted_offset);
+                    }
+
+                    (
+                        Box::pin(cipher.encrypt_reader_async_at_block(
+                            md5_reader,
+                            session.nonce.clone(),
+                            block,
+                        )) as BoxAsyncRead,
+                        None,
+                        encrypted_offset,
+                    )
+                }
+                None => {
+                    let (encrypted_reader, encrypted_md5_digest_future) = encrypted_md5_reader(
+                        Box::pin(
+                            cipher.encrypt_reader_async_nonce(md5_reader, session.nonce.clone()),
+                        ),
+                        verify,
+                    );
+
+                    (encrypted_reader, encrypted_md5_digest_future, 0)
+                }
+            };
+
+        let on_progress = resumable
+            .on_progress
+            .map(|on_progress| Arc::new(decrypt_on_progress_from(offset, on_progress)));
+
+        let mut encrypted_reader = match resumable.wrap_reader {
+            Some(wrap_reader) => wrap_reader(encrypted_reader),
+            None => encrypted_reader,
+        };
+        let chunk_size = RESUMABLE_UPLOAD_CHUNK_BLOCKS * BLOCK_SIZE as i64;
+
+        loop {
+            // chunks end at block boundaries so that the stored offset is
+            // always block aligned
+            let len = if offset == 0 {
+                FILE_HEADER_SIZE as i64 + chunk_size
+            } else {
+                chunk_size
+            };
+
+            let mut chunk = Vec::with_capacity(len as usize);
+
+            (&mut encrypted_reader)
+                .take(len as u64)
+                .read_to_end(&mut chunk)
+                .await
+                .map_err(|err| UploadFileReaderError::IOError(err.to_string()))?;
+
+            if chunk.is_empty() {
+                break;
+            }
+
+            let chunk_len = chunk.len() as i64;
+
+            let chunk_on_progress = on_progress.clone().map(|on_progress| {
+                Box::new(move |n| on_progress(n)) as Box<dyn Fn(usize) + Send + Sync>
+            });
+
+            let remote_session = self
+                .remote_files_service
+                .upload_session_chunk(
+                    &mount_id,
+                    &session.session_id,
+                    offset,
+                    Box::pin(Cursor::new(chunk)),
+                    chunk_on_progress,
+                )
+                .await?;
+
+            offset += chunk_len;
+
+            if remote_session.offset != offset {
+                return Err(UploadFileReaderError::IOError(String::from(
+                    "upload session offset mismatch",
+                )));
+            }
+
+            if chunk_len < len {
+                break;
+            }
+        }
+
+        // the MD5 digest is sent when the reader is dropped
+        drop(encrypted_reader);
+
+        // the existing file is only overwritten when the session is committed
+        let version_path = match conflict_resolution {
+            RepoFilesUploadConflictResolution::Overwrite { .. } => {
+                self.clone()
+                    .create_version(
+                        repo_id,
+                        &repo_encrypted_path_utils::join_path_name(parent_path, &name),
+                    )
+                    .await?
+            }
+            RepoFilesUploadConflictResolution::Error => None,
+        };
+
+        let commit_res = self
+            .remote_files_service
+            .commit_upload_session(&mount_id, &remote_parent_path, &session.session_id)
+            .await;
+
+        if let Some(version_path) = version_path {
+            self.version_finished(repo_id, &version_path, commit_res.is_ok())
+                .await;
+        }
+
+        let (_, remote_file) = commit_res?;
+
+        *resumable.session.lock().unwrap() = None;
+
+        if let Some(encrypted_md5_digest_future) = encrypted_md5_digest_future {
+            self.verify_uploaded(&remote_file, encrypted_md5_digest_future)
+                .await?;
+        }
+
+        self.file_uploaded(
+            repo_id,
+            parent_path,
+            &cipher,
+            remote_file,
+            md5_digest_future,
+        )
+        .await
+    }
+
+    /// Uploads the file in a single request if the storage backend does not
+    /// support upload sessions.
+    async fn upload_file_reader_single(
+        self: Arc<Self>,
+        repo_id: &RepoId,
+        parent_path: &EncryptedPath,
+        name: EncryptedName,
+        reader: BoxAsyncRead,
+        conflict_resolution: RepoFilesUploadConflictResolution,
+        resumable: RepoFilesUploadResumable,
+    ) -> Result<RepoFilesUploadResult, UploadFileReaderError> {
+        self.upload_file_reader_with_options(
+            repo_id,
+            parent_path,
+            name,
+            reader,
+            conflict_resolution,
+            RepoFilesUploadOptions {
+                size: Some(resumable.size),
+                on_progress: resumable.on_progress,
+                wrap_reader: resumable.wrap_reader,
+            },
+        )
+        .await
+    }
+
+    /// Returns the previous upload session and its stored offset if it still
+    /// exists.
+    async fn get_upload_session(
+        &self,
+        mount_id: &MountId,
+        session: Option<RepoFilesUploadSession>,
+    ) -> Result<Option<(RepoFilesUploadSession, i64)>, UploadFileReaderError> {
+        let session = match session {
+            Some(session) => session,
+            None => return Ok(None),
+        };
+
+        match self
+            .remote_files_service
+            .get_upload_session(mount_id, &session.session_id)
             .await
-            .map_err(UploadFileReaderError::RemoteError)?;
+        {
+            Ok(remote_session) => Ok(Some((session, remote_session.offset))),
+            Err(err) if err.is_api_error_code(remote::ApiErrorCode::NotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Uploaded file hashes are verified if transfers.verify_uploads is
+    /// enabled in the config.
+    fn verify_uploads(&self) -> bool {
+        self.store
+            .with_state(|state| state.config.transfers.verify_uploads)
+    }
+
+    /// Compares the MD5 hash of the uploaded ciphertext with the remote file
+    /// hash. The remote file is never deleted on mismatch because an
+    /// overwritten file would be lost, the retried upload overwrites it.
+    async fn verify_uploaded(
+        &self,
+        remote_file: &RemoteFile,
+        encrypted_md5_digest_future: oneshot::Receiver<md5::Digest>,
+    ) -> Result<(), UploadFileReaderError> {
+        let encrypted_hash = format!(
+            "{:x}",
+            encrypted_md5_digest_future
+                .await
+                .map_err(|_| UploadFileReaderError::IOError(String::from(
+                    "failed to compute the uploaded hash"
+                )))?
+        );
+
+        let remote_file_hash = match &remote_file.hash {
+            Some(hash) => hash,
+            None => {
+                log::warn!(
+                    "RepoFilesService verify_uploaded remote file has no hash: {:?}",
+                    remote_file.path,
+                );
+
+                return Ok(());
+            }
+        };
+
+        if remote_file_hash.eq_ignore_ascii_case(&encrypted_hash) {
+            return Ok(());
+        }
 
+        Err(UploadFileReaderError::HashMismatch {
+            remote_size: remote_file.size,
+            remote_modified: remote_file.modified,
+        })
+    }
+
+    async fn file_uploaded(
+        &self,
+        repo_id: &RepoId,
+        parent_path: &EncryptedPath,
+        cipher: &Cipher,
+        remote_file: RemoteFile,
+        md5_digest_future: oneshot::Receiver<md5::Digest>,
+    ) -> Result<RepoFilesUploadResult, UploadFileReaderError> {
         let encrypted_name = EncryptedName(remote_file.name.0.clone());
         let name = cipher.decrypt_filename(&encrypted_name)?;
         let path = repo_encrypted_path_utils::join_path_name(parent_path, &encrypted_name);
@@ -256,12 +759,7 @@ impl RepoFilesService {
             }
 
             for (repo_id, path) in files {
-                let (mount_id, remote_path) = self.get_repo_mount_path(repo_id, path)?;
-
-                self.remote_files_service
-                    .delete_file(&mount_id, &remote_path)
-                    .await
-                    .map_err(DeleteFileError::RemoteError)?;
+                self.delete_file(repo_id, path).await?;
             }
         } else {
             return Err(DeleteFileError::Canceled);
@@ -270,6 +768,357 @@ impl RepoFilesService {
         Ok(())
     }
 
+    /// Deletes the file without a confirmation dialog. The file is moved to
+    /// the trash if the trash is enabled for the repo.
+    pub async fn delete_file(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<(), DeleteFileError> {
+        let trash_enabled = self
+            .store
+            .with_state(|state| repos_selectors::select_trash_enabled(state, repo_id));
+
+        // files that are already in the trash are deleted permanently
+        if trash_enabled && !self.is_trash_path(repo_id, path)? {
+            return self.move_to_trash(repo_id, path).await;
+        }
+
+        let (mount_id, remote_path) = self.get_repo_mount_path(repo_id, path)?;
+
+        self.remote_files_service
+            .delete_file(&mount_id, &remote_path)
+            .await
+            .map_err(DeleteFileError::RemoteError)
+    }
+
+    pub fn get_trash_path(&self, repo_id: &RepoId) -> Result<EncryptedPath, GetCipherError> {
+        Ok(repo_encrypted_path_utils::join_path_name(
+            &EncryptedPath("/".into()),
+            &self.encrypt_dir_name(repo_id, &DecryptedName(TRASH_NAME.into()))?,
+        ))
+    }
+
+    /// Returns true for the trash folder and everything inside it.
+    pub fn is_trash_path(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<bool, GetCipherError> {
+        let trash_path = self.get_trash_path(repo_id)?;
+
+        Ok(path == &trash_path || path.0.starts_with(&format!("{}/", trash_path.0)))
+    }
+
+    pub fn get_versions_path(&self, repo_id: &RepoId) -> Result<EncryptedPath, GetCipherError> {
+        Ok(repo_encrypted_path_utils::join_path_name(
+            &EncryptedPath("/".into()),
+            &self.encrypt_dir_name(repo_id, &DecryptedName(VERSIONS_NAME.into()))?,
+        ))
+    }
+
+    /// Returns the folder with versions of the file at path.
+    pub fn get_file_versions_path(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<EncryptedPath, GetCipherError> {
+        Ok(repo_encrypted_path_utils::join_paths(
+            &self.get_versions_path(repo_id)?,
+            path,
+        ))
+    }
+
+    /// Returns true for the versions folder and everything inside it.
+    pub fn is_versions_path(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<bool, GetCipherError> {
+        let versions_path = self.get_versions_path(repo_id)?;
+
+        Ok(path == &versions_path || path.0.starts_with(&format!("{}/", versions_path.0)))
+    }
+
+    pub fn get_search_path(&self, repo_id: &RepoId) -> Result<EncryptedPath, GetCipherError> {
+        Ok(repo_encrypted_path_utils::join_path_name(
+            &EncryptedPath("/".into()),
+            &self.encrypt_dir_name(repo_id, &DecryptedName(SEARCH_NAME.into()))?,
+        ))
+    }
+
+    /// Returns true for the trash, versions and search folders and everything
+    /// inside them.
+    pub fn is_hidden_path(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<bool, GetCipherError> {
+        let search_path = self.get_search_path(repo_id)?;
+
+        Ok(self.is_trash_path(repo_id, path)?
+            || self.is_versions_path(repo_id, path)?
+            || path == &search_path
+            || path.0.starts_with(&format!("{}/", search_path.0)))
+    }
+
+    /// Copies the current content of the file to its versions folder before
+    /// the file is overwritten and returns the version path. The creation time
+    /// and the user agent of this client are stored in the version tags.
+    /// Nothing is done if versions are disabled for the repo or the file does
+    /// not exist yet. version_finished has to be called after the upload.
+    pub async fn create_version(
+        self: Arc<Self>,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<Option<EncryptedPath>, UploadFileReaderError> {
+        match self
+            .store
+            .with_state(|state| repos_selectors::select_max_versions(state, repo_id))
+        {
+            Some(max_versions) if max_versions > 0 => {}
+            _ => return Ok(None),
+        };
+
+        if self.is_versions_path(repo_id, path)? || self.is_trash_path(repo_id, path)? {
+            return Ok(None);
+        }
+
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        match self.load_file(repo_id, path).await {
+            Ok(()) => {}
+            Err(LoadFileError::RemoteError(err))
+                if err.is_api_error_code(remote::ApiErrorCode::NotFound) =>
+            {
+                return Ok(None)
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let file = self
+            .store
+            .with_state(|state| {
+                selectors::select_file(state, &selectors::get_file_id(repo_id, path)).cloned()
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        if file.typ != RepoFileType::File {
+            return Ok(None);
+        }
+
+        let name = file.decrypted_name()?;
+
+        let versions_path = self.get_file_versions_path(repo_id, path)?;
+
+        self.clone().ensure_dirs(repo_id, &versions_path).await?;
+
+        let created = self.runtime.now().0;
+
+        let version_name = self
+            .get_unused_name(
+                repo_id,
+                &versions_path,
+                &DecryptedName(name_utils::join_name_ext(
+                    &created.to_string(),
+                    name_utils::name_to_ext(&name.0),
+                )),
+            )
+            .await?;
+
+        let version_path = repo_encrypted_path_utils::join_path_name(
+            &versions_path,
+            &cipher.encrypt_filename(&version_name),
+        );
+
+        let (to_mount_id, to_remote_path) = self.get_repo_mount_path(repo_id, &version_path)?;
+
+        self.remote_files_service
+            .copy_file(
+                &file.mount_id,
+                &file.remote_path,
+                &to_mount_id,
+                &to_remote_path,
+            )
+            .await?;
+
+        self.load_file(repo_id, &version_path).await?;
+
+        let user_agent = self
+            .store
+            .with_state(|state| state.config.user_agent.clone());
+
+        if let Err(err) = self
+            .repo_files_tags_service
+            .set_tags(
+                repo_id,
+                &version_path,
+                Box::new(move |_, tags| {
+                    tags.version_created = Some(created);
+                    tags.version_user_agent = user_agent.clone();
+
+                    Ok(())
+                }),
+            )
+            .await
+        {
+            log::warn!(
+                "RepoFilesService create_version failed to set tags: {}",
+                err,
+            );
+        }
+
+        Ok(Some(version_path))
+    }
+
+    /// Only the newest versions are kept if the file was overwritten. If the
+    /// upload failed before the file was overwritten the version is deleted so
+    /// that it does not replace an older version.
+    async fn version_finished(
+        &self,
+        repo_id: &RepoId,
+        version_path: &EncryptedPath,
+        overwritten: bool,
+    ) {
+        let res = if overwritten {
+            self.prune_versions(repo_id, version_path).await
+        } else {
+            self.delete_version(repo_id, version_path).await
+        };
+
+        if let Err(err) = res {
+            log::warn!("RepoFilesService version_finished failed: {}", err);
+        }
+    }
+
+    async fn delete_version(
+        &self,
+        repo_id: &RepoId,
+        version_path: &EncryptedPath,
+    ) -> Result<(), UploadFileReaderError> {
+        let (mount_id, remote_path) = self.get_repo_mount_path(repo_id, version_path)?;
+
+        match self
+            .remote_files_service
+            .delete_file(&mount_id, &remote_path)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(err) if err.is_api_error_code(remote::ApiErrorCode::NotFound) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Deletes all but the newest max_versions versions in the versions folder
+    /// of the version.
+    async fn prune_versions(
+        &self,
+        repo_id: &RepoId,
+        version_path: &EncryptedPath,
+    ) -> Result<(), UploadFileReaderError> {
+        let max_versions = match self
+            .store
+            .with_state(|state| repos_selectors::select_max_versions(state, repo_id))
+        {
+            Some(max_versions) => max_versions,
+            None => return Ok(()),
+        };
+
+        let versions_path = &repo_encrypted_path_utils::parent_path(version_path)
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        self.load_files(repo_id, versions_path).await?;
+
+        let remove_files: Vec<(MountId, RemotePath)> = self.store.with_state(|state| {
+            selectors::select_versions(state, repo_id, versions_path)
+                .into_iter()
+                .skip(max_versions as usize)
+                .map(|file| (file.mount_id.clone(), file.remote_path.clone()))
+                .collect()
+        });
+
+        for (mount_id, remote_path) in remove_files {
+            match self
+                .remote_files_service
+                .delete_file(&mount_id, &remote_path)
+                .await
+            {
+                Ok(()) => {}
+                Err(err) if err.is_api_error_code(remote::ApiErrorCode::NotFound) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves the file to the trash folder. The original path and the deletion
+    /// time are stored in the file tags so that the file can be restored.
+    async fn move_to_trash(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<(), DeleteFileError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let trash_path = self.get_trash_path(repo_id)?;
+
+        let file_id = selectors::get_file_id(repo_id, path);
+
+        if self
+            .store
+            .with_state(|state| selectors::select_file(state, &file_id).is_none())
+        {
+            self.load_file(repo_id, path).await?;
+        }
+
+        let (typ, name) = self
+            .store
+            .with_state(|state| {
+                selectors::select_file(state, &file_id)
+                    .map(|file| (file.typ.clone(), file.decrypted_name().cloned()))
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+        let name = name?;
+
+        self.ensure_dir(repo_id.clone(), trash_path.clone()).await?;
+
+        let original_path = path.0.clone();
+        let deleted = self.runtime.now().0;
+
+        self.repo_files_tags_service
+            .set_tags(
+                repo_id,
+                path,
+                Box::new(move |_, tags| {
+                    tags.trash_original_path = Some(original_path.clone());
+                    tags.trash_deleted = Some(deleted);
+
+                    Ok(())
+                }),
+            )
+            .await?;
+
+        let trash_name = self.get_unused_name(repo_id, &trash_path, &name).await?;
+
+        let encrypted_trash_name = match typ {
+            RepoFileType::Dir => cipher.encrypt_dir_name(&trash_name),
+            RepoFileType::File => cipher.encrypt_filename(&trash_name),
+        };
+
+        let (mount_id, remote_path) = self.get_repo_mount_path(repo_id, path)?;
+
+        let (to_mount_id, to_remote_path) = self.get_repo_mount_path(
+            repo_id,
+            &repo_encrypted_path_utils::join_path_name(&trash_path, &encrypted_trash_name),
+        )?;
+
+        self.remote_files_service
+            .move_file(&mount_id, &remote_path, &to_mount_id, &to_remote_path)
+            .await
+            .map_err(DeleteFileError::RemoteError)
+    }
+
     pub async fn create_dir(
         &self,
         repo_id: &RepoId,
@@ -293,7 +1142,7 @@ impl RepoFilesService {
                 move |value| {
                     let new_name = DecryptedName(value.clone());
                     let encrypted_new_name =
-                        input_value_validator_cipher.encrypt_filename(&new_name);
+                        input_value_validator_cipher.encrypt_dir_name(&new_name);
 
                     input_value_validator_store.with_state(|state| {
                         selectors::select_check_new_name_valid(
@@ -313,7 +1162,7 @@ impl RepoFilesService {
             None => return Err(CreateDirError::Canceled),
         };
 
-        let encrypted_name = self.encrypt_filename(repo_id, &name)?;
+        let encrypted_name = self.encrypt_dir_name(repo_id, &name)?;
 
         let path = repo_encrypted_path_utils::join_path_name(parent_path, &encrypted_name);
 
@@ -522,11 +1371,9 @@ impl RepoFilesService {
         repo_id: &RepoId,
         path: &EncryptedPath,
     ) -> Result<(), RenameFileError> {
-        let (mount_id, remote_path, original_name, typ) = match self.store.with_state(|state| {
+        let (original_name, typ) = match self.store.with_state(|state| {
             selectors::select_file(state, &selectors::get_file_id(repo_id, path)).map(|file| {
                 (
-                    file.mount_id.clone(),
-                    file.remote_path.clone(),
                     file.decrypted_name().map(ToOwned::to_owned),
                     file.typ.clone(),
                 )
@@ -547,6 +1394,7 @@ impl RepoFilesService {
         let input_value_validator_repo_id = repo_id.to_owned();
         let input_value_validator_cipher = self.repos_service.get_cipher(repo_id)?;
         let input_value_validator_path = path.to_owned();
+        let input_value_validator_typ = typ.clone();
 
         if let Some(name) = self
             .dialogs_service
@@ -562,8 +1410,14 @@ impl RepoFilesService {
                 },
                 move |value| {
                     let new_name = DecryptedName(value.clone());
-                    let encrypted_new_name =
-                        input_value_validator_cipher.encrypt_filename(&new_name);
+                    let encrypted_new_name = match input_value_validator_typ {
+                        RepoFileType::Dir => {
+                            input_value_validator_cipher.encrypt_dir_name(&new_name)
+                        }
+                        RepoFileType::File => {
+                            input_value_validator_cipher.encrypt_filename(&new_name)
+                        }
+                    };
 
                     input_value_validator_store.with_state(|state| {
                         selectors::select_check_rename_file(
@@ -579,18 +1433,48 @@ impl RepoFilesService {
             )
             .await
         {
-            let name = DecryptedName(name?);
-            let encrypted_name = self.encrypt_filename(&repo_id, &name)?;
-
-            self.remote_files_service
-                .rename_file(&mount_id, &remote_path, RemoteName(encrypted_name.0))
-                .await
-                .map_err(RenameFileError::RemoteError)?;
+            self.rename_file_name(repo_id, path, &DecryptedName(name?))
+                .await?;
         }
 
         Ok(())
     }
 
+    /// Renames the file without a dialog. The file has to be loaded.
+    pub async fn rename_file_name(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        name: &DecryptedName,
+    ) -> Result<(), RenameFileError> {
+        let (mount_id, remote_path, typ) = self
+            .store
+            .with_state(|state| {
+                selectors::select_file(state, &selectors::get_file_id(repo_id, path)).map(|file| {
+                    (
+                        file.mount_id.clone(),
+                        file.remote_path.clone(),
+                        file.typ.clone(),
+                    )
+                })
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        let encrypted_name = match typ {
+            RepoFileType::Dir => self.encrypt_dir_name(repo_id, name)?,
+            RepoFileType::File => self.encrypt_filename(repo_id, name)?,
+        };
+
+        self.store.with_state(|state| {
+            selectors::select_check_rename_file(state, repo_id, path, name, &encrypted_name)
+        })?;
+
+        self.remote_files_service
+            .rename_file(&mount_id, &remote_path, RemoteName(encrypted_name.0))
+            .await
+            .map_err(RenameFileError::RemoteError)
+    }
+
     pub async fn copy_file(
         &self,
         repo_id: &RepoId,
@@ -635,6 +1519,247 @@ impl RepoFilesService {
             .map_err(MoveFileError::RemoteError)
     }
 
+    /// Copies the file to to_parent_path with a new name. The file has to be
+    /// loaded.
+    pub async fn copy_file_name(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        to_parent_path: &EncryptedPath,
+        name: &DecryptedName,
+    ) -> Result<(), CopyFileError> {
+        let ((mount_id, remote_path), (to_mount_id, to_remote_path)) =
+            self.get_new_name_mount_paths(repo_id, path, to_parent_path, name)?;
+
+        self.remote_files_service
+            .copy_file(&mount_id, &remote_path, &to_mount_id, &to_remote_path)
+            .await
+            .map_err(CopyFileError::RemoteError)
+    }
+
+    /// Moves the file to to_parent_path with a new name. The file has to be
+    /// loaded.
+    pub async fn move_file_name(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        to_parent_path: &EncryptedPath,
+        name: &DecryptedName,
+    ) -> Result<(), MoveFileError> {
+        let ((mount_id, remote_path), (to_mount_id, to_remote_path)) =
+            self.get_new_name_mount_paths(repo_id, path, to_parent_path, name)?;
+
+        self.remote_files_service
+            .move_file(&mount_id, &remote_path, &to_mount_id, &to_remote_path)
+            .await
+            .map_err(MoveFileError::RemoteError)
+    }
+
+    /// Checks the new name in to_parent_path and returns the mount paths of
+    /// the file and its new location.
+    fn get_new_name_mount_paths(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        to_parent_path: &EncryptedPath,
+        name: &DecryptedName,
+    ) -> Result<(MountPath, MountPath), CopyFileError> {
+        let typ = self
+            .store
+            .with_state(|state| {
+                selectors::select_file(state, &selectors::get_file_id(repo_id, path))
+                    .map(|file| file.typ.clone())
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        let encrypted_name = match typ {
+            RepoFileType::Dir => self.encrypt_dir_name(repo_id, name)?,
+            RepoFileType::File => self.encrypt_filename(repo_id, name)?,
+        };
+
+        self.store.with_state(|state| {
+            selectors::select_check_new_name_valid(
+                state,
+                repo_id,
+                to_parent_path,
+                name,
+                &encrypted_name,
+            )
+        })?;
+
+        Ok((
+            self.get_repo_mount_path(repo_id, path)?,
+            self.get_repo_mount_path(
+                repo_id,
+                &repo_encrypted_path_utils::join_path_name(to_parent_path, &encrypted_name),
+            )?,
+        ))
+    }
+
+    /// Repairs files with names that cannot be decrypted or are not valid (e.g.
+    /// files uploaded to the repo folder without Vault). New names are derived
+    /// from the remote names. With dry_run nothing is changed and the planned
+    /// items are returned. Otherwise all items are repaired and failures are
+    /// reported per item.
+    pub async fn repair_files(
+        &self,
+        repo_id: &RepoId,
+        paths: &[EncryptedPath],
+        action: RepoFilesRepairAction,
+        dry_run: bool,
+    ) -> Result<Vec<RepoFilesRepairItem>, RepairFilesError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let lost_and_found_path = repo_encrypted_path_utils::join_path_name(
+            &EncryptedPath("/".into()),
+            &cipher.encrypt_dir_name(&DecryptedName(LOST_AND_FOUND_NAME.into())),
+        );
+
+        // new names must not clash with existing names or with each other
+        let mut used_names: HashMap<EncryptedPath, HashSet<DecryptedNameLower>> = HashMap::new();
+
+        let mut items = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            self.load_file(repo_id, path).await?;
+
+            let (typ, remote_name) = match self.store.with_state(|state| {
+                selectors::select_file(state, &selectors::get_file_id(repo_id, path)).map(|file| {
+                    (
+                        file.typ.clone(),
+                        match &file.name {
+                            RepoFileName::Decrypted { .. } => None,
+                            RepoFileName::DecryptError { encrypted_name, .. } => {
+                                Some(encrypted_name.clone())
+                            }
+                        },
+                    )
+                })
+            }) {
+                Some((typ, Some(remote_name))) => (typ, remote_name),
+                Some((_, None)) => return Err(RepairFilesError::NameNotBroken(path.0.clone())),
+                None => return Err(RepoFilesErrors::not_found().into()),
+            };
+
+            let new_parent_path = match action {
+                RepoFilesRepairAction::Rename => repo_encrypted_path_utils::parent_path(path)
+                    .ok_or_else(RepoFilesErrors::invalid_path)?,
+                RepoFilesRepairAction::MoveToLostFound => lost_and_found_path.clone(),
+                RepoFilesRepairAction::Delete => {
+                    items.push(RepoFilesRepairItem {
+                        path: path.clone(),
+                        typ,
+                        action,
+                        new_name: None,
+                        new_path: None,
+                        status: RepoFilesRepairStatus::Planned,
+                    });
+
+                    continue;
+                }
+            };
+
+            if !used_names.contains_key(&new_parent_path) {
+                let parent_used_names = self
+                    .repair_files_used_names(repo_id, &new_parent_path)
+                    .await?;
+
+                used_names.insert(new_parent_path.clone(), parent_used_names);
+            }
+
+            let parent_used_names = used_names.get_mut(&new_parent_path).unwrap();
+
+            let new_name = selectors::get_unused_name(
+                parent_used_names.clone(),
+                &DecryptedName(name_utils::sanitize_name(&remote_name.0)),
+            );
+
+            parent_used_names.insert(new_name.to_lowercase());
+
+            let new_encrypted_name = match typ {
+                RepoFileType::Dir => cipher.encrypt_dir_name(&new_name),
+                RepoFileType::File => cipher.encrypt_filename(&new_name),
+            };
+
+            items.push(RepoFilesRepairItem {
+                path: path.clone(),
+                typ,
+                action,
+                new_name: Some(new_name),
+                new_path: Some(repo_encrypted_path_utils::join_path_name(
+                    &new_parent_path,
+                    &new_encrypted_name,
+                )),
+                status: RepoFilesRepairStatus::Planned,
+            });
+        }
+
+        if dry_run {
+            return Ok(items);
+        }
+
+        if action == RepoFilesRepairAction::MoveToLostFound && !items.is_empty() {
+            self.ensure_dir(repo_id.clone(), lost_and_found_path)
+                .await?;
+        }
+
+        for item in items.iter_mut() {
+            let (mount_id, remote_path) = self.get_repo_mount_path(repo_id, &item.path)?;
+
+            let res = match (&item.action, &item.new_path) {
+                (RepoFilesRepairAction::Rename, Some(new_path)) => {
+                    let new_name = repo_encrypted_path_utils::path_to_name(new_path)
+                        .ok_or_else(RepoFilesErrors::invalid_path)?;
+
+                    self.remote_files_service
+                        .rename_file(&mount_id, &remote_path, RemoteName(new_name.0))
+                        .await
+                }
+                (RepoFilesRepairAction::MoveToLostFound, Some(new_path)) => {
+                    let (to_mount_id, to_remote_path) =
+                        self.get_repo_mount_path(repo_id, new_path)?;
+
+                    self.remote_files_service
+                        .move_file(&mount_id, &remote_path, &to_mount_id, &to_remote_path)
+                        .await
+                }
+                _ => {
+                    self.remote_files_service
+                        .delete_file(&mount_id, &remote_path)
+                        .await
+                }
+            };
+
+            item.status = match res {
+                Ok(()) => RepoFilesRepairStatus::Repaired,
+                Err(error) => RepoFilesRepairStatus::Failed { error },
+            };
+        }
+
+        Ok(items)
+    }
+
+    async fn repair_files_used_names(
+        &self,
+        repo_id: &RepoId,
+        parent_path: &EncryptedPath,
+    ) -> Result<HashSet<DecryptedNameLower>, RepairFilesError> {
+        match self.load_files(repo_id, parent_path).await {
+            Ok(()) => {}
+            // lost+found is created when the files are moved
+            Err(LoadFilesError::RemoteError(err))
+                if err.is_api_error_code(remote::ApiErrorCode::NotFound) =>
+            {
+                return Ok(HashSet::new())
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(self
+            .store
+            .with_state(|state| selectors::select_used_names(state, repo_id, parent_path)))
+    }
+
     pub async fn get_unused_name(
         &self,
         repo_id: &RepoId,
@@ -659,3 +1784,17 @@ impl Drop for RepoFilesService {
             .mutation_remove_listener(self.repos_mutation_subscription_id);
     }
 }
+
+/// Wraps the encrypted reader with an MD5 reader if the upload is verified.
+fn encrypted_md5_reader(
+    reader: BoxAsyncRead,
+    verify: bool,
+) -> (BoxAsyncRead, Option<oneshot::Receiver<md5::Digest>>) {
+    if verify {
+        let (md5_reader, md5_digest_future) = md5_reader::MD5Reader::new(reader);
+
+        (Box::pin(md5_reader), Some(md5_digest_future))
+    } else {
+        (reader, None)
+    }
+}
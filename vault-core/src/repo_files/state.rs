@@ -1,9 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
 
 use crate::{
     cipher::errors::{DecryptFilenameError, DecryptSizeError},
+    common::state::BoxAsyncRead,
     files::{file_category::FileCategory, file_icon::FileIconAttrs},
-    remote::RemoteFileUploadConflictResolution,
+    remote::{RemoteError, RemoteFileUploadConflictResolution},
     remote_files::state::{RemoteFile, RemoteFileType},
     repo_files_tags::{errors::DecryptTagsError, state::RepoFileTags},
     sort::state::SortDirection,
@@ -223,10 +227,12 @@ impl RepoFilesState {
 
 #[derive(Debug, Clone, Default)]
 pub struct RepoFilesMutationState {
+    pub created_files: Vec<(RepoId, EncryptedPath)>,
     pub removed_files: Vec<(RepoId, EncryptedPath)>,
     pub moved_files: Vec<(RepoId, EncryptedPath, EncryptedPath)>,
 }
 
+#[derive(Clone)]
 pub enum RepoFilesUploadConflictResolution {
     Overwrite {
         if_remote_size: Option<i64>,
@@ -254,6 +260,38 @@ impl Into<RemoteFileUploadConflictResolution> for RepoFilesUploadConflictResolut
     }
 }
 
+/// Server upload session of a resumable upload. The file nonce is kept so that
+/// the encrypted blocks that were already uploaded stay valid when the upload
+/// is resumed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoFilesUploadSession {
+    pub session_id: String,
+    pub nonce: vault_crypto::nonce::Nonce,
+}
+
+pub struct RepoFilesUploadResumable {
+    pub size: i64,
+    /// session of the previous attempt. It is set when a new session is
+    /// created and cleared when the upload is done.
+    pub session: Arc<Mutex<Option<RepoFilesUploadSession>>>,
+    /// called with the number of bytes that were already uploaded in previous
+    /// attempts
+    pub on_resume: Option<Box<dyn Fn(i64) + Send + Sync>>,
+    pub on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    /// wraps the reader of the data that is actually uploaded, after the part
+    /// that was already uploaded is skipped
+    pub wrap_reader: Option<Box<dyn FnOnce(BoxAsyncRead) -> BoxAsyncRead + Send + Sync>>,
+}
+
+#[derive(Default)]
+pub struct RepoFilesUploadOptions {
+    pub size: Option<i64>,
+    pub on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    /// wraps the reader of the encrypted data that is uploaded (e.g. to limit
+    /// the bandwidth)
+    pub wrap_reader: Option<Box<dyn FnOnce(BoxAsyncRead) -> BoxAsyncRead + Send + Sync>>,
+}
+
 #[derive(Debug)]
 pub struct RepoFilesUploadResult {
     pub file_id: RepoFileId,
@@ -261,6 +299,38 @@ pub struct RepoFilesUploadResult {
     pub remote_file: RemoteFile,
 }
 
+/// Files with names that cannot be decrypted or are not valid can be renamed
+/// (to an encrypted name derived from the remote name), moved to the lost+found
+/// folder in the repo root or deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoFilesRepairAction {
+    Rename,
+    MoveToLostFound,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoFilesRepairStatus {
+    /// The item was only planned (dry run).
+    Planned,
+    Repaired,
+    Failed {
+        error: RemoteError,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoFilesRepairItem {
+    pub path: EncryptedPath,
+    pub typ: RepoFileType,
+    pub action: RepoFilesRepairAction,
+    /// None for Delete
+    pub new_name: Option<DecryptedName>,
+    /// None for Delete
+    pub new_path: Option<EncryptedPath>,
+    pub status: RepoFilesRepairStatus,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RepoFilesSortField {
     Name,
@@ -297,21 +297,28 @@ pub fn update_browser(
         dirty = true;
     }
 
-    let select_file_id = if let Some(name) = browser.options.select_name.clone().and_then(|name| {
-        cipher
-            .as_deref()
-            .map(|cipher| cipher.encrypt_filename(&name))
-    }) {
-        let file_id = browser
-            .location
-            .as_ref()
-            .map(|loc| {
-                repo_files_selectors::get_file_id(
-                    &loc.repo_id,
-                    &repo_encrypted_path_utils::join_path_name(&loc.path, &name),
-                )
-            })
-            .filter(|file_id| file_ids_set.contains(file_id));
+    // the selected name can be a file or a dir name
+    let select_names = browser.options.select_name.clone().and_then(|name| {
+        cipher.as_deref().map(|cipher| {
+            [
+                cipher.encrypt_filename(&name),
+                cipher.encrypt_dir_name(&name),
+            ]
+        })
+    });
+
+    let select_file_id = if let Some(names) = select_names {
+        let file_id = browser.location.as_ref().and_then(|loc| {
+            names
+                .iter()
+                .map(|name| {
+                    repo_files_selectors::get_file_id(
+                        &loc.repo_id,
+                        &repo_encrypted_path_utils::join_path_name(&loc.path, name),
+                    )
+                })
+                .find(|file_id| file_ids_set.contains(file_id))
+        });
 
         if matches!(&browser.status, Status::Loaded) || file_id.is_some() {
             browser.options.select_name = None;
@@ -28,7 +28,9 @@ pub fn select_file_ids<'a>(
     repo_id: &RepoId,
     path: &EncryptedPath,
 ) -> impl Iterator<Item = &'a RepoFileId> {
-    repo_files_selectors::select_files(state, repo_id, path).map(|file| &file.id)
+    repo_files_selectors::select_files(state, repo_id, path)
+        .filter(|file| !repo_files_selectors::is_hidden_dir(file))
+        .map(|file| &file.id)
 }
 
 pub fn select_browser<'a>(
@@ -0,0 +1,154 @@
+use thiserror::Error;
+
+use crate::{
+    cipher::errors::{DecryptFilenameError, DecryptSizeError},
+    remote::RemoteError,
+    repo_files::errors::{
+        CopyFileError, EnsureDirError, LoadFileError, LoadFilesError, MoveFileError,
+        RepoFilesErrors,
+    },
+    repo_files_list::errors::{FilesListRecursiveItemError, GetListRecursiveError},
+    repo_files_read::errors::GetFilesReaderError,
+    repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
+    transfers::errors::TransferError,
+    user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CrossMoveError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("{0}")]
+    RepoLocked(#[from] RepoLockedError),
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("{0}")]
+    DecryptSizeError(#[from] DecryptSizeError),
+    #[error("move root")]
+    MoveRoot,
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+    #[error("{0}")]
+    TransferError(#[from] TransferError),
+    #[error("copy of {name} could not be verified")]
+    VerifyFailed { name: String },
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for CrossMoveError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::DecryptSizeError(err) => err.user_error(),
+            Self::MoveRoot => "Cannot move root folder".into(),
+            Self::RemoteError(err) => err.user_error(),
+            Self::TransferError(err) => err.user_error(),
+            Self::VerifyFailed { .. } => self.to_string(),
+            Self::IOError(_) => self.to_string(),
+        }
+    }
+}
+
+impl From<GetCipherError> for CrossMoveError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
+impl From<LoadFileError> for CrossMoveError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<LoadFilesError> for CrossMoveError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFilesError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFilesError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<GetListRecursiveError> for CrossMoveError {
+    fn from(err: GetListRecursiveError) -> Self {
+        match err {
+            GetListRecursiveError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetListRecursiveError::RepoLocked(err) => Self::RepoLocked(err),
+            GetListRecursiveError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            GetListRecursiveError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<FilesListRecursiveItemError> for CrossMoveError {
+    fn from(err: FilesListRecursiveItemError) -> Self {
+        match err {
+            FilesListRecursiveItemError::DecryptFilenameError(err) => {
+                Self::DecryptFilenameError(err)
+            }
+            FilesListRecursiveItemError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<EnsureDirError> for CrossMoveError {
+    fn from(err: EnsureDirError) -> Self {
+        match err {
+            EnsureDirError::RepoNotFound(err) => Self::RepoNotFound(err),
+            EnsureDirError::RepoLocked(err) => Self::RepoLocked(err),
+            EnsureDirError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            EnsureDirError::Canceled => Self::TransferError(TransferError::Aborted),
+            EnsureDirError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<GetFilesReaderError> for CrossMoveError {
+    fn from(err: GetFilesReaderError) -> Self {
+        match err {
+            GetFilesReaderError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetFilesReaderError::RepoLocked(err) => Self::RepoLocked(err),
+            GetFilesReaderError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            GetFilesReaderError::DecryptSizeError(err) => Self::DecryptSizeError(err),
+            GetFilesReaderError::RemoteError(err) => Self::RemoteError(err),
+            err => Self::IOError(err.to_string()),
+        }
+    }
+}
+
+impl From<CopyFileError> for CrossMoveError {
+    fn from(err: CopyFileError) -> Self {
+        match err {
+            CopyFileError::InvalidPath => Self::RemoteError(RepoFilesErrors::invalid_path()),
+            CopyFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            CopyFileError::RepoLocked(err) => Self::RepoLocked(err),
+            CopyFileError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            CopyFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<MoveFileError> for CrossMoveError {
+    fn from(err: MoveFileError) -> Self {
+        match err {
+            MoveFileError::InvalidPath => Self::RemoteError(RepoFilesErrors::invalid_path()),
+            MoveFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            MoveFileError::RepoLocked(err) => Self::RepoLocked(err),
+            MoveFileError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            MoveFileError::MoveRoot => Self::MoveRoot,
+            MoveFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod errors;
+pub mod repo_file_uploadable;
+pub mod service;
+
+pub use self::service::RepoFilesCrossMoveService;
@@ -0,0 +1,73 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::channel::oneshot;
+
+use crate::{
+    common::state::{BoxAsyncRead, SizeInfo},
+    repo_files_read::state::RepoFileReaderProvider,
+    transfers::{errors::UploadableError, uploadable::Uploadable},
+    utils::md5_reader,
+};
+
+type DigestReceiver = Arc<Mutex<Option<oneshot::Receiver<md5::Digest>>>>;
+
+/// Uploads a file from another repo. The file is decrypted while it is read
+/// and the MD5 digest of the decrypted content is kept for verification.
+pub struct RepoFileUploadable {
+    reader_provider: RepoFileReaderProvider,
+    digest_receiver: DigestReceiver,
+}
+
+impl RepoFileUploadable {
+    pub fn new(reader_provider: RepoFileReaderProvider) -> (Self, RepoFileUploadableDigest) {
+        let digest_receiver: DigestReceiver = Default::default();
+
+        (
+            Self {
+                reader_provider,
+                digest_receiver: digest_receiver.clone(),
+            },
+            RepoFileUploadableDigest { digest_receiver },
+        )
+    }
+}
+
+#[async_trait]
+impl Uploadable for RepoFileUploadable {
+    async fn size(&self) -> Result<SizeInfo, UploadableError> {
+        Ok(self.reader_provider.size)
+    }
+
+    async fn is_retriable(&self) -> Result<bool, UploadableError> {
+        Ok(true)
+    }
+
+    async fn reader(&self) -> Result<(BoxAsyncRead, SizeInfo), UploadableError> {
+        let reader = self
+            .reader_provider
+            .reader()
+            .await
+            .map_err(|err| UploadableError::LocalFileError(err.to_string()))?;
+
+        let (md5_reader, digest_receiver) = md5_reader::MD5Reader::new(reader.reader);
+
+        // a retry reads the file again
+        *self.digest_receiver.lock().unwrap() = Some(digest_receiver);
+
+        Ok((Box::pin(md5_reader), reader.size))
+    }
+}
+
+pub struct RepoFileUploadableDigest {
+    digest_receiver: DigestReceiver,
+}
+
+impl RepoFileUploadableDigest {
+    /// Digest of the content read by the last upload attempt.
+    pub async fn digest(self) -> Option<md5::Digest> {
+        let digest_receiver = self.digest_receiver.lock().unwrap().take()?;
+
+        digest_receiver.await.ok()
+    }
+}
@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use futures::{future, io, StreamExt};
+use vault_crypto::data_cipher::encrypted_size;
+
+use crate::{
+    cipher::Cipher,
+    remote_files::RemoteFilesService,
+    repo_files::{
+        errors::RepoFilesErrors,
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFileType, RepoFilesUploadResult},
+        RepoFilesService,
+    },
+    repo_files_list::{state::RepoFilesListRecursiveItem, RepoFilesListService},
+    repo_files_move::state::RepoFilesMoveMode,
+    repo_files_read::RepoFilesReadService,
+    repos::ReposService,
+    store,
+    transfers::{state::TransferUploadRelativeName, TransfersService},
+    types::{DecryptedName, DecryptedPath, EncryptedPath, RepoId},
+    utils::{md5_reader, repo_encrypted_path_utils, repo_path_utils},
+};
+
+use super::{
+    errors::CrossMoveError,
+    repo_file_uploadable::{RepoFileUploadable, RepoFileUploadableDigest},
+};
+
+pub struct RepoFilesCrossMoveService {
+    repos_service: Arc<ReposService>,
+    remote_files_service: Arc<RemoteFilesService>,
+    repo_files_service: Arc<RepoFilesService>,
+    repo_files_list_service: Arc<RepoFilesListService>,
+    repo_files_read_service: Arc<RepoFilesReadService>,
+    transfers_service: Arc<TransfersService>,
+    store: Arc<store::Store>,
+}
+
+impl RepoFilesCrossMoveService {
+    pub fn new(
+        repos_service: Arc<ReposService>,
+        remote_files_service: Arc<RemoteFilesService>,
+        repo_files_service: Arc<RepoFilesService>,
+        repo_files_list_service: Arc<RepoFilesListService>,
+        repo_files_read_service: Arc<RepoFilesReadService>,
+        transfers_service: Arc<TransfersService>,
+        store: Arc<store::Store>,
+    ) -> Self {
+        Self {
+            repos_service,
+            remote_files_service,
+            repo_files_service,
+            repo_files_list_service,
+            repo_files_read_service,
+            transfers_service,
+            store,
+        }
+    }
+
+    /// Copies or moves files and dirs into a dir of another repo. Every file
+    /// is decrypted with the source repo cipher, encrypted with the
+    /// destination repo cipher and uploaded as a transfer. With
+    /// RepoFilesMoveMode::Move a source file or dir is deleted only after all
+    /// of its files have been copied and verified.
+    pub async fn move_files(
+        &self,
+        src_repo_id: &RepoId,
+        src_paths: &[EncryptedPath],
+        dest_repo_id: &RepoId,
+        dest_parent_path: &EncryptedPath,
+        mode: RepoFilesMoveMode,
+    ) -> Result<(), CrossMoveError> {
+        if src_repo_id == dest_repo_id {
+            for src_path in src_paths {
+                match mode {
+                    RepoFilesMoveMode::Copy => {
+                        self.repo_files_service
+                            .copy_file(src_repo_id, src_path, dest_parent_path)
+                            .await?
+                    }
+                    RepoFilesMoveMode::Move => {
+                        self.repo_files_service
+                            .move_file(src_repo_id, src_path, dest_parent_path)
+                            .await?
+                    }
+                }
+            }
+
+            return Ok(());
+        }
+
+        self.repos_service.get_cipher(src_repo_id)?;
+        let dest_cipher = self.repos_service.get_cipher(dest_repo_id)?;
+
+        // the trash, versions and search folders are managed by the vault
+        if self
+            .repo_files_service
+            .is_hidden_path(dest_repo_id, dest_parent_path)?
+        {
+            return Err(RepoFilesErrors::invalid_path().into());
+        }
+
+        for src_path in src_paths {
+            if self
+                .repo_files_service
+                .is_hidden_path(src_repo_id, src_path)?
+            {
+                return Err(RepoFilesErrors::invalid_path().into());
+            }
+        }
+
+        self.repo_files_service
+            .load_files(dest_repo_id, dest_parent_path)
+            .await?;
+
+        for src_path in src_paths {
+            self.move_file(
+                src_repo_id,
+                src_path,
+                dest_repo_id,
+                dest_parent_path,
+                &dest_cipher,
+                &mode,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn move_file(
+        &self,
+        src_repo_id: &RepoId,
+        src_path: &EncryptedPath,
+        dest_repo_id: &RepoId,
+        dest_parent_path: &EncryptedPath,
+        dest_cipher: &Cipher,
+        mode: &RepoFilesMoveMode,
+    ) -> Result<(), CrossMoveError> {
+        if src_path.is_root() {
+            return Err(CrossMoveError::MoveRoot);
+        }
+
+        self.repo_files_service
+            .load_file(src_repo_id, src_path)
+            .await?;
+
+        let file = self
+            .store
+            .with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(src_repo_id, src_path),
+                )
+                .cloned()
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        let name = file.decrypted_name()?.to_owned();
+
+        let dest_name = match file.typ {
+            RepoFileType::Dir => dest_cipher.encrypt_dir_name(&name),
+            RepoFileType::File => dest_cipher.encrypt_filename(&name),
+        };
+        let dest_path = repo_encrypted_path_utils::join_path_name(dest_parent_path, &dest_name);
+
+        if self.store.with_state(|state| {
+            repo_files_selectors::select_file(
+                state,
+                &repo_files_selectors::get_file_id(dest_repo_id, &dest_path),
+            )
+            .is_some()
+        }) {
+            return Err(RepoFilesErrors::already_exists().into());
+        }
+
+        let (dirs, files) = match file.typ {
+            RepoFileType::File => (vec![], vec![(name.0.clone(), file.clone())]),
+            RepoFileType::Dir => self.list_dir(&file, &name).await?,
+        };
+
+        // empty dirs would not be created by the uploads
+        for dir in dirs {
+            let path = repo_encrypted_path_utils::join_paths(
+                dest_parent_path,
+                &dest_cipher.encrypt_dir_path(&dir),
+            );
+
+            self.repo_files_service
+                .clone()
+                .ensure_dirs(dest_repo_id, &path)
+                .await?;
+        }
+
+        let mut uploads = Vec::with_capacity(files.len());
+
+        for (relative_name, src_file) in files {
+            let reader_provider = self
+                .repo_files_read_service
+                .clone()
+                .get_files_reader(vec![src_file.clone()])?;
+
+            let (uploadable, digest) = RepoFileUploadable::new(reader_provider);
+
+            let (_, create_future) = self.transfers_service.clone().upload(
+                dest_repo_id.clone(),
+                dest_parent_path.clone(),
+                TransferUploadRelativeName(relative_name),
+                Box::new(uploadable),
+            );
+
+            uploads.push(async move {
+                let res = create_future.await?.await?;
+
+                let src_hash = verify_upload(&src_file, &res, digest).await?;
+
+                // the source is deleted only if the copy can be read back
+                if matches!(mode, RepoFilesMoveMode::Move) {
+                    self.verify_destination(&res, &src_hash).await?;
+                }
+
+                Ok::<_, CrossMoveError>(())
+            });
+        }
+
+        for res in future::join_all(uploads).await {
+            res?;
+        }
+
+        if matches!(mode, RepoFilesMoveMode::Move) {
+            let (mount_id, remote_path) = self
+                .repo_files_service
+                .get_repo_mount_path(src_repo_id, src_path)?;
+
+            self.remote_files_service
+                .delete_file(&mount_id, &remote_path)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the uploaded file back from the destination repo and compares
+    /// the decrypted content with the source hash.
+    async fn verify_destination(
+        &self,
+        res: &RepoFilesUploadResult,
+        src_hash: &str,
+    ) -> Result<(), CrossMoveError> {
+        let verify_failed = || CrossMoveError::VerifyFailed {
+            name: res.name.0.clone(),
+        };
+
+        let dest_file = self
+            .store
+            .with_state(|state| repo_files_selectors::select_file(state, &res.file_id).cloned())
+            .ok_or_else(verify_failed)?;
+
+        let reader = self
+            .repo_files_read_service
+            .clone()
+            .get_files_reader(vec![dest_file])?
+            .reader()
+            .await?;
+
+        let (mut md5_reader, digest_receiver) = md5_reader::MD5Reader::new(reader.reader);
+
+        io::copy(&mut md5_reader, &mut io::sink())
+            .await
+            .map_err(|err| CrossMoveError::IOError(err.to_string()))?;
+
+        drop(md5_reader);
+
+        let digest = digest_receiver.await.map_err(|_| verify_failed())?;
+
+        if hex::encode(digest.0) != src_hash {
+            return Err(verify_failed());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the dirs (including the dir itself) as decrypted paths and the
+    /// files with their names relative to the dir's parent.
+    async fn list_dir(
+        &self,
+        dir: &RepoFile,
+        name: &DecryptedName,
+    ) -> Result<(Vec<DecryptedPath>, Vec<(String, RepoFile)>), CrossMoveError> {
+        let root_path = DecryptedPath(format!("/{}", name.0));
+
+        let items = self
+            .repo_files_list_service
+            .get_list_recursive(dir)
+            .await?
+            .collect::<Vec<RepoFilesListRecursiveItem>>()
+            .await;
+
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+
+        for item in items {
+            match item {
+                RepoFilesListRecursiveItem::File {
+                    relative_repo_path,
+                    file,
+                } => {
+                    let path = repo_path_utils::join_paths(&root_path, &relative_repo_path?);
+
+                    match file.typ {
+                        RepoFileType::Dir => dirs.push(path),
+                        RepoFileType::File => files.push((path.0[1..].to_owned(), file)),
+                    }
+                }
+                RepoFilesListRecursiveItem::Error { error, .. } => return Err(error.into()),
+            }
+        }
+
+        Ok((dirs, files))
+    }
+}
+
+/// The uploaded file must have the encrypted size of the source file and the
+/// content read from the source must match the source hash if it is known.
+/// Returns the hash of the content read from the source.
+async fn verify_upload(
+    src_file: &RepoFile,
+    res: &RepoFilesUploadResult,
+    digest: RepoFileUploadableDigest,
+) -> Result<String, CrossMoveError> {
+    let verify_failed = || CrossMoveError::VerifyFailed {
+        name: res.name.0.clone(),
+    };
+
+    if let Some(size) = src_file.decrypted_size()? {
+        if res.remote_file.size != Some(encrypted_size(size)) {
+            return Err(verify_failed());
+        }
+    }
+
+    let digest = hex::encode(digest.digest().await.ok_or_else(verify_failed)?.0);
+
+    if let Some(hash) = src_file.hash() {
+        if digest != hash {
+            return Err(verify_failed());
+        }
+    }
+
+    Ok(digest)
+}
@@ -3,7 +3,8 @@ use thiserror::Error;
 use crate::{
     cipher::errors::DecryptFilenameError,
     remote::RemoteError,
-    repo_files::errors::{LoadFilesError, UploadFileReaderError},
+    repo_files::errors::{LoadFileError, LoadFilesError, UploadFileReaderError},
+    repo_files_read::errors::GetFilesReaderError,
     repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
     transfers::errors::TransferError,
     user_error::UserError,
@@ -104,8 +105,12 @@ pub enum SaveError {
     Canceled,
     #[error("cannot save root")]
     CannotSaveRoot,
+    #[error("merge conflict")]
+    MergeConflict,
     #[error("{0}")]
     RemoteError(#[from] RemoteError),
+    #[error("{0}")]
+    IOError(String),
 }
 
 impl UserError for SaveError {
@@ -122,7 +127,9 @@ impl UserError for SaveError {
             Self::DiscardChanges { .. } => self.to_string(),
             Self::Canceled => self.to_string(),
             Self::CannotSaveRoot => self.to_string(),
+            Self::MergeConflict => String::from("File was changed by someone else since your last save and the changes could not be merged automatically. Resolve the conflicts to save the file."),
             Self::RemoteError(err) => err.user_error(),
+            Self::IOError(_) => self.to_string(),
         }
     }
 }
@@ -146,6 +153,29 @@ impl From<LoadFilesError> for SaveError {
     }
 }
 
+impl From<LoadFileError> for SaveError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<GetFilesReaderError> for SaveError {
+    fn from(err: GetFilesReaderError) -> Self {
+        match err {
+            GetFilesReaderError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetFilesReaderError::RepoLocked(err) => Self::RepoLocked(err),
+            GetFilesReaderError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            GetFilesReaderError::RemoteError(err) => Self::RemoteError(err),
+            GetFilesReaderError::Aborted => Self::Canceled,
+            err => Self::IOError(err.to_string()),
+        }
+    }
+}
+
 impl From<UploadFileReaderError> for SaveError {
     fn from(err: UploadFileReaderError) -> Self {
         match err {
@@ -154,6 +184,8 @@ impl From<UploadFileReaderError> for SaveError {
             UploadFileReaderError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
             UploadFileReaderError::Canceled => Self::Canceled,
             UploadFileReaderError::RemoteError(err) => Self::RemoteError(err),
+            UploadFileReaderError::IOError(err) => Self::IOError(err),
+            UploadFileReaderError::HashMismatch { .. } => Self::IOError(err.to_string()),
         }
     }
 }
@@ -10,7 +10,7 @@ use crate::{
     repo_files::{
         errors::{DeleteFileError, LoadFilesError},
         selectors as repo_files_selectors,
-        state::{RepoFile, RepoFilesUploadResult},
+        state::RepoFile,
     },
     repo_files_read::errors::GetFilesReaderError,
     repos::{self, errors::RepoLockedError},
@@ -26,7 +26,8 @@ use super::{
     state::{
         RepoFilesDetails, RepoFilesDetailsContent, RepoFilesDetailsContentData,
         RepoFilesDetailsContentDataBytes, RepoFilesDetailsContentLoading, RepoFilesDetailsLocation,
-        RepoFilesDetailsOptions, SaveInitiator,
+        RepoFilesDetailsMergeConflict, RepoFilesDetailsOptions, RepoFilesDetailsSaved,
+        SaveInitiator,
     },
 };
 
@@ -95,6 +96,7 @@ pub fn create_location(
         is_editing,
         is_dirty: false,
         save_status: Status::Initial,
+        merge_conflict: None,
         delete_status: Status::Initial,
         should_destroy: false,
     })
@@ -568,6 +570,7 @@ pub fn edit_cancel(
         location.is_editing = false;
         location.is_dirty = false;
         location.save_status = Status::Initial;
+        location.merge_conflict = None;
 
         if is_discarded {
             // this will reload the content
@@ -687,7 +690,7 @@ pub fn saved(
     notify: &store::Notify,
     details_id: u32,
     saved_version: u32,
-    res: Result<(EncryptedPath, RepoFilesUploadResult, bool), SaveError>,
+    res: Result<RepoFilesDetailsSaved, SaveError>,
 ) {
     let location = match selectors::select_details_location_mut(state, details_id) {
         Some(location) => location,
@@ -697,20 +700,42 @@ pub fn saved(
     notify(store::Event::RepoFilesDetails);
 
     match res {
-        Ok((saved_path, result, should_destroy)) => {
-            if location.path != saved_path {
-                location.path = saved_path;
+        Ok(saved) => {
+            if location.path != saved.path {
+                location.path = saved.path;
             }
-            if let Some(data) = &mut location.content.data {
-                data.remote_size = result.remote_file.size;
-                data.remote_modified = result.remote_file.modified;
-                data.remote_hash = result.remote_file.hash;
+            let is_current_version = location.content.version == saved_version;
+            // if the content was changed while saving merged changes, the
+            // next save will merge again against the previous base
+            if is_current_version || !saved.is_merged {
+                if let Some(data) = &mut location.content.data {
+                    data.remote_size = saved.upload_result.remote_file.size;
+                    data.remote_modified = saved.upload_result.remote_file.modified;
+                    data.remote_hash = saved.upload_result.remote_file.hash;
+
+                    if is_current_version && saved.is_merged {
+                        if let RepoFilesDetailsContentDataBytes::Decrypted(bytes, _) =
+                            &mut data.bytes
+                        {
+                            if bytes != &saved.bytes {
+                                *bytes = saved.bytes.clone();
+
+                                location.content.version += 1;
+
+                                notify(store::Event::RepoFilesDetailsContentData);
+                            }
+                        }
+                    }
+
+                    data.remote_bytes = Some(saved.bytes);
+                }
             }
-            if location.content.version == saved_version {
+            if is_current_version {
                 location.is_dirty = false;
             }
             location.save_status = Status::Initial;
-            if should_destroy {
+            location.merge_conflict = None;
+            if saved.should_destroy {
                 location.should_destroy = true;
             }
         }
@@ -736,6 +761,69 @@ pub fn saved(
     }
 }
 
+pub fn merge_conflict(
+    state: &mut store::State,
+    notify: &store::Notify,
+    details_id: u32,
+    merge_conflict: RepoFilesDetailsMergeConflict,
+) {
+    let location = match selectors::select_details_location_mut(state, details_id) {
+        Some(location) => location,
+        _ => return,
+    };
+
+    notify(store::Event::RepoFilesDetails);
+
+    location.merge_conflict = Some(merge_conflict);
+}
+
+/// Replaces the content with the resolved content. The remote file at the
+/// time of the conflict becomes the new base so that the next save overwrites
+/// it.
+pub fn merge_conflict_resolved(
+    state: &mut store::State,
+    notify: &store::Notify,
+    details_id: u32,
+    content: Vec<u8>,
+) -> Result<(), SaveError> {
+    let location = match selectors::select_details_location_mut(state, details_id) {
+        Some(location) => location,
+        _ => return Err(SaveError::InvalidState),
+    };
+
+    let data = match &mut location.content.data {
+        Some(data) => data,
+        None => return Err(SaveError::InvalidState),
+    };
+
+    let bytes = match &mut data.bytes {
+        RepoFilesDetailsContentDataBytes::Encrypted(_) => {
+            return Err(SaveError::RepoLocked(RepoLockedError))
+        }
+        RepoFilesDetailsContentDataBytes::Decrypted(bytes, _) => bytes,
+    };
+
+    let merge_conflict = location
+        .merge_conflict
+        .take()
+        .ok_or(SaveError::InvalidState)?;
+
+    *bytes = content;
+    data.remote_size = merge_conflict.remote_size;
+    data.remote_modified = merge_conflict.remote_modified;
+    data.remote_hash = merge_conflict.remote_hash;
+    data.remote_bytes = Some(merge_conflict.remote_bytes);
+
+    location.content.version += 1;
+    location.is_dirty = true;
+    location.save_status = Status::Initial;
+
+    notify(store::Event::RepoFilesDetails);
+    notify(store::Event::RepoFilesDetailsContentData);
+
+    Ok(())
+}
+
 pub fn deleting(state: &mut store::State, notify: &store::Notify, details_id: u32) {
     let location = match selectors::select_details_location_mut(state, details_id) {
         Some(location) => location,
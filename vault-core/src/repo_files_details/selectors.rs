@@ -1,3 +1,5 @@
+use similar::{Algorithm, DiffOp};
+
 use crate::{
     common::state::Status,
     remote::ApiErrorCode,
@@ -25,7 +27,7 @@ use super::{
     state::{
         RepoFilesDetails, RepoFilesDetailsContent, RepoFilesDetailsContentData,
         RepoFilesDetailsContentDataBytes, RepoFilesDetailsContentLoading, RepoFilesDetailsInfo,
-        RepoFilesDetailsLocation,
+        RepoFilesDetailsLocation, RepoFilesDetailsMergeConflict, RepoFilesDetailsMergeHunk,
     },
 };
 
@@ -158,6 +160,7 @@ pub fn get_is_save_conflict(status: &Status<SaveError>) -> bool {
     match status {
         Status::Error { error, .. } => match error {
             SaveError::RemoteError(error) => error.is_api_error_code(ApiErrorCode::Conflict),
+            SaveError::MergeConflict => true,
             _ => false,
         },
         _ => false,
@@ -176,7 +179,13 @@ pub fn get_is_conflict(
 }
 
 pub fn get_save_error(status: &Status<SaveError>) -> Option<String> {
-    if get_is_save_conflict(status) {
+    if let Status::Error {
+        error: error @ SaveError::MergeConflict,
+        ..
+    } = status
+    {
+        Some(error.user_error())
+    } else if get_is_save_conflict(status) {
         get_conflict_error(true)
     } else {
         match status {
@@ -415,6 +424,13 @@ pub fn select_content<'a>(
         .map(|location| &location.content)
 }
 
+pub fn select_merge_conflict<'a>(
+    state: &'a store::State,
+    details_id: u32,
+) -> Option<&'a RepoFilesDetailsMergeConflict> {
+    select_details_location(state, details_id).and_then(|loc| loc.merge_conflict.as_ref())
+}
+
 pub fn select_content_data<'a>(
     state: &'a store::State,
     details_id: u32,
@@ -534,3 +550,165 @@ pub fn select_unlocked_details(
 
     details_ids
 }
+
+/// For every base line returns the index of the same line in other if the
+/// line is unchanged.
+fn get_merge_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+
+    for op in similar::capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for i in 0..len {
+                matches[old_index + i] = Some(new_index + i);
+            }
+        }
+    }
+
+    matches
+}
+
+fn push_merge_resolved(hunks: &mut Vec<RepoFilesDetailsMergeHunk>, lines: &[&str]) {
+    if lines.is_empty() {
+        return;
+    }
+
+    match hunks.last_mut() {
+        Some(RepoFilesDetailsMergeHunk::Resolved { content }) => content.push_str(&lines.concat()),
+        _ => hunks.push(RepoFilesDetailsMergeHunk::Resolved {
+            content: lines.concat(),
+        }),
+    }
+}
+
+fn push_merge_chunk(
+    hunks: &mut Vec<RepoFilesDetailsMergeHunk>,
+    base: &[&str],
+    ours: &[&str],
+    theirs: &[&str],
+) {
+    if ours == base || ours == theirs {
+        push_merge_resolved(hunks, theirs);
+    } else if theirs == base {
+        push_merge_resolved(hunks, ours);
+    } else {
+        hunks.push(RepoFilesDetailsMergeHunk::Conflict {
+            base: base.concat(),
+            ours: ours.concat(),
+            theirs: theirs.concat(),
+        });
+    }
+}
+
+/// Line based three-way merge of the local changes (ours) and the remote
+/// changes (theirs) made to base. Lines are split after the line endings so
+/// that the concatenated hunks are the merged content.
+pub fn get_merge_hunks(base: &str, ours: &str, theirs: &str) -> Vec<RepoFilesDetailsMergeHunk> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+
+    let ours_matches = get_merge_matches(&base_lines, &ours_lines);
+    let theirs_matches = get_merge_matches(&base_lines, &theirs_lines);
+
+    let mut hunks = Vec::new();
+
+    let (mut base_index, mut ours_index, mut theirs_index) = (0, 0, 0);
+
+    loop {
+        // the next base line unchanged in both ours and theirs
+        let sync = (base_index..base_lines.len()).find_map(|i| {
+            match (ours_matches[i], theirs_matches[i]) {
+                (Some(ours_i), Some(theirs_i)) => Some((i, ours_i, theirs_i)),
+                _ => None,
+            }
+        });
+
+        let (base_end, ours_end, theirs_end) =
+            sync.unwrap_or((base_lines.len(), ours_lines.len(), theirs_lines.len()));
+
+        push_merge_chunk(
+            &mut hunks,
+            &base_lines[base_index..base_end],
+            &ours_lines[ours_index..ours_end],
+            &theirs_lines[theirs_index..theirs_end],
+        );
+
+        match sync {
+            Some((base_i, ours_i, theirs_i)) => {
+                push_merge_resolved(&mut hunks, &base_lines[base_i..base_i + 1]);
+
+                base_index = base_i + 1;
+                ours_index = ours_i + 1;
+                theirs_index = theirs_i + 1;
+            }
+            None => break,
+        }
+    }
+
+    hunks
+}
+
+/// Returns the merged content if there are no conflicts.
+pub fn get_merged_content(hunks: &[RepoFilesDetailsMergeHunk]) -> Option<String> {
+    hunks
+        .iter()
+        .map(|hunk| match hunk {
+            RepoFilesDetailsMergeHunk::Resolved { content } => Some(content.as_str()),
+            RepoFilesDetailsMergeHunk::Conflict { .. } => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use crate::repo_files_details::state::RepoFilesDetailsMergeHunk;
+
+    use super::{get_merge_hunks, get_merged_content};
+
+    fn merge(base: &str, ours: &str, theirs: &str) -> Option<String> {
+        get_merged_content(&get_merge_hunks(base, ours, theirs))
+    }
+
+    #[test]
+    fn test_merge_clean() {
+        assert_eq!(
+            merge("a\nb\nc\nd\n", "A\nb\nc\nd\n", "a\nb\nc\nD\n"),
+            Some("A\nb\nc\nD\n".into())
+        );
+        assert_eq!(
+            merge("a\nb\n", "a\nb\nours\n", "theirs\na\nb\n"),
+            Some("theirs\na\nb\nours\n".into())
+        );
+        assert_eq!(merge("a\nb", "a\nB", "a\nB"), Some("a\nB".into()));
+        assert_eq!(merge("a\nb\n", "a\n", "a\nb\n"), Some("a\n".into()));
+        assert_eq!(merge("", "", "x"), Some("x".into()));
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        assert_eq!(
+            get_merge_hunks("a\nb\nc\n", "a\nours\nc\n", "a\ntheirs\nc\n"),
+            vec![
+                RepoFilesDetailsMergeHunk::Resolved {
+                    content: "a\n".into()
+                },
+                RepoFilesDetailsMergeHunk::Conflict {
+                    base: "b\n".into(),
+                    ours: "ours\n".into(),
+                    theirs: "theirs\n".into(),
+                },
+                RepoFilesDetailsMergeHunk::Resolved {
+                    content: "c\n".into()
+                },
+            ]
+        );
+        assert_eq!(merge("a\n", "a\nb\n", "a\nc\n"), None);
+    }
+}
@@ -13,19 +13,28 @@ use futures::{
 use crate::{
     common::state::SizeInfo,
     dialogs::{self, state::DialogShowOptions},
+    files::file_category::FileCategory,
     http::HttpError,
     remote::{ApiErrorCode, RemoteError},
-    remote_files::errors::RemoteFilesErrors,
+    remote_files::{errors::RemoteFilesErrors, state::RemoteFile},
     repo_files::{
         errors::{DeleteFileError, UploadFileReaderError},
-        state::{RepoFile, RepoFilesUploadConflictResolution, RepoFilesUploadResult},
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFilesUploadConflictResolution},
         RepoFilesService,
     },
     repo_files_read::{
         errors::GetFilesReaderError,
-        state::{RepoFileReader, RepoFileReaderBuilder, RepoFileReaderProvider},
+        state::{
+            RepoFileReader, RepoFileReaderBuilder, RepoFileReaderProvider, RepoFileReaderRange,
+        },
         RepoFilesReadService,
     },
+    repo_versions::{
+        errors::RepoVersionsError,
+        state::{RepoVersion, RepoVersionDiffLine},
+        RepoVersionsService,
+    },
     repos::ReposService,
     runtime, store,
     transfers::{downloadable::BoxDownloadable, errors::TransferError, TransfersService},
@@ -38,8 +47,9 @@ use super::{
     errors::{LoadContentError, LoadDetailsError, SaveError, SetContentError},
     mutations, selectors,
     state::{
-        RepoFilesDetailsContentData, RepoFilesDetailsContentDataBytes, RepoFilesDetailsOptions,
-        SaveInitiator,
+        RepoFilesDetailsContentData, RepoFilesDetailsContentDataBytes,
+        RepoFilesDetailsMergeConflict, RepoFilesDetailsMergeHunk, RepoFilesDetailsOptions,
+        RepoFilesDetailsSaved, SaveInitiator,
     },
 };
 
@@ -47,6 +57,7 @@ pub struct RepoFilesDetailsService {
     repos_service: Arc<ReposService>,
     repo_files_service: Arc<RepoFilesService>,
     repo_files_read_service: Arc<RepoFilesReadService>,
+    repo_versions_service: Arc<RepoVersionsService>,
     dialogs_service: Arc<dialogs::DialogsService>,
     transfers_service: Arc<TransfersService>,
     store: Arc<store::Store>,
@@ -62,6 +73,7 @@ impl RepoFilesDetailsService {
         repos_service: Arc<ReposService>,
         repo_files_service: Arc<RepoFilesService>,
         repo_files_read_service: Arc<RepoFilesReadService>,
+        repo_versions_service: Arc<RepoVersionsService>,
         dialogs_service: Arc<dialogs::DialogsService>,
         transfers_service: Arc<TransfersService>,
         store: Arc<store::Store>,
@@ -115,6 +127,7 @@ impl RepoFilesDetailsService {
             repos_service,
             repo_files_service,
             repo_files_read_service,
+            repo_versions_service,
             dialogs_service,
             transfers_service,
             store,
@@ -316,10 +329,11 @@ impl RepoFilesDetailsService {
                         let remote_file = reader.remote_file.unwrap();
 
                         Ok(Some(RepoFilesDetailsContentData {
-                            bytes: RepoFilesDetailsContentDataBytes::Decrypted(buf, cipher),
+                            bytes: RepoFilesDetailsContentDataBytes::Decrypted(buf.clone(), cipher),
                             remote_size: remote_file.size,
                             remote_modified: remote_file.modified,
                             remote_hash: remote_file.hash,
+                            remote_bytes: Some(buf),
                         }))
                     }
                     Err(err) => Err(TransferError::RemoteError(RemoteError::HttpError(
@@ -381,6 +395,20 @@ impl RepoFilesDetailsService {
             }))
     }
 
+    /// Returns a reader for a decrypted byte range of the file (e.g. for
+    /// seeking in video players). Ranged reads do not load the details content.
+    pub async fn get_file_reader_range(
+        self: Arc<Self>,
+        details_id: u32,
+        range: &RepoFileReaderRange,
+    ) -> Result<RepoFileReader, GetFilesReaderError> {
+        let file = self.clone().get_file(details_id).await?;
+
+        self.repo_files_read_service
+            .get_file_reader_range(&file, range)
+            .await
+    }
+
     async fn get_file_reader_wrap_reader_builder(
         self: Arc<Self>,
         reader_builder: &RepoFileReaderBuilder,
@@ -533,6 +561,19 @@ impl RepoFilesDetailsService {
             .await
     }
 
+    /// Saves the content with the merge conflicts resolved by the user.
+    pub async fn resolve_merge_conflict(
+        self: Arc<Self>,
+        details_id: u32,
+        content: Vec<u8>,
+    ) -> Result<(), SaveError> {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::merge_conflict_resolved(state, notify, details_id, content)
+        })?;
+
+        self.save(details_id).await
+    }
+
     async fn save_initiator(
         self: Arc<Self>,
         details_id: u32,
@@ -543,7 +584,7 @@ impl RepoFilesDetailsService {
 
         let res = self
             .clone()
-            .save_inner(initiator, repo_id, path, name, data, is_deleted)
+            .save_inner(details_id, initiator, repo_id, path, name, data, is_deleted)
             .await;
 
         let res_err = res.as_ref().map(|_| ()).map_err(|err| err.clone());
@@ -597,14 +638,16 @@ impl RepoFilesDetailsService {
 
     async fn save_inner(
         self: Arc<Self>,
+        details_id: u32,
         initiator: SaveInitiator,
         repo_id: RepoId,
         path: EncryptedPath,
         original_name: EncryptedName,
-        data: RepoFilesDetailsContentData,
+        mut data: RepoFilesDetailsContentData,
         is_deleted: bool,
-    ) -> Result<(EncryptedPath, RepoFilesUploadResult, bool), SaveError> {
+    ) -> Result<RepoFilesDetailsSaved, SaveError> {
         let mut autorename = false;
+        let mut is_merged = false;
 
         let cipher = self.repos_service.get_cipher(&repo_id)?;
 
@@ -613,15 +656,13 @@ impl RepoFilesDetailsService {
         let mut parent_path = repo_encrypted_path_utils::parent_path(&path)
             .ok_or_else(|| SaveError::CannotSaveRoot)?;
 
-        let bytes = match &data.bytes {
+        let mut bytes = match &data.bytes {
             RepoFilesDetailsContentDataBytes::Encrypted(bytes) => cipher
                 .decrypt_vec(&bytes)
                 .map_err(|err| SaveError::DecryptDataError(err.to_string()))?,
             RepoFilesDetailsContentDataBytes::Decrypted(bytes, _) => bytes.clone(),
         };
 
-        let size = Some(bytes.len() as i64);
-
         if is_deleted {
             self.save_deleted_confirm_new_location(&initiator, &original_name)
                 .await?;
@@ -644,6 +685,7 @@ impl RepoFilesDetailsService {
             let encrypted_name = cipher.encrypt_filename(&name);
             let path = repo_encrypted_path_utils::join_path_name(&parent_path, &encrypted_name);
 
+            let size = Some(bytes.len() as i64);
             let reader = Box::pin(Cursor::new(bytes.clone()));
 
             return match self
@@ -660,18 +702,65 @@ impl RepoFilesDetailsService {
                 )
                 .await
             {
-                Ok(res) => {
+                Ok(upload_result) => {
                     if is_deleted {
                         self.save_show_location_changed_alert(original_name.to_owned());
-
-                        Ok((path, res, true))
-                    } else {
-                        Ok((path, res, false))
                     }
+
+                    Ok(RepoFilesDetailsSaved {
+                        path,
+                        upload_result,
+                        bytes,
+                        is_merged,
+                        should_destroy: is_deleted,
+                    })
                 }
                 Err(UploadFileReaderError::RemoteError(err))
                     if err.is_api_error_code(ApiErrorCode::Conflict) =>
                 {
+                    let merge = match (&data.remote_bytes, autorename) {
+                        (Some(base), false) => {
+                            self.save_merge(&repo_id, &path, base, &bytes).await?
+                        }
+                        _ => None,
+                    };
+
+                    if let Some((hunks, remote_file, remote_bytes)) = merge {
+                        match selectors::get_merged_content(&hunks) {
+                            Some(merged) => {
+                                bytes = merged.into_bytes();
+                                data.remote_size = remote_file.size;
+                                data.remote_modified = remote_file.modified;
+                                data.remote_hash = remote_file.hash;
+                                data.remote_bytes = Some(remote_bytes);
+                                is_merged = true;
+
+                                continue;
+                            }
+                            // the conflicts are resolved by the user, cancel
+                            // has no user interface to do that
+                            None if !matches!(initiator, SaveInitiator::Cancel) => {
+                                self.store.mutate(|state, notify, _, _| {
+                                    mutations::merge_conflict(
+                                        state,
+                                        notify,
+                                        details_id,
+                                        RepoFilesDetailsMergeConflict {
+                                            hunks,
+                                            remote_size: remote_file.size,
+                                            remote_modified: remote_file.modified,
+                                            remote_hash: remote_file.hash,
+                                            remote_bytes,
+                                        },
+                                    );
+                                });
+
+                                return Err(SaveError::MergeConflict);
+                            }
+                            None => {}
+                        }
+                    }
+
                     match self.save_handle_conflict(&initiator).await {
                         Ok(true) => {
                             autorename = true;
@@ -687,6 +776,58 @@ impl RepoFilesDetailsService {
         }
     }
 
+    /// Loads the current remote content and merges the local changes into
+    /// it. Returns None if the file is not a text file.
+    async fn save_merge(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        base: &[u8],
+        ours: &[u8],
+    ) -> Result<Option<(Vec<RepoFilesDetailsMergeHunk>, RemoteFile, Vec<u8>)>, SaveError> {
+        let (base, ours) = match (std::str::from_utf8(base), std::str::from_utf8(ours)) {
+            (Ok(base), Ok(ours)) => (base, ours),
+            _ => return Ok(None),
+        };
+
+        self.repo_files_service.load_file(repo_id, path).await?;
+
+        let file = match self.store.with_state(|state| {
+            repo_files_selectors::select_file(
+                state,
+                &repo_files_selectors::get_file_id(repo_id, path),
+            )
+            .cloned()
+        }) {
+            Some(file) if file.category == FileCategory::Text => file,
+            _ => return Ok(None),
+        };
+
+        let mut reader = self
+            .repo_files_read_service
+            .clone()
+            .get_files_reader(vec![file])?
+            .reader()
+            .await?;
+
+        let mut theirs = Vec::new();
+
+        reader
+            .reader
+            .read_to_end(&mut theirs)
+            .await
+            .map_err(|err| SaveError::IOError(err.to_string()))?;
+
+        let remote_file = reader.remote_file.ok_or(SaveError::InvalidState)?;
+
+        let hunks = match std::str::from_utf8(&theirs) {
+            Ok(theirs) => selectors::get_merge_hunks(base, ours, theirs),
+            Err(_) => return Ok(None),
+        };
+
+        Ok(Some((hunks, remote_file, theirs)))
+    }
+
     async fn save_deleted_confirm_new_location(
         &self,
         initiator: &SaveInitiator,
@@ -877,6 +1018,62 @@ impl RepoFilesDetailsService {
         }
     }
 
+    pub async fn get_versions(
+        &self,
+        details_id: u32,
+    ) -> Result<Vec<RepoVersion>, RepoVersionsError> {
+        let (repo_id, path) = self.get_versions_repo_id_path(details_id)?;
+
+        self.repo_versions_service.list(&repo_id, &path).await
+    }
+
+    pub async fn get_version_file_reader(
+        &self,
+        details_id: u32,
+        version_path: &EncryptedPath,
+    ) -> Result<RepoFileReaderProvider, RepoVersionsError> {
+        let (repo_id, path) = self.get_versions_repo_id_path(details_id)?;
+
+        self.repo_versions_service
+            .get_file_reader(&repo_id, &path, version_path)
+            .await
+    }
+
+    pub async fn get_version_diff(
+        &self,
+        details_id: u32,
+        version_path: &EncryptedPath,
+    ) -> Result<Vec<RepoVersionDiffLine>, RepoVersionsError> {
+        let (repo_id, path) = self.get_versions_repo_id_path(details_id)?;
+
+        self.repo_versions_service
+            .diff(&repo_id, &path, version_path)
+            .await
+    }
+
+    /// Overwrites the file with the version. The details content is reloaded
+    /// because the remote file changed.
+    pub async fn restore_version(
+        &self,
+        details_id: u32,
+        version_path: &EncryptedPath,
+    ) -> Result<(), RepoVersionsError> {
+        let (repo_id, path) = self.get_versions_repo_id_path(details_id)?;
+
+        self.repo_versions_service
+            .restore(&repo_id, &path, version_path)
+            .await
+    }
+
+    fn get_versions_repo_id_path(
+        &self,
+        details_id: u32,
+    ) -> Result<(RepoId, EncryptedPath), RepoVersionsError> {
+        self.store
+            .with_state(|state| selectors::select_repo_id_path_owned(state, details_id))
+            .ok_or(RepoVersionsError::FileNotFound)
+    }
+
     async fn file_removed(&self, details_id: u32) {
         if let Some(file_name) = self.store.with_state(|state| {
             if selectors::select_is_not_deleting_or_deleted(state, details_id) {
@@ -5,7 +5,10 @@ use crate::{
     common::state::Status,
     eventstream::state::MountSubscription,
     files::{file_category::FileCategory, files_filter::FilesFilter},
-    repo_files::errors::{DeleteFileError, LoadFilesError},
+    repo_files::{
+        errors::{DeleteFileError, LoadFilesError},
+        state::RepoFilesUploadResult,
+    },
     repos::errors::RepoInfoError,
     store::NextId,
     transfers::errors::TransferError,
@@ -63,6 +66,9 @@ pub struct RepoFilesDetailsContentData {
     pub remote_size: Option<i64>,
     pub remote_modified: Option<i64>,
     pub remote_hash: Option<String>,
+    /// Decrypted content of the remote file at the time it was loaded or
+    /// saved. It is used as the base when merging conflicting changes.
+    pub remote_bytes: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -81,6 +87,29 @@ pub struct RepoFilesDetailsContent {
     pub transfer_id: Option<u32>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoFilesDetailsMergeHunk {
+    Resolved {
+        content: String,
+    },
+    Conflict {
+        base: String,
+        ours: String,
+        theirs: String,
+    },
+}
+
+/// Conflicting changes that could not be merged automatically. `ours` are the
+/// local changes and `theirs` are the changes in the remote file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoFilesDetailsMergeConflict {
+    pub hunks: Vec<RepoFilesDetailsMergeHunk>,
+    pub remote_size: Option<i64>,
+    pub remote_modified: Option<i64>,
+    pub remote_hash: Option<String>,
+    pub remote_bytes: Vec<u8>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RepoFilesDetailsLocation {
     pub repo_id: RepoId,
@@ -92,6 +121,7 @@ pub struct RepoFilesDetailsLocation {
     pub is_editing: bool,
     pub is_dirty: bool,
     pub save_status: Status<SaveError>,
+    pub merge_conflict: Option<RepoFilesDetailsMergeConflict>,
     pub delete_status: Status<DeleteFileError>,
     pub should_destroy: bool,
 }
@@ -128,6 +158,15 @@ impl RepoFilesDetailsState {
     }
 }
 
+#[derive(Debug)]
+pub struct RepoFilesDetailsSaved {
+    pub path: EncryptedPath,
+    pub upload_result: RepoFilesUploadResult,
+    pub bytes: Vec<u8>,
+    pub is_merged: bool,
+    pub should_destroy: bool,
+}
+
 #[derive(Debug, Clone)]
 pub enum SaveInitiator {
     User,
@@ -43,7 +43,8 @@ pub fn decrypt_files_list_recursive_item(
                         .unwrap()
                         .to_owned(),
                 );
-                let decrypted_item_parent_path = cipher.decrypt_path(&encrypted_item_parent_path);
+                let decrypted_item_parent_path =
+                    cipher.decrypt_dir_path(&encrypted_item_parent_path);
                 let encrypted_parent_path = EncryptedPath(path_utils::join_paths(
                     &encrypted_root_path.0,
                     &encrypted_item_parent_path.0,
@@ -17,6 +17,8 @@ pub enum GetFilesReaderError {
     FileNotFound,
     #[error("files empty")]
     FilesEmpty,
+    #[error("invalid range")]
+    InvalidRange,
     #[error("{0}")]
     DecryptFilenameError(#[from] DecryptFilenameError),
     #[error("{0}")]
@@ -36,6 +38,7 @@ impl UserError for GetFilesReaderError {
             Self::RepoLocked(err) => err.user_error(),
             Self::FileNotFound => self.to_string(),
             Self::FilesEmpty => self.to_string(),
+            Self::InvalidRange => self.to_string(),
             Self::DecryptFilenameError(err) => err.user_error(),
             Self::DecryptSizeError(err) => err.user_error(),
             Self::RemoteError(err) => err.user_error(),
@@ -1,14 +1,20 @@
 use std::{collections::HashMap, sync::Arc};
 
 use futures::{
-    channel::mpsc, io::BufReader, AsyncWrite, FutureExt, SinkExt, StreamExt, TryStreamExt,
+    channel::mpsc, io, io::BufReader, AsyncReadExt, AsyncWrite, FutureExt, SinkExt, StreamExt,
+    TryStreamExt,
+};
+use vault_crypto::{
+    constants::FILE_HEADER_SIZE,
+    data_cipher::{decrypt_header, decrypt_range, decrypt_size},
+    nonce::Nonce,
 };
-use vault_crypto::data_cipher::decrypt_size;
 
 use crate::{
     cipher::{errors::DecryptSizeError, Cipher},
     common::state::{BoxAsyncRead, SizeInfo},
-    remote_files::RemoteFilesService,
+    remote::RemoteFileReaderRange,
+    remote_files::{state::RemoteFilesFileReader, RemoteFilesService},
     repo_files::{
         selectors as repo_files_selectors,
         state::{RepoFile, RepoFileType},
@@ -26,7 +32,10 @@ use crate::{
 use super::{
     errors::GetFilesReaderError,
     mutations, selectors,
-    state::{GetRemoteZipEntries, RemoteZipEntry, RepoFileReader, RepoFileReaderProvider},
+    state::{
+        GetRemoteZipEntries, RemoteZipEntry, RepoFileReader, RepoFileReaderContentRange,
+        RepoFileReaderProvider, RepoFileReaderRange,
+    },
 };
 
 pub struct RepoFilesReadService {
@@ -68,7 +77,7 @@ impl RepoFilesReadService {
     ) -> Result<RepoFileReader, GetFilesReaderError> {
         let encrypted_reader = self
             .remote_files_service
-            .get_file_reader(&mount_id, &remote_path)
+            .get_file_reader(mount_id, remote_path, None)
             .await?;
 
         let size = decrypt_size(encrypted_reader.size)
@@ -82,6 +91,7 @@ impl RepoFilesReadService {
             content_type: content_type.map(str::to_string),
             remote_file: Some(encrypted_reader.file),
             unique_name: unique_name.map(str::to_string),
+            content_range: None,
             reader: decrypt_reader,
         })
     }
@@ -120,6 +130,111 @@ impl RepoFilesReadService {
         Ok(reader)
     }
 
+    /// Returns a reader for a decrypted byte range of a file. Only the
+    /// encrypted blocks that contain the range are downloaded (and the file
+    /// header if the range does not start in the first block).
+    pub async fn get_file_reader_range(
+        &self,
+        file: &RepoFile,
+        range: &RepoFileReaderRange,
+    ) -> Result<RepoFileReader, GetFilesReaderError> {
+        if file.typ != RepoFileType::File {
+            return Err(GetFilesReaderError::InvalidRange);
+        }
+
+        let name = file.decrypted_name()?.to_owned();
+        let size = file
+            .decrypted_size()?
+            .ok_or(GetFilesReaderError::InvalidRange)?;
+
+        let cipher = self.repos_service.get_cipher(&file.repo_id)?;
+
+        let end = range.end.map(|end| end.min(size - 1)).unwrap_or(size - 1);
+
+        if range.start < 0 || range.start > end {
+            return Err(GetFilesReaderError::InvalidRange);
+        }
+
+        let decrypt_range = decrypt_range(range.start, Some(end));
+
+        let (nonce, mut encrypted_reader) = if decrypt_range.block == 0 {
+            let mut encrypted_reader = self
+                .get_remote_file_reader_range(file, 0, decrypt_range.encrypted_end)
+                .await?;
+
+            let nonce = read_header(&mut encrypted_reader.reader).await?;
+
+            (nonce, encrypted_reader)
+        } else {
+            let mut header_reader = self
+                .get_remote_file_reader_range(file, 0, Some(FILE_HEADER_SIZE as i64 - 1))
+                .await?;
+
+            let nonce = read_header(&mut header_reader.reader).await?;
+
+            let encrypted_reader = self
+                .get_remote_file_reader_range(
+                    file,
+                    decrypt_range.encrypted_start,
+                    decrypt_range.encrypted_end,
+                )
+                .await?;
+
+            (nonce, encrypted_reader)
+        };
+
+        if encrypted_reader.content_range.is_none() && decrypt_range.block > 0 {
+            // the server returned the whole file
+            io::copy(
+                (&mut encrypted_reader.reader).take(decrypt_range.encrypted_start as u64),
+                &mut io::sink(),
+            )
+            .await
+            .map_err(|err| GetFilesReaderError::from(&err))?;
+        }
+
+        let len = end - range.start + 1;
+
+        let reader = cipher
+            .decrypt_reader_async_at_block(
+                encrypted_reader.reader,
+                nonce,
+                decrypt_range.block,
+                decrypt_range.skip,
+            )
+            .take(len as u64);
+
+        Ok(RepoFileReader {
+            name,
+            size: SizeInfo::Exact(len),
+            content_type: file.content_type.clone(),
+            remote_file: Some(encrypted_reader.file),
+            unique_name: Some(file.unique_name.clone()),
+            content_range: Some(RepoFileReaderContentRange {
+                start: range.start,
+                end,
+                size,
+            }),
+            reader: Box::pin(reader),
+        })
+    }
+
+    async fn get_remote_file_reader_range(
+        &self,
+        file: &RepoFile,
+        start: i64,
+        end: Option<i64>,
+    ) -> Result<RemoteFilesFileReader, GetFilesReaderError> {
+        Ok(self
+            .remote_files_service
+            .get_file_reader(
+                &file.mount_id,
+                &file.remote_path,
+                Some(&RemoteFileReaderRange { start, end }),
+            )
+            .await?)
+    }
+
     fn generate_missing_hash_reader(
         &self,
         file: &RepoFile,
@@ -464,6 +579,7 @@ impl RepoFilesReadService {
                         content_type: Some("application/zip".into()),
                         remote_file: None,
                         unique_name: None,
+                        content_range: None,
                         reader,
                     })
                 }
@@ -472,3 +588,14 @@ impl RepoFilesReadService {
         })
     }
 }
+
+async fn read_header(reader: &mut BoxAsyncRead) -> Result<Nonce, GetFilesReaderError> {
+    let mut header = [0; FILE_HEADER_SIZE];
+
+    reader
+        .read_exact(&mut header)
+        .await
+        .map_err(|err| GetFilesReaderError::from(&err))?;
+
+    decrypt_header(&header).map_err(|err| GetFilesReaderError::IOError(err.to_string()))
+}
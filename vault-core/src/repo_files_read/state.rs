@@ -23,6 +23,8 @@ pub struct RepoFileReader {
     /// unique_name is used for local file caching. it will not be set for
     /// generated files (e.g. ZIP files of a dir)
     pub unique_name: Option<String>,
+    /// content_range is set for ranged readers. size is the size of the range
+    pub content_range: Option<RepoFileReaderContentRange>,
     pub reader: BoxAsyncRead,
 }
 
@@ -36,11 +38,28 @@ impl RepoFileReader {
             content_type: self.content_type,
             remote_file: self.remote_file,
             unique_name: self.unique_name,
+            content_range: self.content_range,
             reader,
         }
     }
 }
 
+/// Inclusive decrypted byte range, end None reads until the end of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoFileReaderRange {
+    pub start: i64,
+    pub end: Option<i64>,
+}
+
+/// Inclusive decrypted byte range returned by a ranged reader and the
+/// decrypted size of the whole file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoFileReaderContentRange {
+    pub start: i64,
+    pub end: i64,
+    pub size: i64,
+}
+
 /// RepoFileReaderBuilder is Fn() (and not FnOnce()) because download transfers
 /// can be retried
 pub type RepoFileReaderBuilder = Box<
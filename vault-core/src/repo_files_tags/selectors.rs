@@ -70,6 +70,10 @@ pub fn select_set_tags_info(
             RepoFileTags {
                 encrypted_hash,
                 hash: None,
+                trash_original_path: None,
+                trash_deleted: None,
+                version_created: None,
+                version_user_agent: None,
                 unknown: HashMap::new(),
             }
         }
@@ -23,6 +23,19 @@ pub struct RepoFileTags {
         skip_serializing_if = "Option::is_none"
     )]
     pub hash: Option<Vec<u8>>,
+    /// Encrypted path the file had before it was moved to the repo trash.
+    #[serde(default, rename = "tp", skip_serializing_if = "Option::is_none")]
+    pub trash_original_path: Option<String>,
+    /// Time (in milliseconds) when the file was moved to the repo trash.
+    #[serde(default, rename = "td", skip_serializing_if = "Option::is_none")]
+    pub trash_deleted: Option<i64>,
+    /// Time (in milliseconds) when the file was saved to the repo versions
+    /// folder.
+    #[serde(default, rename = "vc", skip_serializing_if = "Option::is_none")]
+    pub version_created: Option<i64>,
+    /// User agent of the client that overwrote the file.
+    #[serde(default, rename = "vu", skip_serializing_if = "Option::is_none")]
+    pub version_user_agent: Option<String>,
     #[serde(flatten)]
     pub unknown: HashMap<String, rmpv::Value>,
 }
@@ -110,6 +123,10 @@ mod tests {
             hash: Some(vec![
                 150, 183, 185, 103, 121, 185, 70, 194, 171, 206, 238, 163, 192, 250, 45, 88,
             ]),
+            trash_original_path: None,
+            trash_deleted: None,
+            version_created: None,
+            version_user_agent: None,
             unknown: HashMap::from([("extra".into(), "value".into())]),
         };
 
@@ -171,6 +188,10 @@ mod tests {
             hash: Some(vec![
                 150, 183, 185, 103, 121, 185, 70, 194, 171, 206, 238, 163, 192, 250, 45, 88,
             ]),
+            trash_original_path: None,
+            trash_deleted: None,
+            version_created: None,
+            version_user_agent: None,
             unknown: HashMap::from([("extra".into(), "value".into())]),
         };
 
@@ -0,0 +1,5 @@
+pub mod errors;
+pub mod service;
+pub mod state;
+
+pub use self::service::RepoFsckService;
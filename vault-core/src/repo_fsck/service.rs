@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use futures::{stream::BoxStream, AsyncReadExt, StreamExt};
+
+use crate::{
+    cipher::Cipher,
+    remote_files::RemoteFilesService,
+    repo_files::{
+        errors::RepoFilesErrors,
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFileName, RepoFileSize, RepoFileType},
+        RepoFilesService,
+    },
+    repo_files_list::{state::RepoFilesListRecursiveItem, RepoFilesListService},
+    repos::ReposService,
+    store,
+    types::{EncryptedPath, RepoId},
+};
+
+use super::{
+    errors::RepoFsckError,
+    state::{RepoFsckEntry, RepoFsckEntryType, RepoFsckOptions, RepoFsckProblem, RepoFsckReport},
+};
+
+pub type RepoFsckEntryStream = BoxStream<'static, RepoFsckEntry>;
+
+const CONTENT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// RepoFsckService walks a whole repo and reports names, sizes, tags and
+/// (in deep mode) contents that are broken. Nothing is changed.
+pub struct RepoFsckService {
+    repos_service: Arc<ReposService>,
+    remote_files_service: Arc<RemoteFilesService>,
+    repo_files_service: Arc<RepoFilesService>,
+    repo_files_list_service: Arc<RepoFilesListService>,
+    store: Arc<store::Store>,
+}
+
+impl RepoFsckService {
+    pub fn new(
+        repos_service: Arc<ReposService>,
+        remote_files_service: Arc<RemoteFilesService>,
+        repo_files_service: Arc<RepoFilesService>,
+        repo_files_list_service: Arc<RepoFilesListService>,
+        store: Arc<store::Store>,
+    ) -> Self {
+        Self {
+            repos_service,
+            remote_files_service,
+            repo_files_service,
+            repo_files_list_service,
+            store,
+        }
+    }
+
+    /// Returns a stream with an entry for every file and dir in the repo
+    /// (except the root) and for every listing error.
+    pub async fn check(
+        &self,
+        repo_id: &RepoId,
+        options: RepoFsckOptions,
+    ) -> Result<RepoFsckEntryStream, RepoFsckError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let root_path = EncryptedPath("/".into());
+
+        self.repo_files_service
+            .load_file(repo_id, &root_path)
+            .await?;
+
+        let root_file = self
+            .store
+            .with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(repo_id, &root_path),
+                )
+                .cloned()
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        let items = self
+            .repo_files_list_service
+            .get_list_recursive(&root_file)
+            .await?;
+
+        let remote_files_service = self.remote_files_service.clone();
+
+        Ok(items
+            .filter(|item| {
+                futures::future::ready(match item {
+                    RepoFilesListRecursiveItem::File { file, .. } => !file.encrypted_path.is_root(),
+                    RepoFilesListRecursiveItem::Error { .. } => true,
+                })
+            })
+            .then(move |item| {
+                let remote_files_service = remote_files_service.clone();
+                let cipher = cipher.clone();
+                let options = options.clone();
+
+                async move { check_item(item, &options, &remote_files_service, &cipher).await }
+            })
+            .boxed())
+    }
+
+    pub async fn report(
+        &self,
+        repo_id: &RepoId,
+        options: RepoFsckOptions,
+    ) -> Result<RepoFsckReport, RepoFsckError> {
+        let mut entries = self.check(repo_id, options).await?;
+
+        let mut report = RepoFsckReport::default();
+
+        while let Some(entry) = entries.next().await {
+            report.add(entry);
+        }
+
+        Ok(report)
+    }
+}
+
+async fn check_item(
+    item: RepoFilesListRecursiveItem,
+    options: &RepoFsckOptions,
+    remote_files_service: &RemoteFilesService,
+    cipher: &Cipher,
+) -> RepoFsckEntry {
+    match item {
+        RepoFilesListRecursiveItem::File { file, .. } => {
+            let mut problems = check_file(&file);
+
+            if options.deep && file.typ == RepoFileType::File && problems.is_empty() {
+                problems.extend(check_content(&file, remote_files_service, cipher).await);
+            }
+
+            RepoFsckEntry {
+                remote_path: Some(file.remote_path.clone()),
+                encrypted_path: Some(file.encrypted_path.clone()),
+                path: file.decrypted_path().ok().cloned(),
+                typ: Some(match file.typ {
+                    RepoFileType::Dir => RepoFsckEntryType::Dir,
+                    RepoFileType::File => RepoFsckEntryType::File,
+                }),
+                problems,
+            }
+        }
+        RepoFilesListRecursiveItem::Error {
+            remote_path, error, ..
+        } => RepoFsckEntry {
+            remote_path,
+            encrypted_path: None,
+            path: None,
+            typ: None,
+            problems: vec![RepoFsckProblem::ListError {
+                error: error.to_string(),
+            }],
+        },
+    }
+}
+
+/// Names, sizes and tags are already decrypted (and names validated) when the
+/// repo is listed, so only the errors have to be collected.
+fn check_file(file: &RepoFile) -> Vec<RepoFsckProblem> {
+    let mut problems = Vec::new();
+
+    if let RepoFileName::DecryptError { error, .. } = &file.name {
+        problems.push(RepoFsckProblem::InvalidName {
+            error: error.to_string(),
+        });
+    }
+
+    if let Some(RepoFileSize::DecryptError {
+        encrypted_size,
+        error,
+    }) = &file.size
+    {
+        problems.push(RepoFsckProblem::InvalidSize {
+            encrypted_size: *encrypted_size,
+            error: error.to_string(),
+        });
+    }
+
+    if let Some(Err(err)) = &file.tags {
+        problems.push(RepoFsckProblem::InvalidTags {
+            error: err.to_string(),
+        });
+    }
+
+    problems
+}
+
+async fn check_content(
+    file: &RepoFile,
+    remote_files_service: &RemoteFilesService,
+    cipher: &Cipher,
+) -> Vec<RepoFsckProblem> {
+    let encrypted_reader = match remote_files_service
+        .get_file_reader(&file.mount_id, &file.remote_path, None)
+        .await
+    {
+        Ok(encrypted_reader) => encrypted_reader,
+        Err(err) => {
+            return vec![RepoFsckProblem::ReadError {
+                error: err.to_string(),
+            }]
+        }
+    };
+
+    let mut reader = cipher.decrypt_reader_async(encrypted_reader.reader);
+    let mut context = md5::Context::new();
+    let mut buf = vec![0; CONTENT_BUFFER_SIZE];
+
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => context.consume(&buf[..n]),
+            Err(err) => {
+                return vec![RepoFsckProblem::ContentDecryptError {
+                    error: err.to_string(),
+                }]
+            }
+        }
+    }
+
+    let actual = format!("{:x}", context.compute());
+
+    match file.hash() {
+        Some(expected) if expected != actual => {
+            vec![RepoFsckProblem::HashMismatch { expected, actual }]
+        }
+        _ => vec![],
+    }
+}
@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+use crate::types::{DecryptedPath, EncryptedPath, RemotePath};
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoFsckOptions {
+    /// Deep mode downloads every file to check that all Poly1305 blocks
+    /// decrypt and that the content matches the hash tag.
+    pub deep: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RepoFsckEntryType {
+    Dir,
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RepoFsckProblem {
+    /// The name cannot be decrypted or the decrypted name is not valid.
+    InvalidName { error: String },
+    /// The encrypted size is not a valid encrypted file size.
+    InvalidSize {
+        #[serde(rename = "encryptedSize")]
+        encrypted_size: i64,
+        error: String,
+    },
+    /// The tags cannot be decoded or they do not belong to the content.
+    InvalidTags { error: String },
+    /// The content could not be downloaded.
+    ReadError { error: String },
+    /// A block of the content failed to decrypt.
+    ContentDecryptError { error: String },
+    /// The decrypted content does not match the hash tag.
+    HashMismatch { expected: String, actual: String },
+    /// A part of the repo could not be listed.
+    ListError { error: String },
+}
+
+impl std::fmt::Display for RepoFsckProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName { error } => write!(f, "invalid name: {}", error),
+            Self::InvalidSize {
+                encrypted_size,
+                error,
+            } => write!(f, "invalid size {}: {}", encrypted_size, error),
+            Self::InvalidTags { error } => write!(f, "invalid tags: {}", error),
+            Self::ReadError { error } => write!(f, "read error: {}", error),
+            Self::ContentDecryptError { error } => write!(f, "content decrypt error: {}", error),
+            Self::HashMismatch { expected, actual } => {
+                write!(f, "hash mismatch: expected {} got {}", expected, actual)
+            }
+            Self::ListError { error } => write!(f, "list error: {}", error),
+        }
+    }
+}
+
+/// A checked file or dir, or a listing error (typ is None).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoFsckEntry {
+    pub remote_path: Option<RemotePath>,
+    pub encrypted_path: Option<EncryptedPath>,
+    pub path: Option<DecryptedPath>,
+    pub typ: Option<RepoFsckEntryType>,
+    pub problems: Vec<RepoFsckProblem>,
+}
+
+impl RepoFsckEntry {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoFsckReport {
+    pub dirs_count: usize,
+    pub files_count: usize,
+    /// Only entries with problems are kept.
+    pub entries: Vec<RepoFsckEntry>,
+}
+
+impl RepoFsckReport {
+    pub fn add(&mut self, entry: RepoFsckEntry) {
+        match entry.typ {
+            Some(RepoFsckEntryType::Dir) => self.dirs_count += 1,
+            Some(RepoFsckEntryType::File) => self.files_count += 1,
+            None => {}
+        }
+
+        if !entry.is_ok() {
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
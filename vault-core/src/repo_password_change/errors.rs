@@ -0,0 +1,81 @@
+use thiserror::Error;
+
+use crate::{
+    cipher::errors::DecryptFilenameError,
+    remote,
+    repos::errors::{
+        InvalidNameOptionsError, InvalidPasswordError, RepoNotFoundError, UpdateRepoError,
+    },
+    user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ChangeRepoPasswordError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("{0}")]
+    InvalidPassword(#[from] InvalidPasswordError),
+    #[error("{0}")]
+    InvalidNameOptions(#[from] InvalidNameOptionsError),
+    #[error("new password is the same as the old password")]
+    SamePassword,
+    #[error("repo at the root of a mount is not supported")]
+    UnsupportedLocation,
+    #[error("unexpected files in the old repo location: {0}")]
+    UnexpectedLocation(String),
+    #[error("{0} files failed")]
+    FilesFailed(usize),
+    #[error("repo was not updated on the server")]
+    RepoNotUpdated,
+    #[error("{0}")]
+    UpdateRepoError(#[from] UpdateRepoError),
+    #[error("{0}")]
+    RemoteError(#[from] remote::RemoteError),
+}
+
+impl UserError for ChangeRepoPasswordError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::InvalidPassword(err) => err.user_error(),
+            Self::InvalidNameOptions(err) => err.user_error(),
+            Self::SamePassword => String::from("New Safe Key must be different."),
+            Self::UnsupportedLocation => {
+                String::from("Safe Key cannot be changed for a Safe Box at the root of a storage.")
+            }
+            Self::UnexpectedLocation(path) => format!(
+                "Cannot finish the Safe Key change because {} contains unexpected files.",
+                path
+            ),
+            Self::FilesFailed(count) => format!(
+                "{} files could not be re-encrypted. Fix the errors and try again.",
+                count
+            ),
+            Self::RepoNotUpdated => String::from(
+                "Cannot finish the Safe Key change because the Safe Box was not updated. Try again.",
+            ),
+            Self::UpdateRepoError(err) => err.user_error(),
+            Self::RemoteError(err) => err.user_error(),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ChangeRepoPasswordFileError {
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("{0}")]
+    RemoteError(#[from] remote::RemoteError),
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for ChangeRepoPasswordFileError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::RemoteError(err) => err.user_error(),
+            Self::IOError(_) => self.to_string(),
+        }
+    }
+}
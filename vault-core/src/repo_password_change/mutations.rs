@@ -0,0 +1,173 @@
+use crate::{
+    common::state::Status,
+    repos::errors::RepoNotFoundError,
+    store,
+    types::{DecryptedPath, RepoId},
+};
+
+use super::{
+    errors::ChangeRepoPasswordError,
+    state::{RepoPasswordChange, RepoPasswordChangeFailure, RepoPasswordChangePhase},
+};
+
+pub fn create(state: &mut store::State, notify: &store::Notify, repo_id: RepoId) -> u32 {
+    notify(store::Event::RepoPasswordChange);
+
+    let change_id = state.repo_password_changes.next_id.next();
+
+    let change = RepoPasswordChange {
+        repo_id,
+        status: Status::Initial,
+        phase: None,
+        total_count: 0,
+        done_count: 0,
+        skipped_count: 0,
+        total_bytes: 0,
+        done_bytes: 0,
+        current_path: None,
+        failures: Vec::new(),
+    };
+
+    state
+        .repo_password_changes
+        .changes
+        .insert(change_id, change);
+
+    change_id
+}
+
+pub fn changing(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+) -> Result<RepoId, ChangeRepoPasswordError> {
+    let change = match state.repo_password_changes.changes.get_mut(&change_id) {
+        Some(change) => change,
+        None => return Err(ChangeRepoPasswordError::RepoNotFound(RepoNotFoundError)),
+    };
+
+    notify(store::Event::RepoPasswordChange);
+
+    // a change can be retried after an error so the progress is reset
+    change.status = Status::Loading {
+        loaded: change.status.loaded(),
+    };
+    change.phase = None;
+    change.total_count = 0;
+    change.done_count = 0;
+    change.skipped_count = 0;
+    change.total_bytes = 0;
+    change.done_bytes = 0;
+    change.current_path = None;
+    change.failures = Vec::new();
+
+    Ok(change.repo_id.clone())
+}
+
+pub fn set_phase(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+    phase: RepoPasswordChangePhase,
+) {
+    if let Some(change) = state.repo_password_changes.changes.get_mut(&change_id) {
+        notify(store::Event::RepoPasswordChange);
+
+        change.phase = Some(phase);
+        change.current_path = None;
+    }
+}
+
+pub fn listed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+    total_count: usize,
+    total_bytes: i64,
+) {
+    if let Some(change) = state.repo_password_changes.changes.get_mut(&change_id) {
+        notify(store::Event::RepoPasswordChange);
+
+        change.total_count = total_count;
+        change.total_bytes = total_bytes;
+    }
+}
+
+pub fn file_processing(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+    path: DecryptedPath,
+) {
+    if let Some(change) = state.repo_password_changes.changes.get_mut(&change_id) {
+        notify(store::Event::RepoPasswordChange);
+
+        change.current_path = Some(path);
+    }
+}
+
+pub fn file_processed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+    size: i64,
+    skipped: bool,
+) {
+    if let Some(change) = state.repo_password_changes.changes.get_mut(&change_id) {
+        notify(store::Event::RepoPasswordChange);
+
+        change.done_count += 1;
+        change.done_bytes += size;
+
+        if skipped {
+            change.skipped_count += 1;
+        }
+
+        change.current_path = None;
+    }
+}
+
+pub fn file_failed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+    failure: RepoPasswordChangeFailure,
+) {
+    if let Some(change) = state.repo_password_changes.changes.get_mut(&change_id) {
+        notify(store::Event::RepoPasswordChange);
+
+        change.failures.push(failure);
+        change.current_path = None;
+    }
+}
+
+pub fn changed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    change_id: u32,
+    res: Result<(), ChangeRepoPasswordError>,
+) -> Result<(), ChangeRepoPasswordError> {
+    let change = match state.repo_password_changes.changes.get_mut(&change_id) {
+        Some(change) => change,
+        None => return Err(ChangeRepoPasswordError::RepoNotFound(RepoNotFoundError)),
+    };
+
+    notify(store::Event::RepoPasswordChange);
+
+    change.status = match res {
+        Ok(()) => Status::Loaded,
+        Err(err) => Status::Error {
+            error: err,
+            loaded: change.status.loaded(),
+        },
+    };
+    change.current_path = None;
+
+    Ok(())
+}
+
+pub fn destroy(state: &mut store::State, notify: &store::Notify, change_id: u32) {
+    notify(store::Event::RepoPasswordChange);
+
+    state.repo_password_changes.changes.remove(&change_id);
+}
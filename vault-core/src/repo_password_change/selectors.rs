@@ -0,0 +1,26 @@
+use crate::{repos::selectors as repos_selectors, store};
+
+use super::state::RepoPasswordChangeInfo;
+
+pub fn select_info<'a>(
+    state: &'a store::State,
+    change_id: u32,
+) -> Option<RepoPasswordChangeInfo<'a>> {
+    state
+        .repo_password_changes
+        .changes
+        .get(&change_id)
+        .map(|change| RepoPasswordChangeInfo {
+            repo_id: &change.repo_id,
+            repo_name: repos_selectors::select_repo_name(state, &change.repo_id),
+            status: &change.status,
+            phase: change.phase,
+            total_count: change.total_count,
+            done_count: change.done_count,
+            skipped_count: change.skipped_count,
+            total_bytes: change.total_bytes,
+            done_bytes: change.done_bytes,
+            current_path: change.current_path.as_ref(),
+            failures: &change.failures,
+        })
+}
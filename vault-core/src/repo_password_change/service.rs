@@ -0,0 +1,727 @@
+use std::{collections::HashMap, sync::Arc};
+
+use futures::{io, StreamExt};
+
+use crate::{
+    cipher::Cipher,
+    remote::{models, ApiErrorCode, RemoteError, RemoteFileUploadConflictResolution},
+    remote_files::{state::RemoteFile, RemoteFilesService},
+    repo_files_tags::{
+        selectors::{get_remote_file_tags_set_conditions, REMOTE_FILE_TAGS_KEY},
+        state::RepoFileTags,
+    },
+    repos::{
+        errors::InvalidPasswordError, password_validator::check_password_validator,
+        selectors as repos_selectors, ReposService,
+    },
+    store,
+    types::{DecryptedPath, EncryptedPath, MountId, RemoteName, RemotePath, RepoId},
+    utils::{md5_reader, remote_path_utils},
+};
+
+use super::{
+    errors::{ChangeRepoPasswordError, ChangeRepoPasswordFileError},
+    mutations,
+    state::{RepoPasswordChangeFailure, RepoPasswordChangePhase},
+};
+
+/// Files are re-encrypted to a sibling of the repo location with this suffix
+/// (e.g. `/My safe box (password change)`). The name is deterministic so that
+/// an interrupted change can be resumed.
+pub const NEW_LOCATION_SUFFIX: &str = " (password change)";
+
+/// Values shared by all phases of a single change.
+struct ChangeContext<'a> {
+    change_id: u32,
+    repo_id: &'a RepoId,
+    mount_id: &'a MountId,
+    new_salt: Option<&'a str>,
+    old_cipher: &'a Cipher,
+    new_cipher: &'a Cipher,
+}
+
+pub struct RepoPasswordChangeService {
+    repos_service: Arc<ReposService>,
+    remote_files_service: Arc<RemoteFilesService>,
+    store: Arc<store::Store>,
+}
+
+impl RepoPasswordChangeService {
+    pub fn new(
+        repos_service: Arc<ReposService>,
+        remote_files_service: Arc<RemoteFilesService>,
+        store: Arc<store::Store>,
+    ) -> Self {
+        Self {
+            repos_service,
+            remote_files_service,
+            store,
+        }
+    }
+
+    pub fn create(&self, repo_id: RepoId) -> u32 {
+        self.store
+            .mutate(|state, notify, _, _| mutations::create(state, notify, repo_id))
+    }
+
+    /// Changes the repo password (and salt). All names and files are
+    /// re-encrypted to a new location, then the repo location and password
+    /// validator are swapped on the remote and the old files are removed.
+    ///
+    /// If the change is interrupted it can be restarted with the same
+    /// passwords. Files that already exist in the new location are skipped
+    /// and if the repo was already swapped only the cleanup is done.
+    pub async fn change(
+        &self,
+        change_id: u32,
+        old_password: &str,
+        new_password: &str,
+        new_salt: Option<&str>,
+    ) -> Result<(), ChangeRepoPasswordError> {
+        let repo_id = self
+            .store
+            .mutate(|state, notify, _, _| mutations::changing(state, notify, change_id))?;
+
+        let res = self
+            .change_repo_password(change_id, &repo_id, old_password, new_password, new_salt)
+            .await;
+
+        let res_err = res.as_ref().map(|_| ()).map_err(|err| err.clone());
+
+        self.store
+            .mutate(|state, notify, _, _| mutations::changed(state, notify, change_id, res))?;
+
+        res_err
+    }
+
+    pub fn destroy(&self, change_id: u32) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::destroy(state, notify, change_id);
+        });
+    }
+
+    async fn change_repo_password(
+        &self,
+        change_id: u32,
+        repo_id: &RepoId,
+        old_password: &str,
+        new_password: &str,
+        new_salt: Option<&str>,
+    ) -> Result<(), ChangeRepoPasswordError> {
+        let repo = self
+            .store
+            .with_state(|state| repos_selectors::select_repo(state, repo_id).cloned())?;
+
+        if old_password == new_password && repo.salt.as_deref() == new_salt {
+            return Err(ChangeRepoPasswordError::SamePassword);
+        }
+
+        // name options stay the same, only the keys change
+        let name_options = repo.name_options.clone()?;
+        let old_cipher = Cipher::new(vault_crypto::Cipher::new_with_name_options(
+            old_password,
+            repo.salt.as_deref(),
+            name_options,
+        ));
+        let new_cipher = Cipher::new(vault_crypto::Cipher::new_with_name_options(
+            new_password,
+            new_salt,
+            name_options,
+        ));
+
+        let is_valid = |cipher: &Cipher| {
+            check_password_validator(
+                cipher,
+                &repo.password_validator,
+                &repo.password_validator_encrypted,
+            )
+        };
+
+        let ctx = ChangeContext {
+            change_id,
+            repo_id,
+            mount_id: &repo.mount_id,
+            new_salt,
+            old_cipher: &old_cipher,
+            new_cipher: &new_cipher,
+        };
+
+        if is_valid(&old_cipher) {
+            let new_path =
+                get_new_location(&repo.path).ok_or(ChangeRepoPasswordError::UnsupportedLocation)?;
+
+            // fail before copying anything if the repo cannot be swapped later
+            self.repos_service.check_update_repo(repo_id).await?;
+
+            self.copy_files(&ctx, &repo.path, &new_path).await?;
+
+            self.set_phase(change_id, RepoPasswordChangePhase::Swapping);
+
+            self.repos_service
+                .update_repo(repo_id, &new_path, new_salt, &new_cipher)
+                .await?;
+
+            self.cleanup(&ctx, &repo.path, &new_path).await
+        } else if is_valid(&new_cipher) {
+            // the repo was already swapped, the change was interrupted during
+            // the cleanup or it is already done
+            match get_old_location(&repo.path) {
+                Some(old_path) => self.cleanup(&ctx, &old_path, &repo.path).await,
+                None => Ok(()),
+            }
+        } else {
+            Err(ChangeRepoPasswordError::InvalidPassword(
+                InvalidPasswordError,
+            ))
+        }
+    }
+
+    async fn copy_files(
+        &self,
+        ctx: &ChangeContext<'_>,
+        old_path: &RemotePath,
+        new_path: &RemotePath,
+    ) -> Result<(), ChangeRepoPasswordError> {
+        let ChangeContext {
+            change_id,
+            mount_id,
+            old_cipher,
+            new_cipher,
+            ..
+        } = *ctx;
+
+        self.set_phase(change_id, RepoPasswordChangePhase::Listing);
+
+        self.ensure_dir(mount_id, new_path).await?;
+
+        let existing: HashMap<String, models::FilesFile> = self
+            .list_files(mount_id, new_path)
+            .await?
+            .into_iter()
+            .map(|(path, file)| (path.0.to_lowercase(), file))
+            .collect();
+
+        let mut items = self.list_files(mount_id, old_path).await?;
+
+        // parents before children
+        items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let total_count = items.len();
+        let total_bytes = items.iter().map(|(_, file)| file.size).sum();
+
+        self.store.mutate(|state, notify, _, _| {
+            mutations::listed(state, notify, change_id, total_count, total_bytes)
+        });
+
+        self.set_phase(change_id, RepoPasswordChangePhase::Copying);
+
+        let mut failed_count = 0;
+
+        for (item_path, file) in items {
+            let encrypted_path = EncryptedPath(item_path.0.clone());
+
+            let decrypted_path = if file.typ == "dir" {
+                old_cipher.decrypt_dir_path(&encrypted_path)
+            } else {
+                old_cipher.decrypt_path(&encrypted_path)
+            };
+
+            let path = match decrypted_path {
+                Ok(path) => path,
+                Err(err) => {
+                    failed_count += 1;
+
+                    self.file_failed(change_id, encrypted_path, None, err.into());
+
+                    continue;
+                }
+            };
+
+            self.store.mutate(|state, notify, _, _| {
+                mutations::file_processing(state, notify, change_id, path.clone())
+            });
+
+            let new_encrypted_path = if file.typ == "dir" {
+                new_cipher.encrypt_dir_path(&path)
+            } else {
+                new_cipher.encrypt_path(&path)
+            };
+            let existing_file = existing.get(&new_encrypted_path.0.to_lowercase());
+
+            let res = self
+                .copy_file(
+                    ctx,
+                    &remote_path_utils::join_paths(old_path, &item_path),
+                    &remote_path_utils::join_paths(
+                        new_path,
+                        &RemotePath(new_encrypted_path.0.clone()),
+                    ),
+                    &file,
+                    existing_file,
+                )
+                .await;
+
+            match res {
+                Ok(skipped) => {
+                    let size = if file.typ == "file" { file.size } else { 0 };
+
+                    self.store.mutate(|state, notify, _, _| {
+                        mutations::file_processed(state, notify, change_id, size, skipped)
+                    });
+                }
+                Err(err) => {
+                    failed_count += 1;
+
+                    self.file_failed(change_id, encrypted_path, Some(path), err);
+                }
+            }
+        }
+
+        match failed_count {
+            0 => Ok(()),
+            _ => Err(ChangeRepoPasswordError::FilesFailed(failed_count)),
+        }
+    }
+
+    /// Copies a single file or dir and returns true if it already existed in
+    /// the new location.
+    async fn copy_file(
+        &self,
+        ctx: &ChangeContext<'_>,
+        old_remote_path: &RemotePath,
+        new_remote_path: &RemotePath,
+        file: &models::FilesFile,
+        existing_file: Option<&models::FilesFile>,
+    ) -> Result<bool, ChangeRepoPasswordFileError> {
+        let ChangeContext {
+            mount_id,
+            old_cipher,
+            new_cipher,
+            ..
+        } = *ctx;
+
+        if file.typ == "dir" {
+            if existing_file
+                .filter(|existing| existing.typ == "dir")
+                .is_some()
+            {
+                return Ok(true);
+            }
+
+            self.ensure_dir(mount_id, new_remote_path).await?;
+
+            return Ok(false);
+        }
+
+        // encrypted size only depends on the plaintext size. a file with the
+        // same size could still be left from an earlier change, so the
+        // content is compared too
+        if existing_file
+            .filter(|existing| existing.typ == "file" && existing.size == file.size)
+            .is_some()
+            && self
+                .is_copied(ctx, old_remote_path, new_remote_path, file)
+                .await?
+        {
+            return Ok(true);
+        }
+
+        let (new_parent_path, new_name) = remote_path_utils::split_parent_name(new_remote_path)
+            .ok_or_else(|| RemoteError::from_code(ApiErrorCode::InvalidPath, "Invalid path"))?;
+
+        let reader = self
+            .remote_files_service
+            .get_file_reader(mount_id, old_remote_path, None)
+            .await?;
+
+        let reader = Box::pin(
+            new_cipher.encrypt_reader_async(old_cipher.decrypt_reader_async(reader.reader)),
+        );
+
+        let (_, new_file) = self
+            .remote_files_service
+            .upload_file_reader(
+                mount_id,
+                &new_parent_path,
+                &new_name,
+                reader,
+                Some(file.size),
+                RemoteFileUploadConflictResolution::Overwrite {
+                    if_size: None,
+                    if_modified: None,
+                    if_hash: None,
+                    ignore_nonexisting: false,
+                },
+                None,
+            )
+            .await?;
+
+        if let Err(err) = self
+            .reencrypt_tags(mount_id, file, &new_file, old_cipher, new_cipher)
+            .await
+        {
+            // tags only contain cached metadata (e.g. the plaintext hash)
+            log::warn!(
+                "Failed to re-encrypt tags for {:?}: {:?}",
+                new_remote_path,
+                err
+            );
+        }
+
+        Ok(false)
+    }
+
+    /// Returns true if the file in the new location decrypts with the new
+    /// password to the same content as the old file.
+    async fn is_copied(
+        &self,
+        ctx: &ChangeContext<'_>,
+        old_remote_path: &RemotePath,
+        new_remote_path: &RemotePath,
+        file: &models::FilesFile,
+    ) -> Result<bool, ChangeRepoPasswordFileError> {
+        let ChangeContext {
+            mount_id,
+            old_cipher,
+            new_cipher,
+            ..
+        } = *ctx;
+
+        // a file that cannot be read or decrypted is copied again
+        let new_hash = match self
+            .get_plaintext_hash(mount_id, new_remote_path, new_cipher)
+            .await
+        {
+            Ok(hash) => hash,
+            Err(_) => return Ok(false),
+        };
+
+        let old_hash = match get_tags_hash(file, old_cipher) {
+            Some(hash) => hash,
+            None => {
+                self.get_plaintext_hash(mount_id, old_remote_path, old_cipher)
+                    .await?
+            }
+        };
+
+        Ok(new_hash == old_hash)
+    }
+
+    async fn get_plaintext_hash(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        cipher: &Cipher,
+    ) -> Result<Vec<u8>, ChangeRepoPasswordFileError> {
+        let reader = self
+            .remote_files_service
+            .get_file_reader(mount_id, path, None)
+            .await?;
+
+        let (md5_reader, md5_digest_future) =
+            md5_reader::MD5Reader::new(cipher.decrypt_reader_async(reader.reader));
+
+        io::copy(md5_reader, &mut io::sink())
+            .await
+            .map_err(|err| ChangeRepoPasswordFileError::IOError(err.to_string()))?;
+
+        md5_digest_future
+            .await
+            .map(|digest| digest.to_vec())
+            .map_err(|_| ChangeRepoPasswordFileError::IOError(String::from("missing hash")))
+    }
+
+    async fn reencrypt_tags(
+        &self,
+        mount_id: &MountId,
+        file: &models::FilesFile,
+        new_file: &RemoteFile,
+        old_cipher: &Cipher,
+        new_cipher: &Cipher,
+    ) -> Result<(), String> {
+        let value = match file
+            .tags
+            .get(REMOTE_FILE_TAGS_KEY)
+            .and_then(|values| values.first())
+        {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+
+        let mut tags =
+            RepoFileTags::from_string(value, old_cipher).map_err(|err| err.to_string())?;
+
+        tags.encrypted_hash = match new_file.hash.as_deref().map(hex::decode) {
+            Some(Ok(hash)) => Some(hash),
+            _ => None,
+        };
+
+        let encrypted_tags = tags.to_string(new_cipher).map_err(|err| err.to_string())?;
+
+        self.remote_files_service
+            .set_tags(
+                mount_id,
+                &new_file.path,
+                HashMap::from([(REMOTE_FILE_TAGS_KEY.to_owned(), vec![encrypted_tags])]),
+                get_remote_file_tags_set_conditions(new_file),
+            )
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    /// Removes the old files and moves the new location to the old path.
+    async fn cleanup(
+        &self,
+        ctx: &ChangeContext<'_>,
+        old_path: &RemotePath,
+        new_path: &RemotePath,
+    ) -> Result<(), ChangeRepoPasswordError> {
+        let ChangeContext {
+            change_id,
+            repo_id,
+            mount_id,
+            new_salt,
+            old_cipher,
+            new_cipher,
+        } = *ctx;
+
+        self.set_phase(change_id, RepoPasswordChangePhase::Cleanup);
+
+        // if the new location does not exist anymore it was already renamed
+        if self.file_exists(mount_id, new_path).await? {
+            self.check_remote_repo(repo_id, new_path, new_cipher)
+                .await?;
+
+            self.check_old_location(mount_id, old_path, old_cipher)
+                .await?;
+
+            match self
+                .remote_files_service
+                .delete_file(mount_id, old_path)
+                .await
+            {
+                Ok(()) => {}
+                Err(err) if err.is_api_error_code(ApiErrorCode::NotFound) => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            let old_name = remote_path_utils::path_to_name(old_path)
+                .ok_or(ChangeRepoPasswordError::UnsupportedLocation)?;
+
+            self.remote_files_service
+                .rename_file(mount_id, new_path, old_name)
+                .await?;
+        }
+
+        self.repos_service
+            .update_repo(repo_id, old_path, new_salt, new_cipher)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Checks that the repo on the server points to the new location and
+    /// uses the new password before the old location is removed. The repo is
+    /// read again because the local state could be stale.
+    async fn check_remote_repo(
+        &self,
+        repo_id: &RepoId,
+        new_path: &RemotePath,
+        new_cipher: &Cipher,
+    ) -> Result<(), ChangeRepoPasswordError> {
+        let repo = self.repos_service.get_remote_repo(repo_id).await?;
+
+        if &repo.path != new_path
+            || !check_password_validator(
+                new_cipher,
+                &repo.password_validator,
+                &repo.password_validator_encrypted,
+            )
+        {
+            return Err(ChangeRepoPasswordError::RepoNotUpdated);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the old location only contains files encrypted with the
+    /// old password before it is removed.
+    async fn check_old_location(
+        &self,
+        mount_id: &MountId,
+        old_path: &RemotePath,
+        old_cipher: &Cipher,
+    ) -> Result<(), ChangeRepoPasswordError> {
+        let items = match self.list_files(mount_id, old_path).await {
+            Ok(items) => items,
+            Err(err) if err.is_api_error_code(ApiErrorCode::NotFound) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        for (item_path, file) in items {
+            let encrypted_path = EncryptedPath(item_path.0);
+
+            let decrypted_path = if file.typ == "dir" {
+                old_cipher.decrypt_dir_path(&encrypted_path)
+            } else {
+                old_cipher.decrypt_path(&encrypted_path)
+            };
+
+            if decrypted_path.is_err() {
+                return Err(ChangeRepoPasswordError::UnexpectedLocation(
+                    old_path.0.clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lists all files and dirs under path (without the root) with paths
+    /// relative to path.
+    async fn list_files(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<Vec<(RemotePath, models::FilesFile)>, RemoteError> {
+        let mut items_stream = self
+            .remote_files_service
+            .get_list_recursive(mount_id, path)
+            .await?;
+
+        let mut items = Vec::new();
+
+        while let Some(item) = items_stream.next().await {
+            match item? {
+                models::FilesListRecursiveItem::File { path, file } => {
+                    if !path.is_root() {
+                        items.push((path, file));
+                    }
+                }
+                models::FilesListRecursiveItem::Error { error, .. } => {
+                    return Err(RemoteError::from_api_error_details(error, None, None));
+                }
+            }
+        }
+
+        Ok(items)
+    }
+
+    async fn file_exists(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<bool, RemoteError> {
+        match self.remote_files_service.load_file(mount_id, path).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.is_api_error_code(ApiErrorCode::NotFound) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn ensure_dir(&self, mount_id: &MountId, path: &RemotePath) -> Result<(), RemoteError> {
+        let (parent_path, name) = match remote_path_utils::split_parent_name(path) {
+            Some(parent_name) => parent_name,
+            None => return Ok(()),
+        };
+
+        match self
+            .remote_files_service
+            .create_dir_name(mount_id, &parent_path, name)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(err) if err.is_api_error_code(ApiErrorCode::AlreadyExists) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn set_phase(&self, change_id: u32, phase: RepoPasswordChangePhase) {
+        self.store
+            .mutate(|state, notify, _, _| mutations::set_phase(state, notify, change_id, phase));
+    }
+
+    fn file_failed(
+        &self,
+        change_id: u32,
+        encrypted_path: EncryptedPath,
+        path: Option<DecryptedPath>,
+        error: ChangeRepoPasswordFileError,
+    ) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::file_failed(
+                state,
+                notify,
+                change_id,
+                RepoPasswordChangeFailure {
+                    encrypted_path,
+                    path,
+                    error,
+                },
+            )
+        });
+    }
+}
+
+/// Returns the plaintext hash from the file tags if the tags belong to the
+/// current file content.
+fn get_tags_hash(file: &models::FilesFile, cipher: &Cipher) -> Option<Vec<u8>> {
+    let value = file.tags.get(REMOTE_FILE_TAGS_KEY)?.first()?;
+    let tags = RepoFileTags::from_string(value, cipher).ok()?;
+
+    if tags.encrypted_hash? != hex::decode(file.hash.as_ref()?).ok()? {
+        return None;
+    }
+
+    tags.hash
+}
+
+fn get_new_location(path: &RemotePath) -> Option<RemotePath> {
+    let (parent_path, name) = remote_path_utils::split_parent_name(path)?;
+
+    Some(remote_path_utils::join_path_name(
+        &parent_path,
+        &RemoteName(format!("{}{}", name.0, NEW_LOCATION_SUFFIX)),
+    ))
+}
+
+fn get_old_location(path: &RemotePath) -> Option<RemotePath> {
+    let (parent_path, name) = remote_path_utils::split_parent_name(path)?;
+    let old_name = name.0.strip_suffix(NEW_LOCATION_SUFFIX)?;
+
+    Some(remote_path_utils::join_path_name(
+        &parent_path,
+        &RemoteName(old_name.to_owned()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use crate::types::RemotePath;
+
+    use super::{get_new_location, get_old_location};
+
+    #[test]
+    fn test_get_new_location() {
+        assert_eq!(
+            get_new_location(&RemotePath("/Vault".into())),
+            Some(RemotePath("/Vault (password change)".into()))
+        );
+        assert_eq!(
+            get_new_location(&RemotePath("/dir/Vault".into())),
+            Some(RemotePath("/dir/Vault (password change)".into()))
+        );
+        assert_eq!(get_new_location(&RemotePath("/".into())), None);
+    }
+
+    #[test]
+    fn test_get_old_location() {
+        assert_eq!(
+            get_old_location(&RemotePath("/dir/Vault (password change)".into())),
+            Some(RemotePath("/dir/Vault".into()))
+        );
+        assert_eq!(get_old_location(&RemotePath("/dir/Vault".into())), None);
+    }
+}
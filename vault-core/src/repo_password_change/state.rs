@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use crate::{
+    common::state::Status,
+    store::NextId,
+    types::{DecryptedName, DecryptedPath, EncryptedPath, RepoId},
+};
+
+use super::errors::{ChangeRepoPasswordError, ChangeRepoPasswordFileError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepoPasswordChangePhase {
+    /// Listing the files in the old and in the new location
+    Listing,
+    /// Re-encrypting the files to the new location
+    Copying,
+    /// Updating the repo location and the password validator on the remote
+    Swapping,
+    /// Removing the old files and moving the new location to the old path
+    Cleanup,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoPasswordChangeFailure {
+    pub encrypted_path: EncryptedPath,
+    pub path: Option<DecryptedPath>,
+    pub error: ChangeRepoPasswordFileError,
+}
+
+pub struct RepoPasswordChangeInfo<'a> {
+    pub repo_id: &'a RepoId,
+    pub repo_name: Option<&'a DecryptedName>,
+    pub status: &'a Status<ChangeRepoPasswordError>,
+    pub phase: Option<RepoPasswordChangePhase>,
+    pub total_count: usize,
+    pub done_count: usize,
+    pub skipped_count: usize,
+    pub total_bytes: i64,
+    pub done_bytes: i64,
+    pub current_path: Option<&'a DecryptedPath>,
+    pub failures: &'a [RepoPasswordChangeFailure],
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoPasswordChange {
+    pub repo_id: RepoId,
+    pub status: Status<ChangeRepoPasswordError>,
+    pub phase: Option<RepoPasswordChangePhase>,
+    pub total_count: usize,
+    /// Files and dirs that have been re-encrypted or skipped
+    pub done_count: usize,
+    /// Files and dirs that already existed in the new location (e.g. after the
+    /// change was interrupted)
+    pub skipped_count: usize,
+    pub total_bytes: i64,
+    /// Bytes of the files that have been committed to the new location
+    pub done_bytes: i64,
+    pub current_path: Option<DecryptedPath>,
+    pub failures: Vec<RepoPasswordChangeFailure>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoPasswordChangesState {
+    pub changes: HashMap<u32, RepoPasswordChange>,
+    pub next_id: NextId,
+}
+
+impl RepoPasswordChangesState {
+    pub fn reset(&mut self) {
+        *self = Self {
+            next_id: self.next_id.clone(),
+            ..Default::default()
+        };
+    }
+}
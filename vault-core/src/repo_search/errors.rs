@@ -0,0 +1,74 @@
+use thiserror::Error;
+
+use crate::{
+    cipher::errors::DecryptFilenameError,
+    remote::RemoteError,
+    repo_files::errors::LoadFileError,
+    repo_files_list::errors::{FilesListRecursiveItemError, GetListRecursiveError},
+    repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
+    user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RepoSearchError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("{0}")]
+    RepoLocked(#[from] RepoLockedError),
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+}
+
+impl UserError for RepoSearchError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::RemoteError(err) => err.user_error(),
+        }
+    }
+}
+
+impl From<GetCipherError> for RepoSearchError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
+impl From<LoadFileError> for RepoSearchError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<GetListRecursiveError> for RepoSearchError {
+    fn from(err: GetListRecursiveError) -> Self {
+        match err {
+            GetListRecursiveError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetListRecursiveError::RepoLocked(err) => Self::RepoLocked(err),
+            GetListRecursiveError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            GetListRecursiveError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<FilesListRecursiveItemError> for RepoSearchError {
+    fn from(err: FilesListRecursiveItemError) -> Self {
+        match err {
+            FilesListRecursiveItemError::DecryptFilenameError(err) => {
+                Self::DecryptFilenameError(err)
+            }
+            FilesListRecursiveItemError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod errors;
+pub mod mutations;
+pub mod selectors;
+pub mod service;
+pub mod state;
+
+pub use self::service::RepoSearchService;
@@ -0,0 +1,436 @@
+use crate::{
+    common::state::Status,
+    eventstream::mutations::{add_mount_subscriber, remove_mount_subscriber},
+    repo_files::{
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFileType},
+    },
+    repos::selectors as repos_selectors,
+    store,
+    types::{DecryptedPath, EncryptedPath, RepoId},
+};
+
+use super::{
+    errors::RepoSearchError,
+    selectors,
+    state::{
+        RepoSearch, RepoSearchEntry, RepoSearchEntryType, RepoSearchIndex, RepoSearchIndexState,
+        RepoSearchPending, INDEX_VERSION,
+    },
+};
+
+pub fn create(state: &mut store::State, notify: &store::Notify, repo_id: RepoId) -> u32 {
+    notify(store::Event::RepoSearch);
+
+    let search_id = state.repo_searches.next_id.next();
+
+    state.repo_searches.searches.insert(
+        search_id,
+        RepoSearch {
+            repo_id,
+            query: String::new(),
+        },
+    );
+
+    search_id
+}
+
+pub fn set_query(state: &mut store::State, notify: &store::Notify, search_id: u32, query: String) {
+    if let Some(search) = state.repo_searches.searches.get_mut(&search_id) {
+        notify(store::Event::RepoSearch);
+
+        search.query = query;
+    }
+}
+
+pub fn destroy(state: &mut store::State, notify: &store::Notify, search_id: u32) {
+    notify(store::Event::RepoSearch);
+
+    state.repo_searches.searches.remove(&search_id);
+}
+
+/// Returns true if the index was already in the state (otherwise the
+/// persisted index should be loaded). A loaded index is kept up to date by
+/// eventstream events and is only listed again if content indexing is
+/// enabled.
+pub fn loading(
+    state: &mut store::State,
+    notify: &store::Notify,
+    mutation_state: &mut store::MutationState,
+    repo_id: &RepoId,
+    include_content: bool,
+) -> bool {
+    notify(store::Event::RepoSearch);
+
+    let exists = state.repo_searches.indexes.contains_key(repo_id);
+
+    let eventstream_mount_subscription = match state
+        .repo_searches
+        .indexes
+        .get(repo_id)
+        .and_then(|index| index.eventstream_mount_subscription.clone())
+    {
+        Some(mount_subscription) => Some(mount_subscription),
+        None => repos_selectors::select_repo(state, repo_id)
+            .ok()
+            .map(|repo| (repo.mount_id.clone(), repo.path.clone()))
+            .map(|(mount_id, path)| {
+                add_mount_subscriber(
+                    state,
+                    notify,
+                    mutation_state,
+                    mount_id,
+                    path,
+                    selectors::get_eventstream_mount_subscriber(repo_id),
+                )
+            }),
+    };
+
+    let index = state
+        .repo_searches
+        .indexes
+        .entry(repo_id.clone())
+        .or_default();
+
+    index.eventstream_mount_subscription = eventstream_mount_subscription;
+
+    if !exists {
+        index.status = Status::Loading { loaded: false };
+    }
+
+    if index.index.include_content != include_content {
+        index.index.include_content = include_content;
+
+        if include_content {
+            // content of all files has to be indexed
+            index.status = Status::Loading {
+                loaded: index.status.loaded(),
+            };
+        } else {
+            for entry in index.index.entries.values_mut() {
+                entry.content = None;
+            }
+
+            index.pending_content.clear();
+        }
+
+        index.dirty = true;
+    }
+
+    exists
+}
+
+/// The persisted index is not used if it has an old version or does not
+/// include content that is needed. Such an index is rebuilt.
+pub fn persisted_index_loaded(
+    state: &mut store::State,
+    notify: &store::Notify,
+    repo_id: &RepoId,
+    persisted_index: RepoSearchIndex,
+) {
+    let index = match state.repo_searches.indexes.get_mut(repo_id) {
+        Some(index) => index,
+        None => return,
+    };
+
+    let include_content = index.index.include_content;
+
+    if persisted_index.version != INDEX_VERSION
+        || !index.index.entries.is_empty()
+        || (include_content && !persisted_index.include_content)
+    {
+        return;
+    }
+
+    notify(store::Event::RepoSearch);
+
+    index.index.entries = persisted_index.entries;
+    index.status = Status::Loaded;
+
+    if persisted_index.include_content && !include_content {
+        for entry in index.index.entries.values_mut() {
+            entry.content = None;
+        }
+
+        index.dirty = true;
+    }
+}
+
+/// Replaces all entries under root_path with the listed files. Content of
+/// unchanged files is kept.
+pub fn listed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    repo_id: &RepoId,
+    root_path: &EncryptedPath,
+    files: Vec<RepoFile>,
+    res: Result<(), RepoSearchError>,
+) {
+    let index = match state.repo_searches.indexes.get_mut(repo_id) {
+        Some(index) => index,
+        None => return,
+    };
+
+    notify(store::Event::RepoSearch);
+
+    if res.is_ok() {
+        let prefix = path_prefix(root_path);
+
+        index
+            .index
+            .entries
+            .retain(|path, _| path != root_path && !path.0.starts_with(&prefix));
+    }
+
+    for file in files {
+        upsert_file(index, &file);
+    }
+
+    index.dirty = true;
+
+    if root_path.is_root() {
+        index.status = match res {
+            Ok(()) => Status::Loaded,
+            Err(err) => Status::Error {
+                error: err,
+                loaded: index.status.loaded(),
+            },
+        };
+    }
+}
+
+pub fn take_pending(state: &mut store::State, repo_id: &RepoId) -> RepoSearchPending {
+    match state.repo_searches.indexes.get_mut(repo_id) {
+        Some(index) => {
+            let save = index.dirty;
+
+            index.dirty = false;
+
+            RepoSearchPending {
+                content: index.pending_content.drain().collect(),
+                dirs: index.pending_dirs.drain().collect(),
+                save,
+            }
+        }
+        None => RepoSearchPending::default(),
+    }
+}
+
+pub fn content_indexed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    repo_id: &RepoId,
+    path: &EncryptedPath,
+    content: String,
+) {
+    let index = match state.repo_searches.indexes.get_mut(repo_id) {
+        Some(index) => index,
+        None => return,
+    };
+
+    if let Some(entry) = index.index.entries.get_mut(path) {
+        notify(store::Event::RepoSearch);
+
+        entry.content = Some(content);
+
+        index.dirty = true;
+    }
+}
+
+pub fn handle_repo_files_mutation(
+    state: &mut store::State,
+    notify: &store::Notify,
+    mutation_state: &store::MutationState,
+) {
+    let mut dirty = false;
+
+    for (repo_id, path) in mutation_state.repo_files.created_files.iter() {
+        let file = match repo_files_selectors::select_file(
+            state,
+            &repo_files_selectors::get_file_id(repo_id, path),
+        ) {
+            Some(file) => file.clone(),
+            None => continue,
+        };
+
+        if let Some(index) = state.repo_searches.indexes.get_mut(repo_id) {
+            if upsert_file(index, &file) {
+                if file.typ == RepoFileType::Dir && !path.is_root() {
+                    index.pending_dirs.insert(path.clone());
+                }
+
+                index.dirty = true;
+
+                dirty = true;
+            }
+        }
+    }
+
+    for (repo_id, path) in mutation_state.repo_files.removed_files.iter() {
+        if let Some(index) = state.repo_searches.indexes.get_mut(repo_id) {
+            let prefix = path_prefix(path);
+
+            let count = index.index.entries.len();
+
+            index
+                .index
+                .entries
+                .retain(|entry_path, _| entry_path != path && !entry_path.0.starts_with(&prefix));
+
+            index
+                .pending_content
+                .retain(|entry_path| entry_path != path && !entry_path.0.starts_with(&prefix));
+
+            if index.index.entries.len() != count {
+                index.dirty = true;
+
+                dirty = true;
+            }
+        }
+    }
+
+    for (repo_id, from_path, to_path) in mutation_state.repo_files.moved_files.iter() {
+        let to_decrypted_path = repo_files_selectors::select_file(
+            state,
+            &repo_files_selectors::get_file_id(repo_id, to_path),
+        )
+        .and_then(|file| file.decrypted_path().ok().cloned())
+        // files moved to the trash are removed from the index
+        .filter(|path| !repo_files_selectors::is_hidden_path(path));
+
+        if let Some(index) = state.repo_searches.indexes.get_mut(repo_id) {
+            if move_entries(index, from_path, to_path, to_decrypted_path) {
+                dirty = true;
+            }
+        }
+    }
+
+    if dirty {
+        notify(store::Event::RepoSearch);
+    }
+}
+
+pub fn handle_repos_mutation(
+    state: &mut store::State,
+    notify: &store::Notify,
+    mutation_state: &mut store::MutationState,
+) {
+    let repo_ids: Vec<RepoId> = mutation_state
+        .repos
+        .locked_repos
+        .iter()
+        .map(|(repo_id, _)| repo_id)
+        .chain(mutation_state.repos.removed_repos.iter())
+        .cloned()
+        .collect();
+
+    for repo_id in repo_ids {
+        if let Some(index) = state.repo_searches.indexes.remove(&repo_id) {
+            notify(store::Event::RepoSearch);
+
+            if let Some(mount_subscription) = index.eventstream_mount_subscription {
+                remove_mount_subscriber(state, notify, mutation_state, mount_subscription);
+            }
+        }
+    }
+}
+
+fn path_prefix(path: &EncryptedPath) -> String {
+    if path.is_root() {
+        "/".into()
+    } else {
+        format!("{}/", path.0)
+    }
+}
+
+/// Returns false if the file cannot be indexed (name cannot be decrypted or
+/// the file is in the trash or versions folder).
+fn upsert_file(index: &mut RepoSearchIndexState, file: &RepoFile) -> bool {
+    let path = match file.decrypted_path() {
+        Ok(path) if !repo_files_selectors::is_hidden_path(path) => path.clone(),
+        _ => return false,
+    };
+
+    let typ = match file.typ {
+        RepoFileType::Dir => RepoSearchEntryType::Dir,
+        RepoFileType::File => RepoSearchEntryType::File,
+    };
+    let size = file.decrypted_size().ok().flatten();
+    let modified = file.modified;
+
+    let content = index
+        .index
+        .entries
+        .get(&file.encrypted_path)
+        .filter(|entry| entry.size == size && entry.modified == modified)
+        .and_then(|entry| entry.content.clone());
+
+    if content.is_none() && index.index.include_content && selectors::is_content_indexable(file) {
+        index.pending_content.insert(file.encrypted_path.clone());
+    }
+
+    index.index.entries.insert(
+        file.encrypted_path.clone(),
+        RepoSearchEntry {
+            path,
+            typ,
+            size,
+            modified,
+            content,
+        },
+    );
+
+    true
+}
+
+fn move_entries(
+    index: &mut RepoSearchIndexState,
+    from_path: &EncryptedPath,
+    to_path: &EncryptedPath,
+    to_decrypted_path: Option<DecryptedPath>,
+) -> bool {
+    let prefix = path_prefix(from_path);
+
+    let from_decrypted_path = match index.index.entries.get(from_path) {
+        Some(entry) => entry.path.clone(),
+        None => return false,
+    };
+
+    let moved_paths: Vec<EncryptedPath> = index
+        .index
+        .entries
+        .keys()
+        .filter(|path| *path == from_path || path.0.starts_with(&prefix))
+        .cloned()
+        .collect();
+
+    for path in moved_paths {
+        let mut entry = match index.index.entries.remove(&path) {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        index.pending_content.remove(&path);
+
+        // the file was moved to a name that cannot be decrypted or to the
+        // trash
+        let to_decrypted_path = match &to_decrypted_path {
+            Some(to_decrypted_path) => to_decrypted_path,
+            None => continue,
+        };
+
+        let new_path = EncryptedPath(format!("{}{}", to_path.0, &path.0[from_path.0.len()..]));
+
+        entry.path = DecryptedPath(format!(
+            "{}{}",
+            to_decrypted_path.0,
+            &entry.path.0[from_decrypted_path.0.len()..]
+        ));
+
+        index.index.entries.insert(new_path, entry);
+    }
+
+    index.dirty = true;
+
+    true
+}
@@ -0,0 +1,128 @@
+use crate::{
+    common::state::Status,
+    files::file_category::FileCategory,
+    repo_files::state::{RepoFile, RepoFileType},
+    store,
+    types::{EncryptedPath, RepoId},
+};
+
+use super::state::{RepoSearchEntry, RepoSearchInfo, RepoSearchResult};
+
+pub const MAX_RESULTS: usize = 100;
+
+/// Only the first MAX_CONTENT_SIZE bytes of text files are indexed.
+pub const MAX_CONTENT_SIZE: usize = 1024 * 1024;
+
+pub fn get_eventstream_mount_subscriber(repo_id: &RepoId) -> String {
+    format!("RepoSearch:{}", repo_id.0)
+}
+
+pub fn select_index_loaded(state: &store::State, repo_id: &RepoId) -> bool {
+    state
+        .repo_searches
+        .indexes
+        .get(repo_id)
+        .map(|index| matches!(index.status, Status::Loaded))
+        .unwrap_or(false)
+}
+
+pub fn select_info<'a>(state: &'a store::State, search_id: u32) -> Option<RepoSearchInfo<'a>> {
+    let search = state.repo_searches.searches.get(&search_id)?;
+    let index = state.repo_searches.indexes.get(&search.repo_id);
+
+    Some(RepoSearchInfo {
+        repo_id: &search.repo_id,
+        status: index.map(|index| index.status.clone()).unwrap_or_default(),
+        indexed_count: index.map(|index| index.index.entries.len()).unwrap_or(0),
+        query: &search.query,
+        results: index
+            .map(|index| select_results(index.index.entries.iter(), &search.query))
+            .unwrap_or_default(),
+    })
+}
+
+pub fn select_pending_repo_ids(state: &store::State) -> Vec<RepoId> {
+    state
+        .repo_searches
+        .indexes
+        .iter()
+        .filter(|(_, index)| {
+            index.dirty || !index.pending_content.is_empty() || !index.pending_dirs.is_empty()
+        })
+        .map(|(repo_id, _)| repo_id.clone())
+        .collect()
+}
+
+pub fn query_terms(query: &str) -> Vec<String> {
+    query.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// Every term has to match either the path or the content. Entries that
+/// match by path are returned before entries that only match by content.
+pub fn select_results<'a>(
+    entries: impl Iterator<Item = (&'a EncryptedPath, &'a RepoSearchEntry)>,
+    query: &str,
+) -> Vec<RepoSearchResult<'a>> {
+    let terms = query_terms(query);
+
+    if terms.is_empty() {
+        return vec![];
+    }
+
+    let mut path_results = Vec::new();
+    let mut content_results = Vec::new();
+
+    for (encrypted_path, entry) in entries {
+        if encrypted_path.is_root() {
+            continue;
+        }
+
+        let path_lower = entry.path_lower();
+
+        let mut content_matched = false;
+
+        let matched = terms.iter().all(|term| {
+            if path_lower.contains(term.as_str()) {
+                true
+            } else if entry
+                .content
+                .as_ref()
+                .filter(|content| content.contains(term.as_str()))
+                .is_some()
+            {
+                content_matched = true;
+
+                true
+            } else {
+                false
+            }
+        });
+
+        if matched {
+            let result = RepoSearchResult {
+                encrypted_path,
+                entry,
+                content_matched,
+            };
+
+            if content_matched {
+                content_results.push(result);
+            } else {
+                path_results.push(result);
+            }
+        }
+    }
+
+    path_results
+        .into_iter()
+        .chain(content_results)
+        .take(MAX_RESULTS)
+        .collect()
+}
+
+pub fn is_content_indexable(file: &RepoFile) -> bool {
+    file.typ == RepoFileType::File
+        && matches!(file.category, FileCategory::Text | FileCategory::Code)
+        && file.decrypted_path().is_ok()
+        && file.decrypted_size().is_ok()
+}
@@ -0,0 +1,427 @@
+use std::sync::Arc;
+
+use futures::{io::Cursor, lock::Mutex as AsyncMutex, AsyncReadExt, FutureExt, StreamExt};
+
+use crate::{
+    cipher::Cipher,
+    remote::{self, RemoteFileUploadConflictResolution},
+    remote_files::RemoteFilesService,
+    repo_files::{
+        errors::RepoFilesErrors, selectors as repo_files_selectors, state::RepoFile,
+        RepoFilesService,
+    },
+    repo_files_list::{state::RepoFilesListRecursiveItem, RepoFilesListService},
+    repos::ReposService,
+    runtime, store,
+    types::{DecryptedName, EncryptedPath, RemoteName, RepoId},
+    utils::repo_encrypted_path_utils,
+};
+
+use super::{errors::RepoSearchError, mutations, selectors, state::RepoSearchIndex};
+
+/// Name of the index file in the search folder of the repo.
+pub const INDEX_NAME: &str = "index.json";
+
+/// RepoSearchService keeps a search index of decrypted paths (and optionally
+/// text content) per repo. The index is updated from repo files mutations
+/// (including eventstream events) and stored in the repo, encrypted with the
+/// repo cipher, so that other clients do not have to list the whole repo.
+pub struct RepoSearchService {
+    indexer: Arc<RepoSearchIndexer>,
+    store: Arc<store::Store>,
+
+    subscription_id: u32,
+    mutation_subscription_id: u32,
+}
+
+impl RepoSearchService {
+    pub fn new(
+        repos_service: Arc<ReposService>,
+        remote_files_service: Arc<RemoteFilesService>,
+        repo_files_service: Arc<RepoFilesService>,
+        repo_files_list_service: Arc<RepoFilesListService>,
+        store: Arc<store::Store>,
+        runtime: Arc<runtime::BoxRuntime>,
+    ) -> Self {
+        let indexer = Arc::new(RepoSearchIndexer {
+            repos_service,
+            remote_files_service,
+            repo_files_service,
+            repo_files_list_service,
+            store: store.clone(),
+            processing: AsyncMutex::new(()),
+        });
+
+        let subscription_id = store.get_next_id();
+        let subscription_indexer = indexer.clone();
+        let subscription_store = store.clone();
+
+        store.on(
+            subscription_id,
+            &[store::Event::RepoSearch],
+            Box::new(move |_, add_side_effect| {
+                for repo_id in subscription_store.with_state(selectors::select_pending_repo_ids) {
+                    let indexer = subscription_indexer.clone();
+                    let runtime = runtime.clone();
+
+                    add_side_effect(Box::new(move || {
+                        runtime
+                            .spawn(async move { indexer.process_pending(&repo_id).await }.boxed())
+                    }));
+                }
+            }),
+        );
+
+        let mutation_subscription_id = store.get_next_id();
+
+        store.mutation_on(
+            mutation_subscription_id,
+            &[store::MutationEvent::RepoFiles, store::MutationEvent::Repos],
+            Box::new(move |state, notify, mutation_state, _| {
+                mutations::handle_repo_files_mutation(state, notify, mutation_state);
+                mutations::handle_repos_mutation(state, notify, mutation_state);
+            }),
+        );
+
+        Self {
+            indexer,
+            store,
+
+            subscription_id,
+            mutation_subscription_id,
+        }
+    }
+
+    pub fn create(&self, repo_id: RepoId) -> u32 {
+        self.store
+            .mutate(|state, notify, _, _| mutations::create(state, notify, repo_id))
+    }
+
+    pub fn set_query(&self, search_id: u32, query: String) {
+        self.store
+            .mutate(|state, notify, _, _| mutations::set_query(state, notify, search_id, query))
+    }
+
+    pub fn destroy(&self, search_id: u32) {
+        self.store
+            .mutate(|state, notify, _, _| mutations::destroy(state, notify, search_id))
+    }
+
+    /// Loads the index stored in the repo (if it is not loaded yet). The repo
+    /// is only listed if there is no usable stored index. Content of new and
+    /// changed text files is indexed before the future resolves.
+    pub async fn load_index(
+        &self,
+        repo_id: &RepoId,
+        include_content: bool,
+    ) -> Result<(), RepoSearchError> {
+        self.indexer.load_index(repo_id, include_content).await
+    }
+
+    /// Indexes pending content and dirs and persists the index. Pending work
+    /// is also processed automatically in the background.
+    pub async fn process_pending(&self, repo_id: &RepoId) {
+        self.indexer.process_pending(repo_id).await
+    }
+}
+
+impl Drop for RepoSearchService {
+    fn drop(&mut self) {
+        self.store.remove_listener(self.subscription_id);
+        self.store
+            .mutation_remove_listener(self.mutation_subscription_id);
+    }
+}
+
+struct RepoSearchIndexer {
+    repos_service: Arc<ReposService>,
+    remote_files_service: Arc<RemoteFilesService>,
+    repo_files_service: Arc<RepoFilesService>,
+    repo_files_list_service: Arc<RepoFilesListService>,
+    store: Arc<store::Store>,
+    /// Pending work is processed by one task at a time so that waiting for
+    /// the lock also waits for the work that was already taken.
+    processing: AsyncMutex<()>,
+}
+
+impl RepoSearchIndexer {
+    async fn load_index(
+        &self,
+        repo_id: &RepoId,
+        include_content: bool,
+    ) -> Result<(), RepoSearchError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let exists = self.store.mutate(|state, notify, mutation_state, _| {
+            mutations::loading(state, notify, mutation_state, repo_id, include_content)
+        });
+
+        if !exists {
+            if let Some(index) = self.load_persisted(repo_id, &cipher).await {
+                self.store.mutate(|state, notify, _, _| {
+                    mutations::persisted_index_loaded(state, notify, repo_id, index)
+                });
+            }
+        }
+
+        let res = if self
+            .store
+            .with_state(|state| selectors::select_index_loaded(state, repo_id))
+        {
+            Ok(())
+        } else {
+            self.list_dir(repo_id, &EncryptedPath("/".into())).await
+        };
+
+        self.process_pending(repo_id).await;
+
+        res
+    }
+
+    async fn list_dir(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<(), RepoSearchError> {
+        let mut files = Vec::new();
+
+        let res = self.list_dir_files(repo_id, path, &mut files).await;
+
+        self.store.mutate(|state, notify, _, _| {
+            mutations::listed(state, notify, repo_id, path, files, res.clone())
+        });
+
+        res
+    }
+
+    async fn list_dir_files(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        files: &mut Vec<RepoFile>,
+    ) -> Result<(), RepoSearchError> {
+        self.repo_files_service.load_file(repo_id, path).await?;
+
+        let dir = self
+            .store
+            .with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(repo_id, path),
+                )
+                .cloned()
+            })
+            .ok_or_else(RepoFilesErrors::not_found)?;
+
+        let mut items = self
+            .repo_files_list_service
+            .get_list_recursive(&dir)
+            .await?;
+
+        let mut res = Ok(());
+
+        while let Some(item) = items.next().await {
+            match item {
+                RepoFilesListRecursiveItem::File { file, .. } => {
+                    // the trash, versions and search folders are not searchable
+                    let hidden = file
+                        .decrypted_path()
+                        .map(repo_files_selectors::is_hidden_path)
+                        .unwrap_or(false);
+
+                    if !hidden {
+                        files.push(file);
+                    }
+                }
+                RepoFilesListRecursiveItem::Error { error, .. } => res = Err(error.into()),
+            }
+        }
+
+        res
+    }
+
+    async fn process_pending(&self, repo_id: &RepoId) {
+        let _processing = self.processing.lock().await;
+
+        loop {
+            let pending = self
+                .store
+                .mutate(|state, _, _, _| mutations::take_pending(state, repo_id));
+
+            if pending.is_empty() {
+                return;
+            }
+
+            let cipher = match self.repos_service.get_cipher(repo_id) {
+                Ok(cipher) => cipher,
+                Err(_) => return,
+            };
+
+            for path in pending.dirs {
+                // errors are ignored, the dir is listed again when the index
+                // is reloaded
+                let _ = self.list_dir(repo_id, &path).await;
+            }
+
+            for path in pending.content {
+                if let Some(content) = self.get_content(repo_id, &path, &cipher).await {
+                    self.store.mutate(|state, notify, _, _| {
+                        mutations::content_indexed(state, notify, repo_id, &path, content)
+                    });
+                }
+            }
+
+            if pending.save {
+                if let Err(err) = self.save_persisted(repo_id, &cipher).await {
+                    log::warn!("Failed to save search index for {}: {}", repo_id.0, err);
+                }
+            }
+        }
+    }
+
+    async fn get_content(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        cipher: &Cipher,
+    ) -> Option<String> {
+        let file = self.store.with_state(|state| {
+            repo_files_selectors::select_file(
+                state,
+                &repo_files_selectors::get_file_id(repo_id, path),
+            )
+            .cloned()
+        })?;
+
+        let encrypted_reader = self
+            .remote_files_service
+            .get_file_reader(&file.mount_id, &file.remote_path, None)
+            .await
+            .ok()?;
+
+        let mut buf = Vec::new();
+
+        cipher
+            .decrypt_reader_async(encrypted_reader.reader)
+            .take(selectors::MAX_CONTENT_SIZE as u64)
+            .read_to_end(&mut buf)
+            .await
+            .ok()?;
+
+        Some(String::from_utf8_lossy(&buf).to_lowercase())
+    }
+
+    fn get_index_path(&self, repo_id: &RepoId, cipher: &Cipher) -> Result<EncryptedPath, String> {
+        let search_path = self
+            .repo_files_service
+            .get_search_path(repo_id)
+            .map_err(|err| err.to_string())?;
+
+        Ok(repo_encrypted_path_utils::join_path_name(
+            &search_path,
+            &cipher.encrypt_filename(&DecryptedName(INDEX_NAME.into())),
+        ))
+    }
+
+    async fn load_persisted(&self, repo_id: &RepoId, cipher: &Cipher) -> Option<RepoSearchIndex> {
+        match self.read_persisted(repo_id, cipher).await {
+            Ok(data) => {
+                // a corrupt index is rebuilt
+                let json = cipher.decrypt_vec(&data?).ok()?;
+
+                serde_json::from_slice(&json).ok()
+            }
+            Err(err) => {
+                log::warn!("Failed to load search index for {}: {}", repo_id.0, err);
+
+                None
+            }
+        }
+    }
+
+    async fn read_persisted(
+        &self,
+        repo_id: &RepoId,
+        cipher: &Cipher,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let index_path = self.get_index_path(repo_id, cipher)?;
+
+        let (mount_id, remote_path) = self
+            .repo_files_service
+            .get_repo_mount_path(repo_id, &index_path)
+            .map_err(|err| err.to_string())?;
+
+        let mut reader = match self
+            .remote_files_service
+            .get_file_reader(&mount_id, &remote_path, None)
+            .await
+        {
+            Ok(reader) => reader,
+            Err(err) if err.is_api_error_code(remote::ApiErrorCode::NotFound) => return Ok(None),
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let mut data = Vec::new();
+
+        reader
+            .reader
+            .read_to_end(&mut data)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        Ok(Some(data))
+    }
+
+    /// Overwrites the index in the repo. The last saved index wins if several
+    /// clients save the index at the same time.
+    async fn save_persisted(&self, repo_id: &RepoId, cipher: &Cipher) -> Result<(), String> {
+        let index = match self.store.with_state(|state| {
+            state
+                .repo_searches
+                .indexes
+                .get(repo_id)
+                .map(|index| serde_json::to_vec(&index.index).unwrap())
+        }) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let data = cipher.encrypt_vec(&index).map_err(|err| err.to_string())?;
+        let size = data.len() as i64;
+
+        let index_path = self.get_index_path(repo_id, cipher)?;
+        let (parent_path, name) = repo_encrypted_path_utils::split_parent_name(&index_path)
+            .ok_or_else(|| String::from("invalid index path"))?;
+
+        self.repo_files_service
+            .clone()
+            .ensure_dirs(repo_id, &parent_path)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let (mount_id, remote_parent_path) = self
+            .repo_files_service
+            .get_repo_mount_path(repo_id, &parent_path)
+            .map_err(|err| err.to_string())?;
+
+        self.remote_files_service
+            .upload_file_reader(
+                &mount_id,
+                &remote_parent_path,
+                &RemoteName(name.0),
+                Box::pin(Cursor::new(data)),
+                Some(size),
+                RemoteFileUploadConflictResolution::Overwrite {
+                    if_size: None,
+                    if_modified: None,
+                    if_hash: None,
+                    ignore_nonexisting: true,
+                },
+                None,
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+}
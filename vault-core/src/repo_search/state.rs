@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::state::Status,
+    eventstream::state::MountSubscription,
+    store::NextId,
+    types::{DecryptedPath, EncryptedPath, RepoId},
+};
+
+use super::errors::RepoSearchError;
+
+/// Bumped when the persisted index format changes. Indexes with a different
+/// version are rebuilt.
+pub const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RepoSearchEntryType {
+    Dir,
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoSearchEntry {
+    pub path: DecryptedPath,
+    pub typ: RepoSearchEntryType,
+    pub size: Option<i64>,
+    pub modified: Option<i64>,
+    /// Lowercase text content (only for text files if content indexing is
+    /// enabled).
+    pub content: Option<String>,
+}
+
+impl RepoSearchEntry {
+    pub fn path_lower(&self) -> String {
+        self.path.0.to_lowercase()
+    }
+}
+
+/// The search index of a repo. It is encrypted with the repo cipher and
+/// stored in the search folder of the repo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoSearchIndex {
+    pub version: u32,
+    pub include_content: bool,
+    pub entries: BTreeMap<EncryptedPath, RepoSearchEntry>,
+}
+
+impl Default for RepoSearchIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            include_content: false,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoSearchIndexState {
+    pub status: Status<RepoSearchError>,
+    pub index: RepoSearchIndex,
+    /// Files whose content has to be (re)indexed.
+    pub pending_content: HashSet<EncryptedPath>,
+    /// Dirs that were created or moved into the repo and have to be listed.
+    pub pending_dirs: HashSet<EncryptedPath>,
+    /// The index changed since it was last persisted.
+    pub dirty: bool,
+    /// Changes in the repo are applied to the index from eventstream events
+    /// while the index is loaded.
+    pub eventstream_mount_subscription: Option<MountSubscription>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoSearch {
+    pub repo_id: RepoId,
+    pub query: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoSearchResult<'a> {
+    pub encrypted_path: &'a EncryptedPath,
+    pub entry: &'a RepoSearchEntry,
+    /// The query only matched the content (and not the path).
+    pub content_matched: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoSearchInfo<'a> {
+    pub repo_id: &'a RepoId,
+    pub status: Status<RepoSearchError>,
+    pub indexed_count: usize,
+    pub query: &'a str,
+    pub results: Vec<RepoSearchResult<'a>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoSearchesState {
+    pub indexes: HashMap<RepoId, RepoSearchIndexState>,
+    pub searches: HashMap<u32, RepoSearch>,
+    pub next_id: NextId,
+}
+
+impl RepoSearchesState {
+    pub fn reset(&mut self) {
+        *self = Self {
+            next_id: self.next_id.clone(),
+            ..Default::default()
+        };
+    }
+}
+
+/// A batch of work for the indexer. It is taken out of the state so that
+/// content is only fetched once.
+#[derive(Debug, Clone, Default)]
+pub struct RepoSearchPending {
+    pub content: Vec<EncryptedPath>,
+    pub dirs: Vec<EncryptedPath>,
+    pub save: bool,
+}
+
+impl RepoSearchPending {
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty() && self.dirs.is_empty() && !self.save
+    }
+}
@@ -0,0 +1,2 @@
+pub mod reconcile;
+pub mod state;
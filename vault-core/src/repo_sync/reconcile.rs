@@ -0,0 +1,700 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::{
+    repo_files::selectors as repo_files_selectors,
+    types::{DecryptedName, DecryptedNameLower, DecryptedPath},
+    utils::repo_path_utils,
+};
+
+use super::state::{
+    path_key, RepoSyncAction, RepoSyncDb, RepoSyncEntryType, RepoSyncLocalEntry, RepoSyncRecord,
+    RepoSyncRemoteEntry,
+};
+
+/// Compares the local and the remote entries (keyed by `path_key`) with the
+/// records from the last sync and returns the actions needed to bring both
+/// sides in sync. Actions are ordered so that parents are created before
+/// their children.
+///
+/// If both sides changed, the local file is renamed to an unused name (the
+/// same way `RepoFilesService::get_unused_name` does it) and uploaded, and the
+/// remote file is downloaded. A local dir that conflicts with a remote file is
+/// only renamed, it is uploaded in the next sync.
+///
+/// A dir deleted on one side is restored instead if the other side added or
+/// changed something inside it.
+pub fn reconcile(
+    local: &BTreeMap<String, RepoSyncLocalEntry>,
+    remote: &BTreeMap<String, RepoSyncRemoteEntry>,
+    db: &RepoSyncDb,
+) -> Vec<RepoSyncAction> {
+    let keys: BTreeSet<&String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(db.records.keys())
+        .collect();
+
+    let mut actions: BTreeMap<String, Vec<RepoSyncAction>> = BTreeMap::new();
+    let mut conflict_names: HashMap<String, HashSet<DecryptedNameLower>> = HashMap::new();
+
+    // children first so that dir deletes can check what happens inside them
+    for key in keys.into_iter().rev() {
+        let local_entry = local.get(key);
+        let remote_entry = remote.get(key);
+        let record = db.records.get(key);
+
+        let path = match (local_entry, remote_entry, record) {
+            (Some(entry), _, _) => entry.path.clone(),
+            (_, Some(entry), _) => entry.path.clone(),
+            (_, _, Some(record)) => record.path.clone(),
+            (None, None, None) => continue,
+        };
+
+        let mut conflict = |actions: &mut BTreeMap<String, Vec<RepoSyncAction>>,
+                            local_entry: &RepoSyncLocalEntry,
+                            remote_entry: &RepoSyncRemoteEntry| {
+            let new_path = get_conflict_path(local, remote, &mut conflict_names, &path);
+
+            if local_entry.typ == RepoSyncEntryType::Dir {
+                // everything inside is moved with the dir
+                remove_descendant_actions(actions, key, |_| true);
+            }
+
+            conflict_actions(&path, new_path, local_entry, remote_entry)
+        };
+
+        let local_changed = is_local_changed(local_entry, record);
+        let remote_changed = is_remote_changed(remote_entry, record);
+
+        let mut path_actions = match (local_changed, remote_changed) {
+            (false, false) => match (local_entry, remote_entry, record) {
+                (Some(local_entry), Some(remote_entry), Some(record))
+                    if &RepoSyncRecord::new(local_entry, remote_entry) != record =>
+                {
+                    vec![RepoSyncAction::Record { path: path.clone() }]
+                }
+                _ => vec![],
+            },
+            (true, false) => local_to_remote(&path, local_entry, remote_entry),
+            (false, true) => remote_to_local(&path, local_entry, remote_entry),
+            (true, true) => match (local_entry, remote_entry) {
+                (None, None) => vec![RepoSyncAction::Forget { path: path.clone() }],
+                (Some(_), None) => local_to_remote(&path, local_entry, None),
+                (None, Some(_)) => remote_to_local(&path, None, remote_entry),
+                (Some(local_entry), Some(remote_entry)) if is_same(local_entry, remote_entry) => {
+                    vec![RepoSyncAction::Record { path: path.clone() }]
+                }
+                (Some(local_entry), Some(remote_entry)) => {
+                    conflict(&mut actions, local_entry, remote_entry)
+                }
+            },
+        };
+
+        let is_type_change = path_actions.len() > 1;
+
+        match path_actions.first() {
+            Some(RepoSyncAction::DeleteRemote { .. })
+                if remote_entry.map(|entry| entry.typ) == Some(RepoSyncEntryType::Dir) =>
+            {
+                let restores = has_descendant_actions(&actions, key, |action| {
+                    matches!(
+                        action,
+                        RepoSyncAction::Download { .. } | RepoSyncAction::CreateLocalDir { .. }
+                    )
+                });
+
+                if restores {
+                    path_actions = match (is_type_change, local_entry, remote_entry) {
+                        (true, Some(local_entry), Some(remote_entry)) => {
+                            conflict(&mut actions, local_entry, remote_entry)
+                        }
+                        _ => vec![RepoSyncAction::CreateLocalDir { path: path.clone() }],
+                    };
+                } else {
+                    remove_descendant_actions(&mut actions, key, |action| {
+                        matches!(
+                            action,
+                            RepoSyncAction::DeleteRemote { .. } | RepoSyncAction::Forget { .. }
+                        )
+                    });
+                }
+            }
+            Some(RepoSyncAction::DeleteLocal { .. })
+                if local_entry.map(|entry| entry.typ) == Some(RepoSyncEntryType::Dir) =>
+            {
+                let restores = has_descendant_actions(&actions, key, |action| {
+                    matches!(
+                        action,
+                        RepoSyncAction::Upload { .. } | RepoSyncAction::CreateRemoteDir { .. }
+                    )
+                });
+
+                if restores {
+                    path_actions = match (is_type_change, local_entry, remote_entry) {
+                        (true, Some(local_entry), Some(remote_entry)) => {
+                            conflict(&mut actions, local_entry, remote_entry)
+                        }
+                        _ => vec![RepoSyncAction::CreateRemoteDir { path: path.clone() }],
+                    };
+                } else {
+                    remove_descendant_actions(&mut actions, key, |action| {
+                        matches!(
+                            action,
+                            RepoSyncAction::DeleteLocal { .. } | RepoSyncAction::Forget { .. }
+                        )
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        if !path_actions.is_empty() {
+            actions.insert(key.clone(), path_actions);
+        }
+    }
+
+    actions.into_values().flatten().collect()
+}
+
+fn is_local_changed(entry: Option<&RepoSyncLocalEntry>, record: Option<&RepoSyncRecord>) -> bool {
+    match (entry, record) {
+        (None, None) => false,
+        (Some(_), None) | (None, Some(_)) => true,
+        (Some(entry), Some(record)) => {
+            entry.typ != record.typ
+                || (entry.typ == RepoSyncEntryType::File
+                    && (entry.size != record.local_size || entry.modified != record.local_modified)
+                    // same content with a new modified time is not a change
+                    && (entry.hash.is_none() || entry.hash != record.hash))
+        }
+    }
+}
+
+fn is_remote_changed(entry: Option<&RepoSyncRemoteEntry>, record: Option<&RepoSyncRecord>) -> bool {
+    match (entry, record) {
+        (None, None) => false,
+        (Some(_), None) | (None, Some(_)) => true,
+        (Some(entry), Some(record)) => {
+            entry.typ != record.typ
+                || (entry.typ == RepoSyncEntryType::File
+                    && match (&entry.remote_hash, &record.remote_hash) {
+                        (Some(remote_hash), Some(record_remote_hash)) => {
+                            remote_hash != record_remote_hash
+                        }
+                        _ => {
+                            entry.size != record.remote_size
+                                || entry.modified != record.remote_modified
+                        }
+                    })
+        }
+    }
+}
+
+fn is_same(local_entry: &RepoSyncLocalEntry, remote_entry: &RepoSyncRemoteEntry) -> bool {
+    match (local_entry.typ, remote_entry.typ) {
+        (RepoSyncEntryType::Dir, RepoSyncEntryType::Dir) => true,
+        (RepoSyncEntryType::File, RepoSyncEntryType::File) => {
+            remote_entry.size == Some(local_entry.size)
+                && local_entry.hash.is_some()
+                && local_entry.hash == remote_entry.hash
+        }
+        _ => false,
+    }
+}
+
+fn local_to_remote(
+    path: &DecryptedPath,
+    local_entry: Option<&RepoSyncLocalEntry>,
+    remote_entry: Option<&RepoSyncRemoteEntry>,
+) -> Vec<RepoSyncAction> {
+    let local_entry = match local_entry {
+        Some(local_entry) => local_entry,
+        None => {
+            return match remote_entry {
+                Some(_) => vec![RepoSyncAction::DeleteRemote { path: path.clone() }],
+                None => vec![],
+            }
+        }
+    };
+
+    let mut actions = Vec::new();
+
+    let remote_entry = match remote_entry {
+        Some(remote_entry) if remote_entry.typ != local_entry.typ => {
+            actions.push(RepoSyncAction::DeleteRemote { path: path.clone() });
+
+            None
+        }
+        remote_entry => remote_entry,
+    };
+
+    actions.push(match (local_entry.typ, remote_entry) {
+        (RepoSyncEntryType::Dir, None) => RepoSyncAction::CreateRemoteDir { path: path.clone() },
+        (RepoSyncEntryType::Dir, Some(_)) => RepoSyncAction::Record { path: path.clone() },
+        (RepoSyncEntryType::File, remote_entry) => RepoSyncAction::Upload {
+            path: path.clone(),
+            if_remote_hash: remote_entry.and_then(|entry| entry.remote_hash.clone()),
+        },
+    });
+
+    actions
+}
+
+fn remote_to_local(
+    path: &DecryptedPath,
+    local_entry: Option<&RepoSyncLocalEntry>,
+    remote_entry: Option<&RepoSyncRemoteEntry>,
+) -> Vec<RepoSyncAction> {
+    let remote_entry = match remote_entry {
+        Some(remote_entry) => remote_entry,
+        None => {
+            return match local_entry {
+                Some(_) => vec![RepoSyncAction::DeleteLocal { path: path.clone() }],
+                None => vec![],
+            }
+        }
+    };
+
+    let mut actions = Vec::new();
+
+    let local_entry = match local_entry {
+        Some(local_entry) if local_entry.typ != remote_entry.typ => {
+            actions.push(RepoSyncAction::DeleteLocal { path: path.clone() });
+
+            None
+        }
+        local_entry => local_entry,
+    };
+
+    actions.push(match (remote_entry.typ, local_entry) {
+        (RepoSyncEntryType::Dir, None) => RepoSyncAction::CreateLocalDir { path: path.clone() },
+        (RepoSyncEntryType::Dir, Some(_)) => RepoSyncAction::Record { path: path.clone() },
+        (RepoSyncEntryType::File, _) => RepoSyncAction::Download { path: path.clone() },
+    });
+
+    actions
+}
+
+fn conflict_actions(
+    path: &DecryptedPath,
+    new_path: DecryptedPath,
+    local_entry: &RepoSyncLocalEntry,
+    remote_entry: &RepoSyncRemoteEntry,
+) -> Vec<RepoSyncAction> {
+    let mut actions = vec![RepoSyncAction::RenameLocal {
+        path: path.clone(),
+        new_path: new_path.clone(),
+    }];
+
+    if local_entry.typ == RepoSyncEntryType::File {
+        actions.push(RepoSyncAction::Upload {
+            path: new_path,
+            if_remote_hash: None,
+        });
+    }
+
+    actions.push(match remote_entry.typ {
+        RepoSyncEntryType::Dir => RepoSyncAction::CreateLocalDir { path: path.clone() },
+        RepoSyncEntryType::File => RepoSyncAction::Download { path: path.clone() },
+    });
+
+    actions
+}
+
+fn get_conflict_path(
+    local: &BTreeMap<String, RepoSyncLocalEntry>,
+    remote: &BTreeMap<String, RepoSyncRemoteEntry>,
+    conflict_names: &mut HashMap<String, HashSet<DecryptedNameLower>>,
+    path: &DecryptedPath,
+) -> DecryptedPath {
+    let (parent_path, name) = match repo_path_utils::split_parent_name(path) {
+        Some(parent_name) => parent_name,
+        None => (DecryptedPath("/".into()), DecryptedName(String::new())),
+    };
+    let parent_key = path_key(&parent_path);
+
+    let assigned_names = conflict_names.entry(parent_key.clone()).or_default();
+
+    let used_names: HashSet<DecryptedNameLower> = local
+        .values()
+        .map(|entry| &entry.path)
+        .chain(remote.values().map(|entry| &entry.path))
+        .filter_map(repo_path_utils::split_parent_name)
+        .filter(|(entry_parent_path, _)| path_key(entry_parent_path) == parent_key)
+        .map(|(_, entry_name)| entry_name.to_lowercase())
+        .chain(assigned_names.iter().cloned())
+        .collect();
+
+    let new_name = repo_files_selectors::get_unused_name(used_names, &name);
+
+    assigned_names.insert(new_name.to_lowercase());
+
+    repo_path_utils::join_path_name(&parent_path, &new_name)
+}
+
+fn descendant_keys<'a>(
+    actions: &'a BTreeMap<String, Vec<RepoSyncAction>>,
+    key: &str,
+) -> impl Iterator<Item = &'a String> {
+    let prefix = if key == "/" {
+        key.to_owned()
+    } else {
+        format!("{}/", key)
+    };
+
+    actions
+        .range(prefix.clone()..)
+        .map(|(descendant_key, _)| descendant_key)
+        .take_while(move |descendant_key| descendant_key.starts_with(&prefix))
+}
+
+fn has_descendant_actions(
+    actions: &BTreeMap<String, Vec<RepoSyncAction>>,
+    key: &str,
+    f: impl Fn(&RepoSyncAction) -> bool,
+) -> bool {
+    descendant_keys(actions, key).any(|descendant_key| actions[descendant_key].iter().any(&f))
+}
+
+fn remove_descendant_actions(
+    actions: &mut BTreeMap<String, Vec<RepoSyncAction>>,
+    key: &str,
+    f: impl Fn(&RepoSyncAction) -> bool,
+) {
+    let keys: Vec<String> = descendant_keys(actions, key).cloned().collect();
+
+    for descendant_key in keys {
+        if let Some(key_actions) = actions.get_mut(&descendant_key) {
+            key_actions.retain(|action| !f(action));
+
+            if key_actions.is_empty() {
+                actions.remove(&descendant_key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use similar_asserts::assert_eq;
+
+    use crate::{
+        repo_sync::state::{
+            path_key, RepoSyncAction, RepoSyncDb, RepoSyncEntryType, RepoSyncLocalEntry,
+            RepoSyncRecord, RepoSyncRemoteEntry,
+        },
+        types::DecryptedPath,
+    };
+
+    use super::reconcile;
+
+    fn local_file(path: &str, content: &str, modified: i64) -> RepoSyncLocalEntry {
+        RepoSyncLocalEntry {
+            path: DecryptedPath(path.into()),
+            typ: RepoSyncEntryType::File,
+            size: content.len() as i64,
+            modified,
+            hash: Some(format!("{:x}", md5::compute(content))),
+        }
+    }
+
+    fn local_dir(path: &str) -> RepoSyncLocalEntry {
+        RepoSyncLocalEntry {
+            path: DecryptedPath(path.into()),
+            typ: RepoSyncEntryType::Dir,
+            size: 0,
+            modified: 0,
+            hash: None,
+        }
+    }
+
+    fn remote_file(path: &str, content: &str, remote_hash: &str) -> RepoSyncRemoteEntry {
+        RepoSyncRemoteEntry {
+            path: DecryptedPath(path.into()),
+            typ: RepoSyncEntryType::File,
+            size: Some(content.len() as i64),
+            modified: Some(1),
+            remote_hash: Some(remote_hash.into()),
+            hash: Some(format!("{:x}", md5::compute(content))),
+        }
+    }
+
+    fn remote_dir(path: &str) -> RepoSyncRemoteEntry {
+        RepoSyncRemoteEntry {
+            path: DecryptedPath(path.into()),
+            typ: RepoSyncEntryType::Dir,
+            size: None,
+            modified: None,
+            remote_hash: None,
+            hash: None,
+        }
+    }
+
+    struct Sides {
+        local: BTreeMap<String, RepoSyncLocalEntry>,
+        remote: BTreeMap<String, RepoSyncRemoteEntry>,
+        db: RepoSyncDb,
+    }
+
+    impl Sides {
+        fn new() -> Self {
+            Self {
+                local: BTreeMap::new(),
+                remote: BTreeMap::new(),
+                db: RepoSyncDb::default(),
+            }
+        }
+
+        fn local(&mut self, entry: RepoSyncLocalEntry) -> &mut Self {
+            self.local.insert(path_key(&entry.path), entry);
+            self
+        }
+
+        fn remote(&mut self, entry: RepoSyncRemoteEntry) -> &mut Self {
+            self.remote.insert(path_key(&entry.path), entry);
+            self
+        }
+
+        /// Records the current local and remote entries as synced.
+        fn synced(&mut self) -> &mut Self {
+            for (key, local_entry) in &self.local {
+                if let Some(remote_entry) = self.remote.get(key) {
+                    self.db.set(RepoSyncRecord::new(local_entry, remote_entry));
+                }
+            }
+            self
+        }
+
+        fn remove_local(&mut self, path: &str) -> &mut Self {
+            self.local
+                .retain(|key, _| key != path && !key.starts_with(&format!("{}/", path)));
+            self
+        }
+
+        fn remove_remote(&mut self, path: &str) -> &mut Self {
+            self.remote
+                .retain(|key, _| key != path && !key.starts_with(&format!("{}/", path)));
+            self
+        }
+
+        fn reconcile(&self) -> Vec<RepoSyncAction> {
+            reconcile(&self.local, &self.remote, &self.db)
+        }
+    }
+
+    fn path(path: &str) -> DecryptedPath {
+        DecryptedPath(path.into())
+    }
+
+    #[test]
+    fn test_reconcile_initial() {
+        let actions = Sides::new()
+            .local(local_dir("/dir"))
+            .local(local_file("/dir/local.txt", "local", 1))
+            .local(local_file("/same.txt", "same", 1))
+            .remote(remote_file("/same.txt", "same", "r1"))
+            .remote(remote_dir("/remote"))
+            .remote(remote_file("/remote/remote.txt", "remote", "r2"))
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![
+                RepoSyncAction::CreateRemoteDir { path: path("/dir") },
+                RepoSyncAction::Upload {
+                    path: path("/dir/local.txt"),
+                    if_remote_hash: None
+                },
+                RepoSyncAction::CreateLocalDir {
+                    path: path("/remote")
+                },
+                RepoSyncAction::Download {
+                    path: path("/remote/remote.txt")
+                },
+                RepoSyncAction::Record {
+                    path: path("/same.txt")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_unchanged() {
+        let actions = Sides::new()
+            .local(local_dir("/dir"))
+            .local(local_file("/dir/file.txt", "file", 1))
+            .remote(remote_dir("/dir"))
+            .remote(remote_file("/dir/file.txt", "file", "r1"))
+            .synced()
+            // only the modified time changed
+            .local(local_file("/dir/file.txt", "file", 2))
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![RepoSyncAction::Record {
+                path: path("/dir/file.txt")
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_changed() {
+        let actions = Sides::new()
+            .local(local_file("/local.txt", "a", 1))
+            .local(local_file("/remote.txt", "b", 1))
+            .remote(remote_file("/local.txt", "a", "r1"))
+            .remote(remote_file("/remote.txt", "b", "r2"))
+            .synced()
+            .local(local_file("/local.txt", "a2", 2))
+            .remote(remote_file("/remote.txt", "b2", "r3"))
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![
+                RepoSyncAction::Upload {
+                    path: path("/local.txt"),
+                    if_remote_hash: Some("r1".into())
+                },
+                RepoSyncAction::Download {
+                    path: path("/remote.txt")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_deleted() {
+        let actions = Sides::new()
+            .local(local_dir("/dir"))
+            .local(local_file("/dir/file.txt", "file", 1))
+            .local(local_file("/remote.txt", "b", 1))
+            .local(local_file("/both.txt", "c", 1))
+            .remote(remote_dir("/dir"))
+            .remote(remote_file("/dir/file.txt", "file", "r1"))
+            .remote(remote_file("/remote.txt", "b", "r2"))
+            .remote(remote_file("/both.txt", "c", "r3"))
+            .synced()
+            .remove_local("/dir")
+            .remove_remote("/remote.txt")
+            .remove_local("/both.txt")
+            .remove_remote("/both.txt")
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![
+                RepoSyncAction::Forget {
+                    path: path("/both.txt")
+                },
+                RepoSyncAction::DeleteRemote { path: path("/dir") },
+                RepoSyncAction::DeleteLocal {
+                    path: path("/remote.txt")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_deleted_dir_restored() {
+        let actions = Sides::new()
+            .local(local_dir("/dir"))
+            .local(local_file("/dir/old.txt", "old", 1))
+            .remote(remote_dir("/dir"))
+            .remote(remote_file("/dir/old.txt", "old", "r1"))
+            .synced()
+            .remove_local("/dir")
+            .remote(remote_file("/dir/new.txt", "new", "r2"))
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![
+                RepoSyncAction::CreateLocalDir { path: path("/dir") },
+                RepoSyncAction::Download {
+                    path: path("/dir/new.txt")
+                },
+                RepoSyncAction::DeleteRemote {
+                    path: path("/dir/old.txt")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_conflict() {
+        let actions = Sides::new()
+            .local(local_file("/file.txt", "a", 1))
+            .remote(remote_file("/file.txt", "a", "r1"))
+            .synced()
+            .local(local_file("/file.txt", "local", 2))
+            .remote(remote_file("/file.txt", "remote", "r2"))
+            .remote(remote_file("/file (1).txt", "other", "r3"))
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![
+                RepoSyncAction::Download {
+                    path: path("/file (1).txt")
+                },
+                RepoSyncAction::RenameLocal {
+                    path: path("/file.txt"),
+                    new_path: path("/file (2).txt")
+                },
+                RepoSyncAction::Upload {
+                    path: path("/file (2).txt"),
+                    if_remote_hash: None
+                },
+                RepoSyncAction::Download {
+                    path: path("/file.txt")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_conflict_dir() {
+        let actions = Sides::new()
+            .local(local_dir("/name"))
+            .local(local_file("/name/file.txt", "local", 1))
+            .remote(remote_file("/name", "remote", "r1"))
+            .reconcile();
+
+        assert_eq!(
+            actions,
+            vec![
+                RepoSyncAction::RenameLocal {
+                    path: path("/name"),
+                    new_path: path("/name (1)")
+                },
+                RepoSyncAction::Download {
+                    path: path("/name")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_db_remove() {
+        let mut sides = Sides::new();
+        sides
+            .local(local_dir("/dir"))
+            .local(local_file("/dir/file.txt", "file", 1))
+            .local(local_file("/dir2", "file", 1))
+            .remote(remote_dir("/dir"))
+            .remote(remote_file("/dir/file.txt", "file", "r1"))
+            .remote(remote_file("/dir2", "file", "r2"))
+            .synced();
+
+        sides.db.remove(&path("/Dir"));
+
+        assert_eq!(
+            sides.db.records.keys().cloned().collect::<Vec<_>>(),
+            vec![String::from("/dir2")]
+        );
+    }
+}
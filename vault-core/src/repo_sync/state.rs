@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{DecryptedPath, RepoId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoSyncEntryType {
+    File,
+    Dir,
+}
+
+/// A local file or dir found when scanning the local directory. `hash` is the
+/// hex MD5 of the content (the same hash that is stored in `RepoFileTags`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoSyncLocalEntry {
+    pub path: DecryptedPath,
+    pub typ: RepoSyncEntryType,
+    pub size: i64,
+    pub modified: i64,
+    pub hash: Option<String>,
+}
+
+/// A remote file or dir relative to the synced repo path. `remote_hash` is the
+/// hash of the encrypted content, `hash` is the plaintext hash from
+/// `RepoFileTags` (if known).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoSyncRemoteEntry {
+    pub path: DecryptedPath,
+    pub typ: RepoSyncEntryType,
+    pub size: Option<i64>,
+    pub modified: Option<i64>,
+    pub remote_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+/// State of a path after it was last synced. A side has changed if it differs
+/// from the record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepoSyncRecord {
+    pub path: DecryptedPath,
+    pub typ: RepoSyncEntryType,
+    pub local_size: i64,
+    pub local_modified: i64,
+    pub remote_size: Option<i64>,
+    pub remote_modified: Option<i64>,
+    pub remote_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl RepoSyncRecord {
+    pub fn new(local: &RepoSyncLocalEntry, remote: &RepoSyncRemoteEntry) -> Self {
+        Self {
+            path: remote.path.clone(),
+            typ: remote.typ,
+            local_size: local.size,
+            local_modified: local.modified,
+            remote_size: remote.size,
+            remote_modified: remote.modified,
+            remote_hash: remote.remote_hash.clone(),
+            hash: local.hash.clone().or_else(|| remote.hash.clone()),
+        }
+    }
+}
+
+/// Persistent sync state. Records are keyed by the lowercase path because
+/// remote names are case insensitive. `repo_id` and `path` identify the synced
+/// repo path so that the state is not used for a different one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RepoSyncDb {
+    #[serde(default)]
+    pub repo_id: Option<RepoId>,
+    #[serde(default)]
+    pub path: Option<DecryptedPath>,
+    pub records: BTreeMap<String, RepoSyncRecord>,
+}
+
+impl RepoSyncDb {
+    pub fn get(&self, path: &DecryptedPath) -> Option<&RepoSyncRecord> {
+        self.records.get(&path_key(path))
+    }
+
+    pub fn set(&mut self, record: RepoSyncRecord) {
+        self.records.insert(path_key(&record.path), record);
+    }
+
+    /// Removes the record for path and all records under it.
+    pub fn remove(&mut self, path: &DecryptedPath) {
+        let key = path_key(path);
+        let prefix = format!("{}/", key);
+
+        self.records
+            .retain(|record_key, _| record_key != &key && !record_key.starts_with(&prefix));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoSyncAction {
+    CreateLocalDir {
+        path: DecryptedPath,
+    },
+    CreateRemoteDir {
+        path: DecryptedPath,
+    },
+    /// Upload the local file. The remote file is only overwritten if it still
+    /// has `if_remote_hash`, a new file is only created if it does not exist.
+    Upload {
+        path: DecryptedPath,
+        if_remote_hash: Option<String>,
+    },
+    Download {
+        path: DecryptedPath,
+    },
+    DeleteLocal {
+        path: DecryptedPath,
+    },
+    DeleteRemote {
+        path: DecryptedPath,
+    },
+    /// Both sides changed. The local file is moved to `new_path` (which is then
+    /// uploaded) so that the remote file can be downloaded to `path`.
+    RenameLocal {
+        path: DecryptedPath,
+        new_path: DecryptedPath,
+    },
+    /// Both sides are the same, only the record is updated.
+    Record {
+        path: DecryptedPath,
+    },
+    /// Both sides were deleted, only the record is removed.
+    Forget {
+        path: DecryptedPath,
+    },
+}
+
+impl RepoSyncAction {
+    pub fn path(&self) -> &DecryptedPath {
+        match self {
+            Self::CreateLocalDir { path }
+            | Self::CreateRemoteDir { path }
+            | Self::Upload { path, .. }
+            | Self::Download { path }
+            | Self::DeleteLocal { path }
+            | Self::DeleteRemote { path }
+            | Self::RenameLocal { path, .. }
+            | Self::Record { path }
+            | Self::Forget { path } => path,
+        }
+    }
+}
+
+pub fn path_key(path: &DecryptedPath) -> String {
+    path.0.to_lowercase()
+}
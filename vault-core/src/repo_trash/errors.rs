@@ -0,0 +1,83 @@
+use thiserror::Error;
+
+use crate::{
+    cipher::errors::DecryptFilenameError,
+    remote::RemoteError,
+    repo_files::errors::{EnsureDirError, LoadFileError, LoadFilesError},
+    repo_files_tags::errors::SetTagsError,
+    repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
+    user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RepoTrashError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("{0}")]
+    RepoLocked(#[from] RepoLockedError),
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("not in trash")]
+    NotInTrash,
+    #[error("canceled")]
+    Canceled,
+    #[error("failed to update trash tags: {0}")]
+    SetTagsError(#[from] SetTagsError),
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+}
+
+impl UserError for RepoTrashError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::NotInTrash => String::from("Item is not in the trash."),
+            Self::Canceled => self.to_string(),
+            Self::SetTagsError(_) => self.to_string(),
+            Self::RemoteError(err) => err.user_error(),
+        }
+    }
+}
+
+impl From<GetCipherError> for RepoTrashError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
+impl From<LoadFilesError> for RepoTrashError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFilesError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFilesError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<LoadFileError> for RepoTrashError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<EnsureDirError> for RepoTrashError {
+    fn from(err: EnsureDirError) -> Self {
+        match err {
+            EnsureDirError::RepoNotFound(err) => Self::RepoNotFound(err),
+            EnsureDirError::RepoLocked(err) => Self::RepoLocked(err),
+            EnsureDirError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
+            EnsureDirError::Canceled => Self::Canceled,
+            EnsureDirError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod errors;
+pub mod selectors;
+pub mod service;
+pub mod state;
+
+pub use self::service::RepoTrashService;
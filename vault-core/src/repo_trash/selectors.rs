@@ -0,0 +1,66 @@
+use crate::{
+    cipher::Cipher,
+    repo_files::{
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFileType},
+    },
+    store,
+    types::{EncryptedPath, RepoId, TimeMillis},
+};
+
+use super::state::RepoTrashEntry;
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+pub fn get_entry(file: &RepoFile, cipher: &Cipher) -> RepoTrashEntry {
+    let tags = file.tags.as_ref().and_then(|tags| tags.as_ref().ok());
+
+    let original_path = tags
+        .and_then(|tags| tags.trash_original_path.clone())
+        .map(EncryptedPath);
+
+    let original_decrypted_path = original_path.as_ref().and_then(|path| {
+        match file.typ {
+            RepoFileType::Dir => cipher.decrypt_dir_path(path),
+            RepoFileType::File => cipher.decrypt_path(path),
+        }
+        .ok()
+    });
+
+    RepoTrashEntry {
+        file: file.clone(),
+        original_path,
+        original_decrypted_path,
+        deleted: tags.and_then(|tags| tags.trash_deleted),
+    }
+}
+
+/// Returns the entries sorted by the deletion time, the most recently deleted
+/// first.
+pub fn select_entries(
+    state: &store::State,
+    repo_id: &RepoId,
+    trash_path: &EncryptedPath,
+    cipher: &Cipher,
+) -> Vec<RepoTrashEntry> {
+    let mut entries: Vec<RepoTrashEntry> =
+        repo_files_selectors::select_files(state, repo_id, trash_path)
+            .map(|file| get_entry(file, cipher))
+            .collect();
+
+    entries.sort_by(|a, b| {
+        b.deleted
+            .cmp(&a.deleted)
+            .then_with(|| a.file.name_lower_force().cmp(b.file.name_lower_force()))
+    });
+
+    entries
+}
+
+/// Entries without the deletion time are never expired.
+pub fn is_expired(entry: &RepoTrashEntry, retention_days: u32, now: TimeMillis) -> bool {
+    match entry.deleted {
+        Some(deleted) => deleted <= now.0 - retention_days as i64 * DAY_MILLIS,
+        None => false,
+    }
+}
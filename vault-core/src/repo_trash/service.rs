@@ -0,0 +1,335 @@
+use std::sync::{Arc, Weak};
+
+use futures::FutureExt;
+
+use crate::{
+    remote::ApiErrorCode,
+    remote_files::RemoteFilesService,
+    repo_files::{
+        errors::{LoadFilesError, RepoFilesErrors},
+        selectors as repo_files_selectors,
+        state::RepoFileType,
+        RepoFilesService,
+    },
+    repo_files_tags::RepoFilesTagsService,
+    repos::{selectors as repos_selectors, ReposService},
+    runtime, store,
+    types::{EncryptedPath, RepoId},
+    utils::repo_encrypted_path_utils,
+};
+
+use super::{errors::RepoTrashError, selectors, state::RepoTrashEntry};
+
+/// RepoTrashService manages files that were moved to the repo trash by
+/// RepoFilesService::delete_files. Expired entries are purged when the repo
+/// is unlocked.
+pub struct RepoTrashService {
+    repos_service: Arc<ReposService>,
+    remote_files_service: Arc<RemoteFilesService>,
+    repo_files_service: Arc<RepoFilesService>,
+    repo_files_tags_service: Arc<RepoFilesTagsService>,
+    store: Arc<store::Store>,
+    runtime: Arc<runtime::BoxRuntime>,
+
+    repos_subscription_id: u32,
+}
+
+impl RepoTrashService {
+    pub fn new(
+        repos_service: Arc<ReposService>,
+        remote_files_service: Arc<RemoteFilesService>,
+        repo_files_service: Arc<RepoFilesService>,
+        repo_files_tags_service: Arc<RepoFilesTagsService>,
+        store: Arc<store::Store>,
+        runtime: Arc<runtime::BoxRuntime>,
+    ) -> Arc<Self> {
+        let repos_subscription_id = store.get_next_id();
+
+        let repo_trash_service = Arc::new(Self {
+            repos_service,
+            remote_files_service,
+            repo_files_service,
+            repo_files_tags_service,
+            store: store.clone(),
+            runtime,
+
+            repos_subscription_id,
+        });
+
+        let repos_subscription_repo_trash_service = Arc::downgrade(&repo_trash_service);
+
+        store.on(
+            repos_subscription_id,
+            &[store::Event::Repos],
+            Box::new(move |mutation_state, add_side_effect| {
+                if !mutation_state.repos.unlocked_repos.is_empty() {
+                    let repo_trash_service = repos_subscription_repo_trash_service.clone();
+
+                    let repo_ids: Vec<RepoId> = mutation_state
+                        .repos
+                        .unlocked_repos
+                        .iter()
+                        .map(|(repo_id, _)| repo_id.clone())
+                        .collect();
+
+                    add_side_effect(Box::new(move || {
+                        handle_unlocked_repos(repo_trash_service, repo_ids);
+                    }));
+                }
+            }),
+        );
+
+        repo_trash_service
+    }
+
+    pub async fn list(&self, repo_id: &RepoId) -> Result<Vec<RepoTrashEntry>, RepoTrashError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let trash_path = self.repo_files_service.get_trash_path(repo_id)?;
+
+        match self
+            .repo_files_service
+            .load_files(repo_id, &trash_path)
+            .await
+        {
+            Ok(()) => {}
+            // the trash folder is created on the first delete
+            Err(LoadFilesError::RemoteError(err))
+                if err.is_api_error_code(ApiErrorCode::NotFound) =>
+            {
+                return Ok(Vec::new())
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(self
+            .store
+            .with_state(|state| selectors::select_entries(state, repo_id, &trash_path, &cipher)))
+    }
+
+    /// Moves the entries back to their original paths. Missing parent dirs
+    /// are created and the names are autorenamed if the original path is
+    /// taken. Entries without the original path are restored to the repo
+    /// root. Returns the restored paths.
+    pub async fn restore(
+        &self,
+        repo_id: &RepoId,
+        paths: &[EncryptedPath],
+    ) -> Result<Vec<EncryptedPath>, RepoTrashError> {
+        let cipher = self.repos_service.get_cipher(repo_id)?;
+
+        let mut restored_paths = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            self.check_entry_path(repo_id, path)?;
+
+            self.repo_files_service.load_file(repo_id, path).await?;
+
+            let entry = self
+                .store
+                .with_state(|state| {
+                    repo_files_selectors::select_file(
+                        state,
+                        &repo_files_selectors::get_file_id(repo_id, path),
+                    )
+                    .map(|file| selectors::get_entry(file, &cipher))
+                })
+                .ok_or_else(RepoFilesErrors::not_found)?;
+
+            let (parent_path, name) = match entry
+                .original_path
+                .as_ref()
+                .and_then(repo_encrypted_path_utils::split_parent_name)
+            {
+                Some((parent_path, encrypted_name)) => {
+                    let name = match entry.file.typ {
+                        RepoFileType::Dir => cipher.decrypt_dir_name(&encrypted_name),
+                        RepoFileType::File => cipher.decrypt_filename(&encrypted_name),
+                    };
+
+                    (parent_path, name)
+                }
+                None => (
+                    EncryptedPath("/".into()),
+                    entry.file.decrypted_name().cloned(),
+                ),
+            };
+            let name = name?;
+
+            self.repo_files_service
+                .clone()
+                .ensure_dirs(repo_id, &parent_path)
+                .await?;
+
+            let new_name = self
+                .repo_files_service
+                .get_unused_name(repo_id, &parent_path, &name)
+                .await?;
+
+            let new_encrypted_name = match entry.file.typ {
+                RepoFileType::Dir => cipher.encrypt_dir_name(&new_name),
+                RepoFileType::File => cipher.encrypt_filename(&new_name),
+            };
+
+            let new_path =
+                repo_encrypted_path_utils::join_path_name(&parent_path, &new_encrypted_name);
+
+            let (to_mount_id, to_remote_path) = self
+                .repo_files_service
+                .get_repo_mount_path(repo_id, &new_path)?;
+
+            self.remote_files_service
+                .move_file(
+                    &entry.file.mount_id,
+                    &entry.file.remote_path,
+                    &to_mount_id,
+                    &to_remote_path,
+                )
+                .await?;
+
+            self.repo_files_service
+                .load_file(repo_id, &new_path)
+                .await?;
+
+            self.repo_files_tags_service
+                .set_tags(
+                    repo_id,
+                    &new_path,
+                    Box::new(|_, tags| {
+                        tags.trash_original_path = None;
+                        tags.trash_deleted = None;
+
+                        Ok(())
+                    }),
+                )
+                .await?;
+
+            restored_paths.push(new_path);
+        }
+
+        Ok(restored_paths)
+    }
+
+    /// Permanently deletes the entries.
+    pub async fn purge(
+        &self,
+        repo_id: &RepoId,
+        paths: &[EncryptedPath],
+    ) -> Result<(), RepoTrashError> {
+        for path in paths {
+            self.check_entry_path(repo_id, path)?;
+        }
+
+        for path in paths {
+            let (mount_id, remote_path) =
+                self.repo_files_service.get_repo_mount_path(repo_id, path)?;
+
+            match self
+                .remote_files_service
+                .delete_file(&mount_id, &remote_path)
+                .await
+            {
+                Ok(()) => {}
+                Err(err) if err.is_api_error_code(ApiErrorCode::NotFound) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Permanently deletes the whole trash.
+    pub async fn purge_all(&self, repo_id: &RepoId) -> Result<(), RepoTrashError> {
+        let trash_path = self.repo_files_service.get_trash_path(repo_id)?;
+
+        let (mount_id, remote_path) = self
+            .repo_files_service
+            .get_repo_mount_path(repo_id, &trash_path)?;
+
+        match self
+            .remote_files_service
+            .delete_file(&mount_id, &remote_path)
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(err) if err.is_api_error_code(ApiErrorCode::NotFound) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Permanently deletes the entries older than the repo trash retention.
+    /// Returns the number of purged entries.
+    pub async fn purge_expired(&self, repo_id: &RepoId) -> Result<usize, RepoTrashError> {
+        let retention_days = match self.store.with_state(|state| {
+            repos_selectors::select_repo_trash_settings(state, repo_id)
+                .and_then(|trash_settings| trash_settings.retention_days)
+        }) {
+            Some(retention_days) => retention_days,
+            None => return Ok(0),
+        };
+
+        let now = self.runtime.now();
+
+        let paths: Vec<EncryptedPath> = self
+            .list(repo_id)
+            .await?
+            .into_iter()
+            .filter(|entry| selectors::is_expired(entry, retention_days, now))
+            .map(|entry| entry.file.encrypted_path)
+            .collect();
+
+        self.purge(repo_id, &paths).await?;
+
+        Ok(paths.len())
+    }
+
+    /// Only direct children of the trash folder are trash entries.
+    fn check_entry_path(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<(), RepoTrashError> {
+        let trash_path = self.repo_files_service.get_trash_path(repo_id)?;
+
+        match repo_encrypted_path_utils::parent_path(path) {
+            Some(parent_path) if parent_path == trash_path => Ok(()),
+            _ => Err(RepoTrashError::NotInTrash),
+        }
+    }
+}
+
+impl Drop for RepoTrashService {
+    fn drop(&mut self) {
+        self.store.remove_listener(self.repos_subscription_id);
+    }
+}
+
+fn handle_unlocked_repos(repo_trash_service: Weak<RepoTrashService>, repo_ids: Vec<RepoId>) {
+    let repo_trash_service = match repo_trash_service.upgrade() {
+        Some(repo_trash_service) => repo_trash_service,
+        None => return,
+    };
+
+    for repo_id in repo_ids {
+        let has_retention = repo_trash_service.store.with_state(|state| {
+            repos_selectors::select_repo_trash_settings(state, &repo_id)
+                .map(|trash_settings| trash_settings.retention_days.is_some())
+                .unwrap_or(false)
+        });
+
+        if !has_retention {
+            continue;
+        }
+
+        let purge_repo_trash_service = repo_trash_service.clone();
+
+        repo_trash_service.runtime.spawn(
+            async move {
+                if let Err(err) = purge_repo_trash_service.purge_expired(&repo_id).await {
+                    log::warn!("Failed to purge expired trash for {}: {}", repo_id.0, err);
+                }
+            }
+            .boxed(),
+        );
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{
+    repo_files::state::RepoFile,
+    types::{DecryptedPath, EncryptedPath},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoTrashEntry {
+    /// The file inside the trash folder.
+    pub file: RepoFile,
+    /// None if the file was not moved to the trash by Vault (e.g. it was
+    /// uploaded to the trash folder directly).
+    pub original_path: Option<EncryptedPath>,
+    pub original_decrypted_path: Option<DecryptedPath>,
+    pub deleted: Option<i64>,
+}
@@ -0,0 +1,80 @@
+use thiserror::Error;
+
+use crate::{
+    cipher::errors::DecryptFilenameError,
+    remote::RemoteError,
+    repo_files::errors::{LoadFileError, LoadFilesError, UploadFileReaderError},
+    repo_files_read::errors::GetFilesReaderError,
+    repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
+    user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RepoVersionsError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("{0}")]
+    RepoLocked(#[from] RepoLockedError),
+    #[error("file not found")]
+    FileNotFound,
+    #[error("not a version")]
+    NotAVersion,
+    #[error("not a text file")]
+    NotText,
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("{0}")]
+    GetFilesReaderError(#[from] GetFilesReaderError),
+    #[error("{0}")]
+    UploadFileReaderError(#[from] UploadFileReaderError),
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for RepoVersionsError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::RepoLocked(err) => err.user_error(),
+            Self::FileNotFound => self.to_string(),
+            Self::NotAVersion => String::from("Item is not a version of this file."),
+            Self::NotText => String::from("Only text files can be compared."),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::GetFilesReaderError(err) => err.user_error(),
+            Self::UploadFileReaderError(err) => err.user_error(),
+            Self::RemoteError(err) => err.user_error(),
+            Self::IOError(_) => self.to_string(),
+        }
+    }
+}
+
+impl From<GetCipherError> for RepoVersionsError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
+            GetCipherError::RepoLocked(err) => Self::RepoLocked(err),
+        }
+    }
+}
+
+impl From<LoadFilesError> for RepoVersionsError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFilesError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFilesError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
+
+impl From<LoadFileError> for RepoVersionsError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => Self::RepoNotFound(err),
+            LoadFileError::RepoLocked(err) => Self::RepoLocked(err),
+            LoadFileError::RemoteError(err) => Self::RemoteError(err),
+        }
+    }
+}
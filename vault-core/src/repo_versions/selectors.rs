@@ -0,0 +1,94 @@
+use similar::{ChangeTag, TextDiff};
+
+use crate::{
+    repo_files::{selectors as repo_files_selectors, state::RepoFile},
+    store,
+    types::{EncryptedPath, RepoId},
+};
+
+use super::state::{RepoVersion, RepoVersionDiffLine, RepoVersionDiffLineKind};
+
+pub fn get_version(file: &RepoFile) -> RepoVersion {
+    let tags = file.tags.as_ref().and_then(|tags| tags.as_ref().ok());
+
+    RepoVersion {
+        file: file.clone(),
+        created: repo_files_selectors::get_version_created(file),
+        user_agent: tags.and_then(|tags| tags.version_user_agent.clone()),
+    }
+}
+
+/// Returns the versions sorted by the creation time, the newest first.
+pub fn select_versions(
+    state: &store::State,
+    repo_id: &RepoId,
+    versions_path: &EncryptedPath,
+) -> Vec<RepoVersion> {
+    repo_files_selectors::select_versions(state, repo_id, versions_path)
+        .into_iter()
+        .map(get_version)
+        .collect()
+}
+
+/// Returns a line based diff from the version content (old) to the current
+/// content (new).
+pub fn get_diff(old: &str, new: &str) -> Vec<RepoVersionDiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| RepoVersionDiffLine {
+            kind: match change.tag() {
+                ChangeTag::Equal => RepoVersionDiffLineKind::Equal,
+                ChangeTag::Delete => RepoVersionDiffLineKind::Delete,
+                ChangeTag::Insert => RepoVersionDiffLineKind::Insert,
+            },
+            old_line_number: change.old_index().map(|index| index + 1),
+            new_line_number: change.new_index().map(|index| index + 1),
+            content: change
+                .value()
+                .trim_end_matches(&['\r', '\n'][..])
+                .to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use crate::repo_versions::state::{RepoVersionDiffLine, RepoVersionDiffLineKind};
+
+    use super::get_diff;
+
+    fn line(
+        kind: RepoVersionDiffLineKind,
+        old_line_number: Option<usize>,
+        new_line_number: Option<usize>,
+        content: &str,
+    ) -> RepoVersionDiffLine {
+        RepoVersionDiffLine {
+            kind,
+            old_line_number,
+            new_line_number,
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn test_get_diff() {
+        assert_eq!(
+            get_diff("a\nb\nc\n", "a\nB\nc\nd"),
+            vec![
+                line(RepoVersionDiffLineKind::Equal, Some(1), Some(1), "a"),
+                line(RepoVersionDiffLineKind::Delete, Some(2), None, "b"),
+                line(RepoVersionDiffLineKind::Insert, None, Some(2), "B"),
+                line(RepoVersionDiffLineKind::Equal, Some(3), Some(3), "c"),
+                line(RepoVersionDiffLineKind::Insert, None, Some(4), "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_diff_empty() {
+        assert_eq!(get_diff("", ""), vec![]);
+    }
+}
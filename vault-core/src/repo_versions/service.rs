@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use futures::{io::Cursor, AsyncReadExt};
+
+use crate::{
+    remote::ApiErrorCode,
+    repo_files::{
+        errors::LoadFilesError,
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFilesUploadConflictResolution},
+        RepoFilesService,
+    },
+    repo_files_read::state::RepoFileReaderProvider,
+    store,
+    types::{EncryptedPath, RepoId},
+    utils::repo_encrypted_path_utils,
+};
+
+use super::{
+    errors::RepoVersionsError,
+    selectors,
+    state::{RepoVersion, RepoVersionDiffLine},
+};
+
+/// RepoVersionsService reads the versions that RepoFilesService creates when
+/// a file is overwritten.
+pub struct RepoVersionsService {
+    repo_files_service: Arc<RepoFilesService>,
+    store: Arc<store::Store>,
+}
+
+impl RepoVersionsService {
+    pub fn new(repo_files_service: Arc<RepoFilesService>, store: Arc<store::Store>) -> Self {
+        Self {
+            repo_files_service,
+            store,
+        }
+    }
+
+    /// Returns the versions of the file at path, the newest first.
+    pub async fn list(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<Vec<RepoVersion>, RepoVersionsError> {
+        let versions_path = self
+            .repo_files_service
+            .get_file_versions_path(repo_id, path)?;
+
+        match self
+            .repo_files_service
+            .load_files(repo_id, &versions_path)
+            .await
+        {
+            Ok(()) => {}
+            // the versions folder is created on the first overwrite
+            Err(LoadFilesError::RemoteError(err))
+                if err.is_api_error_code(ApiErrorCode::NotFound) =>
+            {
+                return Ok(Vec::new())
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(self
+            .store
+            .with_state(|state| selectors::select_versions(state, repo_id, &versions_path)))
+    }
+
+    pub async fn get_file_reader(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        version_path: &EncryptedPath,
+    ) -> Result<RepoFileReaderProvider, RepoVersionsError> {
+        let version = self.get_version_file(repo_id, path, version_path).await?;
+
+        let mut provider = self
+            .repo_files_service
+            .clone()
+            .get_file_reader(repo_id, &version.encrypted_path)?;
+
+        // the version is named by its creation time
+        if let Some(file) = self.get_file(repo_id, path) {
+            provider.name = file.decrypted_name()?.clone();
+        }
+
+        Ok(provider)
+    }
+
+    /// Returns a line based diff from the version to the current content of
+    /// the file.
+    pub async fn diff(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        version_path: &EncryptedPath,
+    ) -> Result<Vec<RepoVersionDiffLine>, RepoVersionsError> {
+        let version = self.get_version_file(repo_id, path, version_path).await?;
+
+        self.repo_files_service.load_file(repo_id, path).await?;
+
+        let old = self.read_text(repo_id, &version.encrypted_path).await?;
+        let new = self.read_text(repo_id, path).await?;
+
+        Ok(selectors::get_diff(&old, &new))
+    }
+
+    /// Overwrites the file with the version content. The current content is
+    /// saved as a new version.
+    pub async fn restore(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        version_path: &EncryptedPath,
+    ) -> Result<(), RepoVersionsError> {
+        let version = self.get_version_file(repo_id, path, version_path).await?;
+
+        let (parent_path, name) = repo_encrypted_path_utils::split_parent_name(path)
+            .ok_or(RepoVersionsError::FileNotFound)?;
+
+        // the content is read before the upload because creating the new
+        // version can prune the restored version
+        let data = self.read(repo_id, &version.encrypted_path).await?;
+        let size = data.len() as i64;
+
+        self.repo_files_service
+            .clone()
+            .upload_file_reader(
+                repo_id,
+                &parent_path,
+                name,
+                Box::pin(Cursor::new(data)),
+                Some(size),
+                RepoFilesUploadConflictResolution::Overwrite {
+                    if_remote_size: None,
+                    if_remote_modified: None,
+                    if_remote_hash: None,
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    fn get_file(&self, repo_id: &RepoId, path: &EncryptedPath) -> Option<RepoFile> {
+        self.store.with_state(|state| {
+            repo_files_selectors::select_file(
+                state,
+                &repo_files_selectors::get_file_id(repo_id, path),
+            )
+            .cloned()
+        })
+    }
+
+    /// Only direct children of the versions folder of the file are versions.
+    async fn get_version_file(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        version_path: &EncryptedPath,
+    ) -> Result<RepoFile, RepoVersionsError> {
+        let versions_path = self
+            .repo_files_service
+            .get_file_versions_path(repo_id, path)?;
+
+        match repo_encrypted_path_utils::parent_path(version_path) {
+            Some(parent_path) if parent_path == versions_path => {}
+            _ => return Err(RepoVersionsError::NotAVersion),
+        }
+
+        if self.get_file(repo_id, version_path).is_none() {
+            self.repo_files_service
+                .load_file(repo_id, version_path)
+                .await?;
+        }
+
+        self.get_file(repo_id, version_path)
+            .ok_or(RepoVersionsError::FileNotFound)
+    }
+
+    async fn read(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<Vec<u8>, RepoVersionsError> {
+        let mut reader = self
+            .repo_files_service
+            .clone()
+            .get_file_reader(repo_id, path)?
+            .reader()
+            .await?;
+
+        let mut data = Vec::new();
+
+        reader
+            .reader
+            .read_to_end(&mut data)
+            .await
+            .map_err(|err| RepoVersionsError::IOError(err.to_string()))?;
+
+        Ok(data)
+    }
+
+    async fn read_text(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+    ) -> Result<String, RepoVersionsError> {
+        String::from_utf8(self.read(repo_id, path).await?).map_err(|_| RepoVersionsError::NotText)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::repo_files::state::RepoFile;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoVersion {
+    /// The file inside the versions folder.
+    pub file: RepoFile,
+    pub created: Option<i64>,
+    /// User agent of the client that overwrote the file. None if the client
+    /// did not know its user agent.
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoVersionDiffLineKind {
+    Equal,
+    /// The line is only in the version.
+    Delete,
+    /// The line is only in the current file.
+    Insert,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoVersionDiffLine {
+    pub kind: RepoVersionDiffLineKind,
+    /// 1-based line number in the version.
+    pub old_line_number: Option<usize>,
+    /// 1-based line number in the current file.
+    pub new_line_number: Option<usize>,
+    /// Line content without the line ending.
+    pub content: String,
+}
@@ -46,12 +46,27 @@ impl UserError for InvalidPasswordError {
     }
 }
 
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("invalid name options: {0}")]
+pub struct InvalidNameOptionsError(pub String);
+
+impl UserError for InvalidNameOptionsError {
+    fn user_error(&self) -> String {
+        format!(
+            "Safe Box file name options are not supported ({}). Names cannot be decrypted.",
+            self.0
+        )
+    }
+}
+
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum BuildCipherError {
     #[error("{0}")]
     RepoNotFound(#[from] RepoNotFoundError),
     #[error("{0}")]
     InvalidPassword(#[from] InvalidPasswordError),
+    #[error("{0}")]
+    InvalidNameOptions(#[from] InvalidNameOptionsError),
 }
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -113,6 +128,8 @@ pub enum UnlockRepoError {
     RepoUnlocked(#[from] RepoUnlockedError),
     #[error("{0}")]
     InvalidPassword(#[from] InvalidPasswordError),
+    #[error("{0}")]
+    InvalidNameOptions(#[from] InvalidNameOptionsError),
 }
 
 impl UserError for UnlockRepoError {
@@ -121,6 +138,7 @@ impl UserError for UnlockRepoError {
             Self::RepoNotFound(err) => err.user_error(),
             Self::RepoUnlocked(err) => err.user_error(),
             Self::InvalidPassword(err) => err.user_error(),
+            Self::InvalidNameOptions(err) => err.user_error(),
         }
     }
 }
@@ -130,6 +148,7 @@ impl From<BuildCipherError> for UnlockRepoError {
         match err {
             BuildCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
             BuildCipherError::InvalidPassword(err) => Self::InvalidPassword(err),
+            BuildCipherError::InvalidNameOptions(err) => Self::InvalidNameOptions(err),
         }
     }
 }
@@ -153,6 +172,8 @@ impl UserError for GetCipherError {
 
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum CreateRepoError {
+    #[error("name options are not supported")]
+    NameOptionsNotSupported,
     #[error("{0}")]
     RemoteError(#[from] remote::RemoteError),
 }
@@ -160,6 +181,9 @@ pub enum CreateRepoError {
 impl UserError for CreateRepoError {
     fn user_error(&self) -> String {
         match self {
+            Self::NameOptionsNotSupported => String::from(
+                "Only the default file name encryption is supported for Safe Boxes in Koofr.",
+            ),
             Self::RemoteError(remote::RemoteError::ApiError {
                 code: remote::ApiErrorCode::VaultReposAlreadyExists,
                 ..
@@ -180,6 +204,8 @@ pub enum RemoveRepoError {
     #[error("{0}")]
     InvalidPassword(#[from] InvalidPasswordError),
     #[error("{0}")]
+    InvalidNameOptions(#[from] InvalidNameOptionsError),
+    #[error("{0}")]
     RemoteError(#[from] remote::RemoteError),
 }
 
@@ -188,6 +214,7 @@ impl UserError for RemoveRepoError {
         match self {
             Self::RepoNotFound(err) => err.user_error(),
             Self::InvalidPassword(err) => err.user_error(),
+            Self::InvalidNameOptions(err) => err.user_error(),
             Self::RemoteError(err) => err.user_error(),
         }
     }
@@ -198,6 +225,33 @@ impl From<BuildCipherError> for RemoveRepoError {
         match err {
             BuildCipherError::RepoNotFound(err) => Self::RepoNotFound(err),
             BuildCipherError::InvalidPassword(err) => Self::InvalidPassword(err),
+            BuildCipherError::InvalidNameOptions(err) => Self::InvalidNameOptions(err),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum UpdateRepoError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("repo update is not supported by the server")]
+    UpdateNotSupported,
+    #[error("{0}")]
+    RemoteError(#[from] remote::RemoteError),
+}
+
+impl UserError for UpdateRepoError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::UpdateNotSupported => {
+                String::from("Safe Box cannot be updated on this server. Try again later.")
+            }
+            Self::RemoteError(remote::RemoteError::ApiError {
+                code: remote::ApiErrorCode::VaultReposAlreadyExists,
+                ..
+            }) => String::from("This location is already a Safe Box."),
+            Self::RemoteError(err) => err.user_error(),
         }
     }
 }
@@ -218,3 +272,37 @@ impl UserError for SetAutoLockError {
         }
     }
 }
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SetTrashSettingsError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("storage error: {0}")]
+    StorageError(#[from] SecureStorageError),
+}
+
+impl UserError for SetTrashSettingsError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::StorageError(err) => format!("Storage error: {}", err),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SetVersionsSettingsError {
+    #[error("{0}")]
+    RepoNotFound(#[from] RepoNotFoundError),
+    #[error("storage error: {0}")]
+    StorageError(#[from] SecureStorageError),
+}
+
+impl UserError for SetVersionsSettingsError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::RepoNotFound(err) => err.user_error(),
+            Self::StorageError(err) => format!("Storage error: {}", err),
+        }
+    }
+}
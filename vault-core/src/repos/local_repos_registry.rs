@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::{
+    remote::{models, ApiErrorCode, RemoteError},
+    runtime,
+    secure_storage::{errors::SecureStorageError, SecureStorageService},
+    types::{RemotePath, RepoId},
+    utils::remote_path_utils,
+};
+
+pub const LOCAL_REPOS_REGISTRY_STORAGE_KEY: &str = "vaultLocalReposRegistry";
+
+/// Registry of repos for storage backends without the vault repos API. Repos
+/// are stored as JSON in the secure storage.
+pub struct LocalReposRegistry {
+    secure_storage_service: Arc<SecureStorageService>,
+    runtime: Arc<runtime::BoxRuntime>,
+}
+
+impl LocalReposRegistry {
+    pub fn new(
+        secure_storage_service: Arc<SecureStorageService>,
+        runtime: Arc<runtime::BoxRuntime>,
+    ) -> Self {
+        Self {
+            secure_storage_service,
+            runtime,
+        }
+    }
+
+    pub fn get_repos(&self) -> Result<Vec<models::VaultRepo>, RemoteError> {
+        self.secure_storage_service
+            .get::<Vec<models::VaultRepo>>(LOCAL_REPOS_REGISTRY_STORAGE_KEY)
+            .map(|x| x.unwrap_or_default())
+            .map_err(storage_error)
+    }
+
+    fn set_repos(&self, repos: &Vec<models::VaultRepo>) -> Result<(), RemoteError> {
+        self.secure_storage_service
+            .set(LOCAL_REPOS_REGISTRY_STORAGE_KEY, repos)
+            .map_err(storage_error)
+    }
+
+    pub fn create_repo(
+        &self,
+        create: models::VaultRepoCreate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        let mut repos = self.get_repos()?;
+
+        if repos
+            .iter()
+            .any(|repo| repo.mount_id == create.mount_id && repo.path == create.path)
+        {
+            return Err(RemoteError::from_code(
+                ApiErrorCode::VaultReposAlreadyExists,
+                "Safe Box already exists",
+            ));
+        }
+
+        let repo = models::VaultRepo {
+            id: RepoId(Uuid::new_v4().to_string()),
+            name: get_repo_name(&create.path),
+            mount_id: create.mount_id,
+            path: create.path,
+            salt: create.salt,
+            password_validator: create.password_validator,
+            password_validator_encrypted: create.password_validator_encrypted,
+            filename_encryption: create.filename_encryption,
+            directory_name_encryption: create.directory_name_encryption,
+            filename_encoding: create.filename_encoding,
+            added: self.runtime.now().0,
+        };
+
+        repos.push(repo.clone());
+
+        self.set_repos(&repos)?;
+
+        Ok(repo)
+    }
+
+    pub fn update_repo(
+        &self,
+        repo_id: &RepoId,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        let mut repos = self.get_repos()?;
+
+        let repo = repos
+            .iter_mut()
+            .find(|repo| &repo.id == repo_id)
+            .ok_or_else(not_found)?;
+
+        repo.name = get_repo_name(&update.path);
+        repo.path = update.path;
+        repo.salt = update.salt;
+        repo.password_validator = update.password_validator;
+        repo.password_validator_encrypted = update.password_validator_encrypted;
+
+        let repo = repo.clone();
+
+        self.set_repos(&repos)?;
+
+        Ok(repo)
+    }
+
+    pub fn remove_repo(&self, repo_id: &RepoId) -> Result<(), RemoteError> {
+        let mut repos = self.get_repos()?;
+
+        let len = repos.len();
+
+        repos.retain(|repo| &repo.id != repo_id);
+
+        if repos.len() == len {
+            return Err(not_found());
+        }
+
+        self.set_repos(&repos)
+    }
+}
+
+/// Repos are named after the last component of the location path.
+fn get_repo_name(path: &RemotePath) -> String {
+    remote_path_utils::path_to_name(path)
+        .map(|name| name.0)
+        .unwrap_or_default()
+}
+
+fn not_found() -> RemoteError {
+    RemoteError::from_code(ApiErrorCode::NotFound, "Safe Box not found")
+}
+
+fn storage_error(err: SecureStorageError) -> RemoteError {
+    RemoteError::from_code(ApiErrorCode::Other("StorageError".into()), &err.to_string())
+}
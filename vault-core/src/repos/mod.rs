@@ -1,5 +1,7 @@
 pub mod errors;
+pub mod local_repos_registry;
 pub mod mutations;
+pub mod name_options;
 pub mod password_validator;
 pub mod repo_tree;
 pub mod selectors;
@@ -20,16 +20,25 @@ use super::{
         LockRepoError, RemoveRepoError, RepoLockedError, RepoNotFoundError, RepoUnlockedError,
         UnlockRepoError,
     },
+    name_options,
     repo_tree::RepoTree,
     selectors,
-    state::{Repo, RepoAutoLock, RepoState},
+    state::{Repo, RepoAutoLock, RepoState, RepoTrashSettings, RepoVersionsSettings},
 };
 
 fn vault_repo_to_repo(
     repo: models::VaultRepo,
     base_url: &str,
     auto_lock: Option<RepoAutoLock>,
+    trash_settings: Option<RepoTrashSettings>,
+    versions_settings: Option<RepoVersionsSettings>,
 ) -> Repo {
+    let name_options = name_options::from_vault_repo(&repo);
+
+    if let Err(err) = &name_options {
+        log::warn!("Repo {}: {}", repo.id.0, err);
+    }
+
     let models::VaultRepo {
         id,
         name,
@@ -39,6 +48,7 @@ fn vault_repo_to_repo(
         password_validator,
         password_validator_encrypted,
         added,
+        ..
     } = repo;
 
     let web_url = format!(
@@ -54,6 +64,7 @@ fn vault_repo_to_repo(
         mount_id,
         path,
         salt,
+        name_options,
         added,
         password_validator,
         password_validator_encrypted,
@@ -61,11 +72,25 @@ fn vault_repo_to_repo(
         web_url,
         last_activity: None,
         auto_lock,
+        trash_settings,
+        versions_settings,
     }
 }
 
-fn repo_loaded(state: &mut store::State, repo: models::VaultRepo, auto_lock: Option<RepoAutoLock>) {
-    let mut repo = vault_repo_to_repo(repo, &state.config.base_url, auto_lock);
+fn repo_loaded(
+    state: &mut store::State,
+    repo: models::VaultRepo,
+    auto_lock: Option<RepoAutoLock>,
+    trash_settings: Option<RepoTrashSettings>,
+    versions_settings: Option<RepoVersionsSettings>,
+) {
+    let mut repo = vault_repo_to_repo(
+        repo,
+        &state.config.base_url,
+        auto_lock,
+        trash_settings,
+        versions_settings,
+    );
 
     if let Some(existing) = state.repos.repos_by_id.get(&repo.id) {
         repo.state = existing.state.clone();
@@ -110,6 +135,8 @@ pub fn repos_loaded(
     mutation_notify: &store::MutationNotify,
     res: Result<Vec<models::VaultRepo>, RemoteError>,
     auto_locks: &HashMap<RepoId, RepoAutoLock>,
+    trash_settings: &HashMap<RepoId, RepoTrashSettings>,
+    versions_settings: &HashMap<RepoId, RepoVersionsSettings>,
 ) {
     match res {
         Ok(repos) => {
@@ -134,8 +161,16 @@ pub fn repos_loaded(
 
             for repo in repos {
                 let auto_lock = auto_locks.get(&repo.id).cloned();
-
-                repo_loaded(state, repo, auto_lock);
+                let repo_trash_settings = trash_settings.get(&repo.id).cloned();
+                let repo_versions_settings = versions_settings.get(&repo.id).cloned();
+
+                repo_loaded(
+                    state,
+                    repo,
+                    auto_lock,
+                    repo_trash_settings,
+                    repo_versions_settings,
+                );
             }
 
             remove_repos(state, mutation_state, &remove_repo_ids);
@@ -255,13 +290,55 @@ pub fn repo_created(
     mutation_notify: &store::MutationNotify,
     repo: models::VaultRepo,
 ) {
-    repo_loaded(state, repo, None);
+    repo_loaded(state, repo, None, None, None);
 
     notify(store::Event::Repos);
 
     mutation_notify(store::MutationEvent::Repos, state, mutation_state);
 }
 
+pub fn repo_updated(
+    state: &mut store::State,
+    notify: &store::Notify,
+    mutation_state: &mut store::MutationState,
+    mutation_notify: &store::MutationNotify,
+    repo: models::VaultRepo,
+) -> Result<(), RepoNotFoundError> {
+    let existing = selectors::select_repo_mut(state, &repo.id)?;
+
+    // the cipher is not valid anymore if the password has changed
+    if let RepoState::Unlocked { cipher } = &existing.state {
+        mutation_state
+            .repos
+            .locked_repos
+            .push((repo.id.clone(), cipher.clone()));
+
+        existing.state = RepoState::Locked;
+    }
+
+    let auto_lock = existing.auto_lock.clone();
+    let trash_settings = existing.trash_settings.clone();
+    let versions_settings = existing.versions_settings.clone();
+    let old_file_id =
+        remote_files_selectors::get_file_id(&existing.mount_id, &existing.path.to_lowercase());
+    let old_mount_id = existing.mount_id.clone();
+    let old_path = existing.path.clone();
+
+    state.repos.repo_ids_by_remote_file_id.remove(&old_file_id);
+
+    if let Some(repo_tree) = state.repos.mount_repo_trees.get_mut(&old_mount_id) {
+        repo_tree.remove(&old_path);
+    }
+
+    repo_loaded(state, repo, auto_lock, trash_settings, versions_settings);
+
+    notify(store::Event::Repos);
+
+    mutation_notify(store::MutationEvent::Repos, state, mutation_state);
+
+    Ok(())
+}
+
 pub fn repo_removed(
     state: &mut store::State,
     notify: &store::Notify,
@@ -314,6 +391,36 @@ pub fn set_auto_lock(
     Ok(())
 }
 
+pub fn set_trash_settings(
+    state: &mut store::State,
+    notify: &store::Notify,
+    repo_id: &RepoId,
+    trash_settings: RepoTrashSettings,
+) -> Result<(), RepoNotFoundError> {
+    let repo = selectors::select_repo_mut(state, repo_id)?;
+
+    notify(store::Event::Repos);
+
+    repo.trash_settings = Some(trash_settings);
+
+    Ok(())
+}
+
+pub fn set_versions_settings(
+    state: &mut store::State,
+    notify: &store::Notify,
+    repo_id: &RepoId,
+    versions_settings: RepoVersionsSettings,
+) -> Result<(), RepoNotFoundError> {
+    let repo = selectors::select_repo_mut(state, repo_id)?;
+
+    notify(store::Event::Repos);
+
+    repo.versions_settings = Some(versions_settings);
+
+    Ok(())
+}
+
 pub fn set_default_auto_lock(
     state: &mut store::State,
     notify: &store::Notify,
@@ -0,0 +1,118 @@
+use vault_crypto::name_cipher::{FilenameEncoding, FilenameEncryption, NameCipherOptions};
+
+use crate::remote::models;
+
+use super::errors::InvalidNameOptionsError;
+
+/// Name options of repos created by other rclone crypt users are stored with
+/// the repo. Missing options are rclone defaults. Unknown values are errors,
+/// names must never be decrypted with the wrong options.
+pub fn from_vault_repo(
+    repo: &models::VaultRepo,
+) -> Result<NameCipherOptions, InvalidNameOptionsError> {
+    let default = NameCipherOptions::default();
+
+    Ok(NameCipherOptions {
+        filename_encryption: match repo.filename_encryption.as_deref() {
+            Some(value) => value
+                .parse::<FilenameEncryption>()
+                .map_err(|err| InvalidNameOptionsError(err.to_string()))?,
+            None => default.filename_encryption,
+        },
+        directory_name_encryption: repo
+            .directory_name_encryption
+            .unwrap_or(default.directory_name_encryption),
+        filename_encoding: match repo.filename_encoding.as_deref() {
+            Some(value) => value
+                .parse::<FilenameEncoding>()
+                .map_err(|err| InvalidNameOptionsError(err.to_string()))?,
+            None => default.filename_encoding,
+        },
+    })
+}
+
+/// Only options that differ from the defaults are set so that the repos
+/// created with default options look the same as before.
+pub fn to_vault_repo_create(
+    name_options: &NameCipherOptions,
+    create: models::VaultRepoCreate,
+) -> models::VaultRepoCreate {
+    let default = NameCipherOptions::default();
+
+    models::VaultRepoCreate {
+        filename_encryption: (name_options.filename_encryption != default.filename_encryption)
+            .then(|| name_options.filename_encryption.to_string()),
+        directory_name_encryption: (name_options.directory_name_encryption
+            != default.directory_name_encryption)
+            .then_some(name_options.directory_name_encryption),
+        filename_encoding: (name_options.filename_encoding != default.filename_encoding)
+            .then(|| name_options.filename_encoding.to_string()),
+        ..create
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+    use vault_crypto::name_cipher::{FilenameEncoding, FilenameEncryption, NameCipherOptions};
+
+    use crate::remote::{models, test_helpers::create_repo};
+
+    use super::{from_vault_repo, to_vault_repo_create};
+
+    #[test]
+    fn test_from_vault_repo() {
+        let repo = create_repo("r1", "m1", "/Vault");
+
+        assert_eq!(from_vault_repo(&repo), Ok(NameCipherOptions::default()));
+
+        let repo = models::VaultRepo {
+            filename_encryption: Some("obfuscate".into()),
+            directory_name_encryption: Some(false),
+            filename_encoding: Some("base32768".into()),
+            ..repo
+        };
+
+        assert_eq!(
+            from_vault_repo(&repo),
+            Ok(NameCipherOptions {
+                filename_encryption: FilenameEncryption::Obfuscate,
+                directory_name_encryption: false,
+                filename_encoding: FilenameEncoding::Base32768,
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_vault_repo_unknown_value() {
+        let repo = models::VaultRepo {
+            filename_encoding: Some("base1024".into()),
+            ..create_repo("r1", "m1", "/Vault")
+        };
+
+        assert!(from_vault_repo(&repo).is_err());
+    }
+
+    #[test]
+    fn test_to_vault_repo_create() {
+        let create = to_vault_repo_create(
+            &NameCipherOptions::default(),
+            models::VaultRepoCreate::default(),
+        );
+
+        assert_eq!(create, models::VaultRepoCreate::default());
+
+        let create = to_vault_repo_create(
+            &NameCipherOptions {
+                filename_encryption: FilenameEncryption::Off,
+                directory_name_encryption: true,
+                filename_encoding: FilenameEncoding::Base64,
+            },
+            models::VaultRepoCreate::default(),
+        );
+
+        assert_eq!(create.filename_encryption.as_deref(), Some("off"));
+        assert_eq!(create.directory_name_encryption, None);
+        assert_eq!(create.filename_encoding.as_deref(), Some("base64"));
+    }
+}
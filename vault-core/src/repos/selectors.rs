@@ -9,7 +9,7 @@ use crate::{
 
 use super::{
     errors::{GetCipherError, RepoInfoError, RepoLockedError, RepoNotFoundError},
-    state::{Repo, RepoAutoLock, RepoInfo, RepoState},
+    state::{Repo, RepoAutoLock, RepoInfo, RepoState, RepoTrashSettings, RepoVersionsSettings},
 };
 
 pub fn select_repos<'a>(state: &'a store::State) -> Vec<&'a Repo> {
@@ -127,6 +127,63 @@ pub fn select_auto_locks(state: &store::State) -> HashMap<RepoId, RepoAutoLock>
         .collect()
 }
 
+pub fn select_trash_settings(state: &store::State) -> HashMap<RepoId, RepoTrashSettings> {
+    state
+        .repos
+        .repos_by_id
+        .iter()
+        .filter_map(|(repo_id, repo)| {
+            repo.trash_settings
+                .clone()
+                .map(|trash_settings| (repo_id.to_owned(), trash_settings))
+        })
+        .collect()
+}
+
+pub fn select_repo_trash_settings<'a>(
+    state: &'a store::State,
+    repo_id: &RepoId,
+) -> Option<&'a RepoTrashSettings> {
+    select_repo(state, repo_id)
+        .ok()
+        .and_then(|repo| repo.trash_settings.as_ref())
+}
+
+pub fn select_trash_enabled(state: &store::State, repo_id: &RepoId) -> bool {
+    select_repo_trash_settings(state, repo_id)
+        .map(|trash_settings| trash_settings.enabled)
+        .unwrap_or(false)
+}
+
+pub fn select_versions_settings(state: &store::State) -> HashMap<RepoId, RepoVersionsSettings> {
+    state
+        .repos
+        .repos_by_id
+        .iter()
+        .filter_map(|(repo_id, repo)| {
+            repo.versions_settings
+                .clone()
+                .map(|versions_settings| (repo_id.to_owned(), versions_settings))
+        })
+        .collect()
+}
+
+pub fn select_repo_versions_settings<'a>(
+    state: &'a store::State,
+    repo_id: &RepoId,
+) -> Option<&'a RepoVersionsSettings> {
+    select_repo(state, repo_id)
+        .ok()
+        .and_then(|repo| repo.versions_settings.as_ref())
+}
+
+/// Returns the number of versions to keep or None if versions are disabled.
+pub fn select_max_versions(state: &store::State, repo_id: &RepoId) -> Option<u32> {
+    select_repo_versions_settings(state, repo_id)
+        .filter(|versions_settings| versions_settings.enabled)
+        .map(|versions_settings| versions_settings.max_versions)
+}
+
 pub fn select_default_auto_lock<'a>(state: &'a store::State) -> &'a RepoAutoLock {
     &state.config.repos.default_auto_lock
 }
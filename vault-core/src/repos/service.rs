@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use lazy_static::lazy_static;
+use vault_crypto::name_cipher::NameCipherOptions;
 
 use crate::{
     cipher::Cipher,
@@ -9,6 +10,7 @@ use crate::{
     remote_files::RemoteFilesService,
     runtime,
     secure_storage::{errors::SecureStorageError, SecureStorageService},
+    storage_backend::BoxStorageBackend,
     store,
     types::{DecryptedName, MountId, RemoteName, RemotePath, RepoId},
     utils::remote_path_utils,
@@ -17,12 +19,17 @@ use crate::{
 use super::{
     errors::{
         BuildCipherError, CreateRepoError, GetCipherError, InvalidPasswordError, LoadReposError,
-        LockRepoError, RemoveRepoError, RepoNotFoundError, SetAutoLockError, UnlockRepoError,
+        LockRepoError, RemoveRepoError, RepoNotFoundError, SetAutoLockError, SetTrashSettingsError,
+        SetVersionsSettingsError, UnlockRepoError, UpdateRepoError,
     },
-    mutations,
+    local_repos_registry::LocalReposRegistry,
+    mutations, name_options,
     password_validator::{check_password_validator, generate_password_validator},
     selectors,
-    state::{Repo, RepoAutoLock, RepoConfig, RepoCreated, RepoUnlockMode},
+    state::{
+        Repo, RepoAutoLock, RepoConfig, RepoCreated, RepoTrashSettings, RepoUnlockMode,
+        RepoVersionsSettings,
+    },
 };
 
 lazy_static! {
@@ -37,8 +44,17 @@ type RepoAutoLocks = HashMap<RepoId, RepoAutoLock>;
 
 pub const REPO_AUTO_LOCKS_STORAGE_KEY: &str = "vaultRepoAutoLocks";
 
+type ReposTrashSettings = HashMap<RepoId, RepoTrashSettings>;
+
+pub const REPO_TRASH_SETTINGS_STORAGE_KEY: &str = "vaultRepoTrashSettings";
+
+type ReposVersionsSettings = HashMap<RepoId, RepoVersionsSettings>;
+
+pub const REPO_VERSIONS_SETTINGS_STORAGE_KEY: &str = "vaultRepoVersionsSettings";
+
 pub struct ReposService {
-    remote: Arc<remote::Remote>,
+    storage_backend: Arc<BoxStorageBackend>,
+    local_repos_registry: LocalReposRegistry,
     remote_files_service: Arc<RemoteFilesService>,
     secure_storage_service: Arc<SecureStorageService>,
     store: Arc<store::Store>,
@@ -47,14 +63,18 @@ pub struct ReposService {
 
 impl ReposService {
     pub fn new(
-        remote: Arc<remote::Remote>,
+        storage_backend: Arc<BoxStorageBackend>,
         remote_files_service: Arc<RemoteFilesService>,
         secure_storage_service: Arc<SecureStorageService>,
         store: Arc<store::Store>,
         runtime: Arc<runtime::BoxRuntime>,
     ) -> Self {
+        let local_repos_registry =
+            LocalReposRegistry::new(secure_storage_service.clone(), runtime.clone());
+
         Self {
-            remote,
+            storage_backend,
+            local_repos_registry,
             remote_files_service,
             secure_storage_service,
             store,
@@ -68,13 +88,91 @@ impl ReposService {
             .map(|x| x.unwrap_or_default())
     }
 
+    pub fn get_trash_settings(&self) -> Result<ReposTrashSettings, SecureStorageError> {
+        self.secure_storage_service
+            .get::<ReposTrashSettings>(REPO_TRASH_SETTINGS_STORAGE_KEY)
+            .map(|x| x.unwrap_or_default())
+    }
+
+    pub fn get_versions_settings(&self) -> Result<ReposVersionsSettings, SecureStorageError> {
+        self.secure_storage_service
+            .get::<ReposVersionsSettings>(REPO_VERSIONS_SETTINGS_STORAGE_KEY)
+            .map(|x| x.unwrap_or_default())
+    }
+
+    /// Returns true if the name options are stored with the repos so that
+    /// every client decrypts the names with the same options. Koofr does not
+    /// store them.
+    pub fn stores_name_options(&self) -> bool {
+        !self.storage_backend.has_vault_repos_api()
+            || self.storage_backend.has_vault_repo_name_options()
+    }
+
+    async fn get_vault_repos(&self) -> Result<Vec<models::VaultRepo>, remote::RemoteError> {
+        if self.storage_backend.has_vault_repos_api() {
+            self.storage_backend
+                .get_vault_repos()
+                .await
+                .map(|res| res.repos)
+        } else {
+            self.local_repos_registry.get_repos()
+        }
+    }
+
+    async fn create_vault_repo(
+        &self,
+        create: models::VaultRepoCreate,
+    ) -> Result<models::VaultRepo, remote::RemoteError> {
+        if self.storage_backend.has_vault_repos_api() {
+            self.storage_backend.create_vault_repo(create).await
+        } else {
+            self.local_repos_registry.create_repo(create)
+        }
+    }
+
+    async fn update_vault_repo(
+        &self,
+        repo_id: &RepoId,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, remote::RemoteError> {
+        if self.storage_backend.has_vault_repos_api() {
+            self.storage_backend
+                .update_vault_repo(repo_id, update)
+                .await
+        } else {
+            self.local_repos_registry.update_repo(repo_id, update)
+        }
+    }
+
+    async fn remove_vault_repo(&self, repo_id: &RepoId) -> Result<(), remote::RemoteError> {
+        if self.storage_backend.has_vault_repos_api() {
+            self.storage_backend.remove_vault_repo(repo_id).await
+        } else {
+            self.local_repos_registry.remove_repo(repo_id)
+        }
+    }
+
+    /// Reads the repo from the server without updating the state.
+    pub async fn get_remote_repo(
+        &self,
+        repo_id: &RepoId,
+    ) -> Result<models::VaultRepo, remote::RemoteError> {
+        self.get_vault_repos()
+            .await?
+            .into_iter()
+            .find(|repo| &repo.id == repo_id)
+            .ok_or_else(|| {
+                remote::RemoteError::from_code(remote::ApiErrorCode::NotFound, "Repo not found")
+            })
+    }
+
     pub async fn load_repos(&self) -> Result<(), LoadReposError> {
         self.store
             .mutate(|state, notify, mutation_state, mutation_notify| {
                 mutations::repos_loading(state, notify, mutation_state, mutation_notify);
             });
 
-        let res = self.remote.get_vault_repos().await.map(|res| res.repos);
+        let res = self.get_vault_repos().await;
 
         let res_err = res
             .as_ref()
@@ -82,6 +180,8 @@ impl ReposService {
             .map_err(|err| LoadReposError::from(err.to_owned()));
 
         let auto_locks = self.get_auto_locks()?;
+        let trash_settings = self.get_trash_settings()?;
+        let versions_settings = self.get_versions_settings()?;
 
         self.store
             .mutate(|state, notify, mutation_state, mutation_notify| {
@@ -92,6 +192,8 @@ impl ReposService {
                     mutation_notify,
                     res,
                     &auto_locks,
+                    &trash_settings,
+                    &versions_settings,
                 );
             });
 
@@ -110,18 +212,23 @@ impl ReposService {
         repo_id: &RepoId,
         password: &str,
     ) -> Result<Cipher, BuildCipherError> {
-        let (salt, password_validator, password_validator_encrypted) =
+        let (salt, name_options, password_validator, password_validator_encrypted) =
             self.store.with_state(|state| {
                 selectors::select_repo(state, repo_id).map(|repo| {
                     (
                         repo.salt.clone(),
+                        repo.name_options.clone(),
                         repo.password_validator.clone(),
                         repo.password_validator_encrypted.clone(),
                     )
                 })
             })?;
 
-        let cipher = Cipher::new(vault_crypto::Cipher::new(password, salt.as_deref()));
+        let cipher = Cipher::new(vault_crypto::Cipher::new_with_name_options(
+            password,
+            salt.as_deref(),
+            name_options?,
+        ));
 
         if !check_password_validator(&cipher, &password_validator, &password_validator_encrypted) {
             return Err(BuildCipherError::InvalidPassword(InvalidPasswordError));
@@ -175,13 +282,23 @@ impl ReposService {
         path: &RemotePath,
         password: &str,
         salt: Option<&str>,
+        name_options: NameCipherOptions,
     ) -> Result<RepoCreated, CreateRepoError> {
+        // other clients would decrypt the names with the default options
+        if name_options != NameCipherOptions::default() && !self.stores_name_options() {
+            return Err(CreateRepoError::NameOptionsNotSupported);
+        }
+
         let already_exists = match (
             remote_path_utils::parent_path(&path),
             remote_path_utils::path_to_name(&path),
         ) {
             (Some(parent_path), Some(name)) => {
-                match self.remote.create_dir(&mount_id, &parent_path, name).await {
+                match self
+                    .storage_backend
+                    .create_dir(&mount_id, &parent_path, name)
+                    .await
+                {
                     Ok(_) => false,
                     Err(remote::RemoteError::ApiError {
                         code: remote::ApiErrorCode::AlreadyExists,
@@ -195,26 +312,33 @@ impl ReposService {
             _ => false,
         };
 
-        let cipher = Cipher::new(vault_crypto::Cipher::new(password, salt.as_deref()));
+        let cipher = Cipher::new(vault_crypto::Cipher::new_with_name_options(
+            password,
+            salt.as_deref(),
+            name_options,
+        ));
 
         let (password_validator, password_validator_encrypted) =
             generate_password_validator(&cipher);
 
         let repo = self
-            .remote
-            .create_vault_repo(models::VaultRepoCreate {
-                mount_id: mount_id.to_owned(),
-                path: path.to_owned(),
-                salt: salt.map(str::to_string),
-                password_validator,
-                password_validator_encrypted,
-            })
+            .create_vault_repo(name_options::to_vault_repo_create(
+                &name_options,
+                models::VaultRepoCreate {
+                    mount_id: mount_id.to_owned(),
+                    path: path.to_owned(),
+                    salt: salt.map(str::to_string),
+                    password_validator,
+                    password_validator_encrypted,
+                    ..Default::default()
+                },
+            ))
             .await?;
         let repo_id = repo.id.clone();
 
         if !already_exists {
             for name in DEFAULT_DIR_NAMES.iter() {
-                let encrypted_name = cipher.encrypt_filename(name);
+                let encrypted_name = cipher.encrypt_dir_name(name);
 
                 self.remote_files_service
                     .create_dir_name(&mount_id, &path, RemoteName(encrypted_name.0))
@@ -229,30 +353,101 @@ impl ReposService {
 
                 let repo = selectors::select_repo(state, &repo_id).unwrap();
 
-                self.generate_repo_config(repo, &password)
+                self.generate_repo_config(repo, &password, name_options)
             });
 
         Ok(RepoCreated { repo_id, config })
     }
 
-    pub async fn remove_repo(
+    /// Updates the repo location, salt and password validator on the remote.
+    /// The repo is locked because its cipher is not valid anymore if the
+    /// password has changed.
+    pub async fn update_repo(
         &self,
         repo_id: &RepoId,
-        password: &str,
-    ) -> Result<(), RemoveRepoError> {
-        let _ = self.build_cipher(repo_id, password)?;
+        path: &RemotePath,
+        salt: Option<&str>,
+        cipher: &Cipher,
+    ) -> Result<(), UpdateRepoError> {
+        let (password_validator, password_validator_encrypted) =
+            generate_password_validator(cipher);
 
-        let res = self
-            .remote
-            .remove_vault_repo(repo_id)
+        let repo = self
+            .update_vault_repo(
+                repo_id,
+                models::VaultRepoUpdate {
+                    path: path.to_owned(),
+                    salt: salt.map(str::to_string),
+                    password_validator,
+                    password_validator_encrypted,
+                },
+            )
             .await
             .map_err(|e| match e {
                 remote::RemoteError::ApiError {
                     code: remote::ApiErrorCode::NotFound,
                     ..
-                } => RemoveRepoError::RepoNotFound(RepoNotFoundError),
-                _ => RemoveRepoError::RemoteError(e),
-            });
+                } => UpdateRepoError::RepoNotFound(RepoNotFoundError),
+                _ => UpdateRepoError::RemoteError(e),
+            })?;
+
+        self.store
+            .mutate(|state, notify, mutation_state, mutation_notify| {
+                mutations::repo_updated(state, notify, mutation_state, mutation_notify, repo)
+            })?;
+
+        Ok(())
+    }
+
+    /// Checks that the server can update the repo by sending an update with
+    /// the current values. Must be called before any work that can only be
+    /// finished with update_repo.
+    pub async fn check_update_repo(&self, repo_id: &RepoId) -> Result<(), UpdateRepoError> {
+        let update = self.store.with_state(|state| {
+            selectors::select_repo(state, repo_id).map(|repo| models::VaultRepoUpdate {
+                path: repo.path.clone(),
+                salt: repo.salt.clone(),
+                password_validator: repo.password_validator.clone(),
+                password_validator_encrypted: repo.password_validator_encrypted.clone(),
+            })
+        })?;
+
+        // the repo exists in the state so NotFound means that the endpoint is
+        // missing
+        self.update_vault_repo(repo_id, update)
+            .await
+            .map(|_| ())
+            .map_err(|e| match e {
+                remote::RemoteError::ApiError {
+                    code: remote::ApiErrorCode::NotFound,
+                    ..
+                }
+                | remote::RemoteError::ApiError {
+                    status_code: Some(404 | 405 | 501),
+                    ..
+                }
+                | remote::RemoteError::UnexpectedStatus {
+                    status_code: 404 | 405 | 501,
+                    ..
+                } => UpdateRepoError::UpdateNotSupported,
+                _ => UpdateRepoError::RemoteError(e),
+            })
+    }
+
+    pub async fn remove_repo(
+        &self,
+        repo_id: &RepoId,
+        password: &str,
+    ) -> Result<(), RemoveRepoError> {
+        let _ = self.build_cipher(repo_id, password)?;
+
+        let res = self.remove_vault_repo(repo_id).await.map_err(|e| match e {
+            remote::RemoteError::ApiError {
+                code: remote::ApiErrorCode::NotFound,
+                ..
+            } => RemoveRepoError::RepoNotFound(RepoNotFoundError),
+            _ => RemoveRepoError::RemoteError(e),
+        });
 
         match res {
             Ok(()) | Err(RemoveRepoError::RepoNotFound(..)) => {
@@ -298,6 +493,42 @@ impl ReposService {
         })
     }
 
+    pub fn set_trash_settings(
+        &self,
+        repo_id: &RepoId,
+        trash_settings: RepoTrashSettings,
+    ) -> Result<(), SetTrashSettingsError> {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::set_trash_settings(state, notify, repo_id, trash_settings)
+                .map_err(SetTrashSettingsError::RepoNotFound)?;
+
+            let trash_settings = selectors::select_trash_settings(state);
+
+            self.secure_storage_service
+                .set(REPO_TRASH_SETTINGS_STORAGE_KEY, &trash_settings)?;
+
+            Ok(())
+        })
+    }
+
+    pub fn set_versions_settings(
+        &self,
+        repo_id: &RepoId,
+        versions_settings: RepoVersionsSettings,
+    ) -> Result<(), SetVersionsSettingsError> {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::set_versions_settings(state, notify, repo_id, versions_settings)
+                .map_err(SetVersionsSettingsError::RepoNotFound)?;
+
+            let versions_settings = selectors::select_versions_settings(state);
+
+            self.secure_storage_service
+                .set(REPO_VERSIONS_SETTINGS_STORAGE_KEY, &versions_settings)?;
+
+            Ok(())
+        })
+    }
+
     pub fn set_default_auto_lock(&self, auto_lock: RepoAutoLock) {
         self.store.mutate(|state, notify, _, _| {
             mutations::set_default_auto_lock(state, notify, auto_lock);
@@ -309,21 +540,27 @@ impl ReposService {
         repo_id: &RepoId,
         password: &str,
     ) -> Result<RepoConfig, UnlockRepoError> {
-        self.unlock_repo(repo_id, password, RepoUnlockMode::Verify)?;
+        let cipher = self.build_cipher(repo_id, password)?;
 
         self.store.with_state(|state| {
             let repo = selectors::select_repo(state, repo_id)?;
 
-            Ok(self.generate_repo_config(&repo, password))
+            Ok(self.generate_repo_config(&repo, password, *cipher.name_options()))
         })
     }
 
-    fn generate_repo_config(&self, repo: &Repo, password: &str) -> RepoConfig {
+    fn generate_repo_config(
+        &self,
+        repo: &Repo,
+        password: &str,
+        name_options: NameCipherOptions,
+    ) -> RepoConfig {
         let rclone_config = rclone::config::generate_config(&rclone::config::Config {
             name: Some(repo.name.0.clone()),
             path: repo.path.0.clone(),
             password: password.to_owned(),
             salt: repo.salt.clone(),
+            name_options,
         });
 
         RepoConfig {
@@ -331,6 +568,7 @@ impl ReposService {
             location: repo.get_location(),
             password: password.to_owned(),
             salt: repo.salt.clone(),
+            name_options,
             rclone_config,
         }
     }
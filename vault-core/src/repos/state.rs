@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Deserializer, Serialize};
+use vault_crypto::name_cipher::NameCipherOptions;
 
 use crate::{
     cipher::Cipher,
@@ -10,7 +11,10 @@ use crate::{
     types::{DecryptedName, MountId, RemoteFileId, RemotePath, RepoId, TimeMillis},
 };
 
-use super::{errors::RepoInfoError, repo_tree::RepoTree};
+use super::{
+    errors::{InvalidNameOptionsError, RepoInfoError},
+    repo_tree::RepoTree,
+};
 
 #[derive(Debug, Clone)]
 pub enum RepoState {
@@ -105,6 +109,25 @@ impl<'de> Deserialize<'de> for RepoAutoLockAfter {
     }
 }
 
+/// Deleted files are moved to the repo trash instead of being deleted if the
+/// trash is enabled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepoTrashSettings {
+    pub enabled: bool,
+    /// Trash entries older than this are purged when the repo is unlocked.
+    /// Entries are kept until they are purged manually if None.
+    pub retention_days: Option<u32>,
+}
+
+/// Previous contents of overwritten files are kept in the repo versions
+/// folder if versions are enabled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepoVersionsSettings {
+    pub enabled: bool,
+    /// Only the newest max_versions versions of each file are kept.
+    pub max_versions: u32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Repo {
     pub id: RepoId,
@@ -112,6 +135,8 @@ pub struct Repo {
     pub mount_id: MountId,
     pub path: RemotePath,
     pub salt: Option<String>,
+    /// Err if the options are not supported. Such a repo cannot be unlocked.
+    pub name_options: Result<NameCipherOptions, InvalidNameOptionsError>,
     pub added: i64,
     pub password_validator: String,
     pub password_validator_encrypted: String,
@@ -119,6 +144,8 @@ pub struct Repo {
     pub state: RepoState,
     pub last_activity: Option<TimeMillis>,
     pub auto_lock: Option<RepoAutoLock>,
+    pub trash_settings: Option<RepoTrashSettings>,
+    pub versions_settings: Option<RepoVersionsSettings>,
 }
 
 impl Repo {
@@ -143,6 +170,7 @@ pub struct RepoConfig {
     pub location: RemoteFilesLocation,
     pub password: String,
     pub salt: Option<String>,
+    pub name_options: NameCipherOptions,
     pub rclone_config: String,
 }
 
@@ -30,6 +30,8 @@ pub fn create_repo(
         &mutation_notify,
         Ok(vec![repo]),
         &HashMap::new(),
+        &HashMap::new(),
+        &HashMap::new(),
     );
 
     let repo = selectors::select_repo(state, &repo_id).unwrap().clone();
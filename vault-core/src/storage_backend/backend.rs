@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+    common::state::BoxAsyncRead,
+    remote::{
+        models,
+        remote::{ListRecursiveItemStream, RemoteFileTagsSetConditions},
+        RemoteError, RemoteFileMoveConditions, RemoteFileReader, RemoteFileReaderRange,
+        RemoteFileRemoveConditions, RemoteFileUploadConflictResolution,
+    },
+    types::{MountId, RemoteName, RemotePath, RepoId},
+};
+
+use super::errors::StorageBackendErrors;
+
+/// Options for StorageBackend::upload_file_reader.
+pub struct StorageBackendUploadOptions {
+    pub size: Option<i64>,
+    pub modified: Option<i64>,
+    pub conflict_resolution: RemoteFileUploadConflictResolution,
+    pub on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+}
+
+/// StorageBackend is the storage used by RemoteFilesService and ReposService.
+/// Paths are remote paths inside a mount. Errors use the Koofr API error
+/// codes (e.g. NotFound, AlreadyExists, Conflict) so that the services can
+/// handle them the same way for every backend.
+///
+/// Upload sessions, bookmarks, shared files and the vault repos API are
+/// optional. Repos are kept in a local registry if the backend has no vault
+/// repos API.
+#[async_trait]
+pub trait StorageBackend {
+    async fn get_mount(&self, id: &MountId) -> Result<models::Mount, RemoteError>;
+
+    async fn get_places(&self) -> Result<Vec<models::Mount>, RemoteError>;
+
+    async fn get_bookmarks(&self) -> Result<Vec<models::Bookmark>, RemoteError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_shared(&self) -> Result<Vec<models::SharedFile>, RemoteError> {
+        Ok(Vec::new())
+    }
+
+    /// Lists the dir.
+    async fn get_bundle(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<models::Bundle, RemoteError>;
+
+    async fn get_list_recursive(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<ListRecursiveItemStream, RemoteError>;
+
+    async fn get_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<models::FilesFile, RemoteError>;
+
+    async fn get_file_reader(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        range: Option<&RemoteFileReaderRange>,
+    ) -> Result<RemoteFileReader, RemoteError>;
+
+    async fn upload_file_reader(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        reader: BoxAsyncRead,
+        options: StorageBackendUploadOptions,
+    ) -> Result<models::FilesFile, RemoteError>;
+
+    fn has_upload_sessions(&self) -> bool {
+        false
+    }
+
+    async fn create_upload_session(
+        &self,
+        _mount_id: &MountId,
+        _parent_path: &RemotePath,
+        _name: &RemoteName,
+        _size: Option<i64>,
+        _modified: Option<i64>,
+        _conflict_resolution: RemoteFileUploadConflictResolution,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn get_upload_session(
+        &self,
+        _mount_id: &MountId,
+        _session_id: &str,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn upload_session_chunk(
+        &self,
+        _mount_id: &MountId,
+        _session_id: &str,
+        _offset: i64,
+        _reader: BoxAsyncRead,
+        _on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn commit_upload_session(
+        &self,
+        _mount_id: &MountId,
+        _session_id: &str,
+    ) -> Result<models::FilesFile, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn delete_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        conditions: RemoteFileRemoveConditions,
+    ) -> Result<(), RemoteError>;
+
+    async fn create_dir(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: RemoteName,
+    ) -> Result<(), RemoteError>;
+
+    async fn rename_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        new_name: RemoteName,
+    ) -> Result<(), RemoteError>;
+
+    async fn copy_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        to_mount_id: &MountId,
+        to_path: &RemotePath,
+    ) -> Result<(), RemoteError>;
+
+    async fn move_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        to_mount_id: &MountId,
+        to_path: &RemotePath,
+        conditions: RemoteFileMoveConditions,
+    ) -> Result<(), RemoteError>;
+
+    async fn file_set_tags(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        tags: HashMap<String, Vec<String>>,
+        conditions: RemoteFileTagsSetConditions,
+    ) -> Result<(), RemoteError>;
+
+    fn has_vault_repos_api(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the vault repos keep the name options
+    /// (filename_encryption, directory_name_encryption and filename_encoding).
+    fn has_vault_repo_name_options(&self) -> bool {
+        false
+    }
+
+    async fn get_vault_repos(&self) -> Result<models::VaultReposBundle, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn create_vault_repo(
+        &self,
+        _create: models::VaultRepoCreate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn update_vault_repo(
+        &self,
+        _repo_id: &RepoId,
+        _update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+
+    async fn remove_vault_repo(&self, _repo_id: &RepoId) -> Result<(), RemoteError> {
+        Err(StorageBackendErrors::not_supported())
+    }
+}
+
+pub type BoxStorageBackend = Box<dyn StorageBackend + Send + Sync>;
@@ -0,0 +1,16 @@
+use crate::remote::{ApiErrorCode, RemoteError};
+
+pub struct StorageBackendErrors;
+
+impl StorageBackendErrors {
+    pub fn not_supported() -> RemoteError {
+        RemoteError::from_code(
+            ApiErrorCode::Other("NotSupported".into()),
+            "Not supported by the storage backend",
+        )
+    }
+
+    pub fn is_not_supported(err: &RemoteError) -> bool {
+        err.is_api_error_code(ApiErrorCode::Other("NotSupported".into()))
+    }
+}
@@ -0,0 +1,8 @@
+pub mod backend;
+pub mod errors;
+pub mod remote_storage_backend;
+
+pub use self::{
+    backend::{BoxStorageBackend, StorageBackend, StorageBackendUploadOptions},
+    remote_storage_backend::RemoteStorageBackend,
+};
@@ -0,0 +1,270 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    common::state::BoxAsyncRead,
+    remote::{
+        models,
+        remote::{ListRecursiveItemStream, RemoteFileTagsSetConditions},
+        Remote, RemoteError, RemoteFileMoveConditions, RemoteFileReader, RemoteFileReaderRange,
+        RemoteFileRemoveConditions, RemoteFileUploadConflictResolution,
+    },
+    types::{MountId, RemoteName, RemotePath, RepoId},
+};
+
+use super::{errors::StorageBackendErrors, StorageBackend, StorageBackendUploadOptions};
+
+/// Koofr storage backend. It supports all optional features. Upload sessions
+/// are not implemented by all servers, they are disabled when the server does
+/// not know the endpoint.
+pub struct RemoteStorageBackend {
+    remote: Arc<Remote>,
+    upload_sessions_unsupported: AtomicBool,
+}
+
+impl RemoteStorageBackend {
+    pub fn new(remote: Arc<Remote>) -> Self {
+        Self {
+            remote,
+            upload_sessions_unsupported: AtomicBool::new(false),
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for RemoteStorageBackend {
+    async fn get_mount(&self, id: &MountId) -> Result<models::Mount, RemoteError> {
+        self.remote.get_mount(id).await
+    }
+
+    async fn get_places(&self) -> Result<Vec<models::Mount>, RemoteError> {
+        self.remote.get_places().await
+    }
+
+    async fn get_bookmarks(&self) -> Result<Vec<models::Bookmark>, RemoteError> {
+        self.remote.get_bookmarks().await
+    }
+
+    async fn get_shared(&self) -> Result<Vec<models::SharedFile>, RemoteError> {
+        self.remote.get_shared().await
+    }
+
+    async fn get_bundle(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<models::Bundle, RemoteError> {
+        self.remote.get_bundle(mount_id, path).await
+    }
+
+    async fn get_list_recursive(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<ListRecursiveItemStream, RemoteError> {
+        self.remote.get_list_recursive(mount_id, path).await
+    }
+
+    async fn get_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<models::FilesFile, RemoteError> {
+        self.remote.get_file(mount_id, path).await
+    }
+
+    async fn get_file_reader(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        range: Option<&RemoteFileReaderRange>,
+    ) -> Result<RemoteFileReader, RemoteError> {
+        self.remote.get_file_reader(mount_id, path, range).await
+    }
+
+    async fn upload_file_reader(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        reader: BoxAsyncRead,
+        options: StorageBackendUploadOptions,
+    ) -> Result<models::FilesFile, RemoteError> {
+        self.remote
+            .upload_file_reader(
+                mount_id,
+                parent_path,
+                name,
+                reader,
+                options.size,
+                options.modified,
+                options.conflict_resolution,
+                options.on_progress,
+            )
+            .await
+    }
+
+    fn has_upload_sessions(&self) -> bool {
+        !self.upload_sessions_unsupported.load(Ordering::SeqCst)
+    }
+
+    async fn create_upload_session(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        size: Option<i64>,
+        modified: Option<i64>,
+        conflict_resolution: RemoteFileUploadConflictResolution,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        match self
+            .remote
+            .create_upload_session(
+                mount_id,
+                parent_path,
+                name,
+                size,
+                modified,
+                conflict_resolution,
+            )
+            .await
+        {
+            Err(err) if err.is_not_implemented() => {
+                self.upload_sessions_unsupported
+                    .store(true, Ordering::SeqCst);
+
+                Err(StorageBackendErrors::not_supported())
+            }
+            res => res,
+        }
+    }
+
+    async fn get_upload_session(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        self.remote.get_upload_session(mount_id, session_id).await
+    }
+
+    async fn upload_session_chunk(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+        offset: i64,
+        reader: BoxAsyncRead,
+        on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    ) -> Result<models::FilesUploadSession, RemoteError> {
+        self.remote
+            .upload_session_chunk(mount_id, session_id, offset, reader, on_progress)
+            .await
+    }
+
+    async fn commit_upload_session(
+        &self,
+        mount_id: &MountId,
+        session_id: &str,
+    ) -> Result<models::FilesFile, RemoteError> {
+        self.remote
+            .commit_upload_session(mount_id, session_id)
+            .await
+    }
+
+    async fn delete_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        conditions: RemoteFileRemoveConditions,
+    ) -> Result<(), RemoteError> {
+        self.remote.delete_file(mount_id, path, conditions).await
+    }
+
+    async fn create_dir(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: RemoteName,
+    ) -> Result<(), RemoteError> {
+        self.remote.create_dir(mount_id, parent_path, name).await
+    }
+
+    async fn rename_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        new_name: RemoteName,
+    ) -> Result<(), RemoteError> {
+        self.remote.rename_file(mount_id, path, new_name).await
+    }
+
+    async fn copy_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        to_mount_id: &MountId,
+        to_path: &RemotePath,
+    ) -> Result<(), RemoteError> {
+        self.remote
+            .copy_file(mount_id, path, to_mount_id, to_path)
+            .await
+    }
+
+    async fn move_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        to_mount_id: &MountId,
+        to_path: &RemotePath,
+        conditions: RemoteFileMoveConditions,
+    ) -> Result<(), RemoteError> {
+        self.remote
+            .move_file(mount_id, path, to_mount_id, to_path, conditions)
+            .await
+    }
+
+    async fn file_set_tags(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        tags: HashMap<String, Vec<String>>,
+        conditions: RemoteFileTagsSetConditions,
+    ) -> Result<(), RemoteError> {
+        self.remote
+            .file_set_tags(mount_id, path, tags, conditions)
+            .await
+    }
+
+    fn has_vault_repos_api(&self) -> bool {
+        true
+    }
+
+    async fn get_vault_repos(&self) -> Result<models::VaultReposBundle, RemoteError> {
+        self.remote.get_vault_repos().await
+    }
+
+    async fn create_vault_repo(
+        &self,
+        create: models::VaultRepoCreate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        self.remote.create_vault_repo(create).await
+    }
+
+    async fn update_vault_repo(
+        &self,
+        repo_id: &RepoId,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        self.remote.update_vault_repo(repo_id, update).await
+    }
+
+    async fn remove_vault_repo(&self, repo_id: &RepoId) -> Result<(), RemoteError> {
+        self.remote.remove_vault_repo(repo_id).await
+    }
+}
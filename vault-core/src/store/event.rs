@@ -12,8 +12,10 @@ pub enum Event {
     RepoCreate,
     RepoUnlock,
     RepoRemove,
+    RepoPasswordChange,
     RepoConfigBackup,
     RepoSpaceUsage,
+    RepoSearch,
     RepoFiles,
     RepoFilesBrowsers,
     RepoFilesDetails,
@@ -39,8 +41,10 @@ impl Event {
             Self::RepoCreate,
             Self::RepoUnlock,
             Self::RepoRemove,
+            Self::RepoPasswordChange,
             Self::RepoConfigBackup,
             Self::RepoSpaceUsage,
+            Self::RepoSearch,
             Self::RepoFiles,
             Self::RepoFilesBrowsers,
             Self::RepoFilesDetails,
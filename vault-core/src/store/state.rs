@@ -6,7 +6,8 @@ use crate::{
     repo_config_backup::state::RepoConfigBackupsState, repo_create::state::RepoCreatesState,
     repo_files::state::RepoFilesState, repo_files_browsers::state::RepoFilesBrowsersState,
     repo_files_details::state::RepoFilesDetailsState, repo_files_move::state::RepoFilesMoveState,
-    repo_remove::state::RepoRemovesState, repo_space_usage::state::RepoSpaceUsagesState,
+    repo_password_change::state::RepoPasswordChangesState, repo_remove::state::RepoRemovesState,
+    repo_search::state::RepoSearchesState, repo_space_usage::state::RepoSpaceUsagesState,
     repo_unlock::state::RepoUnlocksState, repos::state::ReposState,
     space_usage::state::SpaceUsageState, transfers::state::TransfersState, user::state::UserState,
 };
@@ -26,8 +27,10 @@ pub struct State {
     pub repo_creates: RepoCreatesState,
     pub repo_unlocks: RepoUnlocksState,
     pub repo_removes: RepoRemovesState,
+    pub repo_password_changes: RepoPasswordChangesState,
     pub repo_config_backups: RepoConfigBackupsState,
     pub repo_space_usages: RepoSpaceUsagesState,
+    pub repo_searches: RepoSearchesState,
     pub repo_files: RepoFilesState,
     pub repo_files_browsers: RepoFilesBrowsersState,
     pub repo_files_details: RepoFilesDetailsState,
@@ -52,8 +55,10 @@ impl State {
         self.repo_creates.reset();
         self.repo_unlocks.reset();
         self.repo_removes.reset();
+        self.repo_password_changes.reset();
         self.repo_config_backups.reset();
         self.repo_space_usages.reset();
+        self.repo_searches.reset();
         self.repo_files.reset();
         self.repo_files_browsers.reset();
         self.repo_files_details.reset();
@@ -0,0 +1,169 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    common::state::BoxAsyncRead, runtime, store, types::TimeMillis,
+    utils::rate_limited_reader::RateLimitedReader,
+};
+
+use super::{selectors, state::TransferDirection};
+
+/// TokenBucket allows bursts of up to one second of the rate. Reads are not
+/// split, a read larger than the available tokens puts the bucket into debt
+/// which the reader pays off by waiting.
+#[derive(Debug, Default)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Option<TimeMillis>,
+}
+
+impl TokenBucket {
+    /// Returns how long the reader has to wait before the next read.
+    fn consume(&mut self, rate: Option<u64>, n: usize, now: TimeMillis) -> Duration {
+        let rate = match rate {
+            Some(rate) if rate > 0 => rate as f64,
+            _ => {
+                // the bucket starts full when a limit is set again
+                *self = Self::default();
+
+                return Duration::ZERO;
+            }
+        };
+
+        self.tokens = match self.last_refill {
+            Some(last_refill) => {
+                let elapsed = (now - last_refill).num_milliseconds().max(0) as f64 / 1000.0;
+
+                (self.tokens + elapsed * rate).min(rate)
+            }
+            None => rate,
+        };
+        self.last_refill = Some(now);
+
+        self.tokens -= n as f64;
+
+        if self.tokens < 0.0 {
+            Duration::from_secs_f64(-self.tokens / rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// BandwidthLimiter enforces the bandwidth limits from the transfers config.
+/// The buckets are shared by all transfers so the limits apply to the sum of
+/// all concurrent transfers. Limits are read on every read so changes apply
+/// to transfers that are already running.
+pub struct BandwidthLimiter {
+    store: Arc<store::Store>,
+    runtime: Arc<runtime::BoxRuntime>,
+
+    total: Mutex<TokenBucket>,
+    upload: Mutex<TokenBucket>,
+    download: Mutex<TokenBucket>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(store: Arc<store::Store>, runtime: Arc<runtime::BoxRuntime>) -> Self {
+        Self {
+            store,
+            runtime,
+
+            total: Default::default(),
+            upload: Default::default(),
+            download: Default::default(),
+        }
+    }
+
+    pub fn consume(&self, direction: TransferDirection, n: usize) -> Duration {
+        let now = self.runtime.now();
+
+        let limits = self
+            .store
+            .with_state(|state| selectors::select_bandwidth_limits(state, now).clone());
+
+        let (direction_limit, direction_bucket) = match direction {
+            TransferDirection::Upload => (limits.upload, &self.upload),
+            TransferDirection::Download => (limits.download, &self.download),
+        };
+
+        let total_wait = self.total.lock().unwrap().consume(limits.total, n, now);
+        let direction_wait = direction_bucket
+            .lock()
+            .unwrap()
+            .consume(direction_limit, n, now);
+
+        total_wait.max(direction_wait)
+    }
+
+    pub fn reader(
+        self: Arc<Self>,
+        reader: BoxAsyncRead,
+        direction: TransferDirection,
+    ) -> BoxAsyncRead {
+        let runtime = self.runtime.clone();
+
+        Box::pin(RateLimitedReader::new(
+            reader,
+            Box::new(move |n| self.consume(direction, n)),
+            Box::new(move |duration| runtime.sleep(duration)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::types::TimeMillis;
+
+    use super::TokenBucket;
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::default();
+
+        // burst of one second
+        assert_eq!(
+            bucket.consume(Some(1000), 1000, TimeMillis(0)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            bucket.consume(Some(1000), 500, TimeMillis(0)),
+            Duration::from_millis(500)
+        );
+        // the debt is paid off after 500 ms
+        assert_eq!(
+            bucket.consume(Some(1000), 250, TimeMillis(500)),
+            Duration::from_millis(250)
+        );
+        // the bucket does not fill over the rate
+        assert_eq!(
+            bucket.consume(Some(1000), 1000, TimeMillis(10000)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            bucket.consume(Some(1000), 100, TimeMillis(10000)),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn test_token_bucket_unlimited() {
+        let mut bucket = TokenBucket::default();
+
+        assert_eq!(
+            bucket.consume(Some(1000), 2000, TimeMillis(0)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(bucket.consume(None, 2000, TimeMillis(0)), Duration::ZERO);
+        assert_eq!(bucket.consume(Some(0), 2000, TimeMillis(0)), Duration::ZERO);
+        // the debt is forgotten when the limit is removed
+        assert_eq!(
+            bucket.consume(Some(1000), 1000, TimeMillis(0)),
+            Duration::ZERO
+        );
+    }
+}
@@ -91,6 +91,9 @@ pub enum TransferError {
     IOError(String),
     #[error("aborted")]
     Aborted,
+    /// Error of a failed transfer restored from a previous session.
+    #[error("{0}")]
+    RestoredError(String),
 }
 
 impl UserError for TransferError {
@@ -110,6 +113,7 @@ impl UserError for TransferError {
             Self::AlreadyExists => self.to_string(),
             Self::IOError(_) => self.to_string(),
             Self::Aborted => "Transfer has been aborted.".into(),
+            Self::RestoredError(_) => self.to_string(),
         }
     }
 }
@@ -133,6 +137,8 @@ impl From<UploadFileReaderError> for TransferError {
             }
             UploadFileReaderError::Canceled => TransferError::Aborted,
             UploadFileReaderError::RemoteError(err) => TransferError::RemoteError(err),
+            UploadFileReaderError::IOError(err) => TransferError::IOError(err),
+            UploadFileReaderError::HashMismatch { .. } => TransferError::IOError(err.to_string()),
         }
     }
 }
@@ -153,6 +159,7 @@ impl From<GetFilesReaderError> for TransferError {
             GetFilesReaderError::RepoLocked(err) => Self::RepoLocked(err),
             GetFilesReaderError::FileNotFound => Self::RemoteFileNotFound,
             GetFilesReaderError::FilesEmpty => Self::RemoteFilesEmpty,
+            GetFilesReaderError::InvalidRange => Self::IOError(err.to_string()),
             GetFilesReaderError::DecryptFilenameError(err) => Self::DecryptFilenameError(err),
             GetFilesReaderError::DecryptSizeError(err) => Self::DecryptSizeError(err),
             GetFilesReaderError::RemoteError(err) => Self::RemoteError(err),
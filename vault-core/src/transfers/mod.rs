@@ -1,7 +1,9 @@
+pub mod bandwidth_limiter;
 pub mod bytes_uploadable;
 pub mod downloadable;
 pub mod errors;
 pub mod mutations;
+pub mod persistence;
 pub mod selectors;
 pub mod service;
 pub mod state;
@@ -12,7 +12,8 @@ use super::{
     selectors,
     state::{
         RetryInitiator, Transfer, TransferDisplayName, TransferState, TransferType,
-        TransferUploadRelativeName, TransferUploadRelativeNamePath, TransfersState, UploadTransfer,
+        TransferUploadRelativeName, TransferUploadRelativeNamePath, TransfersBandwidthLimits,
+        TransfersBandwidthSchedule, TransfersState, UploadTransfer,
     },
 };
 
@@ -60,7 +61,7 @@ pub fn create_upload_transfer(
         Some(name_rel_path) => EncryptedPath(path_utils::join_path_name(
             &parent_path.0,
             &cipher
-                .encrypt_path(&DecryptedPath(format!("/{}", name_rel_path.0)))
+                .encrypt_dir_path(&DecryptedPath(format!("/{}", name_rel_path.0)))
                 .0[1..],
         )),
         None => parent_path,
@@ -177,6 +178,7 @@ fn create_transfer(
     notify(store::Event::Transfers);
 
     let category = name_to_category(&name);
+    let order = selectors::select_next_order(state);
 
     let transfer = Transfer {
         id,
@@ -191,7 +193,7 @@ fn create_transfer(
         state: TransferState::Waiting,
         transferred_bytes: 0,
         attempts: 0,
-        order: state.transfers.total_count,
+        order,
     };
 
     state.transfers.transfers.insert(id.clone(), transfer);
@@ -255,17 +257,27 @@ pub fn upload_transfer_processed(
     id: u32,
     size: SizeInfo,
     cipher: &Cipher,
+    keep_current_name: bool,
 ) -> Result<EncryptedName, TransferError> {
     let transfer = match state.transfers.transfers.get(&id) {
         Some(transfer) => transfer,
         None => return Err(TransferError::TransferNotFound),
     };
+    // the transfer was paused while it was being processed
+    if matches!(transfer.state, TransferState::Paused) {
+        return Err(TransferError::Aborted);
+    }
     let upload_transfer = match transfer.upload_transfer() {
         Some(upload_transfer) => upload_transfer,
         None => return Err(TransferError::TransferNotFound),
     };
 
-    let name = selectors::select_unused_name(state, transfer, upload_transfer);
+    // the file of the previous attempt is overwritten
+    let name = if keep_current_name {
+        upload_transfer.current_name.clone()
+    } else {
+        selectors::select_unused_name(state, transfer, upload_transfer)
+    };
     let encrypted_name = cipher.encrypt_filename(&name);
 
     let transfer = match state.transfers.transfers.get_mut(&id) {
@@ -321,6 +333,11 @@ pub fn download_transfer_processed(
         None => return Err(TransferError::TransferNotFound),
     };
 
+    // the transfer was paused while it was being processed
+    if matches!(transfer.state, TransferState::Paused) {
+        return Err(TransferError::Aborted);
+    }
+
     notify(store::Event::Transfers);
 
     transfer.state = TransferState::Transferring;
@@ -352,8 +369,8 @@ pub fn transfer_progress(
     now: TimeMillis,
 ) {
     let transfer = match state.transfers.transfers.get_mut(&id) {
-        Some(transfer) => transfer,
-        None => return,
+        Some(transfer) if !matches!(transfer.state, TransferState::Paused) => transfer,
+        _ => return,
     };
 
     transfer.transferred_bytes += n;
@@ -366,29 +383,63 @@ pub fn transfer_progress(
     }
 }
 
+/// Sets the progress of a resumed transfer to the bytes that were already
+/// transferred in previous attempts.
+pub fn transfer_resumed(
+    state: &mut store::State,
+    notify: &store::Notify,
+    id: u32,
+    transferred_bytes: i64,
+) {
+    let transfer = match state.transfers.transfers.get_mut(&id) {
+        Some(transfer) if !matches!(transfer.state, TransferState::Paused) => transfer,
+        _ => return,
+    };
+
+    notify(store::Event::Transfers);
+
+    state.transfers.done_bytes += transferred_bytes - transfer.transferred_bytes;
+
+    transfer.transferred_bytes = transferred_bytes;
+}
+
 pub fn transfer_done(state: &mut store::State, notify: &store::Notify, id: u32) -> bool {
     let remove = match state.transfers.transfers.get_mut(&id) {
         Some(transfer) => {
             notify(store::Event::Transfers);
 
-            transfer.size = SizeInfo::Exact(transfer.transferred_bytes);
+            if matches!(transfer.state, TransferState::Paused) {
+                // the transfer finished before it could be paused. its
+                // progress was already reset so all of it is counted as done
+                // now
+                let size = transfer.size.exact_or_estimate().unwrap_or(0);
 
-            match &transfer.typ {
-                TransferType::Upload(..) => state.transfers.transferring_uploads_count -= 1,
-                TransferType::Download | TransferType::DownloadReader => {
-                    state.transfers.transferring_downloads_count -= 1
+                transfer.transferred_bytes = size;
+                state.transfers.done_bytes += size;
+                state.transfers.paused_count -= 1;
+            } else {
+                match &transfer.typ {
+                    TransferType::Upload(..) => state.transfers.transferring_uploads_count -= 1,
+                    TransferType::Download | TransferType::DownloadReader => {
+                        state.transfers.transferring_downloads_count -= 1
+                    }
                 }
+
+                state.transfers.transferring_count -= 1;
             }
 
-            if transfer.is_persistent {
+            transfer.size = SizeInfo::Exact(transfer.transferred_bytes);
+
+            // done downloads are kept so that they can be opened, persistent
+            // uploads are only kept until they are done
+            if transfer.is_openable {
                 transfer.started = None;
                 transfer.state = TransferState::Done;
             }
 
             state.transfers.done_count += 1;
-            state.transfers.transferring_count -= 1;
 
-            !transfer.is_persistent
+            !transfer.is_openable
         }
         None => false,
     };
@@ -402,6 +453,46 @@ pub fn transfer_done(state: &mut store::State, notify: &store::Notify, id: u32)
     remove
 }
 
+pub fn upload_transfer_restored(
+    state: &mut store::State,
+    notify: &store::Notify,
+    id: u32,
+    attempts: usize,
+    error: Option<TransferError>,
+    paused: bool,
+) {
+    let transfer = match state.transfers.transfers.get_mut(&id) {
+        Some(transfer) => transfer,
+        None => return,
+    };
+
+    notify(store::Event::Transfers);
+
+    transfer.attempts = attempts;
+
+    if paused && error.is_none() {
+        transfer.state = TransferState::Paused;
+
+        state.transfers.paused_count += 1;
+    }
+
+    if let Some(error) = error {
+        transfer.state = TransferState::Failed { error };
+
+        match transfer.size {
+            SizeInfo::Exact(size) => state.transfers.failed_bytes += size,
+            SizeInfo::Estimate(size) => state.transfers.failed_bytes += size,
+            SizeInfo::Unknown => {}
+        }
+
+        state.transfers.failed_count += 1;
+
+        if transfer.is_retriable {
+            state.transfers.retriable_count += 1;
+        }
+    }
+}
+
 pub fn transfer_failed(
     state: &mut store::State,
     notify: &store::Notify,
@@ -409,6 +500,18 @@ pub fn transfer_failed(
     err: TransferError,
     now: TimeMillis,
 ) {
+    // the transfer was paused and will be started again when it is resumed
+    if matches!(
+        state
+            .transfers
+            .transfers
+            .get(&id)
+            .map(|transfer| &transfer.state),
+        Some(TransferState::Paused)
+    ) {
+        return;
+    }
+
     if matches!(err, TransferError::Aborted) {
         abort(state, notify, id);
 
@@ -474,6 +577,9 @@ pub fn abort(state: &mut store::State, notify: &store::Notify, id: u32) {
 
         match &transfer.state {
             TransferState::Waiting => {}
+            TransferState::Paused => {
+                state.transfers.paused_count -= 1;
+            }
             TransferState::Processing | TransferState::Transferring => {
                 state.transfers.done_bytes -= transfer.transferred_bytes;
                 state.transfers.transferring_count -= 1;
@@ -582,6 +688,133 @@ pub fn retry_all(state: &mut store::State, notify: &store::Notify, now: TimeMill
     }
 }
 
+/// Returns true if the transfer was in progress and has to be stopped.
+pub fn pause(state: &mut store::State, notify: &store::Notify, id: u32) -> bool {
+    let transfer = match state.transfers.transfers.get_mut(&id) {
+        Some(transfer) => transfer,
+        None => return false,
+    };
+
+    if !selectors::can_pause(transfer) {
+        return false;
+    }
+
+    notify(store::Event::Transfers);
+
+    let was_transferring = !matches!(transfer.state, TransferState::Waiting);
+
+    if was_transferring {
+        // resumable uploads continue from their upload session, everything
+        // else starts from the beginning when resumed
+        state.transfers.done_bytes -= transfer.transferred_bytes;
+
+        transfer.transferred_bytes = 0;
+        transfer.started = None;
+        // pausing is not a failed attempt
+        transfer.attempts = transfer.attempts.saturating_sub(1);
+
+        state.transfers.transferring_count -= 1;
+
+        match &transfer.typ {
+            TransferType::Upload(..) => state.transfers.transferring_uploads_count -= 1,
+            TransferType::Download | TransferType::DownloadReader => {
+                state.transfers.transferring_downloads_count -= 1
+            }
+        }
+    }
+
+    transfer.state = TransferState::Paused;
+
+    state.transfers.paused_count += 1;
+
+    cleanup(state, notify);
+
+    was_transferring
+}
+
+/// Returns ids of the transfers that were in progress and have to be stopped.
+pub fn pause_all(state: &mut store::State, notify: &store::Notify) -> Vec<u32> {
+    let ids: Vec<u32> = selectors::select_transfers(state)
+        .into_iter()
+        .map(|transfer| transfer.id)
+        .collect();
+
+    ids.into_iter()
+        .filter(|id| pause(state, notify, *id))
+        .collect()
+}
+
+pub fn resume(state: &mut store::State, notify: &store::Notify, id: u32) {
+    let transfer = match state.transfers.transfers.get_mut(&id) {
+        Some(transfer) => transfer,
+        None => return,
+    };
+
+    if !selectors::can_resume(transfer) {
+        return;
+    }
+
+    notify(store::Event::Transfers);
+
+    transfer.state = TransferState::Waiting;
+
+    state.transfers.paused_count -= 1;
+}
+
+pub fn resume_all(state: &mut store::State, notify: &store::Notify) {
+    for id in state
+        .transfers
+        .transfers
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>()
+    {
+        resume(state, notify, id);
+    }
+}
+
+/// Moves the transfer in front of all the other transfers. Transfers that
+/// were in front of it move back by one.
+pub fn move_to_front(state: &mut store::State, notify: &store::Notify, id: u32) {
+    let order = match state.transfers.transfers.get(&id) {
+        Some(transfer) => transfer.order,
+        None => return,
+    };
+
+    let first_order = match selectors::select_first_order(state) {
+        Some(first_order) if first_order < order => first_order,
+        _ => return,
+    };
+
+    notify(store::Event::Transfers);
+
+    for transfer in state.transfers.transfers.values_mut() {
+        if transfer.id == id {
+            transfer.order = first_order;
+        } else if transfer.order >= first_order && transfer.order < order {
+            transfer.order += 1;
+        }
+    }
+}
+
+pub fn set_bandwidth_limits(
+    state: &mut store::State,
+    notify: &store::Notify,
+    limits: TransfersBandwidthLimits,
+    schedule: Option<TransfersBandwidthSchedule>,
+) {
+    state.config.transfers.bandwidth_limits = limits;
+    state.config.transfers.bandwidth_schedule = schedule;
+
+    notify(store::Event::Transfers);
+}
+
+pub fn set_verify_uploads(state: &mut store::State, notify: &store::Notify, verify_uploads: bool) {
+    state.config.transfers.verify_uploads = verify_uploads;
+
+    notify(store::Event::Transfers);
+}
+
 pub fn cleanup(state: &mut store::State, notify: &store::Notify) {
     if state.transfers.transferring_count == 0 && state.transfers.started.is_some() {
         notify(store::Event::Transfers);
@@ -718,8 +951,9 @@ mod tests {
         );
 
         let (notify, _, _) = store_test_helpers::mutation();
-        let name = upload_transfer_processed(&mut state, &notify, 1, SizeInfo::Exact(11), &cipher)
-            .unwrap();
+        let name =
+            upload_transfer_processed(&mut state, &notify, 1, SizeInfo::Exact(11), &cipher, false)
+                .unwrap();
         assert_eq!(
             name,
             cipher.encrypt_filename(&DecryptedName("file (1).txt".into()))
@@ -865,8 +1099,9 @@ mod tests {
         );
 
         let (notify, _, _) = store_test_helpers::mutation();
-        let name = upload_transfer_processed(&mut state, &notify, 1, SizeInfo::Exact(11), &cipher)
-            .unwrap();
+        let name =
+            upload_transfer_processed(&mut state, &notify, 1, SizeInfo::Exact(11), &cipher, false)
+                .unwrap();
         assert_eq!(
             name,
             cipher.encrypt_filename(&DecryptedName("file (1).txt".into()))
@@ -973,8 +1208,9 @@ mod tests {
         start_transfer(&mut state, &notify, 2, TimeMillis(2));
 
         let (notify, _, _) = store_test_helpers::mutation();
-        let name = upload_transfer_processed(&mut state, &notify, 2, SizeInfo::Exact(10), &cipher)
-            .unwrap();
+        let name =
+            upload_transfer_processed(&mut state, &notify, 2, SizeInfo::Exact(10), &cipher, false)
+                .unwrap();
         assert_eq!(
             name,
             cipher.encrypt_filename(&DecryptedName("file.txt".into()))
@@ -1012,8 +1248,9 @@ mod tests {
         );
 
         let (notify, _, _) = store_test_helpers::mutation();
-        let name = upload_transfer_processed(&mut state, &notify, 1, SizeInfo::Exact(11), &cipher)
-            .unwrap();
+        let name =
+            upload_transfer_processed(&mut state, &notify, 1, SizeInfo::Exact(11), &cipher, false)
+                .unwrap();
         assert_eq!(
             name,
             cipher.encrypt_filename(&DecryptedName("file (1).txt".into()))
@@ -0,0 +1,189 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{repos::selectors as repos_selectors, runtime, store, types::RepoId};
+
+use super::{
+    state::{PersistedTransfers, PersistedUploadTransfer, TransferUploadSource},
+    uploadable::BoxUploadable,
+    TransfersService,
+};
+
+pub const TRANSFERS_STORAGE_KEY: &str = "vaultTransfers";
+
+/// Platform storage for persisted transfers. Persisted transfers contain file
+/// names and errors so they have to be stored encrypted (e.g. in the secure
+/// storage under TRANSFERS_STORAGE_KEY).
+pub trait TransfersPersistence {
+    fn load(&self) -> Result<Option<PersistedTransfers>, String>;
+    fn save(&self, transfers: &PersistedTransfers) -> Result<(), String>;
+    /// Returns None if the source no longer exists.
+    fn uploadable(&self, source: &TransferUploadSource) -> Option<BoxUploadable>;
+}
+
+pub type BoxTransfersPersistence = Box<dyn TransfersPersistence + Send + Sync>;
+
+/// TransfersPersistenceService saves persistent uploads whenever transfers
+/// change and restores uploads from the previous session once their repo is
+/// unlocked. Persistence is enabled for as long as the service is alive.
+pub struct TransfersPersistenceService {
+    transfers_service: Arc<TransfersService>,
+    store: Arc<store::Store>,
+    runtime: Arc<runtime::BoxRuntime>,
+    persistence: BoxTransfersPersistence,
+
+    transfers_subscription_id: u32,
+    repos_subscription_id: u32,
+    /// uploads from the previous session that have not been restored yet
+    pending: Mutex<Vec<PersistedUploadTransfer>>,
+    saved: Mutex<Option<PersistedTransfers>>,
+}
+
+impl TransfersPersistenceService {
+    pub fn new(
+        transfers_service: Arc<TransfersService>,
+        store: Arc<store::Store>,
+        runtime: Arc<runtime::BoxRuntime>,
+        persistence: BoxTransfersPersistence,
+    ) -> Arc<Self> {
+        let pending = match persistence.load() {
+            Ok(Some(persisted)) => persisted.uploads,
+            Ok(None) => vec![],
+            Err(err) => {
+                log::warn!("Failed to load persisted transfers: {}", err);
+
+                vec![]
+            }
+        };
+
+        transfers_service.set_persist_uploads(true);
+
+        let transfers_subscription_id = store.get_next_id();
+        let repos_subscription_id = store.get_next_id();
+
+        let transfers_persistence_service = Arc::new(Self {
+            transfers_service,
+            store: store.clone(),
+            runtime,
+            persistence,
+
+            transfers_subscription_id,
+            repos_subscription_id,
+            pending: Mutex::new(pending),
+            saved: Mutex::new(None),
+        });
+
+        let transfers_subscription_service = Arc::downgrade(&transfers_persistence_service);
+
+        store.on(
+            transfers_subscription_id,
+            &[store::Event::Transfers],
+            Box::new(move |_, add_side_effect| {
+                if let Some(transfers_persistence_service) =
+                    transfers_subscription_service.upgrade()
+                {
+                    add_side_effect(Box::new(move || {
+                        transfers_persistence_service.save();
+                    }));
+                }
+            }),
+        );
+
+        let repos_subscription_service = Arc::downgrade(&transfers_persistence_service);
+
+        store.on(
+            repos_subscription_id,
+            &[store::Event::Repos],
+            Box::new(move |mutation_state, add_side_effect| {
+                if !mutation_state.repos.unlocked_repos.is_empty() {
+                    if let Some(transfers_persistence_service) =
+                        repos_subscription_service.upgrade()
+                    {
+                        let repo_ids = mutation_state
+                            .repos
+                            .unlocked_repos
+                            .iter()
+                            .map(|(repo_id, _)| repo_id.clone())
+                            .collect();
+
+                        add_side_effect(Box::new(move || {
+                            transfers_persistence_service.restore(repo_ids);
+                        }));
+                    }
+                }
+            }),
+        );
+
+        let unlocked_repo_ids = store.with_state(|state| {
+            repos_selectors::select_repos(state)
+                .into_iter()
+                .filter(|repo| !repo.state.is_locked())
+                .map(|repo| repo.id.clone())
+                .collect()
+        });
+
+        transfers_persistence_service.restore(unlocked_repo_ids);
+
+        transfers_persistence_service
+    }
+
+    fn restore(&self, repo_ids: Vec<RepoId>) {
+        let restored: Vec<PersistedUploadTransfer> = {
+            let mut pending = self.pending.lock().unwrap();
+
+            let (restored, remaining) = pending
+                .drain(..)
+                .partition(|persisted| repo_ids.contains(&persisted.repo_id));
+
+            *pending = remaining;
+
+            restored
+        };
+
+        for persisted in restored {
+            let uploadable = match self.persistence.uploadable(&persisted.source) {
+                Some(uploadable) => uploadable,
+                None => continue,
+            };
+
+            let (_, create_future) = self
+                .transfers_service
+                .clone()
+                .restore_upload(persisted, uploadable);
+
+            self.runtime.spawn(Box::pin(async move {
+                if let Ok(future) = create_future.await {
+                    let _ = future.await;
+                }
+            }));
+        }
+    }
+
+    fn save(&self) {
+        let mut uploads = self.transfers_service.persisted_uploads();
+
+        // keep uploads for repos that have not been unlocked in this session
+        uploads.extend(self.pending.lock().unwrap().iter().cloned());
+
+        let persisted = PersistedTransfers { uploads };
+
+        let mut saved = self.saved.lock().unwrap();
+
+        if saved.as_ref() == Some(&persisted) {
+            return;
+        }
+
+        match self.persistence.save(&persisted) {
+            Ok(()) => *saved = Some(persisted),
+            Err(err) => log::warn!("Failed to save persisted transfers: {}", err),
+        }
+    }
+}
+
+impl Drop for TransfersPersistenceService {
+    fn drop(&mut self) {
+        self.transfers_service.set_persist_uploads(false);
+
+        self.store.remove_listener(self.transfers_subscription_id);
+        self.store.remove_listener(self.repos_subscription_id);
+    }
+}
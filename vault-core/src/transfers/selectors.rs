@@ -8,12 +8,26 @@ use crate::{
     types::{DecryptedName, TimeMillis},
 };
 
-use super::state::{Transfer, TransferState, TransferType, TransfersState, UploadTransfer};
+use super::state::{
+    Transfer, TransferState, TransferType, TransfersBandwidthLimits, TransfersBandwidthSchedule,
+    TransfersState, UploadTransfer,
+};
 
 pub fn can_retry(transfer: &Transfer) -> bool {
     matches!(transfer.state, TransferState::Failed { .. }) && transfer.is_retriable
 }
 
+pub fn can_pause(transfer: &Transfer) -> bool {
+    matches!(
+        transfer.state,
+        TransferState::Waiting | TransferState::Processing | TransferState::Transferring
+    ) && !matches!(transfer.typ, TransferType::DownloadReader)
+}
+
+pub fn can_resume(transfer: &Transfer) -> bool {
+    matches!(transfer.state, TransferState::Paused)
+}
+
 pub fn can_open(transfer: &Transfer) -> bool {
     matches!(transfer.state, TransferState::Done { .. }) && transfer.is_openable
 }
@@ -71,6 +85,48 @@ pub fn select_transfers<'a>(state: &'a store::State) -> Vec<&'a Transfer> {
     files
 }
 
+pub fn is_in_bandwidth_schedule(schedule: &TransfersBandwidthSchedule, now: TimeMillis) -> bool {
+    let offset_millis = now.0 + schedule.fixed_utc_offset_minutes as i64 * 60 * 1000;
+    let minute = offset_millis.div_euclid(60 * 1000).rem_euclid(24 * 60) as u32;
+
+    if schedule.start_minute <= schedule.end_minute {
+        minute >= schedule.start_minute && minute < schedule.end_minute
+    } else {
+        minute >= schedule.start_minute || minute < schedule.end_minute
+    }
+}
+
+pub fn select_bandwidth_limits(state: &store::State, now: TimeMillis) -> &TransfersBandwidthLimits {
+    let config = select_config(state);
+
+    match &config.bandwidth_schedule {
+        Some(schedule) if is_in_bandwidth_schedule(schedule, now) => &schedule.limits,
+        _ => &config.bandwidth_limits,
+    }
+}
+
+/// New transfers are added after all the existing ones, even if some of them
+/// were moved to the front.
+pub fn select_next_order(state: &store::State) -> usize {
+    state
+        .transfers
+        .transfers
+        .values()
+        .map(|transfer| transfer.order + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The lowest order, used to move a transfer in front of all the others.
+pub fn select_first_order(state: &store::State) -> Option<usize> {
+    state
+        .transfers
+        .transfers
+        .values()
+        .map(|transfer| transfer.order)
+        .min()
+}
+
 pub fn select_should_notify_progress(state: &store::State, now: TimeMillis) -> bool {
     state.transfers.last_progress_update.is_none()
         || matches!(
@@ -95,6 +151,14 @@ pub fn select_can_retry_all(state: &store::State) -> bool {
     state.transfers.retriable_count > 0
 }
 
+pub fn select_can_pause_all(state: &store::State) -> bool {
+    state.transfers.transfers.values().any(can_pause)
+}
+
+pub fn select_can_resume_all(state: &store::State) -> bool {
+    state.transfers.paused_count > 0
+}
+
 pub fn select_can_abort_all(state: &store::State) -> bool {
     state.transfers.total_count > 0
 }
@@ -107,11 +171,25 @@ pub fn select_remaining_count(state: &store::State) -> usize {
     state.transfers.total_count
         - state.transfers.done_count
         - state.transfers.failed_count
+        - state.transfers.paused_count
         - state.transfers.transferring_count
 }
 
+pub fn select_paused_bytes(state: &store::State) -> i64 {
+    state
+        .transfers
+        .transfers
+        .values()
+        .filter(|transfer| matches!(transfer.state, TransferState::Paused))
+        .filter_map(|transfer| transfer.size.exact_or_estimate())
+        .sum()
+}
+
 pub fn select_remaining_bytes(state: &store::State) -> i64 {
-    state.transfers.total_bytes - state.transfers.done_bytes - state.transfers.failed_bytes
+    state.transfers.total_bytes
+        - state.transfers.done_bytes
+        - state.transfers.failed_bytes
+        - select_paused_bytes(state)
 }
 
 pub fn select_bytes_done(state: &store::State) -> i64 {
@@ -207,3 +285,70 @@ pub fn select_unused_name(
 
     repo_files_selectors::get_unused_name(used_names, &upload_transfer.original_name)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        transfers::state::{TransfersBandwidthLimits, TransfersBandwidthSchedule},
+        types::TimeMillis,
+    };
+
+    use super::is_in_bandwidth_schedule;
+
+    fn at(hours: i64, minutes: i64) -> TimeMillis {
+        // 2024-01-01T00:00:00Z
+        TimeMillis(1704067200000 + (hours * 60 + minutes) * 60 * 1000)
+    }
+
+    fn schedule(
+        start_minute: u32,
+        end_minute: u32,
+        fixed_utc_offset_minutes: i32,
+    ) -> TransfersBandwidthSchedule {
+        TransfersBandwidthSchedule {
+            start_minute,
+            end_minute,
+            fixed_utc_offset_minutes,
+            limits: TransfersBandwidthLimits::default(),
+        }
+    }
+
+    #[test]
+    fn test_is_in_bandwidth_schedule() {
+        let day = schedule(9 * 60, 17 * 60, 0);
+
+        assert!(!is_in_bandwidth_schedule(&day, at(8, 59)));
+        assert!(is_in_bandwidth_schedule(&day, at(9, 0)));
+        assert!(is_in_bandwidth_schedule(&day, at(16, 59)));
+        assert!(!is_in_bandwidth_schedule(&day, at(17, 0)));
+    }
+
+    #[test]
+    fn test_is_in_bandwidth_schedule_over_midnight() {
+        let night = schedule(22 * 60, 6 * 60, 0);
+
+        assert!(!is_in_bandwidth_schedule(&night, at(21, 59)));
+        assert!(is_in_bandwidth_schedule(&night, at(22, 0)));
+        assert!(is_in_bandwidth_schedule(&night, at(0, 0)));
+        assert!(is_in_bandwidth_schedule(&night, at(5, 59)));
+        assert!(!is_in_bandwidth_schedule(&night, at(6, 0)));
+    }
+
+    #[test]
+    fn test_is_in_bandwidth_schedule_fixed_utc_offset() {
+        // 22:00 to 06:00 in UTC+2 is 20:00 to 04:00 in UTC
+        let night = schedule(22 * 60, 6 * 60, 120);
+
+        assert!(!is_in_bandwidth_schedule(&night, at(19, 59)));
+        assert!(is_in_bandwidth_schedule(&night, at(20, 0)));
+        assert!(is_in_bandwidth_schedule(&night, at(3, 59)));
+        assert!(!is_in_bandwidth_schedule(&night, at(4, 0)));
+
+        // 22:00 to 06:00 in UTC-5 is 03:00 to 11:00 in UTC
+        let night = schedule(22 * 60, 6 * 60, -300);
+
+        assert!(!is_in_bandwidth_schedule(&night, at(2, 59)));
+        assert!(is_in_bandwidth_schedule(&night, at(3, 0)));
+        assert!(!is_in_bandwidth_schedule(&night, at(11, 0)));
+    }
+}
@@ -1,6 +1,9 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
 };
 
 use futures::{
@@ -14,35 +17,52 @@ use crate::{
     common::state::SizeInfo,
     remote::ApiErrorCode,
     repo_files::{
-        errors::LoadFilesError, selectors as repo_files_selectors,
-        state::RepoFilesUploadConflictResolution, RepoFilesService,
+        errors::{LoadFilesError, UploadFileReaderError},
+        selectors as repo_files_selectors,
+        state::{
+            RepoFilesUploadConflictResolution, RepoFilesUploadOptions, RepoFilesUploadResult,
+            RepoFilesUploadResumable, RepoFilesUploadSession,
+        },
+        RepoFilesService,
     },
     repo_files_read::state::{RepoFileReader, RepoFileReaderProvider},
     repos::ReposService,
     runtime, store,
     types::{EncryptedPath, RepoId},
+    user_error::UserError,
     utils::{
         abort_reader::AbortReader, on_end_reader::OnEndReader, progress_reader::ProgressReader,
     },
 };
 
 use super::{
+    bandwidth_limiter::BandwidthLimiter,
     downloadable::{BoxDownloadable, DownloadableStatus},
     errors::{DownloadableError, TransferError},
     mutations, selectors,
     state::{
         CreateDownloadResult, CreateDownloadResultFuture, CreateUploadResult,
-        CreateUploadResultFuture, DownloadReaderResult, DownloadResult, RetryInitiator,
-        TransferDisplayName, TransferType, TransferUploadRelativeName, UploadResult,
-        UploadTransfer,
+        CreateUploadResultFuture, DownloadReaderResult, DownloadResult, PersistedUploadTransfer,
+        RetryInitiator, TransferDirection, TransferDisplayName, TransferState, TransferType,
+        TransferUploadRelativeName, TransfersBandwidthLimits, TransfersBandwidthSchedule,
+        UploadResult, UploadTransfer,
     },
     uploadable::BoxUploadable,
 };
 
+/// Uploads of at least this size are resumable. Smaller uploads are sent in a
+/// single request.
+pub const RESUMABLE_UPLOAD_MIN_SIZE: i64 = 4 * 1024 * 1024;
+
 #[derive(Default)]
 struct TransfersServiceTransferStateUpload {
     uploadable: Option<Arc<BoxUploadable>>,
     result_sender: Option<Sender<UploadResult>>,
+    upload_session: Arc<Mutex<Option<RepoFilesUploadSession>>>,
+    /// set if the hash of the uploaded file did not match. The retried upload
+    /// overwrites the file if it was not changed in the meantime
+    hash_mismatch_overwrite: Option<RepoFilesUploadConflictResolution>,
+    persisted: Option<PersistedUploadTransfer>,
 }
 
 #[derive(Default)]
@@ -75,6 +95,8 @@ pub struct TransfersService {
     runtime: Arc<runtime::BoxRuntime>,
 
     state: Arc<RwLock<TransfersServiceState>>,
+    persist_uploads: AtomicBool,
+    bandwidth_limiter: Arc<BandwidthLimiter>,
 }
 
 impl TransfersService {
@@ -84,6 +106,8 @@ impl TransfersService {
         store: Arc<store::Store>,
         runtime: Arc<runtime::BoxRuntime>,
     ) -> Self {
+        let bandwidth_limiter = Arc::new(BandwidthLimiter::new(store.clone(), runtime.clone()));
+
         Self {
             repos_service,
             repo_files_service,
@@ -91,9 +115,36 @@ impl TransfersService {
             runtime,
 
             state: Default::default(),
+            persist_uploads: AtomicBool::new(false),
+            bandwidth_limiter,
         }
     }
 
+    /// Uploads created after this is enabled are marked as persistent if
+    /// their uploadable has a source.
+    pub fn set_persist_uploads(&self, persist_uploads: bool) {
+        self.persist_uploads
+            .store(persist_uploads, Ordering::SeqCst);
+    }
+
+    /// Limits apply to transfers that are already running. The schedule
+    /// limits replace the default limits during the scheduled period.
+    pub fn set_bandwidth_limits(
+        &self,
+        limits: TransfersBandwidthLimits,
+        schedule: Option<TransfersBandwidthSchedule>,
+    ) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::set_bandwidth_limits(state, notify, limits, schedule);
+        });
+    }
+
+    pub fn set_verify_uploads(&self, verify_uploads: bool) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::set_verify_uploads(state, notify, verify_uploads);
+        });
+    }
+
     fn get_next_id(&self) -> u32 {
         self.store
             .mutate(|state, _, _, _| mutations::get_next_id(state))
@@ -105,6 +156,34 @@ impl TransfersService {
         parent_path: EncryptedPath,
         name: TransferUploadRelativeName,
         uploadable: BoxUploadable,
+    ) -> (u32, CreateUploadResultFuture) {
+        self.upload_with_restored(repo_id, parent_path, name, uploadable, None)
+    }
+
+    /// Recreates an upload transfer that was persisted in a previous session.
+    /// The transfer keeps its attempts and if it had failed it is restored as
+    /// failed so that the user can retry it.
+    pub fn restore_upload(
+        self: Arc<Self>,
+        persisted: PersistedUploadTransfer,
+        uploadable: BoxUploadable,
+    ) -> (u32, CreateUploadResultFuture) {
+        self.upload_with_restored(
+            persisted.repo_id.clone(),
+            persisted.parent_path.clone(),
+            TransferUploadRelativeName(persisted.name.clone()),
+            uploadable,
+            Some(persisted),
+        )
+    }
+
+    fn upload_with_restored(
+        self: Arc<Self>,
+        repo_id: RepoId,
+        parent_path: EncryptedPath,
+        name: TransferUploadRelativeName,
+        uploadable: BoxUploadable,
+        restored: Option<PersistedUploadTransfer>,
     ) -> (u32, CreateUploadResultFuture) {
         let id = self.get_next_id();
 
@@ -118,7 +197,7 @@ impl TransfersService {
 
         let cleanup_state = self.state.clone();
         let future = self
-            .create_upload(repo_id, parent_path, name, uploadable, id)
+            .create_upload(repo_id, parent_path, name, uploadable, restored, id)
             .map_err(move |err| {
                 cleanup_state.write().unwrap().transfers.remove(&id);
                 err
@@ -134,12 +213,27 @@ impl TransfersService {
         parent_path: EncryptedPath,
         name: TransferUploadRelativeName,
         uploadable: BoxUploadable,
+        restored: Option<PersistedUploadTransfer>,
         id: u32,
     ) -> CreateUploadResult {
         let size = uploadable.size().await?;
 
         let is_retriable = uploadable.is_retriable().await?;
 
+        let persisted = if self.persist_uploads.load(Ordering::SeqCst) {
+            uploadable.source().map(|source| PersistedUploadTransfer {
+                repo_id: repo_id.clone(),
+                parent_path: parent_path.clone(),
+                name: name.0.clone(),
+                source,
+                attempts: 0,
+                error: None,
+                paused: false,
+            })
+        } else {
+            None
+        };
+
         let result_receiver = self.store.mutate(|state, notify, _, _| {
             let result_receiver = match self.state.write().unwrap().transfers.get_mut(&id) {
                 Some(state) => {
@@ -149,6 +243,7 @@ impl TransfersService {
                         TransfersServiceTransferStateType::Upload(upload) => {
                             upload.uploadable = Some(Arc::new(uploadable));
                             upload.result_sender = Some(result_sender);
+                            upload.persisted = persisted.clone();
                         }
                         _ => {}
                     }
@@ -158,7 +253,7 @@ impl TransfersService {
                 None => return Err(TransferError::Aborted),
             };
 
-            let is_persistent = false;
+            let is_persistent = persisted.is_some();
             let is_openable = false;
 
             mutations::create_upload_transfer(
@@ -174,6 +269,17 @@ impl TransfersService {
                 is_openable,
             )?;
 
+            if let Some(restored) = restored {
+                mutations::upload_transfer_restored(
+                    state,
+                    notify,
+                    id,
+                    restored.attempts,
+                    restored.error.map(TransferError::RestoredError),
+                    restored.paused,
+                );
+            }
+
             Ok(result_receiver)
         })?;
 
@@ -182,6 +288,46 @@ impl TransfersService {
         Ok(async { result_receiver.await.unwrap() }.boxed())
     }
 
+    /// Returns persistent uploads that are not done yet, in transfer order.
+    pub fn persisted_uploads(&self) -> Vec<PersistedUploadTransfer> {
+        self.store.with_state(|store_state| {
+            // lock order must match store.mutate
+            let state = self.state.read().unwrap();
+
+            let mut transfers: Vec<_> = store_state
+                .transfers
+                .transfers
+                .values()
+                .filter(|transfer| transfer.is_persistent)
+                .filter(|transfer| !matches!(transfer.state, TransferState::Done))
+                .collect();
+
+            transfers.sort_by_key(|transfer| transfer.order);
+
+            transfers
+                .into_iter()
+                .filter_map(|transfer| {
+                    let persisted = match &state.transfers.get(&transfer.id)?.typ {
+                        TransfersServiceTransferStateType::Upload(upload) => {
+                            upload.persisted.as_ref()?
+                        }
+                        _ => return None,
+                    };
+
+                    Some(PersistedUploadTransfer {
+                        attempts: transfer.attempts,
+                        error: match &transfer.state {
+                            TransferState::Failed { error } => Some(error.user_error()),
+                            _ => None,
+                        },
+                        paused: matches!(transfer.state, TransferState::Paused),
+                        ..persisted.clone()
+                    })
+                })
+                .collect()
+        })
+    }
+
     pub fn download(
         self: Arc<Self>,
         reader_provider: RepoFileReaderProvider,
@@ -311,6 +457,11 @@ impl TransfersService {
         });
 
         let reader = reader.wrap_reader(|reader| {
+            let reader = self
+                .bandwidth_limiter
+                .clone()
+                .reader(reader, TransferDirection::Download);
+
             let abort_reader = AbortReader::new(reader, abort_handle);
 
             let progress_reader =
@@ -401,6 +552,78 @@ impl TransfersService {
         self.process_next();
     }
 
+    /// Pauses a waiting or running transfer. The transfer keeps its place in
+    /// the queue and its result future keeps waiting until it is resumed.
+    pub fn pause(self: Arc<Self>, id: u32) {
+        let abort_handle = self.store.mutate(|state, notify, _, _| {
+            if !mutations::pause(state, notify, id) {
+                return None;
+            }
+
+            self.state
+                .write()
+                .unwrap()
+                .transfers
+                .get_mut(&id)
+                .and_then(|state| state.abort_handle.take())
+        });
+
+        if let Some(abort_handle) = abort_handle {
+            abort_handle.abort();
+        }
+
+        self.process_next();
+    }
+
+    pub fn pause_all(self: Arc<Self>) {
+        let abort_handles: Vec<_> = self.store.mutate(|state, notify, _, _| {
+            let ids = mutations::pause_all(state, notify);
+
+            let mut state = self.state.write().unwrap();
+
+            ids.iter()
+                .filter_map(|id| {
+                    state
+                        .transfers
+                        .get_mut(id)
+                        .and_then(|state| state.abort_handle.take())
+                })
+                .collect()
+        });
+
+        for abort_handle in abort_handles {
+            abort_handle.abort();
+        }
+
+        self.process_next();
+    }
+
+    pub fn resume(self: Arc<Self>, id: u32) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::resume(state, notify, id);
+        });
+
+        self.process_next();
+    }
+
+    pub fn resume_all(self: Arc<Self>) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::resume_all(state, notify);
+        });
+
+        self.process_next();
+    }
+
+    /// Moves the transfer to the front of the queue so that it is started
+    /// before the other waiting transfers. Running transfers are not stopped.
+    pub fn move_to_front(self: Arc<Self>, id: u32) {
+        self.store.mutate(|state, notify, _, _| {
+            mutations::move_to_front(state, notify, id);
+        });
+
+        self.process_next();
+    }
+
     pub async fn open(self: Arc<Self>, id: u32) -> Result<(), TransferError> {
         let downloadable = {
             let state = self.state.read().unwrap();
@@ -519,14 +742,22 @@ impl TransfersService {
             }
         }
 
-        let uploadable = self
+        let (uploadable, upload_session, hash_mismatch_overwrite) = self
             .state
             .read()
             .unwrap()
             .transfers
             .get(&id)
             .and_then(|state| match &state.typ {
-                TransfersServiceTransferStateType::Upload(upload) => upload.uploadable.clone(),
+                TransfersServiceTransferStateType::Upload(upload) => {
+                    upload.uploadable.clone().map(|uploadable| {
+                        (
+                            uploadable,
+                            upload.upload_session.clone(),
+                            upload.hash_mismatch_overwrite.clone(),
+                        )
+                    })
+                }
                 _ => None,
             })
             .ok_or(TransferError::TransferNotFound)?;
@@ -534,27 +765,84 @@ impl TransfersService {
         let (reader, size) = uploadable.reader().await?;
 
         let name = self.store.mutate(|state, notify, _, _| {
-            mutations::upload_transfer_processed(state, notify, id, size, &cipher)
+            mutations::upload_transfer_processed(
+                state,
+                notify,
+                id,
+                size,
+                &cipher,
+                hash_mismatch_overwrite.is_some(),
+            )
         })?;
 
-        let size = match size {
-            SizeInfo::Exact(size) => Some(size),
-            _ => None,
+        let conflict_resolution =
+            hash_mismatch_overwrite.unwrap_or(RepoFilesUploadConflictResolution::Error);
+
+        let res = match size {
+            SizeInfo::Exact(size) if size >= RESUMABLE_UPLOAD_MIN_SIZE => {
+                let on_resume_self = self.clone();
+                let bandwidth_limiter = self.bandwidth_limiter.clone();
+
+                self.repo_files_service
+                    .clone()
+                    .upload_file_reader_resumable(
+                        &upload_transfer.repo_id,
+                        &upload_transfer.parent_path,
+                        name,
+                        reader,
+                        conflict_resolution,
+                        RepoFilesUploadResumable {
+                            size,
+                            session: upload_session,
+                            on_resume: Some(Box::new(move |transferred_bytes| {
+                                on_resume_self.store.mutate(|state, notify, _, _| {
+                                    mutations::transfer_resumed(
+                                        state,
+                                        notify,
+                                        id,
+                                        transferred_bytes,
+                                    );
+                                });
+                            })),
+                            on_progress: Some(self.clone().get_transfer_on_progress(id)),
+                            wrap_reader: Some(Box::new(move |reader| {
+                                bandwidth_limiter.reader(reader, TransferDirection::Upload)
+                            })),
+                        },
+                    )
+                    .await
+            }
+            size => {
+                let size = match size {
+                    SizeInfo::Exact(size) => Some(size),
+                    _ => None,
+                };
+
+                let bandwidth_limiter = self.bandwidth_limiter.clone();
+
+                self.repo_files_service
+                    .clone()
+                    .upload_file_reader_with_options(
+                        &upload_transfer.repo_id,
+                        &upload_transfer.parent_path,
+                        name,
+                        reader,
+                        conflict_resolution,
+                        RepoFilesUploadOptions {
+                            size,
+                            on_progress: Some(self.clone().get_transfer_on_progress(id)),
+                            wrap_reader: Some(Box::new(move |reader| {
+                                bandwidth_limiter.reader(reader, TransferDirection::Upload)
+                            })),
+                        },
+                    )
+                    .await
+            }
         };
 
-        let res = self
-            .repo_files_service
-            .clone()
-            .upload_file_reader(
-                &upload_transfer.repo_id,
-                &upload_transfer.parent_path,
-                name,
-                reader,
-                size,
-                RepoFilesUploadConflictResolution::Error,
-                Some(self.clone().get_transfer_on_progress(id)),
-            )
-            .await?;
+        self.set_hash_mismatch_overwrite(id, &res);
+
+        let res = res?;
 
         let sender = self
             .state
@@ -604,8 +892,12 @@ impl TransfersService {
 
         let reader = reader_provider.reader().await?;
 
-        let progress_reader =
-            ProgressReader::new(reader.reader, self.clone().get_transfer_on_progress(id));
+        let progress_reader = ProgressReader::new(
+            self.bandwidth_limiter
+                .clone()
+                .reader(reader.reader, TransferDirection::Download),
+            self.clone().get_transfer_on_progress(id),
+        );
 
         let name = reader.name.0.clone();
         let unique_name = reader.unique_name.clone();
@@ -680,6 +972,40 @@ impl TransfersService {
         }))
     }
 
+    /// A file with a mismatched hash is overwritten by the retried upload if
+    /// its size and modified time did not change. If the file was changed the
+    /// next retry uploads a new file.
+    fn set_hash_mismatch_overwrite(
+        &self,
+        id: u32,
+        res: &Result<RepoFilesUploadResult, UploadFileReaderError>,
+    ) {
+        let hash_mismatch_overwrite = match res {
+            Err(UploadFileReaderError::HashMismatch {
+                remote_size,
+                remote_modified,
+            }) => Some(RepoFilesUploadConflictResolution::Overwrite {
+                if_remote_size: *remote_size,
+                if_remote_modified: *remote_modified,
+                if_remote_hash: None,
+            }),
+            // the file with the mismatched hash was changed
+            Err(UploadFileReaderError::RemoteError(err))
+                if err.is_api_error_code(ApiErrorCode::Conflict) =>
+            {
+                None
+            }
+            Err(_) => return,
+            Ok(_) => None,
+        };
+
+        if let Some(state) = self.state.write().unwrap().transfers.get_mut(&id) {
+            if let TransfersServiceTransferStateType::Upload(upload) = &mut state.typ {
+                upload.hash_mismatch_overwrite = hash_mismatch_overwrite;
+            }
+        }
+    }
+
     fn get_transfer_on_progress(self: Arc<Self>, id: u32) -> Box<dyn Fn(usize) + Send + Sync> {
         Box::new(move |n| {
             self.store.mutate(|state, notify, _, _| {
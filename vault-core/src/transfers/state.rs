@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     common::state::SizeInfo,
@@ -31,6 +32,7 @@ pub enum TransferState {
     Waiting,
     Processing,
     Transferring,
+    Paused,
     Failed { error: TransferError },
     Done,
 }
@@ -123,6 +125,34 @@ impl Transfer {
     }
 }
 
+/// Where an upload reads its data from. Only uploads with a source that can be
+/// read again after an app restart can be persisted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TransferUploadSource {
+    File { path: String },
+}
+
+/// PersistedUploadTransfer is an upload transfer as it is saved to disk.
+/// parent_path and name are the values the upload was created with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedUploadTransfer {
+    pub repo_id: RepoId,
+    pub parent_path: EncryptedPath,
+    pub name: String,
+    pub source: TransferUploadSource,
+    pub attempts: usize,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct PersistedTransfers {
+    pub uploads: Vec<PersistedUploadTransfer>,
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct TransfersState {
     pub transfers: HashMap<u32, Transfer>,
@@ -135,6 +165,7 @@ pub struct TransfersState {
     pub done_count: usize,
     pub failed_count: usize,
     pub retriable_count: usize,
+    pub paused_count: usize,
     pub total_count: usize,
     pub done_bytes: i64,
     pub failed_bytes: i64,
@@ -150,6 +181,36 @@ impl TransfersState {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+/// Bandwidth limits in bytes per second. None (or 0) means unlimited. The
+/// total limit is shared by uploads and downloads.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransfersBandwidthLimits {
+    pub total: Option<u64>,
+    pub upload: Option<u64>,
+    pub download: Option<u64>,
+}
+
+/// Limits used instead of the default limits between start_minute and
+/// end_minute (minutes since midnight in UTC shifted by
+/// fixed_utc_offset_minutes). If end_minute is before start_minute the period
+/// wraps over midnight, e.g. 22:00 to 06:00.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransfersBandwidthSchedule {
+    pub start_minute: u32,
+    pub end_minute: u32,
+    /// Fixed offset from UTC, e.g. 60 for UTC+1. It does not follow daylight
+    /// saving time, clients have to set the schedule again when their local
+    /// offset changes.
+    pub fixed_utc_offset_minutes: i32,
+    pub limits: TransfersBandwidthLimits,
+}
+
 #[derive(Debug, Clone)]
 pub enum RetryInitiator {
     User,
@@ -2,13 +2,18 @@ use async_trait::async_trait;
 
 use crate::common::state::{BoxAsyncRead, SizeInfo};
 
-use super::errors::UploadableError;
+use super::{errors::UploadableError, state::TransferUploadSource};
 
 #[async_trait]
 pub trait Uploadable {
     async fn size(&self) -> Result<SizeInfo, UploadableError>;
     async fn is_retriable(&self) -> Result<bool, UploadableError>;
     async fn reader(&self) -> Result<(BoxAsyncRead, SizeInfo), UploadableError>;
+
+    /// Uploads without a source are never persisted.
+    fn source(&self) -> Option<TransferUploadSource> {
+        None
+    }
 }
 
 pub type BoxUploadable = Box<dyn Uploadable + Send + Sync>;
@@ -8,6 +8,7 @@ pub mod name_utils;
 pub mod on_end_reader;
 pub mod path_utils;
 pub mod progress_reader;
+pub mod rate_limited_reader;
 pub mod reader_stream;
 pub mod remote_path_utils;
 pub mod repo_encrypted_path_utils;
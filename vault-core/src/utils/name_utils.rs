@@ -58,11 +58,28 @@ pub fn validate_name(name: &str) -> Result<(), InvalidNameError> {
     Ok(())
 }
 
+/// Replaces everything that validate_name rejects with underscores.
+pub fn sanitize_name(name: &str) -> String {
+    if name.is_empty() || name == "." || name == ".." {
+        return "_".repeat(name.len().max(1));
+    }
+
+    name.chars()
+        .map(|c| {
+            if c == '/' || c == '\\' || c == '\x7f' || c < '\x20' {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::name_utils::{join_name_ext, name_to_ext, split_name_ext};
 
-    use super::{unused_name, validate_name};
+    use super::{sanitize_name, unused_name, validate_name};
 
     #[test]
     fn test_split_name_ext() {
@@ -131,6 +148,21 @@ mod tests {
         assert!(validate_name("\x20").is_ok());
     }
 
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(sanitize_name("file.txt"), "file.txt");
+        assert_eq!(sanitize_name(""), "_");
+        assert_eq!(sanitize_name("."), "_");
+        assert_eq!(sanitize_name(".."), "__");
+        assert_eq!(sanitize_name("..."), "...");
+        assert_eq!(sanitize_name("foo/bar\\baz"), "foo_bar_baz");
+        assert_eq!(sanitize_name("a\x7f\x00b"), "a__b");
+
+        for name in ["", ".", "..", "a/b", "\x1F"] {
+            assert!(validate_name(&sanitize_name(name)).is_ok());
+        }
+    }
+
     #[test]
     fn test_escape_name() {
         assert!(validate_name("file.txt").is_ok());
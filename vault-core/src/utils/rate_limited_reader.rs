@@ -0,0 +1,64 @@
+use futures::{
+    future::BoxFuture,
+    ready,
+    task::{Context, Poll},
+    AsyncRead,
+};
+use pin_project_lite::pin_project;
+use std::{io::Result, pin::Pin, sync::Mutex, time::Duration};
+
+pin_project! {
+    /// RateLimitedReader calls `consume` with the number of bytes read and
+    /// waits for the returned duration before the next read.
+    pub struct RateLimitedReader<R> {
+        #[pin]
+        inner: R,
+        consume: Box<dyn Fn(usize) -> Duration + Send + Sync>,
+        sleep: Box<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>,
+        // Mutex only makes the reader Sync, it is never locked
+        delay: Option<Mutex<BoxFuture<'static, ()>>>,
+    }
+}
+
+impl<R> RateLimitedReader<R> {
+    pub fn new(
+        inner: R,
+        consume: Box<dyn Fn(usize) -> Duration + Send + Sync>,
+        sleep: Box<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>,
+    ) -> Self {
+        Self {
+            inner,
+            consume,
+            sleep,
+            delay: None,
+        }
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for RateLimitedReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let mut this = self.project();
+
+        if let Some(delay) = this.delay.as_mut() {
+            ready!(delay.get_mut().unwrap().as_mut().poll(cx));
+
+            *this.delay = None;
+        }
+
+        let n = ready!(this.inner.as_mut().poll_read(cx, buf))?;
+
+        if n > 0 {
+            let duration = (this.consume)(n);
+
+            if !duration.is_zero() {
+                *this.delay = Some(Mutex::new((this.sleep)(duration)));
+            }
+        }
+
+        Poll::Ready(Ok(n))
+    }
+}
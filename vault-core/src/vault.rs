@@ -5,10 +5,11 @@ use futures::future::BoxFuture;
 use crate::{
     auth, config, dialogs, dir_pickers, eventstream, http, lifecycle, notifications, oauth2,
     rclone, relative_time, remote, remote_files, remote_files_browsers, remote_files_dir_pickers,
-    repo_config_backup, repo_create, repo_files, repo_files_browsers, repo_files_details,
-    repo_files_dir_pickers, repo_files_list, repo_files_move, repo_files_read, repo_files_tags,
-    repo_locker, repo_remove, repo_space_usage, repo_unlock, repos, runtime, secure_storage, sort,
-    space_usage, store,
+    repo_config_backup, repo_create, repo_files, repo_files_browsers, repo_files_cross_move,
+    repo_files_details, repo_files_dir_pickers, repo_files_list, repo_files_move, repo_files_read,
+    repo_files_tags, repo_fsck, repo_locker, repo_password_change, repo_remove, repo_search,
+    repo_space_usage, repo_trash, repo_unlock, repo_versions, repos, runtime, secure_storage, sort,
+    space_usage, storage_backend, store,
     transfers::{self, downloadable::BoxDownloadable},
     types::{DecryptedName, EncryptedPath, RepoFileId, RepoId, TimeMillis},
     user,
@@ -24,6 +25,7 @@ pub struct Vault {
     pub oauth2_service: Arc<oauth2::OAuth2Service>,
     pub auth_provider: Arc<Box<(dyn auth::AuthProvider + Send + Sync)>>,
     pub remote: Arc<remote::Remote>,
+    pub storage_backend: Arc<storage_backend::BoxStorageBackend>,
     pub user_service: Arc<user::UserService>,
     pub eventstream_service: Arc<eventstream::EventStreamService>,
     pub transfers_service: Arc<transfers::TransfersService>,
@@ -36,6 +38,7 @@ pub struct Vault {
     pub repo_create_service: Arc<repo_create::RepoCreateService>,
     pub repo_unlock_service: Arc<repo_unlock::RepoUnlockService>,
     pub repo_remove_service: Arc<repo_remove::RepoRemoveService>,
+    pub repo_password_change_service: Arc<repo_password_change::RepoPasswordChangeService>,
     pub repo_config_backup_service: Arc<repo_config_backup::RepoConfigBackupService>,
     pub repo_space_usage_service: Arc<repo_space_usage::RepoSpaceUsageService>,
     pub repo_files_list_service: Arc<repo_files_list::RepoFilesListService>,
@@ -46,6 +49,11 @@ pub struct Vault {
     pub repo_files_browsers_service: Arc<repo_files_browsers::RepoFilesBrowsersService>,
     pub repo_files_details_service: Arc<repo_files_details::RepoFilesDetailsService>,
     pub repo_files_move_service: Arc<repo_files_move::RepoFilesMoveService>,
+    pub repo_files_cross_move_service: Arc<repo_files_cross_move::RepoFilesCrossMoveService>,
+    pub repo_fsck_service: Arc<repo_fsck::RepoFsckService>,
+    pub repo_search_service: Arc<repo_search::RepoSearchService>,
+    pub repo_trash_service: Arc<repo_trash::RepoTrashService>,
+    pub repo_versions_service: Arc<repo_versions::RepoVersionsService>,
     pub space_usage_service: Arc<space_usage::SpaceUsageService>,
     pub lifecycle_service: Arc<lifecycle::LifecycleService>,
 }
@@ -58,10 +66,14 @@ impl Vault {
         eventstream_websocket_client: Box<dyn eventstream::WebSocketClient + Send + Sync>,
         secure_storage: Box<dyn secure_storage::SecureStorage + Send + Sync>,
         runtime: runtime::BoxRuntime,
+        storage_backend: Option<storage_backend::BoxStorageBackend>,
     ) -> Self {
+        // a custom storage backend replaces the Koofr account
+        let local = storage_backend.is_some();
         let state = store::State {
             config: config::state::ConfigState {
                 base_url: base_url.clone(),
+                local,
                 ..Default::default()
             },
             ..Default::default()
@@ -83,14 +95,21 @@ impl Vault {
             store.clone(),
             runtime.clone(),
         ));
-        let auth_provider: Arc<Box<(dyn auth::AuthProvider + Send + Sync + 'static)>> = Arc::new(
-            Box::new(oauth2::OAuth2AuthProvider::new(oauth2_service.clone())),
-        );
+        let auth_provider: Arc<Box<(dyn auth::AuthProvider + Send + Sync + 'static)>> =
+            Arc::new(match local {
+                true => Box::new(auth::local_auth_provider::LocalAuthProvider),
+                false => Box::new(oauth2::OAuth2AuthProvider::new(oauth2_service.clone())),
+            });
         let remote = Arc::new(remote::Remote::new(
             base_url.clone(),
             http_client.clone(),
             auth_provider.clone(),
         ));
+        // files and repos are stored in Koofr by default
+        let storage_backend: Arc<storage_backend::BoxStorageBackend> =
+            Arc::new(storage_backend.unwrap_or_else(|| {
+                Box::new(storage_backend::RemoteStorageBackend::new(remote.clone()))
+            }));
         let user_service = Arc::new(user::UserService::new(remote.clone(), store.clone()));
         let eventstream_service = eventstream::EventStreamService::new(
             base_url.clone(),
@@ -100,7 +119,7 @@ impl Vault {
             runtime.clone(),
         );
         let remote_files_service = Arc::new(remote_files::RemoteFilesService::new(
-            remote.clone(),
+            storage_backend.clone(),
             dialogs_service.clone(),
             store.clone(),
         ));
@@ -115,7 +134,7 @@ impl Vault {
                 store.clone(),
             ));
         let repos_service = Arc::new(repos::ReposService::new(
-            remote.clone(),
+            storage_backend.clone(),
             remote_files_service.clone(),
             secure_storage_service.clone(),
             store.clone(),
@@ -134,6 +153,12 @@ impl Vault {
             repos_service.clone(),
             store.clone(),
         ));
+        let repo_password_change_service =
+            Arc::new(repo_password_change::RepoPasswordChangeService::new(
+                repos_service.clone(),
+                remote_files_service.clone(),
+                store.clone(),
+            ));
         let repo_config_backup_service = Arc::new(
             repo_config_backup::RepoConfigBackupService::new(repos_service.clone(), store.clone()),
         );
@@ -165,6 +190,7 @@ impl Vault {
             repo_files_read_service.clone(),
             dialogs_service.clone(),
             store.clone(),
+            runtime.clone(),
         ));
         let repo_create_service = Arc::new(repo_create::RepoCreateService::new(
             repos_service.clone(),
@@ -188,6 +214,43 @@ impl Vault {
             repo_files_dir_pickers_service.clone(),
             store.clone(),
         ));
+        let repo_files_cross_move_service =
+            Arc::new(repo_files_cross_move::RepoFilesCrossMoveService::new(
+                repos_service.clone(),
+                remote_files_service.clone(),
+                repo_files_service.clone(),
+                repo_files_list_service.clone(),
+                repo_files_read_service.clone(),
+                transfers_service.clone(),
+                store.clone(),
+            ));
+        let repo_fsck_service = Arc::new(repo_fsck::RepoFsckService::new(
+            repos_service.clone(),
+            remote_files_service.clone(),
+            repo_files_service.clone(),
+            repo_files_list_service.clone(),
+            store.clone(),
+        ));
+        let repo_search_service = Arc::new(repo_search::RepoSearchService::new(
+            repos_service.clone(),
+            remote_files_service.clone(),
+            repo_files_service.clone(),
+            repo_files_list_service.clone(),
+            store.clone(),
+            runtime.clone(),
+        ));
+        let repo_trash_service = repo_trash::RepoTrashService::new(
+            repos_service.clone(),
+            remote_files_service.clone(),
+            repo_files_service.clone(),
+            repo_files_tags_service.clone(),
+            store.clone(),
+            runtime.clone(),
+        );
+        let repo_versions_service = Arc::new(repo_versions::RepoVersionsService::new(
+            repo_files_service.clone(),
+            store.clone(),
+        ));
         let repo_files_browsers_service =
             Arc::new(repo_files_browsers::RepoFilesBrowsersService::new(
                 repo_files_service.clone(),
@@ -201,6 +264,7 @@ impl Vault {
                 repos_service.clone(),
                 repo_files_service.clone(),
                 repo_files_read_service.clone(),
+                repo_versions_service.clone(),
                 dialogs_service.clone(),
                 transfers_service.clone(),
                 store.clone(),
@@ -232,6 +296,7 @@ impl Vault {
             oauth2_service,
             auth_provider,
             remote,
+            storage_backend,
             user_service,
             eventstream_service,
             transfers_service,
@@ -243,6 +308,7 @@ impl Vault {
             repo_create_service,
             repo_unlock_service,
             repo_remove_service,
+            repo_password_change_service,
             repo_config_backup_service,
             repo_space_usage_service,
             repo_files_list_service,
@@ -253,6 +319,11 @@ impl Vault {
             repo_files_browsers_service,
             repo_files_details_service,
             repo_files_move_service,
+            repo_files_cross_move_service,
+            repo_fsck_service,
+            repo_search_service,
+            repo_trash_service,
+            repo_versions_service,
             space_usage_service,
             lifecycle_service,
         }
@@ -486,6 +557,24 @@ impl Vault {
         self.repos_service.set_auto_lock(repo_id, auto_lock)
     }
 
+    pub fn repos_set_trash_settings(
+        &self,
+        repo_id: &RepoId,
+        trash_settings: repos::state::RepoTrashSettings,
+    ) -> Result<(), repos::errors::SetTrashSettingsError> {
+        self.repos_service
+            .set_trash_settings(repo_id, trash_settings)
+    }
+
+    pub fn repos_set_versions_settings(
+        &self,
+        repo_id: &RepoId,
+        versions_settings: repos::state::RepoVersionsSettings,
+    ) -> Result<(), repos::errors::SetVersionsSettingsError> {
+        self.repos_service
+            .set_versions_settings(repo_id, versions_settings)
+    }
+
     pub fn repos_set_default_auto_lock(&self, auto_lock: repos::state::RepoAutoLock) {
         self.repos_service.set_default_auto_lock(auto_lock)
     }
@@ -623,6 +712,28 @@ impl Vault {
         self.repo_remove_service.destroy(remove_id)
     }
 
+    // repo_password_change
+
+    pub fn repo_password_change_create(&self, repo_id: RepoId) -> u32 {
+        self.repo_password_change_service.create(repo_id)
+    }
+
+    pub async fn repo_password_change_change(
+        &self,
+        change_id: u32,
+        old_password: &str,
+        new_password: &str,
+        new_salt: Option<&str>,
+    ) -> Result<(), repo_password_change::errors::ChangeRepoPasswordError> {
+        self.repo_password_change_service
+            .change(change_id, old_password, new_password, new_salt)
+            .await
+    }
+
+    pub fn repo_password_change_destroy(&self, change_id: u32) {
+        self.repo_password_change_service.destroy(change_id)
+    }
+
     // repo_config_backup
 
     pub fn repo_config_backup_create(&self, repo_id: RepoId) -> u32 {
@@ -682,6 +793,18 @@ impl Vault {
             .get_file_reader(repo_id, path)
     }
 
+    pub async fn repo_files_get_file_reader_range(
+        &self,
+        repo_id: &RepoId,
+        path: &EncryptedPath,
+        range: &repo_files_read::state::RepoFileReaderRange,
+    ) -> Result<repo_files_read::state::RepoFileReader, repo_files_read::errors::GetFilesReaderError>
+    {
+        self.repo_files_service
+            .get_file_reader_range(repo_id, path, range)
+            .await
+    }
+
     pub async fn repo_files_delete_files(
         &self,
         files: &[(RepoId, EncryptedPath)],
@@ -697,6 +820,19 @@ impl Vault {
         self.repo_files_service.rename_file(repo_id, path).await
     }
 
+    pub async fn repo_files_repair_files(
+        &self,
+        repo_id: &RepoId,
+        paths: &[EncryptedPath],
+        action: repo_files::state::RepoFilesRepairAction,
+        dry_run: bool,
+    ) -> Result<Vec<repo_files::state::RepoFilesRepairItem>, repo_files::errors::RepairFilesError>
+    {
+        self.repo_files_service
+            .repair_files(repo_id, paths, action, dry_run)
+            .await
+    }
+
     // transfers
 
     pub fn transfers_upload(
@@ -744,6 +880,39 @@ impl Vault {
         self.transfers_service.clone().retry_all();
     }
 
+    pub fn transfers_pause(&self, id: u32) {
+        self.transfers_service.clone().pause(id);
+    }
+
+    pub fn transfers_pause_all(&self) {
+        self.transfers_service.clone().pause_all();
+    }
+
+    pub fn transfers_resume(&self, id: u32) {
+        self.transfers_service.clone().resume(id);
+    }
+
+    pub fn transfers_resume_all(&self) {
+        self.transfers_service.clone().resume_all();
+    }
+
+    pub fn transfers_move_to_front(&self, id: u32) {
+        self.transfers_service.clone().move_to_front(id);
+    }
+
+    pub fn transfers_set_bandwidth_limits(
+        &self,
+        limits: transfers::state::TransfersBandwidthLimits,
+        schedule: Option<transfers::state::TransfersBandwidthSchedule>,
+    ) {
+        self.transfers_service
+            .set_bandwidth_limits(limits, schedule);
+    }
+
+    pub fn transfers_set_verify_uploads(&self, verify_uploads: bool) {
+        self.transfers_service.set_verify_uploads(verify_uploads);
+    }
+
     pub async fn transfers_open(&self, id: u32) -> Result<(), transfers::errors::TransferError> {
         self.transfers_service.clone().open(id).await
     }
@@ -919,6 +1088,20 @@ impl Vault {
             .await
     }
 
+    pub async fn repo_files_details_get_file_reader_range(
+        &self,
+        details_id: u32,
+        range: &repo_files_read::state::RepoFileReaderRange,
+    ) -> Result<
+        repo_files_read::state::RepoFileReader,
+        repo_files_read::errors::GetFilesReaderError,
+    > {
+        self.repo_files_details_service
+            .clone()
+            .get_file_reader_range(details_id, range)
+            .await
+    }
+
     pub async fn repo_files_details_download(
         &self,
         details_id: u32,
@@ -964,6 +1147,63 @@ impl Vault {
             .await
     }
 
+    pub async fn repo_files_details_resolve_merge_conflict(
+        &self,
+        details_id: u32,
+        content: Vec<u8>,
+    ) -> Result<(), repo_files_details::errors::SaveError> {
+        self.repo_files_details_service
+            .clone()
+            .resolve_merge_conflict(details_id, content)
+            .await
+    }
+
+    pub async fn repo_files_details_versions(
+        &self,
+        details_id: u32,
+    ) -> Result<Vec<repo_versions::state::RepoVersion>, repo_versions::errors::RepoVersionsError>
+    {
+        self.repo_files_details_service
+            .get_versions(details_id)
+            .await
+    }
+
+    pub async fn repo_files_details_version_get_file_reader(
+        &self,
+        details_id: u32,
+        version_path: &EncryptedPath,
+    ) -> Result<
+        repo_files_read::state::RepoFileReaderProvider,
+        repo_versions::errors::RepoVersionsError,
+    > {
+        self.repo_files_details_service
+            .get_version_file_reader(details_id, version_path)
+            .await
+    }
+
+    pub async fn repo_files_details_version_diff(
+        &self,
+        details_id: u32,
+        version_path: &EncryptedPath,
+    ) -> Result<
+        Vec<repo_versions::state::RepoVersionDiffLine>,
+        repo_versions::errors::RepoVersionsError,
+    > {
+        self.repo_files_details_service
+            .get_version_diff(details_id, version_path)
+            .await
+    }
+
+    pub async fn repo_files_details_version_restore(
+        &self,
+        details_id: u32,
+        version_path: &EncryptedPath,
+    ) -> Result<(), repo_versions::errors::RepoVersionsError> {
+        self.repo_files_details_service
+            .restore_version(details_id, version_path)
+            .await
+    }
+
     pub async fn repo_files_details_delete(
         &self,
         details_id: u32,
@@ -1013,6 +1253,102 @@ impl Vault {
     ) -> Result<(), repo_files::errors::CreateDirError> {
         self.repo_files_move_service.create_dir().await
     }
+
+    // repo_files_cross_move
+
+    pub async fn repo_files_cross_move_move_files(
+        &self,
+        src_repo_id: &RepoId,
+        src_paths: &[EncryptedPath],
+        dest_repo_id: &RepoId,
+        dest_parent_path: &EncryptedPath,
+        mode: repo_files_move::state::RepoFilesMoveMode,
+    ) -> Result<(), repo_files_cross_move::errors::CrossMoveError> {
+        self.repo_files_cross_move_service
+            .move_files(src_repo_id, src_paths, dest_repo_id, dest_parent_path, mode)
+            .await
+    }
+
+    // repo_fsck
+
+    pub async fn repo_fsck_check(
+        &self,
+        repo_id: &RepoId,
+        options: repo_fsck::state::RepoFsckOptions,
+    ) -> Result<repo_fsck::service::RepoFsckEntryStream, repo_fsck::errors::RepoFsckError> {
+        self.repo_fsck_service.check(repo_id, options).await
+    }
+
+    pub async fn repo_fsck_report(
+        &self,
+        repo_id: &RepoId,
+        options: repo_fsck::state::RepoFsckOptions,
+    ) -> Result<repo_fsck::state::RepoFsckReport, repo_fsck::errors::RepoFsckError> {
+        self.repo_fsck_service.report(repo_id, options).await
+    }
+
+    // repo_search
+
+    pub fn repo_search_create(&self, repo_id: RepoId) -> u32 {
+        self.repo_search_service.create(repo_id)
+    }
+
+    pub async fn repo_search_load_index(
+        &self,
+        repo_id: &RepoId,
+        include_content: bool,
+    ) -> Result<(), repo_search::errors::RepoSearchError> {
+        self.repo_search_service
+            .load_index(repo_id, include_content)
+            .await
+    }
+
+    pub fn repo_search_set_query(&self, search_id: u32, query: String) {
+        self.repo_search_service.set_query(search_id, query)
+    }
+
+    pub fn repo_search_destroy(&self, search_id: u32) {
+        self.repo_search_service.destroy(search_id)
+    }
+
+    // repo_trash
+
+    pub async fn repo_trash_list(
+        &self,
+        repo_id: &RepoId,
+    ) -> Result<Vec<repo_trash::state::RepoTrashEntry>, repo_trash::errors::RepoTrashError> {
+        self.repo_trash_service.list(repo_id).await
+    }
+
+    pub async fn repo_trash_restore(
+        &self,
+        repo_id: &RepoId,
+        paths: &[EncryptedPath],
+    ) -> Result<Vec<EncryptedPath>, repo_trash::errors::RepoTrashError> {
+        self.repo_trash_service.restore(repo_id, paths).await
+    }
+
+    pub async fn repo_trash_purge(
+        &self,
+        repo_id: &RepoId,
+        paths: &[EncryptedPath],
+    ) -> Result<(), repo_trash::errors::RepoTrashError> {
+        self.repo_trash_service.purge(repo_id, paths).await
+    }
+
+    pub async fn repo_trash_purge_all(
+        &self,
+        repo_id: &RepoId,
+    ) -> Result<(), repo_trash::errors::RepoTrashError> {
+        self.repo_trash_service.purge_all(repo_id).await
+    }
+
+    pub async fn repo_trash_purge_expired(
+        &self,
+        repo_id: &RepoId,
+    ) -> Result<usize, repo_trash::errors::RepoTrashError> {
+        self.repo_trash_service.purge_expired(repo_id).await
+    }
 }
 
 const _: () = {
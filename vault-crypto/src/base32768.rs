@@ -0,0 +1,218 @@
+//! Base32768 encoding (https://github.com/qntm/base32768) used by rclone
+//! for filename_encoding = base32768. Every character encodes 15 bits, the
+//! final character may encode 7 bits. Unused bits are padded with 1s.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use super::errors::DecryptFilenameError;
+
+const BITS_PER_CHAR: usize = 15;
+const BITS_PER_BYTE: usize = 8;
+
+/// Pairs of first and last code points of the repertoire ranges. The first
+/// string encodes 15 bits per character, the second one 7 bits.
+const PAIR_STRINGS: [&str; 2] = [
+    "ҠҿԀԟڀڿݠޟ߀ߟကဟႠႿᄀᅟᆀᆟᇠሿበቿዠዿጠጿᎠᏟᐠᙟᚠᛟកសᠠᡟᣀᣟᦀᦟ᧠᧿ᨠᨿᯀᯟᰀᰟᴀᴟ⇠⇿⋀⋟⍀⏟␀␟─❟➀➿⠀⥿⦠⦿⨠⩟⪀⪿⫠⭟ⰀⰟⲀⳟⴀⴟⵀⵟ⺠⻟㇀㇟㐀䶟䷀龿ꀀꑿ꒠꒿ꔀꗿꙀꙟꚠꛟ꜀ꝟꞀꞟꡀꡟ",
+    "ƀƟɀʟ",
+];
+
+struct Repertoire {
+    /// encode[0] has 2^15 characters, encode[1] has 2^7 characters
+    encode: [Vec<char>; 2],
+    /// character -> (number of bits, value)
+    decode: HashMap<char, (usize, u16)>,
+}
+
+fn repertoire() -> &'static Repertoire {
+    static REPERTOIRE: OnceLock<Repertoire> = OnceLock::new();
+
+    REPERTOIRE.get_or_init(|| {
+        let encode = PAIR_STRINGS.map(|pair_string| {
+            let chars: Vec<char> = pair_string.chars().collect();
+
+            chars
+                .chunks(2)
+                .flat_map(|pair| (pair[0]..=pair[1]).collect::<Vec<char>>())
+                .collect::<Vec<char>>()
+        });
+
+        let mut decode = HashMap::new();
+
+        for (r, chars) in encode.iter().enumerate() {
+            let num_bits = BITS_PER_CHAR - BITS_PER_BYTE * r;
+
+            for (z, c) in chars.iter().enumerate() {
+                decode.insert(*c, (num_bits, z as u16));
+            }
+        }
+
+        Repertoire { encode, decode }
+    })
+}
+
+fn encode_char(repertoire: &Repertoire, num_bits: usize, z: u16) -> char {
+    let r = (BITS_PER_CHAR - num_bits) / BITS_PER_BYTE;
+
+    repertoire.encode[r][z as usize]
+}
+
+pub fn encode(data: &[u8]) -> String {
+    let repertoire = repertoire();
+
+    let mut out = String::with_capacity((data.len() * BITS_PER_BYTE).div_ceil(BITS_PER_CHAR) * 3);
+    let mut z: u16 = 0;
+    let mut num_z_bits = 0;
+
+    for byte in data {
+        for j in (0..BITS_PER_BYTE).rev() {
+            z = (z << 1) | ((*byte >> j) & 1) as u16;
+            num_z_bits += 1;
+
+            if num_z_bits == BITS_PER_CHAR {
+                out.push(encode_char(repertoire, num_z_bits, z));
+
+                z = 0;
+                num_z_bits = 0;
+            }
+        }
+    }
+
+    if num_z_bits != 0 {
+        let pad_to = if num_z_bits <= BITS_PER_CHAR - BITS_PER_BYTE {
+            BITS_PER_CHAR - BITS_PER_BYTE
+        } else {
+            BITS_PER_CHAR
+        };
+
+        while num_z_bits < pad_to {
+            z = (z << 1) | 1;
+            num_z_bits += 1;
+        }
+
+        out.push(encode_char(repertoire, num_z_bits, z));
+    }
+
+    out
+}
+
+pub fn decode(encoded: &str) -> Result<Vec<u8>, DecryptFilenameError> {
+    let repertoire = repertoire();
+
+    let chars: Vec<char> = encoded.chars().collect();
+
+    let mut out = Vec::with_capacity(chars.len() * BITS_PER_CHAR / BITS_PER_BYTE);
+    let mut byte: u16 = 0;
+    let mut num_byte_bits = 0;
+
+    for (i, c) in chars.iter().enumerate() {
+        let (num_z_bits, z) = repertoire.decode.get(c).ok_or_else(|| {
+            DecryptFilenameError::DecodeError(format!("unrecognised base32768 character: {}", c))
+        })?;
+
+        if *num_z_bits != BITS_PER_CHAR && i != chars.len() - 1 {
+            return Err(DecryptFilenameError::DecodeError(format!(
+                "secondary base32768 character found before end of input at position {}",
+                i
+            )));
+        }
+
+        for j in (0..*num_z_bits).rev() {
+            byte = (byte << 1) | ((z >> j) & 1);
+            num_byte_bits += 1;
+
+            if num_byte_bits == BITS_PER_BYTE {
+                out.push(byte as u8);
+
+                byte = 0;
+                num_byte_bits = 0;
+            }
+        }
+    }
+
+    if byte != (1 << num_byte_bits) - 1 {
+        return Err(DecryptFilenameError::DecodeError(
+            "base32768 padding mismatch".into(),
+        ));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cipher::Cipher,
+        constants::{DATA_KEY_LEN, NAME_CIPHER_BLOCK_SIZE, NAME_KEY_LEN},
+        name_cipher::{FilenameEncoding, NameCipherOptions},
+    };
+
+    use super::{decode, encode, repertoire};
+
+    #[test]
+    fn test_repertoire() {
+        let repertoire = repertoire();
+
+        assert_eq!(repertoire.encode[0].len(), 1 << 15);
+        assert_eq!(repertoire.encode[1].len(), 1 << 7);
+        assert_eq!(repertoire.decode.len(), (1 << 15) + (1 << 7));
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        assert_eq!(encode(&[]), "");
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+
+        for len in 0..64usize {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 + 11) as u8).collect();
+
+            let encoded = encode(&data);
+
+            assert_eq!(encoded.chars().count(), (len * 8).div_ceil(15));
+            assert_eq!(decode(&encoded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(decode("abc").is_err());
+        // a 7 bit character is only allowed at the end
+        assert!(decode("ƀҠ").is_err());
+    }
+
+    /// rclone TestEncryptSegmentBase32768 (an empty password means all-zero
+    /// keys in rclone)
+    #[test]
+    fn test_rclone_encrypt_segment() {
+        let cipher = Cipher::with_keys(
+            [0; DATA_KEY_LEN],
+            [0; NAME_KEY_LEN],
+            [0; NAME_CIPHER_BLOCK_SIZE],
+            NameCipherOptions {
+                filename_encoding: FilenameEncoding::Base32768,
+                ..Default::default()
+            },
+        );
+
+        for (plaintext, expected) in [
+            ("1", "詮㪗鐮僀伎作㻖㢧⪟"),
+            ("12", "竢朧䉱虃光塬䟛⣡蓟"),
+            ("123", "遶㞟鋅缕袡鲅ⵝ蝁ꌟ"),
+            ("1234", "䢟銮䵵狌㐜燳谒颴詟"),
+            ("12345", "钉Ꞇ㖃蚩憶狫朰杜㜿"),
+            ("123456", "啇ᚵⵕ憗䋫➫➓肤卟"),
+            ("1234567", "茫螓翁連劘樓㶔抉矟"),
+            ("12345678", "龝☳䘊辄岅較络㧩襟"),
+            ("123456789", "ⲱ苀㱆犂媐Ꮤ锇惫靟"),
+            ("1234567890", "計宁憕偵匢皫╛纺ꌟ"),
+            ("12345678901", "檆䨿鑫㪺藝ꡖ勇䦛婟"),
+            ("123456789012", "雑頏䰂䲝淚哚鹡魺⪟"),
+            ("1234567890123", "塃璶繁躸圅㔟䗃肃懟"),
+            ("12345678901234", "腺ᕚ崚鏕鏥讥鼌䑺䲿"),
+            ("123456789012345", "怪绕滻蕶肣但⠥荖惟"),
+            ("1234567890123456", "肳哀旚挶靏鏻㾭䱠慟㪳ꏆ賊兲铧敻塹魀ʟ"),
+        ] {
+            assert_eq!(cipher.encrypt_filename(plaintext), expected);
+            assert_eq!(cipher.decrypt_filename(expected).unwrap(), plaintext);
+        }
+    }
+}
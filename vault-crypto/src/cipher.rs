@@ -12,69 +12,82 @@ use super::{
     decrypt_reader::{AsyncDecryptReader, SyncDecryptReader},
     encrypt_reader::{AsyncEncryptReader, SyncEncryptReader},
     errors::DecryptFilenameError,
-    name_cipher::{
-        decrypt_filename, decrypt_path, encrypt_filename, encrypt_path, get_name_cipher,
-    },
+    name_cipher::{NameCipher, NameCipherOptions},
     nonce::Nonce,
 };
 
 pub struct Cipher {
-    name_key: [u8; NAME_KEY_LEN],
-    name_tweak: [u8; NAME_CIPHER_BLOCK_SIZE],
+    name_cipher: NameCipher,
     data_cipher: Arc<XSalsa20Poly1305>,
 }
 
 impl Cipher {
     pub fn new(password: &str, salt: Option<&str>) -> Self {
+        Self::new_with_name_options(password, salt, NameCipherOptions::default())
+    }
+
+    pub fn new_with_name_options(
+        password: &str,
+        salt: Option<&str>,
+        name_options: NameCipherOptions,
+    ) -> Self {
         let DerivedKeys {
             data_key,
             name_key,
             name_tweak,
         } = derive_keys(password, salt);
 
-        Self::with_keys(data_key, name_key, name_tweak)
+        Self::with_keys(data_key, name_key, name_tweak, name_options)
     }
 
     pub fn with_keys(
         data_key: [u8; DATA_KEY_LEN],
         name_key: [u8; NAME_KEY_LEN],
         name_tweak: [u8; NAME_CIPHER_BLOCK_SIZE],
+        name_options: NameCipherOptions,
     ) -> Self {
         let data_cipher = get_data_cipher(&data_key);
 
         Self {
-            name_key,
-            name_tweak,
+            name_cipher: NameCipher::new(name_key, name_tweak, name_options),
             data_cipher: Arc::new(data_cipher),
         }
     }
 
+    pub fn name_options(&self) -> &NameCipherOptions {
+        self.name_cipher.options()
+    }
+
     pub fn encrypt_filename(&self, plaintext: &str) -> String {
-        encrypt_filename(
-            get_name_cipher(&self.name_key, &self.name_tweak),
-            &plaintext,
-        )
+        self.name_cipher.encrypt_filename(plaintext)
+    }
+
+    pub fn encrypt_dir_name(&self, plaintext: &str) -> String {
+        self.name_cipher.encrypt_dir_name(plaintext)
     }
 
     pub fn encrypt_path(&self, plaintext: &str) -> String {
-        encrypt_path(
-            get_name_cipher(&self.name_key, &self.name_tweak),
-            &plaintext,
-        )
+        self.name_cipher.encrypt_path(plaintext)
+    }
+
+    pub fn encrypt_dir_path(&self, plaintext: &str) -> String {
+        self.name_cipher.encrypt_dir_path(plaintext)
     }
 
     pub fn decrypt_filename(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
-        decrypt_filename(
-            get_name_cipher(&self.name_key, &self.name_tweak),
-            &ciphertext,
-        )
+        self.name_cipher.decrypt_filename(ciphertext)
+    }
+
+    pub fn decrypt_dir_name(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        self.name_cipher.decrypt_dir_name(ciphertext)
     }
 
     pub fn decrypt_path(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
-        decrypt_path(
-            get_name_cipher(&self.name_key, &self.name_tweak),
-            &ciphertext,
-        )
+        self.name_cipher.decrypt_path(ciphertext)
+    }
+
+    pub fn decrypt_dir_path(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        self.name_cipher.decrypt_dir_path(ciphertext)
     }
 
     pub fn encrypt_reader_async<R>(&self, reader: R) -> AsyncEncryptReader<R> {
@@ -83,6 +96,21 @@ impl Cipher {
         AsyncEncryptReader::new(reader, self.data_cipher.clone(), nonce)
     }
 
+    /// encrypt_reader_async_nonce encrypts with a known nonce so that the
+    /// encryption can later be continued with encrypt_reader_async_at_block.
+    pub fn encrypt_reader_async_nonce<R>(&self, reader: R, nonce: Nonce) -> AsyncEncryptReader<R> {
+        AsyncEncryptReader::new(reader, self.data_cipher.clone(), nonce)
+    }
+
+    pub fn encrypt_reader_async_at_block<R>(
+        &self,
+        reader: R,
+        nonce: Nonce,
+        block: u64,
+    ) -> AsyncEncryptReader<R> {
+        AsyncEncryptReader::new_at_block(reader, self.data_cipher.clone(), nonce, block)
+    }
+
     pub fn encrypt_reader_sync<R>(&self, reader: R) -> SyncEncryptReader<R> {
         let nonce = Nonce::new_random().unwrap();
 
@@ -99,6 +127,18 @@ impl Cipher {
         AsyncDecryptReader::new(reader, self.data_cipher.clone())
     }
 
+    /// decrypt_reader_async_at_block decrypts a file from the start of block
+    /// (see data_cipher::decrypt_range).
+    pub fn decrypt_reader_async_at_block<R>(
+        &self,
+        reader: R,
+        nonce: Nonce,
+        block: u64,
+        skip: usize,
+    ) -> AsyncDecryptReader<R> {
+        AsyncDecryptReader::new_at_block(reader, self.data_cipher.clone(), nonce, block, skip)
+    }
+
     pub fn decrypt_reader_sync<R>(&self, reader: R) -> SyncDecryptReader<R> {
         SyncDecryptReader::new(reader, self.data_cipher.clone())
     }
@@ -4,7 +4,10 @@ use xsalsa20poly1305::aead::{AeadInPlace, KeyInit};
 pub use xsalsa20poly1305::XSalsa20Poly1305;
 
 use super::{
-    constants::{BLOCK_DATA_SIZE, BLOCK_HEADER_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE},
+    constants::{
+        BLOCK_DATA_SIZE, BLOCK_HEADER_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE, FILE_MAGIC,
+        FILE_MAGIC_SIZE,
+    },
     errors::DecryptSizeError,
     nonce::Nonce,
     CipherError,
@@ -77,3 +80,111 @@ pub fn decrypt_size(size: i64) -> Result<i64, DecryptSizeError> {
 
     Ok(decrypted_size)
 }
+
+/// block_offsets returns the index, the encrypted offset and the decrypted
+/// offset of the last block that starts at or before encrypted_offset. It
+/// returns None if encrypted_offset is inside the file header.
+pub fn block_offsets(encrypted_offset: i64) -> Option<(u64, i64, i64)> {
+    let size = encrypted_offset - FILE_HEADER_SIZE as i64;
+    if size < 0 {
+        return None;
+    }
+    let block = size / BLOCK_SIZE as i64;
+
+    Some((
+        block as u64,
+        FILE_HEADER_SIZE as i64 + block * BLOCK_SIZE as i64,
+        block * BLOCK_DATA_SIZE as i64,
+    ))
+}
+
+/// DecryptRange is the part of an encrypted file that contains a plaintext
+/// range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecryptRange {
+    /// index of the first block
+    pub block: u64,
+    /// encrypted offset of the first block
+    pub encrypted_start: i64,
+    /// inclusive encrypted end of the last block (None until the end of the
+    /// file)
+    pub encrypted_end: Option<i64>,
+    /// number of decrypted bytes to skip in the first block
+    pub skip: usize,
+}
+
+/// decrypt_range maps the inclusive plaintext range start..=end to the
+/// encrypted blocks that contain it. Blocks are decrypted with the file nonce
+/// added the block index.
+pub fn decrypt_range(start: i64, end: Option<i64>) -> DecryptRange {
+    let block = start / BLOCK_DATA_SIZE as i64;
+
+    DecryptRange {
+        block: block as u64,
+        encrypted_start: FILE_HEADER_SIZE as i64 + block * BLOCK_SIZE as i64,
+        encrypted_end: end.map(|end| {
+            FILE_HEADER_SIZE as i64 + (end / BLOCK_DATA_SIZE as i64 + 1) * BLOCK_SIZE as i64 - 1
+        }),
+        skip: (start % BLOCK_DATA_SIZE as i64) as usize,
+    }
+}
+
+/// decrypt_header checks the file magic and returns the file nonce
+pub fn decrypt_header(header: &[u8]) -> Result<Nonce, CipherError> {
+    if header.len() < FILE_HEADER_SIZE {
+        return Err(CipherError::EncryptedFileTooShort);
+    }
+
+    if &header[..FILE_MAGIC_SIZE] != FILE_MAGIC {
+        return Err(CipherError::EncryptedBadMagic);
+    }
+
+    Ok(Nonce::new(
+        header[FILE_MAGIC_SIZE..FILE_HEADER_SIZE]
+            .try_into()
+            .unwrap(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::{BLOCK_DATA_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE};
+
+    use super::{decrypt_range, DecryptRange};
+
+    #[test]
+    fn test_decrypt_range() {
+        assert_eq!(
+            decrypt_range(0, Some(9)),
+            DecryptRange {
+                block: 0,
+                encrypted_start: FILE_HEADER_SIZE as i64,
+                encrypted_end: Some((FILE_HEADER_SIZE + BLOCK_SIZE - 1) as i64),
+                skip: 0,
+            }
+        );
+
+        assert_eq!(
+            decrypt_range(
+                (BLOCK_DATA_SIZE * 2 + 100) as i64,
+                Some((BLOCK_DATA_SIZE * 3) as i64)
+            ),
+            DecryptRange {
+                block: 2,
+                encrypted_start: (FILE_HEADER_SIZE + BLOCK_SIZE * 2) as i64,
+                encrypted_end: Some((FILE_HEADER_SIZE + BLOCK_SIZE * 4 - 1) as i64),
+                skip: 100,
+            }
+        );
+
+        assert_eq!(
+            decrypt_range(BLOCK_DATA_SIZE as i64, None),
+            DecryptRange {
+                block: 1,
+                encrypted_start: (FILE_HEADER_SIZE + BLOCK_SIZE) as i64,
+                encrypted_end: None,
+                skip: 0,
+            }
+        );
+    }
+}
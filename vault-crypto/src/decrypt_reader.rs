@@ -7,15 +7,18 @@ use futures::{
 use pin_project_lite::pin_project;
 use std::{
     cmp,
-    io::{Read, Result},
+    io::{Read, Result, Seek, SeekFrom},
     pin::Pin,
     sync::Arc,
 };
 use xsalsa20poly1305::XSalsa20Poly1305;
 
 use super::{
-    constants::{BLOCK_HEADER_SIZE, BLOCK_SIZE, FILE_MAGIC, FILE_MAGIC_SIZE, FILE_NONCE_SIZE},
-    data_cipher::decrypt_block,
+    constants::{
+        BLOCK_HEADER_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE, FILE_MAGIC, FILE_MAGIC_SIZE,
+        FILE_NONCE_SIZE,
+    },
+    data_cipher::{decrypt_block, decrypt_header, decrypt_range, decrypt_size},
     nonce::Nonce,
     CipherError,
 };
@@ -46,6 +49,11 @@ pub struct SyncDecryptReader<R> {
     inner: R,
     state: DecryptReaderState,
     data_cipher: Arc<XSalsa20Poly1305>,
+    file_nonce: Option<Nonce>,
+    /// decrypted bytes to skip in the next block
+    skip: usize,
+    /// decrypted position
+    position: u64,
 }
 
 impl<R> SyncDecryptReader<R> {
@@ -57,6 +65,9 @@ impl<R> SyncDecryptReader<R> {
                 pos: 0,
             },
             data_cipher,
+            file_nonce: None,
+            skip: 0,
+            position: 0,
         }
     }
 }
@@ -97,10 +108,12 @@ impl<R: Read> Read for SyncDecryptReader<R> {
                     *pos += n;
 
                     if *pos == FILE_NONCE_SIZE {
-                        let nonce = &buffer[..FILE_NONCE_SIZE];
+                        let nonce = Nonce::new(buffer[..FILE_NONCE_SIZE].try_into().unwrap());
+
+                        self.file_nonce = Some(nonce.clone());
 
                         self.state = DecryptReaderState::ReadingCiphertext {
-                            nonce: Nonce::new(nonce.try_into().unwrap()),
+                            nonce,
                             buffer: vec![0; BLOCK_SIZE],
                             pos: 0,
                         };
@@ -128,10 +141,12 @@ impl<R: Read> Read for SyncDecryptReader<R> {
 
                         nonce.increment();
 
+                        let skip = cmp::min(mem::take(&mut self.skip), decrypted.len());
+
                         self.state = DecryptReaderState::WritingPlaintext {
                             nonce: mem::take(nonce),
                             buffer: decrypted,
-                            pos: 0,
+                            pos: skip,
                         };
                     }
                 }
@@ -141,6 +156,7 @@ impl<R: Read> Read for SyncDecryptReader<R> {
                     buf[..n].copy_from_slice(&buffer[*pos..*pos + n]);
 
                     *pos += n;
+                    self.position += n as u64;
 
                     if *pos == buffer.len() {
                         self.state = DecryptReaderState::ReadingCiphertext {
@@ -148,6 +164,10 @@ impl<R: Read> Read for SyncDecryptReader<R> {
                             buffer: vec![0; BLOCK_SIZE],
                             pos: 0,
                         };
+
+                        if n == 0 {
+                            continue;
+                        }
                     }
 
                     return Ok(n);
@@ -157,12 +177,83 @@ impl<R: Read> Read for SyncDecryptReader<R> {
     }
 }
 
+impl<R: Read + Seek> SyncDecryptReader<R> {
+    fn get_file_nonce(&mut self) -> Result<Nonce> {
+        if let Some(nonce) = &self.file_nonce {
+            return Ok(nonce.clone());
+        }
+
+        let mut header = [0; FILE_HEADER_SIZE];
+
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.inner
+            .read_exact(&mut header)
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::UnexpectedEof => CipherError::EncryptedFileTooShort.into(),
+                _ => err,
+            })?;
+
+        let nonce = decrypt_header(&header).map_err(Into::<std::io::Error>::into)?;
+
+        self.file_nonce = Some(nonce.clone());
+
+        Ok(nonce)
+    }
+}
+
+/// Seeking only reads the encrypted block that contains the new position (and
+/// the file header if it has not been read yet).
+impl<R: Read + Seek> Seek for SyncDecryptReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let nonce = self.get_file_nonce()?;
+
+        let position = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => {
+                let encrypted_size = self.inner.seek(SeekFrom::End(0))?;
+
+                let size = decrypt_size(encrypted_size as i64)
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+
+                size + offset
+            }
+        };
+
+        if position < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        }
+
+        let range = decrypt_range(position, None);
+
+        self.inner
+            .seek(SeekFrom::Start(range.encrypted_start as u64))?;
+
+        let mut block_nonce = nonce;
+        block_nonce.add(range.block);
+
+        self.state = DecryptReaderState::ReadingCiphertext {
+            nonce: block_nonce,
+            buffer: vec![0; BLOCK_SIZE],
+            pos: 0,
+        };
+        self.skip = range.skip;
+        self.position = position as u64;
+
+        Ok(self.position)
+    }
+}
+
 pin_project! {
     pub struct AsyncDecryptReader<R> {
         #[pin]
         inner: R,
         state: DecryptReaderState,
-        data_cipher: Arc<XSalsa20Poly1305>
+        data_cipher: Arc<XSalsa20Poly1305>,
+        skip: usize,
     }
 }
 
@@ -175,6 +266,33 @@ impl<R> AsyncDecryptReader<R> {
                 pos: 0,
             },
             data_cipher,
+            skip: 0,
+        }
+    }
+
+    /// new_at_block decrypts a file from the start of block (without the file
+    /// header, see decrypt_range). inner must start at the encrypted offset of
+    /// block and nonce must be the file nonce. skip decrypted bytes of the
+    /// first block are not returned.
+    pub fn new_at_block(
+        inner: R,
+        data_cipher: Arc<XSalsa20Poly1305>,
+        nonce: Nonce,
+        block: u64,
+        skip: usize,
+    ) -> Self {
+        let mut nonce = nonce;
+        nonce.add(block);
+
+        Self {
+            inner,
+            state: DecryptReaderState::ReadingCiphertext {
+                nonce,
+                buffer: vec![0; BLOCK_SIZE],
+                pos: 0,
+            },
+            data_cipher,
+            skip,
         }
     }
 }
@@ -252,10 +370,12 @@ impl<R: AsyncRead> AsyncRead for AsyncDecryptReader<R> {
 
                         nonce.increment();
 
+                        let skip = cmp::min(mem::take(this.skip), decrypted.len());
+
                         *this.state = DecryptReaderState::WritingPlaintext {
                             nonce: mem::take(nonce),
                             buffer: decrypted,
-                            pos: 0,
+                            pos: skip,
                         };
                     }
                 }
@@ -272,6 +392,10 @@ impl<R: AsyncRead> AsyncRead for AsyncDecryptReader<R> {
                             buffer: vec![0; BLOCK_SIZE],
                             pos: 0,
                         };
+
+                        if n == 0 {
+                            continue;
+                        }
                     }
 
                     return Poll::Ready(Ok(n));
@@ -283,15 +407,20 @@ impl<R: AsyncRead> AsyncRead for AsyncDecryptReader<R> {
 
 #[cfg(test)]
 mod tests {
-    use std::{io::Result, sync::Arc, task::Poll};
+    use std::{
+        io::{Read, Result, Seek, SeekFrom},
+        sync::Arc,
+        task::Poll,
+    };
 
     use futures::{channel::mpsc, stream::TryStreamExt, AsyncRead};
     use xsalsa20poly1305::XSalsa20Poly1305;
 
     use crate::{
-        constants::FILE_MAGIC,
-        data_cipher::{encrypt_block, get_data_cipher},
+        constants::{BLOCK_DATA_SIZE, FILE_HEADER_SIZE, FILE_MAGIC},
+        data_cipher::{decrypt_header, decrypt_range, encrypt_block, get_data_cipher},
         decrypt_reader::SyncDecryptReader,
+        encrypt_reader::SyncEncryptReader,
         nonce::Nonce,
         test_helpers::{assert_reader_pending, assert_reader_ready},
     };
@@ -313,6 +442,22 @@ mod tests {
         ])
     }
 
+    fn get_dummy_data() -> Vec<u8> {
+        (0..BLOCK_DATA_SIZE * 3 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect()
+    }
+
+    fn encrypt_data(data_cipher: &Arc<XSalsa20Poly1305>, data: &[u8]) -> Vec<u8> {
+        let mut encrypted = Vec::new();
+
+        SyncEncryptReader::new(data, data_cipher.clone(), get_dummy_nonce())
+            .read_to_end(&mut encrypted)
+            .unwrap();
+
+        encrypted
+    }
+
     fn concat_vecs(vecs: &mut [Vec<u8>]) -> Vec<u8> {
         let mut res = vec![];
         for vec in vecs {
@@ -396,4 +541,86 @@ mod tests {
 
         assert_eq!(res, b"test");
     }
+
+    #[test]
+    fn test_sync_decrypt_reader_seek() {
+        let data_cipher = get_dummy_data_cipher();
+        let data = get_dummy_data();
+        let encrypted = encrypt_data(&data_cipher, &data);
+
+        let mut r = SyncDecryptReader::new(std::io::Cursor::new(encrypted), data_cipher.clone());
+
+        let mut buf = vec![0; 10];
+
+        let offset = BLOCK_DATA_SIZE * 2 - 5;
+        assert_eq!(
+            r.seek(SeekFrom::Start(offset as u64)).unwrap(),
+            offset as u64
+        );
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[offset..offset + 10]);
+
+        let offset = offset + 10 + 1000;
+        assert_eq!(r.seek(SeekFrom::Current(1000)).unwrap(), offset as u64);
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[offset..offset + 10]);
+
+        let offset = data.len() - 10;
+        assert_eq!(r.seek(SeekFrom::End(-10)).unwrap(), offset as u64);
+        let mut rest = Vec::new();
+        r.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, data[offset..]);
+
+        r.seek(SeekFrom::Start(0)).unwrap();
+        let mut all = Vec::new();
+        r.read_to_end(&mut all).unwrap();
+        assert_eq!(all, data);
+
+        // seek past the end of the block
+        r.seek(SeekFrom::Start(BLOCK_DATA_SIZE as u64)).unwrap();
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[BLOCK_DATA_SIZE..BLOCK_DATA_SIZE + 10]);
+
+        assert!(r.seek(SeekFrom::Current(-100_000_000)).is_err());
+    }
+
+    #[test]
+    fn test_async_decrypt_reader_new_at_block() {
+        let data_cipher = get_dummy_data_cipher();
+        let data = get_dummy_data();
+        let encrypted = encrypt_data(&data_cipher, &data);
+
+        let nonce = decrypt_header(&encrypted[..FILE_HEADER_SIZE]).unwrap();
+        assert_eq!(nonce, get_dummy_nonce());
+
+        let start = BLOCK_DATA_SIZE * 2 + 100;
+        let end = BLOCK_DATA_SIZE * 3 + 10;
+        let range = decrypt_range(start as i64, Some(end as i64));
+
+        let encrypted_end =
+            std::cmp::min(range.encrypted_end.unwrap() as usize + 1, encrypted.len());
+        let inner = futures::io::Cursor::new(
+            encrypted[range.encrypted_start as usize..encrypted_end].to_vec(),
+        );
+
+        let res = futures::executor::block_on(async {
+            let mut out = Vec::new();
+            let r = AsyncDecryptReader::new_at_block(
+                inner,
+                data_cipher.clone(),
+                nonce,
+                range.block,
+                range.skip,
+            );
+            futures::AsyncReadExt::read_to_end(
+                &mut futures::AsyncReadExt::take(r, (end - start + 1) as u64),
+                &mut out,
+            )
+            .await
+            .unwrap();
+            out
+        });
+
+        assert_eq!(res, data[start..=end]);
+    }
 }
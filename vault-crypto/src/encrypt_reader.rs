@@ -159,6 +159,29 @@ impl<R> AsyncEncryptReader<R> {
             data_cipher,
         }
     }
+
+    /// new_at_block continues the encryption of a file at the start of block
+    /// (without the file header). inner must start at block * BLOCK_DATA_SIZE
+    /// and nonce must be the file nonce.
+    pub fn new_at_block(
+        inner: R,
+        data_cipher: Arc<XSalsa20Poly1305>,
+        nonce: Nonce,
+        block: u64,
+    ) -> Self {
+        let mut nonce = nonce;
+        nonce.add(block);
+
+        Self {
+            inner,
+            state: EncryptReaderState::ReadingPlaintext {
+                nonce,
+                buffer: vec![0; BLOCK_DATA_SIZE],
+                pos: 0,
+            },
+            data_cipher,
+        }
+    }
 }
 
 impl<R: AsyncRead> AsyncRead for AsyncEncryptReader<R> {
@@ -259,8 +282,8 @@ mod tests {
     use xsalsa20poly1305::XSalsa20Poly1305;
 
     use crate::{
-        constants::FILE_MAGIC,
-        data_cipher::{decrypt_block, get_data_cipher},
+        constants::{BLOCK_DATA_SIZE, BLOCK_SIZE, FILE_HEADER_SIZE, FILE_MAGIC},
+        data_cipher::{block_offsets, decrypt_block, get_data_cipher},
         encrypt_reader::SyncEncryptReader,
         nonce::Nonce,
         test_helpers::{assert_reader_pending, assert_reader_ready},
@@ -386,4 +409,52 @@ mod tests {
 
         assert_eq!(res.unwrap().len(), 0);
     }
+
+    #[test]
+    fn test_async_encrypt_reader_new_at_block() {
+        let data_cipher = get_dummy_data_cipher();
+        let nonce = get_dummy_nonce();
+
+        let data: Vec<u8> = (0..BLOCK_DATA_SIZE * 3 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let encrypted = futures::executor::block_on(async {
+            let mut out = Vec::new();
+            let mut r = AsyncEncryptReader::new(
+                futures::io::Cursor::new(data.clone()),
+                data_cipher.clone(),
+                nonce.clone(),
+            );
+            futures::AsyncReadExt::read_to_end(&mut r, &mut out)
+                .await
+                .unwrap();
+            out
+        });
+
+        // resume in the middle of the third block
+        let (block, encrypted_offset, decrypted_offset) =
+            block_offsets((FILE_HEADER_SIZE + BLOCK_SIZE * 2 + 1000) as i64).unwrap();
+
+        assert_eq!(block, 2);
+        assert_eq!(encrypted_offset, (FILE_HEADER_SIZE + BLOCK_SIZE * 2) as i64);
+        assert_eq!(decrypted_offset, (BLOCK_DATA_SIZE * 2) as i64);
+        assert_eq!(block_offsets(10), None);
+
+        let resumed = futures::executor::block_on(async {
+            let mut out = Vec::new();
+            let mut r = AsyncEncryptReader::new_at_block(
+                futures::io::Cursor::new(data[decrypted_offset as usize..].to_vec()),
+                data_cipher.clone(),
+                nonce.clone(),
+                block,
+            );
+            futures::AsyncReadExt::read_to_end(&mut r, &mut out)
+                .await
+                .unwrap();
+            out
+        });
+
+        assert_eq!(resumed, encrypted[encrypted_offset as usize..].to_vec());
+    }
 }
@@ -57,4 +57,13 @@ pub enum DecryptFilenameError {
     DecryptError,
     #[error("unicode error: {0}")]
     UnicodeError(String),
+    #[error("not an encrypted file name")]
+    NotEncryptedName,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("invalid {name} value: {value}")]
+pub struct ParseNameOptionError {
+    pub name: &'static str,
+    pub value: String,
 }
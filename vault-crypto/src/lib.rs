@@ -1,3 +1,4 @@
+pub mod base32768;
 pub mod cipher;
 pub mod cipher_keys;
 pub mod constants;
@@ -1,80 +1,628 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
 use aes::Aes256;
-use data_encoding::BASE32HEX_NOPAD;
+use data_encoding::{BASE32HEX_NOPAD, BASE64URL_NOPAD};
 use eme_mode::{block_modes::BlockMode, block_padding::Pkcs7, Eme};
 
-use super::{constants::NAME_CIPHER_BLOCK_SIZE, errors::DecryptFilenameError};
+use super::{
+    base32768,
+    constants::{NAME_CIPHER_BLOCK_SIZE, NAME_KEY_LEN},
+    errors::{DecryptFilenameError, ParseNameOptionError},
+};
 
 type Aes256Eme = Eme<Aes256, Pkcs7>;
 
-pub fn get_name_cipher(name_key: &[u8], name_tweak: &[u8]) -> Aes256Eme {
-    Aes256Eme::new_from_slices(&name_key, &name_tweak).unwrap()
+/// Suffix of file names with FilenameEncryption::Off. Same as rclone.
+pub const ENCRYPTED_SUFFIX: &str = ".bin";
+
+const OBFUSCATE_QUOTE: char = '!';
+
+/// rclone crypt filename_encryption option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilenameEncryption {
+    #[default]
+    Standard,
+    Obfuscate,
+    Off,
 }
 
-pub fn encrypt_filename(name_cipher: Aes256Eme, plaintext: &str) -> String {
-    let plaintext_bytes = plaintext.as_bytes();
+impl FilenameEncryption {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Obfuscate => "obfuscate",
+            Self::Off => "off",
+        }
+    }
+}
 
-    let pos = plaintext_bytes.len();
-    let padding = NAME_CIPHER_BLOCK_SIZE - (pos % NAME_CIPHER_BLOCK_SIZE);
+impl FromStr for FilenameEncryption {
+    type Err = ParseNameOptionError;
 
-    let mut buffer = vec![0; pos + padding];
-    buffer[..pos].copy_from_slice(plaintext_bytes);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "obfuscate" => Ok(Self::Obfuscate),
+            "off" => Ok(Self::Off),
+            _ => Err(ParseNameOptionError {
+                name: "filename_encryption",
+                value: s.to_owned(),
+            }),
+        }
+    }
+}
 
-    let encrypted = name_cipher.encrypt(&mut buffer, pos).unwrap();
+impl fmt::Display for FilenameEncryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
-    BASE32HEX_NOPAD.encode(&encrypted).to_lowercase()
+/// rclone crypt filename_encoding option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilenameEncoding {
+    #[default]
+    Base32,
+    Base64,
+    Base32768,
 }
 
-pub fn encrypt_path(name_cipher: Aes256Eme, plaintext: &str) -> String {
-    match plaintext {
-        "/" => plaintext.to_owned(),
-        _ => {
-            let parts: Vec<&str> = plaintext.split("/").skip(1).collect();
-            let mut encrypted_parts: Vec<String> = Vec::with_capacity(parts.len() + 1);
-            encrypted_parts.push(String::from(""));
-            for part in parts {
-                let encrypted_part = encrypt_filename(name_cipher.clone(), &part);
-                encrypted_parts.push(encrypted_part);
-            }
-            encrypted_parts.join("/")
+impl FilenameEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Base32 => "base32",
+            Self::Base64 => "base64",
+            Self::Base32768 => "base32768",
+        }
+    }
+
+    fn encode(&self, data: &[u8]) -> String {
+        match self {
+            Self::Base32 => BASE32HEX_NOPAD.encode(data).to_lowercase(),
+            Self::Base64 => BASE64URL_NOPAD.encode(data),
+            Self::Base32768 => base32768::encode(data),
+        }
+    }
+
+    fn decode(&self, encoded: &str) -> Result<Vec<u8>, DecryptFilenameError> {
+        match self {
+            Self::Base32 => BASE32HEX_NOPAD
+                .decode(encoded.to_uppercase().as_bytes())
+                .map_err(|e| DecryptFilenameError::DecodeError(e.to_string())),
+            Self::Base64 => BASE64URL_NOPAD
+                .decode(encoded.as_bytes())
+                .map_err(|e| DecryptFilenameError::DecodeError(e.to_string())),
+            Self::Base32768 => base32768::decode(encoded),
         }
     }
 }
 
-pub fn decrypt_filename(
-    name_cipher: Aes256Eme,
-    ciphertext: &str,
-) -> Result<String, DecryptFilenameError> {
-    if ciphertext.is_empty() {
-        return Ok(String::from(""));
+impl FromStr for FilenameEncoding {
+    type Err = ParseNameOptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base32" => Ok(Self::Base32),
+            "base64" => Ok(Self::Base64),
+            "base32768" => Ok(Self::Base32768),
+            _ => Err(ParseNameOptionError {
+                name: "filename_encoding",
+                value: s.to_owned(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for FilenameEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How names are encrypted. The default is what Vault uses for new repos and
+/// what rclone uses if the options are not set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameCipherOptions {
+    pub filename_encryption: FilenameEncryption,
+    pub directory_name_encryption: bool,
+    pub filename_encoding: FilenameEncoding,
+}
+
+impl NameCipherOptions {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
     }
 
-    let mut name_encrypted_buf = BASE32HEX_NOPAD
-        .decode(ciphertext.to_uppercase().as_bytes())
-        .map_err(|e| DecryptFilenameError::DecodeError(e.to_string()))?;
+    /// Directory names are left as they are if directory name encryption is
+    /// disabled or if file names are not encrypted.
+    pub fn encrypts_dir_names(&self) -> bool {
+        self.directory_name_encryption && self.filename_encryption != FilenameEncryption::Off
+    }
+}
 
-    let decrypted = name_cipher
-        .decrypt(name_encrypted_buf.as_mut_slice().into())
-        .map_err(|_| DecryptFilenameError::DecryptError)?;
+impl Default for NameCipherOptions {
+    fn default() -> Self {
+        Self {
+            filename_encryption: FilenameEncryption::Standard,
+            directory_name_encryption: true,
+            filename_encoding: FilenameEncoding::Base32,
+        }
+    }
+}
 
-    String::from_utf8(decrypted.to_vec())
-        .map_err(|e| DecryptFilenameError::UnicodeError(e.to_string()))
+pub fn get_name_cipher(name_key: &[u8], name_tweak: &[u8]) -> Aes256Eme {
+    Aes256Eme::new_from_slices(name_key, name_tweak).unwrap()
 }
 
-pub fn decrypt_path(
+/// NameCipher encrypts file and dir names and paths the same way as rclone
+/// crypt. Paths are absolute and use / as the separator. A file path is a
+/// path of dirs with a file name as the last segment.
+#[derive(Clone)]
+pub struct NameCipher {
     name_cipher: Aes256Eme,
-    ciphertext: &str,
-) -> Result<String, DecryptFilenameError> {
-    match ciphertext {
-        "/" => Ok(ciphertext.to_owned()),
-        _ => {
-            let parts: Vec<&str> = ciphertext.split("/").skip(1).collect();
-            let mut decrypted_parts: Vec<String> = Vec::with_capacity(parts.len() + 1);
-            decrypted_parts.push(String::from(""));
-            for part in parts {
-                let decrypted_part = decrypt_filename(name_cipher.clone(), &part)?;
-                decrypted_parts.push(decrypted_part);
+    name_key: [u8; NAME_KEY_LEN],
+    options: NameCipherOptions,
+}
+
+impl NameCipher {
+    pub fn new(
+        name_key: [u8; NAME_KEY_LEN],
+        name_tweak: [u8; NAME_CIPHER_BLOCK_SIZE],
+        options: NameCipherOptions,
+    ) -> Self {
+        Self {
+            name_cipher: get_name_cipher(&name_key, &name_tweak),
+            name_key,
+            options,
+        }
+    }
+
+    pub fn options(&self) -> &NameCipherOptions {
+        &self.options
+    }
+
+    pub fn encrypt_filename(&self, plaintext: &str) -> String {
+        match self.options.filename_encryption {
+            FilenameEncryption::Off => format!("{}{}", plaintext, ENCRYPTED_SUFFIX),
+            _ => self.encrypt_segment(plaintext),
+        }
+    }
+
+    pub fn encrypt_dir_name(&self, plaintext: &str) -> String {
+        if self.options.encrypts_dir_names() {
+            self.encrypt_segment(plaintext)
+        } else {
+            plaintext.to_owned()
+        }
+    }
+
+    pub fn encrypt_path(&self, plaintext: &str) -> String {
+        self.map_path(plaintext, |name, is_last| {
+            Ok::<_, Infallible>(if is_last {
+                self.encrypt_filename(name)
+            } else {
+                self.encrypt_dir_name(name)
+            })
+        })
+        .unwrap()
+    }
+
+    pub fn encrypt_dir_path(&self, plaintext: &str) -> String {
+        self.map_path(plaintext, |name, _| {
+            Ok::<_, Infallible>(self.encrypt_dir_name(name))
+        })
+        .unwrap()
+    }
+
+    pub fn decrypt_filename(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        match self.options.filename_encryption {
+            FilenameEncryption::Off => match ciphertext.strip_suffix(ENCRYPTED_SUFFIX) {
+                Some(name) if !name.is_empty() => Ok(name.to_owned()),
+                _ => Err(DecryptFilenameError::NotEncryptedName),
+            },
+            _ => self.decrypt_segment(ciphertext),
+        }
+    }
+
+    pub fn decrypt_dir_name(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        if self.options.encrypts_dir_names() {
+            self.decrypt_segment(ciphertext)
+        } else {
+            Ok(ciphertext.to_owned())
+        }
+    }
+
+    pub fn decrypt_path(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        self.map_path(ciphertext, |name, is_last| {
+            if is_last {
+                self.decrypt_filename(name)
+            } else {
+                self.decrypt_dir_name(name)
+            }
+        })
+    }
+
+    pub fn decrypt_dir_path(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        self.map_path(ciphertext, |name, _| self.decrypt_dir_name(name))
+    }
+
+    fn map_path<E>(
+        &self,
+        path: &str,
+        f: impl Fn(&str, bool) -> Result<String, E>,
+    ) -> Result<String, E> {
+        match path {
+            "/" => Ok(path.to_owned()),
+            _ => {
+                let parts: Vec<&str> = path.split('/').skip(1).collect();
+                let mut mapped_parts: Vec<String> = Vec::with_capacity(parts.len() + 1);
+                mapped_parts.push(String::from(""));
+                for (i, part) in parts.iter().enumerate() {
+                    mapped_parts.push(f(part, i == parts.len() - 1)?);
+                }
+                Ok(mapped_parts.join("/"))
+            }
+        }
+    }
+
+    fn encrypt_segment(&self, plaintext: &str) -> String {
+        match self.options.filename_encryption {
+            FilenameEncryption::Standard => self.encrypt_standard(plaintext),
+            FilenameEncryption::Obfuscate => self.obfuscate(plaintext),
+            FilenameEncryption::Off => plaintext.to_owned(),
+        }
+    }
+
+    fn decrypt_segment(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        match self.options.filename_encryption {
+            FilenameEncryption::Standard => self.decrypt_standard(ciphertext),
+            FilenameEncryption::Obfuscate => self.deobfuscate(ciphertext),
+            FilenameEncryption::Off => Ok(ciphertext.to_owned()),
+        }
+    }
+
+    fn encrypt_standard(&self, plaintext: &str) -> String {
+        let plaintext_bytes = plaintext.as_bytes();
+
+        let pos = plaintext_bytes.len();
+        let padding = NAME_CIPHER_BLOCK_SIZE - (pos % NAME_CIPHER_BLOCK_SIZE);
+
+        let mut buffer = vec![0; pos + padding];
+        buffer[..pos].copy_from_slice(plaintext_bytes);
+
+        let encrypted = self.name_cipher.clone().encrypt(&mut buffer, pos).unwrap();
+
+        self.options.filename_encoding.encode(encrypted)
+    }
+
+    fn decrypt_standard(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        if ciphertext.is_empty() {
+            return Ok(String::from(""));
+        }
+
+        let mut name_encrypted_buf = self.options.filename_encoding.decode(ciphertext)?;
+
+        let decrypted = self
+            .name_cipher
+            .clone()
+            .decrypt(name_encrypted_buf.as_mut_slice())
+            .map_err(|_| DecryptFilenameError::DecryptError)?;
+
+        String::from_utf8(decrypted.to_vec())
+            .map_err(|e| DecryptFilenameError::UnicodeError(e.to_string()))
+    }
+
+    fn name_key_sum(&self) -> i64 {
+        self.name_key.iter().map(|b| *b as i64).sum()
+    }
+
+    /// Based on rclone implementation
+    /// https://github.com/rclone/rclone/blob/v1.65.0/backend/crypt/cipher.go#L327
+    fn obfuscate(&self, plaintext: &str) -> String {
+        if plaintext.is_empty() {
+            return String::new();
+        }
+
+        let mut dir: i64 = plaintext.chars().map(|c| c as i64).sum::<i64>() % 256;
+
+        let mut result = format!("{}.", dir);
+
+        dir += self.name_key_sum();
+
+        for c in plaintext.chars() {
+            let value = c as i64;
+
+            match c {
+                OBFUSCATE_QUOTE => {
+                    result.push(OBFUSCATE_QUOTE);
+                    result.push(OBFUSCATE_QUOTE);
+                }
+                '0'..='9' => {
+                    let thisdir = (dir % 9) + 1;
+                    let new_value = '0' as i64 + (value - '0' as i64 + thisdir) % 10;
+                    result.push(char::from_u32(new_value as u32).unwrap());
+                }
+                'A'..='Z' | 'a'..='z' => {
+                    let thisdir = dir % 25 + 1;
+                    let mut pos = value - 'A' as i64;
+                    if pos >= 26 {
+                        pos -= 6;
+                    }
+                    pos = (pos + thisdir) % 52;
+                    if pos >= 26 {
+                        pos += 6;
+                    }
+                    result.push(char::from_u32(('A' as i64 + pos) as u32).unwrap());
+                }
+                '\u{A0}'..='\u{FF}' => {
+                    let thisdir = (dir % 95) + 1;
+                    let new_value = 0xA0 + (value - 0xA0 + thisdir) % 96;
+                    result.push(char::from_u32(new_value as u32).unwrap());
+                }
+                _ if value >= 0x100 => {
+                    let thisdir = (dir % 127) + 1;
+                    let base = value - value % 256;
+                    let new_value = base + (value - base + thisdir) % 256;
+                    match char::from_u32(new_value as u32) {
+                        Some(new_c) => result.push(new_c),
+                        None => {
+                            // not a valid char (surrogate), quote it instead
+                            result.push(OBFUSCATE_QUOTE);
+                            result.push(c);
+                        }
+                    }
+                }
+                _ => result.push(c),
             }
-            Ok(decrypted_parts.join("/"))
         }
+
+        result
+    }
+
+    fn deobfuscate(&self, ciphertext: &str) -> Result<String, DecryptFilenameError> {
+        if ciphertext.is_empty() {
+            return Ok(String::new());
+        }
+
+        let (num, rest) = ciphertext
+            .split_once('.')
+            .ok_or(DecryptFilenameError::NotEncryptedName)?;
+
+        if num == "!" {
+            return Ok(rest.to_owned());
+        }
+
+        let mut dir: i64 = num
+            .parse()
+            .map_err(|_| DecryptFilenameError::NotEncryptedName)?;
+
+        dir += self.name_key_sum();
+
+        let mut result = String::with_capacity(rest.len());
+        let mut in_quote = false;
+
+        for c in rest.chars() {
+            let value = c as i64;
+
+            let new_value = match c {
+                _ if in_quote => {
+                    in_quote = false;
+                    value
+                }
+                OBFUSCATE_QUOTE => {
+                    in_quote = true;
+                    continue;
+                }
+                '0'..='9' => {
+                    let thisdir = (dir % 9) + 1;
+                    let mut new_value = '0' as i64 + value - '0' as i64 - thisdir;
+                    if new_value < '0' as i64 {
+                        new_value += 10;
+                    }
+                    new_value
+                }
+                'A'..='Z' | 'a'..='z' => {
+                    let thisdir = dir % 25 + 1;
+                    let mut pos = value - 'A' as i64;
+                    if pos >= 26 {
+                        pos -= 6;
+                    }
+                    pos -= thisdir;
+                    if pos < 0 {
+                        pos += 52;
+                    }
+                    if pos >= 26 {
+                        pos += 6;
+                    }
+                    'A' as i64 + pos
+                }
+                '\u{A0}'..='\u{FF}' => {
+                    let thisdir = (dir % 95) + 1;
+                    let mut new_value = 0xA0 + value - 0xA0 - thisdir;
+                    if new_value < 0xA0 {
+                        new_value += 96;
+                    }
+                    new_value
+                }
+                _ if value >= 0x100 => {
+                    let thisdir = (dir % 127) + 1;
+                    let base = value - value % 256;
+                    let mut new_value = base + (value - base - thisdir);
+                    if new_value < base {
+                        new_value += 256;
+                    }
+                    new_value
+                }
+                _ => value,
+            };
+
+            result.push(
+                char::from_u32(new_value as u32).ok_or(DecryptFilenameError::NotEncryptedName)?,
+            );
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FilenameEncoding, FilenameEncryption, NameCipher, NameCipherOptions};
+
+    fn name_cipher(options: NameCipherOptions) -> NameCipher {
+        NameCipher::new([1; 32], [2; 16], options)
+    }
+
+    #[test]
+    fn test_options_from_str() {
+        assert_eq!(
+            "obfuscate".parse::<FilenameEncryption>().unwrap(),
+            FilenameEncryption::Obfuscate
+        );
+        assert_eq!(
+            "base32768".parse::<FilenameEncoding>().unwrap(),
+            FilenameEncoding::Base32768
+        );
+        assert_eq!(
+            "rot13"
+                .parse::<FilenameEncryption>()
+                .unwrap_err()
+                .to_string(),
+            "invalid filename_encryption value: rot13"
+        );
+    }
+
+    #[test]
+    fn test_encodings_roundtrip() {
+        for filename_encoding in [
+            FilenameEncoding::Base32,
+            FilenameEncoding::Base64,
+            FilenameEncoding::Base32768,
+        ] {
+            let cipher = name_cipher(NameCipherOptions {
+                filename_encoding,
+                ..Default::default()
+            });
+
+            for name in ["a", "file.txt", "čšž ünicode 文件 😀", &"x".repeat(200)] {
+                let encrypted = cipher.encrypt_filename(name);
+
+                assert_ne!(encrypted, name);
+                assert_eq!(cipher.decrypt_filename(&encrypted).unwrap(), name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_base64_encoding() {
+        let base32 = name_cipher(NameCipherOptions::default());
+        let base64 = name_cipher(NameCipherOptions {
+            filename_encoding: FilenameEncoding::Base64,
+            ..Default::default()
+        });
+
+        let encrypted = data_encoding::BASE32HEX_NOPAD
+            .decode(
+                base32
+                    .encrypt_filename("file.txt")
+                    .to_uppercase()
+                    .as_bytes(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            base64.encrypt_filename("file.txt"),
+            data_encoding::BASE64URL_NOPAD.encode(&encrypted)
+        );
+    }
+
+    #[test]
+    fn test_obfuscate() {
+        let cipher = name_cipher(NameCipherOptions {
+            filename_encryption: FilenameEncryption::Obfuscate,
+            ..Default::default()
+        });
+
+        // sum of "file.txt" code points % 256 is 46, "." is not rotated
+        let encrypted = cipher.encrypt_filename("file.txt");
+        assert!(encrypted.starts_with("46."));
+        assert_eq!(encrypted.len(), "46.file.txt".len());
+        assert_eq!(&encrypted[7..8], ".");
+        assert_eq!(cipher.decrypt_filename(&encrypted).unwrap(), "file.txt");
+
+        for name in [
+            "Quote!me",
+            "0123456789",
+            "ABCXYZabcxyz",
+            "àéîõü ÿ",
+            "čšž 文件 😀",
+            "\u{d7ff}\u{e000}",
+        ] {
+            let encrypted = cipher.encrypt_filename(name);
+
+            assert_eq!(cipher.decrypt_filename(&encrypted).unwrap(), name);
+        }
+
+        assert_eq!(
+            cipher.decrypt_filename("!.not rotated").unwrap(),
+            "not rotated"
+        );
+        assert!(cipher.decrypt_filename("nodot").is_err());
+        assert!(cipher.decrypt_filename("x.notanumber").is_err());
+    }
+
+    #[test]
+    fn test_filename_encryption_off() {
+        let cipher = name_cipher(NameCipherOptions {
+            filename_encryption: FilenameEncryption::Off,
+            ..Default::default()
+        });
+
+        assert_eq!(cipher.encrypt_filename("file.txt"), "file.txt.bin");
+        assert_eq!(cipher.encrypt_dir_name("dir"), "dir");
+        assert_eq!(cipher.encrypt_path("/dir/file.txt"), "/dir/file.txt.bin");
+        assert_eq!(cipher.encrypt_dir_path("/dir/sub"), "/dir/sub");
+
+        assert_eq!(cipher.decrypt_filename("file.txt.bin").unwrap(), "file.txt");
+        assert!(cipher.decrypt_filename("file.txt").is_err());
+        assert!(cipher.decrypt_filename(".bin").is_err());
+        assert_eq!(
+            cipher.decrypt_path("/dir/file.txt.bin").unwrap(),
+            "/dir/file.txt"
+        );
+    }
+
+    #[test]
+    fn test_directory_name_encryption_disabled() {
+        let cipher = name_cipher(NameCipherOptions {
+            directory_name_encryption: false,
+            ..Default::default()
+        });
+        let encrypted_name = cipher.encrypt_filename("file.txt");
+
+        assert_eq!(cipher.encrypt_dir_name("dir"), "dir");
+        assert_eq!(
+            cipher.encrypt_path("/dir/sub/file.txt"),
+            format!("/dir/sub/{}", encrypted_name)
+        );
+        assert_eq!(cipher.encrypt_dir_path("/dir/sub"), "/dir/sub");
+        assert_eq!(
+            cipher
+                .decrypt_path(&format!("/dir/sub/{}", encrypted_name))
+                .unwrap(),
+            "/dir/sub/file.txt"
+        );
+        assert_eq!(cipher.decrypt_dir_path("/dir/sub").unwrap(), "/dir/sub");
+    }
+
+    #[test]
+    fn test_default_dir_names_same_as_file_names() {
+        let cipher = name_cipher(NameCipherOptions::default());
+
+        assert_eq!(
+            cipher.encrypt_dir_name("name"),
+            cipher.encrypt_filename("name")
+        );
+        assert_eq!(cipher.encrypt_dir_path("/a/b"), cipher.encrypt_path("/a/b"));
     }
 }
@@ -18,7 +18,7 @@ use crate::{
 };
 
 pub async fn app(
-    port: u16,
+    addr: SocketAddr,
     web_vault: WebVaultBase,
     tokio_runtime: Arc<tokio::runtime::Runtime>,
     encryption: Arc<Encryption>,
@@ -58,7 +58,6 @@ pub async fn app(
     )
     .layer(app);
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], port));
     println!("Backend is listening on http://{}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app.into_make_service())
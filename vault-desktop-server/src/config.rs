@@ -0,0 +1,288 @@
+use std::{fs, net::SocketAddr, path::PathBuf};
+
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use thiserror::Error;
+
+pub const DEFAULT_BASE_URL: &str = "https://app.koofr.net";
+pub const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:1421";
+pub const DEFAULT_OAUTH2_CLIENT_ID: &str = "7ZEK2BNCEVYEJIZC5OR3TR6PQDUJ4NP3";
+pub const DEFAULT_OAUTH2_CLIENT_SECRET: &str =
+    "VWTMENEWUYWH6G523CEV5CWOCHH7FMECW36PPQENOASYYZOQJOSGQXSR2Y62N3HB";
+pub const DEFAULT_USER_AGENT: &str = "vault-desktop";
+
+/// Where the login token and other app data are stored.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecureStorageKind {
+    #[default]
+    Keyring,
+    /// Encrypted file in the data path
+    File,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {error}")]
+    ReadError { path: PathBuf, error: String },
+    #[error("failed to parse config file {path}: {error}")]
+    ParseError { path: PathBuf, error: String },
+}
+
+/// Command-line arguments. Every option can also be set with an env var or in
+/// the config file. Arguments take precedence over env vars and env vars over
+/// the config file.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(author, version, about = "Koofr Vault desktop server", long_about = None)]
+pub struct Args {
+    /// JSON config file with the same options as the arguments (e.g.
+    /// {"base_url": "http://127.0.0.1:3080"})
+    #[arg(long, env = "VAULT_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Koofr base URL (default https://app.koofr.net)
+    #[arg(long, env = "VAULT_BASE_URL")]
+    pub base_url: Option<String>,
+
+    /// OAuth2 auth base URL (default is the base URL)
+    #[arg(long, env = "VAULT_OAUTH2_AUTH_BASE_URL")]
+    pub oauth2_auth_base_url: Option<String>,
+
+    /// HTTP server listen addr (default 127.0.0.1:1421)
+    #[arg(long, env = "VAULT_LISTEN_ADDR")]
+    pub listen_addr: Option<SocketAddr>,
+
+    /// OAuth2 client ID
+    #[arg(long, env = "VAULT_OAUTH2_CLIENT_ID")]
+    pub oauth2_client_id: Option<String>,
+
+    /// OAuth2 client secret
+    #[arg(long, env = "VAULT_OAUTH2_CLIENT_SECRET", hide_env_values = true)]
+    pub oauth2_client_secret: Option<String>,
+
+    /// OAuth2 redirect URI (default http://<listen addr>/oauth2callback)
+    #[arg(long, env = "VAULT_OAUTH2_REDIRECT_URI")]
+    pub oauth2_redirect_uri: Option<String>,
+
+    /// HTTP user agent (default vault-desktop)
+    #[arg(long, env = "VAULT_USER_AGENT")]
+    pub user_agent: Option<String>,
+
+    /// Data path for the app data (default ~/.koofr-vault)
+    #[arg(long, env = "VAULT_DATA_PATH")]
+    pub data_path: Option<PathBuf>,
+
+    /// Secure storage for the app data (default keyring)
+    #[arg(long, env = "VAULT_SECURE_STORAGE")]
+    pub secure_storage: Option<SecureStorageKind>,
+
+    /// Protects the file secure storage with a passphrase instead of a key
+    /// from the OS keyring
+    #[arg(long, env = "VAULT_SECURE_STORAGE_PASSPHRASE", hide_env_values = true)]
+    pub secure_storage_passphrase: Option<String>,
+
+    /// Exposes unlocked repos over WebDAV at http://127.0.0.1:<port>/
+    #[arg(long, env = "VAULT_WEBDAV_PORT")]
+    pub webdav_port: Option<u16>,
+
+    /// Stores repos and files in a local directory (or a NAS mount) instead
+    /// of Koofr. No Koofr account or login is needed.
+    #[arg(long, env = "VAULT_LOCAL_STORAGE_PATH")]
+    pub local_storage_path: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub base_url: Option<String>,
+    pub oauth2_auth_base_url: Option<String>,
+    pub listen_addr: Option<SocketAddr>,
+    pub oauth2_client_id: Option<String>,
+    pub oauth2_client_secret: Option<String>,
+    pub oauth2_redirect_uri: Option<String>,
+    pub user_agent: Option<String>,
+    pub data_path: Option<PathBuf>,
+    pub secure_storage: Option<SecureStorageKind>,
+    pub secure_storage_passphrase: Option<String>,
+    pub webdav_port: Option<u16>,
+    pub local_storage_path: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &PathBuf) -> Result<Self, ConfigError> {
+        let config_json = fs::read_to_string(path).map_err(|err| ConfigError::ReadError {
+            path: path.clone(),
+            error: err.to_string(),
+        })?;
+
+        serde_json::from_str(&config_json).map_err(|err| ConfigError::ParseError {
+            path: path.clone(),
+            error: err.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub base_url: String,
+    pub oauth2_auth_base_url: String,
+    pub listen_addr: SocketAddr,
+    pub oauth2_client_id: String,
+    pub oauth2_client_secret: String,
+    pub oauth2_redirect_uri: String,
+    pub user_agent: String,
+    /// None means the default data path for the app ID
+    pub data_path: Option<PathBuf>,
+    pub secure_storage: SecureStorageKind,
+    pub secure_storage_passphrase: Option<String>,
+    /// None means WebDAV is disabled
+    pub webdav_port: Option<u16>,
+    /// None means repos and files are stored in Koofr
+    pub local_storage_path: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn load_from_args() -> Result<Self, ConfigError> {
+        Self::load(Args::parse())
+    }
+
+    pub fn load(args: Args) -> Result<Self, ConfigError> {
+        let file = match &args.config {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+
+        Ok(Self::resolve(args, file))
+    }
+
+    pub fn resolve(args: Args, file: ConfigFile) -> Self {
+        let base_url = args
+            .base_url
+            .or(file.base_url)
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_owned());
+        let oauth2_auth_base_url = args
+            .oauth2_auth_base_url
+            .or(file.oauth2_auth_base_url)
+            .unwrap_or_else(|| base_url.clone());
+        let listen_addr = args
+            .listen_addr
+            .or(file.listen_addr)
+            .unwrap_or_else(|| DEFAULT_LISTEN_ADDR.parse().unwrap());
+        let oauth2_redirect_uri = args
+            .oauth2_redirect_uri
+            .or(file.oauth2_redirect_uri)
+            .unwrap_or_else(|| format!("http://{}/oauth2callback", listen_addr));
+
+        Self {
+            base_url,
+            oauth2_auth_base_url,
+            listen_addr,
+            oauth2_client_id: args
+                .oauth2_client_id
+                .or(file.oauth2_client_id)
+                .unwrap_or_else(|| DEFAULT_OAUTH2_CLIENT_ID.to_owned()),
+            oauth2_client_secret: args
+                .oauth2_client_secret
+                .or(file.oauth2_client_secret)
+                .unwrap_or_else(|| DEFAULT_OAUTH2_CLIENT_SECRET.to_owned()),
+            oauth2_redirect_uri,
+            user_agent: args
+                .user_agent
+                .or(file.user_agent)
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_owned()),
+            data_path: args.data_path.or(file.data_path),
+            secure_storage: args
+                .secure_storage
+                .or(file.secure_storage)
+                .unwrap_or_default(),
+            secure_storage_passphrase: args
+                .secure_storage_passphrase
+                .or(file.secure_storage_passphrase)
+                .filter(|passphrase| !passphrase.is_empty()),
+            webdav_port: args.webdav_port.or(file.webdav_port),
+            local_storage_path: args.local_storage_path.or(file.local_storage_path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use similar_asserts::assert_eq;
+
+    use super::{
+        Args, Config, ConfigFile, SecureStorageKind, DEFAULT_OAUTH2_CLIENT_ID, DEFAULT_USER_AGENT,
+    };
+
+    #[test]
+    fn test_resolve_defaults() {
+        let config = Config::resolve(Args::default(), ConfigFile::default());
+
+        assert_eq!(config.base_url, "https://app.koofr.net");
+        assert_eq!(config.oauth2_auth_base_url, "https://app.koofr.net");
+        assert_eq!(config.listen_addr.to_string(), "127.0.0.1:1421");
+        assert_eq!(config.oauth2_client_id, DEFAULT_OAUTH2_CLIENT_ID);
+        assert_eq!(
+            config.oauth2_redirect_uri,
+            "http://127.0.0.1:1421/oauth2callback"
+        );
+        assert_eq!(config.user_agent, DEFAULT_USER_AGENT);
+        assert_eq!(config.data_path, None);
+        assert_eq!(config.secure_storage, SecureStorageKind::Keyring);
+        assert_eq!(config.secure_storage_passphrase, None);
+        assert_eq!(config.webdav_port, None);
+        assert_eq!(config.local_storage_path, None);
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let file: ConfigFile = serde_json::from_str(
+            r#"{
+                "base_url": "http://127.0.0.1:3080",
+                "listen_addr": "127.0.0.1:2421",
+                "oauth2_client_id": "file-client",
+                "data_path": "/tmp/vault-file",
+                "secure_storage": "file",
+                "secure_storage_passphrase": "file-passphrase",
+                "webdav_port": 1422,
+                "local_storage_path": "/tmp/vault-file-storage"
+            }"#,
+        )
+        .unwrap();
+
+        let args = Args {
+            oauth2_client_id: Some(String::from("args-client")),
+            secure_storage_passphrase: Some(String::from("args-passphrase")),
+            local_storage_path: Some(PathBuf::from("/tmp/vault-args-storage")),
+            ..Default::default()
+        };
+
+        let config = Config::resolve(args, file);
+
+        assert_eq!(config.base_url, "http://127.0.0.1:3080");
+        assert_eq!(config.oauth2_auth_base_url, "http://127.0.0.1:3080");
+        assert_eq!(config.listen_addr.to_string(), "127.0.0.1:2421");
+        assert_eq!(config.oauth2_client_id, "args-client");
+        assert_eq!(
+            config.oauth2_redirect_uri,
+            "http://127.0.0.1:2421/oauth2callback"
+        );
+        assert_eq!(config.data_path, Some(PathBuf::from("/tmp/vault-file")));
+        assert_eq!(config.secure_storage, SecureStorageKind::File);
+        assert_eq!(
+            config.secure_storage_passphrase,
+            Some(String::from("args-passphrase"))
+        );
+        assert_eq!(config.webdav_port, Some(1422));
+        assert_eq!(
+            config.local_storage_path,
+            Some(PathBuf::from("/tmp/vault-args-storage"))
+        );
+    }
+
+    #[test]
+    fn test_config_file_unknown_field() {
+        assert!(serde_json::from_str::<ConfigFile>(r#"{"port": 1421}"#).is_err());
+    }
+}
@@ -22,6 +22,10 @@ pub fn get_data_path(app_id: &str) -> Result<PathBuf, GetDataPathError> {
         project_dirs.data_dir().to_path_buf()
     };
 
+    ensure_data_path(data_dir)
+}
+
+pub fn ensure_data_path(data_dir: PathBuf) -> Result<PathBuf, GetDataPathError> {
     std::fs::create_dir_all(&data_dir)
         .map_err(|err| GetDataPathError(format!("failed to ensure data dir: {}", err)))?;
 
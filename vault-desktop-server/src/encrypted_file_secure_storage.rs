@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use rand_core::{OsRng, RngCore};
+use vault_core::secure_storage::{MemorySecureStorage, SecureStorage};
+use vault_crypto::{cipher_keys::derive_keys, constants::DATA_KEY_LEN};
+
+use crate::{encryption::Encryption, file_secure_storage::FileSecureStorage};
+
+pub fn get_encrypted_file_secure_storage_path(data_path: &Path) -> PathBuf {
+    data_path.join("storage.enc")
+}
+
+pub fn get_encrypted_file_secure_storage_salt_path(data_path: &Path) -> PathBuf {
+    data_path.join("storage.salt")
+}
+
+/// Loads the machine-local passphrase salt or generates a new one if it does
+/// not exist yet.
+pub fn load_or_create_local_salt(path: &Path) -> Result<String, String> {
+    match fs::read_to_string(path) {
+        Ok(salt) if !salt.trim().is_empty() => return Ok(salt.trim().to_owned()),
+        Ok(_) => {}
+        Err(err) if matches!(err.kind(), io::ErrorKind::NotFound) => {}
+        Err(err) => return Err(format!("failed to read storage salt: {}", err)),
+    }
+
+    let salt = generate_key()?;
+
+    write_file_atomic(path, salt.as_bytes())
+        .map_err(|err| format!("failed to write storage salt: {}", err))?;
+
+    Ok(salt)
+}
+
+/// Derives the storage key from a user passphrase. The salt is not secret, the
+/// storage is only as strong as the passphrase.
+pub fn derive_storage_encryption(passphrase: &str, salt: &str) -> Encryption {
+    let keys = derive_keys(passphrase, Some(salt));
+
+    Encryption::new_with_key_bytes(&keys.data_key)
+}
+
+/// Loads a random storage key from the OS keyring or generates a new one if it
+/// does not exist yet. Only the key is kept in the keyring, the data is stored
+/// in the file.
+pub fn load_or_create_keyring_storage_encryption(service: &str) -> Result<Encryption, String> {
+    let entry = keyring::Entry::new(service, "storage-key").map_err(|err| err.to_string())?;
+
+    let key = match entry.get_password() {
+        Ok(key) => key,
+        Err(keyring::Error::NoEntry) => {
+            let key = generate_key()?;
+
+            entry
+                .set_password(&key)
+                .map_err(|err| format!("failed to save storage key: {}", err))?;
+
+            key
+        }
+        Err(err) => return Err(format!("failed to load storage key: {}", err)),
+    };
+
+    Encryption::new_with_key_str(&key).map_err(|err| format!("invalid storage key: {}", err))
+}
+
+fn generate_key() -> Result<String, String> {
+    let mut key: [u8; DATA_KEY_LEN] = [0; DATA_KEY_LEN];
+
+    OsRng
+        .try_fill_bytes(&mut key)
+        .map_err(|err| format!("failed to generate storage key: {}", err))?;
+
+    Ok(data_encoding::BASE64.encode(&key))
+}
+
+/// Same as FileSecureStorage but the JSON data is encrypted. A plaintext
+/// storage file from FileSecureStorage is migrated on first load and removed.
+pub struct EncryptedFileSecureStorage {
+    path: PathBuf,
+    encryption: Encryption,
+    storage: MemorySecureStorage,
+}
+
+impl EncryptedFileSecureStorage {
+    pub fn new(
+        path: PathBuf,
+        plaintext_path: Option<PathBuf>,
+        encryption: Encryption,
+    ) -> Result<Self, String> {
+        let data = match Self::load_data(&path, &encryption)? {
+            Some(data) => data,
+            None => match plaintext_path {
+                Some(plaintext_path) => Self::migrate(&path, &plaintext_path, &encryption)?,
+                None => HashMap::new(),
+            },
+        };
+
+        let storage = MemorySecureStorage::new_with_data(data);
+
+        Ok(Self {
+            path,
+            encryption,
+            storage,
+        })
+    }
+
+    fn migrate(
+        path: &Path,
+        plaintext_path: &Path,
+        encryption: &Encryption,
+    ) -> Result<HashMap<String, String>, String> {
+        if !plaintext_path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let data = FileSecureStorage::load_data(&plaintext_path.to_path_buf())?;
+
+        Self::save_data(path, encryption, &data)?;
+
+        fs::remove_file(plaintext_path)
+            .map_err(|err| format!("failed to remove plaintext storage: {}", err))?;
+
+        log::info!("Migrated plaintext storage to {:?}", path);
+
+        Ok(data)
+    }
+
+    pub fn load_data(
+        path: &Path,
+        encryption: &Encryption,
+    ) -> Result<Option<HashMap<String, String>>, String> {
+        let encrypted = match fs::read(path) {
+            Ok(encrypted) => encrypted,
+            Err(err) if matches!(err.kind(), io::ErrorKind::NotFound) => return Ok(None),
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let data_json = encryption
+            .decrypt(&encrypted)
+            .map_err(|err| format!("failed to decrypt storage: {}", err))?;
+
+        serde_json::from_slice(&data_json)
+            .map(Some)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn save_data(
+        path: &Path,
+        encryption: &Encryption,
+        data: &HashMap<String, String>,
+    ) -> Result<(), String> {
+        let data_json = serde_json::to_vec(&data).map_err(|err| err.to_string())?;
+
+        let encrypted = encryption
+            .encrypt(&data_json)
+            .map_err(|err| format!("failed to encrypt storage: {}", err))?;
+
+        write_file_atomic(path, encrypted.as_bytes()).map_err(|err| err.to_string())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let data = self.storage.get_data();
+
+        Self::save_data(&self.path, &self.encryption, &data)
+    }
+}
+
+impl std::fmt::Debug for EncryptedFileSecureStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedFileSecureStorage")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl SecureStorage for EncryptedFileSecureStorage {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        self.storage.get_item(key)
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String> {
+        self.storage.set_item(key, value).unwrap();
+
+        self.save()
+    }
+
+    fn remove_item(&self, key: &str) -> Result<(), String> {
+        self.storage.remove_item(key).unwrap();
+
+        self.save()
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.storage.clear().unwrap();
+
+        self.save()
+    }
+}
+
+/// Writes to a temporary file that is synced and then renamed so that an
+/// interrupted write never leaves a partial file behind. Files are only
+/// readable by the current user.
+fn write_file_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut temp_name = path.file_name().map(ToOwned::to_owned).unwrap_or_default();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&temp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp_path, path)?;
+
+    // sync the parent dir so that the rename is durable
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        fs::File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::PathBuf};
+
+    use similar_asserts::assert_eq;
+    use vault_core::secure_storage::SecureStorage;
+
+    use crate::{encryption::Encryption, file_secure_storage::FileSecureStorage};
+
+    use super::{derive_storage_encryption, load_or_create_local_salt, EncryptedFileSecureStorage};
+
+    fn temp_dir() -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("vault-secure-storage-{}", uuid::Uuid::new_v4()));
+
+        fs::create_dir_all(&path).unwrap();
+
+        path
+    }
+
+    fn encryption() -> Encryption {
+        Encryption::new_with_key_str("XrwBl00MUbeAZ4QBW2F+YDFBv80f2kes49VDx7wUs7Y=").unwrap()
+    }
+
+    #[test]
+    fn test_save_load() {
+        let dir = temp_dir();
+        let path = dir.join("storage.enc");
+
+        let storage = EncryptedFileSecureStorage::new(path.clone(), None, encryption()).unwrap();
+        storage.set_item("token", "secret token").unwrap();
+
+        assert!(!fs::read_to_string(&path).unwrap().contains("secret token"));
+        assert!(!dir.join("storage.enc.tmp").exists());
+
+        let storage = EncryptedFileSecureStorage::new(path.clone(), None, encryption()).unwrap();
+        assert_eq!(
+            storage.get_item("token").unwrap(),
+            Some(String::from("secret token"))
+        );
+
+        let other_encryption =
+            Encryption::new_with_key_str("Z0bSUUnh0ZFTzaDgLM6fGRMkrHNIpBj2+Bvjw9+jVnY=").unwrap();
+        assert!(EncryptedFileSecureStorage::new(path, None, other_encryption).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_plaintext() {
+        let dir = temp_dir();
+        let path = dir.join("storage.enc");
+        let plaintext_path = dir.join("storage.json");
+
+        FileSecureStorage::save_data(
+            &plaintext_path,
+            &HashMap::from([(String::from("token"), String::from("secret token"))]),
+        )
+        .unwrap();
+
+        let storage = EncryptedFileSecureStorage::new(
+            path.clone(),
+            Some(plaintext_path.clone()),
+            encryption(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            storage.get_item("token").unwrap(),
+            Some(String::from("secret token"))
+        );
+        assert!(!plaintext_path.exists());
+
+        let storage =
+            EncryptedFileSecureStorage::new(path, Some(plaintext_path), encryption()).unwrap();
+        assert_eq!(
+            storage.get_item("token").unwrap(),
+            Some(String::from("secret token"))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let dir = temp_dir();
+        let path = dir.join("storage.enc");
+        let salt_path = dir.join("storage.salt");
+
+        let salt = load_or_create_local_salt(&salt_path).unwrap();
+        assert_eq!(load_or_create_local_salt(&salt_path).unwrap(), salt);
+
+        let storage = EncryptedFileSecureStorage::new(
+            path.clone(),
+            None,
+            derive_storage_encryption("passphrase", &salt),
+        )
+        .unwrap();
+        storage.set_item("token", "secret token").unwrap();
+
+        let storage = EncryptedFileSecureStorage::new(
+            path.clone(),
+            None,
+            derive_storage_encryption("passphrase", &salt),
+        )
+        .unwrap();
+        assert_eq!(
+            storage.get_item("token").unwrap(),
+            Some(String::from("secret token"))
+        );
+
+        assert!(EncryptedFileSecureStorage::new(
+            path,
+            None,
+            derive_storage_encryption("other passphrase", &salt),
+        )
+        .is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -68,6 +68,10 @@ impl Encryption {
     pub fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>, EncryptionError> {
         let encrypted = data_encoding::BASE64.decode(encrypted)?;
 
+        if encrypted.len() < FILE_NONCE_SIZE {
+            return Err(EncryptionError::InvalidNonce);
+        }
+
         let nonce = encrypted[0..FILE_NONCE_SIZE]
             .try_into()
             .map_err(|_| EncryptionError::InvalidNonce)?;
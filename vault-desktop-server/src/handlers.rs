@@ -5,7 +5,7 @@ use axum::{
     extract::{Query, State},
     http::{
         header::{self, CONTENT_TYPE},
-        StatusCode,
+        HeaderMap, HeaderValue, StatusCode,
     },
     response::{sse::Event, IntoResponse, Response, Sse},
     routing::{get, post},
@@ -38,7 +38,7 @@ use crate::{
     app_state::AppState,
     callbacks::CallbackId,
     extract::{ExtractBase, ExtractCallbacks, ExtractSessions},
-    upload_helper,
+    http_range, upload_helper,
 };
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -241,6 +241,18 @@ pub fn register_routes(router: Router<AppState>) -> Router<AppState> {
             "/WebVault/repoSpaceUsageDestroy",
             post(repo_space_usage_destroy),
         )
+        .route("/WebVault/repoSearchCreate", post(repo_search_create))
+        .route(
+            "/WebVault/repoSearchInfoSubscribe",
+            post(repo_search_info_subscribe),
+        )
+        .route("/WebVault/repoSearchInfoData", post(repo_search_info_data))
+        .route(
+            "/WebVault/repoSearchLoadIndex",
+            post(repo_search_load_index),
+        )
+        .route("/WebVault/repoSearchSetQuery", post(repo_search_set_query))
+        .route("/WebVault/repoSearchDestroy", post(repo_search_destroy))
         .route(
             "/WebVault/repoFilesFileSubscribe",
             post(repo_files_file_subscribe),
@@ -283,6 +295,22 @@ pub fn register_routes(router: Router<AppState>) -> Router<AppState> {
         .route("/WebVault/transfersAbortAll", post(transfers_abort_all))
         .route("/WebVault/transfersRetry", post(transfers_retry))
         .route("/WebVault/transfersRetryAll", post(transfers_retry_all))
+        .route("/WebVault/transfersPause", post(transfers_pause))
+        .route("/WebVault/transfersPauseAll", post(transfers_pause_all))
+        .route("/WebVault/transfersResume", post(transfers_resume))
+        .route("/WebVault/transfersResumeAll", post(transfers_resume_all))
+        .route(
+            "/WebVault/transfersMoveToFront",
+            post(transfers_move_to_front),
+        )
+        .route(
+            "/WebVault/transfersSetBandwidthSettings",
+            post(transfers_set_bandwidth_settings),
+        )
+        .route(
+            "/WebVault/transfersSetVerifyUploads",
+            post(transfers_set_verify_uploads),
+        )
         .route("/WebVault/transfersOpen", post(transfers_open))
         .route(
             "/WebVault/dirPickersItemsSubscribe",
@@ -1008,6 +1036,48 @@ pub async fn repo_space_usage_destroy(
     base.repo_space_usage_destroy(usage_id);
 }
 
+// repo_search
+
+pub async fn repo_search_create(
+    ExtractBase(base): ExtractBase,
+    Json((repo_id,)): Json<(String,)>,
+) -> Json<u32> {
+    Json(base.repo_search_create(repo_id))
+}
+
+pub async fn repo_search_info_subscribe(
+    ExtractBase(base): ExtractBase,
+    ExtractCallbacks(callbacks): ExtractCallbacks,
+    Json((search_id, cb)): Json<(u32, CallbackId)>,
+) -> Json<u32> {
+    Json(base.repo_search_info_subscribe(search_id, callbacks.cb(cb)))
+}
+
+pub async fn repo_search_info_data(
+    ExtractBase(base): ExtractBase,
+    Json((id,)): Json<(u32,)>,
+) -> Json<Option<dto::RepoSearchInfo>> {
+    Json(base.repo_search_info_data(id))
+}
+
+pub async fn repo_search_load_index(
+    ExtractBase(base): ExtractBase,
+    Json((repo_id, include_content)): Json<(String, bool)>,
+) {
+    base.repo_search_load_index(repo_id, include_content);
+}
+
+pub async fn repo_search_set_query(
+    ExtractBase(base): ExtractBase,
+    Json((search_id, query)): Json<(u32, String)>,
+) {
+    base.repo_search_set_query(search_id, query);
+}
+
+pub async fn repo_search_destroy(ExtractBase(base): ExtractBase, Json((search_id,)): Json<(u32,)>) {
+    base.repo_search_destroy(search_id);
+}
+
 // repo_files
 
 pub async fn repo_files_file_subscribe(
@@ -1109,6 +1179,40 @@ pub async fn transfers_retry_all(ExtractBase(base): ExtractBase) {
     base.transfers_retry_all();
 }
 
+pub async fn transfers_pause(ExtractBase(base): ExtractBase, Json((id,)): Json<(u32,)>) {
+    base.transfers_pause(id);
+}
+
+pub async fn transfers_pause_all(ExtractBase(base): ExtractBase) {
+    base.transfers_pause_all();
+}
+
+pub async fn transfers_resume(ExtractBase(base): ExtractBase, Json((id,)): Json<(u32,)>) {
+    base.transfers_resume(id);
+}
+
+pub async fn transfers_resume_all(ExtractBase(base): ExtractBase) {
+    base.transfers_resume_all();
+}
+
+pub async fn transfers_move_to_front(ExtractBase(base): ExtractBase, Json((id,)): Json<(u32,)>) {
+    base.transfers_move_to_front(id);
+}
+
+pub async fn transfers_set_bandwidth_settings(
+    ExtractBase(base): ExtractBase,
+    Json((settings,)): Json<(dto::TransfersBandwidthSettings,)>,
+) {
+    base.transfers_set_bandwidth_settings(settings);
+}
+
+pub async fn transfers_set_verify_uploads(
+    ExtractBase(base): ExtractBase,
+    Json((verify_uploads,)): Json<(bool,)>,
+) {
+    base.transfers_set_verify_uploads(verify_uploads);
+}
+
 pub async fn transfers_open(ExtractBase(base): ExtractBase, Json((id,)): Json<(u32,)>) {
     base.transfers_open(id);
 }
@@ -1670,21 +1774,70 @@ pub async fn repo_files_details_get_file_stream(
     Query(RepoFilesDetailsGetFileStreamQuery { details_id }): Query<
         RepoFilesDetailsGetFileStreamQuery,
     >,
+    headers: HeaderMap,
 ) -> Result<Response, ApiError> {
-    let reader = match base
+    let provider = match base
         .vault
         .clone()
         .repo_files_details_get_file_reader(details_id)
         .await
     {
-        Ok(provider) => match provider.reader().await {
-            Ok(reader) => reader,
-            Err(err) => {
-                base.errors.handle_error(err.clone());
+        Ok(provider) => provider,
+        Err(err) => {
+            base.errors.handle_error(err.clone());
+
+            return Err(err.to_string().into());
+        }
+    };
+
+    let size = match provider.size {
+        SizeInfo::Exact(size) => Some(size),
+        _ => None,
+    };
+    let etag = provider.unique_name.as_deref().map(unique_name_etag);
+
+    let range = match http_range::get_request_range(&headers, size, etag.as_deref()) {
+        Some(Ok(range)) => range,
+        Some(Err(http_range::RangeNotSatisfiable)) => {
+            return Ok(range_not_satisfiable_response(size));
+        }
+        None => {
+            let reader = match provider.reader().await {
+                Ok(reader) => reader,
+                Err(err) => {
+                    base.errors.handle_error(err.clone());
+
+                    return Err(err.to_string().into());
+                }
+            };
+
+            let (_, file_reader) = base.vault.clone().transfers_download_reader(reader);
 
-                return Err(err.to_string().into());
+            let stream = ReaderStream::new(file_reader.reader, BLOCK_SIZE);
+
+            let mut res = Body::from_stream(stream).into_response();
+            *res.status_mut() = StatusCode::OK;
+            if let SizeInfo::Exact(size) = file_reader.size {
+                res.headers_mut()
+                    .insert(header::CONTENT_LENGTH, size.into());
             }
-        },
+            insert_file_stream_headers(&mut res, file_reader.content_type, etag);
+
+            return Ok(res);
+        }
+    };
+
+    // ranged reads are not added to transfers, video players send a new range
+    // request for every seek
+    let reader = match base
+        .vault
+        .repo_files_details_get_file_reader_range(details_id, &range)
+        .await
+    {
+        Ok(reader) => reader,
+        Err(repo_files_read::errors::GetFilesReaderError::InvalidRange) => {
+            return Ok(range_not_satisfiable_response(size));
+        }
         Err(err) => {
             base.errors.handle_error(err.clone());
 
@@ -1692,22 +1845,66 @@ pub async fn repo_files_details_get_file_stream(
         }
     };
 
-    let (_, file_reader) = base.vault.clone().transfers_download_reader(reader);
+    let content_range = reader.content_range.clone().unwrap();
 
-    let stream = ReaderStream::new(file_reader.reader, BLOCK_SIZE);
+    let stream = ReaderStream::new(reader.reader, BLOCK_SIZE);
 
     let mut res = Body::from_stream(stream).into_response();
-    *res.status_mut() = StatusCode::OK;
-    if let SizeInfo::Exact(size) = file_reader.size {
-        res.headers_mut()
-            .insert(header::CONTENT_LENGTH, size.into());
+    *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+    res.headers_mut().insert(
+        header::CONTENT_LENGTH,
+        (content_range.end - content_range.start + 1).into(),
+    );
+    res.headers_mut().insert(
+        header::CONTENT_RANGE,
+        format!(
+            "bytes {}-{}/{}",
+            content_range.start, content_range.end, content_range.size
+        )
+        .try_into()
+        .unwrap(),
+    );
+    insert_file_stream_headers(&mut res, reader.content_type, etag);
+
+    Ok(res)
+}
+
+/// The unique name is the unique id of the remote file (MD5 of its metadata)
+/// followed by the extension of the decrypted name. Only the unique id is
+/// used so that decrypted names never end up in the headers.
+pub fn unique_name_etag(unique_name: &str) -> String {
+    let unique_id = unique_name
+        .split_once('.')
+        .map_or(unique_name, |(unique_id, _)| unique_id);
+
+    format!("\"{}\"", unique_id)
+}
+
+/// Headers with invalid values are omitted.
+pub fn insert_file_stream_headers(
+    res: &mut Response,
+    content_type: Option<String>,
+    etag: Option<String>,
+) {
+    res.headers_mut()
+        .insert(header::ACCEPT_RANGES, "bytes".try_into().unwrap());
+    if let Some(Ok(content_type)) = content_type.map(HeaderValue::try_from) {
+        res.headers_mut().insert(header::CONTENT_TYPE, content_type);
     }
-    if let Some(content_type) = file_reader.content_type {
-        res.headers_mut()
-            .insert(header::CONTENT_TYPE, content_type.try_into().unwrap());
+    if let Some(Ok(etag)) = etag.map(HeaderValue::try_from) {
+        res.headers_mut().insert(header::ETAG, etag);
     }
+}
 
-    Ok(res)
+fn range_not_satisfiable_response(size: Option<i64>) -> Response {
+    let mut res = StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+    if let Some(size) = size {
+        res.headers_mut().insert(
+            header::CONTENT_RANGE,
+            format!("bytes */{}", size).try_into().unwrap(),
+        );
+    }
+    res
 }
 
 pub async fn repo_files_browsers_download_selected(
@@ -0,0 +1,144 @@
+use axum::http::{header, HeaderMap};
+use vault_core::repo_files_read::state::RepoFileReaderRange;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeNotSatisfiable;
+
+/// Returns the requested range of a file with size. None means that the whole
+/// file should be returned (no Range header, If-Range does not match etag,
+/// multiple ranges or an invalid header which must be ignored).
+pub fn get_request_range(
+    headers: &HeaderMap,
+    size: Option<i64>,
+    etag: Option<&str>,
+) -> Option<Result<RepoFileReaderRange, RangeNotSatisfiable>> {
+    let range = headers.get(header::RANGE)?.to_str().ok()?;
+
+    if let Some(if_range) = headers.get(header::IF_RANGE) {
+        // only strong etags are compared, dates are never an exact match
+        if if_range.to_str().ok() != etag || etag.is_none() {
+            return None;
+        }
+    }
+
+    parse_range(range, size)
+}
+
+pub fn parse_range(
+    value: &str,
+    size: Option<i64>,
+) -> Option<Result<RepoFileReaderRange, RangeNotSatisfiable>> {
+    let value = value.trim().strip_prefix("bytes=")?;
+
+    if value.contains(',') {
+        return None;
+    }
+
+    let (start, end) = value.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = if start.is_empty() {
+        // suffix range, the last end bytes
+        let suffix: i64 = end.parse().ok()?;
+        let size = size?;
+
+        if suffix == 0 {
+            return Some(Err(RangeNotSatisfiable));
+        }
+
+        RepoFileReaderRange {
+            start: (size - suffix).max(0),
+            end: None,
+        }
+    } else {
+        let start: i64 = start.parse().ok()?;
+        let end: Option<i64> = match end {
+            "" => None,
+            end => Some(end.parse().ok()?),
+        };
+
+        if matches!(end, Some(end) if end < start) {
+            return None;
+        }
+
+        RepoFileReaderRange { start, end }
+    };
+
+    if matches!(size, Some(size) if range.start >= size) {
+        return Some(Err(RangeNotSatisfiable));
+    }
+
+    Some(Ok(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, HeaderMap};
+    use similar_asserts::assert_eq;
+    use vault_core::repo_files_read::state::RepoFileReaderRange;
+
+    use super::{get_request_range, parse_range, RangeNotSatisfiable};
+
+    fn range(
+        start: i64,
+        end: Option<i64>,
+    ) -> Option<Result<RepoFileReaderRange, RangeNotSatisfiable>> {
+        Some(Ok(RepoFileReaderRange { start, end }))
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", Some(1000)), range(0, Some(99)));
+        assert_eq!(parse_range("bytes=100-", Some(1000)), range(100, None));
+        assert_eq!(parse_range("bytes=-100", Some(1000)), range(900, None));
+        assert_eq!(parse_range("bytes=-2000", Some(1000)), range(0, None));
+        assert_eq!(
+            parse_range("bytes=900-2000", Some(1000)),
+            range(900, Some(2000))
+        );
+        assert_eq!(parse_range("bytes=100-", None), range(100, None));
+        assert_eq!(
+            parse_range("bytes=1000-", Some(1000)),
+            Some(Err(RangeNotSatisfiable))
+        );
+        assert_eq!(
+            parse_range("bytes=-0", Some(1000)),
+            Some(Err(RangeNotSatisfiable))
+        );
+        assert_eq!(parse_range("bytes=-100", None), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", Some(1000)), None);
+        assert_eq!(parse_range("bytes=10-5", Some(1000)), None);
+        assert_eq!(parse_range("items=0-1", Some(1000)), None);
+        assert_eq!(parse_range("bytes=a-", Some(1000)), None);
+    }
+
+    #[test]
+    fn test_get_request_range() {
+        let mut headers = HeaderMap::new();
+
+        assert_eq!(get_request_range(&headers, Some(1000), Some("\"a\"")), None);
+
+        headers.insert(header::RANGE, "bytes=10-".try_into().unwrap());
+
+        assert_eq!(
+            get_request_range(&headers, Some(1000), Some("\"a\"")),
+            range(10, None)
+        );
+
+        headers.insert(header::IF_RANGE, "\"a\"".try_into().unwrap());
+
+        assert_eq!(
+            get_request_range(&headers, Some(1000), Some("\"a\"")),
+            range(10, None)
+        );
+        assert_eq!(get_request_range(&headers, Some(1000), Some("\"b\"")), None);
+        assert_eq!(get_request_range(&headers, Some(1000), None), None);
+
+        headers.insert(
+            header::IF_RANGE,
+            "Wed, 21 Oct 2015 07:28:00 GMT".try_into().unwrap(),
+        );
+
+        assert_eq!(get_request_range(&headers, Some(1000), Some("\"a\"")), None);
+    }
+}
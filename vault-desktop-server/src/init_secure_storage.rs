@@ -1,8 +1,15 @@
+use std::path::PathBuf;
+
 use vault_core::secure_storage::{MemorySecureStorage, SecureStorage};
 
 use crate::{
-    data_path::get_data_path,
-    file_secure_storage::{get_file_secure_storage_path, FileSecureStorage},
+    data_path::GetDataPathError,
+    encrypted_file_secure_storage::{
+        derive_storage_encryption, get_encrypted_file_secure_storage_path,
+        get_encrypted_file_secure_storage_salt_path, load_or_create_keyring_storage_encryption,
+        load_or_create_local_salt, EncryptedFileSecureStorage,
+    },
+    file_secure_storage::get_file_secure_storage_path,
     keyring_secure_storage::KeyringSecureStorage,
 };
 
@@ -22,10 +29,15 @@ pub fn init_keyring_secure_storage(
     }
 }
 
+/// The file storage is encrypted with a key derived from `passphrase` (if set)
+/// or with a random key kept in the OS keyring under `app_id`. If neither is
+/// available the storage is not persisted.
 pub fn init_file_secure_storage(
+    data_path: Result<PathBuf, GetDataPathError>,
     app_id: &str,
+    passphrase: Option<&str>,
 ) -> (Box<dyn SecureStorage + Send + Sync>, Option<String>) {
-    let data_path = match get_data_path(&app_id) {
+    let data_path = match data_path {
         Ok(data_path) => data_path,
         Err(err) => {
             let secure_storage = Box::new(MemorySecureStorage::new());
@@ -42,9 +54,36 @@ pub fn init_file_secure_storage(
 
     log::info!("App data path: {:?}", data_path);
 
-    let secure_storage_path = get_file_secure_storage_path(data_path);
+    let encryption = match passphrase {
+        Some(passphrase) => {
+            load_or_create_local_salt(&get_encrypted_file_secure_storage_salt_path(&data_path))
+                .map(|salt| derive_storage_encryption(passphrase, &salt))
+        }
+        None => load_or_create_keyring_storage_encryption(app_id).map_err(|err| {
+            format!(
+                "{} (set a storage passphrase if the OS keyring is not available)",
+                err
+            )
+        }),
+    };
+
+    let encryption = match encryption {
+        Ok(encryption) => encryption,
+        Err(err) => {
+            let secure_storage = Box::new(MemorySecureStorage::new());
+
+            return (
+                secure_storage,
+                Some(format!("Failed to load app data: {}", err)),
+            );
+        }
+    };
 
-    match FileSecureStorage::new(secure_storage_path) {
+    match EncryptedFileSecureStorage::new(
+        get_encrypted_file_secure_storage_path(&data_path),
+        Some(get_file_secure_storage_path(data_path)),
+        encryption,
+    ) {
         Ok(secure_storage) => (Box::new(secure_storage), None),
         Err(err) => {
             let secure_storage = Box::new(MemorySecureStorage::new());
@@ -1,60 +1,107 @@
 use std::sync::Arc;
 
-use vault_core::oauth2::OAuth2Config;
+use vault_core::{oauth2::OAuth2Config, transfers::persistence::TransfersPersistenceService};
 use vault_desktop_server::{
     app::app,
+    config::{Config, SecureStorageKind},
+    data_path::{ensure_data_path, get_data_path},
     encryption::Encryption,
     file_handlers::FileHandlers,
     init_secure_storage::{init_file_secure_storage, init_keyring_secure_storage},
+    webdav::app::webdav_app,
+};
+use vault_native::{
+    transfers::file_transfers_persistence::FileTransfersPersistence,
+    vault::{build_local_vault, build_vault},
 };
-use vault_native::vault::build_vault;
 use vault_web_api::web_vault_base::WebVaultBase;
 
 fn main() {
-    let port = 1421;
-
-    let base_url = String::from("https://app.koofr.net");
-    // let base_url = String::from("https://127.0.0.1:3443");
-    let oauth2_auth_base_url = String::from("https://app.koofr.net");
-    // let oauth2_auth_base_url = String::from("http://127.0.0.1:3080");
-    let oauth2_client_id = String::from("7ZEK2BNCEVYEJIZC5OR3TR6PQDUJ4NP3");
-    let oauth2_client_secret =
-        String::from("VWTMENEWUYWH6G523CEV5CWOCHH7FMECW36PPQENOASYYZOQJOSGQXSR2Y62N3HB");
-    let oauth2_redirect_uri = format!("http://127.0.0.1:{}/oauth2callback", port);
-    let user_agent = String::from("vault-desktop");
+    let config = match Config::load_from_args() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
     let app_id = String::from("koofr-vault");
     let app_secret = String::from("XrwBl00MUbeAZ4QBW2F+YDFBv80f2kes49VDx7wUs7Y=");
 
-    let (secure_storage, secure_storage_error) =
-        match std::env::var("VAULT_SECURE_STORAGE").as_deref() {
-            Ok("file") => init_file_secure_storage(&app_id),
-            _ => init_keyring_secure_storage(&app_id),
-        };
+    let data_path = || match &config.data_path {
+        Some(data_path) => ensure_data_path(data_path.clone()),
+        None => get_data_path(&app_id),
+    };
+
+    let (secure_storage, secure_storage_error) = match config.secure_storage {
+        SecureStorageKind::File => init_file_secure_storage(
+            data_path(),
+            &app_id,
+            config.secure_storage_passphrase.as_deref(),
+        ),
+        SecureStorageKind::Keyring => init_keyring_secure_storage(&app_id),
+    };
 
     let tokio_runtime = Arc::new(tokio::runtime::Runtime::new().unwrap());
 
     let encryption = Arc::new(Encryption::new_with_key_str(&app_secret).unwrap());
 
     let oauth2_config = OAuth2Config {
-        base_url: base_url.clone(),
-        auth_base_url: oauth2_auth_base_url,
-        client_id: oauth2_client_id,
-        client_secret: oauth2_client_secret,
-        redirect_uri: oauth2_redirect_uri,
+        base_url: config.base_url.clone(),
+        auth_base_url: config.oauth2_auth_base_url.clone(),
+        client_id: config.oauth2_client_id.clone(),
+        client_secret: config.oauth2_client_secret.clone(),
+        redirect_uri: config.oauth2_redirect_uri.clone(),
     };
 
-    let (vault, _, _) = build_vault(
-        base_url,
-        user_agent,
-        oauth2_config,
-        secure_storage,
-        tokio_runtime.clone(),
-    );
+    let vault = match &config.local_storage_path {
+        Some(local_storage_path) => {
+            build_local_vault(
+                config.base_url.clone(),
+                config.user_agent.clone(),
+                oauth2_config,
+                secure_storage,
+                tokio_runtime.clone(),
+                local_storage_path.clone(),
+            )
+            .0
+        }
+        None => {
+            build_vault(
+                config.base_url.clone(),
+                config.user_agent.clone(),
+                oauth2_config,
+                secure_storage,
+                tokio_runtime.clone(),
+            )
+            .0
+        }
+    };
 
     if let Some(err) = secure_storage_error {
         vault.notifications_show(err);
     }
 
+    // transfers used to be persisted to a plaintext file
+    if let Ok(data_path) = data_path() {
+        let _ = std::fs::remove_file(data_path.join("transfers.json"));
+    }
+
+    // pending uploads of local files are restored after restart once their
+    // repo is unlocked
+    let _transfers_persistence_service = TransfersPersistenceService::new(
+        vault.transfers_service.clone(),
+        vault.store.clone(),
+        vault.runtime.clone(),
+        Box::new(FileTransfersPersistence::new(
+            vault.secure_storage_service.clone(),
+        )),
+    );
+
+    if let Some(webdav_port) = config.webdav_port {
+        tokio_runtime.spawn(webdav_app(webdav_port, vault.clone()));
+    }
+
     let web_vault = WebVaultBase::new(vault);
 
     web_vault.load();
@@ -66,6 +113,13 @@ fn main() {
     });
 
     tokio_runtime.clone().block_on(async move {
-        app(port, web_vault, tokio_runtime, encryption, file_handlers).await
+        app(
+            config.listen_addr,
+            web_vault,
+            tokio_runtime,
+            encryption,
+            file_handlers,
+        )
+        .await
     });
 }
@@ -0,0 +1,27 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::Router;
+use tokio::net::TcpListener;
+use vault_core::Vault;
+
+use super::handlers;
+
+/// WebDAV server exposing every unlocked repo as a collection named after the
+/// repo. It only listens on localhost.
+pub async fn webdav_app(port: u16, vault: Arc<Vault>) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    println!("WebDAV is listening on http://{}", addr);
+    let listener = TcpListener::bind(addr).await.unwrap();
+
+    serve(listener, vault).await
+}
+
+pub async fn serve(listener: TcpListener, vault: Arc<Vault>) {
+    axum::serve(listener, webdav_router(vault).into_make_service())
+        .await
+        .unwrap();
+}
+
+pub fn webdav_router(vault: Arc<Vault>) -> Router {
+    Router::new().fallback(handlers::handle).with_state(vault)
+}
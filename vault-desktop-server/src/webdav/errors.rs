@@ -0,0 +1,204 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use thiserror::Error;
+
+use vault_core::{
+    remote::{ApiErrorCode, RemoteError},
+    repo_files::errors::{
+        CopyFileError, CreateDirError, DeleteFileError, LoadFileError, LoadFilesError,
+        MoveFileError, RenameFileError, UploadFileReaderError,
+    },
+    repo_files_read::errors::GetFilesReaderError,
+    repos::errors::{GetCipherError, RepoLockedError, RepoNotFoundError},
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum WebDavError {
+    #[error("not found")]
+    NotFound,
+    #[error("{0}")]
+    RepoLocked(#[from] RepoLockedError),
+    #[error("invalid path")]
+    InvalidPath,
+    #[error("method not allowed")]
+    MethodNotAllowed,
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("parent collection does not exist")]
+    ParentNotFound,
+    #[error("destination already exists")]
+    DestinationExists,
+    #[error("invalid range")]
+    RangeNotSatisfiable(Option<i64>),
+    #[error("{0}")]
+    RemoteError(Box<RemoteError>),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl IntoResponse for WebDavError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::RepoLocked(_) => StatusCode::LOCKED,
+            Self::InvalidPath => StatusCode::BAD_REQUEST,
+            Self::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::ParentNotFound => StatusCode::CONFLICT,
+            Self::DestinationExists => StatusCode::PRECONDITION_FAILED,
+            Self::RangeNotSatisfiable(_) => StatusCode::RANGE_NOT_SATISFIABLE,
+            Self::RemoteError(err) => match err.as_ref() {
+                RemoteError::ApiError { code, .. } => match code {
+                    ApiErrorCode::NotFound => StatusCode::NOT_FOUND,
+                    ApiErrorCode::AlreadyExists
+                    | ApiErrorCode::Conflict
+                    | ApiErrorCode::NotDir
+                    | ApiErrorCode::MoveIntoSelf => StatusCode::CONFLICT,
+                    ApiErrorCode::InvalidPath => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::BAD_GATEWAY,
+                },
+                _ => StatusCode::BAD_GATEWAY,
+            },
+            Self::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let mut res = (status, self.to_string()).into_response();
+
+        if let Self::RangeNotSatisfiable(Some(size)) = self {
+            res.headers_mut().insert(
+                axum::http::header::CONTENT_RANGE,
+                format!("bytes */{}", size).try_into().unwrap(),
+            );
+        }
+
+        res
+    }
+}
+
+impl From<RepoNotFoundError> for WebDavError {
+    fn from(_: RepoNotFoundError) -> Self {
+        Self::NotFound
+    }
+}
+
+impl From<RemoteError> for WebDavError {
+    fn from(err: RemoteError) -> Self {
+        if err.is_api_error_code(ApiErrorCode::NotFound) {
+            Self::NotFound
+        } else {
+            Self::RemoteError(Box::new(err))
+        }
+    }
+}
+
+impl From<GetCipherError> for WebDavError {
+    fn from(err: GetCipherError) -> Self {
+        match err {
+            GetCipherError::RepoNotFound(err) => err.into(),
+            GetCipherError::RepoLocked(err) => err.into(),
+        }
+    }
+}
+
+impl From<LoadFileError> for WebDavError {
+    fn from(err: LoadFileError) -> Self {
+        match err {
+            LoadFileError::RepoNotFound(err) => err.into(),
+            LoadFileError::RepoLocked(err) => err.into(),
+            LoadFileError::RemoteError(err) => err.into(),
+        }
+    }
+}
+
+impl From<LoadFilesError> for WebDavError {
+    fn from(err: LoadFilesError) -> Self {
+        match err {
+            LoadFilesError::RepoNotFound(err) => err.into(),
+            LoadFilesError::RepoLocked(err) => err.into(),
+            LoadFilesError::RemoteError(err) => err.into(),
+        }
+    }
+}
+
+impl From<CreateDirError> for WebDavError {
+    fn from(err: CreateDirError) -> Self {
+        match err {
+            CreateDirError::RepoNotFound(err) => err.into(),
+            CreateDirError::RepoLocked(err) => err.into(),
+            CreateDirError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<DeleteFileError> for WebDavError {
+    fn from(err: DeleteFileError) -> Self {
+        match err {
+            DeleteFileError::RepoNotFound(err) => err.into(),
+            DeleteFileError::RepoLocked(err) => err.into(),
+            DeleteFileError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<RenameFileError> for WebDavError {
+    fn from(err: RenameFileError) -> Self {
+        match err {
+            RenameFileError::RepoNotFound(err) => err.into(),
+            RenameFileError::RepoLocked(err) => err.into(),
+            RenameFileError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<CopyFileError> for WebDavError {
+    fn from(err: CopyFileError) -> Self {
+        match err {
+            CopyFileError::InvalidPath => Self::InvalidPath,
+            CopyFileError::RepoNotFound(err) => err.into(),
+            CopyFileError::RepoLocked(err) => err.into(),
+            CopyFileError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<MoveFileError> for WebDavError {
+    fn from(err: MoveFileError) -> Self {
+        match err {
+            MoveFileError::InvalidPath => Self::InvalidPath,
+            MoveFileError::RepoNotFound(err) => err.into(),
+            MoveFileError::RepoLocked(err) => err.into(),
+            MoveFileError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<UploadFileReaderError> for WebDavError {
+    fn from(err: UploadFileReaderError) -> Self {
+        match err {
+            UploadFileReaderError::RepoNotFound(err) => err.into(),
+            UploadFileReaderError::RepoLocked(err) => err.into(),
+            UploadFileReaderError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<GetFilesReaderError> for WebDavError {
+    fn from(err: GetFilesReaderError) -> Self {
+        match err {
+            GetFilesReaderError::RepoNotFound(err) => err.into(),
+            GetFilesReaderError::RepoLocked(err) => err.into(),
+            GetFilesReaderError::FileNotFound => Self::NotFound,
+            GetFilesReaderError::InvalidRange => Self::RangeNotSatisfiable(None),
+            GetFilesReaderError::RemoteError(err) => err.into(),
+            err => Self::Other(err.to_string()),
+        }
+    }
+}
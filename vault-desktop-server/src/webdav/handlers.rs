@@ -0,0 +1,604 @@
+use std::{io, sync::Arc};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::TryStreamExt;
+use sync_wrapper::SyncStream;
+
+use vault_core::{
+    cipher::Cipher,
+    repo_files::{
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFilesUploadConflictResolution},
+    },
+    repos::selectors as repos_selectors,
+    types::{DecryptedName, DecryptedPath, EncryptedPath, RepoId},
+    utils::{reader_stream::ReaderStream, repo_path_utils},
+    Vault,
+};
+use vault_crypto::constants::BLOCK_SIZE;
+
+use crate::http_range;
+
+use super::{
+    errors::WebDavError,
+    path::{parse_destination, parse_path, WebDavPath},
+    propfind::{self, PropfindDepth, PropfindEntry},
+};
+
+const ALLOW: &str = "OPTIONS, PROPFIND, GET, HEAD, PUT, MKCOL, DELETE, MOVE, COPY";
+
+/// Resolved path inside an unlocked repo. `encrypted_path` is the file path
+/// until the resource is loaded, see `load_file`.
+struct RepoResource {
+    repo_id: RepoId,
+    repo_name: String,
+    cipher: Arc<Cipher>,
+    path: DecryptedPath,
+    encrypted_path: EncryptedPath,
+}
+
+impl RepoResource {
+    fn child(&self, path: DecryptedPath) -> Self {
+        Self {
+            repo_id: self.repo_id.clone(),
+            repo_name: self.repo_name.clone(),
+            cipher: self.cipher.clone(),
+            encrypted_path: self.cipher.encrypt_path(&path),
+            path,
+        }
+    }
+
+    fn webdav_path(&self) -> WebDavPath {
+        WebDavPath::Repo {
+            repo_name: self.repo_name.clone(),
+            path: self.path.clone(),
+        }
+    }
+
+    fn parent_name(&self) -> Result<(Self, DecryptedName), WebDavError> {
+        let (parent_path, name) = repo_path_utils::split_parent_name(&self.path)
+            .ok_or_else(|| WebDavError::Forbidden(String::from("cannot change a repo root")))?;
+
+        let mut parent = self.child(parent_path);
+        parent.encrypted_path = self.cipher.encrypt_dir_path(&parent.path);
+
+        Ok((parent, name))
+    }
+}
+
+pub async fn handle(State(vault): State<Arc<Vault>>, req: Request) -> Response {
+    match handle_request(vault, req).await {
+        Ok(res) => res,
+        Err(err) => err.into_response(),
+    }
+}
+
+async fn handle_request(vault: Arc<Vault>, req: Request) -> Result<Response, WebDavError> {
+    let path = parse_path(req.uri().path())?;
+
+    match req.method().as_str() {
+        "OPTIONS" => Ok(options()),
+        "PROPFIND" => propfind(&vault, &path, req.headers()).await,
+        "GET" => get(&vault, &path, req.headers(), true).await,
+        "HEAD" => get(&vault, &path, req.headers(), false).await,
+        "PUT" => put(&vault, &path, req).await,
+        "MKCOL" => mkcol(&vault, &path).await,
+        "DELETE" => delete(&vault, &path).await,
+        "MOVE" => move_copy(&vault, &path, req.headers(), true).await,
+        "COPY" => move_copy(&vault, &path, req.headers(), false).await,
+        _ => Err(WebDavError::MethodNotAllowed),
+    }
+}
+
+fn options() -> Response {
+    let mut res = StatusCode::OK.into_response();
+    res.headers_mut()
+        .insert("DAV", HeaderValue::from_static("1"));
+    res.headers_mut()
+        .insert(header::ALLOW, HeaderValue::from_static(ALLOW));
+    res.headers_mut()
+        .insert("MS-Author-Via", HeaderValue::from_static("DAV"));
+    res
+}
+
+/// Every request checks that the repo is still unlocked (it can be locked
+/// manually or by auto lock during a session) and counts as repo activity.
+fn resolve(vault: &Vault, path: &WebDavPath) -> Result<Option<RepoResource>, WebDavError> {
+    let (repo_name, path) = match path {
+        WebDavPath::Root => return Ok(None),
+        WebDavPath::Repo { repo_name, path } => (repo_name, path),
+    };
+
+    // the trash, versions and search folders are managed by the vault
+    if repo_files_selectors::is_hidden_path(path) {
+        return Err(WebDavError::NotFound);
+    }
+
+    let repo_id = vault
+        .with_state(|state| {
+            repos_selectors::select_repos(state)
+                .into_iter()
+                .find(|repo| &repo.name.0 == repo_name)
+                .map(|repo| repo.id.clone())
+        })
+        .ok_or(WebDavError::NotFound)?;
+
+    let cipher = vault.repos_service.get_cipher(&repo_id)?;
+
+    vault.repos_service.touch_repo(&repo_id)?;
+
+    Ok(Some(RepoResource {
+        repo_id,
+        repo_name: repo_name.clone(),
+        encrypted_path: cipher.encrypt_path(path),
+        cipher,
+        path: path.clone(),
+    }))
+}
+
+fn resolve_repo(vault: &Vault, path: &WebDavPath) -> Result<RepoResource, WebDavError> {
+    resolve(vault, path)?
+        .ok_or_else(|| WebDavError::Forbidden(String::from("repos cannot be changed")))
+}
+
+/// Files and dirs can have differently encrypted names (e.g. with
+/// directory_name_encryption = false). If the file does not exist the dir
+/// path is tried and `resource.encrypted_path` is updated.
+async fn load_file(vault: &Vault, resource: &mut RepoResource) -> Result<RepoFile, WebDavError> {
+    match load_encrypted_path(vault, &resource.repo_id, &resource.encrypted_path).await {
+        Err(WebDavError::NotFound) => {
+            let dir_path = resource.cipher.encrypt_dir_path(&resource.path);
+
+            if dir_path == resource.encrypted_path {
+                return Err(WebDavError::NotFound);
+            }
+
+            let file = load_encrypted_path(vault, &resource.repo_id, &dir_path).await?;
+
+            resource.encrypted_path = dir_path;
+
+            Ok(file)
+        }
+        res => res,
+    }
+}
+
+async fn load_encrypted_path(
+    vault: &Vault,
+    repo_id: &RepoId,
+    encrypted_path: &EncryptedPath,
+) -> Result<RepoFile, WebDavError> {
+    vault
+        .repo_files_service
+        .load_file(repo_id, encrypted_path)
+        .await?;
+
+    vault
+        .with_state(|state| {
+            repo_files_selectors::select_file(
+                state,
+                &repo_files_selectors::get_file_id(repo_id, encrypted_path),
+            )
+            .cloned()
+        })
+        .ok_or(WebDavError::NotFound)
+}
+
+async fn find_file(
+    vault: &Vault,
+    resource: &mut RepoResource,
+) -> Result<Option<RepoFile>, WebDavError> {
+    match load_file(vault, resource).await {
+        Ok(file) => Ok(Some(file)),
+        Err(WebDavError::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+async fn ensure_parent_dir(vault: &Vault, parent: &mut RepoResource) -> Result<(), WebDavError> {
+    match find_file(vault, parent).await? {
+        Some(file) if file.typ.is_dir() => Ok(()),
+        _ => Err(WebDavError::ParentNotFound),
+    }
+}
+
+fn file_entry(resource: &RepoResource, file: &RepoFile) -> PropfindEntry {
+    let is_dir = file.typ.is_dir();
+
+    let name = match repo_path_utils::path_to_name(&resource.path) {
+        Some(name) => name.0,
+        None => resource.repo_name.clone(),
+    };
+
+    PropfindEntry {
+        href: resource.webdav_path().href(is_dir),
+        name,
+        is_dir,
+        size: file
+            .size
+            .as_ref()
+            .and_then(|size| size.decrypted_size().ok())
+            .filter(|_| !is_dir),
+        modified: file.modified,
+        content_type: file.content_type.clone().filter(|_| !is_dir),
+        etag: file_etag(file),
+    }
+}
+
+fn file_etag(file: &RepoFile) -> Option<String> {
+    match file.typ.is_dir() {
+        true => None,
+        false => Some(crate::handlers::unique_name_etag(&file.unique_name)),
+    }
+}
+
+async fn propfind(
+    vault: &Vault,
+    path: &WebDavPath,
+    headers: &HeaderMap,
+) -> Result<Response, WebDavError> {
+    let depth = propfind::parse_depth(headers);
+
+    let mut entries = Vec::new();
+
+    match resolve(vault, path)? {
+        None => {
+            entries.push(PropfindEntry {
+                href: WebDavPath::Root.href(true),
+                name: String::new(),
+                is_dir: true,
+                size: None,
+                modified: None,
+                content_type: None,
+                etag: None,
+            });
+
+            if depth == PropfindDepth::One {
+                // only unlocked repos are listed
+                let repo_names = vault.with_state(|state| {
+                    repos_selectors::select_repos(state)
+                        .into_iter()
+                        .filter(|repo| !repo.state.is_locked())
+                        .map(|repo| repo.name.0.clone())
+                        .collect::<Vec<_>>()
+                });
+
+                for repo_name in repo_names {
+                    entries.push(PropfindEntry {
+                        href: WebDavPath::Repo {
+                            repo_name: repo_name.clone(),
+                            path: DecryptedPath(String::from("/")),
+                        }
+                        .href(true),
+                        name: repo_name,
+                        is_dir: true,
+                        size: None,
+                        modified: None,
+                        content_type: None,
+                        etag: None,
+                    });
+                }
+            }
+        }
+        Some(mut resource) => {
+            let file = load_file(vault, &mut resource).await?;
+
+            entries.push(file_entry(&resource, &file));
+
+            if file.typ.is_dir() && depth == PropfindDepth::One {
+                vault
+                    .repo_files_service
+                    .load_files(&resource.repo_id, &resource.encrypted_path)
+                    .await?;
+
+                let children = vault.with_state(|state| {
+                    repo_files_selectors::select_files(
+                        state,
+                        &resource.repo_id,
+                        &resource.encrypted_path,
+                    )
+                    .filter(|file| !repo_files_selectors::is_hidden_dir(file))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                });
+
+                for child in children {
+                    // files with names that cannot be decrypted are skipped
+                    if let Ok(name) = child.decrypted_name() {
+                        let child_resource =
+                            resource.child(repo_path_utils::join_path_name(&resource.path, name));
+
+                        entries.push(file_entry(&child_resource, &child));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut res = propfind::multistatus(&entries).into_response();
+    *res.status_mut() = StatusCode::MULTI_STATUS;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/xml; charset=utf-8"),
+    );
+
+    Ok(res)
+}
+
+async fn get(
+    vault: &Vault,
+    path: &WebDavPath,
+    headers: &HeaderMap,
+    with_body: bool,
+) -> Result<Response, WebDavError> {
+    let mut resource = resolve(vault, path)?.ok_or(WebDavError::MethodNotAllowed)?;
+
+    let file = load_file(vault, &mut resource).await?;
+
+    if file.typ.is_dir() {
+        return Err(WebDavError::MethodNotAllowed);
+    }
+
+    let size = file
+        .size
+        .as_ref()
+        .and_then(|size| size.decrypted_size().ok());
+    let etag = file_etag(&file);
+
+    let range = match http_range::get_request_range(headers, size, etag.as_deref()) {
+        Some(Ok(range)) if with_body => Some(range),
+        Some(Err(http_range::RangeNotSatisfiable)) if with_body => {
+            return Err(WebDavError::RangeNotSatisfiable(size));
+        }
+        _ => None,
+    };
+
+    let mut res = match range {
+        None => {
+            let body = if with_body {
+                let reader = vault
+                    .repo_files_get_file_reader(&resource.repo_id, &resource.encrypted_path)?
+                    .reader()
+                    .await?;
+
+                Body::from_stream(ReaderStream::new(reader.reader, BLOCK_SIZE))
+            } else {
+                Body::empty()
+            };
+
+            let mut res = body.into_response();
+            if let Some(size) = size {
+                res.headers_mut()
+                    .insert(header::CONTENT_LENGTH, size.into());
+            }
+            res
+        }
+        Some(range) => {
+            let reader = vault
+                .repo_files_get_file_reader_range(
+                    &resource.repo_id,
+                    &resource.encrypted_path,
+                    &range,
+                )
+                .await
+                .map_err(|err| match WebDavError::from(err) {
+                    WebDavError::RangeNotSatisfiable(_) => WebDavError::RangeNotSatisfiable(size),
+                    err => err,
+                })?;
+
+            let content_range = reader.content_range.clone().unwrap();
+
+            let mut res =
+                Body::from_stream(ReaderStream::new(reader.reader, BLOCK_SIZE)).into_response();
+            *res.status_mut() = StatusCode::PARTIAL_CONTENT;
+            res.headers_mut().insert(
+                header::CONTENT_LENGTH,
+                (content_range.end - content_range.start + 1).into(),
+            );
+            res.headers_mut().insert(
+                header::CONTENT_RANGE,
+                format!(
+                    "bytes {}-{}/{}",
+                    content_range.start, content_range.end, content_range.size
+                )
+                .try_into()
+                .unwrap(),
+            );
+            res
+        }
+    };
+
+    if let Some(modified) = file.modified {
+        res.headers_mut().insert(
+            header::LAST_MODIFIED,
+            propfind::format_http_date(modified).try_into().unwrap(),
+        );
+    }
+
+    crate::handlers::insert_file_stream_headers(&mut res, file.content_type, etag);
+
+    Ok(res)
+}
+
+async fn put(vault: &Vault, path: &WebDavPath, req: Request) -> Result<Response, WebDavError> {
+    let mut resource = resolve(vault, path)?.ok_or(WebDavError::MethodNotAllowed)?;
+    let (mut parent, name) = resource
+        .parent_name()
+        .map_err(|_| WebDavError::MethodNotAllowed)?;
+
+    ensure_parent_dir(vault, &mut parent).await?;
+
+    let existing = find_file(vault, &mut resource).await?;
+
+    if matches!(&existing, Some(file) if file.typ.is_dir()) {
+        return Err(WebDavError::MethodNotAllowed);
+    }
+
+    let size = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<i64>().ok());
+
+    let reader = SyncStream::new(req.into_body().into_data_stream().map_err(io::Error::other))
+        .into_async_read();
+
+    vault
+        .repo_files_service
+        .clone()
+        .upload_file_reader(
+            &resource.repo_id,
+            &parent.encrypted_path,
+            resource.cipher.encrypt_filename(&name),
+            Box::pin(reader),
+            size,
+            RepoFilesUploadConflictResolution::Overwrite {
+                if_remote_size: None,
+                if_remote_modified: None,
+                if_remote_hash: None,
+            },
+            None,
+        )
+        .await?;
+
+    Ok(match existing {
+        Some(_) => StatusCode::NO_CONTENT,
+        None => StatusCode::CREATED,
+    }
+    .into_response())
+}
+
+async fn mkcol(vault: &Vault, path: &WebDavPath) -> Result<Response, WebDavError> {
+    let mut resource = resolve(vault, path)?.ok_or(WebDavError::MethodNotAllowed)?;
+    let (mut parent, name) = resource
+        .parent_name()
+        .map_err(|_| WebDavError::MethodNotAllowed)?;
+
+    if find_file(vault, &mut resource).await?.is_some() {
+        return Err(WebDavError::MethodNotAllowed);
+    }
+
+    ensure_parent_dir(vault, &mut parent).await?;
+
+    vault
+        .repo_files_service
+        .create_dir_name(
+            &resource.repo_id,
+            &parent.encrypted_path,
+            resource.cipher.encrypt_dir_name(&name),
+        )
+        .await?;
+
+    Ok(StatusCode::CREATED.into_response())
+}
+
+async fn delete(vault: &Vault, path: &WebDavPath) -> Result<Response, WebDavError> {
+    let mut resource = resolve_repo(vault, path)?;
+    resource.parent_name()?;
+
+    load_file(vault, &mut resource).await?;
+
+    delete_file(vault, &resource).await?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Deleted files are moved to the trash if it is enabled for the repo.
+async fn delete_file(vault: &Vault, resource: &RepoResource) -> Result<(), WebDavError> {
+    vault
+        .repo_files_service
+        .delete_file(&resource.repo_id, &resource.encrypted_path)
+        .await?;
+
+    Ok(())
+}
+
+async fn move_copy(
+    vault: &Vault,
+    path: &WebDavPath,
+    headers: &HeaderMap,
+    is_move: bool,
+) -> Result<Response, WebDavError> {
+    let mut resource = resolve_repo(vault, path)?;
+    resource.parent_name()?;
+
+    let destination = headers
+        .get("Destination")
+        .and_then(|value| value.to_str().ok())
+        .ok_or(WebDavError::InvalidPath)
+        .and_then(parse_destination)?;
+
+    let mut to_resource = match &destination {
+        WebDavPath::Repo { repo_name, path } if repo_name == &resource.repo_name => {
+            resource.child(path.clone())
+        }
+        _ => {
+            return Err(WebDavError::Forbidden(String::from(
+                "destination must be in the same repo",
+            )))
+        }
+    };
+    let (mut to_parent, to_name) = to_resource.parent_name()?;
+
+    if to_resource.path == resource.path {
+        return Err(WebDavError::Forbidden(String::from(
+            "source and destination are the same",
+        )));
+    }
+
+    let overwrite = headers.get("Overwrite").map(|value| value.as_bytes()) != Some(b"F");
+
+    load_file(vault, &mut resource).await?;
+
+    ensure_parent_dir(vault, &mut to_parent).await?;
+
+    let existing = find_file(vault, &mut to_resource).await?;
+
+    if existing.is_some() {
+        if !overwrite {
+            return Err(WebDavError::DestinationExists);
+        }
+
+        delete_file(vault, &to_resource).await?;
+    }
+
+    let (parent, _) = resource.parent_name()?;
+
+    if !is_move {
+        vault
+            .repo_files_service
+            .copy_file_name(
+                &resource.repo_id,
+                &resource.encrypted_path,
+                &to_parent.encrypted_path,
+                &to_name,
+            )
+            .await?;
+    } else if parent.encrypted_path == to_parent.encrypted_path {
+        vault
+            .repo_files_service
+            .rename_file_name(&resource.repo_id, &resource.encrypted_path, &to_name)
+            .await?;
+    } else {
+        vault
+            .repo_files_service
+            .move_file_name(
+                &resource.repo_id,
+                &resource.encrypted_path,
+                &to_parent.encrypted_path,
+                &to_name,
+            )
+            .await?;
+    }
+
+    Ok(match existing {
+        Some(_) => StatusCode::NO_CONTENT,
+        None => StatusCode::CREATED,
+    }
+    .into_response())
+}
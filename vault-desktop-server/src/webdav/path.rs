@@ -0,0 +1,124 @@
+use axum::http::Uri;
+use vault_core::{types::DecryptedPath, utils::path_utils};
+
+use super::errors::WebDavError;
+
+/// Path of a WebDAV resource. The first path segment is the repo name, the
+/// rest is the decrypted path inside the repo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebDavPath {
+    Root,
+    Repo {
+        repo_name: String,
+        path: DecryptedPath,
+    },
+}
+
+impl WebDavPath {
+    pub fn href(&self, is_dir: bool) -> String {
+        match self {
+            Self::Root => String::from("/"),
+            Self::Repo { repo_name, path } => {
+                let mut href = format!("/{}", urlencoding::encode(repo_name));
+
+                if !path.is_root() {
+                    for name in path.0.split('/').skip(1) {
+                        href.push('/');
+                        href.push_str(&urlencoding::encode(name));
+                    }
+                }
+
+                if is_dir {
+                    href.push('/');
+                }
+
+                href
+            }
+        }
+    }
+}
+
+pub fn parse_path(uri_path: &str) -> Result<WebDavPath, WebDavError> {
+    let uri_path = urlencoding::decode(uri_path).map_err(|_| WebDavError::InvalidPath)?;
+
+    let uri_path = uri_path.trim_start_matches('/');
+
+    let (repo_name, path) = uri_path.split_once('/').unwrap_or((uri_path, ""));
+
+    if repo_name.is_empty() {
+        return Ok(WebDavPath::Root);
+    }
+
+    let path = path_utils::normalize_path(path).map_err(|_| WebDavError::InvalidPath)?;
+
+    Ok(WebDavPath::Repo {
+        repo_name: repo_name.to_owned(),
+        path: DecryptedPath(path),
+    })
+}
+
+/// Destination header is an absolute URI or an absolute path.
+pub fn parse_destination(destination: &str) -> Result<WebDavPath, WebDavError> {
+    let uri: Uri = destination.parse().map_err(|_| WebDavError::InvalidPath)?;
+
+    parse_path(uri.path())
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+    use vault_core::types::DecryptedPath;
+
+    use super::{parse_destination, parse_path, WebDavPath};
+
+    fn repo_path(repo_name: &str, path: &str) -> WebDavPath {
+        WebDavPath::Repo {
+            repo_name: repo_name.to_owned(),
+            path: DecryptedPath(path.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(parse_path("/").unwrap(), WebDavPath::Root);
+        assert_eq!(parse_path("").unwrap(), WebDavPath::Root);
+        assert_eq!(
+            parse_path("/My%20safe%20box").unwrap(),
+            repo_path("My safe box", "/")
+        );
+        assert_eq!(
+            parse_path("/My%20safe%20box/").unwrap(),
+            repo_path("My safe box", "/")
+        );
+        assert_eq!(
+            parse_path("/box/dir/file%20%C5%A1.txt").unwrap(),
+            repo_path("box", "/dir/file š.txt")
+        );
+        assert_eq!(parse_path("/box/dir/").unwrap(), repo_path("box", "/dir"));
+        assert!(parse_path("/box/../other").is_err());
+        assert!(parse_path("/box/%FF").is_err());
+    }
+
+    #[test]
+    fn test_parse_destination() {
+        assert_eq!(
+            parse_destination("http://127.0.0.1:1422/box/a%20b.txt").unwrap(),
+            repo_path("box", "/a b.txt")
+        );
+        assert_eq!(
+            parse_destination("/box/dir/").unwrap(),
+            repo_path("box", "/dir")
+        );
+    }
+
+    #[test]
+    fn test_href() {
+        assert_eq!(WebDavPath::Root.href(true), "/");
+        assert_eq!(repo_path("My box", "/").href(true), "/My%20box/");
+        assert_eq!(
+            repo_path("box", "/dir/a b.txt").href(false),
+            "/box/dir/a%20b.txt"
+        );
+        assert_eq!(repo_path("box", "/dir").href(true), "/box/dir/");
+    }
+}
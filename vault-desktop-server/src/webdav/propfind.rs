@@ -0,0 +1,161 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use axum::http::HeaderMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropfindDepth {
+    Zero,
+    One,
+}
+
+/// Depth: infinity is not supported, it is handled as Depth: 1.
+pub fn parse_depth(headers: &HeaderMap) -> PropfindDepth {
+    match headers.get("Depth").and_then(|value| value.to_str().ok()) {
+        Some("0") => PropfindDepth::Zero,
+        _ => PropfindDepth::One,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropfindEntry {
+    pub href: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<i64>,
+    pub modified: Option<i64>,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+}
+
+/// Builds a 207 Multi-Status body. All live properties are always returned
+/// regardless of the requested properties.
+pub fn multistatus(entries: &[PropfindEntry]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"DAV:\">",
+    );
+
+    for entry in entries {
+        xml.push_str("<D:response><D:href>");
+        xml.push_str(&escape_xml(&entry.href));
+        xml.push_str("</D:href><D:propstat><D:prop>");
+
+        xml.push_str("<D:displayname>");
+        xml.push_str(&escape_xml(&entry.name));
+        xml.push_str("</D:displayname>");
+
+        if entry.is_dir {
+            xml.push_str("<D:resourcetype><D:collection/></D:resourcetype>");
+        } else {
+            xml.push_str("<D:resourcetype/>");
+        }
+
+        if let Some(size) = entry.size {
+            xml.push_str(&format!(
+                "<D:getcontentlength>{}</D:getcontentlength>",
+                size
+            ));
+        }
+
+        if let Some(modified) = entry.modified {
+            xml.push_str("<D:getlastmodified>");
+            xml.push_str(&format_http_date(modified));
+            xml.push_str("</D:getlastmodified>");
+        }
+
+        if let Some(content_type) = &entry.content_type {
+            xml.push_str("<D:getcontenttype>");
+            xml.push_str(&escape_xml(content_type));
+            xml.push_str("</D:getcontenttype>");
+        }
+
+        if let Some(etag) = &entry.etag {
+            xml.push_str("<D:getetag>");
+            xml.push_str(&escape_xml(etag));
+            xml.push_str("</D:getetag>");
+        }
+
+        xml.push_str("</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>");
+    }
+
+    xml.push_str("</D:multistatus>");
+
+    xml
+}
+
+pub fn format_http_date(modified: i64) -> String {
+    httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_millis(modified.max(0) as u64))
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use similar_asserts::assert_eq;
+
+    use super::{format_http_date, multistatus, PropfindEntry};
+
+    #[test]
+    fn test_multistatus() {
+        let xml = multistatus(&[
+            PropfindEntry {
+                href: String::from("/box/"),
+                name: String::from("box"),
+                is_dir: true,
+                size: None,
+                modified: None,
+                content_type: None,
+                etag: None,
+            },
+            PropfindEntry {
+                href: String::from("/box/a%20%26%20b.txt"),
+                name: String::from("a & b.txt"),
+                is_dir: false,
+                size: Some(42),
+                modified: Some(1445412480000),
+                content_type: Some(String::from("text/plain")),
+                etag: Some(String::from("\"x\"")),
+            },
+        ]);
+
+        assert_eq!(
+            xml,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n",
+                "<D:multistatus xmlns:D=\"DAV:\">",
+                "<D:response><D:href>/box/</D:href><D:propstat><D:prop>",
+                "<D:displayname>box</D:displayname>",
+                "<D:resourcetype><D:collection/></D:resourcetype>",
+                "</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                "<D:response><D:href>/box/a%20%26%20b.txt</D:href><D:propstat><D:prop>",
+                "<D:displayname>a &amp; b.txt</D:displayname>",
+                "<D:resourcetype/>",
+                "<D:getcontentlength>42</D:getcontentlength>",
+                "<D:getlastmodified>Wed, 21 Oct 2015 07:28:00 GMT</D:getlastmodified>",
+                "<D:getcontenttype>text/plain</D:getcontenttype>",
+                "<D:getetag>&quot;x&quot;</D:getetag>",
+                "</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+                "</D:multistatus>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_http_date() {
+        assert_eq!(format_http_date(0), "Thu, 01 Jan 1970 00:00:00 GMT");
+    }
+}
@@ -1,44 +1,53 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use futures::{channel::oneshot, FutureExt, TryFutureExt};
 use tauri::{api::dialog::FileDialogBuilder, RunEvent};
 use vault_core::oauth2::OAuth2Config;
 use vault_desktop_server::{
     app::app,
+    config::{Config, SecureStorageKind},
+    data_path::{ensure_data_path, get_data_path},
     encryption::Encryption,
     file_handlers::FileHandlers,
     init_secure_storage::{init_file_secure_storage, init_keyring_secure_storage},
 };
-use vault_native::vault::build_vault;
+use vault_native::vault::{build_local_vault, build_vault};
 use vault_web_api::web_vault_base::WebVaultBase;
 
 struct TauriState {
-    pub port: u16,
+    pub listen_addr: SocketAddr,
     pub app_secret: String,
 }
 
 fn main() {
-    let port = 1421;
-
-    let base_url = String::from("https://app.koofr.net");
-    // let base_url = String::from("https://127.0.0.1:3443");
-    let oauth2_auth_base_url = String::from("https://app.koofr.net");
-    // let oauth2_auth_base_url = String::from("http://127.0.0.1:3080");
-    let oauth2_client_id = String::from("7ZEK2BNCEVYEJIZC5OR3TR6PQDUJ4NP3");
-    let oauth2_client_secret =
-        String::from("VWTMENEWUYWH6G523CEV5CWOCHH7FMECW36PPQENOASYYZOQJOSGQXSR2Y62N3HB");
-    let oauth2_redirect_uri = format!("http://127.0.0.1:{}/oauth2callback", port);
-    let user_agent = String::from("vault-desktop");
+    // the same options as the desktop server (e.g. VAULT_BASE_URL,
+    // VAULT_LISTEN_ADDR or a VAULT_CONFIG file)
+    let config = match Config::load_from_args() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
     let app_id = String::from("koofr-vault");
 
-    let (secure_storage, secure_storage_error) =
-        match std::env::var("VAULT_SECURE_STORAGE").as_deref() {
-            Ok("file") => init_file_secure_storage(&app_id),
-            _ => init_keyring_secure_storage(&app_id),
-        };
+    let data_path = || match &config.data_path {
+        Some(data_path) => ensure_data_path(data_path.clone()),
+        None => get_data_path(&app_id),
+    };
+
+    let (secure_storage, secure_storage_error) = match config.secure_storage {
+        SecureStorageKind::File => init_file_secure_storage(
+            data_path(),
+            &app_id,
+            config.secure_storage_passphrase.as_deref(),
+        ),
+        SecureStorageKind::Keyring => init_keyring_secure_storage(&app_id),
+    };
 
     let tokio_runtime = Arc::new(tokio::runtime::Runtime::new().unwrap());
 
@@ -46,20 +55,36 @@ fn main() {
     let encryption = Arc::new(encryption);
 
     let oauth2_config = OAuth2Config {
-        base_url: base_url.clone(),
-        auth_base_url: oauth2_auth_base_url,
-        client_id: oauth2_client_id,
-        client_secret: oauth2_client_secret,
-        redirect_uri: oauth2_redirect_uri,
+        base_url: config.base_url.clone(),
+        auth_base_url: config.oauth2_auth_base_url.clone(),
+        client_id: config.oauth2_client_id.clone(),
+        client_secret: config.oauth2_client_secret.clone(),
+        redirect_uri: config.oauth2_redirect_uri.clone(),
     };
 
-    let (vault, _, _) = build_vault(
-        base_url,
-        user_agent,
-        oauth2_config,
-        secure_storage,
-        tokio_runtime.clone(),
-    );
+    let vault = match &config.local_storage_path {
+        Some(local_storage_path) => {
+            build_local_vault(
+                config.base_url.clone(),
+                config.user_agent.clone(),
+                oauth2_config,
+                secure_storage,
+                tokio_runtime.clone(),
+                local_storage_path.clone(),
+            )
+            .0
+        }
+        None => {
+            build_vault(
+                config.base_url.clone(),
+                config.user_agent.clone(),
+                oauth2_config,
+                secure_storage,
+                tokio_runtime.clone(),
+            )
+            .0
+        }
+    };
 
     if let Some(err) = secure_storage_error {
         vault.notifications_show(err);
@@ -107,8 +132,10 @@ fn main() {
         }))),
     });
 
+    let listen_addr = config.listen_addr;
+
     tauri::async_runtime::spawn(app(
-        port,
+        listen_addr,
         web_vault,
         tokio_runtime,
         encryption,
@@ -116,7 +143,10 @@ fn main() {
     ));
 
     tauri::Builder::default()
-        .manage(TauriState { port, app_secret })
+        .manage(TauriState {
+            listen_addr,
+            app_secret,
+        })
         .invoke_handler(tauri::generate_handler![
             get_desktop_server_url,
             get_app_secret
@@ -133,7 +163,7 @@ fn main() {
 
 #[tauri::command]
 fn get_desktop_server_url(state: tauri::State<TauriState>) -> Result<String, String> {
-    Ok(format!("http://127.0.0.1:{}", state.port))
+    Ok(format!("http://{}", state.listen_addr))
 }
 
 #[tauri::command]
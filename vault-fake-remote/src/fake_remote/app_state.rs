@@ -6,7 +6,9 @@ use super::{
     interceptor::Interceptor,
     state::FakeRemoteState,
     users_service::UsersService,
-    vault_repos_service::{VaultReposCreateService, VaultReposRemoveService},
+    vault_repos_service::{
+        VaultReposCreateService, VaultReposRemoveService, VaultReposUpdateService,
+    },
 };
 
 #[derive(Clone)]
@@ -16,6 +18,7 @@ pub struct AppState {
     pub files_service: Arc<FilesService>,
     pub users_service: Arc<UsersService>,
     pub vault_repos_create_service: Arc<VaultReposCreateService>,
+    pub vault_repos_update_service: Arc<VaultReposUpdateService>,
     pub vault_repos_remove_service: Arc<VaultReposRemoveService>,
     pub eventstream_listeners: Arc<eventstream::Listeners>,
     pub interceptor: Arc<Option<Interceptor>>,
@@ -37,6 +40,10 @@ impl AppState {
             state.clone(),
             files_service.clone(),
         ));
+        let vault_repos_update_service = Arc::new(VaultReposUpdateService::new(
+            state.clone(),
+            files_service.clone(),
+        ));
 
         Self {
             state,
@@ -44,6 +51,7 @@ impl AppState {
             files_service,
             users_service,
             vault_repos_create_service,
+            vault_repos_update_service,
             vault_repos_remove_service,
             eventstream_listeners,
             interceptor: Default::default(),
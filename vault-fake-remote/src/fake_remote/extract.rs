@@ -15,7 +15,9 @@ use super::{
     files::service::FilesService,
     state::FakeRemoteState,
     users_service::UsersService,
-    vault_repos_service::{VaultReposCreateService, VaultReposRemoveService},
+    vault_repos_service::{
+        VaultReposCreateService, VaultReposRemoveService, VaultReposUpdateService,
+    },
 };
 
 pub fn get_authorization_access_token<'a>(
@@ -129,6 +131,17 @@ impl FromRequestParts<AppState> for ExtractVaultReposCreateService {
     }
 }
 
+pub struct ExtractVaultReposUpdateService(pub Arc<VaultReposUpdateService>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ExtractVaultReposUpdateService {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        Ok(Self(state.vault_repos_update_service.clone()))
+    }
+}
+
 pub struct ExtractVaultReposRemoveService(pub Arc<VaultReposRemoveService>);
 
 #[async_trait]
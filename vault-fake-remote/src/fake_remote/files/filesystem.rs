@@ -12,7 +12,7 @@ use crate::fake_remote::errors::{ApiErrorCode, FakeRemoteError};
 
 use super::{path::NormalizedPath, Name, Path};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CreateFileConflictResolution {
     Autorename,
     Overwrite {
@@ -7,7 +7,7 @@ use axum::{
     response::{IntoResponse, Response},
     Form, Json,
 };
-use futures::TryStreamExt;
+use futures::{AsyncReadExt, TryStreamExt};
 use http::{header, HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
 use urlencoding::encode;
@@ -22,10 +22,10 @@ use super::{
     errors::{ApiErrorCode, FakeRemoteError},
     extract::{
         ExtractFilesService, ExtractState, ExtractVaultReposCreateService,
-        ExtractVaultReposRemoveService,
+        ExtractVaultReposRemoveService, ExtractVaultReposUpdateService,
     },
     files,
-    state::FakeRemoteState,
+    state::{FakeRemoteState, UploadSession},
 };
 
 static PROFILE_PICTURE_PNG: &'static [u8] = &[
@@ -705,6 +705,169 @@ pub async fn content_files_put(
     Ok(Json(file))
 }
 
+pub async fn content_files_put_sessions_create(
+    ExtractState(state): ExtractState,
+    context: Context,
+    Path(mountable): Path<String>,
+    Query(query): Query<FilesPutQuery>,
+) -> Result<Json<models::FilesUploadSession>, FakeRemoteError> {
+    if !matches!(query.info, Some(true)) {
+        return Err(FakeRemoteError::BadRequest("Info must be true".into()));
+    }
+
+    let mut state = state.write().unwrap();
+
+    let mount_id = resolve_mount_id(&context, &state, mountable);
+    let conflict_resolution = files::filesystem::CreateFileConflictResolution::parse(
+        query.autorename,
+        query.overwrite,
+        query.overwrite_if_modified,
+        query.overwrite_if_size,
+        query.overwrite_if_hash,
+        query.overwrite_ignore_nonexisting,
+        query.overwrite_ignore_nonexistent,
+    );
+
+    let id = uuid::Uuid::new_v4().to_string();
+
+    state.upload_sessions.insert(
+        id.clone(),
+        UploadSession {
+            mount_id,
+            parent_path: query.path,
+            name: query.filename,
+            modified: query.modified,
+            conflict_resolution,
+            data: Vec::new(),
+        },
+    );
+
+    Ok(Json(models::FilesUploadSession { id, offset: 0 }))
+}
+
+fn get_upload_session<'a>(
+    state: &'a mut FakeRemoteState,
+    mount_id: &str,
+    session_id: &str,
+) -> Result<&'a mut UploadSession, FakeRemoteError> {
+    state
+        .upload_sessions
+        .get_mut(session_id)
+        .filter(|session| session.mount_id == mount_id)
+        .ok_or_else(|| {
+            FakeRemoteError::ApiError(
+                StatusCode::NOT_FOUND,
+                ApiErrorCode::NotFound,
+                "Upload session not found".into(),
+                None,
+            )
+        })
+}
+
+pub async fn content_files_put_sessions_get(
+    ExtractState(state): ExtractState,
+    context: Context,
+    Path((mountable, session_id)): Path<(String, String)>,
+) -> Result<Json<models::FilesUploadSession>, FakeRemoteError> {
+    let mut state = state.write().unwrap();
+
+    let mount_id = resolve_mount_id(&context, &state, mountable);
+    let session = get_upload_session(&mut state, &mount_id, &session_id)?;
+
+    Ok(Json(models::FilesUploadSession {
+        id: session_id,
+        offset: session.data.len() as i64,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct FilesPutSessionsChunkQuery {
+    offset: i64,
+}
+
+pub async fn content_files_put_sessions_chunk(
+    ExtractState(state): ExtractState,
+    context: Context,
+    Path((mountable, session_id)): Path<(String, String)>,
+    Query(query): Query<FilesPutSessionsChunkQuery>,
+    stream: BodyStream,
+) -> Result<Json<models::FilesUploadSession>, FakeRemoteError> {
+    let mount_id = {
+        let mut state = state.write().unwrap();
+
+        let mount_id = resolve_mount_id(&context, &state, mountable);
+        let session = get_upload_session(&mut state, &mount_id, &session_id)?;
+
+        if query.offset < 0 || query.offset > session.data.len() as i64 {
+            return Err(FakeRemoteError::ApiError(
+                StatusCode::CONFLICT,
+                ApiErrorCode::Conflict,
+                format!(
+                    "Invalid offset {}, upload session offset is {}",
+                    query.offset,
+                    session.data.len()
+                ),
+                None,
+            ));
+        }
+
+        mount_id
+    };
+
+    // the chunk is only stored if the whole body was received
+    let mut chunk = Vec::new();
+
+    stream
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::BrokenPipe, err))
+        .into_async_read()
+        .read_to_end(&mut chunk)
+        .await
+        .map_err(|err| FakeRemoteError::BadRequest(err.to_string()))?;
+
+    let mut state = state.write().unwrap();
+
+    let session = get_upload_session(&mut state, &mount_id, &session_id)?;
+
+    session.data.truncate(query.offset as usize);
+    session.data.extend_from_slice(&chunk);
+
+    Ok(Json(models::FilesUploadSession {
+        id: session_id,
+        offset: session.data.len() as i64,
+    }))
+}
+
+pub async fn content_files_put_sessions_commit(
+    ExtractState(state): ExtractState,
+    ExtractFilesService(files_service): ExtractFilesService,
+    context: Context,
+    Path((mountable, session_id)): Path<(String, String)>,
+) -> Result<Json<models::FilesFile>, FakeRemoteError> {
+    let session = {
+        let mut state = state.write().unwrap();
+
+        let mount_id = resolve_mount_id(&context, &state, mountable);
+
+        get_upload_session(&mut state, &mount_id, &session_id)?;
+
+        state.upload_sessions.remove(&session_id).unwrap()
+    };
+
+    let file = files_service
+        .create_file(
+            &context,
+            &session.mount_id,
+            &session.parent_path,
+            session.name,
+            session.modified,
+            &session.conflict_resolution,
+            Box::pin(futures::io::Cursor::new(session.data)),
+        )
+        .await?;
+
+    Ok(Json(file))
+}
+
 #[derive(Deserialize)]
 pub struct FilesListRecursiveQuery {
     path: files::Path,
@@ -790,6 +953,17 @@ pub async fn vault_repos_create(
     Ok((StatusCode::CREATED, Json(repo)))
 }
 
+pub async fn vault_repos_update(
+    ExtractVaultReposUpdateService(vault_repos_update_service): ExtractVaultReposUpdateService,
+    context: Context,
+    Path(repo_id): Path<String>,
+    Json(update): Json<models::VaultRepoUpdate>,
+) -> Result<Json<models::VaultRepo>, FakeRemoteError> {
+    let repo = vault_repos_update_service.update_vault_repo(&context, &repo_id, update)?;
+
+    Ok(Json(repo))
+}
+
 pub async fn vault_repos_remove(
     ExtractVaultReposRemoveService(vault_repos_remove_service): ExtractVaultReposRemoveService,
     context: Context,
@@ -96,11 +96,24 @@ pub fn build_router(app_state: AppState) -> Router {
             "/content/api/v2.1/mounts/:mount_id/files/put",
             post(handlers::content_files_put),
         )
+        .route(
+            "/content/api/v2.1/mounts/:mount_id/files/put/sessions",
+            post(handlers::content_files_put_sessions_create),
+        )
+        .route(
+            "/content/api/v2.1/mounts/:mount_id/files/put/sessions/:session_id",
+            get(handlers::content_files_put_sessions_get)
+                .put(handlers::content_files_put_sessions_chunk),
+        )
+        .route(
+            "/content/api/v2.1/mounts/:mount_id/files/put/sessions/:session_id/commit",
+            post(handlers::content_files_put_sessions_commit),
+        )
         .route("/api/v2.1/vault/repos", get(handlers::vault_repos_all))
         .route("/api/v2.1/vault/repos", post(handlers::vault_repos_create))
         .route(
             "/api/v2.1/vault/repos/:repo_id",
-            delete(handlers::vault_repos_remove),
+            put(handlers::vault_repos_update).delete(handlers::vault_repos_remove),
         )
         .route("/events", get(eventstream::handler::eventstream))
         .layer(middleware::from_fn(fix_response_json))
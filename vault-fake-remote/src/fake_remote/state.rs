@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use vault_core::remote::models;
 
-use super::files::Filesystem;
+use super::files::{filesystem::CreateFileConflictResolution, Filesystem, Name, Path};
 
 #[derive(Debug)]
 pub struct UserContainer {
@@ -13,6 +13,16 @@ pub struct UserContainer {
     pub user_vault_repos: Vec<String>,
 }
 
+#[derive(Debug)]
+pub struct UploadSession {
+    pub mount_id: String,
+    pub parent_path: Path,
+    pub name: Name,
+    pub modified: Option<i64>,
+    pub conflict_resolution: CreateFileConflictResolution,
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Default)]
 pub struct FakeRemoteState {
     pub default_user_id: Option<String>,
@@ -31,6 +41,9 @@ pub struct FakeRemoteState {
 
     // mount ids to filesystems
     pub filesystems: HashMap<String, Filesystem>,
+
+    // upload session ids to upload sessions
+    pub upload_sessions: HashMap<String, UploadSession>,
 }
 
 impl FakeRemoteState {
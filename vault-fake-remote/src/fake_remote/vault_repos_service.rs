@@ -102,6 +102,10 @@ impl VaultReposCreateService {
             salt: create.salt,
             password_validator: create.password_validator,
             password_validator_encrypted: create.password_validator_encrypted,
+            // Koofr does not store the name options
+            filename_encryption: None,
+            directory_name_encryption: None,
+            filename_encoding: None,
             added: now_ms(),
         };
 
@@ -155,11 +159,121 @@ impl VaultReposCreateService {
                 salt: Some("salt".into()),
                 password_validator: "ad3238a5-5fc7-4b8f-9575-88c69c0c91cd".into(),
                 password_validator_encrypted: "v2:UkNMT05FAABVyJmka7FKh8CKL2AtIZc1xiZk-SO5GeuZPnHvw0ehM1dENa4iBCyPEf50da9V2XvL5CjpZlUle1lifEHtaRy9YHoFLHtiq1PCAqYY".into(),
+                ..Default::default()
             },
         )
     }
 }
 
+pub struct VaultReposUpdateService {
+    state: Arc<RwLock<FakeRemoteState>>,
+    files_service: Arc<FilesService>,
+}
+
+impl VaultReposUpdateService {
+    pub fn new(state: Arc<RwLock<FakeRemoteState>>, files_service: Arc<FilesService>) -> Self {
+        Self {
+            state,
+            files_service,
+        }
+    }
+
+    pub fn update_vault_repo(
+        &self,
+        context: &Context,
+        repo_id: &str,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, FakeRemoteError> {
+        let repo_not_found = || {
+            FakeRemoteError::ApiError(
+                StatusCode::NOT_FOUND,
+                ApiErrorCode::NotFound,
+                "Vault repo not found".into(),
+                None,
+            )
+        };
+
+        let mount_id = {
+            let state = self.state.read().unwrap();
+
+            if !state
+                .users
+                .get(&context.user_id)
+                .unwrap()
+                .user_vault_repos
+                .iter()
+                .any(|id| id == repo_id)
+            {
+                return Err(repo_not_found());
+            }
+
+            state
+                .vault_repos
+                .get(repo_id)
+                .ok_or_else(repo_not_found)?
+                .mount_id
+                .clone()
+        };
+
+        let path: Path = update.path.0.parse().map_err(|_| {
+            FakeRemoteError::ApiError(
+                StatusCode::BAD_REQUEST,
+                ApiErrorCode::BadRequest,
+                "Invalid path".into(),
+                None,
+            )
+        })?;
+
+        match self.files_service.info(&mount_id.0, &path) {
+            Ok(_) => {}
+            Err(FakeRemoteError::ApiError(_, code, _, _)) if code == ApiErrorCode::NotFound => {
+                return Err(FakeRemoteError::ApiError(
+                    StatusCode::NOT_FOUND,
+                    ApiErrorCode::VaultReposLocationNotFound,
+                    "Vault repo location not found.".into(),
+                    None,
+                ))
+            }
+            Err(err) => return Err(err),
+        }
+
+        let mut state = self.state.write().unwrap();
+
+        if state
+            .vault_repos
+            .values()
+            .find(|repo| {
+                repo.id.0 != repo_id
+                    && repo.mount_id == mount_id
+                    && match Path(repo.path.0.clone()).relative_to(&path) {
+                        Some(path) => path.0 == "/",
+                        _ => false,
+                    }
+            })
+            .is_some()
+        {
+            return Err(FakeRemoteError::ApiError(
+                StatusCode::CONFLICT,
+                ApiErrorCode::VaultReposAlreadyExists,
+                "Vault repo already exists for this path.".into(),
+                None,
+            ));
+        }
+
+        let repo = state
+            .vault_repos
+            .get_mut(repo_id)
+            .ok_or_else(repo_not_found)?;
+
+        repo.path = RemotePath(path.0);
+        repo.salt = update.salt;
+        repo.password_validator = update.password_validator;
+        repo.password_validator_encrypted = update.password_validator_encrypted;
+
+        Ok(repo.clone())
+    }
+}
+
 pub struct VaultReposRemoveService {
     state: Arc<RwLock<FakeRemoteState>>,
 }
@@ -85,6 +85,8 @@ public func getTransferDescription(state: TransferState) -> String {
         return "Processing"
     case .transferring:
         return "Transferring"
+    case .paused:
+        return "Paused"
     case .failed(let err):
         return "Failed: \(err)"
     case .done:
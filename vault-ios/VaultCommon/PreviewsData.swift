@@ -47,6 +47,8 @@ public struct PreviewsData {
         isTransferring: true,
         isAllDone: false,
         canRetryAll: false,
+        canPauseAll: true,
+        canResumeAll: false,
         canAbortAll: true
     )
 
@@ -77,6 +79,8 @@ public struct PreviewsData {
             speedDisplay: "495.1 KB/s",
             state: .transferring,
             canRetry: false,
+            canPause: true,
+            canResume: false,
             canOpen: false
         ),
         Transfer(
@@ -105,6 +109,8 @@ public struct PreviewsData {
             speedDisplay: "1.3 MB/s",
             state: .transferring,
             canRetry: false,
+            canPause: true,
+            canResume: false,
             canOpen: false
         ),
         Transfer(
@@ -136,6 +142,8 @@ public struct PreviewsData {
                     "Unknown error: Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec pretium, tortor sit amet condimentum tempus, diam nulla feugiat purus, a facilisis mi nulla at felis. Donec mattis a metus id porta."
             ),
             canRetry: true,
+            canPause: false,
+            canResume: false,
             canOpen: false
         ),
         Transfer(
@@ -164,6 +172,8 @@ public struct PreviewsData {
             speedDisplay: nil,
             state: .done,
             canRetry: false,
+            canPause: false,
+            canResume: false,
             canOpen: true
         ),
     ]
@@ -37,6 +37,7 @@ use vault_core::{
     repo_files_move::state as repo_files_move_state,
     repo_files_read,
     repo_remove::state as repo_remove_state,
+    repo_search::state as repo_search_state,
     repo_unlock::state as repo_unlock_state,
     repos::{self, selectors as repos_selectors, state as repos_state},
     selection::state as selection_state,
@@ -1200,6 +1201,62 @@ pub trait RepoRemoved: Send + Sync + Debug {
     fn on_removed(&self);
 }
 
+// repo_search
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepoSearchResult {
+    pub encrypted_path: String,
+    pub path: String,
+    pub name: String,
+    pub typ: RepoFileType,
+    pub size_display: Option<String>,
+    pub modified: Option<i64>,
+    pub content_matched: bool,
+}
+
+impl<'a> From<&repo_search_state::RepoSearchResult<'a>> for RepoSearchResult {
+    fn from(result: &repo_search_state::RepoSearchResult<'a>) -> Self {
+        Self {
+            encrypted_path: result.encrypted_path.0.clone(),
+            path: result.entry.path.0.clone(),
+            name: result
+                .entry
+                .path
+                .0
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_owned(),
+            typ: match result.entry.typ {
+                repo_search_state::RepoSearchEntryType::Dir => RepoFileType::Dir,
+                repo_search_state::RepoSearchEntryType::File => RepoFileType::File,
+            },
+            size_display: result.entry.size.map(files::file_size::size_display),
+            modified: result.entry.modified,
+            content_matched: result.content_matched,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepoSearchInfo {
+    pub status: Status,
+    pub indexed_count: u32,
+    pub query: String,
+    pub results: Vec<RepoSearchResult>,
+}
+
+impl<'a> From<&repo_search_state::RepoSearchInfo<'a>> for RepoSearchInfo {
+    fn from(info: &repo_search_state::RepoSearchInfo<'a>) -> Self {
+        Self {
+            status: (&info.status).into(),
+            indexed_count: info.indexed_count as u32,
+            query: info.query.to_owned(),
+            results: info.results.iter().map(Into::into).collect(),
+        }
+    }
+}
+
 // repo_files
 
 #[derive(Clone, Debug, PartialEq)]
@@ -1362,6 +1419,7 @@ pub enum TransferState {
     Waiting,
     Processing,
     Transferring,
+    Paused,
     Failed { error: String },
     Done,
 }
@@ -1372,6 +1430,7 @@ impl From<&transfers_state::TransferState> for TransferState {
             transfers_state::TransferState::Waiting => Self::Waiting,
             transfers_state::TransferState::Processing => Self::Processing,
             transfers_state::TransferState::Transferring => Self::Transferring,
+            transfers_state::TransferState::Paused => Self::Paused,
             transfers_state::TransferState::Failed { error } => Self::Failed {
                 error: error.user_error(),
             },
@@ -1395,6 +1454,8 @@ pub struct Transfer {
     pub speed_display: Option<String>,
     pub state: TransferState,
     pub can_retry: bool,
+    pub can_pause: bool,
+    pub can_resume: bool,
     pub can_open: bool,
 }
 
@@ -1426,6 +1487,8 @@ impl From<(&transfers_state::Transfer, TimeMillis)> for Transfer {
             }),
             state: (&transfer.state).into(),
             can_retry: transfers_selectors::can_retry(transfer),
+            can_pause: transfers_selectors::can_pause(transfer),
+            can_resume: transfers_selectors::can_resume(transfer),
             can_open: transfers_selectors::can_open(transfer),
         }
     }
@@ -1443,9 +1506,47 @@ pub struct TransfersSummary {
     pub is_transferring: bool,
     pub is_all_done: bool,
     pub can_retry_all: bool,
+    pub can_pause_all: bool,
+    pub can_resume_all: bool,
     pub can_abort_all: bool,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransfersBandwidthLimits {
+    pub total: Option<u64>,
+    pub upload: Option<u64>,
+    pub download: Option<u64>,
+}
+
+impl Into<transfers_state::TransfersBandwidthLimits> for TransfersBandwidthLimits {
+    fn into(self) -> transfers_state::TransfersBandwidthLimits {
+        transfers_state::TransfersBandwidthLimits {
+            total: self.total,
+            upload: self.upload,
+            download: self.download,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransfersBandwidthSchedule {
+    pub start_minute: u32,
+    pub end_minute: u32,
+    pub fixed_utc_offset_minutes: i32,
+    pub limits: TransfersBandwidthLimits,
+}
+
+impl Into<transfers_state::TransfersBandwidthSchedule> for TransfersBandwidthSchedule {
+    fn into(self) -> transfers_state::TransfersBandwidthSchedule {
+        transfers_state::TransfersBandwidthSchedule {
+            start_minute: self.start_minute,
+            end_minute: self.end_minute,
+            fixed_utc_offset_minutes: self.fixed_utc_offset_minutes,
+            limits: self.limits.into(),
+        }
+    }
+}
+
 pub trait TransfersDownloadOpen: Send + Sync + Debug {
     fn on_open(&self, local_file_path: String, content_type: Option<String>);
 }
@@ -1735,6 +1836,7 @@ struct SubscriptionData {
     repo_create_info: Data<Option<RepoCreateInfo>>,
     repo_unlock_info: Data<Option<RepoUnlockInfo>>,
     repo_remove_info: Data<Option<RepoRemoveInfo>>,
+    repo_search_info: Data<Option<RepoSearchInfo>>,
     repo_files_file: Data<Option<RepoFile>>,
     transfers_is_active: Data<bool>,
     transfers_summary: Data<TransfersSummary>,
@@ -2624,6 +2726,54 @@ impl MobileVault {
         self.vault.repo_remove_destroy(remove_id)
     }
 
+    // repo_search
+
+    pub fn repo_search_create(&self, repo_id: String) -> u32 {
+        self.vault.repo_search_create(RepoId(repo_id))
+    }
+
+    pub fn repo_search_info_subscribe(
+        &self,
+        search_id: u32,
+        cb: Box<dyn SubscriptionCallback>,
+    ) -> u32 {
+        self.subscribe(
+            &[Event::RepoSearch],
+            cb,
+            self.subscription_data.repo_search_info.clone(),
+            move |vault| {
+                vault.with_state(|state| {
+                    vault_core::repo_search::selectors::select_info(state, search_id)
+                        .as_ref()
+                        .map(Into::into)
+                })
+            },
+        )
+    }
+
+    pub fn repo_search_info_data(&self, id: u32) -> Option<RepoSearchInfo> {
+        self.get_data(id, self.subscription_data.repo_search_info.clone())
+            .flatten()
+    }
+
+    pub fn repo_search_load_index(self: Arc<Self>, repo_id: String, include_content: bool) {
+        self.clone().spawn(async move {
+            // errors are displayed in search info
+            let _ = self
+                .vault
+                .repo_search_load_index(&RepoId(repo_id), include_content)
+                .await;
+        });
+    }
+
+    pub fn repo_search_set_query(&self, search_id: u32, query: String) {
+        self.vault.repo_search_set_query(search_id, query)
+    }
+
+    pub fn repo_search_destroy(&self, search_id: u32) {
+        self.vault.repo_search_destroy(search_id)
+    }
+
     // repo_files
 
     pub fn repo_files_file_subscribe(
@@ -2733,6 +2883,8 @@ impl MobileVault {
                         is_transferring: selectors::select_is_transferring(state),
                         is_all_done: selectors::select_is_all_done(state),
                         can_retry_all: selectors::select_can_retry_all(state),
+                        can_pause_all: selectors::select_can_pause_all(state),
+                        can_resume_all: selectors::select_can_resume_all(state),
                         can_abort_all: selectors::select_can_abort_all(state),
                     }
                 })
@@ -3016,6 +3168,39 @@ impl MobileVault {
         self.vault.transfers_retry_all();
     }
 
+    pub fn transfers_pause(&self, id: u32) {
+        self.vault.transfers_pause(id);
+    }
+
+    pub fn transfers_pause_all(&self) {
+        self.vault.transfers_pause_all();
+    }
+
+    pub fn transfers_resume(&self, id: u32) {
+        self.vault.transfers_resume(id);
+    }
+
+    pub fn transfers_resume_all(&self) {
+        self.vault.transfers_resume_all();
+    }
+
+    pub fn transfers_move_to_front(&self, id: u32) {
+        self.vault.transfers_move_to_front(id);
+    }
+
+    pub fn transfers_set_bandwidth_limits(
+        &self,
+        limits: TransfersBandwidthLimits,
+        schedule: Option<TransfersBandwidthSchedule>,
+    ) {
+        self.vault
+            .transfers_set_bandwidth_limits(limits.into(), schedule.map(Into::into));
+    }
+
+    pub fn transfers_set_verify_uploads(&self, verify_uploads: bool) {
+        self.vault.transfers_set_verify_uploads(verify_uploads);
+    }
+
     pub fn transfers_open(self: Arc<Self>, id: u32) {
         self.clone()
             .spawn_result(async move { self.vault.transfers_open(id).await });
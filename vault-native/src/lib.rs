@@ -1,6 +1,9 @@
 pub mod file_utils;
+pub mod local_crypt;
+pub mod local_storage;
 pub mod native_eventstream_websocket_client;
 pub mod native_http_client;
 pub mod native_runtime;
-pub mod vault;
+pub mod repo_sync;
 pub mod transfers;
+pub mod vault;
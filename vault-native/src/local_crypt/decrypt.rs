@@ -0,0 +1,367 @@
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use vault_core::{
+    cipher::Cipher,
+    types::{DecryptedPath, EncryptedName, EncryptedPath},
+    utils::{repo_encrypted_path_utils, repo_path_utils},
+};
+use vault_crypto::{constants::BLOCK_DATA_SIZE, CipherError};
+
+use crate::file_utils::cleanup_name;
+
+use super::errors::LocalDecryptError;
+
+#[derive(Debug, Clone, Default)]
+pub struct LocalDecryptOptions {
+    /// Overwrite existing files in the destination directory
+    pub overwrite: bool,
+    /// Keep the decrypted part of a file that has a corrupt block
+    pub keep_partial: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalDecryptEvent {
+    DirCreated {
+        path: DecryptedPath,
+    },
+    FileDecrypted {
+        path: DecryptedPath,
+        size: u64,
+    },
+    Failed {
+        encrypted_path: EncryptedPath,
+        path: Option<DecryptedPath>,
+        error: LocalDecryptError,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocalDecryptReport {
+    pub dirs_count: usize,
+    pub files_count: usize,
+    pub bytes: u64,
+    pub failed_count: usize,
+}
+
+/// Decrypts a local copy of an encrypted directory (e.g. made with `rclone
+/// copy`) from `src` into `dst` without using the remote. Names that cannot be
+/// decrypted and files with corrupt blocks are reported with
+/// `LocalDecryptEvent::Failed` and skipped, only errors reading `src` or
+/// creating `dst` abort the whole run.
+pub fn decrypt_dir(
+    cipher: &Cipher,
+    src: &Path,
+    dst: &Path,
+    options: &LocalDecryptOptions,
+    on_event: &mut dyn FnMut(LocalDecryptEvent),
+) -> io::Result<LocalDecryptReport> {
+    if !fs::metadata(src)?.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a directory: {}", src.display()),
+        ));
+    }
+
+    fs::create_dir_all(dst)?;
+
+    let mut report = LocalDecryptReport::default();
+
+    let mut on_event = |event: LocalDecryptEvent| {
+        match &event {
+            LocalDecryptEvent::DirCreated { .. } => report.dirs_count += 1,
+            LocalDecryptEvent::FileDecrypted { size, .. } => {
+                report.files_count += 1;
+                report.bytes += size;
+            }
+            LocalDecryptEvent::Failed { .. } => report.failed_count += 1,
+        }
+
+        on_event(event);
+    };
+
+    decrypt_dir_entries(
+        cipher,
+        src,
+        dst,
+        &EncryptedPath("/".into()),
+        options,
+        &mut on_event,
+    )?;
+
+    Ok(report)
+}
+
+fn decrypt_dir_entries(
+    cipher: &Cipher,
+    src: &Path,
+    dst: &Path,
+    encrypted_path: &EncryptedPath,
+    options: &LocalDecryptOptions,
+    on_event: &mut dyn FnMut(LocalDecryptEvent),
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(src)?.collect::<io::Result<Vec<_>>>()?;
+
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let entry_encrypted_path = repo_encrypted_path_utils::join_path_name(
+            encrypted_path,
+            &EncryptedName(entry.file_name().to_string_lossy().to_string()),
+        );
+
+        let fail =
+            |path: Option<DecryptedPath>, error: LocalDecryptError| LocalDecryptEvent::Failed {
+                encrypted_path: entry_encrypted_path.clone(),
+                path,
+                error,
+            };
+
+        if entry.file_name().to_str().is_none() {
+            on_event(fail(None, LocalDecryptError::InvalidEncryptedName));
+            continue;
+        }
+
+        // dir names might not be encrypted (directory_name_encryption = false)
+        let is_dir = fs::metadata(entry.path())
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
+
+        let decrypted_path = if is_dir {
+            cipher.decrypt_dir_path(&entry_encrypted_path)
+        } else {
+            cipher.decrypt_path(&entry_encrypted_path)
+        };
+
+        let path = match decrypted_path {
+            Ok(path) => path,
+            Err(err) => {
+                on_event(fail(None, err.into()));
+                continue;
+            }
+        };
+
+        let name = match repo_path_utils::path_to_name(&path) {
+            Some(name) => cleanup_name(&name.0),
+            None => {
+                on_event(fail(None, LocalDecryptError::InvalidEncryptedName));
+                continue;
+            }
+        };
+
+        let entry_src = entry.path();
+        let entry_dst = dst.join(name);
+
+        // follow symlinks
+        let metadata = match fs::metadata(&entry_src) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                on_event(fail(Some(path), err.into()));
+                continue;
+            }
+        };
+
+        if metadata.is_dir() {
+            match fs::create_dir(&entry_dst) {
+                Ok(()) => on_event(LocalDecryptEvent::DirCreated { path: path.clone() }),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists && entry_dst.is_dir() => {}
+                Err(err) => {
+                    on_event(fail(Some(path), err.into()));
+                    continue;
+                }
+            }
+
+            if let Err(err) = decrypt_dir_entries(
+                cipher,
+                &entry_src,
+                &entry_dst,
+                &entry_encrypted_path,
+                options,
+                on_event,
+            ) {
+                on_event(fail(Some(path), err.into()));
+            }
+        } else if metadata.is_file() {
+            match decrypt_file(cipher, &entry_src, &entry_dst, options) {
+                Ok(size) => on_event(LocalDecryptEvent::FileDecrypted { path, size }),
+                Err(err) => on_event(fail(Some(path), err)),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decrypts a single file and returns the decrypted size.
+pub fn decrypt_file(
+    cipher: &Cipher,
+    src: &Path,
+    dst: &Path,
+    options: &LocalDecryptOptions,
+) -> Result<u64, LocalDecryptError> {
+    let src_file = fs::File::open(src)?;
+
+    let mut dst_file = if options.overwrite {
+        fs::File::create(dst)?
+    } else {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dst)?
+    };
+
+    let res = copy_decrypted(cipher, src_file, &mut dst_file);
+
+    drop(dst_file);
+
+    if res.is_err() && !options.keep_partial {
+        let _ = fs::remove_file(dst);
+    }
+
+    res
+}
+
+fn copy_decrypted(
+    cipher: &Cipher,
+    src_file: fs::File,
+    dst_file: &mut fs::File,
+) -> Result<u64, LocalDecryptError> {
+    let mut reader = cipher.decrypt_reader_sync(io::BufReader::new(src_file));
+    let mut buf = vec![0; BLOCK_DATA_SIZE];
+    let mut size: u64 = 0;
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                return Err(
+                    match err
+                        .get_ref()
+                        .and_then(|inner| inner.downcast_ref::<CipherError>())
+                    {
+                        Some(cipher_err) => LocalDecryptError::CorruptBlock {
+                            block: size / BLOCK_DATA_SIZE as u64,
+                            size,
+                            message: cipher_err.to_string(),
+                        },
+                        None => err.into(),
+                    },
+                )
+            }
+        };
+
+        dst_file.write_all(&buf[..n])?;
+
+        size += n as u64;
+    }
+
+    dst_file.flush()?;
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::Read,
+        path::{Path, PathBuf},
+    };
+
+    use similar_asserts::assert_eq;
+    use vault_core::{
+        cipher::Cipher,
+        types::{DecryptedPath, EncryptedPath},
+    };
+
+    use super::{decrypt_dir, LocalDecryptEvent, LocalDecryptOptions, LocalDecryptReport};
+
+    fn temp_dir() -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("vault-local-decrypt-{}", uuid::Uuid::new_v4()));
+
+        fs::create_dir_all(&path).unwrap();
+
+        path
+    }
+
+    fn write_encrypted(cipher: &Cipher, path: &Path, content: &[u8]) {
+        let mut encrypted = Vec::new();
+
+        cipher
+            .encrypt_reader_sync(content)
+            .read_to_end(&mut encrypted)
+            .unwrap();
+
+        fs::write(path, encrypted).unwrap();
+    }
+
+    #[test]
+    fn test_decrypt_dir() {
+        let cipher = Cipher::new(vault_crypto::Cipher::new("password", Some("salt")));
+
+        let src = temp_dir();
+        let dst = temp_dir();
+
+        let dir_name = cipher.encrypt_path(&DecryptedPath("/dir".into())).0;
+        fs::create_dir(src.join(&dir_name[1..])).unwrap();
+
+        let file_path = cipher
+            .encrypt_path(&DecryptedPath("/dir/file.txt".into()))
+            .0;
+        write_encrypted(&cipher, &src.join(&file_path[1..]), b"hello");
+
+        let corrupt_path = cipher.encrypt_path(&DecryptedPath("/corrupt.txt".into())).0;
+        let mut corrupt = Vec::new();
+        cipher
+            .encrypt_reader_sync(&vec![1; 100 * 1024][..])
+            .read_to_end(&mut corrupt)
+            .unwrap();
+        let corrupt_len = corrupt.len();
+        corrupt[corrupt_len - 1] ^= 0xff;
+        fs::write(src.join(&corrupt_path[1..]), corrupt).unwrap();
+
+        fs::write(src.join("notencrypted"), b"plain").unwrap();
+
+        let mut events = Vec::new();
+
+        let report = decrypt_dir(
+            &cipher,
+            &src,
+            &dst,
+            &LocalDecryptOptions::default(),
+            &mut |event| events.push(event),
+        )
+        .unwrap();
+
+        assert_eq!(
+            report,
+            LocalDecryptReport {
+                dirs_count: 1,
+                files_count: 1,
+                bytes: 5,
+                failed_count: 2,
+            }
+        );
+        assert_eq!(fs::read(dst.join("dir/file.txt")).unwrap(), b"hello");
+        assert!(!dst.join("corrupt.txt").exists());
+        assert!(events.iter().any(|event| matches!(
+            event,
+            LocalDecryptEvent::Failed { encrypted_path, path: None, .. }
+                if encrypted_path == &EncryptedPath("/notencrypted".into())
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            LocalDecryptEvent::Failed { path: Some(path), .. }
+                if path == &DecryptedPath("/corrupt.txt".into())
+        )));
+
+        fs::remove_dir_all(src).unwrap();
+        fs::remove_dir_all(dst).unwrap();
+    }
+}
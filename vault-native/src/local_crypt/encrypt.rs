@@ -0,0 +1,366 @@
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use vault_core::{
+    cipher::Cipher,
+    rclone,
+    types::{DecryptedName, DecryptedPath, EncryptedPath},
+    utils::{name_utils, repo_path_utils},
+};
+
+use super::errors::LocalEncryptError;
+
+#[derive(Debug, Clone, Default)]
+pub struct LocalEncryptOptions {
+    /// Overwrite existing files in the destination directory
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalEncryptEvent {
+    DirCreated {
+        path: DecryptedPath,
+    },
+    FileEncrypted {
+        path: DecryptedPath,
+        encrypted_path: EncryptedPath,
+        size: u64,
+    },
+    Failed {
+        path: DecryptedPath,
+        error: LocalEncryptError,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocalEncryptReport {
+    pub dirs_count: usize,
+    pub files_count: usize,
+    pub bytes: u64,
+    pub failed_count: usize,
+}
+
+/// Encrypts a local directory `src` into `dst` with the same name and data
+/// encryption as the remote repos, so that `dst` can be uploaded with other
+/// tools (e.g. rclone) or shipped on a disk. Files that cannot be read or
+/// written are reported with `LocalEncryptEvent::Failed` and skipped.
+pub fn encrypt_dir(
+    cipher: &Cipher,
+    src: &Path,
+    dst: &Path,
+    options: &LocalEncryptOptions,
+    on_event: &mut dyn FnMut(LocalEncryptEvent),
+) -> io::Result<LocalEncryptReport> {
+    if !fs::metadata(src)?.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a directory: {}", src.display()),
+        ));
+    }
+
+    fs::create_dir_all(dst)?;
+
+    let mut report = LocalEncryptReport::default();
+
+    let mut on_event = |event: LocalEncryptEvent| {
+        match &event {
+            LocalEncryptEvent::DirCreated { .. } => report.dirs_count += 1,
+            LocalEncryptEvent::FileEncrypted { size, .. } => {
+                report.files_count += 1;
+                report.bytes += size;
+            }
+            LocalEncryptEvent::Failed { .. } => report.failed_count += 1,
+        }
+
+        on_event(event);
+    };
+
+    encrypt_dir_entries(
+        cipher,
+        src,
+        dst,
+        &DecryptedPath("/".into()),
+        options,
+        &mut on_event,
+    )?;
+
+    Ok(report)
+}
+
+fn encrypt_dir_entries(
+    cipher: &Cipher,
+    src: &Path,
+    dst: &Path,
+    path: &DecryptedPath,
+    options: &LocalEncryptOptions,
+    on_event: &mut dyn FnMut(LocalEncryptEvent),
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(src)?.collect::<io::Result<Vec<_>>>()?;
+
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(name) => {
+                on_event(LocalEncryptEvent::Failed {
+                    path: repo_path_utils::join_path_name(
+                        path,
+                        &DecryptedName(name.to_string_lossy().to_string()),
+                    ),
+                    error: LocalEncryptError::InvalidName,
+                });
+                continue;
+            }
+        };
+
+        let entry_path = repo_path_utils::join_path_name(path, &DecryptedName(name.clone()));
+
+        // other clients could not decrypt such names
+        if let Err(err) = name_utils::validate_name(&name) {
+            on_event(LocalEncryptEvent::Failed {
+                path: entry_path,
+                error: err.into(),
+            });
+            continue;
+        }
+
+        let entry_src = entry.path();
+
+        // follow symlinks
+        let metadata = match fs::metadata(&entry_src) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                on_event(LocalEncryptEvent::Failed {
+                    path: entry_path,
+                    error: err.into(),
+                });
+                continue;
+            }
+        };
+
+        let encrypted_name = if metadata.is_dir() {
+            cipher.encrypt_dir_name(&DecryptedName(name))
+        } else {
+            cipher.encrypt_filename(&DecryptedName(name))
+        };
+        let entry_dst = dst.join(OsString::from(encrypted_name.0));
+
+        if metadata.is_dir() {
+            match fs::create_dir(&entry_dst) {
+                Ok(()) => on_event(LocalEncryptEvent::DirCreated {
+                    path: entry_path.clone(),
+                }),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists && entry_dst.is_dir() => {}
+                Err(err) => {
+                    on_event(LocalEncryptEvent::Failed {
+                        path: entry_path,
+                        error: err.into(),
+                    });
+                    continue;
+                }
+            }
+
+            if let Err(err) = encrypt_dir_entries(
+                cipher,
+                &entry_src,
+                &entry_dst,
+                &entry_path,
+                options,
+                on_event,
+            ) {
+                on_event(LocalEncryptEvent::Failed {
+                    path: entry_path,
+                    error: err.into(),
+                });
+            }
+        } else if metadata.is_file() {
+            match encrypt_file(cipher, &entry_src, &entry_dst, options) {
+                Ok(size) => on_event(LocalEncryptEvent::FileEncrypted {
+                    encrypted_path: cipher.encrypt_path(&entry_path),
+                    path: entry_path,
+                    size,
+                }),
+                Err(err) => on_event(LocalEncryptEvent::Failed {
+                    path: entry_path,
+                    error: err,
+                }),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Encrypts a single file and returns the plaintext size.
+pub fn encrypt_file(
+    cipher: &Cipher,
+    src: &Path,
+    dst: &Path,
+    options: &LocalEncryptOptions,
+) -> Result<u64, LocalEncryptError> {
+    let src_file = fs::File::open(src)?;
+    let size = src_file.metadata()?.len();
+
+    let mut dst_file = if options.overwrite {
+        fs::File::create(dst)?
+    } else {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dst)?
+    };
+
+    let res = io::copy(
+        &mut cipher.encrypt_reader_sync(io::BufReader::new(src_file)),
+        &mut dst_file,
+    )
+    .and_then(|_| dst_file.flush());
+
+    drop(dst_file);
+
+    match res {
+        Ok(()) => Ok(size),
+        Err(err) => {
+            let _ = fs::remove_file(dst);
+
+            Err(err.into())
+        }
+    }
+}
+
+/// Path of the rclone config written next to the encrypted directory (e.g.
+/// `/backups/vault.rclone.conf` for `/backups/vault`).
+pub fn rclone_config_path(dst: &Path) -> PathBuf {
+    let mut name = dst
+        .file_name()
+        .map(|name| name.to_owned())
+        .unwrap_or_else(|| OsString::from("vault"));
+
+    name.push(".rclone.conf");
+
+    dst.with_file_name(name)
+}
+
+pub fn write_rclone_config(path: &Path, config: &rclone::config::Config) -> io::Result<()> {
+    fs::write(path, rclone::config::generate_config(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use similar_asserts::assert_eq;
+    use vault_core::{cipher::Cipher, common::errors::InvalidNameError, types::DecryptedPath};
+
+    use crate::local_crypt::{
+        decrypt::{decrypt_dir, LocalDecryptOptions},
+        errors::LocalEncryptError,
+    };
+
+    use super::{encrypt_dir, LocalEncryptEvent, LocalEncryptOptions, LocalEncryptReport};
+
+    fn temp_dir() -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("vault-local-encrypt-{}", uuid::Uuid::new_v4()));
+
+        fs::create_dir_all(&path).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_encrypt_dir() {
+        let cipher = Cipher::new(vault_crypto::Cipher::new("password", Some("salt")));
+
+        let src = temp_dir();
+        let encrypted = temp_dir();
+        let decrypted = temp_dir();
+
+        fs::create_dir_all(src.join("dir/empty")).unwrap();
+        fs::write(src.join("dir/file.txt"), b"hello").unwrap();
+        fs::write(src.join("large.bin"), vec![7; 200 * 1024]).unwrap();
+
+        let report = encrypt_dir(
+            &cipher,
+            &src,
+            &encrypted,
+            &LocalEncryptOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        assert_eq!(
+            report,
+            LocalEncryptReport {
+                dirs_count: 2,
+                files_count: 2,
+                bytes: 5 + 200 * 1024,
+                failed_count: 0,
+            }
+        );
+        assert!(!encrypted.join("dir").exists());
+
+        decrypt_dir(
+            &cipher,
+            &encrypted,
+            &decrypted,
+            &LocalDecryptOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(decrypted.join("dir/file.txt")).unwrap(), b"hello");
+        assert_eq!(
+            fs::read(decrypted.join("large.bin")).unwrap(),
+            vec![7; 200 * 1024]
+        );
+        assert!(decrypted.join("dir/empty").is_dir());
+
+        fs::remove_dir_all(src).unwrap();
+        fs::remove_dir_all(encrypted).unwrap();
+        fs::remove_dir_all(decrypted).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_encrypt_dir_invalid_name() {
+        let cipher = Cipher::new(vault_crypto::Cipher::new("password", Some("salt")));
+
+        let src = temp_dir();
+        let encrypted = temp_dir();
+
+        fs::write(src.join("back\\slash.txt"), b"hello").unwrap();
+        fs::write(src.join("file.txt"), b"hello").unwrap();
+
+        let mut events = Vec::new();
+
+        let report = encrypt_dir(
+            &cipher,
+            &src,
+            &encrypted,
+            &LocalEncryptOptions::default(),
+            &mut |event| events.push(event),
+        )
+        .unwrap();
+
+        assert_eq!(report.files_count, 1);
+        assert_eq!(report.failed_count, 1);
+        assert_eq!(
+            events[0],
+            LocalEncryptEvent::Failed {
+                path: DecryptedPath("/back\\slash.txt".into()),
+                error: LocalEncryptError::InvalidNameError(InvalidNameError::new(
+                    "back\\slash.txt"
+                )),
+            }
+        );
+
+        fs::remove_dir_all(src).unwrap();
+        fs::remove_dir_all(encrypted).unwrap();
+    }
+}
@@ -0,0 +1,76 @@
+use thiserror::Error;
+
+use vault_core::{
+    cipher::errors::DecryptFilenameError, common::errors::InvalidNameError, user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LocalDecryptError {
+    #[error("file name is not a valid encrypted name")]
+    InvalidEncryptedName,
+    #[error("{0}")]
+    DecryptFilenameError(#[from] DecryptFilenameError),
+    #[error("corrupt block {block} (decrypted {size} bytes): {message}")]
+    CorruptBlock {
+        block: u64,
+        size: u64,
+        message: String,
+    },
+    #[error("destination already exists")]
+    AlreadyExists,
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for LocalDecryptError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::InvalidEncryptedName => self.to_string(),
+            Self::DecryptFilenameError(err) => err.user_error(),
+            Self::CorruptBlock { .. } => self.to_string(),
+            Self::AlreadyExists => self.to_string(),
+            Self::IOError(err) => err.clone(),
+        }
+    }
+}
+
+impl From<std::io::Error> for LocalDecryptError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+            _ => Self::IOError(err.to_string()),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LocalEncryptError {
+    #[error("file name is not a valid Unicode text")]
+    InvalidName,
+    #[error("{0}")]
+    InvalidNameError(#[from] InvalidNameError),
+    #[error("destination already exists")]
+    AlreadyExists,
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for LocalEncryptError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::InvalidName => self.to_string(),
+            Self::InvalidNameError(err) => err.user_error(),
+            Self::AlreadyExists => self.to_string(),
+            Self::IOError(err) => err.clone(),
+        }
+    }
+}
+
+impl From<std::io::Error> for LocalEncryptError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+            _ => Self::IOError(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::io;
+
+use vault_core::remote::{ApiErrorCode, RemoteError};
+
+/// Local storage errors use the same API error codes as the Koofr API so
+/// that the services handle them the same way as remote errors.
+pub struct LocalStorageErrors;
+
+impl LocalStorageErrors {
+    pub fn not_found() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::NotFound, "File not found")
+    }
+
+    pub fn mount_not_found() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::NotFound, "Mount not found")
+    }
+
+    pub fn repo_not_found() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::NotFound, "Safe Box not found")
+    }
+
+    pub fn already_exists() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::AlreadyExists, "File already exists")
+    }
+
+    pub fn conflict(message: &str) -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::Conflict, message)
+    }
+
+    pub fn not_dir() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::NotDir, "Directory expected")
+    }
+
+    pub fn not_file() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::Other("NotFile".into()), "Not a file")
+    }
+
+    pub fn invalid_path() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::InvalidPath, "Invalid path")
+    }
+
+    pub fn move_into_self() -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::MoveIntoSelf, "Cannot move into itself")
+    }
+
+    pub fn copy_into_self() -> RemoteError {
+        RemoteError::from_code(
+            ApiErrorCode::Other("CopyIntoSelf".into()),
+            "Cannot copy into itself",
+        )
+    }
+
+    pub fn io_error(err: io::Error) -> RemoteError {
+        match err.kind() {
+            io::ErrorKind::NotFound => Self::not_found(),
+            io::ErrorKind::AlreadyExists => Self::already_exists(),
+            _ => RemoteError::from_code(ApiErrorCode::Other("IOError".into()), &err.to_string()),
+        }
+    }
+
+    pub fn sidecar_error(message: &str) -> RemoteError {
+        RemoteError::from_code(ApiErrorCode::Other("SidecarError".into()), message)
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+use vault_core::eventstream::{Message, Request, WebSocketClient};
+
+use super::{local_fs::LocalFs, local_watcher::spawn_local_watcher};
+
+struct LocalEventstreamConnection {
+    messages: mpsc::UnboundedSender<Message>,
+    next_listener_id: i64,
+    listeners: HashMap<i64, JoinHandle<()>>,
+}
+
+/// Eventstream for `LocalStorageBackend`. It answers the eventstream requests
+/// the same way as the Koofr eventstream server, but the events come from
+/// local filesystem notifications instead of the websocket.
+pub struct LocalEventstreamWebSocketClient {
+    tokio_runtime: Arc<tokio::runtime::Runtime>,
+    local_fs: Arc<LocalFs>,
+
+    connection: Arc<Mutex<Option<LocalEventstreamConnection>>>,
+}
+
+impl LocalEventstreamWebSocketClient {
+    pub fn new(tokio_runtime: Arc<tokio::runtime::Runtime>, local_fs: Arc<LocalFs>) -> Self {
+        Self {
+            tokio_runtime,
+            local_fs,
+
+            connection: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl WebSocketClient for LocalEventstreamWebSocketClient {
+    fn open(
+        &self,
+        _url: String,
+        on_open: Box<dyn Fn() + Send + Sync + 'static>,
+        on_message: Box<dyn Fn(String) + Send + Sync + 'static>,
+        on_close: Box<dyn Fn() + Send + Sync + 'static>,
+    ) {
+        let (messages, mut messages_receiver) = mpsc::unbounded_channel();
+
+        *self.connection.lock().unwrap() = Some(LocalEventstreamConnection {
+            messages,
+            next_listener_id: 1,
+            listeners: HashMap::new(),
+        });
+
+        // messages are delivered from a single task so that a listener is
+        // registered before its events arrive
+        self.tokio_runtime.spawn(async move {
+            on_open();
+
+            while let Some(message) = messages_receiver.recv().await {
+                on_message(serde_json::to_string(&message).unwrap());
+            }
+
+            on_close();
+        });
+    }
+
+    fn send(&self, data: String) {
+        let request = match serde_json::from_str::<Request>(&data) {
+            Ok(request) => request,
+            Err(err) => {
+                log::debug!("LocalEventstreamWebSocketClient request error: {:?}", err);
+
+                return;
+            }
+        };
+
+        let mut connection = self.connection.lock().unwrap();
+
+        let connection = match connection.as_mut() {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        match request {
+            Request::Auth { .. } => {
+                let _ = connection.messages.send(Message::Authenticated);
+            }
+            Request::Register {
+                request_id,
+                mount_id,
+                path,
+            } => {
+                let listener_id = connection.next_listener_id;
+                connection.next_listener_id += 1;
+
+                let _ = connection.messages.send(Message::Registered {
+                    request_id,
+                    listener_id,
+                });
+
+                if self.local_fs.check_mount_id(&mount_id).is_ok() {
+                    if let Some(listener) = spawn_local_watcher(
+                        &self.tokio_runtime,
+                        self.local_fs.clone(),
+                        mount_id,
+                        path,
+                        listener_id,
+                        connection.messages.clone(),
+                    ) {
+                        connection.listeners.insert(listener_id, listener);
+                    }
+                }
+            }
+            Request::Deregister { listener_id } => {
+                if let Some(listener) = connection.listeners.remove(&listener_id) {
+                    listener.abort();
+                }
+
+                let _ = connection
+                    .messages
+                    .send(Message::Deregistered { listener_id });
+            }
+            Request::Ping | Request::Unknown => {}
+        }
+    }
+
+    fn close(&self) {
+        // dropping the connection ends the messages task which calls on_close
+        if let Some(connection) = self.connection.lock().unwrap().take() {
+            for listener in connection.listeners.into_values() {
+                listener.abort();
+            }
+        }
+    }
+}
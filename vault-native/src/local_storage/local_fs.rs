@@ -0,0 +1,828 @@
+use std::{
+    collections::HashMap,
+    fs::{self, Metadata},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use uuid::Uuid;
+use vault_core::{
+    files::content_type::ext_to_content_type,
+    remote::{
+        models, remote::RemoteFileTagsSetConditions, ApiErrorCode, RemoteError,
+        RemoteFileMoveConditions, RemoteFileUploadConflictResolution,
+    },
+    remote_files_tags::set_tags::set_tags,
+    types::{MountId, RemoteName, RemotePath, RepoId},
+    utils::{name_utils, remote_path_utils},
+};
+
+use crate::{
+    repo_sync::local::{hash_file, modified_millis},
+    transfers::file_uploadable::file_size,
+};
+
+use super::{
+    errors::LocalStorageErrors,
+    sidecar::{
+        is_path_or_child, load_sidecar, save_sidecar, LocalIndex, LocalRepos, INDEX_FILE_NAME,
+        REPOS_FILE_NAME, SIDECAR_NAME_PREFIX,
+    },
+};
+
+/// The local directory is exposed as a single mount with this id.
+pub const LOCAL_MOUNT_ID: &str = "local";
+
+/// LocalFs maps remote paths to a local directory. Everything the filesystem
+/// cannot store (hashes, tags and repos) is kept in sidecar files in the root
+/// directory.
+///
+/// All methods are blocking.
+pub struct LocalFs {
+    root: PathBuf,
+    /// Serializes the sidecar updates and the file changes that depend on
+    /// them.
+    lock: Mutex<()>,
+}
+
+impl LocalFs {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn mount_id(&self) -> MountId {
+        MountId(LOCAL_MOUNT_ID.into())
+    }
+
+    pub fn check_mount_id(&self, mount_id: &MountId) -> Result<(), RemoteError> {
+        if mount_id.0 == LOCAL_MOUNT_ID {
+            Ok(())
+        } else {
+            Err(LocalStorageErrors::mount_not_found())
+        }
+    }
+
+    pub fn mount(&self) -> models::Mount {
+        models::Mount {
+            id: self.mount_id(),
+            name: RemoteName(
+                self.root
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "Local".into()),
+            ),
+            typ: "device".into(),
+            origin: "desktop".into(),
+            online: true,
+            is_primary: true,
+            space_total: None,
+            space_used: None,
+        }
+    }
+
+    /// Sidecar and temporary names are not valid remote paths.
+    pub fn local_path(&self, path: &RemotePath) -> Result<PathBuf, RemoteError> {
+        if !path.0.starts_with('/') {
+            return Err(LocalStorageErrors::invalid_path());
+        }
+
+        let mut local_path = self.root.clone();
+
+        for name in path.0.split('/').filter(|name| !name.is_empty()) {
+            if name == "." || name == ".." || name.starts_with(SIDECAR_NAME_PREFIX) {
+                return Err(LocalStorageErrors::invalid_path());
+            }
+
+            local_path.push(name);
+        }
+
+        Ok(local_path)
+    }
+
+    /// Temporary files are created next to the destination so that they can
+    /// be renamed into place.
+    pub fn temp_path(&self, parent_path: &RemotePath) -> Result<PathBuf, RemoteError> {
+        Ok(self.local_path(parent_path)?.join(format!(
+            "{}-upload-{}",
+            SIDECAR_NAME_PREFIX,
+            Uuid::new_v4()
+        )))
+    }
+
+    fn with_index<T>(
+        &self,
+        f: impl FnOnce(&mut LocalIndex) -> Result<T, RemoteError>,
+    ) -> Result<T, RemoteError> {
+        let _guard = self.lock.lock().unwrap();
+
+        self.with_index_locked(f)
+    }
+
+    fn with_index_locked<T>(
+        &self,
+        f: impl FnOnce(&mut LocalIndex) -> Result<T, RemoteError>,
+    ) -> Result<T, RemoteError> {
+        let index_path = self.root.join(INDEX_FILE_NAME);
+
+        let mut index: LocalIndex =
+            load_sidecar(&index_path).map_err(|err| LocalStorageErrors::sidecar_error(&err))?;
+        let old_index = index.clone();
+
+        let res = f(&mut index);
+
+        // hashes computed before an error are still valid
+        if index != old_index {
+            save_sidecar(&index_path, &index)
+                .map_err(|err| LocalStorageErrors::sidecar_error(&err))?;
+        }
+
+        res
+    }
+
+    fn with_repos_locked<T>(
+        &self,
+        f: impl FnOnce(&mut LocalRepos) -> Result<T, RemoteError>,
+    ) -> Result<T, RemoteError> {
+        let repos_path = self.root.join(REPOS_FILE_NAME);
+
+        let mut repos: LocalRepos =
+            load_sidecar(&repos_path).map_err(|err| LocalStorageErrors::sidecar_error(&err))?;
+        let old_repos = repos.clone();
+
+        let res = f(&mut repos)?;
+
+        if repos != old_repos {
+            save_sidecar(&repos_path, &repos)
+                .map_err(|err| LocalStorageErrors::sidecar_error(&err))?;
+        }
+
+        Ok(res)
+    }
+
+    fn files_file(
+        &self,
+        index: &mut LocalIndex,
+        path: &RemotePath,
+        local_path: &Path,
+        metadata: &Metadata,
+    ) -> Result<models::FilesFile, RemoteError> {
+        let name = remote_path_utils::path_to_name(path).unwrap_or(RemoteName("".into()));
+        let modified = modified_millis(metadata);
+        let tags = index.get_tags(path);
+
+        if metadata.is_dir() {
+            return Ok(models::FilesFile {
+                name,
+                typ: "dir".into(),
+                modified,
+                size: 0,
+                content_type: "".into(),
+                hash: None,
+                tags,
+            });
+        }
+
+        let size = file_size(metadata);
+
+        let hash = match index.get_hash(path, size, modified) {
+            Some(hash) => hash,
+            None => {
+                let hash = hash_file(local_path).map_err(LocalStorageErrors::io_error)?;
+
+                index.set_hash(path, size, modified, hash.clone());
+
+                hash
+            }
+        };
+
+        let content_type = name_utils::name_to_ext(&name.0)
+            .and_then(|ext| ext_to_content_type(&ext.to_lowercase()).map(str::to_string))
+            .unwrap_or_else(|| "application/octet-stream".into());
+
+        Ok(models::FilesFile {
+            name,
+            typ: "file".into(),
+            modified,
+            size,
+            content_type,
+            hash: Some(hash),
+            tags,
+        })
+    }
+
+    fn get_file_locked(
+        &self,
+        index: &mut LocalIndex,
+        path: &RemotePath,
+    ) -> Result<models::FilesFile, RemoteError> {
+        let local_path = self.local_path(path)?;
+        let metadata = fs::metadata(&local_path).map_err(LocalStorageErrors::io_error)?;
+
+        self.files_file(index, path, &local_path, &metadata)
+    }
+
+    pub fn get_file(&self, path: &RemotePath) -> Result<models::FilesFile, RemoteError> {
+        self.with_index(|index| self.get_file_locked(index, path))
+    }
+
+    pub fn get_bundle(&self, path: &RemotePath) -> Result<models::Bundle, RemoteError> {
+        self.with_index(|index| {
+            let file = self.get_file_locked(index, path)?;
+
+            let files = if file.typ == "dir" {
+                Some(
+                    self.read_children(path)?
+                        .into_iter()
+                        .map(|(child_path, local_path, metadata)| {
+                            self.files_file(index, &child_path, &local_path, &metadata)
+                                .map(files_file_to_bundle_file)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            } else {
+                None
+            };
+
+            Ok(models::Bundle {
+                file: files_file_to_bundle_file(file),
+                files,
+            })
+        })
+    }
+
+    /// Lists files and dirs in the dir, sorted by name. Sidecar files, names
+    /// that are not valid UTF-8 and other file types are skipped.
+    fn read_children(
+        &self,
+        path: &RemotePath,
+    ) -> Result<Vec<(RemotePath, PathBuf, Metadata)>, RemoteError> {
+        let local_path = self.local_path(path)?;
+
+        let mut entries = fs::read_dir(&local_path)
+            .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+            .map_err(LocalStorageErrors::io_error)?;
+
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut children = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let name = match entry.file_name().into_string() {
+                Ok(name) if !name.starts_with(SIDECAR_NAME_PREFIX) => name,
+                _ => continue,
+            };
+
+            // follow symlinks, skip broken ones
+            let metadata = match fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(LocalStorageErrors::io_error(err)),
+            };
+
+            if metadata.is_dir() || metadata.is_file() {
+                children.push((
+                    remote_path_utils::join_path_name(path, &RemoteName(name)),
+                    entry.path(),
+                    metadata,
+                ));
+            }
+        }
+
+        Ok(children)
+    }
+
+    /// Paths of the items are relative to the listed dir, the dir itself is
+    /// the first item with the path `/`.
+    pub fn get_list_recursive(
+        &self,
+        path: &RemotePath,
+    ) -> Result<Vec<models::FilesListRecursiveItem>, RemoteError> {
+        self.with_index(|index| {
+            let file = self.get_file_locked(index, path)?;
+
+            if file.typ != "dir" {
+                return Err(LocalStorageErrors::not_dir());
+            }
+
+            let mut items = vec![models::FilesListRecursiveItem::File {
+                path: RemotePath("/".into()),
+                file,
+            }];
+
+            self.list_recursive_dir(index, path, &RemotePath("/".into()), &mut items)?;
+
+            Ok(items)
+        })
+    }
+
+    fn list_recursive_dir(
+        &self,
+        index: &mut LocalIndex,
+        path: &RemotePath,
+        relative_path: &RemotePath,
+        items: &mut Vec<models::FilesListRecursiveItem>,
+    ) -> Result<(), RemoteError> {
+        for (child_path, local_path, metadata) in self.read_children(path)? {
+            let file = self.files_file(index, &child_path, &local_path, &metadata)?;
+            let child_relative_path = remote_path_utils::join_path_name(relative_path, &file.name);
+            let is_dir = file.typ == "dir";
+
+            items.push(models::FilesListRecursiveItem::File {
+                path: child_relative_path.clone(),
+                file,
+            });
+
+            if is_dir {
+                self.list_recursive_dir(index, &child_path, &child_relative_path, items)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_parent_dir(&self, parent_path: &RemotePath) -> Result<(), RemoteError> {
+        let metadata =
+            fs::metadata(self.local_path(parent_path)?).map_err(LocalStorageErrors::io_error)?;
+
+        if !metadata.is_dir() {
+            return Err(LocalStorageErrors::not_dir());
+        }
+
+        Ok(())
+    }
+
+    fn check_name(&self, name: &RemoteName) -> Result<(), RemoteError> {
+        if name_utils::validate_name(&name.0).is_err() || name.0.starts_with(SIDECAR_NAME_PREFIX) {
+            return Err(LocalStorageErrors::invalid_path());
+        }
+
+        Ok(())
+    }
+
+    fn exists(&self, path: &RemotePath) -> Result<bool, RemoteError> {
+        Ok(fs::symlink_metadata(self.local_path(path)?).is_ok())
+    }
+
+    /// Checks the upload conflict resolution the same way as the Koofr API.
+    pub fn check_upload(
+        &self,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        conflict_resolution: &RemoteFileUploadConflictResolution,
+    ) -> Result<(), RemoteError> {
+        self.with_index(|index| {
+            self.check_upload_locked(index, parent_path, name, conflict_resolution)
+        })
+    }
+
+    fn check_upload_locked(
+        &self,
+        index: &mut LocalIndex,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        conflict_resolution: &RemoteFileUploadConflictResolution,
+    ) -> Result<(), RemoteError> {
+        self.check_name(name)?;
+
+        let path = remote_path_utils::join_path_name(parent_path, name);
+
+        match conflict_resolution {
+            RemoteFileUploadConflictResolution::Autorename => {}
+            RemoteFileUploadConflictResolution::Overwrite {
+                if_size,
+                if_modified,
+                if_hash,
+                ignore_nonexisting,
+            } => match self.get_file_locked(index, &path) {
+                Ok(file) => {
+                    if if_size.is_some() && if_size != &Some(file.size) {
+                        return Err(LocalStorageErrors::conflict(
+                            "Overwrite if size does not match",
+                        ));
+                    }
+                    if if_modified.is_some() && if_modified != &Some(file.modified) {
+                        return Err(LocalStorageErrors::conflict(
+                            "Overwrite if modified does not match",
+                        ));
+                    }
+                    if if_hash.is_some() && if_hash != &file.hash {
+                        return Err(LocalStorageErrors::conflict(
+                            "Overwrite if hash does not match",
+                        ));
+                    }
+                    if file.typ != "file" {
+                        return Err(LocalStorageErrors::not_file());
+                    }
+                }
+                Err(err) if err.is_api_error_code(ApiErrorCode::NotFound) => {
+                    if (if_size.is_some() || if_modified.is_some() || if_hash.is_some())
+                        && !ignore_nonexisting
+                    {
+                        return Err(LocalStorageErrors::conflict("Overwrite file not found"));
+                    }
+                }
+                Err(err) => return Err(err),
+            },
+            RemoteFileUploadConflictResolution::Error => {
+                if self.exists(&path)? {
+                    return Err(LocalStorageErrors::conflict("File already exists"));
+                }
+            }
+        }
+
+        self.check_parent_dir(parent_path)
+    }
+
+    /// Moves the uploaded temporary file into place. Overwritten files keep
+    /// their tags.
+    pub fn commit_upload(
+        &self,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        temp_path: &Path,
+        hash: String,
+        conflict_resolution: &RemoteFileUploadConflictResolution,
+    ) -> Result<models::FilesFile, RemoteError> {
+        self.with_index(|index| {
+            self.check_upload_locked(index, parent_path, name, conflict_resolution)?;
+
+            let name = match conflict_resolution {
+                RemoteFileUploadConflictResolution::Autorename => {
+                    RemoteName(name_utils::unused_name(&name.0, |name| {
+                        self.exists(&remote_path_utils::join_path_name(
+                            parent_path,
+                            &RemoteName(name.to_owned()),
+                        ))
+                        .unwrap_or(true)
+                    }))
+                }
+                _ => name.to_owned(),
+            };
+
+            let path = remote_path_utils::join_path_name(parent_path, &name);
+            let local_path = self.local_path(&path)?;
+
+            match conflict_resolution {
+                RemoteFileUploadConflictResolution::Overwrite { .. } => {}
+                _ => index.remove(&path),
+            }
+
+            fs::rename(temp_path, &local_path).map_err(LocalStorageErrors::io_error)?;
+
+            let metadata = fs::metadata(&local_path).map_err(LocalStorageErrors::io_error)?;
+
+            index.set_hash(
+                &path,
+                file_size(&metadata),
+                modified_millis(&metadata),
+                hash,
+            );
+
+            self.files_file(index, &path, &local_path, &metadata)
+        })
+    }
+
+    /// Deleting a dir also removes the repos inside it.
+    pub fn delete_file(&self, path: &RemotePath, if_empty: bool) -> Result<(), RemoteError> {
+        if path.0 == "/" {
+            return Err(LocalStorageErrors::not_dir());
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        let local_path = self.local_path(path)?;
+        let metadata = fs::metadata(&local_path).map_err(LocalStorageErrors::io_error)?;
+
+        if metadata.is_dir() {
+            if if_empty && !self.read_children(path)?.is_empty() {
+                return Err(LocalStorageErrors::conflict("Conflict"));
+            }
+
+            fs::remove_dir_all(&local_path).map_err(LocalStorageErrors::io_error)?;
+        } else {
+            fs::remove_file(&local_path).map_err(LocalStorageErrors::io_error)?;
+        }
+
+        self.with_index_locked(|index| {
+            index.remove(path);
+
+            Ok(())
+        })?;
+
+        self.with_repos_locked(|repos| {
+            repos
+                .repos
+                .retain(|repo| !is_path_or_child(&repo.path.0, &path.0));
+
+            Ok(())
+        })
+    }
+
+    pub fn create_dir(
+        &self,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+    ) -> Result<(), RemoteError> {
+        self.check_name(name)?;
+        self.check_parent_dir(parent_path)?;
+
+        let path = remote_path_utils::join_path_name(parent_path, name);
+
+        if self.exists(&path)? {
+            return Err(LocalStorageErrors::already_exists());
+        }
+
+        fs::create_dir(self.local_path(&path)?).map_err(LocalStorageErrors::io_error)
+    }
+
+    fn check_copy_move(
+        &self,
+        path: &RemotePath,
+        to_path: &RemotePath,
+    ) -> Result<bool, RemoteError> {
+        let into_self = path.0 == "/" || is_path_or_child(&to_path.0, &path.0);
+
+        if !into_self {
+            if self.exists(to_path)? {
+                return Err(LocalStorageErrors::already_exists());
+            }
+
+            if let Some(to_parent_path) = remote_path_utils::parent_path(to_path) {
+                self.check_parent_dir(&to_parent_path)?;
+            }
+
+            if let Some(to_name) = remote_path_utils::path_to_name(to_path) {
+                self.check_name(&to_name)?;
+            }
+        }
+
+        Ok(into_self)
+    }
+
+    pub fn copy_file(&self, path: &RemotePath, to_path: &RemotePath) -> Result<(), RemoteError> {
+        if self.check_copy_move(path, to_path)? {
+            return Err(LocalStorageErrors::copy_into_self());
+        }
+
+        self.with_index(|index| {
+            copy_recursive(&self.local_path(path)?, &self.local_path(to_path)?)
+                .map_err(LocalStorageErrors::io_error)?;
+
+            index.copy(path, to_path);
+
+            Ok(())
+        })
+    }
+
+    /// Repos inside a moved dir are moved with it.
+    pub fn move_file(
+        &self,
+        path: &RemotePath,
+        to_path: &RemotePath,
+        conditions: &RemoteFileMoveConditions,
+    ) -> Result<(), RemoteError> {
+        if self.check_copy_move(path, to_path)? {
+            return Err(LocalStorageErrors::move_into_self());
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        self.with_index_locked(|index| {
+            let file = self.get_file_locked(index, path)?;
+
+            if conditions.if_size.is_some() && conditions.if_size != Some(file.size) {
+                return Err(LocalStorageErrors::conflict("Move if size does not match"));
+            }
+            if conditions.if_modified.is_some() && conditions.if_modified != Some(file.modified) {
+                return Err(LocalStorageErrors::conflict(
+                    "Move if modified does not match",
+                ));
+            }
+            if conditions.if_hash.is_some() && conditions.if_hash != file.hash {
+                return Err(LocalStorageErrors::conflict("Move if hash does not match"));
+            }
+
+            fs::rename(self.local_path(path)?, self.local_path(to_path)?)
+                .map_err(LocalStorageErrors::io_error)?;
+
+            index.rename(path, to_path);
+
+            Ok(())
+        })?;
+
+        self.with_repos_locked(|repos| {
+            for repo in repos.repos.iter_mut() {
+                if is_path_or_child(&repo.path.0, &path.0) {
+                    repo.path =
+                        RemotePath(format!("{}{}", to_path.0, &repo.path.0[path.0.len()..]));
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn rename_file(&self, path: &RemotePath, new_name: &RemoteName) -> Result<(), RemoteError> {
+        let parent_path =
+            remote_path_utils::parent_path(path).ok_or_else(LocalStorageErrors::not_dir)?;
+
+        self.move_file(
+            path,
+            &remote_path_utils::join_path_name(&parent_path, new_name),
+            &Default::default(),
+        )
+    }
+
+    pub fn set_tags(
+        &self,
+        path: &RemotePath,
+        tags: HashMap<String, Vec<String>>,
+        conditions: &RemoteFileTagsSetConditions,
+    ) -> Result<(), RemoteError> {
+        self.with_index(|index| {
+            let mut file = self.get_file_locked(index, path)?;
+
+            set_tags(
+                Some(file.size),
+                Some(file.modified),
+                file.hash.as_deref(),
+                &mut file.tags,
+                tags,
+                conditions,
+            )
+            .map_err(|err| LocalStorageErrors::conflict(&err))?;
+
+            index.set_tags(path, file.tags);
+
+            Ok(())
+        })
+    }
+
+    pub fn get_vault_repos(&self) -> Result<models::VaultReposBundle, RemoteError> {
+        let _guard = self.lock.lock().unwrap();
+
+        let repos = self.with_repos_locked(|repos| Ok(repos.repos.clone()))?;
+
+        Ok(models::VaultReposBundle {
+            repos,
+            mounts: HashMap::from([(self.mount_id(), self.mount())]),
+        })
+    }
+
+    pub fn create_vault_repo(
+        &self,
+        create: models::VaultRepoCreate,
+        added: i64,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        if create.mount_id.0 != LOCAL_MOUNT_ID {
+            return Err(RemoteError::from_code(
+                ApiErrorCode::VaultReposMountNotAllowed,
+                "Mount not allowed",
+            ));
+        }
+
+        let _guard = self.lock.lock().unwrap();
+
+        match self.local_path(&create.path).map(fs::metadata) {
+            Ok(Ok(metadata)) if metadata.is_dir() => {}
+            _ => {
+                return Err(RemoteError::from_code(
+                    ApiErrorCode::VaultReposLocationNotFound,
+                    "Safe Box location not found",
+                ))
+            }
+        }
+
+        self.with_repos_locked(|repos| {
+            if repos.repos.iter().any(|repo| repo.path == create.path) {
+                return Err(RemoteError::from_code(
+                    ApiErrorCode::VaultReposAlreadyExists,
+                    "Safe Box already exists",
+                ));
+            }
+
+            let repo = models::VaultRepo {
+                id: RepoId(Uuid::new_v4().to_string()),
+                name: get_repo_name(&create.path),
+                mount_id: create.mount_id,
+                path: create.path,
+                salt: create.salt,
+                password_validator: create.password_validator,
+                password_validator_encrypted: create.password_validator_encrypted,
+                filename_encryption: create.filename_encryption,
+                directory_name_encryption: create.directory_name_encryption,
+                filename_encoding: create.filename_encoding,
+                added,
+            };
+
+            repos.repos.push(repo.clone());
+
+            Ok(repo)
+        })
+    }
+
+    pub fn update_vault_repo(
+        &self,
+        repo_id: &RepoId,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        let _guard = self.lock.lock().unwrap();
+
+        self.with_repos_locked(|repos| {
+            let repo = repos
+                .repos
+                .iter_mut()
+                .find(|repo| &repo.id == repo_id)
+                .ok_or_else(LocalStorageErrors::repo_not_found)?;
+
+            repo.name = get_repo_name(&update.path);
+            repo.path = update.path;
+            repo.salt = update.salt;
+            repo.password_validator = update.password_validator;
+            repo.password_validator_encrypted = update.password_validator_encrypted;
+
+            Ok(repo.clone())
+        })
+    }
+
+    pub fn remove_vault_repo(&self, repo_id: &RepoId) -> Result<(), RemoteError> {
+        let _guard = self.lock.lock().unwrap();
+
+        self.with_repos_locked(|repos| {
+            let len = repos.repos.len();
+
+            repos.repos.retain(|repo| &repo.id != repo_id);
+
+            if repos.repos.len() == len {
+                return Err(LocalStorageErrors::repo_not_found());
+            }
+
+            Ok(())
+        })
+    }
+}
+
+fn files_file_to_bundle_file(file: models::FilesFile) -> models::BundleFile {
+    models::BundleFile {
+        name: file.name,
+        typ: file.typ,
+        modified: file.modified,
+        size: file.size,
+        content_type: file.content_type,
+        hash: file.hash,
+        tags: file.tags,
+    }
+}
+
+/// Repos are named after the last component of the location path.
+fn get_repo_name(path: &RemotePath) -> String {
+    remote_path_utils::path_to_name(path)
+        .map(|name| name.0)
+        .unwrap_or_default()
+}
+
+/// Copies files and dirs. Modified times of files are preserved so that the
+/// copied index hashes stay valid.
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::metadata(from)?;
+
+    if metadata.is_dir() {
+        fs::create_dir(to)?;
+
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(SIDECAR_NAME_PREFIX)
+            {
+                continue;
+            }
+
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(from, to)?;
+
+        set_modified(to, modified_millis(&metadata))?;
+    }
+
+    Ok(())
+}
+
+pub fn set_modified(path: &Path, modified: i64) -> io::Result<()> {
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_modified(UNIX_EPOCH + Duration::from_millis(modified.max(0) as u64))
+}
@@ -0,0 +1,392 @@
+use std::{collections::HashMap, io::SeekFrom, path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use futures::{stream, AsyncReadExt, StreamExt};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+use vault_core::{
+    common::state::BoxAsyncRead,
+    remote::{
+        models,
+        remote::{ListRecursiveItemStream, RemoteFileContentRange, RemoteFileTagsSetConditions},
+        RemoteError, RemoteFileMoveConditions, RemoteFileReader, RemoteFileReaderRange,
+        RemoteFileRemoveConditions,
+    },
+    storage_backend::{StorageBackend, StorageBackendUploadOptions},
+    types::{MountId, RemoteName, RemotePath, RepoId},
+    utils::{md5_reader::MD5Reader, progress_reader::ProgressReader},
+};
+
+use crate::native_runtime::now;
+
+use super::{errors::LocalStorageErrors, local_fs::LocalFs};
+
+/// Storage backend for a local directory or a NAS mount, for repos that do
+/// not need the Koofr service. The directory is exposed as a single mount and
+/// the repos are kept in a sidecar file instead of the vault repos API.
+///
+/// Copy, move and rename are reported by `LocalEventstreamWebSocketClient`.
+pub struct LocalStorageBackend {
+    local_fs: Arc<LocalFs>,
+}
+
+impl LocalStorageBackend {
+    pub fn new(local_fs: Arc<LocalFs>) -> Self {
+        Self { local_fs }
+    }
+
+    async fn blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&LocalFs) -> Result<T, RemoteError> + Send + 'static,
+    ) -> Result<T, RemoteError> {
+        let local_fs = self.local_fs.clone();
+
+        tokio::task::spawn_blocking(move || f(&local_fs))
+            .await
+            .map_err(|err| LocalStorageErrors::io_error(std::io::Error::other(err)))?
+    }
+
+    async fn write_temp_file(
+        &self,
+        temp_path: &Path,
+        reader: BoxAsyncRead,
+        modified: Option<i64>,
+        on_progress: Option<Box<dyn Fn(usize) + Send + Sync>>,
+    ) -> Result<String, std::io::Error> {
+        let reader: BoxAsyncRead = match on_progress {
+            Some(on_progress) => Box::pin(ProgressReader::new(reader, on_progress)),
+            None => reader,
+        };
+
+        let (md5_reader, md5_digest_future) = MD5Reader::new(reader);
+
+        let mut file = tokio::fs::File::create(temp_path).await?.compat_write();
+
+        futures::io::copy(md5_reader, &mut file).await?;
+
+        let mut file = file.into_inner();
+
+        file.flush().await?;
+
+        if let Some(modified) = modified {
+            drop(file);
+
+            let temp_path = temp_path.to_owned();
+
+            tokio::task::spawn_blocking(move || {
+                super::local_fs::set_modified(&temp_path, modified)
+            })
+            .await
+            .map_err(std::io::Error::other)??;
+        }
+
+        let digest = md5_digest_future
+            .await
+            .map_err(|_| std::io::Error::other("MD5 digest not computed"))?;
+
+        Ok(format!("{:x}", digest))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorageBackend {
+    async fn get_mount(&self, id: &MountId) -> Result<models::Mount, RemoteError> {
+        self.local_fs.check_mount_id(id)?;
+
+        Ok(self.local_fs.mount())
+    }
+
+    async fn get_places(&self) -> Result<Vec<models::Mount>, RemoteError> {
+        Ok(vec![self.local_fs.mount()])
+    }
+
+    async fn get_bundle(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<models::Bundle, RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let path = path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.get_bundle(&path))
+            .await
+    }
+
+    async fn get_list_recursive(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<ListRecursiveItemStream, RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let path = path.to_owned();
+
+        let items = self
+            .blocking(move |local_fs| local_fs.get_list_recursive(&path))
+            .await?;
+
+        Ok(stream::iter(items.into_iter().map(Ok)).boxed())
+    }
+
+    async fn get_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+    ) -> Result<models::FilesFile, RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let path = path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.get_file(&path))
+            .await
+    }
+
+    async fn get_file_reader(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        range: Option<&RemoteFileReaderRange>,
+    ) -> Result<RemoteFileReader, RemoteError> {
+        let file = self.get_file(mount_id, path).await?;
+
+        if file.typ != "file" {
+            return Err(LocalStorageErrors::not_file());
+        }
+
+        let mut reader = tokio::fs::File::open(self.local_fs.local_path(path)?)
+            .await
+            .map_err(LocalStorageErrors::io_error)?;
+
+        let content_range = match range {
+            Some(range) => {
+                let end = range
+                    .end
+                    .unwrap_or(file.size - 1)
+                    .min(file.size - 1)
+                    .max(range.start - 1);
+
+                reader
+                    .seek(SeekFrom::Start(range.start as u64))
+                    .await
+                    .map_err(LocalStorageErrors::io_error)?;
+
+                Some(RemoteFileContentRange {
+                    start: range.start,
+                    end,
+                    size: file.size,
+                })
+            }
+            None => None,
+        };
+
+        let size = match &content_range {
+            Some(content_range) => content_range.end - content_range.start + 1,
+            None => file.size,
+        };
+
+        Ok(RemoteFileReader {
+            file,
+            size,
+            content_range,
+            reader: Box::pin(reader.compat().take(size as u64)),
+        })
+    }
+
+    async fn upload_file_reader(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: &RemoteName,
+        reader: BoxAsyncRead,
+        options: StorageBackendUploadOptions,
+    ) -> Result<models::FilesFile, RemoteError> {
+        let StorageBackendUploadOptions {
+            modified,
+            conflict_resolution,
+            on_progress,
+            ..
+        } = options;
+
+        self.local_fs.check_mount_id(mount_id)?;
+
+        {
+            let parent_path = parent_path.to_owned();
+            let name = name.to_owned();
+            let conflict_resolution = conflict_resolution.clone();
+
+            self.blocking(move |local_fs| {
+                local_fs.check_upload(&parent_path, &name, &conflict_resolution)
+            })
+            .await?;
+        }
+
+        let temp_path = self.local_fs.temp_path(parent_path)?;
+
+        let hash = match self
+            .write_temp_file(&temp_path, reader, modified, on_progress)
+            .await
+        {
+            Ok(hash) => hash,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&temp_path).await;
+
+                return Err(LocalStorageErrors::io_error(err));
+            }
+        };
+
+        let parent_path = parent_path.to_owned();
+        let name = name.to_owned();
+        let commit_temp_path = temp_path.clone();
+
+        let res = self
+            .blocking(move |local_fs| {
+                local_fs.commit_upload(
+                    &parent_path,
+                    &name,
+                    &commit_temp_path,
+                    hash,
+                    &conflict_resolution,
+                )
+            })
+            .await;
+
+        if res.is_err() {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+        }
+
+        res
+    }
+
+    async fn delete_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        conditions: RemoteFileRemoveConditions,
+    ) -> Result<(), RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let path = path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.delete_file(&path, conditions.if_empty))
+            .await
+    }
+
+    async fn create_dir(
+        &self,
+        mount_id: &MountId,
+        parent_path: &RemotePath,
+        name: RemoteName,
+    ) -> Result<(), RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let parent_path = parent_path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.create_dir(&parent_path, &name))
+            .await
+    }
+
+    async fn rename_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        new_name: RemoteName,
+    ) -> Result<(), RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let path = path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.rename_file(&path, &new_name))
+            .await
+    }
+
+    async fn copy_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        to_mount_id: &MountId,
+        to_path: &RemotePath,
+    ) -> Result<(), RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+        self.local_fs.check_mount_id(to_mount_id)?;
+
+        let path = path.to_owned();
+        let to_path = to_path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.copy_file(&path, &to_path))
+            .await
+    }
+
+    async fn move_file(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        to_mount_id: &MountId,
+        to_path: &RemotePath,
+        conditions: RemoteFileMoveConditions,
+    ) -> Result<(), RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+        self.local_fs.check_mount_id(to_mount_id)?;
+
+        let path = path.to_owned();
+        let to_path = to_path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.move_file(&path, &to_path, &conditions))
+            .await
+    }
+
+    async fn file_set_tags(
+        &self,
+        mount_id: &MountId,
+        path: &RemotePath,
+        tags: HashMap<String, Vec<String>>,
+        conditions: RemoteFileTagsSetConditions,
+    ) -> Result<(), RemoteError> {
+        self.local_fs.check_mount_id(mount_id)?;
+
+        let path = path.to_owned();
+
+        self.blocking(move |local_fs| local_fs.set_tags(&path, tags, &conditions))
+            .await
+    }
+
+    fn has_vault_repos_api(&self) -> bool {
+        true
+    }
+
+    fn has_vault_repo_name_options(&self) -> bool {
+        true
+    }
+
+    async fn get_vault_repos(&self) -> Result<models::VaultReposBundle, RemoteError> {
+        self.blocking(|local_fs| local_fs.get_vault_repos()).await
+    }
+
+    async fn create_vault_repo(
+        &self,
+        create: models::VaultRepoCreate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        let added = now().0;
+
+        self.blocking(move |local_fs| local_fs.create_vault_repo(create, added))
+            .await
+    }
+
+    async fn update_vault_repo(
+        &self,
+        repo_id: &RepoId,
+        update: models::VaultRepoUpdate,
+    ) -> Result<models::VaultRepo, RemoteError> {
+        let repo_id = repo_id.to_owned();
+
+        self.blocking(move |local_fs| local_fs.update_vault_repo(&repo_id, update))
+            .await
+    }
+
+    async fn remove_vault_repo(&self, repo_id: &RepoId) -> Result<(), RemoteError> {
+        let repo_id = repo_id.to_owned();
+
+        self.blocking(move |local_fs| local_fs.remove_vault_repo(&repo_id))
+            .await
+    }
+}
@@ -0,0 +1,333 @@
+use std::sync::Arc;
+
+use tokio::{sync::mpsc, task::JoinHandle};
+use vault_core::{
+    eventstream::Message,
+    types::{MountId, RemotePath},
+};
+
+use super::local_fs::LocalFs;
+
+/// Watches the dir and sends eventstream events for the changes in it. Event
+/// paths are relative to the watched dir, the same as in the Koofr
+/// eventstream.
+///
+/// Changes are only reported on platforms with inotify.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn spawn_local_watcher(
+    tokio_runtime: &tokio::runtime::Runtime,
+    local_fs: Arc<LocalFs>,
+    mount_id: MountId,
+    path: RemotePath,
+    listener_id: i64,
+    messages: mpsc::UnboundedSender<Message>,
+) -> Option<JoinHandle<()>> {
+    Some(tokio_runtime.spawn(async move {
+        if let Err(err) =
+            inotify_watcher::watch(local_fs, mount_id, &path, listener_id, messages).await
+        {
+            log::warn!("Local watcher error: {}: {:?}", path.0, err);
+        }
+    }))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn spawn_local_watcher(
+    _tokio_runtime: &tokio::runtime::Runtime,
+    _local_fs: Arc<LocalFs>,
+    _mount_id: MountId,
+    path: RemotePath,
+    _listener_id: i64,
+    _messages: mpsc::UnboundedSender<Message>,
+) -> Option<JoinHandle<()>> {
+    log::debug!("Local watcher not supported: {}", path.0);
+
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod inotify_watcher {
+    use std::{collections::HashMap, fs, io, path::Path, sync::Arc, time::Duration};
+
+    use futures::StreamExt;
+    use inotify::{EventMask, EventStream, Inotify, WatchDescriptor, WatchMask};
+    use tokio::sync::mpsc;
+    use vault_core::{
+        eventstream::{Event, Message},
+        remote::models,
+        types::{MountId, RemoteName, RemotePath},
+        utils::remote_path_utils,
+    };
+
+    use crate::local_storage::{
+        local_fs::LocalFs,
+        sidecar::{is_path_or_child, SIDECAR_NAME_PREFIX},
+    };
+
+    /// Moves are reported as a MOVED_FROM and MOVED_TO pair. A MOVED_FROM
+    /// without a MOVED_TO is a file moved out of the watched dir.
+    const MOVED_TO_TIMEOUT: Duration = Duration::from_millis(100);
+
+    struct Watcher {
+        local_fs: Arc<LocalFs>,
+        mount_id: MountId,
+        path: RemotePath,
+        listener_id: i64,
+        messages: mpsc::UnboundedSender<Message>,
+        stream: EventStream<[u8; 4096]>,
+        /// Watched dirs, relative to the watched path.
+        dirs: HashMap<WatchDescriptor, RemotePath>,
+    }
+
+    struct PendingMove {
+        cookie: u32,
+        path: RemotePath,
+        is_dir: bool,
+    }
+
+    pub async fn watch(
+        local_fs: Arc<LocalFs>,
+        mount_id: MountId,
+        path: &RemotePath,
+        listener_id: i64,
+        messages: mpsc::UnboundedSender<Message>,
+    ) -> io::Result<()> {
+        let local_path = local_fs
+            .local_path(path)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+
+        let stream = Inotify::init()?.into_event_stream([0; 4096])?;
+
+        let mut watcher = Watcher {
+            local_fs,
+            mount_id,
+            path: path.to_owned(),
+            listener_id,
+            messages,
+            stream,
+            dirs: HashMap::new(),
+        };
+
+        watcher.add_dir(&local_path, RemotePath("/".into()))?;
+
+        watcher.run().await
+    }
+
+    impl Watcher {
+        fn add_dir(&mut self, local_path: &Path, path: RemotePath) -> io::Result<()> {
+            let wd = self.stream.watches().add(
+                local_path,
+                WatchMask::CREATE
+                    | WatchMask::CLOSE_WRITE
+                    | WatchMask::DELETE
+                    | WatchMask::MOVED_FROM
+                    | WatchMask::MOVED_TO
+                    | WatchMask::ONLYDIR,
+            )?;
+
+            self.dirs.insert(wd, path.clone());
+
+            for entry in fs::read_dir(local_path)? {
+                let entry = entry?;
+
+                let name = match entry.file_name().into_string() {
+                    Ok(name) if !name.starts_with(SIDECAR_NAME_PREFIX) => name,
+                    _ => continue,
+                };
+
+                if entry.file_type()?.is_dir() {
+                    self.add_dir(
+                        &entry.path(),
+                        remote_path_utils::join_path_name(&path, &RemoteName(name)),
+                    )?;
+                }
+            }
+
+            Ok(())
+        }
+
+        fn remove_dirs(&mut self, path: &RemotePath) {
+            let wds = self
+                .dirs
+                .iter()
+                .filter(|(_, dir_path)| is_path_or_child(&dir_path.0, &path.0))
+                .map(|(wd, _)| wd.clone())
+                .collect::<Vec<_>>();
+
+            for wd in wds {
+                self.dirs.remove(&wd);
+
+                // the watch is already gone if the dir was deleted
+                let _ = self.stream.watches().remove(wd);
+            }
+        }
+
+        fn move_dirs(&mut self, path: &RemotePath, new_path: &RemotePath) {
+            for dir_path in self.dirs.values_mut() {
+                if is_path_or_child(&dir_path.0, &path.0) {
+                    *dir_path =
+                        RemotePath(format!("{}{}", new_path.0, &dir_path.0[path.0.len()..]));
+                }
+            }
+        }
+
+        async fn run(&mut self) -> io::Result<()> {
+            let mut pending_move: Option<PendingMove> = None;
+
+            loop {
+                let event = match &pending_move {
+                    Some(_) => {
+                        match tokio::time::timeout(MOVED_TO_TIMEOUT, self.stream.next()).await {
+                            Ok(event) => event,
+                            Err(_) => {
+                                if let Some(pending_move) = pending_move.take() {
+                                    self.removed(pending_move.path, pending_move.is_dir);
+                                }
+
+                                continue;
+                            }
+                        }
+                    }
+                    None => self.stream.next().await,
+                };
+
+                let event = match event {
+                    Some(event) => event?,
+                    None => return Ok(()),
+                };
+
+                if event.mask.contains(EventMask::Q_OVERFLOW) {
+                    log::warn!("Local watcher queue overflow: {}", self.path.0);
+
+                    continue;
+                }
+
+                if event.mask.contains(EventMask::IGNORED) {
+                    self.dirs.remove(&event.wd);
+
+                    continue;
+                }
+
+                let name = match event.name.and_then(|name| name.into_string().ok()) {
+                    Some(name) if !name.starts_with(SIDECAR_NAME_PREFIX) => name,
+                    _ => continue,
+                };
+
+                let path = match self.dirs.get(&event.wd) {
+                    Some(dir_path) => {
+                        remote_path_utils::join_path_name(dir_path, &RemoteName(name))
+                    }
+                    None => continue,
+                };
+
+                let is_dir = event.mask.contains(EventMask::ISDIR);
+
+                if let Some(pending) = pending_move.take() {
+                    if event.mask.contains(EventMask::MOVED_TO) && event.cookie == pending.cookie {
+                        if pending.is_dir {
+                            self.move_dirs(&pending.path, &path);
+                        }
+
+                        self.moved(pending.path, path).await;
+
+                        continue;
+                    }
+
+                    self.removed(pending.path, pending.is_dir);
+                }
+
+                if event.mask.contains(EventMask::MOVED_FROM) {
+                    pending_move = Some(PendingMove {
+                        cookie: event.cookie,
+                        path,
+                        is_dir,
+                    });
+                } else if event.mask.contains(EventMask::DELETE) {
+                    self.removed(path, is_dir);
+                } else if event.mask.contains(EventMask::CREATE)
+                    || event.mask.contains(EventMask::MOVED_TO)
+                {
+                    if is_dir {
+                        let local_path = self.local_path(&path)?;
+
+                        // the dir could be removed already
+                        let _ = self.add_dir(&local_path, path.clone());
+                    }
+
+                    // files are reported when they are written
+                    if is_dir || event.mask.contains(EventMask::MOVED_TO) {
+                        self.created(path).await;
+                    }
+                } else if event.mask.contains(EventMask::CLOSE_WRITE) {
+                    self.created(path).await;
+                }
+            }
+        }
+
+        fn local_path(&self, path: &RemotePath) -> io::Result<std::path::PathBuf> {
+            self.local_fs
+                .local_path(&remote_path_utils::join_paths(&self.path, path))
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))
+        }
+
+        async fn get_file(&self, path: &RemotePath) -> Option<models::FilesFile> {
+            let local_fs = self.local_fs.clone();
+            let path = remote_path_utils::join_paths(&self.path, path);
+
+            tokio::task::spawn_blocking(move || local_fs.get_file(&path).ok())
+                .await
+                .ok()
+                .flatten()
+        }
+
+        fn send(&self, event: Event) {
+            let _ = self.messages.send(Message::Event {
+                listener_id: self.listener_id,
+                event,
+            });
+        }
+
+        async fn created(&self, path: RemotePath) {
+            // the file could be removed already
+            if let Some(file) = self.get_file(&path).await {
+                self.send(Event::FileCreatedEvent {
+                    mount_id: self.mount_id.clone(),
+                    path,
+                    file,
+                    user_agent: None,
+                });
+            }
+        }
+
+        async fn moved(&self, path: RemotePath, new_path: RemotePath) {
+            if let Some(file) = self.get_file(&new_path).await {
+                self.send(Event::FileMovedEvent {
+                    mount_id: self.mount_id.clone(),
+                    path,
+                    new_path,
+                    file,
+                    user_agent: None,
+                });
+            }
+        }
+
+        fn removed(&mut self, path: RemotePath, is_dir: bool) {
+            if is_dir {
+                self.remove_dirs(&path);
+            }
+
+            let file = models::FilesFile {
+                name: remote_path_utils::path_to_name(&path).unwrap_or(RemoteName("".into())),
+                typ: if is_dir { "dir" } else { "file" }.into(),
+                ..Default::default()
+            };
+
+            self.send(Event::FileRemovedEvent {
+                mount_id: self.mount_id.clone(),
+                path,
+                file,
+                user_agent: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub mod errors;
+pub mod local_eventstream_websocket_client;
+pub mod local_fs;
+pub mod local_storage_backend;
+pub mod local_watcher;
+pub mod sidecar;
+
+pub use self::{
+    local_eventstream_websocket_client::LocalEventstreamWebSocketClient, local_fs::LocalFs,
+    local_storage_backend::LocalStorageBackend,
+};
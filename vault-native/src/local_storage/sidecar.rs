@@ -0,0 +1,125 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use vault_core::{remote::models, types::RemotePath};
+
+/// Names starting with this prefix are hidden from the listings (sidecar
+/// files and temporary upload files).
+pub const SIDECAR_NAME_PREFIX: &str = ".vault-local";
+
+pub const INDEX_FILE_NAME: &str = ".vault-local-index.json";
+pub const REPOS_FILE_NAME: &str = ".vault-local-repos.json";
+
+/// File metadata that the filesystem cannot store. The hash is only valid
+/// while the size and the modified time match the file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalIndexEntry {
+    pub size: i64,
+    pub modified: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub tags: HashMap<String, Vec<String>>,
+}
+
+/// Sidecar index of file hashes and tags, keyed by the path.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalIndex {
+    pub entries: BTreeMap<String, LocalIndexEntry>,
+}
+
+impl LocalIndex {
+    pub fn get_hash(&self, path: &RemotePath, size: i64, modified: i64) -> Option<String> {
+        self.entries
+            .get(&path.0)
+            .filter(|entry| entry.size == size && entry.modified == modified)
+            .and_then(|entry| entry.hash.clone())
+    }
+
+    pub fn get_tags(&self, path: &RemotePath) -> HashMap<String, Vec<String>> {
+        self.entries
+            .get(&path.0)
+            .map(|entry| entry.tags.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_hash(&mut self, path: &RemotePath, size: i64, modified: i64, hash: String) {
+        let entry = self.entries.entry(path.0.clone()).or_default();
+
+        entry.size = size;
+        entry.modified = modified;
+        entry.hash = Some(hash);
+    }
+
+    pub fn set_tags(&mut self, path: &RemotePath, tags: HashMap<String, Vec<String>>) {
+        self.entries.entry(path.0.clone()).or_default().tags = tags;
+    }
+
+    pub fn remove(&mut self, path: &RemotePath) {
+        self.entries
+            .retain(|entry_path, _| !is_path_or_child(entry_path, &path.0));
+    }
+
+    pub fn copy(&mut self, path: &RemotePath, to_path: &RemotePath) {
+        let copied = self
+            .entries
+            .iter()
+            .filter(|(entry_path, _)| is_path_or_child(entry_path, &path.0))
+            .map(|(entry_path, entry)| {
+                (
+                    format!("{}{}", to_path.0, &entry_path[path.0.len()..]),
+                    entry.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.entries.extend(copied);
+    }
+
+    pub fn rename(&mut self, path: &RemotePath, to_path: &RemotePath) {
+        self.copy(path, to_path);
+
+        self.entries.retain(|entry_path, _| {
+            !is_path_or_child(entry_path, &path.0) || is_path_or_child(entry_path, &to_path.0)
+        });
+    }
+}
+
+/// Repos sidecar, the local replacement for the vault repos API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalRepos {
+    pub repos: Vec<models::VaultRepo>,
+}
+
+pub fn is_path_or_child(path: &str, parent_path: &str) -> bool {
+    parent_path == "/"
+        || path == parent_path
+        || (path.starts_with(parent_path) && path[parent_path.len()..].starts_with('/'))
+}
+
+/// Loads a sidecar file. A missing file is an empty sidecar.
+pub fn load_sidecar<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    match fs::read(path) {
+        Ok(data) => serde_json::from_slice(&data).map_err(|err| err.to_string()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Saves a sidecar file. It is written to a temporary file first so that an
+/// interrupted save does not corrupt the previous content.
+pub fn save_sidecar<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let data = serde_json::to_vec(value).map_err(|err| err.to_string())?;
+
+    let mut temp_name = path.file_name().map(ToOwned::to_owned).unwrap_or_default();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    fs::write(&temp_path, data)
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|err| err.to_string())
+}
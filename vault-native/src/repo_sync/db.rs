@@ -0,0 +1,30 @@
+use std::{fs, io, path::Path};
+
+use vault_core::repo_sync::state::RepoSyncDb;
+
+use super::errors::RepoSyncError;
+
+/// Loads the sync state. A missing file is an empty state (first sync).
+pub fn load_db(path: &Path) -> Result<RepoSyncDb, RepoSyncError> {
+    match fs::read(path) {
+        Ok(data) => {
+            serde_json::from_slice(&data).map_err(|err| RepoSyncError::DbError(err.to_string()))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(RepoSyncDb::default()),
+        Err(err) => Err(RepoSyncError::DbError(err.to_string())),
+    }
+}
+
+/// Saves the sync state. The state is written to a temporary file first so
+/// that an interrupted save does not corrupt the previous state.
+pub fn save_db(path: &Path, db: &RepoSyncDb) -> Result<(), RepoSyncError> {
+    let data = serde_json::to_vec(db).map_err(|err| RepoSyncError::DbError(err.to_string()))?;
+
+    let mut temp_name = path.file_name().map(ToOwned::to_owned).unwrap_or_default();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    fs::write(&temp_path, data)
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|err| RepoSyncError::DbError(err.to_string()))
+}
@@ -0,0 +1,95 @@
+use thiserror::Error;
+
+use vault_core::{
+    remote::RemoteError,
+    repo_files::errors::{DeleteFileError, EnsureDirError, LoadFileError, UploadFileReaderError},
+    repo_files_list::errors::{FilesListRecursiveItemError, GetListRecursiveError},
+    repo_files_read::errors::GetFilesReaderError,
+    repos::errors::GetCipherError,
+    user_error::UserError,
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RepoSyncError {
+    #[error("{0}")]
+    GetCipherError(#[from] GetCipherError),
+    #[error("{0}")]
+    EnsureDirError(#[from] EnsureDirError),
+    #[error("{0}")]
+    LoadFileError(#[from] LoadFileError),
+    #[error("repo path is not a directory")]
+    NotADir,
+    #[error("{0}")]
+    GetListRecursiveError(#[from] GetListRecursiveError),
+    #[error("{0}")]
+    ListError(#[from] FilesListRecursiveItemError),
+    #[error("sync state error: {0}")]
+    DbError(String),
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for RepoSyncError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::GetCipherError(err) => err.user_error(),
+            Self::EnsureDirError(err) => err.user_error(),
+            Self::LoadFileError(err) => err.user_error(),
+            Self::NotADir => self.to_string(),
+            Self::GetListRecursiveError(err) => err.user_error(),
+            Self::ListError(err) => err.user_error(),
+            Self::DbError(_) => self.to_string(),
+            Self::IOError(err) => err.clone(),
+        }
+    }
+}
+
+impl From<std::io::Error> for RepoSyncError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IOError(err.to_string())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RepoSyncFileError {
+    #[error("file name is not a valid Unicode text")]
+    InvalidName,
+    #[error("another file with the same name in different case already exists")]
+    CaseConflict,
+    #[error("file was changed during sync")]
+    Changed,
+    #[error("{0}")]
+    EnsureDirError(#[from] EnsureDirError),
+    #[error("{0}")]
+    UploadFileReaderError(#[from] UploadFileReaderError),
+    #[error("{0}")]
+    GetFilesReaderError(#[from] GetFilesReaderError),
+    #[error("{0}")]
+    DeleteFileError(#[from] DeleteFileError),
+    #[error("{0}")]
+    RemoteError(#[from] RemoteError),
+    #[error("{0}")]
+    IOError(String),
+}
+
+impl UserError for RepoSyncFileError {
+    fn user_error(&self) -> String {
+        match self {
+            Self::InvalidName => self.to_string(),
+            Self::CaseConflict => self.to_string(),
+            Self::Changed => self.to_string(),
+            Self::EnsureDirError(err) => err.user_error(),
+            Self::UploadFileReaderError(err) => err.user_error(),
+            Self::GetFilesReaderError(err) => err.user_error(),
+            Self::DeleteFileError(err) => err.user_error(),
+            Self::RemoteError(err) => err.user_error(),
+            Self::IOError(err) => err.clone(),
+        }
+    }
+}
+
+impl From<std::io::Error> for RepoSyncFileError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IOError(err.to_string())
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, Metadata},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use vault_core::{
+    repo_sync::state::{path_key, RepoSyncDb, RepoSyncEntryType, RepoSyncLocalEntry},
+    types::{DecryptedName, DecryptedPath},
+    utils::repo_path_utils,
+};
+
+use crate::transfers::file_uploadable::file_size;
+
+use super::errors::RepoSyncFileError;
+
+/// Names starting with this prefix are not synced (the sync state and
+/// temporary download files).
+pub const IGNORED_NAME_PREFIX: &str = ".vault-sync";
+
+#[derive(Debug, Default)]
+pub struct LocalScan {
+    pub entries: BTreeMap<String, RepoSyncLocalEntry>,
+    pub failures: Vec<(DecryptedPath, RepoSyncFileError)>,
+}
+
+/// Scans the local directory. File hashes are only computed if the size or the
+/// modified time differ from the last sync.
+///
+/// Errors reading a directory abort the scan, otherwise its files would look
+/// deleted and would be deleted remotely.
+pub fn scan_local_dir(root: &Path, db: &RepoSyncDb) -> io::Result<LocalScan> {
+    let mut scan = LocalScan::default();
+
+    scan_dir_entries(root, &DecryptedPath("/".into()), db, &mut scan)?;
+
+    Ok(scan)
+}
+
+fn scan_dir_entries(
+    dir: &Path,
+    path: &DecryptedPath,
+    db: &RepoSyncDb,
+    scan: &mut LocalScan,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(name) => {
+                scan.failures.push((
+                    repo_path_utils::join_path_name(
+                        path,
+                        &DecryptedName(name.to_string_lossy().to_string()),
+                    ),
+                    RepoSyncFileError::InvalidName,
+                ));
+                continue;
+            }
+        };
+
+        if name.starts_with(IGNORED_NAME_PREFIX) {
+            continue;
+        }
+
+        let entry_path = repo_path_utils::join_path_name(path, &DecryptedName(name));
+        let key = path_key(&entry_path);
+
+        if scan.entries.contains_key(&key) {
+            scan.failures
+                .push((entry_path, RepoSyncFileError::CaseConflict));
+            continue;
+        }
+
+        // follow symlinks
+        let metadata = fs::metadata(entry.path())?;
+
+        if metadata.is_dir() {
+            scan.entries.insert(key, dir_entry(entry_path.clone()));
+
+            scan_dir_entries(&entry.path(), &entry_path, db, scan)?;
+        } else if metadata.is_file() {
+            let size = file_size(&metadata);
+            let modified = modified_millis(&metadata);
+
+            let hash = match db.get(&entry_path) {
+                Some(record)
+                    if record.typ == RepoSyncEntryType::File
+                        && record.local_size == size
+                        && record.local_modified == modified
+                        && record.hash.is_some() =>
+                {
+                    record.hash.clone()
+                }
+                _ => Some(hash_file(&entry.path())?),
+            };
+
+            scan.entries.insert(
+                key,
+                RepoSyncLocalEntry {
+                    path: entry_path,
+                    typ: RepoSyncEntryType::File,
+                    size,
+                    modified,
+                    hash,
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Dirs have no size and the modified time is ignored because it changes
+/// every time a child is added or removed.
+pub fn dir_entry(path: DecryptedPath) -> RepoSyncLocalEntry {
+    RepoSyncLocalEntry {
+        path,
+        typ: RepoSyncEntryType::Dir,
+        size: 0,
+        modified: 0,
+        hash: None,
+    }
+}
+
+pub fn local_path(root: &Path, path: &DecryptedPath) -> PathBuf {
+    path.0
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .fold(root.to_owned(), |local_path, name| local_path.join(name))
+}
+
+pub fn modified_millis(metadata: &Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Hex MD5 of the file content, the same as `RepoFileTags::hash_hex`.
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut context = md5::Context::new();
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        context.consume(&buf[..n]);
+    }
+
+    Ok(format!("{:x}", context.compute()))
+}
@@ -0,0 +1,4 @@
+pub mod db;
+pub mod errors;
+pub mod local;
+pub mod sync;
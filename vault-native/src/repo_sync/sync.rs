@@ -0,0 +1,609 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::{AsyncReadExt, StreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+use vault_core::{
+    cipher::Cipher,
+    remote::ApiErrorCode,
+    repo_files::{
+        errors::DeleteFileError,
+        selectors as repo_files_selectors,
+        state::{RepoFile, RepoFilesUploadConflictResolution},
+    },
+    repo_files_list::state::RepoFilesListRecursiveItem,
+    repo_sync::{
+        reconcile::reconcile,
+        state::{
+            path_key, RepoSyncAction, RepoSyncDb, RepoSyncEntryType, RepoSyncLocalEntry,
+            RepoSyncRecord, RepoSyncRemoteEntry,
+        },
+    },
+    types::{DecryptedPath, EncryptedPath, RepoId},
+    utils::{repo_encrypted_path_utils, repo_path_utils},
+    Vault,
+};
+
+use crate::transfers::file_uploadable::file_size;
+
+use super::{
+    db::{load_db, save_db},
+    errors::{RepoSyncError, RepoSyncFileError},
+    local::{self, IGNORED_NAME_PREFIX},
+};
+
+/// The sync state is saved after this many actions so that an interrupted
+/// sync does not have to compare everything again.
+const SAVE_DB_INTERVAL: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoSyncEvent {
+    Synced {
+        action: RepoSyncAction,
+    },
+    Failed {
+        path: DecryptedPath,
+        error: RepoSyncFileError,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoSyncReport {
+    pub uploaded_count: usize,
+    pub downloaded_count: usize,
+    pub deleted_local_count: usize,
+    pub deleted_remote_count: usize,
+    pub conflicts_count: usize,
+    pub failed_count: usize,
+}
+
+/// Two-way sync between a local directory and a decrypted repo path. The repo
+/// has to be unlocked.
+pub struct RepoSync {
+    vault: Arc<Vault>,
+    repo_id: RepoId,
+    path: DecryptedPath,
+    local_path: PathBuf,
+    db_path: PathBuf,
+}
+
+struct SyncRun {
+    cipher: Arc<Cipher>,
+    encrypted_root_path: EncryptedPath,
+    local: BTreeMap<String, RepoSyncLocalEntry>,
+    remote: BTreeMap<String, RepoSyncRemoteEntry>,
+    remote_files: HashMap<String, RepoFile>,
+    db: RepoSyncDb,
+}
+
+impl RepoSync {
+    pub fn new(
+        vault: Arc<Vault>,
+        repo_id: RepoId,
+        path: DecryptedPath,
+        local_path: PathBuf,
+        db_path: PathBuf,
+    ) -> Self {
+        Self {
+            vault,
+            repo_id,
+            path,
+            local_path,
+            db_path,
+        }
+    }
+
+    /// Default sync state location inside the synced directory (it is not
+    /// synced itself).
+    pub fn default_db_path(local_path: &Path) -> PathBuf {
+        local_path.join(format!("{}.json", IGNORED_NAME_PREFIX))
+    }
+
+    pub async fn sync(
+        &self,
+        on_event: &mut (dyn FnMut(RepoSyncEvent) + Send),
+    ) -> Result<RepoSyncReport, RepoSyncError> {
+        let mut report = RepoSyncReport::default();
+
+        let mut on_event = |event: RepoSyncEvent| {
+            match &event {
+                RepoSyncEvent::Synced { action } => match action {
+                    RepoSyncAction::Upload { .. } => report.uploaded_count += 1,
+                    RepoSyncAction::Download { .. } => report.downloaded_count += 1,
+                    RepoSyncAction::DeleteLocal { .. } => report.deleted_local_count += 1,
+                    RepoSyncAction::DeleteRemote { .. } => report.deleted_remote_count += 1,
+                    RepoSyncAction::RenameLocal { .. } => report.conflicts_count += 1,
+                    _ => {}
+                },
+                RepoSyncEvent::Failed { .. } => report.failed_count += 1,
+            }
+
+            on_event(event);
+        };
+
+        let mut run = self.prepare().await?;
+
+        let scan = {
+            let local_path = self.local_path.clone();
+            let db = run.db.clone();
+
+            tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(&local_path)?;
+
+                local::scan_local_dir(&local_path, &db)
+            })
+            .await
+            .map_err(|err| RepoSyncError::IOError(err.to_string()))??
+        };
+
+        for (path, error) in scan.failures {
+            on_event(RepoSyncEvent::Failed { path, error });
+        }
+
+        // the trash, versions and search folders are managed by the vault
+        run.local = scan
+            .entries
+            .into_iter()
+            .filter(|(_, entry)| !self.is_hidden_path(&entry.path))
+            .collect();
+
+        let actions = reconcile(&run.local, &run.remote, &run.db);
+
+        for (i, action) in actions.into_iter().enumerate() {
+            match self.execute(&mut run, &action).await {
+                Ok(()) => on_event(RepoSyncEvent::Synced { action }),
+                Err(error) => on_event(RepoSyncEvent::Failed {
+                    path: action.path().clone(),
+                    error,
+                }),
+            }
+
+            if (i + 1) % SAVE_DB_INTERVAL == 0 {
+                save_db(&self.db_path, &run.db)?;
+            }
+        }
+
+        save_db(&self.db_path, &run.db)?;
+
+        Ok(report)
+    }
+
+    async fn prepare(&self) -> Result<SyncRun, RepoSyncError> {
+        let mut db = load_db(&self.db_path)?;
+
+        if !db.records.is_empty()
+            && (db.repo_id.as_ref() != Some(&self.repo_id) || db.path.as_ref() != Some(&self.path))
+        {
+            return Err(RepoSyncError::DbError(String::from(
+                "sync state belongs to a different repo or path",
+            )));
+        }
+
+        db.repo_id = Some(self.repo_id.clone());
+        db.path = Some(self.path.clone());
+
+        let cipher = self.vault.repos_service.get_cipher(&self.repo_id)?;
+        let encrypted_root_path = cipher.encrypt_dir_path(&self.path);
+
+        self.vault
+            .repo_files_service
+            .clone()
+            .ensure_dirs(&self.repo_id, &encrypted_root_path)
+            .await?;
+
+        self.vault
+            .repo_files_service
+            .load_file(&self.repo_id, &encrypted_root_path)
+            .await?;
+
+        let root_file = self
+            .vault
+            .with_state(|state| {
+                repo_files_selectors::select_file(
+                    state,
+                    &repo_files_selectors::get_file_id(&self.repo_id, &encrypted_root_path),
+                )
+                .cloned()
+            })
+            .filter(|file| file.typ.is_dir())
+            .ok_or(RepoSyncError::NotADir)?;
+
+        let mut items = self
+            .vault
+            .repo_files_list_service
+            .get_list_recursive(&root_file)
+            .await?;
+
+        let mut remote = BTreeMap::new();
+        let mut remote_files = HashMap::new();
+
+        while let Some(item) = items.next().await {
+            match item {
+                RepoFilesListRecursiveItem::File {
+                    relative_repo_path: Ok(path),
+                    file,
+                } => {
+                    if path.is_root() || self.is_hidden_path(&path) {
+                        continue;
+                    }
+
+                    let key = path_key(&path);
+
+                    remote.insert(key.clone(), remote_entry(path, &file));
+                    remote_files.insert(key, file);
+                }
+                // files that cannot be decrypted are not synced
+                RepoFilesListRecursiveItem::File {
+                    relative_repo_path: Err(err),
+                    file,
+                } => {
+                    log::warn!("RepoSync cannot decrypt {:?}: {}", file.encrypted_path, err);
+                }
+                // an incomplete list would delete local files
+                RepoFilesListRecursiveItem::Error { error, .. } => return Err(error.into()),
+            }
+        }
+
+        Ok(SyncRun {
+            cipher,
+            encrypted_root_path,
+            local: BTreeMap::new(),
+            remote,
+            remote_files,
+            db,
+        })
+    }
+
+    async fn execute(
+        &self,
+        run: &mut SyncRun,
+        action: &RepoSyncAction,
+    ) -> Result<(), RepoSyncFileError> {
+        match action {
+            RepoSyncAction::CreateLocalDir { path } => {
+                tokio::fs::create_dir_all(self.local_file_path(path)).await?;
+
+                let remote_entry = run.remote_entry(path)?;
+
+                run.db.set(RepoSyncRecord::new(
+                    &local::dir_entry(path.clone()),
+                    &remote_entry,
+                ));
+            }
+            RepoSyncAction::CreateRemoteDir { path } => {
+                self.vault
+                    .repo_files_service
+                    .clone()
+                    .ensure_dirs(&self.repo_id, &run.encrypted_dir_path(path))
+                    .await?;
+
+                run.db.set(RepoSyncRecord::new(
+                    &local::dir_entry(path.clone()),
+                    &RepoSyncRemoteEntry {
+                        path: path.clone(),
+                        typ: RepoSyncEntryType::Dir,
+                        size: None,
+                        modified: None,
+                        remote_hash: None,
+                        hash: None,
+                    },
+                ));
+            }
+            RepoSyncAction::Upload {
+                path,
+                if_remote_hash,
+            } => self.upload(run, path, if_remote_hash.clone()).await?,
+            RepoSyncAction::Download { path } => self.download(run, path).await?,
+            RepoSyncAction::DeleteLocal { path } => {
+                let local_file_path = self.local_file_path(path);
+
+                let res = match run.local.get(&path_key(path)).map(|entry| entry.typ) {
+                    Some(RepoSyncEntryType::Dir) => {
+                        tokio::fs::remove_dir_all(&local_file_path).await
+                    }
+                    _ => tokio::fs::remove_file(&local_file_path).await,
+                };
+
+                match res {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+
+                run.db.remove(path);
+            }
+            RepoSyncAction::DeleteRemote { path } => {
+                let file = run
+                    .remote_files
+                    .get(&path_key(path))
+                    .ok_or(RepoSyncFileError::Changed)?;
+
+                // deleted files are moved to the trash if it is enabled
+                match self
+                    .vault
+                    .repo_files_service
+                    .delete_file(&self.repo_id, &file.encrypted_path)
+                    .await
+                {
+                    Ok(()) => {}
+                    Err(DeleteFileError::RemoteError(err))
+                        if err.is_api_error_code(ApiErrorCode::NotFound) => {}
+                    Err(err) => return Err(err.into()),
+                }
+
+                run.db.remove(path);
+            }
+            RepoSyncAction::RenameLocal { path, new_path } => {
+                tokio::fs::rename(self.local_file_path(path), self.local_file_path(new_path))
+                    .await?;
+
+                run.db.remove(path);
+
+                if let Some(mut entry) = run.local.remove(&path_key(path)) {
+                    entry.path = new_path.clone();
+
+                    run.local.insert(path_key(new_path), entry);
+                }
+            }
+            RepoSyncAction::Record { path } => {
+                let local_entry = run
+                    .local
+                    .get(&path_key(path))
+                    .ok_or(RepoSyncFileError::Changed)?;
+                let remote_entry = run.remote_entry(path)?;
+
+                run.db.set(RepoSyncRecord::new(local_entry, &remote_entry));
+            }
+            RepoSyncAction::Forget { path } => run.db.remove(path),
+        }
+
+        Ok(())
+    }
+
+    async fn upload(
+        &self,
+        run: &mut SyncRun,
+        path: &DecryptedPath,
+        if_remote_hash: Option<String>,
+    ) -> Result<(), RepoSyncFileError> {
+        let local_entry = run
+            .local
+            .get(&path_key(path))
+            .cloned()
+            .ok_or(RepoSyncFileError::Changed)?;
+        let local_file_path = self.local_file_path(path);
+
+        let file = tokio::fs::File::open(&local_file_path).await?;
+
+        let encrypted_path = run.encrypted_path(path);
+        let (parent_path, name) = repo_encrypted_path_utils::split_parent_name(&encrypted_path)
+            .ok_or(RepoSyncFileError::InvalidName)?;
+
+        let conflict_resolution = match if_remote_hash {
+            Some(if_remote_hash) => RepoFilesUploadConflictResolution::Overwrite {
+                if_remote_size: None,
+                if_remote_modified: None,
+                if_remote_hash: Some(if_remote_hash),
+            },
+            None => RepoFilesUploadConflictResolution::Error,
+        };
+
+        let result = self
+            .vault
+            .repo_files_service
+            .clone()
+            .upload_file_reader(
+                &self.repo_id,
+                &parent_path,
+                name,
+                Box::pin(file.compat()),
+                Some(local_entry.size),
+                conflict_resolution,
+                None,
+            )
+            .await?;
+
+        // if the file changed during the upload the record is not updated so
+        // that it is uploaded again in the next sync
+        let metadata = tokio::fs::metadata(&local_file_path).await?;
+
+        if file_size(&metadata) != local_entry.size
+            || local::modified_millis(&metadata) != local_entry.modified
+        {
+            return Err(RepoSyncFileError::Changed);
+        }
+
+        run.db.set(RepoSyncRecord::new(
+            &local_entry,
+            &RepoSyncRemoteEntry {
+                path: path.clone(),
+                typ: RepoSyncEntryType::File,
+                size: Some(local_entry.size),
+                modified: result.remote_file.modified,
+                remote_hash: result.remote_file.hash,
+                hash: local_entry.hash.clone(),
+            },
+        ));
+
+        Ok(())
+    }
+
+    async fn download(
+        &self,
+        run: &mut SyncRun,
+        path: &DecryptedPath,
+    ) -> Result<(), RepoSyncFileError> {
+        let key = path_key(path);
+        let file = run
+            .remote_files
+            .get(&key)
+            .cloned()
+            .ok_or(RepoSyncFileError::Changed)?;
+        let remote_entry = run.remote_entry(path)?;
+
+        let local_file_path = self.local_file_path(path);
+        let parent_path = local_file_path
+            .parent()
+            .ok_or(RepoSyncFileError::InvalidName)?
+            .to_owned();
+
+        tokio::fs::create_dir_all(&parent_path).await?;
+
+        let temp_path = parent_path.join(format!(
+            "{}-{}.tmp",
+            IGNORED_NAME_PREFIX,
+            uuid::Uuid::new_v4()
+        ));
+
+        let res = self.download_to(file, &temp_path).await;
+
+        let hash = match res {
+            Ok(hash) => hash,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&temp_path).await;
+
+                return Err(err);
+            }
+        };
+
+        // do not overwrite local changes made after the scan
+        let local_entry = run.local.get(&key);
+
+        let is_changed = match tokio::fs::metadata(&local_file_path).await {
+            Ok(metadata) => match local_entry {
+                Some(local_entry) => {
+                    file_size(&metadata) != local_entry.size
+                        || local::modified_millis(&metadata) != local_entry.modified
+                }
+                None => true,
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => false,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&temp_path).await;
+
+                return Err(err.into());
+            }
+        };
+
+        if is_changed {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+
+            return Err(RepoSyncFileError::Changed);
+        }
+
+        if let Err(err) = tokio::fs::rename(&temp_path, &local_file_path).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+
+            return Err(err.into());
+        }
+
+        let metadata = tokio::fs::metadata(&local_file_path).await?;
+
+        let local_entry = RepoSyncLocalEntry {
+            path: path.clone(),
+            typ: RepoSyncEntryType::File,
+            size: file_size(&metadata),
+            modified: local::modified_millis(&metadata),
+            hash: Some(hash),
+        };
+
+        run.db.set(RepoSyncRecord::new(&local_entry, &remote_entry));
+
+        run.local.insert(key, local_entry);
+
+        Ok(())
+    }
+
+    /// Downloads the remote file to path and returns the content hash.
+    async fn download_to(&self, file: RepoFile, path: &Path) -> Result<String, RepoSyncFileError> {
+        let mut reader = self
+            .vault
+            .repo_files_read_service
+            .clone()
+            .get_files_reader(vec![file])?
+            .reader()
+            .await?
+            .reader;
+
+        let mut local_file = tokio::fs::File::create(path).await?;
+        let mut context = md5::Context::new();
+        let mut buf = vec![0; 64 * 1024];
+
+        loop {
+            let n = reader.read(&mut buf).await?;
+
+            if n == 0 {
+                break;
+            }
+
+            context.consume(&buf[..n]);
+
+            local_file.write_all(&buf[..n]).await?;
+        }
+
+        local_file.flush().await?;
+
+        Ok(format!("{:x}", context.compute()))
+    }
+
+    fn local_file_path(&self, path: &DecryptedPath) -> PathBuf {
+        local::local_path(&self.local_path, path)
+    }
+
+    /// Returns true if the path relative to the synced dir is in the trash or
+    /// versions folder.
+    fn is_hidden_path(&self, path: &DecryptedPath) -> bool {
+        repo_files_selectors::is_hidden_path(&repo_path_utils::join_paths(&self.path, path))
+    }
+}
+
+impl SyncRun {
+    fn encrypted_path(&self, path: &DecryptedPath) -> EncryptedPath {
+        repo_encrypted_path_utils::join_paths(
+            &self.encrypted_root_path,
+            &self.cipher.encrypt_path(path),
+        )
+    }
+
+    fn encrypted_dir_path(&self, path: &DecryptedPath) -> EncryptedPath {
+        repo_encrypted_path_utils::join_paths(
+            &self.encrypted_root_path,
+            &self.cipher.encrypt_dir_path(path),
+        )
+    }
+
+    fn remote_entry(&self, path: &DecryptedPath) -> Result<RepoSyncRemoteEntry, RepoSyncFileError> {
+        self.remote
+            .get(&path_key(path))
+            .cloned()
+            .ok_or(RepoSyncFileError::Changed)
+    }
+}
+
+fn remote_entry(path: DecryptedPath, file: &RepoFile) -> RepoSyncRemoteEntry {
+    if file.typ.is_dir() {
+        // dir modified times are not compared
+        RepoSyncRemoteEntry {
+            path,
+            typ: RepoSyncEntryType::Dir,
+            size: None,
+            modified: None,
+            remote_hash: None,
+            hash: None,
+        }
+    } else {
+        RepoSyncRemoteEntry {
+            path,
+            typ: RepoSyncEntryType::File,
+            size: file.decrypted_size().ok().flatten(),
+            modified: file.modified,
+            remote_hash: file.remote_hash.clone(),
+            hash: file.hash(),
+        }
+    }
+}
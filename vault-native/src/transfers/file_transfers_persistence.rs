@@ -0,0 +1,57 @@
+use std::{path::PathBuf, sync::Arc};
+
+use vault_core::{
+    secure_storage::SecureStorageService,
+    transfers::{
+        persistence::{TransfersPersistence, TRANSFERS_STORAGE_KEY},
+        state::{PersistedTransfers, TransferUploadSource},
+        uploadable::BoxUploadable,
+    },
+};
+
+use super::file_uploadable::FileUploadable;
+
+/// Persists transfers in the secure storage (names and errors must not be
+/// stored in plaintext) and restores uploads of local files.
+pub struct FileTransfersPersistence {
+    secure_storage_service: Arc<SecureStorageService>,
+}
+
+impl FileTransfersPersistence {
+    pub fn new(secure_storage_service: Arc<SecureStorageService>) -> Self {
+        Self {
+            secure_storage_service,
+        }
+    }
+}
+
+impl TransfersPersistence for FileTransfersPersistence {
+    fn load(&self) -> Result<Option<PersistedTransfers>, String> {
+        self.secure_storage_service
+            .get(TRANSFERS_STORAGE_KEY)
+            .map_err(|err| err.to_string())
+    }
+
+    fn save(&self, transfers: &PersistedTransfers) -> Result<(), String> {
+        self.secure_storage_service
+            .set(TRANSFERS_STORAGE_KEY, transfers)
+            .map_err(|err| err.to_string())
+    }
+
+    fn uploadable(&self, source: &TransferUploadSource) -> Option<BoxUploadable> {
+        match source {
+            TransferUploadSource::File { path } => {
+                let path = PathBuf::from(path);
+
+                if !path.is_file() {
+                    return None;
+                }
+
+                Some(Box::new(FileUploadable {
+                    path,
+                    cleanup: None,
+                }))
+            }
+        }
+    }
+}
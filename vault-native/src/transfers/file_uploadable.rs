@@ -6,7 +6,7 @@ use tokio_util::compat::TokioAsyncReadCompatExt;
 
 use vault_core::{
     common::state::{BoxAsyncRead, SizeInfo},
-    transfers::{errors::UploadableError, uploadable::Uploadable},
+    transfers::{errors::UploadableError, state::TransferUploadSource, uploadable::Uploadable},
 };
 
 pub struct FileUploadable {
@@ -32,6 +32,17 @@ impl Uploadable for FileUploadable {
 
         Ok((Box::pin(file.compat()), size))
     }
+
+    fn source(&self) -> Option<TransferUploadSource> {
+        // files with a cleanup are temporary and will not exist after restart
+        if self.cleanup.is_some() {
+            return None;
+        }
+
+        self.path.to_str().map(|path| TransferUploadSource::File {
+            path: path.to_owned(),
+        })
+    }
 }
 
 impl Drop for FileUploadable {
@@ -1,4 +1,5 @@
 pub mod file_downloadable;
+pub mod file_transfers_persistence;
 pub mod file_uploadable;
 pub mod pick_file_downloadable;
 pub mod temp_file_downloadable;
@@ -1,8 +1,9 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use vault_core::{oauth2::OAuth2Config, secure_storage::SecureStorage, Vault};
 
 use crate::{
+    local_storage::{LocalEventstreamWebSocketClient, LocalFs, LocalStorageBackend},
     native_eventstream_websocket_client::{
         get_tokio_tungstenite_connector, NativeEventstreamWebSocketClient,
     },
@@ -30,7 +31,10 @@ pub fn build_vault(
     let accept_invalid_certs = accept_invalid_certs(&base_url);
 
     let reqwest_client = Arc::new(get_reqwest_client(accept_invalid_certs));
-    let http_client = Box::new(NativeHttpClient::new(reqwest_client.clone(), user_agent));
+    let http_client = Box::new(NativeHttpClient::new(
+        reqwest_client.clone(),
+        user_agent.clone(),
+    ));
 
     let tokio_tungstenite_connector = get_tokio_tungstenite_connector(accept_invalid_certs);
     let eventstream_websocket_client = Box::new(NativeEventstreamWebSocketClient::new(
@@ -47,7 +51,59 @@ pub fn build_vault(
         eventstream_websocket_client,
         secure_storage,
         runtime,
+        None,
     ));
 
+    vault.store.mutate(|state, _, _, _| {
+        state.config.user_agent = Some(user_agent);
+    });
+
     (vault, reqwest_client, tokio_tungstenite_connector)
 }
+
+/// Builds a vault backed by a local directory (or a NAS mount) instead of the
+/// Koofr storage. Repos and file tags are kept in sidecar files in the
+/// directory and the eventstream events come from filesystem notifications.
+/// There is no Koofr account, the vault is loaded without OAuth2 login as a
+/// local user.
+pub fn build_local_vault(
+    base_url: String,
+    user_agent: String,
+    oauth2_config: OAuth2Config,
+    secure_storage: Box<dyn SecureStorage + Send + Sync>,
+    tokio_runtime: Arc<tokio::runtime::Runtime>,
+    local_root: PathBuf,
+) -> (Arc<Vault>, Arc<reqwest::Client>) {
+    let accept_invalid_certs = accept_invalid_certs(&base_url);
+
+    let reqwest_client = Arc::new(get_reqwest_client(accept_invalid_certs));
+    let http_client = Box::new(NativeHttpClient::new(
+        reqwest_client.clone(),
+        user_agent.clone(),
+    ));
+
+    let local_fs = Arc::new(LocalFs::new(local_root));
+
+    let eventstream_websocket_client = Box::new(LocalEventstreamWebSocketClient::new(
+        tokio_runtime.clone(),
+        local_fs.clone(),
+    ));
+
+    let runtime = Box::new(NativeRuntime::new(tokio_runtime.clone()));
+
+    let vault = Arc::new(Vault::new(
+        base_url.clone(),
+        oauth2_config,
+        http_client,
+        eventstream_websocket_client,
+        secure_storage,
+        runtime,
+        Some(Box::new(LocalStorageBackend::new(local_fs))),
+    ));
+
+    vault.store.mutate(|state, _, _, _| {
+        state.config.user_agent = Some(user_agent);
+    });
+
+    (vault, reqwest_client)
+}
@@ -68,6 +68,12 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "RepoAutoLock")]
     pub type RepoAutoLock;
 
+    #[wasm_bindgen(typescript_type = "RepoTrashSettings")]
+    pub type RepoTrashSettings;
+
+    #[wasm_bindgen(typescript_type = "RepoVersionsSettings")]
+    pub type RepoVersionsSettings;
+
     #[wasm_bindgen(typescript_type = "RepoCreateInfo | undefined")]
     pub type RepoCreateInfoOption;
 
@@ -86,6 +92,21 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "RepoSpaceUsageInfo | undefined")]
     pub type RepoSpaceUsageInfoOption;
 
+    #[wasm_bindgen(typescript_type = "RepoSearchInfo | undefined")]
+    pub type RepoSearchInfoOption;
+
+    #[wasm_bindgen(typescript_type = "RepoTrashEntry[] | undefined")]
+    pub type RepoTrashEntryVecOption;
+
+    #[wasm_bindgen(typescript_type = "RepoVersion[] | undefined")]
+    pub type RepoVersionVecOption;
+
+    #[wasm_bindgen(typescript_type = "RepoVersionDiffLine[] | undefined")]
+    pub type RepoVersionDiffLineVecOption;
+
+    #[wasm_bindgen(typescript_type = "RepoFilesDetailsMergeHunk[] | undefined")]
+    pub type RepoFilesDetailsMergeHunkVecOption;
+
     #[wasm_bindgen(typescript_type = "RepoFile | undefined")]
     pub type RepoFileOption;
 
@@ -125,6 +146,9 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "TransfersList | undefined")]
     pub type TransfersListOption;
 
+    #[wasm_bindgen(typescript_type = "TransfersBandwidthSettings")]
+    pub type TransfersBandwidthSettings;
+
     #[wasm_bindgen(typescript_type = "FileStream | undefined")]
     pub type FileStreamOption;
 
@@ -196,6 +220,7 @@ impl WebVault {
             )),
             Box::new(BrowserSecureStorage::new(storage)),
             Box::new(BrowserRuntime::new()),
+            None,
         ));
 
         let base = Arc::new(vault_web_api::web_vault_base::WebVaultBase::new(
@@ -434,6 +459,26 @@ impl WebVault {
         );
     }
 
+    #[wasm_bindgen(js_name = reposSetTrashSettings)]
+    pub fn repos_set_trash_settings(&self, repo_id: String, trash_settings: RepoTrashSettings) {
+        self.base.repos_set_trash_settings(
+            repo_id,
+            serde_wasm_bindgen::from_value(trash_settings.into()).unwrap(),
+        );
+    }
+
+    #[wasm_bindgen(js_name = reposSetVersionsSettings)]
+    pub fn repos_set_versions_settings(
+        &self,
+        repo_id: String,
+        versions_settings: RepoVersionsSettings,
+    ) {
+        self.base.repos_set_versions_settings(
+            repo_id,
+            serde_wasm_bindgen::from_value(versions_settings.into()).unwrap(),
+        );
+    }
+
     #[wasm_bindgen(js_name = reposSetDefaultAutoLock)]
     pub fn repos_set_default_auto_lock(&self, auto_lock: RepoAutoLock) {
         self.base
@@ -628,6 +673,60 @@ impl WebVault {
         self.base.repo_space_usage_destroy(usage_id);
     }
 
+    // repo_search
+
+    #[wasm_bindgen(js_name = repoSearchCreate)]
+    pub fn repo_search_create(&self, repo_id: String) -> u32 {
+        self.base.repo_search_create(repo_id)
+    }
+
+    #[wasm_bindgen(js_name = repoSearchInfoSubscribe)]
+    pub fn repo_search_info_subscribe(&self, search_id: u32, cb: js_sys::Function) -> u32 {
+        self.base.repo_search_info_subscribe(search_id, to_cb(cb))
+    }
+
+    #[wasm_bindgen(js_name = repoSearchInfoData)]
+    pub fn repo_search_info_data(&self, id: u32) -> RepoSearchInfoOption {
+        to_js(&self.base.repo_search_info_data(id))
+    }
+
+    #[wasm_bindgen(js_name = repoSearchLoadIndex)]
+    pub fn repo_search_load_index(&self, repo_id: String, include_content: bool) {
+        self.base.repo_search_load_index(repo_id, include_content);
+    }
+
+    #[wasm_bindgen(js_name = repoSearchSetQuery)]
+    pub fn repo_search_set_query(&self, search_id: u32, query: String) {
+        self.base.repo_search_set_query(search_id, query);
+    }
+
+    #[wasm_bindgen(js_name = repoSearchDestroy)]
+    pub fn repo_search_destroy(&self, search_id: u32) {
+        self.base.repo_search_destroy(search_id);
+    }
+
+    // repo_trash
+
+    #[wasm_bindgen(js_name = repoTrashList)]
+    pub async fn repo_trash_list(&self, repo_id: String) -> RepoTrashEntryVecOption {
+        to_js(&self.base.repo_trash_list(repo_id).await)
+    }
+
+    #[wasm_bindgen(js_name = repoTrashRestore)]
+    pub fn repo_trash_restore(&self, repo_id: String, encrypted_path: String) {
+        self.base.repo_trash_restore(repo_id, encrypted_path);
+    }
+
+    #[wasm_bindgen(js_name = repoTrashPurge)]
+    pub fn repo_trash_purge(&self, repo_id: String, encrypted_path: String) {
+        self.base.repo_trash_purge(repo_id, encrypted_path);
+    }
+
+    #[wasm_bindgen(js_name = repoTrashPurgeAll)]
+    pub fn repo_trash_purge_all(&self, repo_id: String) {
+        self.base.repo_trash_purge_all(repo_id);
+    }
+
     // repo_files
 
     #[wasm_bindgen(js_name = repoFilesFileSubscribe)]
@@ -707,6 +806,43 @@ impl WebVault {
         self.base.transfers_retry_all();
     }
 
+    #[wasm_bindgen(js_name = transfersPause)]
+    pub fn transfers_pause(&self, id: u32) {
+        self.base.transfers_pause(id);
+    }
+
+    #[wasm_bindgen(js_name = transfersPauseAll)]
+    pub fn transfers_pause_all(&self) {
+        self.base.transfers_pause_all();
+    }
+
+    #[wasm_bindgen(js_name = transfersResume)]
+    pub fn transfers_resume(&self, id: u32) {
+        self.base.transfers_resume(id);
+    }
+
+    #[wasm_bindgen(js_name = transfersResumeAll)]
+    pub fn transfers_resume_all(&self) {
+        self.base.transfers_resume_all();
+    }
+
+    #[wasm_bindgen(js_name = transfersMoveToFront)]
+    pub fn transfers_move_to_front(&self, id: u32) {
+        self.base.transfers_move_to_front(id);
+    }
+
+    #[wasm_bindgen(js_name = transfersSetBandwidthSettings)]
+    pub fn transfers_set_bandwidth_settings(&self, settings: TransfersBandwidthSettings) {
+        self.base.transfers_set_bandwidth_settings(
+            serde_wasm_bindgen::from_value(settings.into()).unwrap(),
+        );
+    }
+
+    #[wasm_bindgen(js_name = transfersSetVerifyUploads)]
+    pub fn transfers_set_verify_uploads(&self, verify_uploads: bool) {
+        self.base.transfers_set_verify_uploads(verify_uploads);
+    }
+
     #[wasm_bindgen(js_name = transfersOpen)]
     pub fn transfers_open(&self, id: u32) {
         self.base.transfers_open(id);
@@ -925,6 +1061,55 @@ impl WebVault {
         self.base.repo_files_details_delete(details_id);
     }
 
+    #[wasm_bindgen(js_name = repoFilesDetailsMergeConflictSubscribe)]
+    pub fn repo_files_details_merge_conflict_subscribe(
+        &self,
+        details_id: u32,
+        cb: js_sys::Function,
+    ) -> u32 {
+        self.base
+            .repo_files_details_merge_conflict_subscribe(details_id, to_cb(cb))
+    }
+
+    #[wasm_bindgen(js_name = repoFilesDetailsMergeConflictData)]
+    pub fn repo_files_details_merge_conflict_data(
+        &self,
+        id: u32,
+    ) -> RepoFilesDetailsMergeHunkVecOption {
+        to_js(&self.base.repo_files_details_merge_conflict_data(id))
+    }
+
+    #[wasm_bindgen(js_name = repoFilesDetailsResolveMergeConflict)]
+    pub fn repo_files_details_resolve_merge_conflict(&self, details_id: u32, content: Vec<u8>) {
+        self.base
+            .repo_files_details_resolve_merge_conflict(details_id, content);
+    }
+
+    #[wasm_bindgen(js_name = repoFilesDetailsVersions)]
+    pub async fn repo_files_details_versions(&self, details_id: u32) -> RepoVersionVecOption {
+        to_js(&self.base.repo_files_details_versions(details_id).await)
+    }
+
+    #[wasm_bindgen(js_name = repoFilesDetailsVersionDiff)]
+    pub async fn repo_files_details_version_diff(
+        &self,
+        details_id: u32,
+        encrypted_path: String,
+    ) -> RepoVersionDiffLineVecOption {
+        to_js(
+            &self
+                .base
+                .repo_files_details_version_diff(details_id, encrypted_path)
+                .await,
+        )
+    }
+
+    #[wasm_bindgen(js_name = repoFilesDetailsVersionRestore)]
+    pub fn repo_files_details_version_restore(&self, details_id: u32, encrypted_path: String) {
+        self.base
+            .repo_files_details_version_restore(details_id, encrypted_path);
+    }
+
     // repo_files_move
 
     #[wasm_bindgen(js_name = repoFilesMoveInfoSubscribe)]
@@ -1134,4 +1319,33 @@ impl WebVault {
         )
         .await
     }
+
+    #[wasm_bindgen(js_name = repoFilesDetailsVersionGetFileStream)]
+    pub async fn repo_files_details_version_get_file_stream(
+        &self,
+        details_id: u32,
+        encrypted_path: String,
+        force_blob: bool,
+        abort_signal: AbortSignal,
+    ) -> FileStreamOption {
+        let provider = match self
+            .vault
+            .repo_files_details_version_get_file_reader(details_id, &EncryptedPath(encrypted_path))
+            .await
+        {
+            Ok(provider) => provider,
+            Err(err) => {
+                self.errors.handle_error(err);
+
+                return JsValue::UNDEFINED.into();
+            }
+        };
+
+        self.repo_file_reader_to_file_stream(
+            provider.reader().await,
+            force_blob,
+            Some(abort_signal),
+        )
+        .await
+    }
 }
@@ -22,8 +22,11 @@ use vault_core::{
     repo_files_move::state as repo_files_move_state,
     repo_files_tags,
     repo_remove::state as repo_remove_state,
+    repo_search::state as repo_search_state,
     repo_space_usage::state as repo_space_usage_state,
+    repo_trash::state as repo_trash_state,
     repo_unlock::state as repo_unlock_state,
+    repo_versions::state as repo_versions_state,
     repos::{selectors as repos_selectors, state as repos_state},
     selection::state as selection_state,
     sort::state as sort_state,
@@ -523,6 +526,68 @@ impl Into<Option<repos_state::RepoAutoLockAfter>> for RepoAutoLockAfter {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoTrashSettings {
+    pub enabled: bool,
+    #[serde(rename = "retentionDays")]
+    pub retention_days: Option<u32>,
+}
+
+impl From<Option<&repos_state::RepoTrashSettings>> for RepoTrashSettings {
+    fn from(trash_settings: Option<&repos_state::RepoTrashSettings>) -> Self {
+        match trash_settings {
+            Some(trash_settings) => Self {
+                enabled: trash_settings.enabled,
+                retention_days: trash_settings.retention_days,
+            },
+            None => Self {
+                enabled: false,
+                retention_days: None,
+            },
+        }
+    }
+}
+
+impl Into<repos_state::RepoTrashSettings> for RepoTrashSettings {
+    fn into(self) -> repos_state::RepoTrashSettings {
+        repos_state::RepoTrashSettings {
+            enabled: self.enabled,
+            retention_days: self.retention_days,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoVersionsSettings {
+    pub enabled: bool,
+    #[serde(rename = "maxVersions")]
+    pub max_versions: u32,
+}
+
+impl From<Option<&repos_state::RepoVersionsSettings>> for RepoVersionsSettings {
+    fn from(versions_settings: Option<&repos_state::RepoVersionsSettings>) -> Self {
+        match versions_settings {
+            Some(versions_settings) => Self {
+                enabled: versions_settings.enabled,
+                max_versions: versions_settings.max_versions,
+            },
+            None => Self {
+                enabled: false,
+                max_versions: 0,
+            },
+        }
+    }
+}
+
+impl Into<repos_state::RepoVersionsSettings> for RepoVersionsSettings {
+    fn into(self) -> repos_state::RepoVersionsSettings {
+        repos_state::RepoVersionsSettings {
+            enabled: self.enabled,
+            max_versions: self.max_versions,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
 pub struct Repo {
     pub id: String,
@@ -536,6 +601,10 @@ pub struct Repo {
     pub web_url: String,
     #[serde(rename = "autoLock")]
     pub auto_lock: RepoAutoLock,
+    #[serde(rename = "trashSettings")]
+    pub trash_settings: RepoTrashSettings,
+    #[serde(rename = "versionsSettings")]
+    pub versions_settings: RepoVersionsSettings,
 }
 
 impl From<(&repos_state::Repo, &repos_state::RepoAutoLock)> for Repo {
@@ -549,6 +618,8 @@ impl From<(&repos_state::Repo, &repos_state::RepoAutoLock)> for Repo {
             added: repo.added as f64,
             web_url: repo.web_url.clone(),
             auto_lock: repo.auto_lock.as_ref().unwrap_or(default_auto_lock).into(),
+            trash_settings: repo.trash_settings.as_ref().into(),
+            versions_settings: repo.versions_settings.as_ref().into(),
         }
     }
 }
@@ -786,6 +857,65 @@ impl<'a> From<&repo_space_usage_state::RepoSpaceUsageInfo<'a>> for RepoSpaceUsag
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoSearchResult {
+    #[serde(rename = "encryptedPath")]
+    pub encrypted_path: String,
+    pub path: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: RepoFileType,
+    #[serde(rename = "sizeDisplay")]
+    pub size_display: Option<String>,
+    pub modified: Option<f64>,
+    #[serde(rename = "contentMatched")]
+    pub content_matched: bool,
+}
+
+impl<'a> From<&repo_search_state::RepoSearchResult<'a>> for RepoSearchResult {
+    fn from(result: &repo_search_state::RepoSearchResult<'a>) -> Self {
+        Self {
+            encrypted_path: result.encrypted_path.0.clone(),
+            path: result.entry.path.0.clone(),
+            name: result
+                .entry
+                .path
+                .0
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_owned(),
+            typ: match result.entry.typ {
+                repo_search_state::RepoSearchEntryType::Dir => RepoFileType::Dir,
+                repo_search_state::RepoSearchEntryType::File => RepoFileType::File,
+            },
+            size_display: result.entry.size.map(size_display),
+            modified: result.entry.modified.map(|modified| modified as f64),
+            content_matched: result.content_matched,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoSearchInfo {
+    pub status: Status,
+    #[serde(rename = "indexedCount")]
+    pub indexed_count: usize,
+    pub query: String,
+    pub results: Vec<RepoSearchResult>,
+}
+
+impl<'a> From<&repo_search_state::RepoSearchInfo<'a>> for RepoSearchInfo {
+    fn from(info: &repo_search_state::RepoSearchInfo<'a>) -> Self {
+        Self {
+            status: (&info.status).into(),
+            indexed_count: info.indexed_count,
+            query: info.query.to_owned(),
+            results: info.results.iter().map(Into::into).collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
 pub enum RemoteFileType {
     Dir,
@@ -942,6 +1072,120 @@ impl From<&repo_files_state::RepoFile> for RepoFile {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoTrashEntry {
+    pub file: RepoFile,
+    #[serde(rename = "originalPath")]
+    pub original_path: Option<String>,
+    #[serde(rename = "originalDecryptedPath")]
+    pub original_decrypted_path: Option<String>,
+    pub deleted: Option<f64>,
+}
+
+impl From<&repo_trash_state::RepoTrashEntry> for RepoTrashEntry {
+    fn from(entry: &repo_trash_state::RepoTrashEntry) -> Self {
+        Self {
+            file: (&entry.file).into(),
+            original_path: entry.original_path.as_ref().map(|path| path.0.clone()),
+            original_decrypted_path: entry
+                .original_decrypted_path
+                .as_ref()
+                .map(|path| path.0.clone()),
+            deleted: entry.deleted.map(|deleted| deleted as f64),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoVersion {
+    pub file: RepoFile,
+    pub created: Option<f64>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+}
+
+impl From<&repo_versions_state::RepoVersion> for RepoVersion {
+    fn from(version: &repo_versions_state::RepoVersion) -> Self {
+        Self {
+            file: (&version.file).into(),
+            created: version.created.map(|created| created as f64),
+            user_agent: version.user_agent.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub enum RepoVersionDiffLineKind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+impl From<&repo_versions_state::RepoVersionDiffLineKind> for RepoVersionDiffLineKind {
+    fn from(kind: &repo_versions_state::RepoVersionDiffLineKind) -> Self {
+        match kind {
+            repo_versions_state::RepoVersionDiffLineKind::Equal => Self::Equal,
+            repo_versions_state::RepoVersionDiffLineKind::Delete => Self::Delete,
+            repo_versions_state::RepoVersionDiffLineKind::Insert => Self::Insert,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct RepoVersionDiffLine {
+    pub kind: RepoVersionDiffLineKind,
+    #[serde(rename = "oldLineNumber")]
+    pub old_line_number: Option<u32>,
+    #[serde(rename = "newLineNumber")]
+    pub new_line_number: Option<u32>,
+    pub content: String,
+}
+
+impl From<&repo_versions_state::RepoVersionDiffLine> for RepoVersionDiffLine {
+    fn from(line: &repo_versions_state::RepoVersionDiffLine) -> Self {
+        Self {
+            kind: (&line.kind).into(),
+            old_line_number: line.old_line_number.map(|number| number as u32),
+            new_line_number: line.new_line_number.map(|number| number as u32),
+            content: line.content.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(tag = "type")]
+pub enum RepoFilesDetailsMergeHunk {
+    Resolved {
+        content: String,
+    },
+    Conflict {
+        base: String,
+        ours: String,
+        theirs: String,
+    },
+}
+
+impl From<&repo_files_details_state::RepoFilesDetailsMergeHunk> for RepoFilesDetailsMergeHunk {
+    fn from(hunk: &repo_files_details_state::RepoFilesDetailsMergeHunk) -> Self {
+        match hunk {
+            repo_files_details_state::RepoFilesDetailsMergeHunk::Resolved { content } => {
+                Self::Resolved {
+                    content: content.clone(),
+                }
+            }
+            repo_files_details_state::RepoFilesDetailsMergeHunk::Conflict {
+                base,
+                ours,
+                theirs,
+            } => Self::Conflict {
+                base: base.clone(),
+                ours: ours.clone(),
+                theirs: theirs.clone(),
+            },
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
 pub struct RepoFilesSort {
     field: RepoFilesSortField,
@@ -1275,6 +1519,7 @@ pub enum TransferState {
     Waiting,
     Processing,
     Transferring,
+    Paused,
     Failed { error: String },
     Done,
 }
@@ -1285,6 +1530,7 @@ impl From<&transfers_state::TransferState> for TransferState {
             transfers_state::TransferState::Waiting => Self::Waiting,
             transfers_state::TransferState::Processing => Self::Processing,
             transfers_state::TransferState::Transferring => Self::Transferring,
+            transfers_state::TransferState::Paused => Self::Paused,
             transfers_state::TransferState::Failed { error } => Self::Failed {
                 error: error.user_error(),
             },
@@ -1312,6 +1558,10 @@ pub struct Transfer {
     pub state: TransferState,
     #[serde(rename = "canRetry")]
     pub can_retry: bool,
+    #[serde(rename = "canPause")]
+    pub can_pause: bool,
+    #[serde(rename = "canResume")]
+    pub can_resume: bool,
     #[serde(rename = "canOpen")]
     pub can_open: bool,
 }
@@ -1331,6 +1581,8 @@ impl From<(&transfers_state::Transfer, TimeMillis)> for Transfer {
                 .map(|duration| speed_display_bytes_duration(transfer.transferred_bytes, duration)),
             state: (&transfer.state).into(),
             can_retry: transfers_selectors::can_retry(transfer),
+            can_pause: transfers_selectors::can_pause(transfer),
+            can_resume: transfers_selectors::can_resume(transfer),
             can_open: transfers_selectors::can_open(transfer),
         }
     }
@@ -1362,10 +1614,59 @@ pub struct TransfersSummary {
     pub is_all_done: bool,
     #[serde(rename = "canRetryAll")]
     pub can_retry_all: bool,
+    #[serde(rename = "canPauseAll")]
+    pub can_pause_all: bool,
+    #[serde(rename = "canResumeAll")]
+    pub can_resume_all: bool,
     #[serde(rename = "canAbortAll")]
     pub can_abort_all: bool,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct TransfersBandwidthLimits {
+    pub total: Option<u64>,
+    pub upload: Option<u64>,
+    pub download: Option<u64>,
+}
+
+impl Into<transfers_state::TransfersBandwidthLimits> for TransfersBandwidthLimits {
+    fn into(self) -> transfers_state::TransfersBandwidthLimits {
+        transfers_state::TransfersBandwidthLimits {
+            total: self.total,
+            upload: self.upload,
+            download: self.download,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct TransfersBandwidthSchedule {
+    #[serde(rename = "startMinute")]
+    pub start_minute: u32,
+    #[serde(rename = "endMinute")]
+    pub end_minute: u32,
+    #[serde(rename = "fixedUtcOffsetMinutes")]
+    pub fixed_utc_offset_minutes: i32,
+    pub limits: TransfersBandwidthLimits,
+}
+
+impl Into<transfers_state::TransfersBandwidthSchedule> for TransfersBandwidthSchedule {
+    fn into(self) -> transfers_state::TransfersBandwidthSchedule {
+        transfers_state::TransfersBandwidthSchedule {
+            start_minute: self.start_minute,
+            end_minute: self.end_minute,
+            fixed_utc_offset_minutes: self.fixed_utc_offset_minutes,
+            limits: self.limits.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct TransfersBandwidthSettings {
+    pub limits: TransfersBandwidthLimits,
+    pub schedule: Option<TransfersBandwidthSchedule>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
 pub enum DirPickerItemType {
     Folder,
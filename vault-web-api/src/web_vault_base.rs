@@ -10,8 +10,8 @@ use vault_core::{
     common, dialogs,
     dir_pickers::state::DirPickerItemId,
     files, notifications, oauth2, remote_files, repo_config_backup, repo_create, repo_files,
-    repo_files_browsers, repo_files_details, repo_files_move, repo_remove, repo_space_usage,
-    repo_unlock, repos,
+    repo_files_browsers, repo_files_details, repo_files_move, repo_remove, repo_search,
+    repo_space_usage, repo_unlock, repos,
     store::{self, Event, Subscription},
     transfers,
     types::{DecryptedName, EncryptedPath, RepoFileId, RepoId, TimeMillis},
@@ -41,6 +41,7 @@ pub struct SubscriptionData {
     pub repo_remove_info: Data<Option<dto::RepoRemoveInfo>>,
     pub repo_config_backup_info: Data<Option<dto::RepoConfigBackupInfo>>,
     pub repo_space_usage_info: Data<Option<dto::RepoSpaceUsageInfo>>,
+    pub repo_search_info: Data<Option<dto::RepoSearchInfo>>,
     pub repo_files_file: Data<Option<dto::RepoFile>>,
     pub transfers_is_active: Data<bool>,
     pub transfers_summary: Data<dto::TransfersSummary>,
@@ -50,6 +51,7 @@ pub struct SubscriptionData {
     pub repo_files_details_info: Data<Option<dto::RepoFilesDetailsInfo>>,
     pub repo_files_details_file: Data<Option<dto::RepoFile>>,
     pub repo_files_details_content_bytes: Data<dto::Versioned<Option<Vec<u8>>>>,
+    pub repo_files_details_merge_conflict: Data<Option<Vec<dto::RepoFilesDetailsMergeHunk>>>,
     pub repo_files_move_info: Data<Option<dto::RepoFilesMoveInfo>>,
     pub space_usage: Data<Option<dto::SpaceUsage>>,
 }
@@ -465,6 +467,28 @@ impl WebVaultBase {
         );
     }
 
+    pub fn repos_set_trash_settings(
+        &self,
+        repo_id: String,
+        trash_settings: dto::RepoTrashSettings,
+    ) {
+        self.handle_result(
+            self.vault
+                .repos_set_trash_settings(&RepoId(repo_id), trash_settings.into()),
+        );
+    }
+
+    pub fn repos_set_versions_settings(
+        &self,
+        repo_id: String,
+        versions_settings: dto::RepoVersionsSettings,
+    ) {
+        self.handle_result(
+            self.vault
+                .repos_set_versions_settings(&RepoId(repo_id), versions_settings.into()),
+        );
+    }
+
     pub fn repos_set_default_auto_lock(&self, auto_lock: dto::RepoAutoLock) {
         self.vault.repos_set_default_auto_lock(auto_lock.into());
     }
@@ -776,6 +800,94 @@ impl WebVaultBase {
         self.vault.repo_space_usage_destroy(usage_id);
     }
 
+    // repo_search
+
+    pub fn repo_search_create(&self, repo_id: String) -> u32 {
+        self.vault.repo_search_create(RepoId(repo_id))
+    }
+
+    pub fn repo_search_info_subscribe(&self, search_id: u32, cb: Callback) -> u32 {
+        self.subscribe(
+            &[Event::RepoSearch],
+            cb,
+            self.subscription_data.repo_search_info.clone(),
+            move |vault| {
+                vault.with_state(|state| {
+                    repo_search::selectors::select_info(state, search_id)
+                        .as_ref()
+                        .map(Into::into)
+                })
+            },
+        )
+    }
+
+    pub fn repo_search_info_data(&self, id: u32) -> Option<dto::RepoSearchInfo> {
+        self.get_data(id, self.subscription_data.repo_search_info.clone())
+            .flatten()
+    }
+
+    pub fn repo_search_load_index(&self, repo_id: String, include_content: bool) {
+        self.spawn(move |vault| {
+            async move {
+                // errors are displayed in search info
+                let _ = vault
+                    .repo_search_load_index(&RepoId(repo_id), include_content)
+                    .await;
+            }
+            .boxed()
+        });
+    }
+
+    pub fn repo_search_set_query(&self, search_id: u32, query: String) {
+        self.vault.repo_search_set_query(search_id, query);
+    }
+
+    pub fn repo_search_destroy(&self, search_id: u32) {
+        self.vault.repo_search_destroy(search_id);
+    }
+
+    // repo_trash
+
+    pub async fn repo_trash_list(&self, repo_id: String) -> Option<Vec<dto::RepoTrashEntry>> {
+        match self.vault.repo_trash_list(&RepoId(repo_id)).await {
+            Ok(entries) => Some(entries.iter().map(Into::into).collect()),
+            Err(err) => {
+                self.handle_error(err);
+
+                None
+            }
+        }
+    }
+
+    pub fn repo_trash_restore(&self, repo_id: String, encrypted_path: String) {
+        self.spawn_result(move |vault| {
+            async move {
+                vault
+                    .repo_trash_restore(&RepoId(repo_id), &[EncryptedPath(encrypted_path)])
+                    .await
+                    .map(|_| ())
+            }
+            .boxed()
+        });
+    }
+
+    pub fn repo_trash_purge(&self, repo_id: String, encrypted_path: String) {
+        self.spawn_result(move |vault| {
+            async move {
+                vault
+                    .repo_trash_purge(&RepoId(repo_id), &[EncryptedPath(encrypted_path)])
+                    .await
+            }
+            .boxed()
+        });
+    }
+
+    pub fn repo_trash_purge_all(&self, repo_id: String) {
+        self.spawn_result(move |vault| {
+            async move { vault.repo_trash_purge_all(&RepoId(repo_id)).await }.boxed()
+        });
+    }
+
     // repo_files
 
     pub fn repo_files_file_subscribe(&self, file_id: String, cb: Callback) -> u32 {
@@ -877,6 +989,8 @@ impl WebVaultBase {
                         is_transferring: selectors::select_is_transferring(state),
                         is_all_done: selectors::select_is_all_done(state),
                         can_retry_all: selectors::select_can_retry_all(state),
+                        can_pause_all: selectors::select_can_pause_all(state),
+                        can_resume_all: selectors::select_can_resume_all(state),
                         can_abort_all: selectors::select_can_abort_all(state),
                     }
                 })
@@ -926,6 +1040,37 @@ impl WebVaultBase {
         self.vault.transfers_retry_all();
     }
 
+    pub fn transfers_pause(&self, id: u32) {
+        self.vault.transfers_pause(id);
+    }
+
+    pub fn transfers_pause_all(&self) {
+        self.vault.transfers_pause_all();
+    }
+
+    pub fn transfers_resume(&self, id: u32) {
+        self.vault.transfers_resume(id);
+    }
+
+    pub fn transfers_resume_all(&self) {
+        self.vault.transfers_resume_all();
+    }
+
+    pub fn transfers_move_to_front(&self, id: u32) {
+        self.vault.transfers_move_to_front(id);
+    }
+
+    pub fn transfers_set_bandwidth_settings(&self, settings: dto::TransfersBandwidthSettings) {
+        self.vault.transfers_set_bandwidth_limits(
+            settings.limits.into(),
+            settings.schedule.map(Into::into),
+        );
+    }
+
+    pub fn transfers_set_verify_uploads(&self, verify_uploads: bool) {
+        self.vault.transfers_set_verify_uploads(verify_uploads);
+    }
+
     pub fn transfers_open(&self, id: u32) {
         self.spawn_result(move |vault| async move { vault.transfers_open(id).await }.boxed());
     }
@@ -1267,6 +1412,51 @@ impl WebVaultBase {
         });
     }
 
+    pub fn repo_files_details_merge_conflict_subscribe(
+        &self,
+        details_id: u32,
+        cb: Callback,
+    ) -> u32 {
+        self.subscribe(
+            &[Event::RepoFilesDetails],
+            cb,
+            self.subscription_data
+                .repo_files_details_merge_conflict
+                .clone(),
+            move |vault| {
+                vault.with_state(|state| {
+                    repo_files_details::selectors::select_merge_conflict(state, details_id)
+                        .map(|merge_conflict| merge_conflict.hunks.iter().map(Into::into).collect())
+                })
+            },
+        )
+    }
+
+    pub fn repo_files_details_merge_conflict_data(
+        &self,
+        id: u32,
+    ) -> Option<Vec<dto::RepoFilesDetailsMergeHunk>> {
+        self.get_data(
+            id,
+            self.subscription_data
+                .repo_files_details_merge_conflict
+                .clone(),
+        )
+        .flatten()
+    }
+
+    pub fn repo_files_details_resolve_merge_conflict(&self, details_id: u32, content: Vec<u8>) {
+        self.spawn(move |vault| {
+            async move {
+                // error is displayed in the details component
+                let _ = vault
+                    .repo_files_details_resolve_merge_conflict(details_id, content)
+                    .await;
+            }
+            .boxed()
+        });
+    }
+
     pub fn repo_files_details_delete(&self, details_id: u32) {
         self.spawn_result(move |vault| {
             async move {
@@ -1279,6 +1469,50 @@ impl WebVaultBase {
         });
     }
 
+    pub async fn repo_files_details_versions(
+        &self,
+        details_id: u32,
+    ) -> Option<Vec<dto::RepoVersion>> {
+        match self.vault.repo_files_details_versions(details_id).await {
+            Ok(versions) => Some(versions.iter().map(Into::into).collect()),
+            Err(err) => {
+                self.handle_error(err);
+
+                None
+            }
+        }
+    }
+
+    pub async fn repo_files_details_version_diff(
+        &self,
+        details_id: u32,
+        encrypted_path: String,
+    ) -> Option<Vec<dto::RepoVersionDiffLine>> {
+        match self
+            .vault
+            .repo_files_details_version_diff(details_id, &EncryptedPath(encrypted_path))
+            .await
+        {
+            Ok(lines) => Some(lines.iter().map(Into::into).collect()),
+            Err(err) => {
+                self.handle_error(err);
+
+                None
+            }
+        }
+    }
+
+    pub fn repo_files_details_version_restore(&self, details_id: u32, encrypted_path: String) {
+        self.spawn_result(move |vault| {
+            async move {
+                vault
+                    .repo_files_details_version_restore(details_id, &EncryptedPath(encrypted_path))
+                    .await
+            }
+            .boxed()
+        });
+    }
+
     // repo_files_move
 
     pub fn repo_files_move_info_subscribe(&self, cb: Callback) -> u32 {
@@ -37,6 +37,9 @@ export const TransfersListTransfer = memo<{ transfer: Transfer }>(
       case 'Transferring':
         text = 'is being transferred.';
         break;
+      case 'Paused':
+        text = 'is paused.';
+        break;
       case 'Failed':
         text = `failed. ${state.error}`;
         break;